@@ -0,0 +1,629 @@
+use crate::logging;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const BACKUP_PREFIX: &str = "noteban-backup-";
+const BACKUP_EXTENSION: &str = ".zip";
+const BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// Vault directories that are never worth backing up
+pub(crate) const EXCLUDED_DIRS: [&str; 2] = [".git", ".trash"];
+/// Archive entry listing the size and hash of every backed up file
+const MANIFEST_NAME: &str = ".noteban-backup-manifest.json";
+/// Notes test-restored by a verification drill
+const DRILL_SAMPLE_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestFile {
+    size: u64,
+    sha256: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BackupManifest {
+    files: BTreeMap<String, ManifestFile>,
+}
+
+/// Outcome of checking a backup and test-restoring part of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub ok: bool,
+    /// Files in the archive, excluding the manifest
+    pub files: usize,
+    /// Backups made before manifests were added can only be checked for corruption
+    pub has_manifest: bool,
+    /// Files whose size or hash differs from the manifest
+    pub mismatched: Vec<String>,
+    /// Files listed in the manifest but absent from the archive
+    pub missing: Vec<String>,
+    /// Notes restored into a temporary directory during the drill
+    pub drill_notes: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Backups default to `<data dir>/<profile>/backups`, outside both the vault
+/// and the cache directory
+pub fn default_backup_dir(profile_id: &str) -> Result<PathBuf, String> {
+    let proj_dirs =
+        ProjectDirs::from("", "", "noteban").ok_or("Could not determine data directory")?;
+    Ok(proj_dirs.data_dir().join(profile_id).join("backups"))
+}
+
+/// Backups are named `noteban-backup-<timestamp>[-n].zip`; returns the
+/// timestamp and the collision counter `n` (0 when absent)
+fn parse_backup_name(file_name: &str) -> Option<(DateTime<Utc>, usize)> {
+    let stem = file_name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_EXTENSION)?;
+    let timestamp = stem.get(..15)?;
+    let created_at = NaiveDateTime::parse_from_str(timestamp, BACKUP_TIME_FORMAT)
+        .ok()?
+        .and_utc();
+    let counter = match &stem[15..] {
+        "" => 0,
+        rest => rest.strip_prefix('-')?.parse().ok()?,
+    };
+    Some((created_at, counter))
+}
+
+/// List the backups in `dir`, newest first
+pub fn list_backups_in(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(usize, BackupInfo)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let (created_at, counter) = parse_backup_name(&file_name)?;
+            let size = entry.metadata().ok()?.len();
+            Some((
+                counter,
+                BackupInfo {
+                    path: entry.path().to_string_lossy().to_string(),
+                    file_name,
+                    size,
+                    created_at,
+                },
+            ))
+        })
+        .collect();
+    backups.sort_by(|(a_counter, a), (b_counter, b)| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| b_counter.cmp(a_counter))
+    });
+    backups.into_iter().map(|(_, backup)| backup).collect()
+}
+
+/// Zip every file of the vault into a new archive in `backup_dir`
+pub fn create_backup(vault: &Path, backup_dir: &Path) -> Result<BackupInfo, String> {
+    fs::create_dir_all(backup_dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    // A backup directory inside the vault must not back itself up
+    let backup_dir_canonical = backup_dir.canonicalize().ok();
+
+    let now = Utc::now();
+    let stamp = now.format(BACKUP_TIME_FORMAT).to_string();
+    let mut file_name = format!("{}{}{}", BACKUP_PREFIX, stamp, BACKUP_EXTENSION);
+    let mut counter = 1;
+    while backup_dir.join(&file_name).exists() {
+        file_name = format!("{}{}-{}{}", BACKUP_PREFIX, stamp, counter, BACKUP_EXTENSION);
+        counter += 1;
+    }
+    let target = backup_dir.join(&file_name);
+    let partial = backup_dir.join(format!("{}.partial", file_name));
+
+    let result = write_archive(vault, &partial, backup_dir_canonical.as_deref())
+        .and_then(|_| fs::rename(&partial, &target).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("Failed to write backup: {}", e));
+    }
+
+    let size = fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+    Ok(BackupInfo {
+        file_name,
+        path: target.to_string_lossy().to_string(),
+        size,
+        created_at: now,
+    })
+}
+
+fn write_archive(vault: &Path, target: &Path, skip_dir: Option<&Path>) -> Result<(), String> {
+    let file = File::create(target).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut manifest = BackupManifest::default();
+
+    for entry in WalkDir::new(vault)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| {
+            let excluded_name = e.file_type().is_dir()
+                && e.file_name()
+                    .to_str()
+                    .is_some_and(|name| EXCLUDED_DIRS.contains(&name));
+            let is_backup_dir = e.file_type().is_dir()
+                && skip_dir.is_some_and(|skip| e.path().canonicalize().is_ok_and(|p| p == skip));
+            !excluded_name && !is_backup_dir
+        })
+    {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(vault)
+            .map_err(|e| e.to_string())?;
+        // Zip entries always use forward slashes
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if name == MANIFEST_NAME {
+            continue;
+        }
+
+        let bytes = fs::read(entry.path()).map_err(|e| e.to_string())?;
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(&bytes).map_err(|e| e.to_string())?;
+        manifest.files.insert(
+            name,
+            ManifestFile {
+                size: bytes.len() as u64,
+                sha256: sha256_hex(&bytes),
+            },
+        );
+    }
+
+    let encoded = serde_json::to_vec(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file(MANIFEST_NAME, options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(&encoded).map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Delete all but the `keep` newest backups, returning how many were removed
+pub fn apply_rotation(backup_dir: &Path, keep: usize) -> usize {
+    let mut removed = 0;
+    for backup in list_backups_in(backup_dir).into_iter().skip(keep) {
+        match fs::remove_file(&backup.path) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("Failed to rotate backup {}: {}", backup.file_name, e),
+        }
+    }
+    removed
+}
+
+/// Check that every entry of a backup reads back intact (CRC verified) and
+/// stays inside the directory it is extracted to. Returns the file entries,
+/// without the manifest.
+pub fn validate_backup(archive_path: &Path) -> Result<Vec<String>, String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Backup is not a valid archive: {}", e))?;
+
+    let mut names = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Backup is corrupt: {}", e))?;
+        if entry.enclosed_name().is_none() {
+            return Err(format!("Backup contains an unsafe path: {}", entry.name()));
+        }
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        io::copy(&mut entry, &mut io::sink())
+            .map_err(|e| format!("Backup entry {} is corrupt: {}", name, e))?;
+        if name != MANIFEST_NAME {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Entries needed to restore `notes` (archive paths of `.md` files), including
+/// each note's attachments folder
+fn select_entries(entries: &[String], notes: &[String]) -> Result<BTreeSet<String>, String> {
+    let mut selected = BTreeSet::new();
+    for note in notes {
+        let note = note.trim_start_matches('/');
+        if !entries.iter().any(|entry| entry == note) {
+            return Err(format!("Note not found in backup: {}", note));
+        }
+        selected.insert(note.to_string());
+        if let Some(stem) = note.strip_suffix(".md") {
+            let attachments = format!("{}.attachments/", stem);
+            selected.extend(
+                entries
+                    .iter()
+                    .filter(|entry| entry.starts_with(&attachments))
+                    .cloned(),
+            );
+        }
+    }
+    Ok(selected)
+}
+
+/// Extract a validated backup into `target`, either entirely or only the given
+/// notes. Existing files are overwritten; files missing from the backup are
+/// left alone. Returns the restored paths.
+pub fn restore_backup_to(
+    archive_path: &Path,
+    target: &Path,
+    notes: Option<&[String]>,
+) -> Result<Vec<PathBuf>, String> {
+    let entries = validate_backup(archive_path)?;
+    let selected: BTreeSet<String> = match notes {
+        Some(notes) => select_entries(&entries, notes)?,
+        None => entries.into_iter().collect(),
+    };
+
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Backup is not a valid archive: {}", e))?;
+
+    let mut restored = Vec::new();
+    for name in &selected {
+        let mut entry = archive
+            .by_name(name)
+            .map_err(|e| format!("Failed to read {} from backup: {}", name, e))?;
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Backup contains an unsafe path: {}", name))?;
+        let destination = target.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+
+        // Write beside the destination and rename so a failed restore never
+        // leaves a half-written note
+        let mut partial = destination.clone().into_os_string();
+        partial.push(".restore-partial");
+        let partial = PathBuf::from(partial);
+        let result = File::create(&partial)
+            .and_then(|mut out| io::copy(&mut entry, &mut out))
+            .and_then(|_| fs::rename(&partial, &destination));
+        if let Err(e) = result {
+            let _ = fs::remove_file(&partial);
+            return Err(format!("Failed to restore {}: {}", name, e));
+        }
+        restored.push(destination);
+    }
+    Ok(restored)
+}
+
+/// Hashes of every file entry, plus the manifest when the backup has one
+fn hash_entries(
+    archive_path: &Path,
+) -> Result<(BTreeMap<String, ManifestFile>, Option<BackupManifest>), String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Backup is not a valid archive: {}", e))?;
+
+    let mut hashes = BTreeMap::new();
+    let mut manifest = None;
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Backup is corrupt: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Backup entry {} is corrupt: {}", name, e))?;
+        if name == MANIFEST_NAME {
+            manifest = Some(
+                serde_json::from_slice(&bytes)
+                    .map_err(|e| format!("Backup manifest is unreadable: {}", e))?,
+            );
+        } else {
+            let sha256 = sha256_hex(&bytes);
+            hashes.insert(
+                name,
+                ManifestFile {
+                    size: bytes.len() as u64,
+                    sha256,
+                },
+            );
+        }
+    }
+    Ok((hashes, manifest))
+}
+
+/// Up to `count` notes spread evenly over the archive
+fn drill_sample(entries: &[String], count: usize) -> Vec<String> {
+    let notes: Vec<&String> = entries
+        .iter()
+        .filter(|entry| entry.ends_with(".md"))
+        .collect();
+    if notes.len() <= count {
+        return notes.into_iter().cloned().collect();
+    }
+    (0..count)
+        .map(|i| notes[i * notes.len() / count].clone())
+        .collect()
+}
+
+/// Restore `sample` into a scratch directory and check every restored file
+/// against the archive hashes
+fn run_drill(
+    archive_path: &Path,
+    sample: &[String],
+    hashes: &BTreeMap<String, ManifestFile>,
+) -> Result<Vec<String>, String> {
+    let scratch = std::env::temp_dir().join(format!("noteban-drill-{}", uuid::Uuid::new_v4()));
+    let result = restore_backup_to(archive_path, &scratch, Some(sample)).map(|restored| {
+        let mut problems = Vec::new();
+        for path in restored {
+            let name = path
+                .strip_prefix(&scratch)
+                .map(|relative| {
+                    relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                })
+                .unwrap_or_default();
+            let restored_hash = fs::read(&path).map(|bytes| sha256_hex(&bytes));
+            match (restored_hash, hashes.get(&name)) {
+                (Ok(hash), Some(expected)) if hash == expected.sha256 => {}
+                (Err(e), _) => problems.push(format!("Failed to read restored {}: {}", name, e)),
+                _ => problems.push(format!("Restored {} does not match the backup", name)),
+            }
+        }
+        problems
+    });
+    if let Err(e) = fs::remove_dir_all(&scratch) {
+        if scratch.exists() {
+            log::warn!(
+                "Failed to clean up drill directory {}: {}",
+                logging::path(&scratch),
+                e
+            );
+        }
+    }
+    result
+}
+
+/// Check a backup end to end: every entry must read back intact, match the
+/// manifest written with it, and a sample of notes must restore cleanly
+pub fn verify_backup_archive(archive_path: &Path) -> Result<BackupVerification, String> {
+    let mut report = BackupVerification {
+        ok: false,
+        files: 0,
+        has_manifest: false,
+        mismatched: Vec::new(),
+        missing: Vec::new(),
+        drill_notes: Vec::new(),
+        problems: Vec::new(),
+    };
+
+    let entries = match validate_backup(archive_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            report.problems.push(e);
+            return Ok(report);
+        }
+    };
+    report.files = entries.len();
+
+    let (hashes, manifest) = match hash_entries(archive_path) {
+        Ok(result) => result,
+        Err(e) => {
+            report.problems.push(e);
+            return Ok(report);
+        }
+    };
+    if let Some(manifest) = manifest {
+        report.has_manifest = true;
+        for (name, expected) in &manifest.files {
+            match hashes.get(name) {
+                Some(actual)
+                    if actual.size == expected.size && actual.sha256 == expected.sha256 => {}
+                Some(_) => report.mismatched.push(name.clone()),
+                None => report.missing.push(name.clone()),
+            }
+        }
+        let unlisted = hashes
+            .keys()
+            .filter(|name| !manifest.files.contains_key(*name))
+            .count();
+        if unlisted > 0 {
+            report
+                .problems
+                .push(format!("{} files are not listed in the manifest", unlisted));
+        }
+    }
+
+    report.drill_notes = drill_sample(&entries, DRILL_SAMPLE_SIZE);
+    if !report.drill_notes.is_empty() {
+        match run_drill(archive_path, &report.drill_notes, &hashes) {
+            Ok(problems) => report.problems.extend(problems),
+            Err(e) => report.problems.push(format!("Test restore failed: {}", e)),
+        }
+    }
+
+    report.ok =
+        report.mismatched.is_empty() && report.missing.is_empty() && report.problems.is_empty();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_backup_names() {
+        let (time, counter) = parse_backup_name("noteban-backup-20240102-030405.zip").unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-01-02T03:04:05+00:00");
+        assert_eq!(counter, 0);
+        assert_eq!(
+            parse_backup_name("noteban-backup-20240102-030405-2.zip").map(|(_, n)| n),
+            Some(2)
+        );
+        assert!(parse_backup_name("noteban-backup-20240102-030405.zip.partial").is_none());
+        assert!(parse_backup_name("notes.zip").is_none());
+    }
+
+    #[test]
+    fn backs_up_vault_and_rotates() {
+        let root = std::env::temp_dir().join(format!("noteban-backup-{}", uuid::Uuid::new_v4()));
+        let vault = root.join("vault");
+        fs::create_dir_all(vault.join("work/spec.attachments")).unwrap();
+        fs::create_dir_all(vault.join(".trash/x")).unwrap();
+        fs::write(vault.join("work/spec.md"), "spec").unwrap();
+        fs::write(vault.join("work/spec.attachments/a.png"), "png").unwrap();
+        fs::write(vault.join(".trash/x/item.json"), "{}").unwrap();
+        // Backups stored inside the vault are skipped
+        let backups = vault.join("backups");
+
+        let first = create_backup(&vault, &backups).unwrap();
+        let second = create_backup(&vault, &backups).unwrap();
+        assert_ne!(first.file_name, second.file_name);
+
+        let mut archive = zip::ZipArchive::new(File::open(&second.path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive
+            .file_names()
+            .filter(|name| *name != MANIFEST_NAME)
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["work/spec.attachments/a.png", "work/spec.md"]);
+        let mut content = String::new();
+        archive
+            .by_name("work/spec.md")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "spec");
+
+        assert_eq!(apply_rotation(&backups, 1), 1);
+        assert_eq!(list_backups_in(&backups)[0].file_name, second.file_name);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn restores_selected_notes_with_attachments() {
+        let root = std::env::temp_dir().join(format!("noteban-restore-{}", uuid::Uuid::new_v4()));
+        let vault = root.join("vault");
+        fs::create_dir_all(vault.join("work/spec.attachments")).unwrap();
+        fs::write(vault.join("work/spec.md"), "spec").unwrap();
+        fs::write(vault.join("work/spec.attachments/a.png"), "png").unwrap();
+        fs::write(vault.join("other.md"), "other").unwrap();
+        let info = create_backup(&vault, &root.join("backups")).unwrap();
+
+        let target = root.join("restored");
+        let restored = restore_backup_to(
+            Path::new(&info.path),
+            &target,
+            Some(&["work/spec.md".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(
+            fs::read_to_string(target.join("work/spec.md")).unwrap(),
+            "spec"
+        );
+        assert!(target.join("work/spec.attachments/a.png").exists());
+        assert!(!target.join("other.md").exists());
+
+        assert!(restore_backup_to(
+            Path::new(&info.path),
+            &target,
+            Some(&["missing.md".to_string()])
+        )
+        .is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_archives_escaping_the_target() {
+        let root = std::env::temp_dir().join(format!("noteban-restore-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let archive_path = root.join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&archive_path).unwrap());
+        zip.start_file("../escaped.md", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"x").unwrap();
+        zip.finish().unwrap();
+
+        assert!(validate_backup(&archive_path).is_err());
+        assert!(restore_backup_to(&archive_path, &root.join("target"), None).is_err());
+        assert!(!root.join("escaped.md").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn verifies_backup_against_manifest() {
+        let root = std::env::temp_dir().join(format!("noteban-verify-{}", uuid::Uuid::new_v4()));
+        let vault = root.join("vault");
+        fs::create_dir_all(vault.join("work/spec.attachments")).unwrap();
+        fs::write(vault.join("work/spec.md"), "spec").unwrap();
+        fs::write(vault.join("work/spec.attachments/a.png"), "png").unwrap();
+        let info = create_backup(&vault, &root.join("backups")).unwrap();
+
+        let report = verify_backup_archive(Path::new(&info.path)).unwrap();
+        assert!(report.ok, "{:?}", report.problems);
+        assert!(report.has_manifest);
+        assert_eq!(report.files, 2);
+        assert_eq!(report.drill_notes, vec!["work/spec.md"]);
+
+        // Rewrite the archive with a changed note but the original manifest
+        let tampered = root.join("tampered.zip");
+        let mut source = ZipArchive::new(File::open(&info.path).unwrap()).unwrap();
+        let mut zip = ZipWriter::new(File::create(&tampered).unwrap());
+        for i in 0..source.len() {
+            let mut entry = source.by_index(i).unwrap();
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).unwrap();
+            if entry.name() == "work/spec.md" {
+                bytes = b"changed".to_vec();
+            }
+            zip.start_file(entry.name(), SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(&bytes).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let report = verify_backup_archive(&tampered).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.mismatched, vec!["work/spec.md"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
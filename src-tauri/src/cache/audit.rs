@@ -0,0 +1,120 @@
+use super::db::CacheDb;
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// One recorded file operation; rows are only ever appended
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: String,
+    /// Label of the window that invoked the operation, `backend` if none did
+    pub actor: String,
+    /// `create`, `update`, `delete` or `move`
+    pub action: String,
+    pub path: String,
+    /// Where the note or folder ended up, for moves and renames
+    pub target_path: Option<String>,
+    /// `None` when the operation succeeded
+    pub error: Option<String>,
+}
+
+impl CacheDb {
+    pub fn append_audit_entry(
+        &self,
+        actor: &str,
+        action: &str,
+        path: &str,
+        target_path: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "INSERT INTO audit_log (at, actor, action, path, target_path, error)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                Utc::now().to_rfc3339(),
+                actor,
+                action,
+                path,
+                target_path,
+                error
+            ],
+        )
+        .map_err(|e| format!("Failed to write audit log: {}", e))?;
+        Ok(())
+    }
+
+    /// Newest entries first, optionally only those touching `path` as source
+    /// or target
+    pub fn get_audit_log(
+        &self,
+        path: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, at, actor, action, path, target_path, error FROM audit_log
+                 WHERE ?1 IS NULL OR path = ?1 OR target_path = ?1
+                 ORDER BY id DESC LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to prepare audit log query: {}", e))?;
+
+        let entries = stmt
+            .query_map(params![path, limit as i64], |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    at: row.get(1)?,
+                    actor: row.get(2)?,
+                    action: row.get(3)?,
+                    path: row.get(4)?,
+                    target_path: row.get(5)?,
+                    error: row.get(6)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query audit log: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_entries_by_either_path_newest_first() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        cache
+            .append_audit_entry("main", "create", "/v/a.md", None, None)
+            .unwrap();
+        cache
+            .append_audit_entry("main", "move", "/v/a.md", Some("/v/b.md"), None)
+            .unwrap();
+        cache
+            .append_audit_entry("quick", "delete", "/v/c.md", None, Some("Note is locked"))
+            .unwrap();
+
+        let all = cache.get_audit_log(None, 10).unwrap();
+        let actions: Vec<&str> = all.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["delete", "move", "create"]);
+        assert_eq!(all[0].error.as_deref(), Some("Note is locked"));
+        assert_eq!(all[0].actor, "quick");
+
+        let b = cache.get_audit_log(Some("/v/b.md"), 10).unwrap();
+        assert_eq!(b.len(), 1);
+        assert_eq!(b[0].target_path.as_deref(), Some("/v/b.md"));
+        assert_eq!(cache.get_audit_log(Some("/v/a.md"), 10).unwrap().len(), 2);
+        assert_eq!(cache.get_audit_log(None, 1).unwrap().len(), 1);
+    }
+}
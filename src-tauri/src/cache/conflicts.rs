@@ -0,0 +1,92 @@
+use super::db::CacheDb;
+use rusqlite::params;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct ConflictRecord {
+    pub file_path: String,
+    pub original_path: String,
+    pub reason: String,
+    pub source: Option<String>,
+    pub detected_at: String,
+}
+
+impl CacheDb {
+    pub fn upsert_conflict(&self, record: &ConflictRecord) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO conflict_files
+             (file_path, original_path, reason, source, detected_at)
+             VALUES (?, ?, ?, ?, ?)",
+            params![
+                record.file_path,
+                record.original_path,
+                record.reason,
+                record.source,
+                record.detected_at,
+            ],
+        )
+        .map_err(|e| format!("Failed to record conflict: {}", e))?;
+
+        Ok(())
+    }
+
+    pub fn remove_conflict(&self, file_path: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "DELETE FROM conflict_files WHERE file_path = ?",
+            [file_path],
+        )
+        .map_err(|e| format!("Failed to remove conflict: {}", e))?;
+        Ok(())
+    }
+
+    /// Get all recorded conflict copies, newest first
+    pub fn get_conflicts(&self) -> Result<Vec<ConflictRecord>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT file_path, original_path, reason, source, detected_at
+                 FROM conflict_files ORDER BY detected_at DESC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let records = stmt
+            .query_map([], |row| {
+                Ok(ConflictRecord {
+                    file_path: row.get(0)?,
+                    original_path: row.get(1)?,
+                    reason: row.get(2)?,
+                    source: row.get(3)?,
+                    detected_at: row.get(4)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query conflicts: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(records)
+    }
+
+    /// Remove conflict records whose files are no longer present
+    pub fn remove_conflicts_not_in(&self, valid_paths: &HashSet<String>) -> Result<(), String> {
+        let records = self.get_conflicts()?;
+        for record in records {
+            if !valid_paths.contains(&record.file_path) {
+                self.remove_conflict(&record.file_path)?;
+            }
+        }
+        Ok(())
+    }
+}
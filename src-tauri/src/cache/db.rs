@@ -1,18 +1,68 @@
+use crate::commands::secrets::{profile_account, read_secret, remove_secret, write_secret};
+use crate::crypto;
 use directories::ProjectDirs;
-use rusqlite::Connection;
-use std::path::PathBuf;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use super::schema::SCHEMA;
+use super::schema::{ADDED_NOTE_COLUMNS, SCHEMA, SCHEMA_VERSION};
+use super::transitions::normalize_transition_times;
+
+const CONNECTION_PRAGMAS: &str =
+    "PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL; PRAGMA foreign_keys=ON;";
+/// Keychain service holding the SQLCipher key of encrypted caches
+const CACHE_KEY_SERVICE: &str = "noteban.cache-key";
 
 pub struct CacheDb {
     pub conn: Mutex<Connection>,
     pub profile_id: String,
+    /// Raw SQLCipher key (hex) when the database is encrypted
+    key: Option<String>,
+}
+
+/// Unlock an SQLCipher database; must run before anything else touches it
+fn apply_key(conn: &Connection, key: Option<&str>) -> Result<(), String> {
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", format!("x'{}'", key))
+            .map_err(|e| format!("Failed to unlock cache database: {}", e))?;
+    }
+    Ok(())
+}
+
+fn cache_key(profile_id: &str) -> Result<Option<String>, String> {
+    let key = read_secret(CACHE_KEY_SERVICE, &profile_account(profile_id))?;
+    if key.is_some() && !CacheDb::encryption_supported() {
+        return Err("Cache is encrypted but this build has no SQLCipher support".to_string());
+    }
+    Ok(key)
+}
+
+fn add_missing_note_columns(conn: &Connection) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info('notes')")
+        .map_err(|e| format!("Failed to inspect cache schema: {}", e))?;
+    let existing: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to inspect cache schema: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    for (name, definition) in ADDED_NOTE_COLUMNS {
+        if !existing.iter().any(|column| column == name) {
+            conn.execute(
+                &format!("ALTER TABLE notes ADD COLUMN {} {}", name, definition),
+                [],
+            )
+            .map_err(|e| format!("Failed to upgrade cache schema: {}", e))?;
+        }
+    }
+    Ok(())
 }
 
 impl CacheDb {
     pub fn new(profile_id: &str) -> Result<Self, String> {
         let cache_path = Self::get_cache_path(profile_id)?;
+        let key = cache_key(profile_id)?;
 
         // Ensure parent directory exists
         if let Some(parent) = cache_path.parent() {
@@ -22,28 +72,186 @@ impl CacheDb {
 
         let conn = Connection::open(&cache_path)
             .map_err(|e| format!("Failed to open cache database: {}", e))?;
+        apply_key(&conn, key.as_deref())?;
 
         // Enable WAL mode for better concurrent read performance
-        conn.execute_batch(
-            "PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL; PRAGMA foreign_keys=ON;",
-        )
-        .map_err(|e| format!("Failed to set pragmas: {}", e))?;
+        conn.execute_batch(CONNECTION_PRAGMAS)
+            .map_err(|e| format!("Failed to set pragmas: {}", e))?;
 
         let db = Self {
             conn: Mutex::new(conn),
             profile_id: profile_id.to_string(),
+            key,
         };
 
         db.initialize_schema()?;
         Ok(db)
     }
 
+    /// Unencrypted cache that lives only as long as the handle
+    #[cfg(test)]
+    pub fn in_memory(profile_id: &str) -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open cache database: {}", e))?;
+        conn.execute_batch("PRAGMA foreign_keys=ON;")
+            .map_err(|e| format!("Failed to set pragmas: {}", e))?;
+        let db = Self {
+            conn: Mutex::new(conn),
+            profile_id: profile_id.to_string(),
+            key: None,
+        };
+        db.initialize_schema()?;
+        Ok(db)
+    }
+
+    /// Open an existing cache without creating, migrating or writing to it.
+    /// Used in safe mode; every write through this handle fails.
+    pub fn open_readonly(profile_id: &str) -> Result<Self, String> {
+        let cache_path = Self::get_cache_path(profile_id)?;
+        let key = cache_key(profile_id)?;
+        let conn = Connection::open_with_flags(&cache_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("Failed to open cache database: {}", e))?;
+        apply_key(&conn, key.as_deref())?;
+
+        let version: Option<String> = conn
+            .query_row(
+                "SELECT value FROM cache_meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read schema version: {}", e))?;
+        if version.as_deref() != Some(SCHEMA_VERSION) {
+            return Err("Cache was written by a different version".to_string());
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            profile_id: profile_id.to_string(),
+            key,
+        })
+    }
+
     fn get_cache_path(profile_id: &str) -> Result<PathBuf, String> {
         let proj_dirs =
             ProjectDirs::from("", "", "noteban").ok_or("Could not determine cache directory")?;
         Ok(proj_dirs.cache_dir().join(profile_id).join("cache.db"))
     }
 
+    /// Open a separate connection that SQLite itself refuses to write through
+    pub fn open_readonly_connection(&self) -> Result<Connection, String> {
+        let cache_path = Self::get_cache_path(&self.profile_id)?;
+        let conn = Connection::open_with_flags(
+            &cache_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| format!("Failed to open read-only cache connection: {}", e))?;
+        apply_key(&conn, self.key.as_deref())?;
+        Ok(conn)
+    }
+
+    /// Whether this build links SQLCipher and can encrypt the cache
+    pub fn encryption_supported() -> bool {
+        cfg!(feature = "encrypted-cache")
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Key sealing the profile's note history, present exactly when the
+    /// database is encrypted
+    pub fn history_key(&self) -> Option<crypto::Key> {
+        let master = crypto::from_hex(self.key.as_deref()?).ok()?;
+        Some(crypto::derive_subkey(&master, "noteban history"))
+    }
+
+    /// Rewrite the database encrypted under a new random key kept in the OS
+    /// keychain, or back to plaintext, and reopen it
+    pub fn set_encrypted(&mut self, encrypted: bool) -> Result<(), String> {
+        if encrypted == self.is_encrypted() {
+            return Ok(());
+        }
+        if !Self::encryption_supported() {
+            return Err("This build cannot encrypt the cache database".to_string());
+        }
+        let cache_path = Self::get_cache_path(&self.profile_id)?;
+        let export_path = cache_path.with_extension("db.export");
+        let _ = fs::remove_file(&export_path);
+        let key = encrypted.then(|| crypto::to_hex(&crypto::random_key()));
+        let account = profile_account(&self.profile_id);
+
+        {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|_| "Cache lock error".to_string())?;
+            let attach_key = key
+                .as_ref()
+                .map(|key| format!("x'{}'", key))
+                .unwrap_or_default();
+            conn.execute(
+                "ATTACH DATABASE ?1 AS export KEY ?2",
+                params![export_path.to_string_lossy(), attach_key],
+            )
+            .map_err(|e| format!("Failed to create cache copy: {}", e))?;
+            let exported = conn
+                .query_row("SELECT sqlcipher_export('export')", [], |_| Ok(()))
+                .map_err(|e| format!("Failed to copy cache: {}", e));
+            conn.execute("DETACH DATABASE export", [])
+                .map_err(|e| format!("Failed to finish cache copy: {}", e))?;
+            exported?;
+        }
+
+        match &key {
+            Some(key) => write_secret(CACHE_KEY_SERVICE, &account, key)?,
+            None => remove_secret(CACHE_KEY_SERVICE, &account)?,
+        }
+        if let Err(e) = self.replace_file(&cache_path, &export_path, key.as_deref()) {
+            // Keep the keychain in step with the file that is still in place
+            match &self.key {
+                Some(old) => write_secret(CACHE_KEY_SERVICE, &account, old)?,
+                None => remove_secret(CACHE_KEY_SERVICE, &account)?,
+            }
+            return Err(e);
+        }
+        self.key = key;
+        Ok(())
+    }
+
+    /// Swap the database file for `replacement` and reopen it with `key`
+    fn replace_file(
+        &self,
+        cache_path: &Path,
+        replacement: &Path,
+        key: Option<&str>,
+    ) -> Result<(), String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        // Close the old file first; Windows can't replace an open file
+        *conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to close cache database: {}", e))?;
+        for suffix in ["-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", cache_path.display(), suffix));
+        }
+        let reopen = |key: Option<&str>| -> Result<Connection, String> {
+            let conn = Connection::open(cache_path)
+                .map_err(|e| format!("Failed to open cache database: {}", e))?;
+            apply_key(&conn, key)?;
+            conn.execute_batch(CONNECTION_PRAGMAS)
+                .map_err(|e| format!("Failed to set pragmas: {}", e))?;
+            Ok(conn)
+        };
+        if let Err(e) = fs::rename(replacement, cache_path) {
+            *conn = reopen(self.key.as_deref())?;
+            return Err(format!("Failed to replace cache database: {}", e));
+        }
+        *conn = reopen(key)?;
+        Ok(())
+    }
+
     fn initialize_schema(&self) -> Result<(), String> {
         let conn = self
             .conn
@@ -51,6 +259,26 @@ impl CacheDb {
             .map_err(|_| "Cache lock error".to_string())?;
         conn.execute_batch(SCHEMA)
             .map_err(|e| format!("Failed to initialize schema: {}", e))?;
+
+        let version: Option<String> = conn
+            .query_row(
+                "SELECT value FROM cache_meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read schema version: {}", e))?;
+        if version.as_deref() != Some(SCHEMA_VERSION) {
+            add_missing_note_columns(&conn)?;
+            normalize_transition_times(&conn)?;
+            conn.execute("DELETE FROM notes", [])
+                .map_err(|e| format!("Failed to invalidate cache: {}", e))?;
+            conn.execute(
+                "INSERT OR REPLACE INTO cache_meta (key, value) VALUES ('schema_version', ?)",
+                [SCHEMA_VERSION],
+            )
+            .map_err(|e| format!("Failed to write schema version: {}", e))?;
+        }
         Ok(())
     }
 
@@ -64,6 +292,54 @@ impl CacheDb {
         Ok(())
     }
 
+    /// Sizes in bytes of the database file and its write-ahead log
+    pub fn disk_usage(&self) -> Result<(u64, u64), String> {
+        let cache_path = Self::get_cache_path(&self.profile_id)?;
+        let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let wal_path = PathBuf::from(format!("{}-wal", cache_path.display()));
+        Ok((size(&cache_path), size(&wal_path)))
+    }
+
+    /// Number of cached notes and known tags
+    pub fn counts(&self) -> Result<(usize, usize), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM notes), (SELECT COUNT(*) FROM tags)",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as usize,
+                ))
+            },
+        )
+        .map_err(|e| format!("Failed to count cached rows: {}", e))
+    }
+
+    /// Drop tags no note uses any more, then compact the database, refresh
+    /// the query planner statistics and truncate the write-ahead log.
+    /// Returns the number of tags removed.
+    pub fn optimize(&self) -> Result<usize, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let removed_tags = conn
+            .execute(
+                "DELETE FROM tags WHERE id NOT IN (SELECT DISTINCT tag_id FROM note_tags)",
+                [],
+            )
+            .map_err(|e| format!("Failed to remove unused tags: {}", e))?;
+        conn.execute_batch("VACUUM; ANALYZE;")
+            .map_err(|e| format!("Failed to compact cache: {}", e))?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| format!("Failed to checkpoint cache: {}", e))?;
+        Ok(removed_tags)
+    }
+
     pub fn verify_integrity(&self) -> Result<bool, String> {
         let conn = self
             .conn
@@ -75,4 +351,39 @@ impl CacheDb {
 
         Ok(result == "ok")
     }
+
+    pub fn set_meta(&self, key: &str, value: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO cache_meta (key, value) VALUES (?, ?)",
+            params![key, value],
+        )
+        .map_err(|e| format!("Failed to write cache metadata: {}", e))?;
+        Ok(())
+    }
+
+    pub fn delete_meta(&self, key: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute("DELETE FROM cache_meta WHERE key = ?", [key])
+            .map_err(|e| format!("Failed to delete cache metadata: {}", e))?;
+        Ok(())
+    }
+
+    pub fn get_meta(&self, key: &str) -> Result<Option<String>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.query_row("SELECT value FROM cache_meta WHERE key = ?", [key], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|e| format!("Failed to read cache metadata: {}", e))
+    }
 }
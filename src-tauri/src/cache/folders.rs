@@ -0,0 +1,236 @@
+use super::db::CacheDb;
+use crate::commands::mounts::MOUNTS_DIR_NAME;
+use crate::commands::notes::Folder;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, MAIN_SEPARATOR_STR};
+
+/// A vault folder as recorded by the last scan or folder command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFolder {
+    pub path: String,
+    pub name: String,
+    pub relative_path: String,
+    /// Absolute path of the enclosing folder, `None` at the top level
+    pub parent_path: Option<String>,
+    /// Cached notes directly inside the folder
+    pub note_count: usize,
+}
+
+fn parent_of(path: &str) -> Option<String> {
+    Path::new(path)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+impl CacheDb {
+    /// Replace the whole folder index with the result of a vault walk
+    pub fn replace_folders(&self, folders: &[Folder]) -> Result<(), String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        tx.execute("DELETE FROM folders", [])
+            .map_err(|e| format!("Failed to clear folder index: {}", e))?;
+        for folder in folders {
+            insert_folder(&tx, folder)?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit folder index: {}", e))?;
+        Ok(())
+    }
+
+    pub fn upsert_folder(&self, folder: &Folder) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        insert_folder(&conn, folder)
+    }
+
+    /// Point a renamed folder and everything below it at its new location
+    pub fn rename_folder_tree(&self, old_path: &str, renamed: &Folder) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let old_relative: Option<String> = conn
+            .query_row(
+                "SELECT relative_path FROM folders WHERE path = ?",
+                [old_path],
+                |row| row.get(0),
+            )
+            .ok();
+
+        conn.execute(
+            "UPDATE folders SET
+                 path = ?2 || substr(path, length(?1) + 1),
+                 parent_path = CASE WHEN path = ?1 THEN parent_path
+                     ELSE ?2 || substr(parent_path, length(?1) + 1) END,
+                 relative_path = CASE WHEN ?3 IS NULL THEN relative_path
+                     ELSE ?4 || substr(relative_path, length(?3) + 1) END,
+                 name = CASE WHEN path = ?1 THEN ?5 ELSE name END
+             WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || ?6",
+            params![
+                old_path,
+                renamed.path,
+                old_relative,
+                renamed.relative_path,
+                renamed.name,
+                MAIN_SEPARATOR_STR
+            ],
+        )
+        .map_err(|e| format!("Failed to update folder index: {}", e))?;
+        Ok(())
+    }
+
+    /// Forget a folder and everything below it. Returns false if nothing
+    /// was indexed at `path`.
+    pub fn remove_folder_tree(&self, path: &str) -> Result<bool, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let removed = conn
+            .execute(
+                "DELETE FROM folders WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || ?2",
+                params![path, MAIN_SEPARATOR_STR],
+            )
+            .map_err(|e| format!("Failed to update folder index: {}", e))?;
+        Ok(removed > 0)
+    }
+
+    /// Indexed folders sorted by relative path, with their note counts
+    pub fn get_folders(&self) -> Result<Vec<CachedFolder>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut note_counts: HashMap<String, usize> = HashMap::new();
+        let mut stmt = conn
+            .prepare("SELECT file_path FROM notes")
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        let note_paths = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to query notes: {}", e))?
+            .filter_map(|r| r.ok());
+        for file_path in note_paths {
+            if let Some(parent) = parent_of(&file_path) {
+                *note_counts.entry(parent).or_default() += 1;
+            }
+        }
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, name, relative_path, parent_path FROM folders
+                 ORDER BY relative_path",
+            )
+            .map_err(|e| format!("Failed to prepare folder query: {}", e))?;
+        let folders = stmt
+            .query_map([], |row| {
+                let path: String = row.get(0)?;
+                Ok(CachedFolder {
+                    note_count: note_counts.get(&path).copied().unwrap_or(0),
+                    path,
+                    name: row.get(1)?,
+                    relative_path: row.get(2)?,
+                    parent_path: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query folders: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(folders)
+    }
+}
+
+fn insert_folder(conn: &rusqlite::Connection, folder: &Folder) -> Result<(), String> {
+    // Top-level folders (and read-only mounts) have no indexed parent
+    let parent_path = Path::new(&folder.relative_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty() && *p != Path::new(MOUNTS_DIR_NAME))
+        .and_then(|_| parent_of(&folder.path));
+    conn.execute(
+        "INSERT OR REPLACE INTO folders (path, name, relative_path, parent_path)
+         VALUES (?, ?, ?, ?)",
+        params![folder.path, folder.name, folder.relative_path, parent_path],
+    )
+    .map_err(|e| format!("Failed to index folder: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::parse_note_content;
+
+    fn folder(relative: &str) -> Folder {
+        let path = Path::new("/v").join(relative);
+        Folder {
+            path: path.to_string_lossy().to_string(),
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            relative_path: relative.to_string(),
+        }
+    }
+
+    fn paths(cache: &CacheDb) -> Vec<(String, Option<String>)> {
+        cache
+            .get_folders()
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.relative_path, f.parent_path))
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn renames_and_removes_folder_trees() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        cache
+            .replace_folders(&[folder("Work"), folder("Work/Acme"), folder("Home")])
+            .unwrap();
+        let text = "---\nid: a\ntitle: a\ncolumn: todo\n---\n\nBody";
+        let note = parse_note_content(text, Path::new("/v/Work/Acme/a.md")).unwrap();
+        cache.upsert_note(&note, "hash", 0, &[]).unwrap();
+
+        let acme = cache
+            .get_folders()
+            .unwrap()
+            .into_iter()
+            .find(|f| f.name == "Acme")
+            .unwrap();
+        assert_eq!(acme.note_count, 1);
+        assert_eq!(acme.parent_path.as_deref(), Some("/v/Work"));
+
+        cache
+            .rename_folder_tree("/v/Work", &folder("Jobs"))
+            .unwrap();
+        assert_eq!(
+            paths(&cache),
+            [
+                ("Home".to_string(), None),
+                ("Jobs".to_string(), None),
+                ("Jobs/Acme".to_string(), Some("/v/Jobs".to_string())),
+            ]
+        );
+
+        assert!(cache.remove_folder_tree("/v/Jobs").unwrap());
+        assert!(!cache.remove_folder_tree("/v/Jobs").unwrap());
+        cache.upsert_folder(&folder(".mounts/specs")).unwrap();
+        assert_eq!(
+            paths(&cache),
+            [
+                (".mounts/specs".to_string(), None),
+                ("Home".to_string(), None)
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use super::db::CacheDb;
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+
+/// How long an idempotency key is remembered after the note was created
+const IDEMPOTENCY_KEY_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// A claim whose note never appeared is given up after this long, e.g. when
+/// the app quit halfway through creating it
+const PENDING_CLAIM_SECS: i64 = 60;
+
+/// Outcome of claiming an idempotency key for a new note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyClaim {
+    /// The key is now held for the note id passed in; create the note
+    Claimed,
+    /// Another request with the key is still creating its note
+    Pending,
+    /// The key already produced the note now at this path
+    Created(String),
+}
+
+impl CacheDb {
+    /// Claim `key` for a note about to be created with `note_id`, or find
+    /// the note an earlier request created for it. The note is looked up by
+    /// id, so it is found after being renamed or moved. Lookup and claim run
+    /// in one transaction so concurrent retries cannot both create a note.
+    pub fn claim_idempotency_key(
+        &self,
+        key: &str,
+        note_id: &str,
+    ) -> Result<IdempotencyClaim, String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let now = Utc::now().timestamp();
+
+        tx.execute(
+            "DELETE FROM idempotency_keys WHERE created_at < ?",
+            [now - IDEMPOTENCY_KEY_TTL_SECS],
+        )
+        .map_err(|e| format!("Failed to prune idempotency keys: {}", e))?;
+
+        // A claim's file path stays empty until its note has been written
+        let existing: Option<(i64, String, Option<String>)> = tx
+            .query_row(
+                "SELECT k.created_at, k.file_path,
+                        (SELECT n.file_path FROM notes n WHERE n.id = k.note_id LIMIT 1)
+                 FROM idempotency_keys k WHERE k.key = ?",
+                [key],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read idempotency key: {}", e))?;
+
+        let claim = match existing {
+            Some((_, _, Some(file_path))) => IdempotencyClaim::Created(file_path),
+            Some((claimed_at, written, None))
+                if written.is_empty() && now - claimed_at < PENDING_CLAIM_SECS =>
+            {
+                IdempotencyClaim::Pending
+            }
+            // New key, or its note is gone: this request creates it
+            _ => {
+                tx.execute(
+                    "INSERT OR REPLACE INTO idempotency_keys (key, note_id, file_path, created_at)
+                     VALUES (?, ?, '', ?)",
+                    params![key, note_id, now],
+                )
+                .map_err(|e| format!("Failed to record idempotency key: {}", e))?;
+                IdempotencyClaim::Claimed
+            }
+        };
+        tx.commit()
+            .map_err(|e| format!("Failed to commit idempotency key: {}", e))?;
+        Ok(claim)
+    }
+
+    /// Note where the claimed key's note was written
+    pub fn complete_idempotency_key(&self, key: &str, file_path: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "UPDATE idempotency_keys SET file_path = ? WHERE key = ?",
+            params![file_path, key],
+        )
+        .map_err(|e| format!("Failed to record idempotency key: {}", e))?;
+        Ok(())
+    }
+
+    pub fn forget_idempotency_key(&self, key: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute("DELETE FROM idempotency_keys WHERE key = ?", [key])
+            .map_err(|e| format!("Failed to forget idempotency key: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::parse_note_content;
+    use std::path::Path;
+
+    fn cache_note(cache: &CacheDb, id: &str, file_path: &str) {
+        let text = format!("---\nid: {id}\ntitle: {id}\ncolumn: todo\n---\n\nBody");
+        let note = parse_note_content(&text, Path::new(file_path)).unwrap();
+        cache.upsert_note(&note, "hash", 0, &[]).unwrap();
+    }
+
+    #[test]
+    fn claims_keys_once_and_follows_the_note_id() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        assert_eq!(
+            cache.claim_idempotency_key("k", "n1").unwrap(),
+            IdempotencyClaim::Claimed
+        );
+        // A retry while the first request is still writing must not create
+        assert_eq!(
+            cache.claim_idempotency_key("k", "n2").unwrap(),
+            IdempotencyClaim::Pending
+        );
+
+        cache_note(&cache, "n1", "/vault/a.md");
+        cache.complete_idempotency_key("k", "/vault/a.md").unwrap();
+        assert_eq!(
+            cache.claim_idempotency_key("k", "n3").unwrap(),
+            IdempotencyClaim::Created("/vault/a.md".to_string())
+        );
+
+        // Renamed: found at its new path through the id
+        cache.remove_note("/vault/a.md").unwrap();
+        cache_note(&cache, "n1", "/vault/work/b.md");
+        assert_eq!(
+            cache.claim_idempotency_key("k", "n4").unwrap(),
+            IdempotencyClaim::Created("/vault/work/b.md".to_string())
+        );
+
+        // Deleted: the key may be used again
+        cache.remove_note("/vault/work/b.md").unwrap();
+        assert_eq!(
+            cache.claim_idempotency_key("k", "n5").unwrap(),
+            IdempotencyClaim::Claimed
+        );
+        cache.forget_idempotency_key("k").unwrap();
+        assert_eq!(
+            cache.claim_idempotency_key("k", "n6").unwrap(),
+            IdempotencyClaim::Claimed
+        );
+    }
+}
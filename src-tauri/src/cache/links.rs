@@ -0,0 +1,205 @@
+use super::db::CacheDb;
+use crate::commands::import::WIKILINK_REGEX;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+/// Names a cached note can be found by
+#[derive(Debug, Clone)]
+pub struct NoteNames {
+    pub file_path: String,
+    pub title: String,
+    pub aliases: Vec<String>,
+}
+
+pub(super) fn replace_note_aliases_tx(
+    tx: &Transaction<'_>,
+    note_id: &str,
+    aliases: &[String],
+) -> Result<(), String> {
+    tx.execute("DELETE FROM note_aliases WHERE note_id = ?", [note_id])
+        .map_err(|e| format!("Failed to clear note aliases: {}", e))?;
+    for alias in aliases {
+        tx.execute(
+            "INSERT OR IGNORE INTO note_aliases (note_id, alias) VALUES (?, ?)",
+            params![note_id, alias],
+        )
+        .map_err(|e| format!("Failed to insert note alias: {}", e))?;
+    }
+    Ok(())
+}
+
+pub(super) fn load_note_aliases(conn: &Connection, note_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare_cached("SELECT alias FROM note_aliases WHERE note_id = ? ORDER BY rowid")
+        .map_err(|e| format!("Failed to prepare aliases query: {}", e))?;
+    let aliases = stmt
+        .query_map([note_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to query note aliases: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(aliases)
+}
+
+/// Target of a wikilink without its label and heading
+fn link_target(inner: &str) -> &str {
+    let target = inner.split('|').next().unwrap_or(inner);
+    target.split('#').next().unwrap_or(target).trim()
+}
+
+impl CacheDb {
+    /// Path of the note a `[[target]]` link points at: the note with that
+    /// title, then one with that alias, then one with that file name or
+    /// relative path. Names are compared case-insensitively.
+    pub fn resolve_wikilink(&self, target: &str) -> Result<Option<String>, String> {
+        let target = link_target(target);
+        let target = target.strip_suffix(".md").unwrap_or(target);
+        if target.is_empty() {
+            return Ok(None);
+        }
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let by_name = conn
+            .query_row(
+                "SELECT file_path, 0 AS rank FROM notes WHERE title = ?1 COLLATE NOCASE
+                 UNION ALL
+                 SELECT n.file_path, 1 FROM note_aliases a JOIN notes n ON n.id = a.note_id
+                 WHERE a.alias = ?1 COLLATE NOCASE
+                 ORDER BY rank, file_path
+                 LIMIT 1",
+                [target],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to resolve link: {}", e))?;
+        if by_name.is_some() {
+            return Ok(by_name);
+        }
+
+        let suffix = format!("/{}.md", target.replace('\\', "/").to_lowercase());
+        let mut stmt = conn
+            .prepare("SELECT file_path FROM notes ORDER BY length(file_path)")
+            .map_err(|e| format!("Failed to prepare link query: {}", e))?;
+        let by_path = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to resolve link: {}", e))?
+            .filter_map(|r| r.ok())
+            .find(|path| path.replace('\\', "/").to_lowercase().ends_with(&suffix));
+        Ok(by_path)
+    }
+
+    /// Title and aliases of every cached note, leaving out archived ones
+    /// unless `include_archived` is set
+    pub fn note_names(&self, include_archived: bool) -> Result<Vec<NoteNames>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT n.file_path, n.title, a.alias FROM notes n
+                 LEFT JOIN note_aliases a ON a.note_id = n.id
+                 WHERE ?1 OR n.archived = 0
+                 ORDER BY n.file_path, a.rowid",
+            )
+            .map_err(|e| format!("Failed to prepare names query: {}", e))?;
+        let rows = stmt
+            .query_map([include_archived], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query note names: {}", e))?;
+
+        let mut names: Vec<NoteNames> = Vec::new();
+        for (file_path, title, alias) in rows.filter_map(|r| r.ok()) {
+            if names.last().map(|last| &last.file_path) != Some(&file_path) {
+                names.push(NoteNames {
+                    file_path,
+                    title,
+                    aliases: Vec::new(),
+                });
+            }
+            if let (Some(alias), Some(last)) = (alias, names.last_mut()) {
+                last.aliases.push(alias);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Whether any cached note contains a wikilink to `title`
+    pub fn has_wikilinks_to(&self, title: &str) -> Result<bool, String> {
+        let title = title.trim().to_lowercase();
+        if title.is_empty() {
+            return Ok(false);
+        }
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT content FROM notes WHERE instr(lower(content), ?) > 0")
+            .map_err(|e| format!("Failed to prepare link query: {}", e))?;
+        let contents = stmt
+            .query_map([format!("[[{}", title)], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to query links: {}", e))?;
+
+        let linked = contents.filter_map(|r| r.ok()).any(|content| {
+            WIKILINK_REGEX
+                .captures_iter(&content)
+                .any(|caps| link_target(&caps[2]).to_lowercase() == title)
+        });
+        Ok(linked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::parse_note_content;
+    use std::path::Path;
+
+    fn cache_note(cache: &CacheDb, file_path: &str, title: &str, extra: &str, body: &str) {
+        let text =
+            format!("---\nid: {file_path}\ntitle: {title}\ncolumn: todo\n{extra}---\n\n{body}");
+        let note = parse_note_content(&text, Path::new(file_path)).unwrap();
+        cache.upsert_note(&note, "hash", 0, &[]).unwrap();
+    }
+
+    #[test]
+    fn resolves_links_by_title_then_alias_then_path() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        cache_note(&cache, "/v/Work/plan.md", "Roadmap", "aliases: [Q3]\n", "");
+        cache_note(&cache, "/v/q3.md", "Quarter", "", "");
+        cache_note(
+            &cache,
+            "/v/old.md",
+            "Old",
+            "archived: true\n",
+            "See [[roadmap#goals|the plan]]",
+        );
+
+        let resolve = |target: &str| cache.resolve_wikilink(target).unwrap();
+        assert_eq!(
+            resolve("roadmap#goals|x").as_deref(),
+            Some("/v/Work/plan.md")
+        );
+        // An alias outranks a file with the same name
+        assert_eq!(resolve("q3").as_deref(), Some("/v/Work/plan.md"));
+        assert_eq!(resolve("Work/plan.md").as_deref(), Some("/v/Work/plan.md"));
+        assert_eq!(resolve("nothing"), None);
+        assert_eq!(resolve(" "), None);
+
+        assert!(cache.has_wikilinks_to("ROADMAP").unwrap());
+        assert!(!cache.has_wikilinks_to("Quarter").unwrap());
+
+        let names = cache.note_names(false).unwrap();
+        assert_eq!(names.len(), 2);
+        let plan = names.iter().find(|n| n.title == "Roadmap").unwrap();
+        assert_eq!(plan.aliases, ["Q3"]);
+        assert_eq!(cache.note_names(true).unwrap().len(), 3);
+    }
+}
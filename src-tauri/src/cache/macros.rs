@@ -0,0 +1,169 @@
+use super::db::CacheDb;
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// One command invocation inside a macro; string arguments may contain
+/// `{{param}}` placeholders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroStep {
+    pub command: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroDefinition {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Parameters the caller must supply when running the macro
+    #[serde(default)]
+    pub params: Vec<String>,
+    pub steps: Vec<MacroStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedMacro {
+    pub name: String,
+    #[serde(flatten)]
+    pub definition: MacroDefinition,
+    pub updated_at: String,
+}
+
+impl CacheDb {
+    pub fn save_macro(
+        &self,
+        name: &str,
+        definition: &MacroDefinition,
+    ) -> Result<SavedMacro, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let encoded = serde_json::to_string(definition)
+            .map_err(|e| format!("Failed to encode macro: {}", e))?;
+        let updated_at = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO macros (name, definition, updated_at) VALUES (?, ?, ?)",
+            params![name, encoded, updated_at],
+        )
+        .map_err(|e| format!("Failed to save macro: {}", e))?;
+
+        Ok(SavedMacro {
+            name: name.to_string(),
+            definition: definition.clone(),
+            updated_at,
+        })
+    }
+
+    pub fn get_macro(&self, name: &str) -> Result<Option<SavedMacro>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let row: Option<(String, String)> = conn
+            .query_row(
+                "SELECT definition, updated_at FROM macros WHERE name = ?",
+                [name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read macro: {}", e))?;
+
+        match row {
+            Some((definition, updated_at)) => Ok(Some(SavedMacro {
+                name: name.to_string(),
+                definition: serde_json::from_str(&definition)
+                    .map_err(|e| format!("Failed to decode macro: {}", e))?,
+                updated_at,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_macros(&self) -> Result<Vec<SavedMacro>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT name, definition, updated_at FROM macros ORDER BY name")
+            .map_err(|e| format!("Failed to prepare macros query: {}", e))?;
+
+        let macros = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query macros: {}", e))?
+            .filter_map(|r| r.ok())
+            .filter_map(|(name, definition, updated_at)| {
+                Some(SavedMacro {
+                    name,
+                    definition: serde_json::from_str(&definition).ok()?,
+                    updated_at,
+                })
+            })
+            .collect();
+
+        Ok(macros)
+    }
+
+    /// Returns false when no macro with that name existed
+    pub fn delete_macro(&self, name: &str) -> Result<bool, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let removed = conn
+            .execute("DELETE FROM macros WHERE name = ?", [name])
+            .map_err(|e| format!("Failed to delete macro: {}", e))?;
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn definition(command: &str) -> MacroDefinition {
+        MacroDefinition {
+            description: None,
+            params: vec!["tag".to_string()],
+            steps: vec![MacroStep {
+                command: command.to_string(),
+                args: json!({ "tags": ["{{tag}}"] }),
+            }],
+        }
+    }
+
+    #[test]
+    fn saves_replaces_and_deletes_macros() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        cache.save_macro("tidy", &definition("add_tags")).unwrap();
+        cache.save_macro("archive", &definition("archive")).unwrap();
+        cache.save_macro("tidy", &definition("move")).unwrap();
+
+        let names: Vec<String> = cache
+            .get_macros()
+            .unwrap()
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(names, ["archive", "tidy"]);
+        let tidy = cache.get_macro("tidy").unwrap().unwrap();
+        assert_eq!(tidy.definition.steps[0].command, "move");
+        assert_eq!(tidy.definition.steps[0].args["tags"][0], "{{tag}}");
+
+        assert!(cache.delete_macro("tidy").unwrap());
+        assert!(!cache.delete_macro("tidy").unwrap());
+        assert!(cache.get_macro("tidy").unwrap().is_none());
+    }
+}
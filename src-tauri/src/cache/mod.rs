@@ -1,6 +1,17 @@
+pub mod audit;
+pub mod conflicts;
 pub mod db;
+pub mod folders;
+pub mod idempotency;
+pub mod links;
+pub mod macros;
+pub mod mounts;
 pub mod queries;
 pub mod schema;
+pub mod storage;
 pub mod sync;
+pub mod tags;
+pub mod transitions;
+pub mod views;
 
 pub use db::CacheDb;
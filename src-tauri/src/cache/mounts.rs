@@ -0,0 +1,92 @@
+use super::db::CacheDb;
+use rusqlite::params;
+
+#[derive(Debug, Clone)]
+pub struct MountRecord {
+    pub name: String,
+    pub path: String,
+    pub added_at: String,
+}
+
+impl CacheDb {
+    pub fn add_readonly_mount(&self, record: &MountRecord) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        conn.execute(
+            "INSERT INTO readonly_mounts (name, path, added_at) VALUES (?, ?, ?)",
+            params![record.name, record.path, record.added_at],
+        )
+        .map_err(|e| format!("Failed to add read-only mount: {}", e))?;
+
+        Ok(())
+    }
+
+    pub fn remove_readonly_mount(&self, name: &str) -> Result<bool, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let removed = conn
+            .execute("DELETE FROM readonly_mounts WHERE name = ?", [name])
+            .map_err(|e| format!("Failed to remove read-only mount: {}", e))?;
+        Ok(removed > 0)
+    }
+
+    pub fn get_readonly_mounts(&self) -> Result<Vec<MountRecord>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT name, path, added_at FROM readonly_mounts ORDER BY name")
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let mounts = stmt
+            .query_map([], |row| {
+                Ok(MountRecord {
+                    name: row.get(0)?,
+                    path: row.get(1)?,
+                    added_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query read-only mounts: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(mounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str) -> MountRecord {
+        MountRecord {
+            name: name.to_string(),
+            path: format!("/refs/{}", name),
+            added_at: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn keeps_mount_names_unique() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        cache.add_readonly_mount(&record("specs")).unwrap();
+        cache.add_readonly_mount(&record("docs")).unwrap();
+        assert!(cache.add_readonly_mount(&record("docs")).is_err());
+
+        let mounts = cache.get_readonly_mounts().unwrap();
+        let names: Vec<&str> = mounts.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["docs", "specs"]);
+        assert_eq!(mounts[0].path, "/refs/docs");
+
+        assert!(cache.remove_readonly_mount("docs").unwrap());
+        assert!(!cache.remove_readonly_mount("docs").unwrap());
+        assert_eq!(cache.get_readonly_mounts().unwrap().len(), 1);
+    }
+}
@@ -1,34 +1,163 @@
 use super::db::CacheDb;
-use crate::commands::notes::{Note, NoteFrontmatter};
+use super::links::{load_note_aliases, replace_note_aliases_tx};
+use super::transitions::record_column_transition_tx;
+use crate::commands::notes::{FrontmatterFormat, Note, NoteFrontmatter, Priority};
+use crate::utils::{compute_content_hash, make_excerpt};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Transaction};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
 use std::collections::HashSet;
+use std::fs;
 
 #[derive(Debug, Clone)]
 pub struct CachedNote {
     pub note: Note,
     pub inline_tags: Vec<String>,
+    /// Plaintext preview of the body
+    pub excerpt: String,
+}
+
+/// A note just written to disk, with the file state the cache records
+pub struct NoteWrite<'a> {
+    pub note: &'a Note,
+    pub content_hash: String,
+    pub file_mtime: i64,
+    pub inline_tags: &'a [String],
+}
+
+/// Where a cached note sits on its board
+#[derive(Debug, Clone)]
+pub struct NotePlacement {
+    pub file_path: String,
+    pub column: String,
+    pub order: i32,
+    pub archived: bool,
+}
+
+/// Size of a file on disk, recorded next to its mtime
+fn file_size(file_path: &str) -> Option<i64> {
+    fs::metadata(file_path).ok().map(|m| m.len() as i64)
+}
+
+/// Typed value used to order notes by a custom property. Numbers sort before
+/// text in SQLite, and unsortable values (lists, maps) become NULL.
+fn property_sort_value(value: &serde_yaml::Value) -> SqlValue {
+    match value {
+        serde_yaml::Value::Number(n) => n.as_f64().map(SqlValue::Real).unwrap_or(SqlValue::Null),
+        serde_yaml::Value::String(s) => SqlValue::Text(s.to_lowercase()),
+        serde_yaml::Value::Bool(b) => SqlValue::Integer(*b as i64),
+        _ => SqlValue::Null,
+    }
+}
+
+fn replace_note_properties_tx(
+    tx: &Transaction<'_>,
+    note_id: &str,
+    properties: &serde_yaml::Mapping,
+) -> Result<(), String> {
+    tx.execute("DELETE FROM note_properties WHERE note_id = ?", [note_id])
+        .map_err(|e| format!("Failed to clear note properties: {}", e))?;
+
+    for (key, value) in properties {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let encoded = serde_json::to_string(value)
+            .map_err(|e| format!("Failed to encode note property: {}", e))?;
+        tx.execute(
+            "INSERT INTO note_properties (note_id, key, value, sort_value) VALUES (?, ?, ?, ?)",
+            params![note_id, key, encoded, property_sort_value(value)],
+        )
+        .map_err(|e| format!("Failed to insert note property: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn load_note_properties(conn: &Connection, note_id: &str) -> Result<serde_yaml::Mapping, String> {
+    // Rows are inserted in frontmatter order
+    let mut stmt = conn
+        .prepare_cached("SELECT key, value FROM note_properties WHERE note_id = ? ORDER BY rowid")
+        .map_err(|e| format!("Failed to prepare properties query: {}", e))?;
+
+    let properties = stmt
+        .query_map([note_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| format!("Failed to query note properties: {}", e))?
+        .filter_map(|r| r.ok())
+        .filter_map(|(key, value)| Some((key.into(), serde_json::from_str(&value).ok()?)))
+        .collect();
+
+    Ok(properties)
 }
 
 impl CacheDb {
-    /// Check if a file needs re-parsing based on mtime
+    /// Check if a file needs re-parsing based on mtime. Coarse or preserved
+    /// mtimes (FAT/exFAT, some network mounts, sync tools) can survive an
+    /// edit, so an unchanged mtime with a different file size is settled by
+    /// comparing content hashes.
     pub fn needs_update(&self, file_path: &str, current_mtime: i64) -> bool {
         let conn = match self.conn.lock() {
             Ok(c) => c,
             Err(_) => return true, // Assume update needed if lock fails
         };
-        let result: Result<i64, _> = conn.query_row(
-            "SELECT file_mtime FROM notes WHERE file_path = ?",
+        let result: Result<(i64, Option<i64>, String), _> = conn.query_row(
+            "SELECT file_mtime, file_size, content_hash FROM notes WHERE file_path = ?",
             [file_path],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         );
+        drop(conn);
 
         match result {
-            Ok(cached_mtime) => cached_mtime != current_mtime,
+            Ok((cached_mtime, _, _)) if cached_mtime != current_mtime => true,
+            Ok((_, cached_size, cached_hash)) => {
+                let current_size = file_size(file_path);
+                if cached_size.is_none() || current_size.is_none() || cached_size == current_size {
+                    return false;
+                }
+                match fs::read_to_string(file_path) {
+                    Ok(content) => compute_content_hash(&content) != cached_hash,
+                    Err(_) => true,
+                }
+            }
             Err(_) => true, // Not in cache, needs parsing
         }
     }
 
+    /// Content hash and mtime recorded when the file was last indexed
+    pub fn get_indexed_state(&self, file_path: &str) -> Result<Option<(String, i64)>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.query_row(
+            "SELECT content_hash, file_mtime FROM notes WHERE file_path = ?",
+            [file_path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read indexed state: {}", e))
+    }
+
+    /// Cached body, content hash and mtime of a note file
+    pub fn get_note_content(
+        &self,
+        file_path: &str,
+    ) -> Result<Option<(String, String, i64)>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.query_row(
+            "SELECT content, content_hash, file_mtime FROM notes WHERE file_path = ?",
+            [file_path],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read cached content: {}", e))
+    }
+
     /// Get a cached note by file path
     pub fn get_note(&self, file_path: &str) -> Result<Option<CachedNote>, String> {
         let conn = self
@@ -37,7 +166,8 @@ impl CacheDb {
             .map_err(|_| "Cache lock error".to_string())?;
 
         let note_result = conn.query_row(
-            "SELECT id, file_path, title, created, modified, date, column_name, order_num, content
+            "SELECT id, file_path, title, created, modified, date, column_name, order_num, content,
+                    excerpt, pinned, priority, due, color, cover, icon, archived
              FROM notes WHERE file_path = ?",
             [file_path],
             |row| {
@@ -50,8 +180,16 @@ impl CacheDb {
                 let column: String = row.get(6)?;
                 let order: i32 = row.get(7)?;
                 let content: String = row.get(8)?;
-
-                Ok(Note {
+                let excerpt: String = row.get(9)?;
+                let pinned: bool = row.get(10)?;
+                let priority: Option<i64> = row.get(11)?;
+                let due: Option<String> = row.get(12)?;
+                let color: Option<String> = row.get(13)?;
+                let cover: Option<String> = row.get(14)?;
+                let icon: Option<String> = row.get(15)?;
+                let archived: bool = row.get(16)?;
+
+                let note = Note {
                     frontmatter: NoteFrontmatter {
                         id,
                         title,
@@ -62,18 +200,29 @@ impl CacheDb {
                             .map(|dt| dt.with_timezone(&Utc))
                             .unwrap_or_else(|_| Utc::now()),
                         date,
+                        due,
                         column,
                         tags: Vec::new(), // Will be populated below
                         order,
+                        pinned,
+                        archived,
+                        priority: priority.and_then(Priority::from_rank),
+                        color,
+                        cover,
+                        icon,
+                        aliases: Vec::new(),
+                        extra: serde_yaml::Mapping::new(),
+                        format: FrontmatterFormat::Yaml,
                     },
                     content,
                     file_path,
-                })
+                };
+                Ok((note, excerpt))
             },
         );
 
         match note_result {
-            Ok(mut note) => {
+            Ok((mut note, excerpt)) => {
                 // Get frontmatter tags
                 let mut stmt = conn
                     .prepare(
@@ -90,6 +239,8 @@ impl CacheDb {
                     .collect();
 
                 note.frontmatter.tags = frontmatter_tags;
+                note.frontmatter.aliases = load_note_aliases(&conn, &note.frontmatter.id)?;
+                note.frontmatter.extra = load_note_properties(&conn, &note.frontmatter.id)?;
 
                 // Get inline tags
                 let mut stmt = conn
@@ -106,7 +257,11 @@ impl CacheDb {
                     .filter_map(|r| r.ok())
                     .collect();
 
-                Ok(Some(CachedNote { note, inline_tags }))
+                Ok(Some(CachedNote {
+                    note,
+                    inline_tags,
+                    excerpt,
+                }))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(format!("Failed to get cached note: {}", e)),
@@ -125,16 +280,52 @@ impl CacheDb {
             .conn
             .lock()
             .map_err(|_| "Cache lock error".to_string())?;
-        let now = Utc::now().timestamp();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        self.upsert_note_tx(&tx, note, content_hash, file_mtime, inline_tags)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit cache transaction: {}", e))
+    }
 
+    /// Upsert several notes in one transaction, so either all or none of
+    /// them are updated
+    pub fn upsert_notes(&self, writes: &[NoteWrite<'_>]) -> Result<(), String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
         let tx = conn
             .transaction()
             .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        for write in writes {
+            self.upsert_note_tx(
+                &tx,
+                write.note,
+                &write.content_hash,
+                write.file_mtime,
+                write.inline_tags,
+            )?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit cache transaction: {}", e))
+    }
+
+    fn upsert_note_tx(
+        &self,
+        tx: &Transaction<'_>,
+        note: &Note,
+        content_hash: &str,
+        file_mtime: i64,
+        inline_tags: &[String],
+    ) -> Result<(), String> {
+        let now = Utc::now().timestamp();
+        record_column_transition_tx(tx, &note.frontmatter.id, &note.frontmatter.column)?;
 
         tx.execute(
             "INSERT OR REPLACE INTO notes
-             (id, file_path, title, created, modified, date, column_name, order_num, content, content_hash, file_mtime, cached_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             (id, file_path, title, created, modified, date, column_name, order_num, pinned, archived, priority, due, color, cover, icon, content, excerpt, content_hash, file_mtime, file_size, cached_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 note.frontmatter.id,
                 note.file_path,
@@ -144,9 +335,18 @@ impl CacheDb {
                 note.frontmatter.date,
                 note.frontmatter.column,
                 note.frontmatter.order,
+                note.frontmatter.pinned,
+                note.frontmatter.archived,
+                note.frontmatter.priority.map(Priority::rank),
+                note.frontmatter.due,
+                note.frontmatter.color,
+                note.frontmatter.cover,
+                note.frontmatter.icon,
                 note.content,
+                make_excerpt(&note.content),
                 content_hash,
                 file_mtime,
+                file_size(&note.file_path),
                 now
             ],
         )
@@ -154,16 +354,13 @@ impl CacheDb {
 
         // Update tags
         self.update_note_tags_internal_tx(
-            &tx,
+            tx,
             &note.frontmatter.id,
             &note.frontmatter.tags,
             inline_tags,
         )?;
-
-        tx.commit()
-            .map_err(|e| format!("Failed to commit cache transaction: {}", e))?;
-
-        Ok(())
+        replace_note_aliases_tx(tx, &note.frontmatter.id, &note.frontmatter.aliases)?;
+        replace_note_properties_tx(tx, &note.frontmatter.id, &note.frontmatter.extra)
     }
 
     fn update_note_tags_internal_tx(
@@ -210,6 +407,40 @@ impl CacheDb {
         Ok(())
     }
 
+    /// Point a cached note at the path its file was renamed to, keeping its
+    /// tags, properties and column history. Returns false if `old_path` was
+    /// not cached.
+    pub fn rename_note_path(
+        &self,
+        old_path: &str,
+        new_path: &str,
+        file_mtime: i64,
+    ) -> Result<bool, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "DELETE FROM notes WHERE file_path = ?1 AND ?1 != ?2",
+            params![new_path, old_path],
+        )
+        .map_err(|e| format!("Failed to move cached note: {}", e))?;
+        let moved = conn
+            .execute(
+                "UPDATE notes SET file_path = ?, file_mtime = ?, file_size = ?, cached_at = ?
+                 WHERE file_path = ?",
+                params![
+                    new_path,
+                    file_mtime,
+                    file_size(new_path),
+                    Utc::now().timestamp(),
+                    old_path
+                ],
+            )
+            .map_err(|e| format!("Failed to move cached note: {}", e))?;
+        Ok(moved > 0)
+    }
+
     /// Remove a note from cache by file path
     pub fn remove_note(&self, file_path: &str) -> Result<(), String> {
         let conn = self
@@ -250,6 +481,30 @@ impl CacheDb {
         Ok(())
     }
 
+    /// Column placement of every cached note
+    pub fn note_placements(&self) -> Result<Vec<NotePlacement>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT file_path, column_name, order_num, archived FROM notes")
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        let placements = stmt
+            .query_map([], |row| {
+                Ok(NotePlacement {
+                    file_path: row.get(0)?,
+                    column: row.get(1)?,
+                    order: row.get(2)?,
+                    archived: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query notes: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read notes: {}", e))?;
+        Ok(placements)
+    }
+
     /// Get all cached notes
     pub fn get_all_notes(&self) -> Result<Vec<CachedNote>, String> {
         let conn = self
@@ -259,12 +514,13 @@ impl CacheDb {
 
         let mut stmt = conn
             .prepare(
-                "SELECT id, file_path, title, created, modified, date, column_name, order_num, content
+                "SELECT id, file_path, title, created, modified, date, column_name, order_num, content,
+                        excerpt, pinned, priority, due, color, cover, icon, archived
                  FROM notes",
             )
             .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
-        let notes: Vec<Note> = stmt
+        let notes: Vec<(Note, String)> = stmt
             .query_map([], |row| {
                 let id: String = row.get(0)?;
                 let file_path: String = row.get(1)?;
@@ -275,8 +531,16 @@ impl CacheDb {
                 let column: String = row.get(6)?;
                 let order: i32 = row.get(7)?;
                 let content: String = row.get(8)?;
-
-                Ok(Note {
+                let excerpt: String = row.get(9)?;
+                let pinned: bool = row.get(10)?;
+                let priority: Option<i64> = row.get(11)?;
+                let due: Option<String> = row.get(12)?;
+                let color: Option<String> = row.get(13)?;
+                let cover: Option<String> = row.get(14)?;
+                let icon: Option<String> = row.get(15)?;
+                let archived: bool = row.get(16)?;
+
+                let note = Note {
                     frontmatter: NoteFrontmatter {
                         id,
                         title,
@@ -287,13 +551,24 @@ impl CacheDb {
                             .map(|dt| dt.with_timezone(&Utc))
                             .unwrap_or_else(|_| Utc::now()),
                         date,
+                        due,
                         column,
                         tags: Vec::new(),
                         order,
+                        pinned,
+                        archived,
+                        priority: priority.and_then(Priority::from_rank),
+                        color,
+                        cover,
+                        icon,
+                        aliases: Vec::new(),
+                        extra: serde_yaml::Mapping::new(),
+                        format: FrontmatterFormat::Yaml,
                     },
                     content,
                     file_path,
-                })
+                };
+                Ok((note, excerpt))
             })
             .map_err(|e| format!("Failed to query notes: {}", e))?
             .filter_map(|r| r.ok())
@@ -303,7 +578,7 @@ impl CacheDb {
 
         // Get tags for each note (keep lock held to avoid re-acquisition per note)
         let mut result = Vec::new();
-        for mut note in notes {
+        for (mut note, excerpt) in notes {
             // Get frontmatter tags
             let mut frontmatter_stmt = conn
                 .prepare(
@@ -320,6 +595,8 @@ impl CacheDb {
                 .collect();
 
             note.frontmatter.tags = frontmatter_tags;
+            note.frontmatter.aliases = load_note_aliases(&conn, &note.frontmatter.id)?;
+            note.frontmatter.extra = load_note_properties(&conn, &note.frontmatter.id)?;
 
             // Get inline tags
             let mut inline_stmt = conn
@@ -336,9 +613,116 @@ impl CacheDb {
                 .filter_map(|r| r.ok())
                 .collect();
 
-            result.push(CachedNote { note, inline_tags });
+            result.push(CachedNote {
+                note,
+                inline_tags,
+                excerpt,
+            });
         }
 
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::parse_note_content;
+    use std::path::Path;
+
+    fn note(file_path: &str, id: &str, extra: &str) -> Note {
+        let text = format!(
+            "---\nid: {id}\ntitle: {id}\ncolumn: todo\n{extra}---\n\nFirst line of {id} #inline"
+        );
+        parse_note_content(&text, Path::new(file_path)).unwrap()
+    }
+
+    #[test]
+    fn round_trips_notes_with_tags_properties_and_aliases() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        let a = note(
+            "/v/a.md",
+            "a",
+            "tags: [work]\naliases: [Alpha]\nsprint: 4\nowner: ana\n",
+        );
+        cache
+            .upsert_note(&a, "hash-a", 10, &["inline".to_string()])
+            .unwrap();
+
+        let cached = cache.get_note("/v/a.md").unwrap().unwrap();
+        assert_eq!(cached.note.frontmatter.tags, ["work"]);
+        assert_eq!(cached.note.frontmatter.aliases, ["Alpha"]);
+        assert_eq!(cached.inline_tags, ["inline"]);
+        let keys: Vec<&str> = cached
+            .note
+            .frontmatter
+            .extra
+            .keys()
+            .filter_map(|k| k.as_str())
+            .collect();
+        assert_eq!(keys, ["sprint", "owner"]);
+        assert!(cached.excerpt.starts_with("First line of a"));
+        assert_eq!(
+            cache.get_indexed_state("/v/a.md").unwrap(),
+            Some(("hash-a".to_string(), 10))
+        );
+        assert!(cache.get_note("/v/missing.md").unwrap().is_none());
+    }
+
+    #[test]
+    fn renames_and_prunes_cached_notes() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        for id in ["a", "b", "c"] {
+            let path = format!("/v/{id}.md");
+            cache
+                .upsert_note(&note(&path, id, "tags: [work]\n"), id, 0, &[])
+                .unwrap();
+        }
+
+        assert!(cache
+            .rename_note_path("/v/a.md", "/v/done/a.md", 5)
+            .unwrap());
+        assert!(!cache.rename_note_path("/v/a.md", "/v/x.md", 5).unwrap());
+        let moved = cache.get_note("/v/done/a.md").unwrap().unwrap();
+        assert_eq!(moved.note.frontmatter.tags, ["work"]);
+        assert_eq!(moved.note.file_path, "/v/done/a.md");
+
+        cache.remove_note("/v/b.md").unwrap();
+        let keep: HashSet<String> = ["/v/done/a.md".to_string()].into();
+        cache.remove_notes_not_in(&keep).unwrap();
+        let paths: Vec<String> = cache
+            .note_placements()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.file_path)
+            .collect();
+        assert_eq!(paths, ["/v/done/a.md"]);
+        assert_eq!(cache.get_all_notes().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn needs_update_compares_hashes_when_mtimes_match() {
+        let dir = std::env::temp_dir().join(format!("noteban-queries-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("a.md");
+        let file_path = file.to_string_lossy().to_string();
+        fs::write(&file, "one").unwrap();
+        let cache = CacheDb::in_memory("test").unwrap();
+        assert!(cache.needs_update(&file_path, 10));
+
+        cache
+            .upsert_note(
+                &note(&file_path, "a", ""),
+                &compute_content_hash("one"),
+                10,
+                &[],
+            )
+            .unwrap();
+        assert!(!cache.needs_update(&file_path, 10));
+        assert!(cache.needs_update(&file_path, 11));
+        // Edited without the mtime moving
+        fs::write(&file, "three").unwrap();
+        assert!(cache.needs_update(&file_path, 10));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,21 @@
+/// Bump when cached note rows need rebuilding after a schema change; existing
+/// rows are dropped and re-parsed from disk on the next scan
+pub const SCHEMA_VERSION: &str = "13";
+
+/// Columns added to `notes` after it was first created, with their
+/// definitions; caches from before get them when the schema version changes
+pub const ADDED_NOTE_COLUMNS: &[(&str, &str)] = &[
+    ("excerpt", "TEXT NOT NULL DEFAULT ''"),
+    ("file_size", "INTEGER"),
+    ("pinned", "INTEGER NOT NULL DEFAULT 0"),
+    ("priority", "INTEGER"),
+    ("due", "TEXT"),
+    ("color", "TEXT"),
+    ("cover", "TEXT"),
+    ("icon", "TEXT"),
+    ("archived", "INTEGER NOT NULL DEFAULT 0"),
+];
+
 pub const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS notes (
     id TEXT PRIMARY KEY,
@@ -8,9 +26,18 @@ CREATE TABLE IF NOT EXISTS notes (
     date TEXT,
     column_name TEXT NOT NULL,
     order_num INTEGER DEFAULT 0,
+    pinned INTEGER NOT NULL DEFAULT 0,
+    archived INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER,
+    due TEXT,
+    color TEXT,
+    cover TEXT,
+    icon TEXT,
     content TEXT NOT NULL,
+    excerpt TEXT NOT NULL DEFAULT '',
     content_hash TEXT NOT NULL,
     file_mtime INTEGER NOT NULL,
+    file_size INTEGER,
     cached_at INTEGER NOT NULL
 );
 
@@ -28,10 +55,27 @@ CREATE TABLE IF NOT EXISTS note_tags (
     FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS note_properties (
+    note_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    sort_value,
+    PRIMARY KEY (note_id, key),
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS note_aliases (
+    note_id TEXT NOT NULL,
+    alias TEXT NOT NULL,
+    PRIMARY KEY (note_id, alias),
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
 CREATE INDEX IF NOT EXISTS idx_notes_file_path ON notes(file_path);
 CREATE INDEX IF NOT EXISTS idx_notes_column ON notes(column_name);
 CREATE INDEX IF NOT EXISTS idx_note_tags_note ON note_tags(note_id);
 CREATE INDEX IF NOT EXISTS idx_note_tags_tag ON note_tags(tag_id);
+CREATE INDEX IF NOT EXISTS idx_note_aliases_alias ON note_aliases(alias COLLATE NOCASE);
 
 CREATE TABLE IF NOT EXISTS sync_files (
     relative_path TEXT PRIMARY KEY,
@@ -44,8 +88,96 @@ CREATE TABLE IF NOT EXISTS sync_files (
     synced_at INTEGER NOT NULL
 );
 
+-- Content of markdown files as last synced, the base of three-way merges
+CREATE TABLE IF NOT EXISTS sync_bases (
+    relative_path TEXT PRIMARY KEY,
+    hash TEXT NOT NULL,
+    content BLOB NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS sync_state (
     key TEXT PRIMARY KEY,
     value TEXT NOT NULL
 );
+
+CREATE TABLE IF NOT EXISTS cache_meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS storage_files (
+    file_path TEXT PRIMARY KEY,
+    note_path TEXT,
+    size INTEGER NOT NULL,
+    is_attachment INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_files_note ON storage_files(note_path);
+
+CREATE TABLE IF NOT EXISTS conflict_files (
+    file_path TEXT PRIMARY KEY,
+    original_path TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    source TEXT,
+    detected_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS readonly_mounts (
+    name TEXT PRIMARY KEY,
+    path TEXT UNIQUE NOT NULL,
+    added_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key TEXT PRIMARY KEY,
+    note_id TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS column_transitions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id TEXT NOT NULL,
+    from_column TEXT,
+    to_column TEXT NOT NULL,
+    at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_column_transitions_note ON column_transitions(note_id);
+CREATE INDEX IF NOT EXISTS idx_column_transitions_at ON column_transitions(at);
+
+CREATE TABLE IF NOT EXISTS saved_views (
+    name TEXT PRIMARY KEY,
+    sort TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS macros (
+    name TEXT PRIMARY KEY,
+    definition TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Append-only record of note and folder operations
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    at TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    path TEXT NOT NULL,
+    target_path TEXT,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_path ON audit_log(path);
+
+-- Vault folders, kept in step by the scanner and folder commands
+CREATE TABLE IF NOT EXISTS folders (
+    path TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    relative_path TEXT NOT NULL,
+    parent_path TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_folders_parent ON folders(parent_path);
 "#;
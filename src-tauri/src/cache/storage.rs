@@ -0,0 +1,103 @@
+use super::db::CacheDb;
+use rusqlite::params;
+
+#[derive(Debug, Clone)]
+pub struct StorageFileRecord {
+    pub file_path: String,
+    pub note_path: Option<String>,
+    pub size: i64,
+    pub is_attachment: bool,
+}
+
+impl CacheDb {
+    /// Replace the stored vault scan with a fresh set of file records
+    pub fn replace_storage_files(&self, records: &[StorageFileRecord]) -> Result<(), String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        tx.execute("DELETE FROM storage_files", [])
+            .map_err(|e| format!("Failed to clear storage scan: {}", e))?;
+
+        for record in records {
+            tx.execute(
+                "INSERT OR REPLACE INTO storage_files (file_path, note_path, size, is_attachment)
+                 VALUES (?, ?, ?, ?)",
+                params![
+                    record.file_path,
+                    record.note_path,
+                    record.size,
+                    record.is_attachment
+                ],
+            )
+            .map_err(|e| format!("Failed to cache storage entry: {}", e))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit storage scan: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Record a single file changed since the last scan
+    pub fn upsert_storage_file(&self, record: &StorageFileRecord) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO storage_files (file_path, note_path, size, is_attachment)
+             VALUES (?, ?, ?, ?)",
+            params![
+                record.file_path,
+                record.note_path,
+                record.size,
+                record.is_attachment
+            ],
+        )
+        .map_err(|e| format!("Failed to cache storage entry: {}", e))?;
+        Ok(())
+    }
+
+    pub fn remove_storage_file(&self, file_path: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute("DELETE FROM storage_files WHERE file_path = ?", [file_path])
+            .map_err(|e| format!("Failed to remove storage entry: {}", e))?;
+        Ok(())
+    }
+
+    /// Get every file record from the last vault scan
+    pub fn get_storage_files(&self) -> Result<Vec<StorageFileRecord>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT file_path, note_path, size, is_attachment FROM storage_files")
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let records = stmt
+            .query_map([], |row| {
+                Ok(StorageFileRecord {
+                    file_path: row.get(0)?,
+                    note_path: row.get(1)?,
+                    size: row.get(2)?,
+                    is_attachment: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query storage scan: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(records)
+    }
+}
@@ -13,6 +13,19 @@ pub struct SyncFileRecord {
     pub synced_at: i64,
 }
 
+fn record_from_row(row: &rusqlite::Row) -> rusqlite::Result<SyncFileRecord> {
+    Ok(SyncFileRecord {
+        relative_path: row.get(0)?,
+        local_hash: row.get(1)?,
+        remote_etag: row.get(2)?,
+        last_synced_hash: row.get(3)?,
+        local_mtime: row.get(4)?,
+        remote_mtime: row.get(5)?,
+        remote_size: row.get(6)?,
+        synced_at: row.get(7)?,
+    })
+}
+
 impl CacheDb {
     pub fn get_sync_record(&self, relative_path: &str) -> Result<Option<SyncFileRecord>, String> {
         let conn = self
@@ -24,23 +37,32 @@ impl CacheDb {
             "SELECT relative_path, local_hash, remote_etag, last_synced_hash, local_mtime, remote_mtime, remote_size, synced_at
              FROM sync_files WHERE relative_path = ?",
             [relative_path],
-            |row| {
-                Ok(SyncFileRecord {
-                    relative_path: row.get(0)?,
-                    local_hash: row.get(1)?,
-                    remote_etag: row.get(2)?,
-                    last_synced_hash: row.get(3)?,
-                    local_mtime: row.get(4)?,
-                    remote_mtime: row.get(5)?,
-                    remote_size: row.get(6)?,
-                    synced_at: row.get(7)?,
-                })
-            },
+            record_from_row,
         )
         .optional()
         .map_err(|e| format!("Failed to read sync record: {}", e))
     }
 
+    pub fn list_sync_records(&self) -> Result<Vec<SyncFileRecord>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT relative_path, local_hash, remote_etag, last_synced_hash, local_mtime, remote_mtime, remote_size, synced_at
+                 FROM sync_files",
+            )
+            .map_err(|e| format!("Failed to prepare sync record query: {}", e))?;
+        let records = stmt
+            .query_map([], record_from_row)
+            .map_err(|e| format!("Failed to read sync records: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read sync records: {}", e))?;
+        Ok(records)
+    }
+
     pub fn upsert_sync_record(&self, record: &SyncFileRecord) -> Result<(), String> {
         let conn = self
             .conn
@@ -78,6 +100,78 @@ impl CacheDb {
             [relative_path],
         )
         .map_err(|e| format!("Failed to remove sync record: {}", e))?;
+        conn.execute(
+            "DELETE FROM sync_bases WHERE relative_path = ?",
+            [relative_path],
+        )
+        .map_err(|e| format!("Failed to remove sync base: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Drop the stored content of a file without forgetting that it synced
+    pub fn remove_sync_base(&self, relative_path: &str) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        conn.execute(
+            "DELETE FROM sync_bases WHERE relative_path = ?",
+            [relative_path],
+        )
+        .map_err(|e| format!("Failed to remove sync base: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Hash and content of a file as of its last sync
+    pub fn get_sync_base(&self, relative_path: &str) -> Result<Option<(String, Vec<u8>)>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        conn.query_row(
+            "SELECT hash, content FROM sync_bases WHERE relative_path = ?",
+            [relative_path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read sync base: {}", e))
+    }
+
+    pub fn get_sync_base_hash(&self, relative_path: &str) -> Result<Option<String>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        conn.query_row(
+            "SELECT hash FROM sync_bases WHERE relative_path = ?",
+            [relative_path],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read sync base: {}", e))
+    }
+
+    pub fn set_sync_base(
+        &self,
+        relative_path: &str,
+        hash: &str,
+        content: &[u8],
+    ) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_bases (relative_path, hash, content) VALUES (?, ?, ?)",
+            params![relative_path, hash, content],
+        )
+        .map_err(|e| format!("Failed to write sync base: {}", e))?;
 
         Ok(())
     }
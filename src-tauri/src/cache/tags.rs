@@ -0,0 +1,71 @@
+use super::db::CacheDb;
+
+/// Number of notes carrying both tags of a pair (`tag_a` sorts before `tag_b`)
+#[derive(Debug, Clone)]
+pub struct TagPairCount {
+    pub tag_a: String,
+    pub tag_b: String,
+    pub count: i64,
+}
+
+impl CacheDb {
+    /// Number of notes per tag, counting a note once even if the tag appears in
+    /// both frontmatter and inline
+    pub fn get_tag_note_counts(&self) -> Result<Vec<(String, i64)>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.name, COUNT(DISTINCT nt.note_id) FROM tags t
+                 JOIN note_tags nt ON t.id = nt.tag_id
+                 GROUP BY t.name
+                 ORDER BY t.name",
+            )
+            .map_err(|e| format!("Failed to prepare tag counts query: {}", e))?;
+
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| format!("Failed to query tag counts: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(counts)
+    }
+
+    /// Co-occurrence counts for every pair of tags that share at least one note
+    pub fn get_tag_cooccurrence(&self) -> Result<Vec<TagPairCount>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT a.name, b.name, COUNT(DISTINCT x.note_id) FROM note_tags x
+                 JOIN note_tags y ON x.note_id = y.note_id
+                 JOIN tags a ON a.id = x.tag_id
+                 JOIN tags b ON b.id = y.tag_id
+                 WHERE a.name < b.name
+                 GROUP BY a.name, b.name
+                 ORDER BY a.name, b.name",
+            )
+            .map_err(|e| format!("Failed to prepare tag co-occurrence query: {}", e))?;
+
+        let pairs = stmt
+            .query_map([], |row| {
+                Ok(TagPairCount {
+                    tag_a: row.get(0)?,
+                    tag_b: row.get(1)?,
+                    count: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query tag co-occurrence: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(pairs)
+    }
+}
@@ -0,0 +1,324 @@
+use super::db::CacheDb;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
+
+/// When a note entered its current column, with its current location
+#[derive(Debug, Clone)]
+pub struct ColumnEntry {
+    pub note_id: String,
+    pub file_path: String,
+    pub title: String,
+    pub column: String,
+    pub entered_at: DateTime<Utc>,
+}
+
+/// Transition times are stored as fixed-width UTC timestamps, so comparing
+/// them as text orders them in time
+fn transition_time(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Rewrite transition times stored before they were normalized
+pub(super) fn normalize_transition_times(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "UPDATE column_transitions SET at = strftime('%Y-%m-%dT%H:%M:%fZ', at)
+         WHERE strftime('%Y-%m-%dT%H:%M:%fZ', at) IS NOT NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to normalize column transitions: {}", e))?;
+    Ok(())
+}
+
+/// Record a column transition if `column` differs from the note's last known
+/// column. Notes seen for the first time get no transition; their time in the
+/// column is measured from their creation date instead.
+pub(super) fn record_column_transition_tx(
+    tx: &Transaction<'_>,
+    note_id: &str,
+    column: &str,
+) -> Result<(), String> {
+    let last_transition: Option<String> = tx
+        .query_row(
+            "SELECT to_column FROM column_transitions WHERE note_id = ?
+             ORDER BY at DESC, id DESC LIMIT 1",
+            [note_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read column transitions: {}", e))?;
+
+    let previous = match last_transition {
+        Some(column) => Some(column),
+        None => tx
+            .query_row(
+                "SELECT column_name FROM notes WHERE id = ?",
+                [note_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read cached column: {}", e))?,
+    };
+
+    if let Some(previous) = previous {
+        if previous != column {
+            tx.execute(
+                "INSERT INTO column_transitions (note_id, from_column, to_column, at)
+                 VALUES (?, ?, ?, ?)",
+                params![note_id, previous, column, transition_time(Utc::now())],
+            )
+            .map_err(|e| format!("Failed to record column transition: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A recorded move of a note between columns
+#[derive(Debug, Clone)]
+pub struct ColumnTransition {
+    pub note_id: String,
+    pub from_column: String,
+    pub to_column: String,
+    pub at: DateTime<Utc>,
+}
+
+impl CacheDb {
+    /// Transitions recorded in `[from, to)`, oldest first
+    pub fn get_transitions_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ColumnTransition>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT note_id, from_column, to_column, at FROM column_transitions
+                 WHERE at >= ?1 AND at < ?2
+                 ORDER BY at, id",
+            )
+            .map_err(|e| format!("Failed to prepare transitions query: {}", e))?;
+
+        let transitions = stmt
+            .query_map(params![transition_time(from), transition_time(to)], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query transitions: {}", e))?
+            .filter_map(|r| r.ok())
+            .filter_map(|(note_id, from_column, to_column, at)| {
+                let at = DateTime::parse_from_rfc3339(&at).ok()?.with_timezone(&Utc);
+                Some(ColumnTransition {
+                    note_id,
+                    from_column,
+                    to_column,
+                    at,
+                })
+            })
+            .collect();
+
+        Ok(transitions)
+    }
+
+    /// When each cached note entered its current column
+    pub fn get_column_entries(&self) -> Result<Vec<ColumnEntry>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT n.id, n.file_path, n.title, n.column_name,
+                        COALESCE(
+                            (SELECT MAX(t.at) FROM column_transitions t
+                             WHERE t.note_id = n.id AND t.to_column = n.column_name),
+                            n.created
+                        )
+                 FROM notes n",
+            )
+            .map_err(|e| format!("Failed to prepare column entries query: {}", e))?;
+
+        let entries = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query column entries: {}", e))?
+            .filter_map(|r| r.ok())
+            .filter_map(|(note_id, file_path, title, column, entered_at)| {
+                let entered_at = DateTime::parse_from_rfc3339(&entered_at)
+                    .ok()?
+                    .with_timezone(&Utc);
+                Some(ColumnEntry {
+                    note_id,
+                    file_path,
+                    title,
+                    column,
+                    entered_at,
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Whole days each cached note has spent in its current column, by note id
+    pub fn get_days_in_column(&self) -> Result<HashMap<String, i64>, String> {
+        let now = Utc::now();
+        Ok(self
+            .get_column_entries()?
+            .into_iter()
+            .map(|entry| (entry.note_id, days_since(entry.entered_at, now)))
+            .collect())
+    }
+
+    /// Rename a column in the recorded transitions of `note_ids`, as if it
+    /// always had the new name. Moves between the old and the new name are
+    /// dropped.
+    pub fn rename_column_transitions(
+        &self,
+        from: &str,
+        to: &str,
+        note_ids: &[String],
+    ) -> Result<(), String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        for note_id in note_ids {
+            for sql in [
+                "UPDATE column_transitions SET from_column = ?2 WHERE note_id = ?3 AND from_column = ?1",
+                "UPDATE column_transitions SET to_column = ?2 WHERE note_id = ?3 AND to_column = ?1",
+            ] {
+                tx.execute(sql, params![from, to, note_id])
+                    .map_err(|e| format!("Failed to rename column transitions: {}", e))?;
+            }
+            tx.execute(
+                "DELETE FROM column_transitions WHERE note_id = ? AND from_column = to_column",
+                [note_id],
+            )
+            .map_err(|e| format!("Failed to rename column transitions: {}", e))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit cache transaction: {}", e))
+    }
+
+    /// Drop transitions of notes that are no longer cached
+    pub fn prune_column_transitions(&self) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        conn.execute(
+            "DELETE FROM column_transitions WHERE note_id NOT IN (SELECT id FROM notes)",
+            [],
+        )
+        .map_err(|e| format!("Failed to prune column transitions: {}", e))?;
+        Ok(())
+    }
+}
+
+pub fn days_since(since: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    now.signed_duration_since(since).num_days().max(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn insert_note(tx: &Transaction<'_>, column: &str) {
+        record_column_transition_tx(tx, "n1", column).unwrap();
+        tx.execute(
+            "INSERT OR REPLACE INTO notes (id, file_path, title, created, modified, column_name,
+             content, content_hash, file_mtime, cached_at)
+             VALUES ('n1', '/n1.md', 'N', '2024-01-01T00:00:00+00:00', '', ?, '', '', 0, 0)",
+            [column],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn records_only_column_changes() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::cache::schema::SCHEMA).unwrap();
+        let tx = conn.transaction().unwrap();
+        insert_note(&tx, "todo");
+        insert_note(&tx, "todo");
+        insert_note(&tx, "doing");
+        insert_note(&tx, "doing");
+        insert_note(&tx, "done");
+
+        let transitions: Vec<(Option<String>, String)> = tx
+            .prepare("SELECT from_column, to_column FROM column_transitions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                (Some("todo".to_string()), "doing".to_string()),
+                (Some("doing".to_string()), "done".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_transitions_by_normalized_time() {
+        let cache = CacheDb::in_memory("test").unwrap();
+        {
+            let conn = cache.conn.lock().unwrap();
+            for (note_id, at) in [
+                ("early", "2024-03-01T23:30:00+00:00"),
+                // Stored with an offset before times were normalized: 2024-03-01T23:30Z
+                ("offset", "2024-03-02T01:30:00.5+02:00"),
+                ("inside", "2024-03-02T10:00:00.000Z"),
+                ("end", "2024-03-03T00:00:00.000Z"),
+            ] {
+                conn.execute(
+                    "INSERT INTO column_transitions (note_id, from_column, to_column, at)
+                     VALUES (?, 'todo', 'done', ?)",
+                    [note_id, at],
+                )
+                .unwrap();
+            }
+            normalize_transition_times(&conn).unwrap();
+        }
+
+        let day = |d: u32| {
+            DateTime::parse_from_rfc3339(&format!("2024-03-{:02}T00:00:00Z", d))
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let ids = |from, to| -> Vec<String> {
+            cache
+                .get_transitions_between(from, to)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.note_id)
+                .collect()
+        };
+        assert_eq!(ids(day(2), day(3)), ["inside"]);
+        assert_eq!(ids(day(1), day(3)), ["early", "offset", "inside"]);
+        assert_eq!(ids(day(1), day(4)).last().unwrap(), "end");
+    }
+}
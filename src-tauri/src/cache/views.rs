@@ -0,0 +1,262 @@
+use super::db::CacheDb;
+use chrono::Utc;
+use rusqlite::{params, params_from_iter, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Prefix selecting a custom frontmatter property as sort field, e.g. `prop:priority`
+pub const PROPERTY_FIELD_PREFIX: &str = "prop:";
+const MAX_SORT_KEYS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortKey {
+    /// Built-in field, computed field or `prop:<name>`
+    pub field: String,
+    #[serde(default)]
+    pub descending: bool,
+    /// Missing values sort last in either direction unless this is set
+    #[serde(default)]
+    pub nulls_first: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    pub sort: Vec<SortKey>,
+    pub updated_at: String,
+}
+
+fn field_expression(field: &str, params: &mut Vec<String>) -> Result<String, String> {
+    if let Some(property) = field.strip_prefix(PROPERTY_FIELD_PREFIX) {
+        if property.is_empty() {
+            return Err("Property sort field needs a property name".to_string());
+        }
+        params.push(property.to_string());
+        return Ok(format!(
+            "(SELECT p.sort_value FROM note_properties p WHERE p.note_id = n.id AND p.key = ?{})",
+            params.len()
+        ));
+    }
+
+    let expression = match field {
+        "title" => "n.title COLLATE NOCASE",
+        "created" => "n.created",
+        "modified" => "n.modified",
+        "date" => "NULLIF(n.date, '')",
+        "due" => "n.due",
+        "column" => "n.column_name",
+        "order" => "n.order_num",
+        "pinned" => "n.pinned",
+        "archived" => "n.archived",
+        "priority" => "n.priority",
+        "color" => "n.color",
+        "file_path" => "n.file_path",
+        // Computed fields
+        "tag_count" => {
+            "(SELECT COUNT(DISTINCT nt.tag_id) FROM note_tags nt WHERE nt.note_id = n.id)"
+        }
+        "content_length" => "length(n.content)",
+        other => return Err(format!("Unknown sort field: {}", other)),
+    };
+    Ok(expression.to_string())
+}
+
+/// Compile sort keys into an `ORDER BY` clause and its bound parameters.
+/// The file path is always appended as a final tie-breaker so the order is stable.
+pub fn compile_order_by(keys: &[SortKey]) -> Result<(String, Vec<String>), String> {
+    if keys.len() > MAX_SORT_KEYS {
+        return Err(format!("At most {} sort keys are supported", MAX_SORT_KEYS));
+    }
+
+    let mut params = Vec::new();
+    let mut terms = Vec::new();
+    for key in keys {
+        let expression = field_expression(&key.field, &mut params)?;
+        terms.push(format!(
+            "{} {} NULLS {}",
+            expression,
+            if key.descending { "DESC" } else { "ASC" },
+            if key.nulls_first { "FIRST" } else { "LAST" }
+        ));
+    }
+    terms.push("n.file_path ASC".to_string());
+
+    Ok((format!("ORDER BY {}", terms.join(", ")), params))
+}
+
+impl CacheDb {
+    /// File paths of all cached notes in the order described by `keys`
+    pub fn get_sorted_note_paths(&self, keys: &[SortKey]) -> Result<Vec<String>, String> {
+        let (order_by, order_params) = compile_order_by(keys)?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare(&format!("SELECT n.file_path FROM notes n {}", order_by))
+            .map_err(|e| format!("Failed to prepare sorted notes query: {}", e))?;
+
+        let paths = stmt
+            .query_map(params_from_iter(order_params.iter()), |row| row.get(0))
+            .map_err(|e| format!("Failed to query sorted notes: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(paths)
+    }
+
+    pub fn save_view(&self, name: &str, sort: &[SortKey]) -> Result<SavedView, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let encoded =
+            serde_json::to_string(sort).map_err(|e| format!("Failed to encode view: {}", e))?;
+        let updated_at = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO saved_views (name, sort, updated_at) VALUES (?, ?, ?)",
+            params![name, encoded, updated_at],
+        )
+        .map_err(|e| format!("Failed to save view: {}", e))?;
+
+        Ok(SavedView {
+            name: name.to_string(),
+            sort: sort.to_vec(),
+            updated_at,
+        })
+    }
+
+    pub fn get_view(&self, name: &str) -> Result<Option<SavedView>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let row: Option<(String, String)> = conn
+            .query_row(
+                "SELECT sort, updated_at FROM saved_views WHERE name = ?",
+                [name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read view: {}", e))?;
+
+        match row {
+            Some((sort, updated_at)) => Ok(Some(SavedView {
+                name: name.to_string(),
+                sort: serde_json::from_str(&sort)
+                    .map_err(|e| format!("Failed to decode view: {}", e))?,
+                updated_at,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_views(&self) -> Result<Vec<SavedView>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT name, sort, updated_at FROM saved_views ORDER BY name")
+            .map_err(|e| format!("Failed to prepare views query: {}", e))?;
+
+        let views = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query views: {}", e))?
+            .filter_map(|r| r.ok())
+            .filter_map(|(name, sort, updated_at)| {
+                Some(SavedView {
+                    name,
+                    sort: serde_json::from_str(&sort).ok()?,
+                    updated_at,
+                })
+            })
+            .collect();
+
+        Ok(views)
+    }
+
+    /// Returns false when no view with that name existed
+    pub fn delete_view(&self, name: &str) -> Result<bool, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Cache lock error".to_string())?;
+        let removed = conn
+            .execute("DELETE FROM saved_views WHERE name = ?", [name])
+            .map_err(|e| format!("Failed to delete view: {}", e))?;
+        Ok(removed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(field: &str, descending: bool) -> SortKey {
+        SortKey {
+            field: field.to_string(),
+            descending,
+            nulls_first: false,
+        }
+    }
+
+    #[test]
+    fn compiles_multi_key_sorts() {
+        let (order_by, params) = compile_order_by(&[
+            key("prop:priority", true),
+            key("date", false),
+            key("title", false),
+        ])
+        .unwrap();
+        assert_eq!(params, vec!["priority"]);
+        assert!(order_by.starts_with("ORDER BY (SELECT p.sort_value"));
+        assert!(order_by.contains("p.key = ?1) DESC NULLS LAST"));
+        assert!(order_by.contains("NULLIF(n.date, '') ASC NULLS LAST"));
+        assert!(order_by.ends_with("n.title COLLATE NOCASE ASC NULLS LAST, n.file_path ASC"));
+
+        assert!(compile_order_by(&[key("content; DROP TABLE notes", false)]).is_err());
+        assert!(compile_order_by(&[key("prop:", false)]).is_err());
+    }
+
+    #[test]
+    fn orders_by_properties_with_missing_values_last() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::cache::schema::SCHEMA).unwrap();
+        for (id, title) in [("a", "Alpha"), ("b", "Beta"), ("c", "Gamma")] {
+            conn.execute(
+                "INSERT INTO notes (id, file_path, title, created, modified, column_name,
+                 content, content_hash, file_mtime, cached_at)
+                 VALUES (?1, ?1, ?2, '', '', 'todo', '', '', 0, 0)",
+                params![id, title],
+            )
+            .unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO note_properties VALUES ('a', 'priority', '1', 1.0);
+             INSERT INTO note_properties VALUES ('c', 'priority', '3', 3.0);",
+        )
+        .unwrap();
+
+        let (order_by, order_params) =
+            compile_order_by(&[key("prop:priority", true), key("title", false)]).unwrap();
+        let mut stmt = conn
+            .prepare(&format!("SELECT n.file_path FROM notes n {}", order_by))
+            .unwrap();
+        let paths: Vec<String> = stmt
+            .query_map(params_from_iter(order_params.iter()), |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(paths, vec!["c", "a", "b"]);
+    }
+}
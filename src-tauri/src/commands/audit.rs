@@ -0,0 +1,122 @@
+use crate::cache::audit::AuditEntry;
+use crate::lock_or_err;
+use crate::AppState;
+use std::cell::RefCell;
+use tauri::State;
+
+/// Actor recorded for operations no window asked for (schedulers, startup)
+const BACKEND_ACTOR: &str = "backend";
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+const MAX_AUDIT_LIMIT: usize = 5000;
+
+thread_local! {
+    /// Label of the window whose command is running on this thread
+    static INVOKING_WINDOW: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `label` as the actor of any operation it records. Commands
+/// are synchronous, so the whole command runs inside `f`.
+pub(crate) fn with_invoking_window<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    let previous = INVOKING_WINDOW.with(|window| window.replace(Some(label.to_string())));
+    let result = f();
+    INVOKING_WINDOW.with(|window| *window.borrow_mut() = previous);
+    result
+}
+
+pub(crate) fn current_actor() -> String {
+    INVOKING_WINDOW
+        .with(|window| window.borrow().clone())
+        .unwrap_or_else(|| BACKEND_ACTOR.to_string())
+}
+
+/// Append the outcome of a file operation to the audit log. Failing to log
+/// never fails the operation itself.
+pub(crate) fn record<T>(
+    state: &State<AppState>,
+    action: &str,
+    path: &str,
+    target_path: Option<&str>,
+    result: &Result<T, String>,
+) {
+    let Ok(cache_lock) = state.cache.lock() else {
+        return;
+    };
+    let Some(cache) = cache_lock.as_ref() else {
+        return;
+    };
+    if let Err(e) = cache.append_audit_entry(
+        &current_actor(),
+        action,
+        path,
+        target_path,
+        result.as_ref().err().map(String::as_str),
+    ) {
+        log::warn!("Failed to record {} in audit log: {}", action, e);
+    }
+}
+
+/// Recorded file operations, newest first. With `path`, only those that
+/// touched that note or folder.
+#[tauri::command]
+pub fn get_audit_log(
+    path: Option<String>,
+    limit: Option<usize>,
+    state: State<AppState>,
+) -> Result<Vec<AuditEntry>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    let limit = limit.unwrap_or(DEFAULT_AUDIT_LIMIT).min(MAX_AUDIT_LIMIT);
+    cache.get_audit_log(path.as_deref(), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_operations_to_the_invoking_window() {
+        assert_eq!(current_actor(), BACKEND_ACTOR);
+        with_invoking_window("main", || {
+            assert_eq!(current_actor(), "main");
+            with_invoking_window("quick-capture-1", || {
+                assert_eq!(current_actor(), "quick-capture-1");
+            });
+            assert_eq!(current_actor(), "main");
+        });
+        assert_eq!(current_actor(), BACKEND_ACTOR);
+    }
+
+    #[test]
+    fn records_locks_deletes_and_their_undo() {
+        use crate::commands::notes::{delete_note, set_note_locked};
+        use crate::commands::undo::undo_last_operation;
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        let note = vault.note("a.md", "a", "");
+        let notes_dir = vault.notes_dir();
+        set_note_locked(notes_dir.clone(), note.clone(), true, vault.state()).unwrap();
+        assert!(delete_note(notes_dir.clone(), note.clone(), None, vault.state()).is_err());
+        set_note_locked(notes_dir.clone(), note.clone(), false, vault.state()).unwrap();
+        delete_note(notes_dir, note.clone(), None, vault.state()).unwrap();
+        undo_last_operation(vault.state()).unwrap();
+        assert!(vault.exists("a.md"));
+
+        let log = get_audit_log(None, None, vault.state()).unwrap();
+        let actions: Vec<(&str, bool)> = log
+            .iter()
+            .map(|entry| (entry.action.as_str(), entry.error.is_none()))
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                ("restore", true),
+                ("delete", true),
+                ("update", true),
+                ("delete", false),
+                ("update", true)
+            ]
+        );
+        assert_eq!(log[0].target_path.as_deref(), Some(note.as_str()));
+    }
+}
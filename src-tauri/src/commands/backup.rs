@@ -0,0 +1,333 @@
+use crate::backup::{self, BackupInfo};
+use crate::commands::mounts::ensure_writable;
+use crate::commands::notes::{read_vault, record_write, scan_vault};
+use crate::commands::symlinks::symlink_allowlist;
+use crate::commands::watch;
+use crate::lock_or_err;
+use crate::logging;
+use crate::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+pub(crate) const BACKUP_CONFIG_KEY: &str = "backup_config";
+const BACKUP_LAST_AT_KEY: &str = "backup_last_at";
+/// How often the scheduler checks whether a backup is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(5 * 60);
+
+fn default_interval_hours() -> u32 {
+    24
+}
+
+fn default_keep() -> usize {
+    10
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Vault to back up on schedule; set from the frontend when enabling
+    #[serde(default)]
+    pub notes_dir: Option<String>,
+    /// Defaults to the profile's data directory when unset
+    #[serde(default)]
+    pub backup_dir: Option<String>,
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    /// Number of backups kept; older ones are deleted after each backup
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            notes_dir: None,
+            backup_dir: None,
+            interval_hours: default_interval_hours(),
+            keep: default_keep(),
+        }
+    }
+}
+
+/// Which part of a backup to restore
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Every file in the archive
+    Full,
+    /// Only these notes (paths inside the archive) and their attachments
+    Selected { notes: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub restored_files: usize,
+    pub restored_notes: usize,
+    /// Notes in the target directory after the restore
+    pub total_notes: usize,
+}
+
+/// Backup configuration, destination directory and time of the last backup
+/// for the active profile
+fn load_backup_settings(
+    state: &AppState,
+) -> Result<(BackupConfig, PathBuf, Option<DateTime<Utc>>), String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+
+    let config: BackupConfig = cache
+        .get_meta(BACKUP_CONFIG_KEY)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default();
+    let backup_dir = match &config.backup_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => backup::default_backup_dir(&cache.profile_id)?,
+    };
+    let last_at = cache
+        .get_meta(BACKUP_LAST_AT_KEY)?
+        .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+        .map(|dt| dt.with_timezone(&Utc));
+
+    Ok((config, backup_dir, last_at))
+}
+
+/// Zip the vault and rotate old backups. The cache lock is only held while
+/// reading and recording settings, never while archiving.
+fn run_backup(notes_dir: &str, state: &AppState) -> Result<BackupInfo, String> {
+    let vault = PathBuf::from(notes_dir);
+    if !vault.is_dir() {
+        return Err("Notes directory does not exist".to_string());
+    }
+    let (config, backup_dir, _) = load_backup_settings(state)?;
+
+    let info = backup::create_backup(&vault, &backup_dir)?;
+    backup::apply_rotation(&backup_dir, config.keep.max(1));
+
+    let cache_lock = lock_or_err(&state.cache)?;
+    if let Some(cache) = cache_lock.as_ref() {
+        if let Err(e) = cache.set_meta(BACKUP_LAST_AT_KEY, &info.created_at.to_rfc3339()) {
+            log::warn!("Failed to record backup time: {}", e);
+        }
+    }
+
+    Ok(info)
+}
+
+fn run_scheduled_backup(state: &AppState) {
+    let Ok((config, _, last_at)) = load_backup_settings(state) else {
+        return;
+    };
+    let Some(notes_dir) = config.notes_dir.filter(|_| config.enabled) else {
+        return;
+    };
+    let interval = ChronoDuration::hours(config.interval_hours.max(1) as i64);
+    if last_at.is_some_and(|last| Utc::now() - last < interval) {
+        return;
+    }
+
+    match run_backup(&notes_dir, state) {
+        Ok(info) => log::info!("Scheduled backup written to {}", logging::path(&info.path)),
+        Err(e) => log::warn!("Scheduled backup failed: {}", e),
+    }
+}
+
+/// Periodically back up the active profile's vault when scheduled backups are enabled
+pub fn start_backup_scheduler(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(SCHEDULER_TICK);
+        run_scheduled_backup(&app.state::<AppState>());
+    });
+}
+
+#[tauri::command]
+pub fn get_backup_config(state: State<AppState>) -> Result<BackupConfig, String> {
+    Ok(load_backup_settings(&state)?.0)
+}
+
+#[tauri::command]
+pub fn set_backup_config(
+    config: BackupConfig,
+    state: State<AppState>,
+) -> Result<BackupConfig, String> {
+    if config.interval_hours == 0 {
+        return Err("Backup interval must be at least one hour".to_string());
+    }
+    if config.keep == 0 {
+        return Err("At least one backup must be kept".to_string());
+    }
+    if let Some(dir) = &config.backup_dir {
+        if !PathBuf::from(dir).is_absolute() {
+            return Err("Backup directory must be an absolute path".to_string());
+        }
+    }
+    if config.enabled && config.notes_dir.is_none() {
+        return Err("Scheduled backups need a notes directory".to_string());
+    }
+
+    let encoded = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to encode backup config: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(BACKUP_CONFIG_KEY, &encoded)?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub fn backup_now(notes_dir: String, state: State<AppState>) -> Result<BackupInfo, String> {
+    run_backup(&notes_dir, &state)
+}
+
+#[tauri::command]
+pub fn list_backups(state: State<AppState>) -> Result<Vec<BackupInfo>, String> {
+    let (_, backup_dir, _) = load_backup_settings(&state)?;
+    Ok(backup::list_backups_in(&backup_dir))
+}
+
+fn is_note_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+/// Restore a backup into `target_dir`. The cache is rebuilt from it only
+/// when it is the open vault `notes_dir`; restoring into a side directory
+/// leaves the vault's cache and index alone.
+#[tauri::command]
+pub fn restore_backup(
+    archive_path: String,
+    notes_dir: String,
+    target_dir: String,
+    mode: RestoreMode,
+    state: State<AppState>,
+) -> Result<RestoreSummary, String> {
+    let archive = PathBuf::from(&archive_path);
+    if !archive.is_file() {
+        return Err("Backup archive does not exist".to_string());
+    }
+    let target = PathBuf::from(&target_dir);
+    if !target.is_absolute() {
+        return Err("Restore target must be an absolute path".to_string());
+    }
+    ensure_writable(&target, &state)?;
+    fs::create_dir_all(&target).map_err(|e| format!("Failed to create restore target: {}", e))?;
+
+    let notes = match &mode {
+        RestoreMode::Full => None,
+        RestoreMode::Selected { notes } if notes.is_empty() => {
+            return Err("No notes selected for restore".to_string())
+        }
+        RestoreMode::Selected { notes } => Some(notes.as_slice()),
+    };
+    let into_vault = target.canonicalize().ok() == Path::new(&notes_dir).canonicalize().ok();
+    if !into_vault {
+        // Folders inside the vault reach the cache through the watcher
+        let restored = backup::restore_backup_to(&archive, &target, notes)?;
+        let listing = read_vault(&target_dir, &symlink_allowlist(&state))?;
+        return Ok(RestoreSummary {
+            restored_files: restored.len(),
+            restored_notes: restored.iter().filter(|path| is_note_file(path)).count(),
+            total_notes: listing.notes.len(),
+        });
+    }
+
+    let _pause = watch::pause(&state);
+    let restored = backup::restore_backup_to(&archive, &target, notes)?;
+
+    let restored_notes: Vec<String> = restored
+        .iter()
+        .filter(|path| is_note_file(path))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    // The rebuild below picks these up; keep the watcher from echoing them
+    for path in &restored_notes {
+        record_write(path, &state);
+    }
+
+    if matches!(mode, RestoreMode::Full) {
+        let cache_lock = lock_or_err(&state.cache)?;
+        if let Some(cache) = cache_lock.as_ref() {
+            cache.invalidate_all()?;
+        }
+    }
+    let listing = scan_vault(&target_dir, &state, &mut |_| {})?;
+
+    Ok(RestoreSummary {
+        restored_files: restored.len(),
+        restored_notes: restored_notes.len(),
+        total_notes: listing.notes.len(),
+    })
+}
+
+/// Check a backup's integrity and test-restore a sample of its notes into a
+/// temporary directory, without touching the vault
+#[tauri::command]
+pub fn verify_backup(backup_path: String) -> Result<backup::BackupVerification, String> {
+    let archive = PathBuf::from(&backup_path);
+    if !archive.is_file() {
+        return Err("Backup archive does not exist".to_string());
+    }
+    backup::verify_backup_archive(&archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+
+    fn cached_paths(vault: &TestVault) -> Vec<String> {
+        let state = vault.state();
+        let cache_lock = state.cache.lock().unwrap();
+        let mut paths: Vec<String> = cache_lock
+            .as_ref()
+            .unwrap()
+            .get_all_notes()
+            .unwrap()
+            .into_iter()
+            .map(|cached| cached.note.file_path)
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn restores_into_side_directory_without_touching_the_cache() {
+        let vault = TestVault::new();
+        let plan = vault.note("plan.md", "plan", "");
+        scan_vault(&vault.notes_dir(), &vault.state(), &mut |_| {}).unwrap();
+
+        let source = TestVault::new();
+        source.note("old.md", "old", "");
+        let archive = backup::create_backup(&source.dir, &source.dir.join("backups")).unwrap();
+        let archive_path = archive.path;
+
+        let side = std::env::temp_dir().join(format!("noteban-side-{}", uuid::Uuid::new_v4()));
+        let summary = restore_backup(
+            archive_path.clone(),
+            vault.notes_dir(),
+            side.to_string_lossy().to_string(),
+            RestoreMode::Full,
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!((summary.restored_notes, summary.total_notes), (1, 1));
+        assert!(side.join("old.md").exists());
+        assert_eq!(cached_paths(&vault), vec![plan.clone()]);
+
+        let summary = restore_backup(
+            archive_path,
+            vault.notes_dir(),
+            vault.notes_dir(),
+            RestoreMode::Full,
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(summary.total_notes, 2);
+        assert_eq!(cached_paths(&vault), vec![vault.path("old.md"), plan]);
+        fs::remove_dir_all(&side).unwrap();
+    }
+}
@@ -0,0 +1,964 @@
+use crate::cache::queries::NotePlacement;
+use crate::cache::transitions::days_since;
+use crate::commands::notes::{
+    normalize_color, slugify_or_fallback, update_notes_frontmatter, NoteFrontmatter, NoteWithTags,
+};
+use crate::commands::{audit, broadcast, note_index};
+use crate::lock_or_err;
+use crate::vault_config::{
+    board_folders, board_root, folder_board_root, has_board, load_config, save_config, BoardColumn,
+    BoardConfig, BoardScope, WipPolicy, BOARD_FILE,
+};
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use tauri::State;
+use uuid::Uuid;
+
+/// Color of a new column when none is given
+const DEFAULT_COLUMN_COLOR: &str = "#6c7086";
+/// Sent when a card moves into a column over its soft WIP limit
+pub(crate) const WIP_EXCEEDED_EVENT: &str = "board://wip-exceeded";
+/// Gap left between the orders of neighbouring cards, so a moved card
+/// usually fits between two others without renumbering them
+const ORDER_STEP: i32 = 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleCard {
+    pub file_path: String,
+    pub title: String,
+    pub column: String,
+    pub entered_column_at: DateTime<Utc>,
+    pub days_in_column: i64,
+}
+
+/// Cards that have sat in their current column for at least `threshold_days`,
+/// longest-waiting first
+#[tauri::command]
+pub fn get_stale_cards(
+    threshold_days: i64,
+    column: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<StaleCard>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    let now = Utc::now();
+
+    let mut cards: Vec<StaleCard> = cache
+        .get_column_entries()?
+        .into_iter()
+        .filter(|entry| match &column {
+            Some(column) => column == &entry.column,
+            None => true,
+        })
+        .map(|entry| StaleCard {
+            days_in_column: days_since(entry.entered_at, now),
+            file_path: entry.file_path,
+            title: entry.title,
+            column: entry.column,
+            entered_column_at: entry.entered_at,
+        })
+        .filter(|card| card.days_in_column >= threshold_days)
+        .collect();
+
+    cards.sort_by(|a, b| {
+        a.entered_column_at
+            .cmp(&b.entered_column_at)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    Ok(cards)
+}
+
+/// Where the notes of a deleted column go
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reassignment {
+    /// Into another column of the board
+    Column { column: String },
+    /// Into the archive, filed under the first remaining column so they
+    /// reappear on the board when unarchived
+    Archive,
+}
+
+/// A column holding, or about to hold, more cards than its WIP limit. Sent
+/// as an event for soft limits and returned as the error for hard ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WipViolation {
+    pub column: String,
+    pub count: usize,
+    pub limit: usize,
+    pub policy: WipPolicy,
+}
+
+impl fmt::Display for WipViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Column {} is limited to {} card(s)",
+            self.column, self.limit
+        )
+    }
+}
+
+impl From<WipViolation> for String {
+    fn from(violation: WipViolation) -> Self {
+        violation.to_string()
+    }
+}
+
+/// Cards in a column against its WIP limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnWip {
+    pub column: String,
+    pub count: usize,
+    pub limit: Option<usize>,
+    pub policy: WipPolicy,
+    pub over_limit: bool,
+}
+
+/// A board and the folder it belongs to, relative to the vault; the vault's
+/// own board has an empty folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardSummary {
+    pub folder: String,
+    pub config: BoardConfig,
+}
+
+/// A board change and the notes it moved to another column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnChange {
+    pub board: BoardConfig,
+    pub updated: Vec<NoteWithTags>,
+}
+
+fn update_board(
+    root: &Path,
+    change: impl FnOnce(&mut BoardConfig) -> Result<(), String>,
+) -> Result<BoardConfig, String> {
+    let mut board: BoardConfig = load_config(root, BOARD_FILE)?;
+    change(&mut board)?;
+    save_config(root, BOARD_FILE, &board)?;
+    Ok(board)
+}
+
+/// Put back the board a failed change started from
+fn restore_board(root: &Path, previous: &BoardConfig) {
+    if let Err(e) = save_config(root, BOARD_FILE, previous) {
+        log::error!("Failed to restore {}: {}", BOARD_FILE, e);
+    }
+}
+
+fn column_title(title: &str) -> Result<String, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Column title cannot be empty".to_string());
+    }
+    Ok(title.to_string())
+}
+
+/// Columns of a board, in display order: the vault's, or that of the folder
+/// `board`. A board without a board file gets the default columns.
+#[tauri::command]
+pub fn get_board_config(notes_dir: String, board: Option<String>) -> Result<BoardConfig, String> {
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    load_config(&root, BOARD_FILE)
+}
+
+/// The vault's board followed by every folder board below it
+#[tauri::command]
+pub fn list_boards(notes_dir: String) -> Result<Vec<BoardSummary>, String> {
+    let base = Path::new(&notes_dir);
+    std::iter::once(String::new())
+        .chain(board_folders(base))
+        .map(|folder| {
+            let config = load_config(&base.join(&folder), BOARD_FILE)?;
+            Ok(BoardSummary { folder, config })
+        })
+        .collect()
+}
+
+/// Give `folder` a board of its own, starting with the default columns. Its
+/// notes, and those of its subfolders without a board, move off the board
+/// above it.
+#[tauri::command]
+pub fn create_board(
+    notes_dir: String,
+    folder: String,
+    state: State<AppState>,
+) -> Result<BoardConfig, String> {
+    if folder.trim().trim_matches('/').is_empty() {
+        return Err("The vault already has a board".to_string());
+    }
+    let root = board_root(Path::new(&notes_dir), Some(&folder))?;
+    if has_board(&root) {
+        return Err(format!("{} already has a board", folder));
+    }
+    let board = BoardConfig::default();
+    save_config(&root, BOARD_FILE, &board)?;
+    note_index::forget_boards(&state);
+    Ok(board)
+}
+
+/// Notes shown on the board of `scope`
+fn board_placements(
+    scope: &BoardScope,
+    state: &State<AppState>,
+) -> Result<Vec<NotePlacement>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    Ok(cache
+        .note_placements()?
+        .into_iter()
+        .filter(|placement| scope.contains(Path::new(&placement.file_path)))
+        .collect())
+}
+
+/// Cards in each column; archived notes are off the board and don't count
+fn column_counts(placements: &[NotePlacement]) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for placement in placements.iter().filter(|placement| !placement.archived) {
+        *counts.entry(placement.column.as_str()).or_default() += 1;
+    }
+    counts
+}
+
+/// Id for a column titled `title` that no column other than `current` has
+fn unique_column_id(board: &BoardConfig, title: &str, current: Option<&str>) -> String {
+    let slug = slugify_or_fallback(title, &Uuid::new_v4().to_string());
+    let mut id = slug.clone();
+    let mut suffix = 2;
+    while board.column(&id).is_some() && current != Some(id.as_str()) {
+        id = format!("{}-{}", slug, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+/// Apply `edit` to the cards in `file_paths` as one batch
+fn update_cards(
+    notes_dir: &str,
+    file_paths: &[String],
+    state: &State<AppState>,
+    edit: impl Fn(&str, &mut NoteFrontmatter),
+) -> Result<Vec<NoteWithTags>, String> {
+    let result =
+        update_notes_frontmatter(notes_dir, file_paths, state, |file_path, frontmatter| {
+            edit(file_path, frontmatter);
+            Ok(())
+        });
+    for file_path in file_paths {
+        audit::record(state, "update", file_path, None, &result);
+    }
+    let notes = result?;
+    if !notes.is_empty() {
+        broadcast::broadcast(notes_dir, notes.clone(), Vec::new());
+    }
+    Ok(notes)
+}
+
+/// Apply `edit` to every note in column `from` of the board at `root`, as
+/// one batch
+fn migrate_notes(
+    notes_dir: &str,
+    root: &Path,
+    from: &str,
+    state: &State<AppState>,
+    edit: impl Fn(&mut NoteFrontmatter),
+) -> Result<Vec<NoteWithTags>, String> {
+    let scope = note_index::board_scope(state, root.to_path_buf());
+    let file_paths: Vec<String> = board_placements(&scope, state)?
+        .into_iter()
+        .filter(|placement| placement.column == from)
+        .map(|placement| placement.file_path)
+        .collect();
+    update_cards(notes_dir, &file_paths, state, |_, frontmatter| {
+        edit(frontmatter)
+    })
+}
+
+/// Which cards form the longest run whose current orders already increase.
+/// Cards not yet in the column (`None`) are never part of it.
+fn longest_increasing(orders: &[Option<i32>]) -> Vec<bool> {
+    // `tails[k]` is the card ending the best run of length `k + 1` so far
+    let mut tails: Vec<(usize, i32)> = Vec::new();
+    let mut previous = vec![None; orders.len()];
+    for (index, order) in orders.iter().enumerate() {
+        let Some(order) = *order else {
+            continue;
+        };
+        let len = tails.partition_point(|(_, tail)| *tail < order);
+        previous[index] = len.checked_sub(1).map(|k| tails[k].0);
+        if len == tails.len() {
+            tails.push((index, order));
+        } else {
+            tails[len] = (index, order);
+        }
+    }
+    let mut kept = vec![false; orders.len()];
+    let mut next = tails.last().map(|(index, _)| *index);
+    while let Some(index) = next {
+        kept[index] = true;
+        next = previous[index];
+    }
+    kept
+}
+
+/// New `order` values for cards shown in sequence, given the order of those
+/// already in the column. The longest run already in order keeps its values
+/// and the other cards are spread between their neighbours; every card is
+/// renumbered only when there is no room left between two of them.
+fn sparse_orders(current: &[Option<i32>]) -> Vec<i32> {
+    let renumbered = || -> Vec<i32> {
+        (1..=current.len() as i32)
+            .map(|position| position.saturating_mul(ORDER_STEP))
+            .collect()
+    };
+    let kept = longest_increasing(current);
+    let mut orders: Vec<Option<i32>> = current
+        .iter()
+        .zip(&kept)
+        .map(|(order, kept)| order.filter(|_| *kept))
+        .collect();
+
+    let step = i64::from(ORDER_STEP);
+    let mut end = 0;
+    while let Some(offset) = orders[end..].iter().position(Option::is_none) {
+        let start = end + offset;
+        end = orders[start..]
+            .iter()
+            .position(Option::is_some)
+            .map_or(orders.len(), |len| start + len);
+        let count = (end - start) as i64;
+        let before = start.checked_sub(1).and_then(|index| orders[index]);
+        let after = orders.get(end).copied().flatten();
+        let (first, gap) = match (before.map(i64::from), after.map(i64::from)) {
+            (Some(before), Some(after)) => {
+                let gap = (after - before) / (count + 1);
+                (before + gap, gap)
+            }
+            (Some(before), None) => (before + step, step),
+            (None, Some(after)) => (after - step * count, step),
+            (None, None) => (step, step),
+        };
+        if gap < 1 {
+            return renumbered();
+        }
+        for (position, slot) in orders[start..end].iter_mut().enumerate() {
+            match i32::try_from(first + gap * position as i64) {
+                Ok(order) => *slot = Some(order),
+                Err(_) => return renumbered(),
+            }
+        }
+    }
+    orders.into_iter().flatten().collect()
+}
+
+/// Check that `entering` more cards fit in `column` of the board at `root`.
+/// Over a hard limit the move is refused; over a soft one it goes ahead and
+/// [`WIP_EXCEEDED_EVENT`] is sent.
+pub(crate) fn check_wip_limit(
+    root: &Path,
+    column: &str,
+    entering: usize,
+    state: &State<AppState>,
+) -> Result<(), WipViolation> {
+    if entering == 0 {
+        return Ok(());
+    }
+    let board: BoardConfig = match load_config(root, BOARD_FILE) {
+        Ok(board) => board,
+        Err(e) => {
+            log::warn!("Skipping WIP limit check: {}", e);
+            return Ok(());
+        }
+    };
+    let Some((limit, policy)) = board
+        .column(column)
+        .and_then(|column| Some((column.wip_limit?, column.wip_policy)))
+    else {
+        return Ok(());
+    };
+    let placements =
+        match board_placements(&note_index::board_scope(state, root.to_path_buf()), state) {
+            Ok(placements) => placements,
+            Err(e) => {
+                log::warn!("Skipping WIP limit check: {}", e);
+                return Ok(());
+            }
+        };
+    let count = column_counts(&placements).get(column).copied().unwrap_or(0) + entering;
+    if count <= limit {
+        return Ok(());
+    }
+    let violation = WipViolation {
+        column: column.to_string(),
+        count,
+        limit,
+        policy,
+    };
+    match policy {
+        WipPolicy::Hard => Err(violation),
+        WipPolicy::Soft => {
+            broadcast::emit(WIP_EXCEEDED_EVENT, violation);
+            Ok(())
+        }
+    }
+}
+
+/// Check the limit of `column` on the board showing notes in `folder`, for a
+/// card about to land there
+pub(crate) fn check_card_entering(
+    notes_dir: &Path,
+    folder: &Path,
+    column: &str,
+    state: &State<AppState>,
+) -> Result<(), WipViolation> {
+    check_wip_limit(&folder_board_root(notes_dir, folder), column, 1, state)
+}
+
+/// Card counts of every column of a board against its WIP limit, in board
+/// order
+#[tauri::command]
+pub fn get_wip_status(
+    notes_dir: String,
+    board: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<ColumnWip>, String> {
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    let config: BoardConfig = load_config(&root, BOARD_FILE)?;
+    let placements = board_placements(&note_index::board_scope(&state, root), &state)?;
+    let counts = column_counts(&placements);
+    Ok(config
+        .columns
+        .into_iter()
+        .map(|column| {
+            let count = counts.get(column.id.as_str()).copied().unwrap_or(0);
+            ColumnWip {
+                over_limit: column.wip_limit.is_some_and(|limit| count > limit),
+                count,
+                limit: column.wip_limit,
+                policy: column.wip_policy,
+                column: column.id,
+            }
+        })
+        .collect())
+}
+
+/// Set or clear (`None`) a column's WIP limit and how it is enforced
+#[tauri::command]
+pub fn set_column_wip_limit(
+    notes_dir: String,
+    column_id: String,
+    limit: Option<usize>,
+    policy: WipPolicy,
+    board: Option<String>,
+) -> Result<BoardConfig, String> {
+    if limit == Some(0) {
+        return Err("A WIP limit must be at least 1".to_string());
+    }
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    update_board(&root, |board| {
+        let column = board.column_mut(&column_id)?;
+        column.wip_limit = limit;
+        column.wip_policy = policy;
+        Ok(())
+    })
+}
+
+/// Append a column; its id is derived from the title
+#[tauri::command]
+pub fn add_column(
+    notes_dir: String,
+    title: String,
+    color: Option<String>,
+    board: Option<String>,
+) -> Result<BoardConfig, String> {
+    let title = column_title(&title)?;
+    let color = match color {
+        Some(color) => normalize_color(&color)?,
+        None => None,
+    };
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    update_board(&root, |board| {
+        let id = unique_column_id(board, &title, None);
+        board.columns.push(BoardColumn {
+            id,
+            title,
+            color: color.unwrap_or_else(|| DEFAULT_COLUMN_COLOR.to_string()),
+            wip_limit: None,
+            wip_policy: WipPolicy::Soft,
+        });
+        Ok(())
+    })
+}
+
+/// Retitle a column. Its id follows the new title, and every note in the
+/// column is moved to the new id so no card falls off the board.
+#[tauri::command]
+pub fn rename_column(
+    notes_dir: String,
+    column_id: String,
+    title: String,
+    board: Option<String>,
+    state: State<AppState>,
+) -> Result<ColumnChange, String> {
+    let title = column_title(&title)?;
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    let previous: BoardConfig = load_config(&root, BOARD_FILE)?;
+    let mut board = previous.clone();
+    let new_id = unique_column_id(&board, &title, Some(&column_id));
+    let column = board.column_mut(&column_id)?;
+    column.title = title;
+    column.id = new_id.clone();
+    save_config(&root, BOARD_FILE, &board)?;
+    if new_id == column_id {
+        return Ok(ColumnChange {
+            board,
+            updated: Vec::new(),
+        });
+    }
+
+    let result = migrate_notes(&notes_dir, &root, &column_id, &state, |frontmatter| {
+        frontmatter.column = new_id.clone();
+    });
+    let updated = match result {
+        Ok(updated) => updated,
+        Err(e) => {
+            restore_board(&root, &previous);
+            return Err(e);
+        }
+    };
+    // Other boards may have a column with the same id, so only the history
+    // of the moved notes is renamed
+    let note_ids: Vec<String> = updated
+        .iter()
+        .map(|note| note.note.frontmatter.id.clone())
+        .collect();
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            if let Err(e) = cache.rename_column_transitions(&column_id, &new_id, &note_ids) {
+                log::warn!("Failed to rename column history: {}", e);
+            }
+        }
+    }
+    Ok(ColumnChange { board, updated })
+}
+
+/// Put the columns in the order of `column_ids`, which must list each column
+/// exactly once
+#[tauri::command]
+pub fn reorder_columns(
+    notes_dir: String,
+    column_ids: Vec<String>,
+    board: Option<String>,
+) -> Result<BoardConfig, String> {
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    update_board(&root, |board| {
+        let unique: HashSet<&String> = column_ids.iter().collect();
+        if unique.len() != column_ids.len() || column_ids.len() != board.columns.len() {
+            return Err("The new order must list every column once".to_string());
+        }
+        board.columns = column_ids
+            .iter()
+            .map(|id| board.column_mut(id).cloned())
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    })
+}
+
+/// Show the cards of `column` in the order of `file_paths`, moving cards
+/// from other columns of the same board into it. Only notes whose column or
+/// order changes are rewritten, as one batch.
+#[tauri::command]
+pub fn reorder_notes(
+    notes_dir: String,
+    column: String,
+    file_paths: Vec<String>,
+    board: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<NoteWithTags>, String> {
+    let unique: HashSet<&String> = file_paths.iter().collect();
+    if unique.len() != file_paths.len() {
+        return Err("A card can only appear once in the new order".to_string());
+    }
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    let scope = note_index::board_scope(&state, root.clone());
+    if let Some(outside) = file_paths
+        .iter()
+        .find(|file_path| !scope.contains(Path::new(file_path)))
+    {
+        return Err(format!("Note is not on this board: {}", outside));
+    }
+    let placements: HashMap<String, NotePlacement> = board_placements(&scope, &state)?
+        .into_iter()
+        .map(|placement| (placement.file_path.clone(), placement))
+        .collect();
+    let current: Vec<Option<i32>> = file_paths
+        .iter()
+        .map(|file_path| {
+            placements
+                .get(file_path)
+                .filter(|placement| placement.column == column)
+                .map(|placement| placement.order)
+        })
+        .collect();
+    let orders: HashMap<&str, i32> = file_paths
+        .iter()
+        .map(String::as_str)
+        .zip(sparse_orders(&current))
+        .collect();
+    let changed: Vec<String> = file_paths
+        .iter()
+        .zip(&current)
+        .filter(|(file_path, order)| **order != orders.get(file_path.as_str()).copied())
+        .map(|(file_path, _)| file_path.clone())
+        .collect();
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let entering = current.iter().filter(|order| order.is_none()).count();
+    check_wip_limit(&root, &column, entering, &state)?;
+    update_cards(&notes_dir, &changed, &state, |file_path, frontmatter| {
+        frontmatter.column = column.clone();
+        if let Some(order) = orders.get(file_path) {
+            frontmatter.order = *order;
+        }
+    })
+}
+
+/// Remove a column, moving its notes as `reassign_to` says. The last column
+/// can't be removed.
+#[tauri::command]
+pub fn delete_column(
+    notes_dir: String,
+    column_id: String,
+    reassign_to: Reassignment,
+    board: Option<String>,
+    state: State<AppState>,
+) -> Result<ColumnChange, String> {
+    let root = board_root(Path::new(&notes_dir), board.as_deref())?;
+    let previous: BoardConfig = load_config(&root, BOARD_FILE)?;
+    let mut board = previous.clone();
+    board.column_mut(&column_id)?;
+    board.columns.retain(|column| column.id != column_id);
+    let Some(first) = board.columns.first() else {
+        return Err("A board needs at least one column".to_string());
+    };
+    let (target, archive) = match reassign_to {
+        Reassignment::Column { column } if column == column_id => {
+            return Err("Notes can't be moved to the column being deleted".to_string())
+        }
+        Reassignment::Column { column } => {
+            let target = board
+                .column(&column)
+                .ok_or_else(|| format!("Column not found: {}", column))?;
+            (target.id.clone(), false)
+        }
+        Reassignment::Archive => (first.id.clone(), true),
+    };
+    if !archive {
+        let placements = board_placements(&note_index::board_scope(&state, root.clone()), &state)?;
+        let entering = column_counts(&placements)
+            .get(column_id.as_str())
+            .copied()
+            .unwrap_or(0);
+        check_wip_limit(&root, &target, entering, &state)?;
+    }
+    save_config(&root, BOARD_FILE, &board)?;
+
+    let result = migrate_notes(&notes_dir, &root, &column_id, &state, |frontmatter| {
+        frontmatter.column = target.clone();
+        frontmatter.archived |= archive;
+    });
+    let updated = match result {
+        Ok(updated) => updated,
+        Err(e) => {
+            restore_board(&root, &previous);
+            return Err(e);
+        }
+    };
+    Ok(ColumnChange { board, updated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::{create_note, parse_note, scan_vault, CreateNoteInput};
+    use crate::test_support::TestVault;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn keeps_orders_of_cards_already_in_sequence() {
+        // One card moved up: only it gets a new order
+        assert_eq!(
+            sparse_orders(&[Some(1024), Some(3072), Some(2048)]),
+            [1024, 1536, 2048]
+        );
+        // Cards from other columns fit around the ones already there
+        assert_eq!(
+            sparse_orders(&[None, Some(1024), None, None]),
+            [0, 1024, 2048, 3072]
+        );
+        assert_eq!(sparse_orders(&[None, None]), [1024, 2048]);
+        // No room between 1 and 2: everything is renumbered
+        assert_eq!(sparse_orders(&[Some(1), None, Some(2)]), [1024, 2048, 3072]);
+        assert!(sparse_orders(&[]).is_empty());
+    }
+
+    #[test]
+    fn edits_board_columns() {
+        let test_vault = TestVault::new();
+        let vault = test_vault.dir.clone();
+        let notes_dir = test_vault.notes_dir();
+        let ids = |board: &BoardConfig| -> Vec<String> {
+            board
+                .columns
+                .iter()
+                .map(|column| column.id.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            ids(&get_board_config(notes_dir.clone(), None).unwrap()),
+            ["backlog", "todo", "doing", "done"]
+        );
+        let board = add_column(
+            notes_dir.clone(),
+            " To Do ".to_string(),
+            Some("Red".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(board.columns[4].id, "to-do");
+        assert_eq!(board.columns[4].title, "To Do");
+        assert_eq!(board.columns[4].color, "red");
+        assert_eq!(unique_column_id(&board, "Done", None), "done-2");
+        assert_eq!(unique_column_id(&board, "Done", Some("done")), "done");
+        let change = rename_column(
+            notes_dir.clone(),
+            "to-do".to_string(),
+            "Next".to_string(),
+            None,
+            test_vault.state(),
+        )
+        .unwrap();
+        assert_eq!(change.board.columns[4].id, "next");
+        assert_eq!(change.board.columns[4].title, "Next");
+        assert!(change.updated.is_empty());
+
+        let order = ["next", "done", "doing", "todo", "backlog"].map(String::from);
+        assert!(reorder_columns(notes_dir.clone(), order[..4].to_vec(), None).is_err());
+        reorder_columns(notes_dir.clone(), order.to_vec(), None).unwrap();
+        assert_eq!(
+            ids(&get_board_config(notes_dir.clone(), None).unwrap()),
+            order
+        );
+        assert!(add_column(notes_dir.clone(), " ".to_string(), None, None).is_err());
+
+        // A folder board starts with the default columns and is edited apart
+        fs::create_dir_all(vault.join("acme")).unwrap();
+        let acme = Some("acme".to_string());
+        create_board(notes_dir.clone(), "acme".to_string(), test_vault.state()).unwrap();
+        assert!(create_board(notes_dir.clone(), "acme".to_string(), test_vault.state()).is_err());
+        assert!(create_board(notes_dir.clone(), "".to_string(), test_vault.state()).is_err());
+        add_column(notes_dir.clone(), "Review".to_string(), None, acme.clone()).unwrap();
+        let boards = list_boards(notes_dir.clone()).unwrap();
+        assert_eq!(boards.len(), 2);
+        assert_eq!(ids(&boards[0].config), order);
+        assert_eq!(boards[1].folder, "acme");
+        assert_eq!(
+            ids(&boards[1].config),
+            ["backlog", "todo", "doing", "done", "review"]
+        );
+    }
+
+    #[test]
+    fn cards_and_new_notes_follow_renamed_columns() {
+        let vault = TestVault::new();
+        let plan = vault.note("plan.md", "plan", "");
+        vault.note("acme/spec.md", "spec", "");
+        scan_vault(&vault.notes_dir(), &vault.state(), &mut |_| {}).unwrap();
+
+        let change = rename_column(
+            vault.notes_dir(),
+            "todo".to_string(),
+            "Up Next".to_string(),
+            None,
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(change.board.columns[1].id, "up-next");
+        let mut moved: Vec<String> = change
+            .updated
+            .iter()
+            .map(|note| note.note.file_path.clone())
+            .collect();
+        moved.sort();
+        assert_eq!(moved, [vault.path("acme/spec.md"), plan]);
+        assert!(vault.read("plan.md").contains("column: up-next"));
+        assert!(vault.read("acme/spec.md").contains("column: up-next"));
+
+        // Without a "todo" column new notes land in the board's first one
+        let create = |folder: Option<&str>| {
+            create_note(
+                CreateNoteInput {
+                    notes_dir: vault.notes_dir(),
+                    folder_path: folder.map(str::to_string),
+                    title: "New".to_string(),
+                    content: None,
+                    date: None,
+                    due: None,
+                    column: None,
+                    tags: None,
+                    idempotency_key: None,
+                },
+                vault.state(),
+            )
+            .unwrap()
+            .note
+            .frontmatter
+            .column
+        };
+        assert_eq!(create(None), "backlog");
+        create_board(vault.notes_dir(), "acme".to_string(), vault.state()).unwrap();
+        assert_eq!(create(Some("acme")), "todo");
+        vault.write("loose.md", "# Loose\n");
+        let loose = parse_note(&PathBuf::from(vault.path("loose.md"))).unwrap();
+        assert_eq!(loose.frontmatter.column, "backlog");
+    }
+
+    #[test]
+    fn deleting_the_default_column_reassigns_cards_and_new_notes() {
+        let vault = TestVault::new();
+        vault.note("plan.md", "plan", "");
+        scan_vault(&vault.notes_dir(), &vault.state(), &mut |_| {}).unwrap();
+
+        let change = delete_column(
+            vault.notes_dir(),
+            "todo".to_string(),
+            Reassignment::Column {
+                column: "doing".to_string(),
+            },
+            None,
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(change.updated.len(), 1);
+        assert!(vault.read("plan.md").contains("column: doing"));
+
+        let note = create_note(
+            CreateNoteInput {
+                notes_dir: vault.notes_dir(),
+                folder_path: None,
+                title: "Next".to_string(),
+                content: None,
+                date: None,
+                due: None,
+                column: None,
+                tags: None,
+                idempotency_key: None,
+            },
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(note.note.frontmatter.column, "backlog");
+    }
+
+    #[test]
+    fn hard_wip_limits_guard_every_way_onto_a_column() {
+        use crate::commands::inbox::{triage_note, TriageDecision};
+        use crate::commands::notes::{move_note, unarchive_note};
+
+        let vault = TestVault::new();
+        let notes_dir = || vault.notes_dir();
+        vault.note("full.md", "full", "");
+        vault.note("shelved.md", "shelved", "archived: true\n");
+        let doing = vault.note("doing.md", "doing", "");
+        fs::write(&doing, vault.read("doing.md").replace("todo", "doing")).unwrap();
+        let side = vault.note("side/card.md", "card", "");
+        create_board(notes_dir(), "side".to_string(), vault.state()).unwrap();
+        for (root, column) in [(None, "todo"), (None, "doing"), (Some("side"), "todo")] {
+            set_column_wip_limit(
+                notes_dir(),
+                column.to_string(),
+                Some(1),
+                WipPolicy::Hard,
+                root.map(str::to_string),
+            )
+            .unwrap();
+        }
+        scan_vault(&notes_dir(), &vault.state(), &mut |_| {}).unwrap();
+
+        assert_eq!(
+            check_wip_limit(&vault.dir, "todo", 1, &vault.state()),
+            Err(WipViolation {
+                column: "todo".to_string(),
+                count: 2,
+                limit: 1,
+                policy: WipPolicy::Hard,
+            })
+        );
+        let is_wip_error = |result: Result<(), String>| {
+            result.is_err_and(|e| e == "Column todo is limited to 1 card(s)")
+        };
+
+        let input = CreateNoteInput {
+            notes_dir: notes_dir(),
+            folder_path: None,
+            title: "Another".to_string(),
+            content: None,
+            date: None,
+            due: None,
+            column: None,
+            tags: None,
+            idempotency_key: None,
+        };
+        assert!(is_wip_error(create_note(input, vault.state()).map(|_| ())));
+        assert!(is_wip_error(
+            unarchive_note(notes_dir(), vault.path("shelved.md"), vault.state()).map(|_| ())
+        ));
+        assert!(is_wip_error(
+            move_note(
+                notes_dir(),
+                side.clone(),
+                String::new(),
+                None,
+                vault.state()
+            )
+            .map(|_| ())
+        ));
+        assert!(is_wip_error(
+            triage_note(
+                notes_dir(),
+                doing.clone(),
+                TriageDecision::Schedule {
+                    date: "2024-02-01".to_string(),
+                    column: Some("todo".to_string()),
+                },
+                vault.state(),
+            )
+            .map(|_| ())
+        ));
+        assert!(delete_column(
+            notes_dir(),
+            "doing".to_string(),
+            Reassignment::Column {
+                column: "todo".to_string(),
+            },
+            None,
+            vault.state(),
+        )
+        .is_err());
+
+        let board = get_board_config(notes_dir(), None).unwrap();
+        assert!(board.column("doing").is_some());
+        assert!(vault.read("shelved.md").contains("archived: true"));
+        assert!(vault.read("doing.md").contains("column: doing"));
+        assert!(vault.exists("side/card.md"));
+        assert_eq!(fs::read_dir(&vault.dir).unwrap().count(), 5);
+    }
+}
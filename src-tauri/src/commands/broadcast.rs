@@ -0,0 +1,97 @@
+use crate::commands::audit;
+use crate::commands::notes::{Note, NoteWithTags};
+use crate::utils::{extract_inline_tags, make_excerpt};
+use serde::Serialize;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+
+/// Sent after every successful note mutation so other windows on the same
+/// vault can patch their boards
+pub(crate) const VAULT_CHANGED_EVENT: &str = "vault://changed";
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultChange {
+    pub notes_dir: String,
+    /// Label of the window that made the change; it already has the result
+    pub origin: String,
+    pub updated_notes: Vec<NoteWithTags>,
+    pub removed_paths: Vec<String>,
+}
+
+/// Called once at startup so commands can broadcast without an `AppHandle`
+pub(crate) fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+pub(crate) fn listed(note: Note) -> NoteWithTags {
+    let inline_tags = extract_inline_tags(&note.content);
+    let excerpt = make_excerpt(&note.content);
+    NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: Some(excerpt),
+    }
+}
+
+pub(crate) fn broadcast(
+    notes_dir: &str,
+    updated_notes: Vec<NoteWithTags>,
+    removed_paths: Vec<String>,
+) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let change = VaultChange {
+        notes_dir: notes_dir.to_string(),
+        origin: audit::current_actor(),
+        updated_notes,
+        removed_paths,
+    };
+    if let Err(e) = app.emit(VAULT_CHANGED_EVENT, change) {
+        log::warn!("Failed to emit {}: {}", VAULT_CHANGED_EVENT, e);
+    }
+}
+
+/// Send any other event to every window
+pub(crate) fn emit<T: Serialize + Clone>(event: &str, payload: T) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    if let Err(e) = app.emit(event, payload) {
+        log::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::parse_note_content;
+    use std::path::Path;
+
+    #[test]
+    fn lists_notes_and_serializes_changes_for_the_frontend() {
+        let text = "---\nid: a\ntitle: A\ncolumn: todo\n---\n\nShip it #release";
+        let note = parse_note_content(text, Path::new("/vault/a.md")).unwrap();
+        let listed = listed(note);
+        assert_eq!(listed.inline_tags, ["release"]);
+        assert!(listed.excerpt.unwrap().starts_with("Ship it"));
+
+        let change = VaultChange {
+            notes_dir: "/vault".to_string(),
+            origin: "main".to_string(),
+            updated_notes: Vec::new(),
+            removed_paths: vec!["/vault/b.md".to_string()],
+        };
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json["notesDir"], "/vault");
+        assert_eq!(json["removedPaths"][0], "/vault/b.md");
+
+        // Nothing to send to before the app is running
+        broadcast("/vault", Vec::new(), Vec::new());
+        emit("test://event", "payload");
+    }
+}
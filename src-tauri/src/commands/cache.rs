@@ -0,0 +1,131 @@
+use crate::cache::CacheDb;
+use crate::history;
+use crate::lock_or_err;
+use crate::AppState;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::State;
+
+/// How well the cache serves listings, counted since startup
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    /// Notes served from the cache without reading the file
+    pub hits: AtomicU64,
+    /// Notes that had to be read and parsed
+    pub misses: AtomicU64,
+    /// Milliseconds the last full vault scan took; 0 before the first one
+    pub last_scan_ms: AtomicU64,
+}
+
+impl CacheCounters {
+    pub(crate) fn record(&self, hits: u64, misses: u64) {
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+        self.misses.fetch_add(misses, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEncryptionStatus {
+    /// This build can encrypt the cache (SQLCipher)
+    pub supported: bool,
+    pub enabled: bool,
+}
+
+#[tauri::command]
+pub fn get_cache_encryption(state: State<AppState>) -> Result<CacheEncryptionStatus, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    Ok(CacheEncryptionStatus {
+        supported: CacheDb::encryption_supported(),
+        enabled: cache.is_encrypted(),
+    })
+}
+
+/// Encrypt the profile's cache database with a key kept in the OS keychain,
+/// or decrypt it again
+#[tauri::command]
+pub fn set_cache_encryption(
+    enabled: bool,
+    state: State<AppState>,
+) -> Result<CacheEncryptionStatus, String> {
+    if state.safe_mode {
+        return Err("The cache is read-only in safe mode".to_string());
+    }
+    let mut cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_mut().ok_or("Cache is not initialized")?;
+    let old_key = cache.history_key();
+    cache.set_encrypted(enabled)?;
+    let new_key = cache.history_key();
+    if new_key != old_key {
+        // Version history follows the database key so it is never left
+        // readable next to an encrypted cache
+        history::rekey_history(&cache.profile_id, old_key.as_ref(), new_key.as_ref())
+            .map_err(|e| format!("Failed to reseal version history: {}", e))?;
+    }
+    Ok(CacheEncryptionStatus {
+        supported: CacheDb::encryption_supported(),
+        enabled: cache.is_encrypted(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheOptimizeSummary {
+    pub removed_tags: usize,
+    /// Database and write-ahead log size before and after, in bytes
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Compact the cache database and drop unused tags; long-lived caches grow
+/// and fragment otherwise
+#[tauri::command]
+pub fn optimize_cache(state: State<AppState>) -> Result<CacheOptimizeSummary, String> {
+    if state.safe_mode {
+        return Err("The cache is read-only in safe mode".to_string());
+    }
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    let (db_before, wal_before) = cache.disk_usage()?;
+    let removed_tags = cache.optimize()?;
+    let (db_after, wal_after) = cache.disk_usage()?;
+    Ok(CacheOptimizeSummary {
+        removed_tags,
+        size_before: db_before + wal_before,
+        size_after: db_after + wal_after,
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub notes: usize,
+    pub tags: usize,
+    pub db_size: u64,
+    pub wal_size: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub last_scan_ms: Option<u64>,
+    pub encrypted: bool,
+}
+
+/// Diagnostics showing whether the cache is effective
+#[tauri::command]
+pub fn cache_stats(state: State<AppState>) -> Result<CacheStats, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    let (notes, tags) = cache.counts()?;
+    let (db_size, wal_size) = cache.disk_usage()?;
+    let counters = &state.cache_counters;
+    Ok(CacheStats {
+        notes,
+        tags,
+        db_size,
+        wal_size,
+        hits: counters.hits.load(Ordering::Relaxed),
+        misses: counters.misses.load(Ordering::Relaxed),
+        last_scan_ms: Some(counters.last_scan_ms.load(Ordering::Relaxed)).filter(|ms| *ms > 0),
+        encrypted: cache.is_encrypted(),
+    })
+}
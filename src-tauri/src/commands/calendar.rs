@@ -0,0 +1,391 @@
+use crate::commands::notes::{atomic_write, fill_days_in_column, read_vault, Note, NoteWithTags};
+use crate::commands::symlinks::symlink_allowlist;
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::State;
+
+/// Column whose notes are never overdue unless another is given
+const DEFAULT_DONE_COLUMN: &str = "done";
+/// iCalendar lines longer than this many bytes are folded
+const ICAL_LINE_LIMIT: usize = 75;
+const FEED_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Running localhost calendar feed; its thread exits once `stop` is set
+pub struct IcalFeed {
+    url: String,
+    stop: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IcalExportSummary {
+    pub path: String,
+    pub events: usize,
+}
+
+enum EventTime {
+    Day(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+impl EventTime {
+    fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if let Ok(day) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+            return Some(Self::Day(day));
+        }
+        DateTime::parse_from_rfc3339(raw)
+            .ok()
+            .map(|at| Self::At(at.with_timezone(&Utc)))
+    }
+
+    /// Whether the time has passed; a day passes once it is over (UTC)
+    fn is_past(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            Self::Day(day) => *day < now.date_naive(),
+            Self::At(at) => *at < now,
+        }
+    }
+
+    fn start(&self) -> DateTime<Utc> {
+        match self {
+            Self::Day(day) => day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+            Self::At(at) => *at,
+        }
+    }
+}
+
+/// The note's `date`, or its due date when it has none
+fn event_time(note: &Note) -> Option<EventTime> {
+    let frontmatter = &note.frontmatter;
+    EventTime::parse(frontmatter.date.as_deref().or(frontmatter.due.as_deref())?)
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Append a content line, folded at 75 bytes without splitting a character
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > ICAL_LINE_LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn ical_stamp(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// One all-day or one-hour event per dated note
+fn build_calendar(notes: &[Note], name: &str) -> (String, usize) {
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//noteban//noteban//EN",
+        "CALSCALE:GREGORIAN",
+    ] {
+        push_line(&mut out, line);
+    }
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    let mut events = 0;
+    for note in notes {
+        let Some(time) = event_time(note) else {
+            continue;
+        };
+        let frontmatter = &note.frontmatter;
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}@noteban", frontmatter.id));
+        push_line(
+            &mut out,
+            &format!("DTSTAMP:{}", ical_stamp(frontmatter.modified)),
+        );
+        push_line(
+            &mut out,
+            &format!("LAST-MODIFIED:{}", ical_stamp(frontmatter.modified)),
+        );
+        match time {
+            EventTime::Day(day) => {
+                let next = day + ChronoDuration::days(1);
+                push_line(
+                    &mut out,
+                    &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")),
+                );
+                push_line(
+                    &mut out,
+                    &format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+                );
+            }
+            EventTime::At(at) => {
+                push_line(&mut out, &format!("DTSTART:{}", ical_stamp(at)));
+                push_line(
+                    &mut out,
+                    &format!("DTEND:{}", ical_stamp(at + ChronoDuration::hours(1))),
+                );
+            }
+        }
+        push_line(
+            &mut out,
+            &format!("SUMMARY:{}", escape_text(&frontmatter.title)),
+        );
+        push_line(
+            &mut out,
+            &format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!("Column: {}", frontmatter.column))
+            ),
+        );
+        if !frontmatter.tags.is_empty() {
+            let tags: Vec<String> = frontmatter.tags.iter().map(|t| escape_text(t)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", tags.join(",")));
+        }
+        push_line(&mut out, "END:VEVENT");
+        events += 1;
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    (out, events)
+}
+
+fn vault_calendar(
+    notes_dir: &str,
+    allowed_symlink_targets: &[PathBuf],
+) -> Result<(String, usize), String> {
+    let notes = read_vault(notes_dir, allowed_symlink_targets)?.notes;
+    let name = PathBuf::from(notes_dir)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "noteban".to_string());
+    Ok(build_calendar(&notes, &name))
+}
+
+/// Write an `.ics` calendar with an event for every note that has a `date`
+/// (or a `due` property)
+#[tauri::command]
+pub fn export_ical(
+    notes_dir: String,
+    dest: String,
+    state: State<AppState>,
+) -> Result<IcalExportSummary, String> {
+    let dest_path = PathBuf::from(&dest);
+    if let Some(parent) = dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+    let (calendar, events) = vault_calendar(&notes_dir, &symlink_allowlist(&state))?;
+    atomic_write(&dest_path, &calendar)?;
+    Ok(IcalExportSummary { path: dest, events })
+}
+
+fn serve_feed_request(
+    mut stream: TcpStream,
+    notes_dir: &str,
+    allowed_symlink_targets: &[PathBuf],
+    feed_path: &str,
+) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut buffer = [0u8; 4096];
+    let read = stream.read(&mut buffer).unwrap_or(0);
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut parts = request.split_whitespace();
+    let is_feed = parts.next() == Some("GET") && parts.next() == Some(feed_path);
+
+    let response = match is_feed.then(|| vault_calendar(notes_dir, allowed_symlink_targets)) {
+        Some(Ok((calendar, _))) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/calendar; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            calendar.len(),
+            calendar
+        ),
+        Some(Err(e)) => {
+            log::warn!("Calendar feed failed: {}", e);
+            "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string()
+        }
+        None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+    };
+    let _ = stream.write_all(response.as_bytes());
+}
+
+/// Serve the vault's calendar on localhost so calendar apps can subscribe to
+/// it and pick up changes. The URL contains a random token; only one feed
+/// runs at a time and starting a new one replaces it.
+#[tauri::command]
+pub fn start_ical_feed(
+    notes_dir: String,
+    port: Option<u16>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    stop_ical_feed(state.clone())?;
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port.unwrap_or(0)))
+        .map_err(|e| format!("Failed to start calendar feed: {}", e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to start calendar feed: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start calendar feed: {}", e))?
+        .port();
+    let feed_path = format!("/{}.ics", uuid::Uuid::new_v4().simple());
+    let url = format!("http://127.0.0.1:{}{}", port, feed_path);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = Arc::clone(&stop);
+    let allowed_symlink_targets = symlink_allowlist(&state);
+    std::thread::spawn(move || {
+        while !stop_flag.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    serve_feed_request(stream, &notes_dir, &allowed_symlink_targets, &feed_path)
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(FEED_POLL_INTERVAL)
+                }
+                Err(e) => {
+                    log::warn!("Calendar feed stopped: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    *lock_or_err(&state.ical_feed)? = Some(IcalFeed {
+        url: url.clone(),
+        stop,
+    });
+    Ok(url)
+}
+
+/// URL of the running calendar feed
+#[tauri::command]
+pub fn get_ical_feed(state: State<AppState>) -> Result<Option<String>, String> {
+    Ok(lock_or_err(&state.ical_feed)?
+        .as_ref()
+        .map(|feed| feed.url.clone()))
+}
+
+#[tauri::command]
+pub fn stop_ical_feed(state: State<AppState>) -> Result<(), String> {
+    if let Some(feed) = lock_or_err(&state.ical_feed)?.take() {
+        feed.stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Notes whose due date has passed, most overdue first. Notes in the done
+/// column and archived notes are left out.
+#[tauri::command]
+pub fn get_overdue_notes(
+    done_column: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<NoteWithTags>, String> {
+    let done_column = done_column.as_deref().unwrap_or(DEFAULT_DONE_COLUMN);
+    let now = Utc::now();
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+
+    let mut overdue: Vec<(DateTime<Utc>, NoteWithTags)> = cache
+        .get_all_notes()?
+        .into_iter()
+        .filter(|cached| {
+            cached.note.frontmatter.column != done_column && !cached.note.frontmatter.archived
+        })
+        .filter_map(|cached| {
+            let due = EventTime::parse(cached.note.frontmatter.due.as_deref()?)?;
+            due.is_past(now).then(|| {
+                (
+                    due.start(),
+                    NoteWithTags {
+                        note: cached.note,
+                        inline_tags: cached.inline_tags,
+                        days_in_column: None,
+                        excerpt: Some(cached.excerpt),
+                    },
+                )
+            })
+        })
+        .collect();
+    overdue.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then_with(|| a.1.note.file_path.cmp(&b.1.note.file_path))
+    });
+
+    let mut notes: Vec<NoteWithTags> = overdue.into_iter().map(|(_, note)| note).collect();
+    fill_days_in_column(cache, &mut notes);
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_events_for_dated_notes() {
+        let note = |id: &str, title: &str, date: Option<&str>, due: Option<&str>| -> Note {
+            serde_json::from_value(serde_json::json!({
+                "frontmatter": {
+                    "id": id,
+                    "title": title,
+                    "created": "2024-01-01T00:00:00Z",
+                    "modified": "2024-01-02T03:04:05Z",
+                    "column": "todo",
+                    "date": date,
+                    "due": due,
+                    "tags": ["work"]
+                },
+                "content": "",
+                "file_path": format!("/vault/{}.md", id)
+            }))
+            .unwrap()
+        };
+        let notes = [
+            note("a", "Ship v2, finally; really", Some("2024-05-31"), None),
+            note("b", "Standup", None, Some("2024-06-01T09:30:00+02:00")),
+            note("c", "Someday", None, None),
+        ];
+        let (calendar, events) = build_calendar(&notes, "Work");
+        assert_eq!(events, 2);
+        assert!(calendar.contains(
+            "UID:a@noteban\r\nDTSTAMP:20240102T030405Z\r\nLAST-MODIFIED:20240102T030405Z\r\n\
+             DTSTART;VALUE=DATE:20240531\r\nDTEND;VALUE=DATE:20240601\r\n\
+             SUMMARY:Ship v2\\, finally\\; really\r\n"
+        ));
+        assert!(calendar.contains("DTSTART:20240601T073000Z\r\nDTEND:20240601T083000Z\r\n"));
+        assert!(calendar.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+
+        let now = "2024-06-01T12:00:00Z".parse().unwrap();
+        assert!(EventTime::parse("2024-05-31").unwrap().is_past(now));
+        assert!(!EventTime::parse("2024-06-01").unwrap().is_past(now));
+        assert!(EventTime::parse("2024-06-01T11:00:00Z")
+            .unwrap()
+            .is_past(now));
+
+        let mut folded = String::new();
+        push_line(&mut folded, &"é".repeat(40));
+        assert_eq!(
+            folded,
+            format!("{}\r\n {}\r\n", "é".repeat(37), "é".repeat(3))
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::lock_or_err;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::InvokeMessage;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Commands every secondary window needs to start up
+const BASE_COMMANDS: [&str; 3] = ["get_initial_profile", "get_safe_mode", "get_profile_lock"];
+
+const BOARD_VIEW_COMMANDS: [&str; 11] = [
+    "list_notes",
+    "list_notes_cached",
+    "list_folders_cached",
+    "start_vault_scan",
+    "list_notes_sorted",
+    "read_note",
+    "get_note_content",
+    "list_views",
+    "get_stale_cards",
+    "list_boards",
+    "get_board_config",
+];
+
+const QUICK_CAPTURE_COMMANDS: [&str; 4] = [
+    "create_note",
+    "get_inbox_config",
+    "get_scratchpad",
+    "append_to_scratchpad",
+];
+
+/// What a secondary window is for, and with it the commands it may invoke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowRole {
+    /// Board that can be looked at but not changed
+    BoardView,
+    /// Popup that only adds notes
+    QuickCapture,
+}
+
+impl WindowRole {
+    fn label_prefix(self) -> &'static str {
+        match self {
+            WindowRole::BoardView => "board-view",
+            WindowRole::QuickCapture => "quick-capture",
+        }
+    }
+
+    fn allows(self, command: &str) -> bool {
+        let allowed: &[&str] = match self {
+            WindowRole::BoardView => &BOARD_VIEW_COMMANDS,
+            WindowRole::QuickCapture => &QUICK_CAPTURE_COMMANDS,
+        };
+        BASE_COMMANDS.contains(&command) || allowed.contains(&command)
+    }
+}
+
+/// Reject commands outside the role of the window invoking them. Windows
+/// without a registered role (the main window) may invoke anything.
+pub(crate) fn check_command<R: Runtime>(message: &InvokeMessage<R>) -> Result<(), String> {
+    let webview = message.webview_ref();
+    let state = webview.state::<AppState>();
+    let roles = lock_or_err(&state.window_roles)?;
+    match roles.get(webview.label()) {
+        Some(role) if !role.allows(message.command()) => Err(format!(
+            "{} is not available in this window",
+            message.command()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Open a secondary window restricted to the commands of `role`
+#[tauri::command]
+pub fn open_secondary_window(
+    role: WindowRole,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<String, String> {
+    #[cfg(mobile)]
+    {
+        let _ = (role, app, state);
+        return Err("Secondary windows are not supported on mobile".to_string());
+    }
+
+    #[cfg(not(mobile))]
+    {
+        let label = format!(
+            "{}-{}",
+            role.label_prefix(),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        // Register before the page loads so its first command is already checked
+        lock_or_err(&state.window_roles)?.insert(label.clone(), role);
+        let (title, width, height) = match role {
+            WindowRole::BoardView => ("Board", 1000.0, 700.0),
+            WindowRole::QuickCapture => ("Quick capture", 480.0, 320.0),
+        };
+        let url = format!("index.html?window={}", role.label_prefix());
+        let built =
+            tauri::WebviewWindowBuilder::new(&app, &label, tauri::WebviewUrl::App(url.into()))
+                .title(title)
+                .inner_size(width, height)
+                .build();
+        let window = match built {
+            Ok(window) => window,
+            Err(e) => {
+                lock_or_err(&state.window_roles)?.remove(&label);
+                return Err(format!("Failed to open window: {}", e));
+            }
+        };
+        let closed_label = label.clone();
+        window.on_window_event(move |event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Ok(mut roles) = app.state::<AppState>().window_roles.lock() {
+                    roles.remove(&closed_label);
+                }
+            }
+        });
+        Ok(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricts_commands_by_role() {
+        assert!(WindowRole::BoardView.allows("list_notes"));
+        assert!(WindowRole::BoardView.allows("get_safe_mode"));
+        assert!(!WindowRole::BoardView.allows("update_note"));
+        assert!(WindowRole::QuickCapture.allows("create_note"));
+        assert!(!WindowRole::QuickCapture.allows("read_note"));
+        assert!(!WindowRole::QuickCapture.allows("delete_note"));
+    }
+}
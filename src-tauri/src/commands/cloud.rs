@@ -0,0 +1,182 @@
+use crate::commands::notes::{get_file_mtime, parse_note, NoteWithTags};
+use crate::lock_or_err;
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Suffix of the stub iCloud Drive leaves for an evicted file `.name.icloud`
+const ICLOUD_STUB_SUFFIX: &str = ".icloud";
+const HYDRATE_TIMEOUT: Duration = Duration::from_secs(120);
+const HYDRATE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A note whose content only exists in the cloud until it is downloaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudPlaceholder {
+    /// Path the note has once downloaded
+    pub path: String,
+    pub name: String,
+    pub relative_path: String,
+}
+
+/// Whether the file's data lives only with the cloud provider. Reading such
+/// a file blocks on (or fails without) a download.
+fn is_dehydrated(metadata: &fs::Metadata) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+        const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+        const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+        metadata.file_attributes()
+            & (FILE_ATTRIBUTE_OFFLINE
+                | FILE_ATTRIBUTE_RECALL_ON_OPEN
+                | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+            != 0
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::macos::fs::MetadataExt;
+        const SF_DATALESS: u32 = 0x40000000;
+        metadata.st_flags() & SF_DATALESS != 0
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+/// The note an iCloud stub (`.Note.md.icloud`) stands in for
+fn icloud_stub_target(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let original = name
+        .strip_prefix('.')?
+        .strip_suffix(ICLOUD_STUB_SUFFIX)
+        .filter(|original| original.ends_with(".md"))?;
+    Some(path.with_file_name(original))
+}
+
+/// The note path when `path` is a cloud placeholder of a note: an iCloud
+/// stub or a markdown file whose content has not been downloaded
+pub(crate) fn placeholder_note_path(path: &Path) -> Option<PathBuf> {
+    if let Some(target) = icloud_stub_target(path) {
+        return Some(target);
+    }
+    let dehydrated = path.extension().is_some_and(|ext| ext == "md")
+        && fs::symlink_metadata(path).is_ok_and(|metadata| is_dehydrated(&metadata));
+    dehydrated.then(|| path.to_path_buf())
+}
+
+pub(crate) fn placeholder_for(note_path: &Path, relative: &Path) -> CloudPlaceholder {
+    let relative = match icloud_stub_target(relative) {
+        Some(target) => target,
+        None => relative.to_path_buf(),
+    };
+    CloudPlaceholder {
+        path: note_path.to_string_lossy().to_string(),
+        name: note_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        relative_path: relative.to_string_lossy().to_string(),
+    }
+}
+
+/// Ask the provider for the file's content and wait until it is local
+fn hydrate(note_path: &Path) -> Result<(), String> {
+    let stub = note_path
+        .parent()
+        .zip(note_path.file_name())
+        .map(|(dir, name)| dir.join(format!(".{}{}", name.to_string_lossy(), ICLOUD_STUB_SUFFIX)));
+    if !note_path.exists() && stub.as_ref().is_some_and(|stub| stub.exists()) {
+        #[cfg(target_os = "macos")]
+        {
+            let status = std::process::Command::new("brctl")
+                .arg("download")
+                .arg(note_path)
+                .status()
+                .map_err(|e| format!("Failed to request download: {}", e))?;
+            if !status.success() {
+                return Err("iCloud refused to download the note".to_string());
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        return Err("iCloud downloads are only supported on macOS".to_string());
+    }
+
+    let started = Instant::now();
+    loop {
+        if note_path.exists() {
+            // Reading the whole file makes OneDrive and APFS recall its data
+            fs::read(note_path)
+                .map_err(|e| format!("Failed to download {}: {}", note_path.display(), e))?;
+            let metadata = fs::metadata(note_path).map_err(|e| e.to_string())?;
+            if !is_dehydrated(&metadata) {
+                return Ok(());
+            }
+        }
+        if started.elapsed() > HYDRATE_TIMEOUT {
+            return Err(format!("Timed out downloading {}", note_path.display()));
+        }
+        std::thread::sleep(HYDRATE_POLL_INTERVAL);
+    }
+}
+
+/// Download a placeholder note from its cloud provider and return it parsed
+#[tauri::command]
+pub async fn hydrate_note(file_path: String, app: AppHandle) -> Result<NoteWithTags, String> {
+    let path = PathBuf::from(&file_path);
+    let note_path = placeholder_note_path(&path).unwrap_or(path);
+    let path = note_path.clone();
+    tauri::async_runtime::spawn_blocking(move || hydrate(&path))
+        .await
+        .map_err(|e| format!("Download failed: {}", e))??;
+
+    let note = parse_note(&note_path)?;
+    let inline_tags = extract_inline_tags(&note.content);
+    let state = app.state::<AppState>();
+    if let Some(cache) = lock_or_err(&state.cache)?.as_ref() {
+        let content = fs::read_to_string(&note_path).unwrap_or_else(|_| note.content.clone());
+        let mtime = get_file_mtime(&note_path).unwrap_or(0);
+        if let Err(e) =
+            cache.upsert_note(&note, &compute_content_hash(&content), mtime, &inline_tags)
+        {
+            log::warn!("Cache update failed for downloaded note: {}", e);
+        }
+    }
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_icloud_stubs_to_their_note() {
+        assert_eq!(
+            placeholder_note_path(Path::new("/vault/work/.Plan.md.icloud")),
+            Some(PathBuf::from("/vault/work/Plan.md"))
+        );
+        assert_eq!(
+            icloud_stub_target(Path::new("/vault/.photo.png.icloud")),
+            None
+        );
+        assert_eq!(icloud_stub_target(Path::new("/vault/Plan.md")), None);
+
+        let placeholder = placeholder_for(
+            Path::new("/vault/work/Plan.md"),
+            Path::new("work/.Plan.md.icloud"),
+        );
+        assert_eq!(placeholder.relative_path, "work/Plan.md");
+        assert_eq!(placeholder.name, "Plan");
+    }
+}
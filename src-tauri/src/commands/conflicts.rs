@@ -0,0 +1,483 @@
+use crate::cache::conflicts::ConflictRecord;
+use crate::commands::audit;
+use crate::commands::mounts::ensure_writable;
+use crate::commands::notes::{
+    atomic_write, ensure_modifiable, get_file_mtime, is_skipped_dir_name, parse_note,
+    parse_note_content, record_write, record_written_content, serialize_note,
+    validate_existing_path_within_base, Note,
+};
+use crate::commands::trash::move_note_to_trash;
+use crate::lock_or_err;
+use crate::merge::union_merge_note;
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::State;
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+/// Marker inserted between the note stem and the timestamp of a conflict copy
+const CONFLICT_MARKER: &str = ".conflict-";
+/// Frontmatter key holding the provenance of a conflict copy
+const PROVENANCE_KEY: &str = "conflict";
+
+/// Marker Syncthing puts between the stem and the date of its conflict files
+const SYNCTHING_MARKER: &str = ".sync-conflict-";
+
+lazy_static! {
+    // Dropbox and Nextcloud: `note (conflicted copy).md`,
+    // `note (Alice's conflicted copy 2024-01-02).md`
+    static ref CONFLICTED_COPY_REGEX: Regex =
+        Regex::new(r"^(.*?) \([^()]*[Cc]onflicted [Cc]opy[^()]*\)$").unwrap();
+}
+
+pub const REASON_SYNC: &str = "sync";
+/// A save from the editor raced a write by another program
+pub const REASON_EXTERNAL_EDIT: &str = "external_edit";
+
+/// Where a conflict copy came from, stored under the `conflict` frontmatter key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictProvenance {
+    /// File name of the note this copy lost against (a sibling of the copy)
+    pub of: String,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictInfo {
+    pub file_path: String,
+    pub original_path: String,
+    pub original_exists: bool,
+    pub title: Option<String>,
+    pub reason: String,
+    pub source: Option<String>,
+    pub detected_at: String,
+}
+
+/// Build `<stem>.conflict-<timestamp>[-n].<ext>` for a file name
+pub(crate) fn conflict_file_name(original: &Path, counter: usize) -> String {
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
+    let stem = original
+        .file_stem()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_else(|| "conflict".to_string());
+    let extension = original
+        .extension()
+        .map(|value| format!(".{}", value.to_string_lossy()))
+        .unwrap_or_default();
+    if counter == 0 {
+        format!("{}{}{}{}", stem, CONFLICT_MARKER, timestamp, extension)
+    } else {
+        format!(
+            "{}{}{}-{}{}",
+            stem, CONFLICT_MARKER, timestamp, counter, extension
+        )
+    }
+}
+
+pub(crate) fn is_conflict_file(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.contains(CONFLICT_MARKER))
+        || third_party_conflict(path).is_some()
+}
+
+/// Stem of the original and the tool that made the copy, for conflict files
+/// left by Dropbox, Nextcloud or Syncthing
+fn third_party_conflict(path: &Path) -> Option<(&str, &'static str)> {
+    let stem = path.file_stem()?.to_str()?;
+    if let Some(idx) = stem.rfind(SYNCTHING_MARKER) {
+        return Some((&stem[..idx], "syncthing"));
+    }
+    let captures = CONFLICTED_COPY_REGEX.captures(stem)?;
+    Some((captures.get(1)?.as_str(), "conflicted_copy"))
+}
+
+/// Derive the original note path from a conflict copy's file name
+fn original_path_for(conflict_path: &Path) -> Option<PathBuf> {
+    let stem = conflict_path.file_stem()?.to_str()?;
+    let original_stem = match stem.rfind(CONFLICT_MARKER) {
+        Some(idx) => &stem[..idx],
+        None => third_party_conflict(conflict_path)?.0,
+    };
+    let extension = conflict_path
+        .extension()
+        .map(|value| format!(".{}", value.to_string_lossy()))
+        .unwrap_or_default();
+    Some(
+        conflict_path
+            .parent()?
+            .join(format!("{}{}", original_stem, extension)),
+    )
+}
+
+/// Cache record for a conflict file another sync tool left in the vault,
+/// found while scanning
+pub(crate) fn third_party_conflict_record(path: &Path) -> Option<ConflictRecord> {
+    let (_, source) = third_party_conflict(path)?;
+    let original = original_path_for(path)?;
+    Some(ConflictRecord {
+        source: Some(source.to_string()),
+        ..conflict_record(path, &original, REASON_SYNC, None)
+    })
+}
+
+/// Give a losing note version a fresh identity and provenance frontmatter so it
+/// can live next to the original without colliding in the cache. The
+/// frontmatter is rewritten in the syntax it was read in.
+/// Content that is not UTF-8 or can't be parsed is returned unchanged.
+pub(crate) fn add_conflict_provenance(
+    bytes: &[u8],
+    original_name: &str,
+    reason: &str,
+    source: Option<&str>,
+) -> Vec<u8> {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return bytes.to_vec();
+    };
+    let Ok(mut note) = parse_note_content(text, Path::new(original_name)) else {
+        return bytes.to_vec();
+    };
+
+    let provenance = ConflictProvenance {
+        of: original_name.to_string(),
+        reason: reason.to_string(),
+        source: source.map(str::to_string),
+        detected_at: Utc::now(),
+    };
+    let frontmatter = &mut note.frontmatter;
+    frontmatter.id = Uuid::new_v4().to_string();
+    frontmatter.modified = Utc::now();
+    frontmatter.title.push_str(" (Conflict)");
+    if let Ok(value) = serde_yaml::to_value(&provenance) {
+        frontmatter.extra.insert(PROVENANCE_KEY.into(), value);
+    }
+    serialize_note(&note.frontmatter, &note.content).into_bytes()
+}
+
+/// Write the losing version of `original` next to it as a conflict copy and
+/// return the path of the copy
+pub(crate) fn write_conflict_copy(
+    original: &Path,
+    bytes: &[u8],
+    reason: &str,
+    source: Option<&str>,
+) -> Result<PathBuf, String> {
+    let parent = original.parent().ok_or("Invalid note path")?;
+    let mut candidate = parent.join(conflict_file_name(original, 0));
+    let mut counter = 1;
+    while candidate.exists() {
+        candidate = parent.join(conflict_file_name(original, counter));
+        counter += 1;
+    }
+
+    let original_name = original
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let bytes = if original.extension().is_some_and(|ext| ext == "md") {
+        add_conflict_provenance(bytes, &original_name, reason, source)
+    } else {
+        bytes.to_vec()
+    };
+
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    fs::write(&candidate, bytes).map_err(|e| format!("Failed to write conflict copy: {}", e))?;
+    Ok(candidate)
+}
+
+/// Build the cache record for a freshly written conflict copy
+pub(crate) fn conflict_record(
+    conflict_path: &Path,
+    original: &Path,
+    reason: &str,
+    source: Option<&str>,
+) -> ConflictRecord {
+    ConflictRecord {
+        file_path: conflict_path.to_string_lossy().to_string(),
+        original_path: original.to_string_lossy().to_string(),
+        reason: reason.to_string(),
+        source: source.map(str::to_string),
+        detected_at: Utc::now().to_rfc3339(),
+    }
+}
+
+fn inspect_conflict(path: &Path) -> Option<ConflictInfo> {
+    let note = parse_note(&path.to_path_buf()).ok();
+    let provenance = note
+        .as_ref()
+        .and_then(|note| note.frontmatter.extra.get(PROVENANCE_KEY))
+        .and_then(|value| serde_yaml::from_value::<ConflictProvenance>(value.clone()).ok());
+    let title = note.map(|note| note.frontmatter.title);
+
+    let (original_path, reason, source, detected_at) = match provenance {
+        Some(provenance) => (
+            path.parent()?.join(&provenance.of),
+            provenance.reason,
+            provenance.source,
+            provenance.detected_at.to_rfc3339(),
+        ),
+        None => {
+            let third_party = third_party_conflict(path).map(|(_, source)| source.to_string());
+            let detected_at = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+                .and_then(|elapsed| DateTime::from_timestamp(elapsed.as_secs() as i64, 0))
+                .unwrap_or_else(Utc::now);
+            let reason = match third_party {
+                Some(_) => REASON_SYNC.to_string(),
+                None => "unknown".to_string(),
+            };
+            (
+                original_path_for(path)?,
+                reason,
+                third_party,
+                detected_at.to_rfc3339(),
+            )
+        }
+    };
+
+    Some(ConflictInfo {
+        file_path: path.to_string_lossy().to_string(),
+        original_exists: original_path.exists(),
+        original_path: original_path.to_string_lossy().to_string(),
+        title,
+        reason,
+        source,
+        detected_at,
+    })
+}
+
+/// List every conflict copy in the vault with its provenance, newest first
+#[tauri::command]
+pub fn list_conflicts(
+    notes_dir: String,
+    state: State<AppState>,
+) -> Result<Vec<ConflictInfo>, String> {
+    let base_path = PathBuf::from(&notes_dir);
+    if !base_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut conflicts = Vec::new();
+    for entry in WalkDir::new(&base_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| {
+            !e.file_name()
+                .to_str()
+                .map(is_skipped_dir_name)
+                .unwrap_or(false)
+        })
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if !entry.file_type().is_file()
+            || !path.extension().is_some_and(|ext| ext == "md")
+            || !is_conflict_file(path)
+        {
+            continue;
+        }
+        if let Some(info) = inspect_conflict(path) {
+            conflicts.push(info);
+        }
+    }
+
+    conflicts.sort_by(|a, b| b.detected_at.cmp(&a.detected_at));
+
+    let cache_lock = lock_or_err(&state.cache)?;
+    if let Some(cache) = cache_lock.as_ref() {
+        let seen: HashSet<String> = conflicts.iter().map(|c| c.file_path.clone()).collect();
+        for conflict in &conflicts {
+            let record = ConflictRecord {
+                file_path: conflict.file_path.clone(),
+                original_path: conflict.original_path.clone(),
+                reason: conflict.reason.clone(),
+                source: conflict.source.clone(),
+                detected_at: conflict.detected_at.clone(),
+            };
+            if let Err(e) = cache.upsert_conflict(&record) {
+                log::warn!("Failed to index conflict: {}", e);
+            }
+        }
+        if let Err(e) = cache.remove_conflicts_not_in(&seen) {
+            log::warn!("Failed to remove stale conflict entries: {}", e);
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Fold a conflict copy back into its original: frontmatter and body lines
+/// from both are kept, then the copy goes to the trash. Returns the merged
+/// note.
+#[tauri::command]
+pub fn merge_conflict(
+    notes_dir: String,
+    original: String,
+    conflict: String,
+    state: State<AppState>,
+) -> Result<Note, String> {
+    let result = merge_conflict_copy(&notes_dir, &original, &conflict, &state);
+    audit::record(&state, "merge", &conflict, Some(&original), &result);
+    result
+}
+
+fn merge_conflict_copy(
+    notes_dir: &str,
+    original: &str,
+    conflict: &str,
+    state: &State<AppState>,
+) -> Result<Note, String> {
+    let base_path = PathBuf::from(notes_dir);
+    let original_path = PathBuf::from(original);
+    let conflict_path = PathBuf::from(conflict);
+    validate_existing_path_within_base(&original_path, &base_path)?;
+    validate_existing_path_within_base(&conflict_path, &base_path)?;
+    if original_path == conflict_path {
+        return Err("A note cannot be merged with itself".to_string());
+    }
+    ensure_writable(&original_path, state)?;
+    ensure_writable(&conflict_path, state)?;
+    ensure_modifiable(&parse_note(&original_path)?.frontmatter, false)?;
+
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    };
+    let merged = union_merge_note(
+        &read(&original_path)?,
+        &read(&conflict_path)?,
+        &["id", "title", "created", PROVENANCE_KEY],
+    )
+    .ok_or("Both versions need valid frontmatter to be merged")?;
+
+    record_written_content(original, &merged, state);
+    atomic_write(&original_path, &merged)?;
+    let note = parse_note(&original_path)?;
+    record_write(conflict, state);
+    let title = parse_note(&conflict_path)
+        .ok()
+        .map(|note| note.frontmatter.title);
+    move_note_to_trash(&base_path, &conflict_path, title)?;
+
+    if let Some(cache) = lock_or_err(&state.cache)?.as_ref() {
+        let hash = compute_content_hash(&merged);
+        let mtime = get_file_mtime(&original_path).unwrap_or(0);
+        let tags = extract_inline_tags(&note.content);
+        if let Err(e) = cache.upsert_note(&note, &hash, mtime, &tags) {
+            log::warn!("Cache update failed for merged note: {}", e);
+        }
+        if let Err(e) = cache.remove_note(conflict) {
+            log::warn!("Cache remove failed for merged conflict: {}", e);
+        }
+        if let Err(e) = cache.remove_conflict(conflict) {
+            log::warn!("Failed to remove conflict entry: {}", e);
+        }
+    }
+
+    Ok(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::FrontmatterFormat;
+
+    #[test]
+    fn derives_original_from_conflict_name() {
+        let name = conflict_file_name(Path::new("/vault/todo.md"), 2);
+        assert!(name.starts_with("todo.conflict-"));
+        assert!(name.ends_with("-2.md"));
+
+        let conflict = Path::new("/vault").join(name);
+        assert!(is_conflict_file(&conflict));
+        assert_eq!(
+            original_path_for(&conflict),
+            Some(PathBuf::from("/vault/todo.md"))
+        );
+        assert!(!is_conflict_file(Path::new("/vault/todo.md")));
+
+        for (name, source) in [
+            ("todo (conflicted copy).md", "conflicted_copy"),
+            (
+                "todo (Alice's conflicted copy 2024-01-02).md",
+                "conflicted_copy",
+            ),
+            ("todo.sync-conflict-20240102-101010-ABCDEFG.md", "syncthing"),
+        ] {
+            let record = third_party_conflict_record(&Path::new("/vault").join(name)).unwrap();
+            assert_eq!(record.original_path, "/vault/todo.md");
+            assert_eq!(record.source.as_deref(), Some(source));
+        }
+        assert!(third_party_conflict_record(Path::new("/vault/copy (draft).md")).is_none());
+    }
+
+    #[test]
+    fn writes_provenance_frontmatter() {
+        let original = br#"---
+id: original
+title: Test
+column: todo
+---
+
+Body"#;
+        let rewritten = String::from_utf8(add_conflict_provenance(
+            original,
+            "test.md",
+            REASON_SYNC,
+            Some("remote"),
+        ))
+        .unwrap();
+        assert!(rewritten.contains("title: Test (Conflict)"));
+        assert!(rewritten.contains("of: test.md"));
+        assert!(rewritten.contains("reason: sync"));
+        assert!(rewritten.ends_with("\n\nBody"));
+    }
+
+    #[test]
+    fn rewrites_identity_of_toml_conflict_copies() {
+        let original = b"+++\nid = \"original\"\ntitle = \"Launch\"\ndraft = true\n+++\n\nBody";
+        let rewritten = add_conflict_provenance(original, "launch.md", REASON_SYNC, None);
+        let copy = parse_note_content(
+            std::str::from_utf8(&rewritten).unwrap(),
+            Path::new("/vault/launch.conflict.md"),
+        )
+        .unwrap();
+        assert_eq!(copy.frontmatter.format, FrontmatterFormat::Toml);
+        assert_ne!(copy.frontmatter.id, "original");
+        assert_eq!(copy.frontmatter.title, "Launch (Conflict)");
+        assert!(copy.frontmatter.extra.contains_key(PROVENANCE_KEY));
+        assert!(copy.frontmatter.extra.contains_key("draft"));
+        assert_eq!(copy.content, "Body");
+    }
+
+    #[test]
+    fn inspects_provenance_of_toml_conflict_copies() {
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        let original = b"+++\nid = \"original\"\ntitle = \"A --- B\"\n+++\n\nBody";
+        let rewritten = add_conflict_provenance(original, "launch.md", REASON_SYNC, Some("lan"));
+        let copy = vault.write(
+            "launch.conflict-20240101.md",
+            std::str::from_utf8(&rewritten).unwrap(),
+        );
+
+        let info = inspect_conflict(Path::new(&copy)).unwrap();
+        assert_eq!(info.title.as_deref(), Some("A --- B (Conflict)"));
+        assert_eq!(info.original_path, vault.path("launch.md"));
+        assert_eq!(info.reason, REASON_SYNC);
+        assert_eq!(info.source.as_deref(), Some("lan"));
+    }
+}
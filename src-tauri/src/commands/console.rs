@@ -0,0 +1,205 @@
+use crate::lock_or_err;
+use crate::AppState;
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+pub(crate) const ADVANCED_MODE_KEY: &str = "advanced_mode";
+const DEFAULT_MAX_ROWS: usize = 500;
+const MAX_ROWS_LIMIT: usize = 5000;
+/// Queries still running after this long are interrupted
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// True when more rows were available than the row limit allowed
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+fn value_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+        ValueRef::Blob(blob) => format!("<{} byte blob>", blob.len()).into(),
+    }
+}
+
+/// Run a single read-only statement, stopping after `max_rows` rows or once
+/// `timeout` has elapsed
+fn execute_readonly(
+    conn: &Connection,
+    sql: &str,
+    max_rows: usize,
+    timeout: Duration,
+) -> Result<QueryResult, String> {
+    let started = Instant::now();
+    // ATTACH and DETACH pass as read-only but would let a query open any
+    // database file on disk
+    conn.authorizer(Some(|context: AuthContext<'_>| match context.action {
+        AuthAction::Attach { .. } | AuthAction::Detach { .. } => Authorization::Deny,
+        _ => Authorization::Allow,
+    }))
+    .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Invalid query: {}", e))?;
+    if !stmt.readonly() {
+        return Err("Only read-only queries are allowed".to_string());
+    }
+    let columns: Vec<String> = stmt
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    // Interrupt the query from a watchdog thread unless it finishes in time
+    let interrupt = conn.get_interrupt_handle();
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let watchdog = thread::spawn(move || {
+        if let Err(mpsc::RecvTimeoutError::Timeout) = done_rx.recv_timeout(timeout) {
+            interrupt.interrupt();
+        }
+    });
+
+    let result = (|| {
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut query = stmt.query([])?;
+        while let Some(row) = query.next()? {
+            if rows.len() == max_rows {
+                truncated = true;
+                break;
+            }
+            let values = (0..columns.len())
+                .map(|i| row.get_ref(i).map(value_to_json))
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.push(values);
+        }
+        Ok::<_, rusqlite::Error>((rows, truncated))
+    })();
+
+    let _ = done_tx.send(());
+    let _ = watchdog.join();
+
+    let (rows, truncated) = result.map_err(|e| match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::OperationInterrupted) => {
+            format!("Query exceeded the {}s time limit", timeout.as_secs())
+        }
+        _ => format!("Query failed: {}", e),
+    })?;
+
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[tauri::command]
+pub fn get_advanced_mode(state: State<AppState>) -> Result<bool, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let Some(cache) = cache_lock.as_ref() else {
+        return Ok(false);
+    };
+    Ok(cache.get_meta(ADVANCED_MODE_KEY)?.as_deref() == Some("true"))
+}
+
+#[tauri::command]
+pub fn set_advanced_mode(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(ADVANCED_MODE_KEY, if enabled { "true" } else { "false" })
+}
+
+/// Execute a SELECT against a read-only connection to the cache database.
+/// Only available once advanced mode has been enabled.
+#[tauri::command]
+pub fn run_readonly_query(
+    sql: String,
+    max_rows: Option<usize>,
+    state: State<AppState>,
+) -> Result<QueryResult, String> {
+    // Open a dedicated connection and release the cache lock before running,
+    // so a slow query never blocks regular cache access
+    let conn = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        if cache.get_meta(ADVANCED_MODE_KEY)?.as_deref() != Some("true") {
+            return Err("The query console requires advanced mode".to_string());
+        }
+        cache.open_readonly_connection()?
+    };
+
+    let max_rows = max_rows
+        .unwrap_or(DEFAULT_MAX_ROWS)
+        .clamp(1, MAX_ROWS_LIMIT);
+    execute_readonly(&conn, &sql, max_rows, QUERY_TIMEOUT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE t (id INTEGER, name TEXT);
+             INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn limits_rows_and_rejects_writes() {
+        let conn = test_connection();
+        let result = execute_readonly(
+            &conn,
+            "SELECT id, name FROM t ORDER BY id",
+            2,
+            QUERY_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["id", "name"]);
+        assert_eq!(result.rows.len(), 2);
+        assert!(result.truncated);
+        assert_eq!(
+            result.rows[1],
+            vec![serde_json::json!(2), serde_json::json!("b")]
+        );
+
+        assert!(execute_readonly(&conn, "DELETE FROM t", 10, QUERY_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn rejects_attaching_databases() {
+        let conn = test_connection();
+        let other =
+            std::env::temp_dir().join(format!("noteban-attach-{}.db", uuid::Uuid::new_v4()));
+        let attach = format!("ATTACH DATABASE '{}' AS other", other.display());
+        assert!(execute_readonly(&conn, &attach, 10, QUERY_TIMEOUT).is_err());
+        assert!(execute_readonly(&conn, "DETACH DATABASE temp", 10, QUERY_TIMEOUT).is_err());
+        assert!(!other.exists());
+        assert!(execute_readonly(&conn, "SELECT count(*) FROM t", 10, QUERY_TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn interrupts_slow_queries() {
+        let conn = test_connection();
+        let sql = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c)
+                   SELECT count(*) FROM c";
+        let err = execute_readonly(&conn, sql, 10, Duration::from_millis(50)).unwrap_err();
+        assert!(err.contains("time limit"), "{}", err);
+    }
+}
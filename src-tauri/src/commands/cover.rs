@@ -0,0 +1,161 @@
+use crate::commands::import::{MARKDOWN_IMAGE_REGEX, WIKILINK_REGEX};
+use crate::commands::notes::{
+    parse_note, update_note_frontmatter, validate_existing_path_within_base, NoteWithTags,
+};
+use crate::commands::{audit, broadcast};
+use crate::AppState;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "webp", "svg", "avif", "bmp"];
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Check that `cover`, relative to the note's folder, is an image file in the
+/// vault. Returns it with forward slashes.
+fn validate_cover(notes_dir: &Path, note_path: &Path, cover: &str) -> Result<String, String> {
+    let cover = cover.trim().replace('\\', "/");
+    let note_dir = note_path.parent().ok_or("Invalid note path")?;
+    let path = note_dir.join(&cover);
+    if cover.is_empty() || !is_image(&path) {
+        return Err(format!("Cover must be an image file: {}", cover));
+    }
+    validate_existing_path_within_base(&path, notes_dir)
+        .map_err(|_| format!("Cover image not found: {}", cover))?;
+    if !path.is_file() {
+        return Err(format!("Cover image not found: {}", cover));
+    }
+    Ok(cover)
+}
+
+/// First image the note embeds that exists in the vault, or else the first
+/// image in its attachments folder
+fn first_image(notes_dir: &Path, note_path: &Path, content: &str) -> Option<String> {
+    let stem = note_path.file_stem()?.to_string_lossy().to_string();
+    let attachments = format!("{}.attachments", stem);
+
+    let mut embeds: Vec<(usize, Vec<String>)> = MARKDOWN_IMAGE_REGEX
+        .captures_iter(content)
+        .filter(|caps| !caps[2].contains("://"))
+        .map(|caps| {
+            let target = urlencoding::decode(&caps[2])
+                .map(|decoded| decoded.into_owned())
+                .unwrap_or_else(|_| caps[2].to_string());
+            (caps.get(0).map_or(0, |m| m.start()), vec![target])
+        })
+        .collect();
+    // Obsidian-style `![[image.png]]` embeds name a file next to the note or
+    // in its attachments folder
+    embeds.extend(
+        WIKILINK_REGEX
+            .captures_iter(content)
+            .filter(|caps| &caps[1] == "!")
+            .map(|caps| {
+                let target = caps[2].split('|').next().unwrap_or_default().trim();
+                (
+                    caps.get(0).map_or(0, |m| m.start()),
+                    vec![target.to_string(), format!("{}/{}", attachments, target)],
+                )
+            }),
+    );
+    embeds.sort_by_key(|(start, _)| *start);
+    let embedded = embeds
+        .into_iter()
+        .flat_map(|(_, candidates)| candidates)
+        .find_map(|candidate| validate_cover(notes_dir, note_path, &candidate).ok());
+    if embedded.is_some() {
+        return embedded;
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(note_path.parent()?.join(&attachments))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_image(path))
+        .collect();
+    files.sort();
+    let name = files.first()?.file_name()?.to_string_lossy().to_string();
+    Some(format!("{}/{}", attachments, name))
+}
+
+fn set_cover(
+    notes_dir: &str,
+    file_path: &str,
+    cover: Option<String>,
+    state: &State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = update_note_frontmatter(notes_dir, file_path, state, |frontmatter| {
+        frontmatter.cover = cover;
+        Ok(())
+    });
+    audit::record(state, "update", file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+/// Set or clear (`None`) a note's cover image, given relative to the note's
+/// folder like image links in its body
+#[tauri::command]
+pub fn set_note_cover(
+    notes_dir: String,
+    file_path: String,
+    cover: Option<String>,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let cover = cover
+        .filter(|cover| !cover.trim().is_empty())
+        .map(|cover| validate_cover(Path::new(&notes_dir), Path::new(&file_path), &cover))
+        .transpose()?;
+    set_cover(&notes_dir, &file_path, cover, &state)
+}
+
+/// Use the first image of a note as its cover
+#[tauri::command]
+pub fn pick_note_cover(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, Path::new(&notes_dir))?;
+    let note = parse_note(&path)?;
+    let cover =
+        first_image(Path::new(&notes_dir), &path, &note.content).ok_or("The note has no images")?;
+    set_cover(&notes_dir, &file_path, Some(cover), &state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn picks_first_existing_embedded_image() {
+        let vault = std::env::temp_dir().join(format!("noteban-cover-{}", Uuid::new_v4()));
+        fs::create_dir_all(vault.join("plan.attachments")).unwrap();
+        fs::write(vault.join("plan.attachments/a.png"), "png").unwrap();
+        fs::write(vault.join("plan.attachments/my chart.png"), "png").unwrap();
+        let note = vault.join("plan.md");
+
+        let content = "![gone](missing.png) ![[my chart.png|300]] ![a](plan.attachments/a.png)";
+        assert_eq!(
+            first_image(&vault, &note, content).as_deref(),
+            Some("plan.attachments/my chart.png")
+        );
+        assert_eq!(
+            first_image(&vault, &note, "no images").as_deref(),
+            Some("plan.attachments/a.png")
+        );
+        assert!(validate_cover(&vault, &note, "../outside.png").is_err());
+        assert!(validate_cover(&vault, &note, "plan.md").is_err());
+
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+use crate::commands::audit;
+use crate::commands::history::active_profile_id;
+use crate::commands::mounts::ensure_writable;
+use crate::commands::notes::{
+    atomic_write, ensure_modifiable, get_file_mtime, parse_note_content, record_written_content,
+    serialize_note, validate_existing_path_within_base, Note, NoteFrontmatter,
+};
+use crate::commands::sync::normalize_relative_path;
+use crate::crypto;
+use crate::history::note_history_dir;
+use crate::lock_or_err;
+use crate::logging;
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::AppState;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Frontmatter key marking a locked note; holds the key derivation salt
+const ENCRYPTED_KEY: &str = "encrypted";
+const SALT_KEY: &str = "salt";
+/// Hex characters per line of the encrypted body
+const ARMOR_WIDTH: usize = 64;
+
+/// Whether the note's body and metadata are encrypted on disk
+pub(crate) fn is_encrypted(frontmatter: &NoteFrontmatter) -> bool {
+    frontmatter.extra.contains_key(ENCRYPTED_KEY)
+}
+
+fn locked_salt(frontmatter: &NoteFrontmatter) -> Result<Vec<u8>, String> {
+    let salt = frontmatter
+        .extra
+        .get(ENCRYPTED_KEY)
+        .and_then(|value| value.get(SALT_KEY))
+        .and_then(Value::as_str)
+        .ok_or("Note is not locked")?;
+    crypto::from_hex(salt)
+}
+
+/// Encrypt a whole note into a locked note that only keeps what the board
+/// needs to place the card (id, title, dates, column, order) in the clear
+fn lock_text(text: &str, passphrase: &str) -> Result<String, String> {
+    let note = parse_note_content(text, Path::new(""))?;
+    if is_encrypted(&note.frontmatter) {
+        return Err("Note is already locked".to_string());
+    }
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+
+    let salt = crypto::random_salt();
+    let key = crypto::derive_key(passphrase, &salt)?;
+    let hex = crypto::to_hex(&crypto::encrypt(&key, text.as_bytes())?);
+    let armored = hex
+        .as_bytes()
+        .chunks(ARMOR_WIDTH)
+        .map(|line| String::from_utf8_lossy(line).to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut lock = Mapping::new();
+    lock.insert(SALT_KEY.into(), crypto::to_hex(&salt).into());
+    let frontmatter = NoteFrontmatter {
+        tags: Vec::new(),
+        aliases: Vec::new(),
+        extra: Mapping::from_iter([(ENCRYPTED_KEY.into(), Value::Mapping(lock))]),
+        ..note.frontmatter
+    };
+    Ok(serialize_note(&frontmatter, &armored))
+}
+
+/// Decrypt a locked note back to plain markdown. Card placement changed
+/// while the note was locked is kept.
+fn unlock_text(text: &str, passphrase: &str) -> Result<String, String> {
+    let locked = parse_note_content(text, Path::new(""))?;
+    let key = crypto::derive_key(passphrase, &locked_salt(&locked.frontmatter)?)?;
+    let hex: String = locked
+        .content
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let plaintext = crypto::decrypt(&key, &crypto::from_hex(&hex)?)
+        .map_err(|_| "Wrong passphrase or corrupted note".to_string())?;
+    let plaintext = String::from_utf8(plaintext).map_err(|_| "Decrypted note is not text")?;
+
+    let note = parse_note_content(&plaintext, Path::new(""))?;
+    let clear = locked.frontmatter;
+    let frontmatter = NoteFrontmatter {
+        title: clear.title,
+        modified: clear.modified,
+        date: clear.date,
+        due: clear.due,
+        column: clear.column,
+        order: clear.order,
+        pinned: clear.pinned,
+        archived: clear.archived,
+        priority: clear.priority,
+        color: clear.color,
+        cover: clear.cover,
+        icon: clear.icon,
+        ..note.frontmatter
+    };
+    Ok(serialize_note(&frontmatter, &note.content))
+}
+
+/// Drop copies of the note's plaintext kept outside the vault: version
+/// history and the last synced content
+fn forget_plaintext(notes_dir: &str, path: &Path, note_id: &str, state: &State<AppState>) {
+    let Some(profile_id) = active_profile_id(state) else {
+        return;
+    };
+    if let Ok(dir) = note_history_dir(&profile_id, note_id) {
+        if dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                log::warn!("Failed to remove history of locked note: {}", e);
+            }
+        }
+    }
+    let relative = path
+        .strip_prefix(notes_dir)
+        .ok()
+        .map(normalize_relative_path);
+    if let (Some(relative), Ok(cache_lock)) = (relative, lock_or_err(&state.cache)) {
+        if let Some(cache) = cache_lock.as_ref() {
+            if let Err(e) = cache.remove_sync_base(&relative) {
+                log::warn!("Failed to remove sync base of locked note: {}", e);
+            }
+        }
+    }
+}
+
+/// Replace a note's text with what `transform` makes of it, refusing
+/// notes marked `locked: true`
+fn rewrite_note(
+    notes_dir: &str,
+    file_path: &str,
+    state: &State<AppState>,
+    transform: impl FnOnce(&str) -> Result<String, String>,
+) -> Result<Note, String> {
+    let result = write_rewritten_note(notes_dir, file_path, state, transform);
+    audit::record(state, "update", file_path, None, &result);
+    result
+}
+
+fn write_rewritten_note(
+    notes_dir: &str,
+    file_path: &str,
+    state: &State<AppState>,
+    transform: impl FnOnce(&str) -> Result<String, String>,
+) -> Result<Note, String> {
+    let path = PathBuf::from(file_path);
+    validate_existing_path_within_base(&path, Path::new(notes_dir))?;
+    ensure_writable(&path, state)?;
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    ensure_modifiable(&parse_note_content(&text, &path)?.frontmatter, false)?;
+    let updated = transform(&text)?;
+
+    record_written_content(file_path, &updated, state);
+    atomic_write(&path, &updated)?;
+    let note = parse_note_content(&updated, &path)?;
+    if let Some(cache) = lock_or_err(&state.cache)?.as_ref() {
+        let mtime = get_file_mtime(&path).unwrap_or(0);
+        // Locked notes are cached with their ciphertext and no inline tags
+        let inline_tags = if is_encrypted(&note.frontmatter) {
+            Vec::new()
+        } else {
+            extract_inline_tags(&note.content)
+        };
+        if let Err(e) =
+            cache.upsert_note(&note, &compute_content_hash(&updated), mtime, &inline_tags)
+        {
+            log::warn!(
+                "Cache update failed for note {}: {}",
+                logging::path(file_path),
+                e
+            );
+        }
+    }
+    Ok(note)
+}
+
+/// Encrypt a note at rest with a passphrase
+#[tauri::command]
+pub fn lock_note(
+    notes_dir: String,
+    file_path: String,
+    passphrase: String,
+    state: State<AppState>,
+) -> Result<Note, String> {
+    let note = rewrite_note(&notes_dir, &file_path, &state, |text| {
+        lock_text(text, &passphrase)
+    })?;
+    forget_plaintext(
+        &notes_dir,
+        Path::new(&file_path),
+        &note.frontmatter.id,
+        &state,
+    );
+    Ok(note)
+}
+
+/// Decrypt a locked note back to plain markdown
+#[tauri::command]
+pub fn unlock_note(
+    notes_dir: String,
+    file_path: String,
+    passphrase: String,
+    state: State<AppState>,
+) -> Result<Note, String> {
+    rewrite_note(&notes_dir, &file_path, &state, |text| {
+        unlock_text(text, &passphrase)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_everything_but_card_placement() {
+        let text = "---\nid: n1\ntitle: Bank\ncreated: 2024-01-01T00:00:00Z\nmodified: 2024-01-01T00:00:00Z\ncolumn: todo\ntags: [finance]\norder: 2\nsecret: yes\n---\n\nPIN 1234 #private";
+        let locked = lock_text(text, "hunter2").unwrap();
+        assert!(
+            !locked.contains("PIN") && !locked.contains("finance") && !locked.contains("secret")
+        );
+        let note = parse_note_content(&locked, Path::new("")).unwrap();
+        assert!(is_encrypted(&note.frontmatter));
+        assert_eq!(note.frontmatter.title, "Bank");
+        assert!(lock_text(&locked, "hunter2").is_err());
+        assert!(unlock_text(&locked, "wrong").is_err());
+
+        let moved = locked.replace("column: todo", "column: done");
+        let unlocked =
+            parse_note_content(&unlock_text(&moved, "hunter2").unwrap(), Path::new("")).unwrap();
+        assert!(!is_encrypted(&unlocked.frontmatter));
+        assert_eq!(unlocked.frontmatter.column, "done");
+        assert_eq!(unlocked.frontmatter.tags, vec!["finance"]);
+        assert_eq!(unlocked.content, "PIN 1234 #private");
+    }
+
+    #[test]
+    fn refuses_locked_notes_and_forgets_the_sync_base() {
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        let pinned = vault.note("pinned.md", "pinned", "locked: true\n");
+        let bank = vault.note("bank.md", "bank", "");
+        {
+            let state = vault.state();
+            let cache_lock = state.cache.lock().unwrap();
+            let cache = cache_lock.as_ref().unwrap();
+            cache.set_sync_base("bank.md", "hash", b"plain").unwrap();
+        }
+
+        let lock = |file_path: &str| {
+            lock_note(
+                vault.notes_dir(),
+                file_path.to_string(),
+                "hunter2".to_string(),
+                vault.state(),
+            )
+        };
+        assert!(lock(&pinned).is_err());
+        assert!(!vault.read("pinned.md").contains(ENCRYPTED_KEY));
+        assert!(lock(&bank).is_ok());
+
+        let state = vault.state();
+        let cache_lock = state.cache.lock().unwrap();
+        let cache = cache_lock.as_ref().unwrap();
+        assert!(cache.get_sync_base("bank.md").unwrap().is_none());
+        let log = cache.get_audit_log(None, 10).unwrap();
+        let errors: Vec<_> = log.iter().map(|entry| entry.error.is_some()).collect();
+        assert_eq!(errors, [false, true]);
+    }
+}
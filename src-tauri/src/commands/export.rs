@@ -0,0 +1,1055 @@
+use crate::cache::queries::CachedNote;
+use crate::commands::import::{relative_link, WIKILINK_REGEX};
+use crate::commands::notes::{
+    atomic_write, ensure_safe_relative_path, parse_note, read_vault,
+    validate_existing_path_within_base, Note,
+};
+use crate::commands::report::escape_html;
+use crate::commands::symlinks::symlink_allowlist;
+use crate::lock_or_err;
+use crate::AppState;
+use lazy_static::lazy_static;
+use pulldown_cmark::{html, Options, Parser};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Gap between card positions, as Trello spaces them
+const TRELLO_POS_STEP: f64 = 16384.0;
+
+lazy_static! {
+    // `- [ ] item` / `* [x] item`
+    static ref CHECKBOX_REGEX: Regex = Regex::new(r"^\s*[-*+] \[([ xX])\] (.*)$").unwrap();
+    static ref HEADING_REGEX: Regex = Regex::new(r"^#{1,6}\s+(.+?)\s*$").unwrap();
+}
+
+const DEFAULT_CSV_FIELDS: [&str; 8] = [
+    "title", "column", "tags", "created", "modified", "date", "order", "path",
+];
+
+/// Which notes to export; every set condition must match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportQuery {
+    /// Case-insensitive match against title and content
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub column: Option<String>,
+    /// Only notes below this directory
+    #[serde(default)]
+    pub folder: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvExportSummary {
+    pub path: String,
+    pub rows: usize,
+}
+
+const SITE_INDEX_NAME: &str = "index.html";
+const SITE_STYLE: &str = "body{max-width:46rem;margin:2rem auto;padding:0 1rem;font-family:system-ui,sans-serif;line-height:1.6}img{max-width:100%}pre{overflow-x:auto;background:#f4f4f4;padding:.75rem}table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:.25rem .5rem}.tags{color:#666}";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HtmlExportOptions {
+    /// Vault-relative folder to publish; the whole vault when unset
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Heading of the index page
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HtmlExportSummary {
+    pub output_dir: String,
+    pub pages: usize,
+    pub attachments_copied: usize,
+    /// Wikilinks that match no exported note, as `<page>: <target>`
+    pub broken_links: Vec<String>,
+}
+
+/// Chromium-based browsers that can print a page to PDF headlessly
+const PDF_BROWSER_NAMES: [&str; 7] = [
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "microsoft-edge",
+    "msedge",
+    "brave-browser",
+];
+const PDF_BROWSER_PATHS: [&str; 6] = [
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+];
+
+/// Column from the board settings; names the exported lists and fixes their order
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportColumn {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloListOut {
+    id: String,
+    name: String,
+    closed: bool,
+    pos: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloLabelOut {
+    id: String,
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCardOut {
+    id: String,
+    name: String,
+    desc: String,
+    id_list: String,
+    closed: bool,
+    due: Option<String>,
+    pos: f64,
+    id_labels: Vec<String>,
+    labels: Vec<TrelloLabelOut>,
+    id_checklists: Vec<String>,
+    date_last_activity: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCheckItemOut {
+    id: String,
+    name: String,
+    state: &'static str,
+    pos: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloChecklistOut {
+    id: String,
+    id_card: String,
+    name: String,
+    pos: f64,
+    check_items: Vec<TrelloCheckItemOut>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloBoardOut {
+    name: String,
+    lists: Vec<TrelloListOut>,
+    cards: Vec<TrelloCardOut>,
+    checklists: Vec<TrelloChecklistOut>,
+    labels: Vec<TrelloLabelOut>,
+}
+
+/// A run of checkboxes pulled out of a note, named after the heading above it
+#[derive(Debug, PartialEq)]
+struct Checklist {
+    name: String,
+    items: Vec<(String, bool)>,
+}
+
+/// Separate checkbox runs from the rest of a note. A heading directly above a
+/// run names the checklist and is removed from the description.
+fn split_checklists(content: &str) -> (String, Vec<Checklist>) {
+    let mut description: Vec<&str> = Vec::new();
+    let mut checklists: Vec<Checklist> = Vec::new();
+    let mut in_run = false;
+
+    for line in content.lines() {
+        let Some(caps) = CHECKBOX_REGEX.captures(line) else {
+            in_run = false;
+            description.push(line);
+            continue;
+        };
+        if !in_run {
+            while description.last().is_some_and(|l| l.trim().is_empty()) {
+                description.pop();
+            }
+            let heading = description
+                .last()
+                .and_then(|l| HEADING_REGEX.captures(l))
+                .map(|h| h[1].to_string());
+            let name = match heading {
+                Some(heading) => {
+                    description.pop();
+                    while description.last().is_some_and(|l| l.trim().is_empty()) {
+                        description.pop();
+                    }
+                    heading
+                }
+                None if checklists.is_empty() => "Checklist".to_string(),
+                None => format!("Checklist {}", checklists.len() + 1),
+            };
+            checklists.push(Checklist {
+                name,
+                items: Vec::new(),
+            });
+            in_run = true;
+        }
+        if let Some(checklist) = checklists.last_mut() {
+            checklist
+                .items
+                .push((caps[2].trim().to_string(), &caps[1] != " "));
+        }
+    }
+
+    (description.join("\n").trim().to_string(), checklists)
+}
+
+fn build_trello_board(name: &str, notes: &[Note], columns: &[ExportColumn]) -> TrelloBoardOut {
+    // Settings columns first, then any column only found on notes
+    let mut list_ids: Vec<(String, String)> = columns
+        .iter()
+        .map(|column| (column.id.clone(), column.title.clone()))
+        .collect();
+    let mut unknown: Vec<&str> = notes
+        .iter()
+        .map(|note| note.frontmatter.column.as_str())
+        .filter(|column| !list_ids.iter().any(|(id, _)| id == column))
+        .collect();
+    unknown.sort();
+    unknown.dedup();
+    list_ids.extend(
+        unknown
+            .into_iter()
+            .map(|id| (id.to_string(), id.to_string())),
+    );
+
+    let lists = list_ids
+        .iter()
+        .enumerate()
+        .map(|(index, (id, title))| TrelloListOut {
+            id: id.clone(),
+            name: title.clone(),
+            closed: false,
+            pos: (index + 1) as f64 * TRELLO_POS_STEP,
+        })
+        .collect();
+
+    let mut labels: BTreeMap<String, String> = BTreeMap::new();
+    for tag in notes.iter().flat_map(|note| &note.frontmatter.tags) {
+        let id = format!("label-{}", labels.len() + 1);
+        labels.entry(tag.clone()).or_insert(id);
+    }
+    let label_out = |tag: &String| TrelloLabelOut {
+        id: labels[tag].clone(),
+        name: tag.clone(),
+        color: None,
+    };
+
+    let mut sorted: Vec<&Note> = notes.iter().collect();
+    sorted.sort_by_key(|note| (note.frontmatter.order, note.frontmatter.created));
+
+    let mut cards = Vec::new();
+    let mut checklists = Vec::new();
+    for (index, note) in sorted.into_iter().enumerate() {
+        let frontmatter = &note.frontmatter;
+        let (desc, note_checklists) = split_checklists(&note.content);
+        let mut id_checklists = Vec::new();
+        for (list_index, checklist) in note_checklists.into_iter().enumerate() {
+            let id = format!("{}-checklist-{}", frontmatter.id, list_index + 1);
+            id_checklists.push(id.clone());
+            checklists.push(TrelloChecklistOut {
+                check_items: checklist
+                    .items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(item_index, (name, checked))| TrelloCheckItemOut {
+                        id: format!("{}-{}", id, item_index + 1),
+                        name,
+                        state: if checked { "complete" } else { "incomplete" },
+                        pos: (item_index + 1) as f64 * TRELLO_POS_STEP,
+                    })
+                    .collect(),
+                id,
+                id_card: frontmatter.id.clone(),
+                name: checklist.name,
+                pos: (list_index + 1) as f64 * TRELLO_POS_STEP,
+            });
+        }
+
+        cards.push(TrelloCardOut {
+            id: frontmatter.id.clone(),
+            name: frontmatter.title.clone(),
+            desc,
+            id_list: frontmatter.column.clone(),
+            closed: false,
+            // Dates without a time are due at midnight UTC
+            due: frontmatter.date.as_ref().map(|date| match date.len() {
+                10 => format!("{}T00:00:00.000Z", date),
+                _ => date.clone(),
+            }),
+            pos: (index + 1) as f64 * TRELLO_POS_STEP,
+            id_labels: frontmatter
+                .tags
+                .iter()
+                .map(|tag| labels[tag].clone())
+                .collect(),
+            labels: frontmatter.tags.iter().map(label_out).collect(),
+            id_checklists,
+            date_last_activity: frontmatter.modified.to_rfc3339(),
+        });
+    }
+
+    TrelloBoardOut {
+        name: name.to_string(),
+        lists,
+        cards,
+        checklists,
+        labels: labels.keys().map(label_out).collect(),
+    }
+}
+
+/// Export the board as Trello-compatible JSON: columns become lists, notes
+/// become cards and checkbox lists become checklists. `columns` are the board
+/// settings; columns only present on notes are appended.
+#[tauri::command]
+pub fn export_board_json(
+    notes_dir: String,
+    board_name: Option<String>,
+    columns: Option<Vec<ExportColumn>>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let notes = read_vault(&notes_dir, &symlink_allowlist(&state))?.notes;
+    let board = build_trello_board(
+        board_name.as_deref().unwrap_or("Noteban"),
+        &notes,
+        &columns.unwrap_or_default(),
+    );
+    serde_json::to_string_pretty(&board).map_err(|e| format!("Failed to encode board: {}", e))
+}
+
+fn matches_query(cached: &CachedNote, query: &ExportQuery) -> bool {
+    let note = &cached.note;
+    if let Some(text) = query.text.as_ref().map(|t| t.to_lowercase()) {
+        if !note.frontmatter.title.to_lowercase().contains(&text)
+            && !note.content.to_lowercase().contains(&text)
+        {
+            return false;
+        }
+    }
+    if let Some(tag) = query
+        .tag
+        .as_ref()
+        .map(|t| t.trim_start_matches('#').to_lowercase())
+    {
+        let has_tag = note
+            .frontmatter
+            .tags
+            .iter()
+            .chain(&cached.inline_tags)
+            .any(|t| t.to_lowercase() == tag);
+        if !has_tag {
+            return false;
+        }
+    }
+    if query
+        .column
+        .as_ref()
+        .is_some_and(|column| *column != note.frontmatter.column)
+    {
+        return false;
+    }
+    if let Some(folder) = &query.folder {
+        if !PathBuf::from(&note.file_path).starts_with(folder) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Value of a frontmatter field as spreadsheet text; unknown names are read
+/// from custom frontmatter
+fn csv_field(note: &Note, field: &str) -> String {
+    let frontmatter = &note.frontmatter;
+    match field {
+        "id" => frontmatter.id.clone(),
+        "title" => frontmatter.title.clone(),
+        "column" => frontmatter.column.clone(),
+        "tags" => frontmatter.tags.join(", "),
+        "created" => frontmatter.created.to_rfc3339(),
+        "modified" => frontmatter.modified.to_rfc3339(),
+        "date" => frontmatter.date.clone().unwrap_or_default(),
+        "order" => frontmatter.order.to_string(),
+        "path" => note.file_path.clone(),
+        other => match frontmatter.extra.get(other) {
+            Some(serde_yaml::Value::String(text)) => text.clone(),
+            Some(serde_yaml::Value::Null) | None => String::new(),
+            Some(value) => serde_yaml::to_string(value)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        },
+    }
+}
+
+/// Quote a CSV cell when needed. Cells a spreadsheet would run as a formula
+/// are prefixed with an apostrophe.
+fn escape_csv(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn render_csv(notes: &[&Note], fields: &[String]) -> String {
+    let mut out = fields
+        .iter()
+        .map(|field| escape_csv(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    out.push_str("\r\n");
+    for note in notes {
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| escape_csv(&csv_field(note, field)))
+            .collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Write the frontmatter of matching notes to a CSV file at `dest`, one row
+/// per note ordered by column and card order. `columns` picks and orders the
+/// fields (any custom frontmatter key works); the default covers title,
+/// column, tags, dates, order and path.
+#[tauri::command]
+pub fn export_csv(
+    query: Option<ExportQuery>,
+    columns: Option<Vec<String>>,
+    dest: String,
+    state: State<AppState>,
+) -> Result<CsvExportSummary, String> {
+    let query = query.unwrap_or_default();
+    let fields = match columns {
+        Some(columns) if !columns.is_empty() => columns,
+        _ => DEFAULT_CSV_FIELDS.iter().map(|f| f.to_string()).collect(),
+    };
+
+    let notes = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        cache.get_all_notes()?
+    };
+    let mut selected: Vec<&Note> = notes
+        .iter()
+        .filter(|cached| matches_query(cached, &query))
+        .map(|cached| &cached.note)
+        .collect();
+    selected.sort_by(|a, b| {
+        (
+            &a.frontmatter.column,
+            a.frontmatter.order,
+            &a.frontmatter.title,
+        )
+            .cmp(&(
+                &b.frontmatter.column,
+                b.frontmatter.order,
+                &b.frontmatter.title,
+            ))
+    });
+
+    let dest_path = PathBuf::from(&dest);
+    if let Some(parent) = dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+    // The byte order mark makes spreadsheet apps read the file as UTF-8
+    let content = format!("\u{feff}{}", render_csv(&selected, &fields));
+    atomic_write(&dest_path, &content)?;
+
+    Ok(CsvExportSummary {
+        path: dest,
+        rows: selected.len(),
+    })
+}
+
+/// A note published as `html_path`, relative to the output directory
+struct SitePage<'a> {
+    note: &'a Note,
+    html_path: PathBuf,
+}
+
+fn link_key(target: &str) -> String {
+    let target = target.trim().trim_start_matches('/');
+    target.strip_suffix(".md").unwrap_or(target).to_lowercase()
+}
+
+/// Wikilink targets by relative path, file name, title and alias. Earlier
+/// pages win when two notes share a name.
+fn site_link_index(pages: &[SitePage]) -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+    for (i, page) in pages.iter().enumerate() {
+        let relative = page.html_path.with_extension("");
+        index
+            .entry(link_key(&relative.to_string_lossy().replace('\\', "/")))
+            .or_insert(i);
+    }
+    for (i, page) in pages.iter().enumerate() {
+        if let Some(stem) = page.html_path.file_stem() {
+            index.entry(link_key(&stem.to_string_lossy())).or_insert(i);
+        }
+        index
+            .entry(link_key(&page.note.frontmatter.title))
+            .or_insert(i);
+    }
+    for (i, page) in pages.iter().enumerate() {
+        for alias in &page.note.frontmatter.aliases {
+            index.entry(link_key(alias)).or_insert(i);
+        }
+    }
+    index
+}
+
+/// Turn wikilinks into markdown links between the exported pages. Embeds of
+/// files in the note's attachments folder become images; links that resolve
+/// to nothing are left as their plain label.
+fn rewrite_site_links(
+    page: &SitePage,
+    pages: &[SitePage],
+    index: &HashMap<String, usize>,
+    broken: &mut Vec<String>,
+) -> String {
+    let page_dir = page.html_path.parent().unwrap_or(Path::new(""));
+    let note_path = Path::new(&page.note.file_path);
+    let attachments = note_path.with_extension("attachments");
+
+    let mut out = String::with_capacity(page.note.content.len());
+    let mut in_fence = false;
+    for line in page.note.content.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains("[[") {
+            out.push_str(line);
+            continue;
+        }
+
+        let line = WIKILINK_REGEX.replace_all(line, |caps: &Captures| {
+            let (target, alias) = match caps[2].split_once('|') {
+                Some((target, alias)) => (target.trim(), Some(alias.trim())),
+                None => (caps[2].trim(), None),
+            };
+            let target = target.split('#').next().unwrap_or(target).trim();
+
+            if &caps[1] == "!" && attachments.join(target).is_file() {
+                let dir_name = attachments
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                return format!(
+                    "![{}]({}/{})",
+                    alias.unwrap_or(target),
+                    urlencoding::encode(&dir_name),
+                    urlencoding::encode(target)
+                );
+            }
+
+            match index.get(&link_key(target)) {
+                Some(&i) => format!(
+                    "[{}]({})",
+                    alias.unwrap_or(&pages[i].note.frontmatter.title),
+                    relative_link(page_dir, &pages[i].html_path)
+                ),
+                None => {
+                    broken.push(format!("{}: {}", page.html_path.display(), target));
+                    alias.unwrap_or(target).to_string()
+                }
+            }
+        });
+        out.push_str(&line);
+    }
+    out
+}
+
+fn render_markdown_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new_ext(markdown, options));
+    out
+}
+
+fn site_document(title: &str, index_href: Option<&str>, body: &str) -> String {
+    let nav = index_href
+        .map(|href| format!("<nav><a href=\"{}\">Index</a></nav>\n", href))
+        .unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}{}</body>\n</html>\n",
+        escape_html(title),
+        SITE_STYLE,
+        nav,
+        body
+    )
+}
+
+fn site_index(title: &str, pages: &[SitePage]) -> String {
+    let mut order: Vec<&SitePage> = pages.iter().collect();
+    order.sort_by_key(|page| {
+        (
+            page.html_path.parent().map(Path::to_path_buf),
+            page.note.frontmatter.title.to_lowercase(),
+        )
+    });
+
+    let mut body = format!("<h1>{}</h1>\n", escape_html(title));
+    let mut current_folder: Option<Option<&Path>> = None;
+    for page in order {
+        let folder = page
+            .html_path
+            .parent()
+            .filter(|f| !f.as_os_str().is_empty());
+        if current_folder != Some(folder) {
+            if current_folder.is_some() {
+                body.push_str("</ul>\n");
+            }
+            if let Some(name) = folder {
+                body.push_str(&format!(
+                    "<h2>{}</h2>\n",
+                    escape_html(&name.to_string_lossy())
+                ));
+            }
+            body.push_str("<ul>\n");
+            current_folder = Some(folder);
+        }
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>\n",
+            relative_link(Path::new(""), &page.html_path),
+            escape_html(&page.note.frontmatter.title)
+        ));
+    }
+    if current_folder.is_some() {
+        body.push_str("</ul>\n");
+    }
+    site_document(title, None, &body)
+}
+
+/// Copy a note's attachments folder next to its page, returning the number of files
+fn copy_site_attachments(note_path: &Path, dest_dir: &Path) -> Result<usize, String> {
+    let source = note_path.with_extension("attachments");
+    let Some(dir_name) = source.file_name().filter(|_| source.is_dir()) else {
+        return Ok(0);
+    };
+    let dest = dest_dir.join(dir_name);
+    let mut copied = 0;
+    for entry in WalkDir::new(&source).min_depth(1).into_iter().flatten() {
+        let Ok(relative) = entry.path().strip_prefix(&source) else {
+            continue;
+        };
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+        } else if entry.file_type().is_file() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+            }
+            fs::copy(entry.path(), &target)
+                .map_err(|e| format!("Failed to copy attachment: {}", e))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Publish the vault, or one folder of it, as a static site in `output_dir`:
+/// one page per note with wikilinks pointing at the other pages, the notes'
+/// attachments, and an `index.html` listing every page by folder.
+#[tauri::command]
+pub fn export_html(
+    notes_dir: String,
+    output_dir: String,
+    options: Option<HtmlExportOptions>,
+    state: State<AppState>,
+) -> Result<HtmlExportSummary, String> {
+    let options = options.unwrap_or_default();
+    let base = PathBuf::from(&notes_dir);
+    let root = match options.folder.as_deref().filter(|f| !f.is_empty()) {
+        Some(folder) => {
+            ensure_safe_relative_path(Path::new(folder))?;
+            base.join(folder)
+        }
+        None => base.clone(),
+    };
+    if !root.is_dir() {
+        return Err("Folder to export does not exist".to_string());
+    }
+
+    let output = PathBuf::from(&output_dir);
+    let canonical_base = base
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve notes directory: {}", e))?;
+    if output.starts_with(&base) || output.starts_with(&canonical_base) {
+        return Err("Export folder must be outside the notes directory".to_string());
+    }
+    fs::create_dir_all(&output).map_err(|e| format!("Failed to create export folder: {}", e))?;
+
+    let notes = read_vault(&notes_dir, &symlink_allowlist(&state))?.notes;
+    let mut pages: Vec<SitePage> = notes
+        .iter()
+        .filter_map(|note| {
+            let relative = Path::new(&note.file_path).strip_prefix(&root).ok()?;
+            Some(SitePage {
+                note,
+                html_path: relative.with_extension("html"),
+            })
+        })
+        .collect();
+    pages.sort_by(|a, b| a.html_path.cmp(&b.html_path));
+    let index = site_link_index(&pages);
+
+    let mut broken_links = Vec::new();
+    let mut attachments_copied = 0;
+    for page in &pages {
+        let markdown = rewrite_site_links(page, &pages, &index, &mut broken_links);
+        let frontmatter = &page.note.frontmatter;
+        let mut body = format!("<h1>{}</h1>\n", escape_html(&frontmatter.title));
+        if !frontmatter.tags.is_empty() {
+            let tags: Vec<String> = frontmatter
+                .tags
+                .iter()
+                .map(|tag| format!("#{}", escape_html(tag)))
+                .collect();
+            body.push_str(&format!("<p class=\"tags\">{}</p>\n", tags.join(" ")));
+        }
+        body.push_str(&render_markdown_html(&markdown));
+
+        let page_dir = page.html_path.parent().unwrap_or(Path::new(""));
+        let index_href = relative_link(page_dir, Path::new(SITE_INDEX_NAME));
+        let dest = output.join(&page.html_path);
+        let dest_dir = output.join(page_dir);
+        fs::create_dir_all(&dest_dir)
+            .map_err(|e| format!("Failed to create export folder: {}", e))?;
+        atomic_write(
+            &dest,
+            &site_document(&frontmatter.title, Some(&index_href), &body),
+        )?;
+        attachments_copied += copy_site_attachments(Path::new(&page.note.file_path), &dest_dir)?;
+    }
+
+    let title = options.title.unwrap_or_else(|| {
+        root.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Notes".to_string())
+    });
+    atomic_write(&output.join(SITE_INDEX_NAME), &site_index(&title, &pages))?;
+
+    Ok(HtmlExportSummary {
+        output_dir,
+        pages: pages.len(),
+        attachments_copied,
+        broken_links,
+    })
+}
+
+/// First installed browser able to print to PDF, searching `PATH` before the
+/// usual install locations
+fn find_pdf_browser() -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    for dir in std::env::split_paths(&path_var) {
+        for name in PDF_BROWSER_NAMES {
+            let candidate = dir
+                .join(name)
+                .with_extension(std::env::consts::EXE_EXTENSION);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+    PDF_BROWSER_PATHS
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+}
+
+/// Standalone HTML for one note; relative image paths resolve against the
+/// note's folder and links to other notes become plain text
+fn note_print_html(note: &Note) -> Result<String, String> {
+    let note_path = Path::new(&note.file_path);
+    let note_dir = note_path.parent().ok_or("Invalid note path")?;
+    let base = url::Url::from_directory_path(note_dir)
+        .map_err(|_| "Note path must be absolute".to_string())?;
+    let page = SitePage {
+        note,
+        html_path: PathBuf::from(note_path.file_name().unwrap_or_default()).with_extension("html"),
+    };
+    let markdown = rewrite_site_links(&page, &[], &HashMap::new(), &mut Vec::new());
+
+    let body = format!(
+        "<base href=\"{}\">\n<h1>{}</h1>\n{}",
+        escape_html(base.as_str()),
+        escape_html(&note.frontmatter.title),
+        render_markdown_html(&markdown)
+    );
+    Ok(site_document(&note.frontmatter.title, None, &body))
+}
+
+#[cfg(not(mobile))]
+fn print_pdf(html: &str, output: &Path) -> Result<(), String> {
+    let browser =
+        find_pdf_browser().ok_or("PDF export needs Chrome, Chromium or Edge to be installed")?;
+    let work_dir = std::env::temp_dir().join(format!("noteban-pdf-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&work_dir).map_err(|e| format!("Failed to create temp folder: {}", e))?;
+    let page = work_dir.join("note.html");
+
+    let result = fs::write(&page, html)
+        .map_err(|e| format!("Failed to write temp page: {}", e))
+        .and_then(|_| {
+            let page_url = url::Url::from_file_path(&page)
+                .map_err(|_| "Invalid temp page path".to_string())?;
+            // A throwaway profile keeps the print apart from a running browser
+            std::process::Command::new(&browser)
+                .arg("--headless")
+                .arg("--disable-gpu")
+                .arg("--no-pdf-header-footer")
+                .arg("--allow-file-access-from-files")
+                .arg(format!(
+                    "--user-data-dir={}",
+                    work_dir.join("profile").display()
+                ))
+                .arg(format!("--print-to-pdf={}", output.display()))
+                .arg(page_url.as_str())
+                .output()
+                .map_err(|e| format!("Failed to run {}: {}", browser.display(), e))
+        });
+    let _ = fs::remove_dir_all(&work_dir);
+
+    let run = result?;
+    if !run.status.success() || !output.is_file() {
+        return Err(format!(
+            "PDF export failed: {}",
+            String::from_utf8_lossy(&run.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Render a note, images included, to a PDF at `output_path` by printing it
+/// with a headless Chromium-based browser
+#[tauri::command]
+pub async fn export_pdf(file_path: String, output_path: String) -> Result<String, String> {
+    let note = parse_note(&PathBuf::from(&file_path))?;
+    let html = note_print_html(&note)?;
+    let output = PathBuf::from(&output_path);
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+
+    #[cfg(mobile)]
+    {
+        let _ = html;
+        Err("PDF export is not supported on mobile".to_string())
+    }
+
+    #[cfg(not(mobile))]
+    {
+        tauri::async_runtime::spawn_blocking(move || print_pdf(&html, &output))
+            .await
+            .map_err(|e| format!("PDF export failed: {}", e))??;
+        Ok(output_path)
+    }
+}
+
+/// Zip a note as `<name>.md` next to its `<name>.attachments` folder, the
+/// layout its relative attachment links expect
+pub(crate) fn write_note_bundle(note_path: &Path, dest: &Path) -> Result<(), String> {
+    let file_name = note_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("Invalid note path")?;
+    let note_bytes = fs::read(note_path).map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let file = File::create(dest).map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(file_name.as_str(), options)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(&note_bytes).map_err(|e| e.to_string())?;
+
+    let attachments = note_path.with_extension("attachments");
+    if let Some(dir_name) = attachments.file_name().filter(|_| attachments.is_dir()) {
+        let dir_name = dir_name.to_string_lossy();
+        for entry in WalkDir::new(&attachments).min_depth(1) {
+            let entry = entry.map_err(|e| e.to_string())?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(&attachments)
+                .map_err(|e| e.to_string())?;
+            // Zip entries always use forward slashes
+            let mut name = dir_name.to_string();
+            for component in relative.components() {
+                name.push('/');
+                name.push_str(&component.as_os_str().to_string_lossy());
+            }
+            let bytes = fs::read(entry.path()).map_err(|e| e.to_string())?;
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| e.to_string())?;
+            zip.write_all(&bytes).map_err(|e| e.to_string())?;
+        }
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Pack a note and its attachments into a zip at `dest` for moving it to
+/// another vault with `import_note_bundle`
+#[tauri::command]
+pub fn export_note_bundle(
+    notes_dir: String,
+    file_path: String,
+    dest: String,
+) -> Result<String, String> {
+    let note_path =
+        validate_existing_path_within_base(Path::new(&file_path), Path::new(&notes_dir))?;
+    let dest_path = PathBuf::from(&dest);
+    if let Some(parent) = dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+    if let Err(e) = write_note_bundle(&note_path, &dest_path) {
+        let _ = fs::remove_file(&dest_path);
+        return Err(e);
+    }
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_checklists_from_description() {
+        let (desc, checklists) = split_checklists(
+            "Details\n\n## Steps\n\n- [x] One\n- [ ] Two\n\nMore text\n* [ ] Loose",
+        );
+        assert_eq!(desc, "Details\n\nMore text");
+        assert_eq!(
+            checklists,
+            vec![
+                Checklist {
+                    name: "Steps".to_string(),
+                    items: vec![("One".to_string(), true), ("Two".to_string(), false)],
+                },
+                Checklist {
+                    name: "Checklist 2".to_string(),
+                    items: vec![("Loose".to_string(), false)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_escaped_csv_rows() {
+        let note: Note = serde_json::from_value(serde_json::json!({
+            "frontmatter": {
+                "id": "1",
+                "title": "Fix \"login\", again",
+                "created": "2024-01-01T00:00:00Z",
+                "modified": "2024-01-02T00:00:00Z",
+                "column": "doing",
+                "tags": ["auth", "bug"],
+                "order": 2,
+                "owner": "=cmd()"
+            },
+            "content": "",
+            "file_path": "/vault/fix.md"
+        }))
+        .unwrap();
+        let fields = ["title", "tags", "order", "owner", "missing"].map(String::from);
+        assert_eq!(
+            render_csv(&[&note], &fields),
+            "title,tags,order,owner,missing\r\n\
+             \"Fix \"\"login\"\", again\",\"auth, bug\",2,'=cmd(),\r\n"
+        );
+    }
+
+    #[test]
+    fn resolves_wikilinks_between_site_pages() {
+        let note = |title: &str, path: &str, content: &str| -> Note {
+            serde_json::from_value(serde_json::json!({
+                "frontmatter": {
+                    "id": path,
+                    "title": title,
+                    "created": "2024-01-01T00:00:00Z",
+                    "modified": "2024-01-01T00:00:00Z",
+                    "column": "todo"
+                },
+                "content": content,
+                "file_path": format!("/vault/{}", path)
+            }))
+            .unwrap()
+        };
+        let notes = [
+            note(
+                "Home",
+                "home.md",
+                "See [[Project Plan|the plan]] and [[roadmap]], not [[Missing]].\n```\n[[kept]]\n```\n",
+            ),
+            note("Project Plan", "work/project-plan.md", ""),
+            note("Roadmap", "work/roadmap.md", "Back to [[home]]"),
+        ];
+        let pages: Vec<SitePage> = notes
+            .iter()
+            .map(|note| SitePage {
+                note,
+                html_path: Path::new(&note.file_path)
+                    .strip_prefix("/vault")
+                    .unwrap()
+                    .with_extension("html"),
+            })
+            .collect();
+        let index = site_link_index(&pages);
+        let mut broken = Vec::new();
+
+        assert_eq!(
+            rewrite_site_links(&pages[0], &pages, &index, &mut broken),
+            "See [the plan](work/project-plan.html) and [Roadmap](work/roadmap.html), not Missing.\n```\n[[kept]]\n```\n"
+        );
+        assert_eq!(
+            rewrite_site_links(&pages[2], &pages, &index, &mut broken),
+            "Back to [Home](../home.html)"
+        );
+        assert_eq!(broken, vec!["home.html: Missing".to_string()]);
+    }
+}
@@ -0,0 +1,652 @@
+use crate::commands::audit;
+use crate::commands::history::write_restored_note;
+use crate::commands::mounts::ensure_writable;
+use crate::commands::notes::{
+    create_note, parse_note, update_note_frontmatter, validate_existing_path_within_base,
+    validate_path_within_base, CreateNoteInput, NoteWithTags,
+};
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use git2::{Commit, Oid, Repository, Sort};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+
+const DEFAULT_LOG_LIMIT: usize = 100;
+const MAX_LOG_LIMIT: usize = 1000;
+/// Frontmatter property holding the commits linked to a card
+const COMMITS_KEY: &str = "commits";
+/// Commits read for a single changelog
+const MAX_CHANGELOG_COMMITS: usize = 5000;
+/// Changelog sections by conventional-commit type, in display order
+const CHANGELOG_SECTIONS: [(&str, &str); 10] = [
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "Continuous Integration"),
+    ("style", "Style"),
+    ("chore", "Chores"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitCommitInfo {
+    pub id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub time: DateTime<Utc>,
+    /// True when the commit removed the file
+    pub deleted: bool,
+}
+
+/// A commit referenced from a note's frontmatter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitLink {
+    pub repo: String,
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedCommit {
+    pub repo: String,
+    pub sha: String,
+    /// None when the repository or commit can no longer be found
+    pub commit: Option<GitCommitInfo>,
+    pub error: Option<String>,
+}
+
+fn commit_info(commit: &Commit<'_>, deleted: bool) -> GitCommitInfo {
+    let id = commit.id().to_string();
+    let author = commit.author();
+    GitCommitInfo {
+        short_id: id.chars().take(7).collect(),
+        id,
+        summary: commit.summary().unwrap_or_default().to_string(),
+        author_name: author.name().unwrap_or_default().to_string(),
+        author_email: author.email().unwrap_or_default().to_string(),
+        time: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+        deleted,
+    }
+}
+
+pub(crate) fn open_repo(notes_dir: &str) -> Result<Repository, String> {
+    Repository::discover(notes_dir)
+        .map_err(|_| "Notes directory is not inside a git repository".to_string())
+}
+
+/// Check that a note path lies in the vault. The note may have been deleted,
+/// so its folder is resolved and the file name checked on its own.
+fn validate_note_path(path: &Path, notes_dir: &str) -> Result<(), String> {
+    let parent = path.parent().ok_or("Invalid note path")?;
+    validate_path_within_base(parent, Path::new(notes_dir))?;
+    match path.components().next_back() {
+        Some(Component::Normal(_)) => Ok(()),
+        _ => Err("Invalid note path".to_string()),
+    }
+}
+
+/// Path of `path` relative to the repository working directory
+fn repo_relative_path(repo: &Repository, path: &Path) -> Result<PathBuf, String> {
+    let workdir = repo
+        .workdir()
+        .ok_or("Bare repositories are not supported")?
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve repository path: {}", e))?;
+    // The file may no longer exist, so resolve through its parent
+    let parent = path
+        .parent()
+        .ok_or("Invalid note path")?
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve note path: {}", e))?;
+    let file_name = path.file_name().ok_or("Invalid note path")?;
+    parent
+        .join(file_name)
+        .strip_prefix(&workdir)
+        .map(Path::to_path_buf)
+        .map_err(|_| "Note is outside the git repository".to_string())
+}
+
+fn blob_id_at(commit: &Commit<'_>, relative: &Path) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()?
+        .get_path(relative)
+        .ok()
+        .map(|entry| entry.id())
+}
+
+/// Commits that added, changed or removed `relative`, newest first
+fn file_log(
+    repo: &Repository,
+    relative: &Path,
+    limit: usize,
+) -> Result<Vec<GitCommitInfo>, String> {
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to read git history: {}", e))?;
+    if revwalk.push_head().is_err() {
+        // Repository without commits yet
+        return Ok(Vec::new());
+    }
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+        .map_err(|e| format!("Failed to read git history: {}", e))?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        if commits.len() >= limit {
+            break;
+        }
+        let oid = oid.map_err(|e| format!("Failed to read git history: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to read commit: {}", e))?;
+
+        let current = blob_id_at(&commit, relative);
+        let parent = commit
+            .parent(0)
+            .ok()
+            .and_then(|parent| blob_id_at(&parent, relative));
+        if current == parent {
+            continue;
+        }
+
+        commits.push(commit_info(&commit, current.is_none()));
+    }
+
+    Ok(commits)
+}
+
+/// Content of `relative` as of `rev` (any revision git understands)
+fn file_at(repo: &Repository, relative: &Path, rev: &str) -> Result<String, String> {
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| format!("Unknown revision: {}", rev))?;
+    let blob_id =
+        blob_id_at(&commit, relative).ok_or_else(|| format!("Note does not exist at {}", rev))?;
+    let blob = repo
+        .find_blob(blob_id)
+        .map_err(|e| format!("Failed to read note at {}: {}", rev, e))?;
+    String::from_utf8(blob.content().to_vec())
+        .map_err(|_| format!("Note at {} is not valid UTF-8", rev))
+}
+
+#[tauri::command]
+pub fn git_log(
+    notes_dir: String,
+    file_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommitInfo>, String> {
+    let path = PathBuf::from(&file_path);
+    validate_note_path(&path, &notes_dir)?;
+    let repo = open_repo(&notes_dir)?;
+    let relative = repo_relative_path(&repo, &path)?;
+    let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+    file_log(&repo, &relative, limit)
+}
+
+#[tauri::command]
+pub fn git_show(notes_dir: String, file_path: String, rev: String) -> Result<String, String> {
+    let path = PathBuf::from(&file_path);
+    validate_note_path(&path, &notes_dir)?;
+    let repo = open_repo(&notes_dir)?;
+    let relative = repo_relative_path(&repo, &path)?;
+    file_at(&repo, &relative, &rev)
+}
+
+/// Overwrite a note with its content at `rev`. Only the working copy changes;
+/// nothing is committed.
+#[tauri::command]
+pub fn git_restore(
+    notes_dir: String,
+    file_path: String,
+    rev: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, Path::new(&notes_dir))?;
+    ensure_writable(&path, &state)?;
+
+    let repo = open_repo(&notes_dir)?;
+    let relative = repo_relative_path(&repo, &path)?;
+    let restored = file_at(&repo, &relative, &rev)?;
+    let previous_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let result = write_restored_note(&path, &previous_content, &restored, &state);
+    audit::record(&state, "restore", &file_path, None, &result);
+    result
+}
+
+fn find_commit(repo_path: &str, sha: &str) -> Result<GitCommitInfo, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|_| format!("Not a git repository: {}", repo_path))?;
+    let commit = repo
+        .revparse_single(sha)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| format!("Unknown commit: {}", sha))?;
+    Ok(commit_info(&commit, false))
+}
+
+fn commit_links(frontmatter_value: Option<&serde_yaml::Value>) -> Vec<CommitLink> {
+    frontmatter_value
+        .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Link a card to a commit; the commit is resolved to its full id first
+#[tauri::command]
+pub fn link_commit(
+    notes_dir: String,
+    file_path: String,
+    repo_path: String,
+    commit_sha: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let repo = Path::new(&repo_path)
+        .canonicalize()
+        .map_err(|_| format!("Repository does not exist: {}", repo_path))?
+        .to_string_lossy()
+        .to_string();
+    let commit = find_commit(&repo, commit_sha.trim())?;
+
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        let mut links = commit_links(frontmatter.extra.get(COMMITS_KEY));
+        let link = CommitLink {
+            repo,
+            sha: commit.id,
+        };
+        if links.contains(&link) {
+            return Err("Commit is already linked".to_string());
+        }
+        links.push(link);
+        let value = serde_yaml::to_value(&links)
+            .map_err(|e| format!("Failed to encode commit links: {}", e))?;
+        frontmatter.extra.insert(COMMITS_KEY.into(), value);
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    result
+}
+
+#[tauri::command]
+pub fn unlink_commit(
+    notes_dir: String,
+    file_path: String,
+    commit_sha: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        let mut links = commit_links(frontmatter.extra.get(COMMITS_KEY));
+        let before = links.len();
+        links.retain(|link| !link.sha.starts_with(commit_sha.trim()));
+        if links.len() == before {
+            return Err("Commit is not linked".to_string());
+        }
+        if links.is_empty() {
+            frontmatter.extra.shift_remove(COMMITS_KEY);
+        } else {
+            let value = serde_yaml::to_value(&links)
+                .map_err(|e| format!("Failed to encode commit links: {}", e))?;
+            frontmatter.extra.insert(COMMITS_KEY.into(), value);
+        }
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    result
+}
+
+/// Metadata for every commit linked to a note, in link order
+#[tauri::command]
+pub fn get_linked_commits(
+    notes_dir: String,
+    file_path: String,
+) -> Result<Vec<LinkedCommit>, String> {
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, Path::new(&notes_dir))?;
+    let note = parse_note(&path)?;
+
+    Ok(commit_links(note.frontmatter.extra.get(COMMITS_KEY))
+        .into_iter()
+        .map(|link| match find_commit(&link.repo, &link.sha) {
+            Ok(commit) => LinkedCommit {
+                repo: link.repo,
+                sha: link.sha,
+                commit: Some(commit),
+                error: None,
+            },
+            Err(e) => LinkedCommit {
+                repo: link.repo,
+                sha: link.sha,
+                commit: None,
+                error: Some(e),
+            },
+        })
+        .collect())
+}
+
+/// A commit message split into its conventional-commit parts
+#[derive(Debug, PartialEq)]
+struct ConventionalCommit<'a> {
+    kind: Option<String>,
+    scope: Option<&'a str>,
+    breaking: bool,
+    description: &'a str,
+}
+
+/// Parse `type(scope)!: description`; messages that don't follow the
+/// convention keep their whole summary as the description
+fn parse_conventional<'a>(summary: &'a str, body: &str) -> ConventionalCommit<'a> {
+    let breaking_body = body.contains("BREAKING CHANGE");
+    let plain = ConventionalCommit {
+        kind: None,
+        scope: None,
+        breaking: breaking_body,
+        description: summary.trim(),
+    };
+    let Some((header, description)) = summary.split_once(": ") else {
+        return plain;
+    };
+    let (header, bang) = match header.strip_suffix('!') {
+        Some(header) => (header, true),
+        None => (header, false),
+    };
+    let (kind, scope) = match header.split_once('(') {
+        Some((kind, rest)) => match rest.strip_suffix(')') {
+            Some(scope) => (kind, Some(scope)),
+            None => return plain,
+        },
+        None => (header, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+        return plain;
+    }
+    ConventionalCommit {
+        kind: Some(kind.to_lowercase()),
+        scope,
+        breaking: bang || breaking_body,
+        description: description.trim(),
+    }
+}
+
+/// Render commits as `(short id, summary, body)` into grouped markdown
+fn render_changelog(commits: &[(String, String, String)]) -> String {
+    let mut breaking = Vec::new();
+    let mut sections: Vec<Vec<String>> = vec![Vec::new(); CHANGELOG_SECTIONS.len()];
+    let mut other = Vec::new();
+
+    for (short_id, summary, body) in commits {
+        let parsed = parse_conventional(summary, body);
+        let line = match parsed.scope {
+            Some(scope) => format!("- **{}:** {} ({})", scope, parsed.description, short_id),
+            None => format!("- {} ({})", parsed.description, short_id),
+        };
+        if parsed.breaking {
+            breaking.push(line.clone());
+        }
+        let section = parsed
+            .kind
+            .as_deref()
+            .and_then(|kind| CHANGELOG_SECTIONS.iter().position(|(k, _)| *k == kind));
+        match section {
+            Some(index) => sections[index].push(line),
+            None => other.push(line),
+        }
+    }
+
+    let mut out = String::new();
+    let mut push_section = |heading: &str, lines: &[String]| {
+        if lines.is_empty() {
+            return;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {}\n\n{}\n", heading, lines.join("\n")));
+    };
+    push_section("Breaking Changes", &breaking);
+    for ((_, heading), lines) in CHANGELOG_SECTIONS.iter().zip(&sections) {
+        push_section(heading, lines);
+    }
+    push_section("Other Changes", &other);
+    out
+}
+
+/// Non-merge commits in `range` (`from..to`, or everything reachable from a
+/// single revision), oldest first
+fn commits_in_range(
+    repo: &Repository,
+    range: &str,
+) -> Result<Vec<(String, String, String)>, String> {
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to read git history: {}", e))?;
+    if range.contains("..") {
+        revwalk.push_range(range)
+    } else {
+        repo.revparse_single(range)
+            .and_then(|object| revwalk.push(object.id()))
+    }
+    .map_err(|_| format!("Invalid revision range: {}", range))?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)
+        .map_err(|e| format!("Failed to read git history: {}", e))?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk.take(MAX_CHANGELOG_COMMITS) {
+        let oid = oid.map_err(|e| format!("Failed to read git history: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to read commit: {}", e))?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        commits.push((
+            oid.to_string().chars().take(7).collect(),
+            commit.summary().unwrap_or_default().to_string(),
+            commit.body().unwrap_or_default().to_string(),
+        ));
+    }
+    Ok(commits)
+}
+
+/// Create a note summarizing the commits in `range`, grouped by
+/// conventional-commit type
+#[tauri::command]
+pub fn generate_changelog_note(
+    notes_dir: String,
+    repo_path: String,
+    range: String,
+    title: Option<String>,
+    folder_path: Option<String>,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|_| format!("Not a git repository: {}", repo_path))?;
+    let range = range.trim();
+    let commits = commits_in_range(&repo, range)?;
+    if commits.is_empty() {
+        return Err(format!("No commits in {}", range));
+    }
+
+    let title = title
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| format!("Changelog {}", range));
+    create_note(
+        CreateNoteInput {
+            notes_dir,
+            folder_path,
+            title,
+            content: Some(render_changelog(&commits)),
+            date: Some(Utc::now().format("%Y-%m-%d").to_string()),
+            column: None,
+            tags: Some(vec!["changelog".to_string()]),
+            due: None,
+            idempotency_key: None,
+        },
+        state,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_file(repo: &Repository, name: &str, content: Option<&str>, message: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        match content {
+            Some(content) => {
+                fs::write(workdir.join(name), content).unwrap();
+                index.add_path(Path::new(name)).unwrap();
+            }
+            None => {
+                fs::remove_file(workdir.join(name)).unwrap();
+                index.remove_path(Path::new(name)).unwrap();
+            }
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<Commit<'_>> = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<&Commit<'_>> = parents.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn lists_and_shows_file_history() {
+        let dir = std::env::temp_dir().join(format!("noteban-git-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init(&dir).unwrap();
+        let first = commit_file(&repo, "a.md", Some("one"), "add a");
+        commit_file(&repo, "b.md", Some("other"), "add b");
+        commit_file(&repo, "a.md", Some("two"), "edit a");
+        commit_file(&repo, "a.md", None, "remove a");
+
+        let log = file_log(&repo, Path::new("a.md"), 10).unwrap();
+        let summaries: Vec<&str> = log.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["remove a", "edit a", "add a"]);
+        assert!(log[0].deleted);
+
+        assert_eq!(
+            file_at(&repo, Path::new("a.md"), &first.to_string()).unwrap(),
+            "one"
+        );
+        assert_eq!(file_at(&repo, Path::new("a.md"), "HEAD~1").unwrap(), "two");
+        assert!(file_at(&repo, Path::new("a.md"), "HEAD").is_err());
+
+        // The note is gone from the working copy but its history is not
+        let workdir = dir.to_string_lossy().to_string();
+        let deleted = dir.join("a.md").to_string_lossy().to_string();
+        assert_eq!(
+            git_log(workdir.clone(), deleted.clone(), None)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            git_show(workdir.clone(), deleted, "HEAD~1".to_string()).unwrap(),
+            "two"
+        );
+        let outside = dir.join("../a.md").to_string_lossy().to_string();
+        assert!(git_log(workdir.clone(), outside, None).is_err());
+
+        let found = find_commit(&workdir, &first.to_string()[..7]).unwrap();
+        assert_eq!(found.id, first.to_string());
+        assert_eq!(found.summary, "add a");
+        assert!(find_commit(&workdir, "0000000").is_err());
+
+        let range = format!("{}..HEAD", first);
+        let summaries: Vec<String> = commits_in_range(&repo, &range)
+            .unwrap()
+            .into_iter()
+            .map(|(_, summary, _)| summary)
+            .collect();
+        assert_eq!(summaries, vec!["add b", "edit a", "remove a"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_to_change_commit_links_of_locked_notes() {
+        let vault = crate::test_support::TestVault::new();
+        let repo = Repository::init(vault.path("repo")).unwrap();
+        let commit = commit_file(&repo, "a.md", Some("one"), "add a").to_string();
+        let repo_path = vault.path("repo");
+        let note = vault.note("card.md", "card", "");
+
+        link_commit(
+            vault.notes_dir(),
+            note.clone(),
+            repo_path.clone(),
+            commit.clone(),
+            vault.state(),
+        )
+        .unwrap();
+        let locked = vault.note("locked.md", "locked", "locked: true\n");
+        let refused = link_commit(
+            vault.notes_dir(),
+            locked.clone(),
+            repo_path,
+            commit.clone(),
+            vault.state(),
+        );
+        assert!(refused.is_err());
+        assert!(!vault.read("locked.md").contains(COMMITS_KEY));
+
+        // Locking after linking keeps the link in place
+        crate::commands::notes::set_note_locked(
+            vault.notes_dir(),
+            note.clone(),
+            true,
+            vault.state(),
+        )
+        .unwrap();
+        let before = vault.read("card.md");
+        assert!(unlink_commit(vault.notes_dir(), note, commit, vault.state()).is_err());
+        assert_eq!(vault.read("card.md"), before);
+    }
+
+    #[test]
+    fn groups_changelog_by_commit_type() {
+        let commit = |id: &str, summary: &str, body: &str| {
+            (id.to_string(), summary.to_string(), body.to_string())
+        };
+        let changelog = render_changelog(&[
+            commit("a1", "feat(board): add WIP limits", ""),
+            commit("b2", "fix: keep order on move", ""),
+            commit("c3", "feat!: drop legacy sync", ""),
+            commit("d4", "Update readme", ""),
+            commit("e5", "refactor: split cache", "BREAKING CHANGE: new schema"),
+        ]);
+        assert_eq!(
+            changelog,
+            "## Breaking Changes\n\n- drop legacy sync (c3)\n- split cache (e5)\n\n\
+             ## Features\n\n- **board:** add WIP limits (a1)\n- drop legacy sync (c3)\n\n\
+             ## Bug Fixes\n\n- keep order on move (b2)\n\n\
+             ## Refactoring\n\n- split cache (e5)\n\n\
+             ## Other Changes\n\n- Update readme (d4)\n"
+        );
+        assert_eq!(parse_conventional("fix(: oops", "").kind, None);
+    }
+}
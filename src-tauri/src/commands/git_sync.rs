@@ -0,0 +1,514 @@
+use crate::commands::conflicts::{conflict_record, write_conflict_copy, REASON_SYNC};
+use crate::commands::git::open_repo;
+use crate::commands::sync::{
+    emit_progress, read_selective_sync, SelectiveSyncConfig, SyncProgress,
+};
+use crate::commands::watch;
+use crate::lock_or_err;
+use crate::AppState;
+use git2::build::CheckoutBuilder;
+use git2::{Commit, Index, IndexAddOption, IndexEntry, Oid, Reference, Repository, Signature};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const GIT_SYNC_CONFIG_KEY: &str = "git_sync_config";
+const DEFAULT_REMOTE: &str = "origin";
+const CONFLICT_EVENT: &str = "git-sync-conflict";
+/// Source recorded on conflict copies made by a git merge
+const CONFLICT_SOURCE: &str = "git";
+/// Stage bits of an index entry's flags; zero for resolved entries
+const INDEX_STAGE_MASK: u16 = 0x3000;
+
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitSyncConfig {
+    /// Remote to pull from and push to; `origin` when unset
+    #[serde(default)]
+    pub remote: Option<String>,
+    /// Remote branch to sync with; the checked out branch when unset
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitSyncSummary {
+    pub committed: bool,
+    /// Remote changes were merged into the vault
+    pub pulled: bool,
+    pub pushed: bool,
+    /// Conflict copies holding the remote side of conflicting files
+    pub conflicts: Vec<String>,
+}
+
+/// Files the remote changed in conflict with local edits, with the remote content
+#[derive(Debug, Default)]
+struct MergeOutcome {
+    merged: bool,
+    conflicts: Vec<(PathBuf, Vec<u8>)>,
+}
+
+/// Resets the running flag when a sync ends, however it ends
+struct SyncGuard;
+
+impl Drop for SyncGuard {
+    fn drop(&mut self) {
+        SYNC_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+fn validate_config(config: &GitSyncConfig) -> Result<(), String> {
+    if let Some(remote) = &config.remote {
+        let valid = !remote.is_empty()
+            && !remote.starts_with('-')
+            && remote
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err("Invalid remote name".to_string());
+        }
+    }
+    if let Some(branch) = &config.branch {
+        if branch.starts_with('-') || !Reference::is_valid_name(&format!("refs/heads/{}", branch)) {
+            return Err("Invalid branch name".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    repo.signature()
+        .or_else(|_| Signature::now("noteban", "noteban@localhost"))
+        .map_err(|e| format!("Failed to create commit signature: {}", e))
+}
+
+/// Branch HEAD points at, also before the first commit
+fn current_branch(repo: &Repository) -> Result<String, String> {
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+    head.symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(str::to_string)
+        .ok_or_else(|| "HEAD is detached; check out a branch to sync".to_string())
+}
+
+/// Commit every change below `pathspec` that selective sync includes,
+/// returning whether there was any
+fn commit_changes(
+    repo: &Repository,
+    pathspec: &str,
+    selective: &SelectiveSyncConfig,
+    message: &str,
+) -> Result<bool, String> {
+    let prefix = format!("{}/", pathspec);
+    let mut skip_excluded = |path: &Path, _: &[u8]| -> i32 {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let relative = path.strip_prefix(&prefix).unwrap_or(&path);
+        if selective.includes(relative) {
+            0
+        } else {
+            1
+        }
+    };
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    index
+        .add_all(
+            [pathspec],
+            IndexAddOption::DEFAULT,
+            Some(&mut skip_excluded),
+        )
+        .map_err(|e| format!("Failed to stage changes: {}", e))?;
+    index
+        .update_all([pathspec], Some(&mut skip_excluded))
+        .map_err(|e| format!("Failed to stage changes: {}", e))?;
+    index.write().map_err(|e| e.to_string())?;
+    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    if parent.as_ref().is_some_and(|p| p.tree_id() == tree_id) {
+        return Ok(false);
+    }
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+    let signature = signature(repo)?;
+    let parents: Vec<&Commit> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(true)
+}
+
+fn resolved_entry(entry: &IndexEntry) -> IndexEntry {
+    IndexEntry {
+        ctime: entry.ctime,
+        mtime: entry.mtime,
+        dev: entry.dev,
+        ino: entry.ino,
+        mode: entry.mode,
+        uid: entry.uid,
+        gid: entry.gid,
+        file_size: entry.file_size,
+        id: entry.id,
+        flags: entry.flags & !INDEX_STAGE_MASK,
+        flags_extended: entry.flags_extended,
+        path: entry.path.clone(),
+    }
+}
+
+/// Settle every conflict in favour of the local side, keeping the remote
+/// content of files both sides changed for conflict copies
+fn resolve_conflicts(
+    repo: &Repository,
+    index: &mut Index,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, String> {
+    let conflicts = index
+        .conflicts()
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut remote_sides = Vec::new();
+    for conflict in conflicts {
+        let Some(path) = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).to_string()))
+            .next()
+        else {
+            continue;
+        };
+        index.remove_path(&path).map_err(|e| e.to_string())?;
+        match (&conflict.our, &conflict.their) {
+            (Some(ours), theirs) => {
+                index
+                    .add(&resolved_entry(ours))
+                    .map_err(|e| e.to_string())?;
+                if let Some(theirs) = theirs {
+                    let blob = repo.find_blob(theirs.id).map_err(|e| e.to_string())?;
+                    remote_sides.push((path, blob.content().to_vec()));
+                }
+            }
+            // Deleted here but changed remotely: keep the remote edit
+            (None, Some(theirs)) => index
+                .add(&resolved_entry(theirs))
+                .map_err(|e| e.to_string())?,
+            (None, None) => {}
+        }
+    }
+    Ok(remote_sides)
+}
+
+/// Bring `remote` into the checked out branch: fast-forward when possible,
+/// otherwise a merge commit where conflicts keep the local version
+fn merge_remote(repo: &Repository, branch: &str, remote: Oid) -> Result<MergeOutcome, String> {
+    let annotated = repo
+        .find_annotated_commit(remote)
+        .map_err(|e| e.to_string())?;
+    let (analysis, _) = repo
+        .merge_analysis(&[&annotated])
+        .map_err(|e| format!("Failed to analyze merge: {}", e))?;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe();
+
+    if analysis.is_up_to_date() {
+        return Ok(MergeOutcome::default());
+    }
+    if analysis.is_unborn() || analysis.is_fast_forward() {
+        // Check out before moving the branch so the old HEAD is the baseline
+        // that tells local edits apart from remote changes
+        let target = repo.find_object(remote, None).map_err(|e| e.to_string())?;
+        repo.checkout_tree(&target, Some(&mut checkout))
+            .map_err(|e| format!("Failed to update the vault: {}", e))?;
+        let ref_name = format!("refs/heads/{}", branch);
+        repo.reference(&ref_name, remote, true, "noteban sync: fast-forward")
+            .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+        repo.set_head(&ref_name).map_err(|e| e.to_string())?;
+        return Ok(MergeOutcome {
+            merged: true,
+            conflicts: Vec::new(),
+        });
+    }
+
+    let local = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| e.to_string())?;
+    let remote_commit = repo.find_commit(remote).map_err(|e| e.to_string())?;
+    let mut index = repo
+        .merge_commits(&local, &remote_commit, None)
+        .map_err(|e| format!("Failed to merge: {}", e))?;
+    let conflicts = if index.has_conflicts() {
+        resolve_conflicts(repo, &mut index)?
+    } else {
+        Vec::new()
+    };
+
+    let tree_id = index
+        .write_tree_to(repo)
+        .map_err(|e| format!("Failed to write merge: {}", e))?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+    repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+        .map_err(|e| format!("Failed to update the vault: {}", e))?;
+    let signature = signature(repo)?;
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Merge remote changes",
+        &tree,
+        &[&local, &remote_commit],
+    )
+    .map_err(|e| format!("Failed to commit merge: {}", e))?;
+    Ok(MergeOutcome {
+        merged: true,
+        conflicts,
+    })
+}
+
+fn run_git(workdir: &Path, args: &[&str]) -> Result<Output, String> {
+    Command::new("git")
+        .current_dir(workdir)
+        .args(args)
+        // Never wait for a password prompt nobody can answer
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| format!("Git sync needs the git command line tool: {}", e))
+}
+
+fn git_error(action: &str, output: &Output) -> String {
+    format!(
+        "Failed to {}: {}",
+        action,
+        String::from_utf8_lossy(&output.stderr).trim()
+    )
+}
+
+/// Report `committing`, `pulling`, `merging`, `pushing` or `done` on the
+/// shared sync progress event
+fn emit_stage(app: &AppHandle, stage: &str) {
+    emit_progress(app, SyncProgress::stage(stage));
+}
+
+fn run_git_sync(
+    notes_dir: &str,
+    config: &GitSyncConfig,
+    selective: &SelectiveSyncConfig,
+    app: &AppHandle,
+) -> Result<GitSyncSummary, String> {
+    let repo = open_repo(notes_dir)?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Bare repositories are not supported")?
+        .to_path_buf();
+    let vault = Path::new(notes_dir)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve notes directory: {}", e))?;
+    let workdir_canonical = workdir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve repository path: {}", e))?;
+    let pathspec = match vault.strip_prefix(&workdir_canonical) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => return Err("Notes directory is outside the git repository".to_string()),
+    };
+    let remote = config.remote.as_deref().unwrap_or(DEFAULT_REMOTE);
+    repo.find_remote(remote)
+        .map_err(|_| format!("Remote \"{}\" is not configured", remote))?;
+    let local_branch = current_branch(&repo)?;
+    let branch = config
+        .branch
+        .clone()
+        .unwrap_or_else(|| local_branch.clone());
+    let mut summary = GitSyncSummary::default();
+
+    emit_stage(app, "committing");
+    summary.committed = commit_changes(&repo, &pathspec, selective, "Sync local changes")?;
+
+    emit_stage(app, "pulling");
+    let listed = run_git(
+        &workdir,
+        &["ls-remote", "--exit-code", "--heads", remote, &branch],
+    )?;
+    // Exit code 2: the remote branch does not exist yet and the push creates it
+    let remote_has_branch = match listed.status.code() {
+        Some(0) => true,
+        Some(2) => false,
+        _ => return Err(git_error("reach the remote", &listed)),
+    };
+    if remote_has_branch {
+        let tracking = format!("refs/remotes/{}/{}", remote, branch);
+        let refspec = format!("+refs/heads/{}:{}", branch, tracking);
+        let fetched = run_git(&workdir, &["fetch", "--no-tags", remote, &refspec])?;
+        if !fetched.status.success() {
+            return Err(git_error("pull", &fetched));
+        }
+        let remote_oid = repo
+            .refname_to_id(&tracking)
+            .map_err(|e| format!("Failed to read fetched branch: {}", e))?;
+
+        emit_stage(app, "merging");
+        let outcome = merge_remote(&repo, &local_branch, remote_oid)?;
+        summary.pulled = outcome.merged;
+
+        let state = app.state::<AppState>();
+        for (relative, bytes) in outcome.conflicts {
+            let original = workdir.join(&relative);
+            let copy = write_conflict_copy(&original, &bytes, REASON_SYNC, Some(CONFLICT_SOURCE))?;
+            if let Ok(cache_lock) = lock_or_err(&state.cache) {
+                if let Some(cache) = cache_lock.as_ref() {
+                    let record =
+                        conflict_record(&copy, &original, REASON_SYNC, Some(CONFLICT_SOURCE));
+                    if let Err(e) = cache.upsert_conflict(&record) {
+                        log::warn!("Failed to index sync conflict: {}", e);
+                    }
+                }
+            }
+            let copy = copy.to_string_lossy().to_string();
+            if let Err(e) = app.emit(CONFLICT_EVENT, &copy) {
+                log::warn!("Failed to report sync conflict: {}", e);
+            }
+            summary.conflicts.push(copy);
+        }
+        if !summary.conflicts.is_empty() {
+            commit_changes(
+                &repo,
+                &pathspec,
+                selective,
+                "Keep conflicting remote changes",
+            )?;
+        }
+    }
+
+    if repo.head().is_ok() {
+        emit_stage(app, "pushing");
+        let destination = format!("HEAD:refs/heads/{}", branch);
+        let pushed = run_git(&workdir, &["push", remote, &destination])?;
+        if !pushed.status.success() {
+            return Err(git_error("push", &pushed));
+        }
+        summary.pushed = true;
+    }
+    emit_stage(app, "done");
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn get_git_sync_config(state: State<AppState>) -> Result<GitSyncConfig, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    Ok(cache
+        .get_meta(GIT_SYNC_CONFIG_KEY)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn set_git_sync_config(
+    config: GitSyncConfig,
+    state: State<AppState>,
+) -> Result<GitSyncConfig, String> {
+    validate_config(&config)?;
+    let encoded = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to encode git sync config: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(GIT_SYNC_CONFIG_KEY, &encoded)?;
+    Ok(config)
+}
+
+/// Sync the vault through its git repository: commit local changes, fetch
+/// and merge the remote branch, then push. Conflicting files keep the local
+/// version and get a conflict copy with the remote one. Progress is emitted
+/// as `git-sync-progress` events and each conflict copy as a
+/// `git-sync-conflict` event. Network access goes through the `git` command
+/// so the user's SSH keys and credential helpers apply.
+#[tauri::command]
+pub async fn git_sync_now(notes_dir: String, app: AppHandle) -> Result<GitSyncSummary, String> {
+    let (config, selective) = {
+        let state = app.state::<AppState>();
+        if state.safe_mode {
+            return Err("Sync is disabled in safe mode".to_string());
+        }
+        let selective = {
+            let cache_lock = lock_or_err(&state.cache)?;
+            let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+            read_selective_sync(cache)?
+        };
+        (get_git_sync_config(state)?, selective)
+    };
+    if SYNC_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("A git sync is already running".to_string());
+    }
+    let guard = SyncGuard;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = guard;
+        let state = app.state::<AppState>();
+        let _pause = watch::pause(&state);
+        run_git_sync(&notes_dir, &config, &selective, &app)
+    })
+    .await
+    .map_err(|e| format!("Git sync failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn commit_file(repo: &Repository, name: &str, content: &str, parents: &[&Commit]) -> Oid {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut builder = repo
+            .treebuilder(parents.first().map(|p| p.tree().unwrap()).as_ref())
+            .unwrap();
+        builder.insert(name, blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        repo.commit(None, &signature, &signature, name, &tree, parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn merges_remote_and_keeps_local_side_of_conflicts() {
+        let root = std::env::temp_dir().join(format!("noteban-gitsync-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init(&root).unwrap();
+        fs::write(root.join("plan.md"), "base").unwrap();
+        assert!(commit_changes(&repo, ".", &SelectiveSyncConfig::default(), "base").unwrap());
+        assert!(!commit_changes(&repo, ".", &SelectiveSyncConfig::default(), "nothing").unwrap());
+        let branch = current_branch(&repo).unwrap();
+
+        let base = repo.head().unwrap().peel_to_commit().unwrap();
+        let theirs = commit_file(&repo, "plan.md", "theirs", &[&base]);
+        let theirs = repo.find_commit(theirs).unwrap();
+        let remote = commit_file(&repo, "added.md", "new", &[&theirs]);
+
+        fs::write(root.join("plan.md"), "ours").unwrap();
+        assert!(commit_changes(&repo, ".", &SelectiveSyncConfig::default(), "local").unwrap());
+
+        let outcome = merge_remote(&repo, &branch, remote).unwrap();
+        assert!(outcome.merged);
+        assert_eq!(
+            outcome.conflicts,
+            vec![(PathBuf::from("plan.md"), b"theirs".to_vec())]
+        );
+        assert_eq!(fs::read_to_string(root.join("plan.md")).unwrap(), "ours");
+        assert_eq!(fs::read_to_string(root.join("added.md")).unwrap(), "new");
+        assert_eq!(
+            repo.head()
+                .unwrap()
+                .peel_to_commit()
+                .unwrap()
+                .parent_count(),
+            2
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
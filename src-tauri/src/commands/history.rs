@@ -0,0 +1,240 @@
+use crate::commands::audit;
+use crate::commands::mounts::ensure_writable;
+use crate::commands::notes::{
+    atomic_write, ensure_modifiable, get_file_mtime, parse_note, parse_note_content,
+    record_written_content, serialize_note, validate_existing_path_within_base, NoteWithTags,
+};
+use crate::crypto::Key;
+use crate::history::{self, SnapshotInfo, VersionDiff};
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::AppState;
+use chrono::Utc;
+use std::fs;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Profile whose cache is currently open; history is stored per profile
+pub(crate) fn active_profile_id(state: &State<AppState>) -> Option<String> {
+    match state.cache.lock() {
+        Ok(cache_lock) => cache_lock.as_ref().map(|cache| cache.profile_id.clone()),
+        Err(_) => None,
+    }
+}
+
+/// Open profile plus the key sealing its history, set when the cache is encrypted
+pub(crate) fn history_access(state: &State<AppState>) -> Option<(String, Option<Key>)> {
+    match state.cache.lock() {
+        Ok(cache_lock) => cache_lock
+            .as_ref()
+            .map(|cache| (cache.profile_id.clone(), cache.history_key())),
+        Err(_) => None,
+    }
+}
+
+/// List the stored versions of a note, newest first
+#[tauri::command]
+pub fn list_versions(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<Vec<SnapshotInfo>, String> {
+    let base_path = PathBuf::from(&notes_dir);
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, &base_path)?;
+
+    let (profile_id, _) = history_access(&state).ok_or("Cache is not initialized")?;
+    let note = parse_note(&path)?;
+    let dir = history::note_history_dir(&profile_id, &note.frontmatter.id)?;
+    Ok(history::list_snapshots_in(&dir))
+}
+
+/// Diff two stored versions of a note, or a stored version against the
+/// current file when `to` is omitted
+#[tauri::command]
+pub fn diff_versions(
+    notes_dir: String,
+    file_path: String,
+    from: String,
+    to: Option<String>,
+    state: State<AppState>,
+) -> Result<VersionDiff, String> {
+    let base_path = PathBuf::from(&notes_dir);
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, &base_path)?;
+
+    let (profile_id, key) = history_access(&state).ok_or("Cache is not initialized")?;
+    let current = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let note_id = parse_note_content(&current, &path)?.frontmatter.id;
+    let dir = history::note_history_dir(&profile_id, &note_id)?;
+
+    let old = history::read_snapshot_in(&dir, &from, key.as_ref())?;
+    let (new, new_label) = match to {
+        Some(to) => (history::read_snapshot_in(&dir, &to, key.as_ref())?, to),
+        None => (current, "current".to_string()),
+    };
+
+    Ok(history::diff_texts(&old, &new, &from, &new_label))
+}
+
+/// Replace a note's file with `restored` text, keeping its identity and
+/// location. The current content is snapshotted first so the restore itself
+/// can be undone. Locked notes are refused.
+pub(crate) fn write_restored_note(
+    path: &PathBuf,
+    previous_content: &str,
+    restored: &str,
+    state: &State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let current = parse_note_content(previous_content, path)?;
+    ensure_modifiable(&current.frontmatter, false)?;
+    let note_id = current.frontmatter.id;
+    let mut note = parse_note_content(restored, path)?;
+    note.frontmatter.id = note_id.clone();
+    note.frontmatter.modified = Utc::now();
+
+    if let Some((profile_id, key)) = history_access(state) {
+        if let Err(e) =
+            history::save_snapshot(&profile_id, &note_id, previous_content, key.as_ref())
+        {
+            log::warn!("Failed to snapshot note before restore: {}", e);
+        }
+    }
+
+    let file_content = serialize_note(&note.frontmatter, &note.content);
+    record_written_content(&path.to_string_lossy(), &file_content, state);
+    atomic_write(path, &file_content)?;
+
+    let inline_tags = extract_inline_tags(&note.content);
+
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            let hash = compute_content_hash(&file_content);
+            let mtime = get_file_mtime(path).unwrap_or(0);
+            if let Err(e) = cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+                log::warn!("Cache update failed for restored note: {}", e);
+            }
+        }
+    }
+
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+/// Replace a note with one of its stored versions
+#[tauri::command]
+pub fn restore_version(
+    notes_dir: String,
+    file_path: String,
+    version_id: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = restore_note_version(&notes_dir, &file_path, &version_id, &state);
+    audit::record(&state, "restore", &file_path, None, &result);
+    result
+}
+
+fn restore_note_version(
+    notes_dir: &str,
+    file_path: &str,
+    version_id: &str,
+    state: &State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let base_path = PathBuf::from(notes_dir);
+    let path = PathBuf::from(file_path);
+    validate_existing_path_within_base(&path, &base_path)?;
+    ensure_writable(&path, state)?;
+
+    let (profile_id, key) = history_access(state).ok_or("Cache is not initialized")?;
+    let previous_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let note_id = parse_note_content(&previous_content, &path)?.frontmatter.id;
+    let dir = history::note_history_dir(&profile_id, &note_id)?;
+
+    let snapshot = history::read_snapshot_in(&dir, version_id, key.as_ref())?;
+    write_restored_note(&path, &previous_content, &snapshot, state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TestVault, TEST_PROFILE};
+
+    /// A note with one stored version whose heading differs from the file
+    fn note_with_version(vault: &TestVault, extra: &str) -> (String, String, SnapshotInfo) {
+        let id = format!("history-{}", uuid::Uuid::new_v4());
+        let file_path = vault.note("a.md", &id, extra);
+        let old = vault.read("a.md").replace("# ", "# Old ");
+        let saved = history::save_snapshot(TEST_PROFILE, &id, &old, None)
+            .unwrap()
+            .unwrap();
+        (id, file_path, saved)
+    }
+
+    #[test]
+    fn restores_versions_keeping_the_current_one() {
+        let vault = TestVault::new();
+        let (id, file_path, saved) = note_with_version(&vault, "");
+        let versions = list_versions(vault.notes_dir(), file_path.clone(), vault.state()).unwrap();
+        assert_eq!(versions.len(), 1);
+
+        let restored = restore_version(
+            vault.notes_dir(),
+            file_path.clone(),
+            saved.id.clone(),
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(restored.note.frontmatter.id, id);
+        assert!(vault.read("a.md").contains(&format!("# Old {}", id)));
+        // The content it replaced became a version of its own
+        let versions = list_versions(vault.notes_dir(), file_path.clone(), vault.state()).unwrap();
+        assert_eq!(versions.len(), 2);
+
+        assert!(restore_version(
+            vault.notes_dir(),
+            file_path,
+            "../escape".to_string(),
+            vault.state()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn diffs_a_version_against_the_current_file() {
+        let vault = TestVault::new();
+        let (_, file_path, saved) = note_with_version(&vault, "");
+        let diff = diff_versions(
+            vault.notes_dir(),
+            file_path.clone(),
+            saved.id.clone(),
+            None,
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!((diff.additions, diff.deletions), (1, 1));
+        assert!(diff.unified.contains("-# Old "));
+        assert!(diff_versions(
+            vault.notes_dir(),
+            file_path,
+            saved.id.clone(),
+            Some(saved.id),
+            vault.state()
+        )
+        .unwrap()
+        .hunks
+        .is_empty());
+    }
+
+    #[test]
+    fn refuses_to_restore_over_a_locked_note() {
+        let vault = TestVault::new();
+        let (_, file_path, saved) = note_with_version(&vault, "locked: true\n");
+        let before = vault.read("a.md");
+        assert!(restore_version(vault.notes_dir(), file_path, saved.id, vault.state()).is_err());
+        assert_eq!(vault.read("a.md"), before);
+    }
+}
@@ -0,0 +1,2276 @@
+use crate::commands::audit;
+use crate::commands::mounts::{ensure_not_mount_folder, ensure_writable};
+use crate::commands::notes::{
+    atomic_write, clean_aliases, ensure_safe_relative_path, file_times, first_heading,
+    get_file_mtime, is_skipped_dir_name, parse_note_content, record_write, sanitize_tags,
+    serialize_note, slugify_or_fallback, split_frontmatter, FrontmatterFormat, Note,
+    NoteFrontmatter,
+};
+use crate::commands::watch;
+use crate::lock_or_err;
+use crate::logging;
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::vault_config;
+use crate::AppState;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+use uuid::Uuid;
+use walkdir::WalkDir;
+use zip::ZipArchive;
+
+/// Frontmatter keys noteban manages itself; imported values are kept under a prefix
+const RESERVED_KEYS: [&str; 14] = [
+    "id", "title", "created", "modified", "date", "due", "column", "order", "pinned", "priority",
+    "color", "cover", "icon", "archived",
+];
+
+lazy_static! {
+    // [[Note]], [[Note|Alias]], [[Note#Heading]] and embeds ![[image.png|300]]
+    pub(crate) static ref WIKILINK_REGEX: Regex = Regex::new(r"(!?)\[\[([^\[\]\n]+?)\]\]").unwrap();
+    // ![alt](relative/path.png)
+    pub(crate) static ref MARKDOWN_IMAGE_REGEX: Regex = Regex::new(r"!\[([^\]\n]*)\]\(([^)\s]+)\)").unwrap();
+    // [text](target) and ![alt](target)
+    static ref MARKDOWN_LINK_REGEX: Regex = Regex::new(r"(!?)\[([^\]\n]*)\]\(([^)\s]+)\)").unwrap();
+    // Notion appends a 32 hex digit page id to exported file and folder names
+    static ref NOTION_ID_REGEX: Regex = Regex::new(r"^(.+?)\s+[0-9a-f]{32}$").unwrap();
+    // `Property: value` lines under the title of a database row
+    static ref NOTION_PROPERTY_REGEX: Regex = Regex::new(r"^([^:\n]{1,60}):\s*(.*)$").unwrap();
+    // `key: value` metadata lines closing every Joplin item
+    static ref JOPLIN_META_REGEX: Regex = Regex::new(r"^([a-z_]+): ?(.*)$").unwrap();
+    // Links to notes and resources by id: [text](:/0123...) and ![alt](:/0123...)
+    static ref JOPLIN_LINK_REGEX: Regex = Regex::new(r"(!?)\[([^\]\n]*)\]\(:/([0-9a-f]{32})\)").unwrap();
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportOptions {
+    /// Folder inside the notes directory that receives the import
+    #[serde(default)]
+    pub target_folder: Option<String>,
+    /// Column for imported notes; defaults to `todo`
+    #[serde(default)]
+    pub column: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub notes_imported: usize,
+    pub attachments_copied: usize,
+    /// Source files that were not imported
+    pub skipped: Vec<String>,
+    /// Links that could not be resolved and similar non-fatal problems
+    pub warnings: Vec<String>,
+}
+
+/// Tags from a YAML `tags` value, either a list or a comma/space separated string
+fn yaml_tags(value: &serde_yaml::Value) -> Vec<String> {
+    let raw: Vec<String> = match value {
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        serde_yaml::Value::String(text) => text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    raw.into_iter()
+        .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+        .collect()
+}
+
+fn yaml_aliases(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        serde_yaml::Value::String(text) => vec![text.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// Markdown link target from `from_dir` to `to`, with each segment URL-encoded
+pub(crate) fn relative_link(from_dir: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to_components: Vec<Component> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut segments: Vec<String> = vec!["..".to_string(); from.len() - common];
+    segments.extend(
+        to_components[common..]
+            .iter()
+            .map(|c| urlencoding::encode(&c.as_os_str().to_string_lossy()).into_owned()),
+    );
+    segments.join("/")
+}
+
+/// Pick a free `<slug>.md` in `dir`, also avoiding paths planned earlier in the import
+fn unique_note_path(dir: &Path, slug: &str, taken: &mut HashSet<PathBuf>) -> PathBuf {
+    let mut path = dir.join(format!("{}.md", slug));
+    let mut counter = 1;
+    while taken.contains(&path) || path.exists() {
+        path = dir.join(format!("{}-{}.md", slug, counter));
+        counter += 1;
+    }
+    taken.insert(path.clone());
+    path
+}
+
+/// Resolve and check the directory an import writes into
+/// Column imported notes go to: the chosen one, else the default column of
+/// the board they land on
+fn import_column(notes_dir: &str, target: &Path, options: &ImportOptions) -> String {
+    options
+        .column
+        .clone()
+        .unwrap_or_else(|| vault_config::default_column(Path::new(notes_dir), target))
+}
+
+fn import_target(
+    notes_dir: &str,
+    options: &ImportOptions,
+    state: &State<AppState>,
+) -> Result<PathBuf, String> {
+    let base = PathBuf::from(notes_dir);
+    if !base.is_dir() {
+        return Err("Notes directory does not exist".to_string());
+    }
+    let target = match &options.target_folder {
+        Some(folder) => {
+            let folder = PathBuf::from(folder);
+            ensure_safe_relative_path(&folder)?;
+            ensure_not_mount_folder(&folder)?;
+            base.join(folder)
+        }
+        None => base,
+    };
+    ensure_writable(&target, state)?;
+    Ok(target)
+}
+
+/// Index imported notes so they show up without waiting for the watcher, and
+/// add each to the audit log
+fn index_imported(notes: &[Note], state: &State<AppState>) {
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            for note in notes {
+                let path = PathBuf::from(&note.file_path);
+                let hash = compute_content_hash(&serialize_note(&note.frontmatter, &note.content));
+                let mtime = get_file_mtime(&path).unwrap_or(0);
+                let inline_tags = extract_inline_tags(&note.content);
+                if let Err(e) = cache.upsert_note(note, &hash, mtime, &inline_tags) {
+                    log::warn!("Cache update failed for imported note: {}", e);
+                }
+            }
+        }
+    }
+    for note in notes {
+        audit::record(
+            state,
+            "import",
+            &note.file_path,
+            None,
+            &Ok::<(), String>(()),
+        );
+    }
+}
+
+/// Ids of the notes already in the vault, so imports don't duplicate them
+fn vault_note_ids(state: &State<AppState>) -> Result<HashSet<String>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    match cache_lock.as_ref() {
+        Some(cache) => Ok(cache
+            .get_all_notes()?
+            .into_iter()
+            .map(|cached| cached.note.frontmatter.id)
+            .collect()),
+        None => Ok(HashSet::new()),
+    }
+}
+
+/// A markdown file of the source vault and where it will be written
+struct PlannedNote {
+    source: PathBuf,
+    /// Path inside the vault without the `.md` extension, lowercased
+    key: String,
+    title: String,
+    dest: PathBuf,
+}
+
+struct ObsidianVault {
+    root: PathBuf,
+    notes: Vec<PlannedNote>,
+    /// Lowercased file name and vault-relative path of every non-note file
+    attachments: HashMap<String, PathBuf>,
+}
+
+impl ObsidianVault {
+    fn scan(root: &Path, target: &Path, summary: &mut ImportSummary) -> Result<Self, String> {
+        let mut notes = Vec::new();
+        let mut attachments = HashMap::new();
+        let mut taken = HashSet::new();
+
+        // Obsidian keeps settings in .obsidian and deleted files in .trash
+        let walker = WalkDir::new(root)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'));
+        for entry in walker {
+            let entry = entry.map_err(|e| format!("Failed to read vault: {}", e))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(root)
+                .map_err(|e| e.to_string())?
+                .to_path_buf();
+            let relative_name = relative.to_string_lossy().replace('\\', "/");
+
+            if relative.extension().is_some_and(|ext| ext == "md") {
+                let folder = relative.parent().unwrap_or(Path::new(""));
+                if folder
+                    .components()
+                    .any(|c| c.as_os_str().to_string_lossy().ends_with(".attachments"))
+                {
+                    summary.skipped.push(relative_name);
+                    continue;
+                }
+                let title = relative
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let id = Uuid::new_v4().to_string();
+                let dest = unique_note_path(
+                    &target.join(folder),
+                    &slugify_or_fallback(&title, &id),
+                    &mut taken,
+                );
+                notes.push(PlannedNote {
+                    source: entry.path().to_path_buf(),
+                    key: relative_name
+                        .strip_suffix(".md")
+                        .unwrap_or(&relative_name)
+                        .to_lowercase(),
+                    title,
+                    dest,
+                });
+            } else {
+                let file_name = entry.file_name().to_string_lossy().to_lowercase();
+                attachments
+                    .entry(file_name)
+                    .or_insert_with(|| entry.path().to_path_buf());
+                attachments.insert(relative_name.to_lowercase(), entry.path().to_path_buf());
+            }
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            notes,
+            attachments,
+        })
+    }
+
+    /// Obsidian resolves `[[Name]]` by file name anywhere in the vault and
+    /// `[[folder/Name]]` by path; prefer a match in the linking note's folder
+    fn find_note(&self, target: &str, from: &PlannedNote) -> Option<&PlannedNote> {
+        let key = target.trim().trim_end_matches(".md").to_lowercase();
+        if key.is_empty() {
+            return None;
+        }
+        if let Some(exact) = self.notes.iter().find(|note| note.key == key) {
+            return Some(exact);
+        }
+        let from_folder = from.key.rsplit_once('/').map(|(folder, _)| folder);
+        let mut candidates = self
+            .notes
+            .iter()
+            .filter(|note| note.key.rsplit('/').next() == Some(key.as_str()));
+        let first = candidates.next()?;
+        let same_folder = std::iter::once(first)
+            .chain(candidates)
+            .find(|note| note.key.rsplit_once('/').map(|(folder, _)| folder) == from_folder);
+        Some(same_folder.unwrap_or(first))
+    }
+
+    fn find_attachment(&self, target: &str, from: &PlannedNote) -> Option<&PathBuf> {
+        let decoded = urlencoding::decode(target)
+            .map(|t| t.into_owned())
+            .unwrap_or_else(|_| target.to_string());
+        // Paths relative to the note, then to the vault root, then bare file names
+        let beside_note = from.source.parent().map(|dir| dir.join(&decoded));
+        if let Some(path) = beside_note.filter(|p| p.is_file() && p.starts_with(&self.root)) {
+            let relative = path.strip_prefix(&self.root).ok()?;
+            return self
+                .attachments
+                .get(&relative.to_string_lossy().replace('\\', "/").to_lowercase());
+        }
+        let key = decoded.trim_start_matches("./").to_lowercase();
+        self.attachments.get(&key).or_else(|| {
+            let file_name = key.rsplit('/').next()?;
+            self.attachments.get(file_name)
+        })
+    }
+}
+
+/// Attachments a note needs copied into its `.attachments` folder, by file name
+type AttachmentCopies = BTreeMap<String, PathBuf>;
+
+fn attachments_dir_name(note_dest: &Path) -> String {
+    format!(
+        "{}.attachments",
+        note_dest
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    )
+}
+
+/// Queue `source` for copying next to the note and return the link to use
+fn attachment_link(note_dest: &Path, source: &Path, copies: &mut AttachmentCopies) -> String {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    attachment_link_named(note_dest, source, &name, copies)
+}
+
+/// Like `attachment_link`, storing the copy as `name`. Different files
+/// sharing a name get a numbered suffix.
+fn attachment_link_named(
+    note_dest: &Path,
+    source: &Path,
+    name: &str,
+    copies: &mut AttachmentCopies,
+) -> String {
+    let original = name.replace(['/', '\\'], "-");
+    let (stem, extension) = match original.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+        _ => (original.clone(), String::new()),
+    };
+    let mut file_name = original;
+    let mut counter = 1;
+    while let Some(existing) = copies.get(&file_name) {
+        if existing == source {
+            break;
+        }
+        file_name = format!("{}-{}{}", stem, counter, extension);
+        counter += 1;
+    }
+    copies.insert(file_name.clone(), source.to_path_buf());
+    format!(
+        "{}/{}",
+        attachments_dir_name(note_dest),
+        urlencoding::encode(&file_name).into_owned()
+    )
+}
+
+/// Copy a note's attachments and write the note itself
+fn write_imported_note(
+    dest: &Path,
+    frontmatter: NoteFrontmatter,
+    content: String,
+    copies: &AttachmentCopies,
+    summary: &mut ImportSummary,
+    before_write: &mut impl FnMut(&Path),
+) -> Result<Note, String> {
+    let dir = dest.parent().ok_or("Invalid import destination")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create folder: {}", e))?;
+    if !copies.is_empty() {
+        let attachments_dir = dest.with_extension("attachments");
+        fs::create_dir_all(&attachments_dir)
+            .map_err(|e| format!("Failed to create attachments folder: {}", e))?;
+        for (file_name, source) in copies {
+            match fs::copy(source, attachments_dir.join(file_name)) {
+                Ok(_) => summary.attachments_copied += 1,
+                Err(e) => summary.warnings.push(format!(
+                    "{}: failed to copy {}: {}",
+                    frontmatter.title, file_name, e
+                )),
+            }
+        }
+    }
+
+    before_write(dest);
+    atomic_write(&dest.to_path_buf(), &serialize_note(&frontmatter, &content))?;
+    summary.notes_imported += 1;
+    Ok(Note {
+        frontmatter,
+        content,
+        file_path: dest.to_string_lossy().to_string(),
+    })
+}
+
+/// Rewrite wikilinks and embeds outside fenced code blocks
+fn rewrite_obsidian_links(
+    body: &str,
+    note: &PlannedNote,
+    vault: &ObsidianVault,
+    copies: &mut AttachmentCopies,
+    warnings: &mut Vec<String>,
+) -> String {
+    let note_dir = note.dest.parent().unwrap_or(Path::new(""));
+    let attachments_dir = attachments_dir_name(&note.dest);
+    let mut attach = |source: &PathBuf| attachment_link(&note.dest, source, copies);
+
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains('[') {
+            out.push_str(line);
+            continue;
+        }
+
+        let line = WIKILINK_REGEX.replace_all(line, |caps: &Captures| {
+            let embed = &caps[1] == "!";
+            let (target, alias) = match caps[2].split_once('|') {
+                Some((target, alias)) => (target, Some(alias.trim())),
+                None => (&caps[2], None),
+            };
+            let target = target.split('#').next().unwrap_or_default().trim();
+
+            if let Some(linked) = vault.find_note(target, note) {
+                let text = alias.unwrap_or(&linked.title);
+                return format!("[{}]({})", text, relative_link(note_dir, &linked.dest));
+            }
+            if let Some(source) = vault.find_attachment(target, note) {
+                let link = attach(source);
+                // In embeds the part after `|` is a size, not a caption
+                let text = if embed {
+                    target
+                } else {
+                    alias.unwrap_or(target)
+                };
+                return if embed {
+                    format!("![{}]({})", text, link)
+                } else {
+                    format!("[{}]({})", text, link)
+                };
+            }
+            warnings.push(format!("{}: unresolved link [[{}]]", note.title, &caps[2]));
+            caps[0].to_string()
+        });
+
+        let line = MARKDOWN_IMAGE_REGEX.replace_all(&line, |caps: &Captures| {
+            let target = &caps[2];
+            let already_copied = target.starts_with(&format!("{}/", attachments_dir));
+            if already_copied || target.contains("://") || target.starts_with('#') {
+                return caps[0].to_string();
+            }
+            match vault.find_attachment(target, note) {
+                Some(source) => format!("![{}]({})", &caps[1], attach(source)),
+                None => caps[0].to_string(),
+            }
+        });
+        out.push_str(&line);
+    }
+    out
+}
+
+/// Build noteban frontmatter for an imported markdown file, keeping its other
+/// YAML properties as custom frontmatter. Properties that clash with the keys
+/// noteban manages are kept under `<prefix>_<key>`.
+fn properties_frontmatter(
+    title: &str,
+    source: &Path,
+    yaml: Option<&str>,
+    content: &str,
+    column: &str,
+    prefix: &str,
+    warnings: &mut Vec<String>,
+) -> NoteFrontmatter {
+    let (created, modified) = file_times(source);
+    let mut tags = Vec::new();
+    let mut aliases = Vec::new();
+    let mut extra = serde_yaml::Mapping::new();
+
+    let mapping = yaml.and_then(
+        |yaml| match serde_yaml::from_str::<serde_yaml::Mapping>(yaml) {
+            Ok(mapping) => Some(mapping),
+            Err(e) => {
+                warnings.push(format!("{}: ignored invalid properties: {}", title, e));
+                None
+            }
+        },
+    );
+    for (key, value) in mapping.into_iter().flatten() {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        match key {
+            "tags" | "tag" => tags.extend(yaml_tags(&value)),
+            "aliases" | "alias" => aliases.extend(yaml_aliases(&value)),
+            key if RESERVED_KEYS.contains(&key) => {
+                extra.insert(format!("{}_{}", prefix, key).into(), value);
+            }
+            key => {
+                extra.insert(key.into(), value);
+            }
+        }
+    }
+    tags.extend(extract_inline_tags(content));
+    let mut tags = sanitize_tags(tags);
+    tags.sort();
+    tags.dedup();
+
+    NoteFrontmatter {
+        id: Uuid::new_v4().to_string(),
+        title: title.to_string(),
+        created,
+        modified,
+        date: None,
+        due: None,
+        column: column.to_string(),
+        tags,
+        order: 0,
+        pinned: false,
+        archived: false,
+        priority: None,
+        color: None,
+        cover: None,
+        icon: None,
+        aliases: clean_aliases(aliases),
+        extra,
+        format: FrontmatterFormat::Yaml,
+    }
+}
+
+/// Convert an Obsidian vault into notes under `target`. `before_write` is
+/// called with every note path before it is written.
+fn import_obsidian_vault(
+    vault_path: &Path,
+    target: &Path,
+    column: &str,
+    mut before_write: impl FnMut(&Path),
+) -> Result<(ImportSummary, Vec<Note>), String> {
+    let mut summary = ImportSummary::default();
+    let vault = ObsidianVault::scan(vault_path, target, &mut summary)?;
+    let mut imported = Vec::new();
+    let mut referenced: HashSet<PathBuf> = HashSet::new();
+
+    for note in &vault.notes {
+        let text = match fs::read_to_string(&note.source) {
+            Ok(text) => text,
+            Err(e) => {
+                summary
+                    .warnings
+                    .push(format!("{}: failed to read: {}", note.title, e));
+                summary
+                    .skipped
+                    .push(note.source.to_string_lossy().to_string());
+                continue;
+            }
+        };
+        let (yaml, body) = split_frontmatter(&text);
+        let mut copies = AttachmentCopies::new();
+        let content = rewrite_obsidian_links(
+            body.trim(),
+            note,
+            &vault,
+            &mut copies,
+            &mut summary.warnings,
+        );
+        let frontmatter = properties_frontmatter(
+            &note.title,
+            &note.source,
+            yaml,
+            &content,
+            column,
+            "obsidian",
+            &mut summary.warnings,
+        );
+
+        referenced.extend(copies.values().cloned());
+        imported.push(write_imported_note(
+            &note.dest,
+            frontmatter,
+            content,
+            &copies,
+            &mut summary,
+            &mut before_write,
+        )?);
+    }
+
+    let mut unreferenced: Vec<String> = vault
+        .attachments
+        .values()
+        .filter(|source| !referenced.contains(*source))
+        .filter_map(|source| source.strip_prefix(vault_path).ok())
+        .map(|relative| relative.to_string_lossy().to_string())
+        .collect();
+    unreferenced.sort();
+    unreferenced.dedup();
+    summary.skipped.extend(unreferenced);
+
+    Ok((summary, imported))
+}
+
+/// Import an Obsidian vault: folders are kept, `[[wikilinks]]` become
+/// markdown links, embedded files are copied into each note's `.attachments`
+/// folder and tags come from both properties and inline `#tags`. The source
+/// vault is never modified.
+#[tauri::command]
+pub fn import_obsidian(
+    notes_dir: String,
+    vault_path: String,
+    options: Option<ImportOptions>,
+    state: State<AppState>,
+) -> Result<ImportSummary, String> {
+    let _pause = watch::pause(&state);
+    let options = options.unwrap_or_default();
+    let vault = PathBuf::from(&vault_path);
+    if !vault.is_dir() {
+        return Err("Obsidian vault does not exist".to_string());
+    }
+    let target = import_target(&notes_dir, &options, &state)?;
+    let (vault_canonical, target_canonical) = (
+        vault.canonicalize().map_err(|e| e.to_string())?,
+        PathBuf::from(&notes_dir)
+            .canonicalize()
+            .map_err(|e| e.to_string())?,
+    );
+    if target_canonical.starts_with(&vault_canonical)
+        || vault_canonical.starts_with(&target_canonical)
+    {
+        return Err("The vault and the notes directory must not contain each other".to_string());
+    }
+
+    let column = import_column(&notes_dir, &target, &options);
+    let (summary, imported) = import_obsidian_vault(&vault, &target, &column, |path| {
+        record_write(&path.to_string_lossy(), &state)
+    })?;
+    index_imported(&imported, &state);
+    Ok(summary)
+}
+
+/// Extract a Notion export, including the part archives large exports are
+/// split into
+fn extract_export<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    dest: &Path,
+    nested: bool,
+) -> Result<(), String> {
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Export is corrupt: {}", e))?;
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Export contains an unsafe path: {}", entry.name()))?;
+        if entry.is_dir() {
+            continue;
+        }
+        if !nested && relative.extension().is_some_and(|ext| ext == "zip") {
+            let mut bytes = Vec::new();
+            entry
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read {}: {}", relative.display(), e))?;
+            let mut part = ZipArchive::new(Cursor::new(bytes))
+                .map_err(|e| format!("{} is not a valid archive: {}", relative.display(), e))?;
+            extract_export(&mut part, dest, true)?;
+            continue;
+        }
+        let path = dest.join(&relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        File::create(&path)
+            .and_then(|mut out| io::copy(&mut entry, &mut out))
+            .map_err(|e| format!("Failed to extract {}: {}", relative.display(), e))?;
+    }
+    Ok(())
+}
+
+fn strip_notion_id(name: &str) -> String {
+    match NOTION_ID_REGEX.captures(name) {
+        Some(caps) => caps[1].to_string(),
+        None => name.to_string(),
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+fn parse_notion_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%B %d, %Y %I:%M %p")
+                .ok()
+                .map(|dt| dt.and_utc())
+        })
+        .or_else(|| {
+            ["%B %d, %Y", "%Y-%m-%d"].iter().find_map(|format| {
+                NaiveDate::parse_from_str(value, format)
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|dt| dt.and_utc())
+            })
+        })
+}
+
+/// A markdown page of a Notion export and where it will be written
+struct NotionPage {
+    source: PathBuf,
+    text: String,
+    title: String,
+    dest: PathBuf,
+    /// Row of a database, whose properties follow the title
+    database_row: bool,
+}
+
+/// Split `# Title` off the top of an exported page
+fn notion_title(text: &str) -> (Option<&str>, &str) {
+    let trimmed = text.trim_start_matches('\u{feff}');
+    match trimmed.split_once('\n') {
+        Some((first, rest)) if first.starts_with("# ") => (Some(first[2..].trim()), rest),
+        None if trimmed.starts_with("# ") => (Some(trimmed[2..].trim()), ""),
+        _ => (None, trimmed),
+    }
+}
+
+/// Leading `Property: value` lines of a database row and the remaining body
+fn notion_properties(body: &str) -> (Vec<(String, String)>, &str) {
+    let body = body.trim_start_matches(['\r', '\n']);
+    let mut properties = Vec::new();
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let Some(caps) = NOTION_PROPERTY_REGEX.captures(line.trim_end()) else {
+            break;
+        };
+        properties.push((caps[1].trim().to_string(), caps[2].trim().to_string()));
+        offset += line.len();
+    }
+    (properties, &body[offset..])
+}
+
+fn notion_frontmatter(
+    title: &str,
+    source: &Path,
+    properties: Vec<(String, String)>,
+    column: &str,
+) -> NoteFrontmatter {
+    let (created, modified) = file_times(source);
+    let mut frontmatter = NoteFrontmatter {
+        id: Uuid::new_v4().to_string(),
+        title: title.to_string(),
+        created,
+        modified,
+        date: None,
+        due: None,
+        column: column.to_string(),
+        tags: Vec::new(),
+        order: 0,
+        pinned: false,
+        archived: false,
+        priority: None,
+        color: None,
+        cover: None,
+        icon: None,
+        aliases: Vec::new(),
+        extra: serde_yaml::Mapping::new(),
+        format: FrontmatterFormat::Yaml,
+    };
+
+    let mut tags = Vec::new();
+    for (name, value) in properties {
+        let key = name.to_lowercase().replace(' ', "_");
+        match key.as_str() {
+            "tags" | "tag" => tags.extend(value.split(',').map(|tag| tag.trim().to_lowercase())),
+            "created" | "created_time" => match parse_notion_date(&value) {
+                Some(created) => frontmatter.created = created,
+                None => {
+                    frontmatter
+                        .extra
+                        .insert(format!("notion_{}", key).into(), value.into());
+                }
+            },
+            "last_edited_time" | "updated" => {
+                if let Some(modified) = parse_notion_date(&value) {
+                    frontmatter.modified = modified;
+                }
+            }
+            _ if value.is_empty() => {}
+            key if RESERVED_KEYS.contains(&key) => {
+                frontmatter
+                    .extra
+                    .insert(format!("notion_{}", key).into(), value.into());
+            }
+            _ => {
+                frontmatter.extra.insert(key.into(), value.into());
+            }
+        }
+    }
+    frontmatter.tags = sanitize_tags(tags);
+    frontmatter.created = frontmatter.created.min(frontmatter.modified);
+    frontmatter
+}
+
+/// Point links between pages at the imported notes and relocate linked
+/// files into the note's attachments folder
+fn rewrite_notion_links(
+    body: &str,
+    page: &NotionPage,
+    pages: &HashMap<PathBuf, PathBuf>,
+    root: &Path,
+    copies: &mut AttachmentCopies,
+    warnings: &mut Vec<String>,
+) -> String {
+    let note_dir = page.dest.parent().unwrap_or(Path::new(""));
+    let source_dir = page.source.parent().unwrap_or(root);
+
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || !line.contains("](") {
+            out.push_str(line);
+            continue;
+        }
+
+        let line = MARKDOWN_LINK_REGEX.replace_all(line, |caps: &Captures| {
+            let target = &caps[3];
+            if target.contains("://") || target.starts_with('#') || target.starts_with("mailto:") {
+                return caps[0].to_string();
+            }
+            let decoded = urlencoding::decode(target)
+                .map(|t| t.into_owned())
+                .unwrap_or_else(|_| target.to_string());
+            let resolved = normalize_path(&source_dir.join(decoded));
+            if !resolved.starts_with(root) {
+                return caps[0].to_string();
+            }
+
+            if let Some(dest) = pages.get(&resolved) {
+                return format!("[{}]({})", &caps[2], relative_link(note_dir, dest));
+            }
+            if resolved.is_file() && resolved.extension().map_or(true, |ext| ext != "csv") {
+                let link = attachment_link(&page.dest, &resolved, copies);
+                return format!("{}[{}]({})", &caps[1], &caps[2], link);
+            }
+            if resolved.extension().is_some_and(|ext| ext == "md") {
+                warnings.push(format!("{}: unresolved link {}", page.title, target));
+            }
+            caps[0].to_string()
+        });
+        out.push_str(&line);
+    }
+    out
+}
+
+/// Convert an extracted Notion markdown export into notes under `target`
+fn import_notion_export(
+    root: &Path,
+    target: &Path,
+    column: &str,
+    mut before_write: impl FnMut(&Path),
+) -> Result<(ImportSummary, Vec<Note>), String> {
+    let mut summary = ImportSummary::default();
+    let mut databases = HashSet::new();
+    let mut sources = Vec::new();
+    let mut html_pages = 0;
+
+    for entry in WalkDir::new(root).min_depth(1).sort_by_file_name() {
+        let entry = entry.map_err(|e| format!("Failed to read export: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("md") => sources.push(path.to_path_buf()),
+            Some("csv") => {
+                // `Tasks <id>.csv` (or `_all.csv`) describes the rows in `Tasks <id>/`
+                let stem = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let folder = stem.strip_suffix("_all").unwrap_or(&stem).to_string();
+                databases.insert(path.with_file_name(folder));
+                summary.skipped.push(path.to_string_lossy().to_string());
+            }
+            Some("html") => {
+                html_pages += 1;
+                summary.skipped.push(path.to_string_lossy().to_string());
+            }
+            _ => {}
+        }
+    }
+    if html_pages > 0 {
+        summary.warnings.push(format!(
+            "{} HTML pages were skipped; export from Notion as Markdown & CSV to import them",
+            html_pages
+        ));
+    }
+
+    let mut taken = HashSet::new();
+    let mut pages = Vec::new();
+    for source in sources {
+        let text = match fs::read_to_string(&source) {
+            Ok(text) => text,
+            Err(e) => {
+                summary
+                    .warnings
+                    .push(format!("{}: failed to read: {}", source.display(), e));
+                summary.skipped.push(source.to_string_lossy().to_string());
+                continue;
+            }
+        };
+        let relative = source.strip_prefix(root).map_err(|e| e.to_string())?;
+        let folder: PathBuf = relative
+            .parent()
+            .unwrap_or(Path::new(""))
+            .components()
+            .map(|c| strip_notion_id(&c.as_os_str().to_string_lossy()))
+            .collect();
+        let title = match notion_title(&text).0 {
+            Some(title) if !title.is_empty() => title.to_string(),
+            _ => strip_notion_id(
+                &relative
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+        };
+        let id = Uuid::new_v4().to_string();
+        let dest = unique_note_path(
+            &target.join(folder),
+            &slugify_or_fallback(&title, &id),
+            &mut taken,
+        );
+        let database_row = source
+            .parent()
+            .is_some_and(|parent| databases.contains(parent));
+        pages.push(NotionPage {
+            source,
+            text,
+            title,
+            dest,
+            database_row,
+        });
+    }
+
+    let destinations: HashMap<PathBuf, PathBuf> = pages
+        .iter()
+        .map(|page| (page.source.clone(), page.dest.clone()))
+        .collect();
+    let mut imported = Vec::new();
+    for page in &pages {
+        let body = notion_title(&page.text).1;
+        let (properties, body) = if page.database_row {
+            notion_properties(body)
+        } else {
+            (Vec::new(), body)
+        };
+        let mut copies = AttachmentCopies::new();
+        let content = rewrite_notion_links(
+            body.trim(),
+            page,
+            &destinations,
+            root,
+            &mut copies,
+            &mut summary.warnings,
+        );
+        let frontmatter = notion_frontmatter(&page.title, &page.source, properties, column);
+        imported.push(write_imported_note(
+            &page.dest,
+            frontmatter,
+            content,
+            &copies,
+            &mut summary,
+            &mut before_write,
+        )?);
+    }
+
+    Ok((summary, imported))
+}
+
+/// Import a Notion "Markdown & CSV" export, either the downloaded zip or an
+/// extracted folder. Page ids are dropped from names, database rows become
+/// notes whose properties are stored as frontmatter, and linked files move
+/// into each note's `.attachments` folder.
+#[tauri::command]
+pub fn import_notion(
+    notes_dir: String,
+    export_path: String,
+    options: Option<ImportOptions>,
+    state: State<AppState>,
+) -> Result<ImportSummary, String> {
+    let _pause = watch::pause(&state);
+    let options = options.unwrap_or_default();
+    let export = PathBuf::from(&export_path);
+    let target = import_target(&notes_dir, &options, &state)?;
+    let column = import_column(&notes_dir, &target, &options);
+    let mut record = |path: &Path| record_write(&path.to_string_lossy(), &state);
+
+    let (summary, imported) = if export.is_dir() {
+        import_notion_export(&export, &target, &column, &mut record)?
+    } else if export.is_file() {
+        let file = File::open(&export).map_err(|e| format!("Failed to open export: {}", e))?;
+        let mut archive =
+            ZipArchive::new(file).map_err(|e| format!("Export is not a valid archive: {}", e))?;
+        let scratch = std::env::temp_dir().join(format!("noteban-notion-{}", Uuid::new_v4()));
+        let result = extract_export(&mut archive, &scratch, false)
+            .and_then(|_| import_notion_export(&scratch, &target, &column, &mut record));
+        if let Err(e) = fs::remove_dir_all(&scratch) {
+            log::warn!(
+                "Failed to clean up extracted export {}: {}",
+                logging::path(&scratch),
+                e
+            );
+        }
+        result?
+    } else {
+        return Err("Notion export does not exist".to_string());
+    };
+
+    index_imported(&imported, &state);
+    Ok(summary)
+}
+
+const JOPLIN_NOTE: u32 = 1;
+const JOPLIN_FOLDER: u32 = 2;
+const JOPLIN_RESOURCE: u32 = 4;
+const JOPLIN_TAG: u32 = 5;
+const JOPLIN_NOTE_TAG: u32 = 6;
+
+/// One `<id>.md` item of a Joplin export: a title line, an optional body and
+/// a trailing block of `key: value` metadata
+struct JoplinItem {
+    item_type: u32,
+    title: String,
+    body: String,
+    meta: HashMap<String, String>,
+}
+
+impl JoplinItem {
+    fn parse(text: &str) -> Option<Self> {
+        let lines: Vec<&str> = text.trim_end().lines().collect();
+        let mut split = lines.len();
+        while split > 0 && JOPLIN_META_REGEX.is_match(lines[split - 1]) {
+            split -= 1;
+        }
+        let meta: HashMap<String, String> = lines[split..]
+            .iter()
+            .filter_map(|line| JOPLIN_META_REGEX.captures(line))
+            .map(|caps| (caps[1].to_string(), caps[2].to_string()))
+            .collect();
+        let item_type = meta.get("type_")?.parse().ok()?;
+        let head = &lines[..split];
+        Some(Self {
+            item_type,
+            title: head
+                .first()
+                .map(|t| t.trim().to_string())
+                .unwrap_or_default(),
+            body: head
+                .get(1..)
+                .map(|rest| rest.join("\n"))
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            meta,
+        })
+    }
+
+    fn get(&self, key: &str) -> &str {
+        self.meta.get(key).map(String::as_str).unwrap_or("")
+    }
+
+    /// Prefer the user-editable timestamp Joplin keeps beside the sync one
+    fn time(&self, key: &str) -> Option<DateTime<Utc>> {
+        [format!("user_{}", key), key.to_string()]
+            .iter()
+            .find_map(|key| DateTime::parse_from_rfc3339(self.get(key)).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+/// Make a notebook title usable as a single folder name
+fn folder_component(title: &str) -> String {
+    let name = title.replace(['/', '\\'], "-").trim().to_string();
+    if name.is_empty() || name == "." || name == ".." {
+        "Untitled".to_string()
+    } else if is_skipped_dir_name(&name) {
+        format!("{} notebook", name)
+    } else {
+        name
+    }
+}
+
+/// Folder path of a notebook, following parents up to the root
+fn notebook_path(id: &str, folders: &HashMap<String, JoplinItem>) -> PathBuf {
+    let mut parts = Vec::new();
+    let mut current = id;
+    // Bounded in case an export contains a parent cycle
+    while let Some(folder) = folders.get(current).filter(|_| parts.len() < 32) {
+        parts.push(folder_component(&folder.title));
+        current = folder.get("parent_id");
+    }
+    parts.iter().rev().collect()
+}
+
+/// Convert an extracted Joplin export (the contents of a `.jex` or a RAW
+/// export directory) into notes under `target`
+fn import_joplin_export(
+    root: &Path,
+    target: &Path,
+    column: &str,
+    mut before_write: impl FnMut(&Path),
+) -> Result<(ImportSummary, Vec<Note>), String> {
+    let mut summary = ImportSummary::default();
+    let mut notes = Vec::new();
+    let mut folders = HashMap::new();
+    let mut resources = HashMap::new();
+    let mut tags = HashMap::new();
+    let mut note_tags: Vec<(String, String)> = Vec::new();
+
+    let entries = fs::read_dir(root).map_err(|e| format!("Failed to read export: {}", e))?;
+    let mut item_paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    item_paths.sort();
+    for path in item_paths {
+        let Some(item) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| JoplinItem::parse(&text))
+        else {
+            summary.skipped.push(path.to_string_lossy().to_string());
+            continue;
+        };
+        let id = item.get("id").to_string();
+        match item.item_type {
+            JOPLIN_NOTE => notes.push(item),
+            JOPLIN_FOLDER => {
+                folders.insert(id, item);
+            }
+            JOPLIN_RESOURCE => {
+                resources.insert(id, item);
+            }
+            JOPLIN_TAG => {
+                tags.insert(id, item.title);
+            }
+            JOPLIN_NOTE_TAG => note_tags.push((
+                item.get("note_id").to_string(),
+                item.get("tag_id").to_string(),
+            )),
+            // Revisions, master keys and other sync metadata
+            _ => {}
+        }
+    }
+
+    // Resource files are stored as resources/<id>.<extension>
+    let resource_dir = root.join("resources");
+    let mut resource_files: HashMap<String, (PathBuf, String)> = HashMap::new();
+    if let Ok(entries) = fs::read_dir(&resource_dir) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let Some(resource) = resources.get(&id) else {
+                continue;
+            };
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .unwrap_or_else(|| resource.get("file_extension").to_string());
+            let name = match resource.title.as_str() {
+                "" => format!("{}.{}", id, extension),
+                title if title.contains('.') => title.to_string(),
+                title => format!("{}.{}", title, extension),
+            };
+            resource_files.insert(id, (path, name));
+        }
+    }
+
+    let mut tags_by_note: HashMap<String, Vec<String>> = HashMap::new();
+    for (note_id, tag_id) in note_tags {
+        if let Some(tag) = tags.get(&tag_id) {
+            tags_by_note.entry(note_id).or_default().push(tag.clone());
+        }
+    }
+
+    let mut taken = HashSet::new();
+    let destinations: HashMap<String, PathBuf> = notes
+        .iter()
+        .map(|note| {
+            let id = note.get("id").to_string();
+            let folder = notebook_path(note.get("parent_id"), &folders);
+            let dest = unique_note_path(
+                &target.join(folder),
+                &slugify_or_fallback(&note.title, &id),
+                &mut taken,
+            );
+            (id, dest)
+        })
+        .collect();
+
+    let mut imported = Vec::new();
+    for note in &notes {
+        let id = note.get("id");
+        let Some(dest) = destinations.get(id) else {
+            continue;
+        };
+        let note_dir = dest.parent().unwrap_or(target);
+        let mut copies = AttachmentCopies::new();
+        let content = JOPLIN_LINK_REGEX
+            .replace_all(&note.body, |caps: &Captures| {
+                let linked = &caps[3];
+                if let Some(linked_dest) = destinations.get(linked) {
+                    return format!("[{}]({})", &caps[2], relative_link(note_dir, linked_dest));
+                }
+                if let Some((source, name)) = resource_files.get(linked) {
+                    let link = attachment_link_named(dest, source, name, &mut copies);
+                    return format!("{}[{}]({})", &caps[1], &caps[2], link);
+                }
+                summary
+                    .warnings
+                    .push(format!("{}: unresolved link :/{}", note.title, linked));
+                caps[0].to_string()
+            })
+            .into_owned();
+
+        let modified = note.time("updated_time").unwrap_or_else(Utc::now);
+        let created = note.time("created_time").unwrap_or(modified).min(modified);
+        let mut extra = serde_yaml::Mapping::new();
+        for key in ["source_url", "author"] {
+            if !note.get(key).is_empty() {
+                extra.insert(key.into(), note.get(key).into());
+            }
+        }
+        let due = note
+            .get("todo_due")
+            .parse::<i64>()
+            .ok()
+            .filter(|due| *due > 0)
+            .and_then(DateTime::from_timestamp_millis)
+            .map(|due| due.format("%Y-%m-%d").to_string());
+        let frontmatter = NoteFrontmatter {
+            id: Uuid::new_v4().to_string(),
+            title: note.title.clone(),
+            created,
+            modified,
+            date: None,
+            due,
+            column: column.to_string(),
+            tags: sanitize_tags(
+                tags_by_note
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .map(|tag| tag.to_lowercase())
+                    .collect(),
+            ),
+            order: 0,
+            pinned: false,
+            archived: false,
+            priority: None,
+            color: None,
+            cover: None,
+            icon: None,
+            aliases: Vec::new(),
+            extra,
+            format: FrontmatterFormat::Yaml,
+        };
+
+        imported.push(write_imported_note(
+            dest,
+            frontmatter,
+            content,
+            &copies,
+            &mut summary,
+            &mut before_write,
+        )?);
+    }
+
+    Ok((summary, imported))
+}
+
+/// Import a Joplin `.jex` archive or RAW export directory. Notebooks become
+/// folders, tags and resources carry over, and the original created/updated
+/// times are kept.
+#[tauri::command]
+pub fn import_joplin(
+    notes_dir: String,
+    export_path: String,
+    options: Option<ImportOptions>,
+    state: State<AppState>,
+) -> Result<ImportSummary, String> {
+    let _pause = watch::pause(&state);
+    let options = options.unwrap_or_default();
+    let export = PathBuf::from(&export_path);
+    let target = import_target(&notes_dir, &options, &state)?;
+    let column = import_column(&notes_dir, &target, &options);
+    let mut record = |path: &Path| record_write(&path.to_string_lossy(), &state);
+
+    let (summary, imported) = if export.is_dir() {
+        import_joplin_export(&export, &target, &column, &mut record)?
+    } else if export.is_file() {
+        let file = File::open(&export).map_err(|e| format!("Failed to open export: {}", e))?;
+        let scratch = std::env::temp_dir().join(format!("noteban-joplin-{}", Uuid::new_v4()));
+        // `unpack` refuses entries that would land outside `scratch`
+        let result = tar::Archive::new(file)
+            .unpack(&scratch)
+            .map_err(|e| format!("Export is not a valid JEX archive: {}", e))
+            .and_then(|_| import_joplin_export(&scratch, &target, &column, &mut record));
+        if let Err(e) = fs::remove_dir_all(&scratch) {
+            log::warn!(
+                "Failed to clean up extracted export {}: {}",
+                logging::path(&scratch),
+                e
+            );
+        }
+        result?
+    } else {
+        return Err("Joplin export does not exist".to_string());
+    };
+
+    index_imported(&imported, &state);
+    Ok(summary)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloBoard {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+    #[serde(default)]
+    checklists: Vec<TrelloChecklist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloAttachment {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    due: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    #[serde(default)]
+    attachments: Vec<TrelloAttachment>,
+    #[serde(default)]
+    date_last_activity: Option<DateTime<Utc>>,
+    #[serde(default)]
+    short_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloChecklist {
+    id_card: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<TrelloCheckItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloCheckItem {
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+/// A board column created for an imported list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedColumn {
+    pub id: String,
+    pub title: String,
+    pub order: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrelloImportSummary {
+    #[serde(flatten)]
+    pub summary: ImportSummary,
+    /// Columns the cards were placed in, in board order, for the board settings
+    pub columns: Vec<ImportedColumn>,
+}
+
+/// Trello ids are MongoDB object ids whose first 8 hex digits are the
+/// creation time in seconds
+fn trello_id_time(id: &str) -> Option<DateTime<Utc>> {
+    let seconds = i64::from_str_radix(id.get(..8)?, 16).ok()?;
+    DateTime::from_timestamp(seconds, 0)
+}
+
+fn trello_card_content(card: &TrelloCard, checklists: &[&TrelloChecklist]) -> String {
+    let mut sections = Vec::new();
+    if !card.desc.trim().is_empty() {
+        sections.push(card.desc.trim().to_string());
+    }
+    for checklist in checklists {
+        let mut items: Vec<&TrelloCheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        let mut section = format!("## {}\n", checklist.name.trim());
+        for item in items {
+            let mark = if item.state == "complete" { 'x' } else { ' ' };
+            section.push_str(&format!("\n- [{}] {}", mark, item.name.trim()));
+        }
+        sections.push(section);
+    }
+    let links: Vec<String> = card
+        .attachments
+        .iter()
+        .filter(|attachment| !attachment.url.is_empty())
+        .map(|attachment| {
+            let name = match attachment.name.trim() {
+                "" => attachment.url.as_str(),
+                name => name,
+            };
+            format!("- [{}]({})", name, attachment.url)
+        })
+        .collect();
+    if !links.is_empty() {
+        sections.push(format!("## Attachments\n\n{}", links.join("\n")));
+    }
+    sections.join("\n\n")
+}
+
+/// Convert a Trello board export into notes in `target`, one column per open list
+fn import_trello_board(
+    board: &TrelloBoard,
+    target: &Path,
+    mut before_write: impl FnMut(&Path),
+) -> Result<(TrelloImportSummary, Vec<Note>), String> {
+    let mut summary = ImportSummary::default();
+
+    let mut lists: Vec<&TrelloList> = board.lists.iter().filter(|list| !list.closed).collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    let mut columns = Vec::new();
+    let mut column_ids: HashMap<&str, String> = HashMap::new();
+    for list in lists {
+        let base = slugify_or_fallback(&list.name, &list.id);
+        let mut id = base.clone();
+        let mut counter = 1;
+        while columns.iter().any(|c: &ImportedColumn| c.id == id) {
+            id = format!("{}-{}", base, counter);
+            counter += 1;
+        }
+        column_ids.insert(list.id.as_str(), id.clone());
+        columns.push(ImportedColumn {
+            id,
+            title: list.name.trim().to_string(),
+            order: columns.len(),
+        });
+    }
+
+    let mut cards: Vec<&TrelloCard> = Vec::new();
+    for card in &board.cards {
+        if card.closed || !column_ids.contains_key(card.id_list.as_str()) {
+            summary.skipped.push(format!("{} (archived)", card.name));
+        } else {
+            cards.push(card);
+        }
+    }
+    cards.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    let mut taken = HashSet::new();
+    let mut positions: HashMap<&str, i32> = HashMap::new();
+    let mut imported = Vec::new();
+    for card in cards {
+        let column = column_ids[card.id_list.as_str()].clone();
+        // Trello positions are sparse floats; keep their order as ranks
+        let position = positions.entry(card.id_list.as_str()).or_insert(0);
+        let order = *position;
+        *position += 1;
+
+        let mut checklists: Vec<&TrelloChecklist> = board
+            .checklists
+            .iter()
+            .filter(|checklist| checklist.id_card == card.id)
+            .collect();
+        checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+        let modified = card.date_last_activity.unwrap_or_else(Utc::now);
+        let created = trello_id_time(&card.id).unwrap_or(modified).min(modified);
+        let mut extra = serde_yaml::Mapping::new();
+        if let Some(url) = &card.short_url {
+            extra.insert("trello_url".into(), url.as_str().into());
+        }
+        let tags = card
+            .labels
+            .iter()
+            .filter_map(|label| match label.name.trim() {
+                "" => label.color.clone(),
+                name => Some(name.to_string()),
+            })
+            .map(|tag| tag.to_lowercase().replace(' ', "-"))
+            .collect();
+        let frontmatter = NoteFrontmatter {
+            id: Uuid::new_v4().to_string(),
+            title: card.name.trim().to_string(),
+            created,
+            modified,
+            date: None,
+            due: card.due.map(|due| due.format("%Y-%m-%d").to_string()),
+            column,
+            tags: sanitize_tags(tags),
+            order,
+            pinned: false,
+            archived: false,
+            priority: None,
+            color: None,
+            cover: None,
+            icon: None,
+            aliases: Vec::new(),
+            extra,
+            format: FrontmatterFormat::Yaml,
+        };
+
+        let dest = unique_note_path(
+            target,
+            &slugify_or_fallback(&card.name, &card.id),
+            &mut taken,
+        );
+        imported.push(write_imported_note(
+            &dest,
+            frontmatter,
+            trello_card_content(card, &checklists),
+            &AttachmentCopies::new(),
+            &mut summary,
+            &mut before_write,
+        )?);
+    }
+
+    Ok((TrelloImportSummary { summary, columns }, imported))
+}
+
+/// Import a board exported from Trello as JSON. Open lists become columns
+/// (returned so the board settings can add them), cards become notes and
+/// checklists become markdown checkboxes. Without a target folder the cards
+/// go into a folder named after the board.
+#[tauri::command]
+pub fn import_trello(
+    notes_dir: String,
+    json_path: String,
+    options: Option<ImportOptions>,
+    state: State<AppState>,
+) -> Result<TrelloImportSummary, String> {
+    let _pause = watch::pause(&state);
+    let bytes = fs::read(&json_path).map_err(|e| format!("Failed to read Trello export: {}", e))?;
+    let board: TrelloBoard =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Not a Trello board export: {}", e))?;
+
+    let mut options = options.unwrap_or_default();
+    if options.target_folder.is_none() && !board.name.trim().is_empty() {
+        options.target_folder = Some(folder_component(&board.name));
+    }
+    let target = import_target(&notes_dir, &options, &state)?;
+    let (summary, imported) = import_trello_board(&board, &target, |path| {
+        record_write(&path.to_string_lossy(), &state)
+    })?;
+    index_imported(&imported, &state);
+    Ok(summary)
+}
+
+/// Title for a plain markdown file: its `title` property, else its first H1,
+/// else the file name
+fn markdown_title(properties: Option<&serde_yaml::Mapping>, body: &str, stem: &str) -> String {
+    properties
+        .and_then(|mapping| mapping.get("title"))
+        .and_then(|value| value.as_str())
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .or_else(|| first_heading(body))
+        .unwrap_or(stem)
+        .to_string()
+}
+
+/// Copy a folder of markdown files into `target`, keeping file names and
+/// subfolders so relative links between them and to their images keep
+/// working. Files that already are noteban notes are copied as they are;
+/// others get generated frontmatter. Ids that already exist in the vault are
+/// replaced.
+fn import_markdown_tree(
+    source: &Path,
+    target: &Path,
+    column: &str,
+    existing_ids: &HashSet<String>,
+    mut before_write: impl FnMut(&Path),
+) -> Result<(ImportSummary, Vec<Note>), String> {
+    let mut summary = ImportSummary::default();
+    let mut imported = Vec::new();
+    let mut taken = HashSet::new();
+    let mut seen_ids = existing_ids.clone();
+
+    let walker = WalkDir::new(source)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker {
+        let entry = entry.map_err(|e| format!("Failed to read folder: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| e.to_string())?;
+        let folder = target.join(relative.parent().unwrap_or(Path::new("")));
+        let relative_name = relative.to_string_lossy().to_string();
+        let in_attachments = relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|c| c.as_os_str().to_string_lossy().ends_with(".attachments"));
+
+        if relative.extension().is_some_and(|ext| ext == "md") && !in_attachments {
+            let text = match fs::read_to_string(entry.path()) {
+                Ok(text) => text,
+                Err(e) => {
+                    summary
+                        .warnings
+                        .push(format!("{}: failed to read: {}", relative_name, e));
+                    summary.skipped.push(relative_name);
+                    continue;
+                }
+            };
+            let stem = relative
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let dest = unique_note_path(&folder, &stem, &mut taken);
+
+            // Only files with a complete noteban frontmatter are kept as they are
+            let note = split_frontmatter(&text)
+                .0
+                .and_then(|_| parse_note_content(&text, &dest).ok());
+            let (mut frontmatter, content) = match note {
+                Some(note) => (note.frontmatter, note.content),
+                None => {
+                    let (yaml, body) = split_frontmatter(&text);
+                    let properties = yaml
+                        .and_then(|yaml| serde_yaml::from_str::<serde_yaml::Mapping>(yaml).ok());
+                    let title = markdown_title(properties.as_ref(), body, &stem);
+                    let mut frontmatter = properties_frontmatter(
+                        &title,
+                        entry.path(),
+                        yaml,
+                        body,
+                        column,
+                        "imported",
+                        &mut summary.warnings,
+                    );
+                    frontmatter.extra.shift_remove("imported_title");
+                    (frontmatter, body.trim().to_string())
+                }
+            };
+            if !seen_ids.insert(frontmatter.id.clone()) {
+                frontmatter.id = Uuid::new_v4().to_string();
+                seen_ids.insert(frontmatter.id.clone());
+            }
+            imported.push(write_imported_note(
+                &dest,
+                frontmatter,
+                content,
+                &AttachmentCopies::new(),
+                &mut summary,
+                &mut before_write,
+            )?);
+        } else {
+            let dest = folder.join(entry.file_name());
+            if dest.exists() {
+                summary.skipped.push(relative_name);
+                continue;
+            }
+            fs::create_dir_all(&folder).map_err(|e| format!("Failed to create folder: {}", e))?;
+            match fs::copy(entry.path(), &dest) {
+                Ok(_) => summary.attachments_copied += 1,
+                Err(e) => summary
+                    .warnings
+                    .push(format!("{}: failed to copy: {}", relative_name, e)),
+            }
+        }
+    }
+    Ok((summary, imported))
+}
+
+/// Import a folder of plain markdown files, such as notes from another editor
+/// or a docs folder. Subfolders and file names are kept, frontmatter is
+/// generated where missing and titles come from the `title` property, the
+/// first heading or the file name. Other files are copied alongside.
+#[tauri::command]
+pub fn import_markdown_folder(
+    notes_dir: String,
+    source_dir: String,
+    options: Option<ImportOptions>,
+    state: State<AppState>,
+) -> Result<ImportSummary, String> {
+    let _pause = watch::pause(&state);
+    let options = options.unwrap_or_default();
+    let source = PathBuf::from(&source_dir);
+    if !source.is_dir() {
+        return Err("Source folder does not exist".to_string());
+    }
+    let target = import_target(&notes_dir, &options, &state)?;
+    let (source_canonical, notes_canonical) = (
+        source.canonicalize().map_err(|e| e.to_string())?,
+        PathBuf::from(&notes_dir)
+            .canonicalize()
+            .map_err(|e| e.to_string())?,
+    );
+    if notes_canonical.starts_with(&source_canonical)
+        || source_canonical.starts_with(&notes_canonical)
+    {
+        return Err(
+            "The source folder and the notes directory must not contain each other".to_string(),
+        );
+    }
+
+    let existing_ids = vault_note_ids(&state)?;
+    let column = import_column(&notes_dir, &target, &options);
+    let (summary, imported) =
+        import_markdown_tree(&source, &target, &column, &existing_ids, |path| {
+            record_write(&path.to_string_lossy(), &state)
+        })?;
+    index_imported(&imported, &state);
+    Ok(summary)
+}
+
+/// Contents of a note bundle: the note file name, its text and the files of
+/// its attachments folder by path inside that folder
+struct NoteBundle {
+    stem: String,
+    text: String,
+    attachments: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn read_note_bundle<R: Read + Seek>(reader: R) -> Result<NoteBundle, String> {
+    let mut archive =
+        ZipArchive::new(reader).map_err(|e| format!("Not a valid note bundle: {}", e))?;
+    let mut note: Option<(String, String)> = None;
+    let mut files: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Note bundle is corrupt: {}", e))?;
+        let path = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Note bundle contains an unsafe path: {}", entry.name()))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Note bundle entry {} is corrupt: {}", entry.name(), e))?;
+
+        let is_note =
+            path.components().count() == 1 && path.extension().is_some_and(|ext| ext == "md");
+        if is_note {
+            if note.is_some() {
+                return Err("Note bundle contains more than one note".to_string());
+            }
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let text = String::from_utf8(bytes).map_err(|_| "Note is not valid UTF-8")?;
+            note = Some((stem, text));
+        } else {
+            files.push((path, bytes));
+        }
+    }
+
+    let (stem, text) = note.ok_or("Note bundle contains no note")?;
+    let attachments_dir = PathBuf::from(format!("{}.attachments", stem));
+    let attachments = files
+        .into_iter()
+        .map(|(path, bytes)| match path.strip_prefix(&attachments_dir) {
+            Ok(relative) => Ok((relative.to_path_buf(), bytes)),
+            Err(_) => Err(format!(
+                "Note bundle contains an unexpected file: {}",
+                path.display()
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(NoteBundle {
+        stem,
+        text,
+        attachments,
+    })
+}
+
+/// Write a bundled note into `target` under a free name, renaming its
+/// attachments folder and links along with it. A fresh id is assigned when
+/// the vault already has a note with the bundled one's id.
+fn unpack_note_bundle(
+    bundle: NoteBundle,
+    target: &Path,
+    existing_ids: &HashSet<String>,
+    column: Option<&str>,
+    mut before_write: impl FnMut(&Path),
+) -> Result<Note, String> {
+    fs::create_dir_all(target).map_err(|e| format!("Failed to create folder: {}", e))?;
+    let dest = unique_note_path(target, &bundle.stem, &mut HashSet::new());
+    let mut note = parse_note_content(&bundle.text, &dest)?;
+
+    let stem = dest
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if stem != bundle.stem {
+        note.content = note.content.replace(
+            &format!("{}.attachments/", bundle.stem),
+            &format!("{}.attachments/", stem),
+        );
+    }
+    if existing_ids.contains(&note.frontmatter.id) {
+        note.frontmatter.id = Uuid::new_v4().to_string();
+    }
+    if let Some(column) = column {
+        note.frontmatter.column = column.to_string();
+    }
+
+    let attachments_dir = dest.with_extension("attachments");
+    for (relative, bytes) in &bundle.attachments {
+        let path = attachments_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create attachments folder: {}", e))?;
+        }
+        fs::write(&path, bytes).map_err(|e| format!("Failed to write attachment: {}", e))?;
+    }
+    before_write(&dest);
+    atomic_write(&dest, &serialize_note(&note.frontmatter, &note.content))?;
+    Ok(note)
+}
+
+/// Add a note exported with `export_note_bundle`, together with its
+/// attachments, to the vault
+#[tauri::command]
+pub fn import_note_bundle(
+    notes_dir: String,
+    bundle_path: String,
+    options: Option<ImportOptions>,
+    state: State<AppState>,
+) -> Result<Note, String> {
+    let options = options.unwrap_or_default();
+    let target = import_target(&notes_dir, &options, &state)?;
+    let file = File::open(&bundle_path).map_err(|e| format!("Failed to open bundle: {}", e))?;
+    let bundle = read_note_bundle(file)?;
+
+    let existing_ids = vault_note_ids(&state)?;
+    let note = unpack_note_bundle(
+        bundle,
+        &target,
+        &existing_ids,
+        options.column.as_deref(),
+        |path| record_write(&path.to_string_lossy(), &state),
+    )?;
+    index_imported(std::slice::from_ref(&note), &state);
+    Ok(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_vault_with_links_and_attachments() {
+        let root = std::env::temp_dir().join(format!("noteban-obsidian-{}", Uuid::new_v4()));
+        let vault = root.join("vault");
+        fs::create_dir_all(vault.join("Projects")).unwrap();
+        fs::create_dir_all(vault.join(".obsidian")).unwrap();
+        fs::create_dir_all(vault.join("assets")).unwrap();
+        fs::write(vault.join(".obsidian/app.json"), "{}").unwrap();
+        fs::write(vault.join("assets/Diagram 1.png"), "png").unwrap();
+        fs::write(
+            vault.join("Projects/Launch Plan.md"),
+            "---\ntags: [work, Q3]\nstatus: draft\ncreated: 2024-01-01\n---\n\
+             See [[Meeting Notes|the notes]] and [[Missing]].\n![[Diagram 1.png|300]] #urgent\n\
+             ```\n[[Meeting Notes]]\n```\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.join("Meeting Notes.md"),
+            "Back to [[Launch Plan#Goals]]",
+        )
+        .unwrap();
+
+        let target = root.join("notes");
+        let (summary, notes) = import_obsidian_vault(&vault, &target, "todo", |_| {}).unwrap();
+        assert_eq!(summary.notes_imported, 2);
+        assert_eq!(summary.attachments_copied, 1);
+        assert_eq!(summary.warnings.len(), 1);
+
+        let plan = notes
+            .iter()
+            .find(|n| n.frontmatter.title == "Launch Plan")
+            .unwrap();
+        assert!(plan.file_path.ends_with("Projects/launch-plan.md"));
+        assert_eq!(plan.frontmatter.tags, vec!["q3", "urgent", "work"]);
+        assert_eq!(plan.frontmatter.extra["status"], "draft");
+        assert!(plan.frontmatter.extra.contains_key("obsidian_created"));
+        assert!(plan
+            .content
+            .contains("See [the notes](../meeting-notes.md) and [[Missing]]."));
+        assert!(plan
+            .content
+            .contains("![Diagram 1.png](launch-plan.attachments/Diagram%201.png)"));
+        assert!(plan.content.contains("```\n[[Meeting Notes]]\n```"));
+        assert!(target
+            .join("Projects/launch-plan.attachments/Diagram 1.png")
+            .exists());
+
+        let meeting = fs::read_to_string(target.join("meeting-notes.md")).unwrap();
+        assert!(meeting.contains("Back to [Launch Plan](Projects/launch-plan.md)"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn imports_notion_export_with_databases() {
+        let root = std::env::temp_dir().join(format!("noteban-notion-{}", Uuid::new_v4()));
+        let export = root.join("export");
+        let id = "0123456789abcdef0123456789abcdef";
+        let row_id = "fedcba9876543210fedcba9876543210";
+        fs::create_dir_all(export.join(format!("Roadmap {}/Tasks {}", id, id))).unwrap();
+        fs::write(
+            export.join(format!("Roadmap {}.md", id)),
+            format!(
+                "# Roadmap\n\nSee [Ship it](Roadmap%20{id}/Tasks%20{id}/Ship%20it%20{row}.md)\n\
+                 ![](Roadmap%20{id}/diagram.png)\n",
+                id = id,
+                row = row_id
+            ),
+        )
+        .unwrap();
+        fs::write(export.join(format!("Roadmap {}/diagram.png", id)), "png").unwrap();
+        fs::write(
+            export.join(format!("Roadmap {}/Tasks {}.csv", id, id)),
+            "Name,Status\n",
+        )
+        .unwrap();
+        fs::write(
+            export.join(format!("Roadmap {}/Tasks {}/Ship it {}.md", id, id, row_id)),
+            "# Ship it\n\nStatus: In progress\nTags: launch, Q3\nCreated: January 5, 2024 3:04 PM\n\nBody text",
+        )
+        .unwrap();
+
+        let target = root.join("notes");
+        let (summary, notes) = import_notion_export(&export, &target, "todo", |_| {}).unwrap();
+        assert_eq!(summary.notes_imported, 2);
+        assert_eq!(summary.attachments_copied, 1);
+
+        let roadmap = notes
+            .iter()
+            .find(|n| n.frontmatter.title == "Roadmap")
+            .unwrap();
+        assert!(roadmap.file_path.ends_with("notes/roadmap.md"));
+        assert!(roadmap
+            .content
+            .contains("[Ship it](Roadmap/Tasks/ship-it.md)"));
+        assert!(roadmap
+            .content
+            .contains("![](roadmap.attachments/diagram.png)"));
+        assert!(target.join("roadmap.attachments/diagram.png").exists());
+
+        let task = notes
+            .iter()
+            .find(|n| n.frontmatter.title == "Ship it")
+            .unwrap();
+        assert!(task.file_path.ends_with("Roadmap/Tasks/ship-it.md"));
+        assert_eq!(task.content, "Body text");
+        assert_eq!(task.frontmatter.tags, vec!["launch", "q3"]);
+        assert_eq!(task.frontmatter.extra["status"], "In progress");
+        assert_eq!(
+            task.frontmatter.created.to_rfc3339(),
+            "2024-01-05T15:04:00+00:00"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn imports_joplin_export() {
+        let root = std::env::temp_dir().join(format!("noteban-joplin-{}", Uuid::new_v4()));
+        let export = root.join("export");
+        fs::create_dir_all(export.join("resources")).unwrap();
+        let id = |n: u8| format!("{:032x}", n);
+        let item = |name: String, text: String| {
+            fs::write(export.join(format!("{}.md", name)), text).unwrap()
+        };
+        item(
+            id(1),
+            format!("Work\n\nid: {}\nparent_id: \ntype_: 2", id(1)),
+        );
+        item(
+            id(2),
+            format!("Q3/Q4\n\nid: {}\nparent_id: {}\ntype_: 2", id(2), id(1)),
+        );
+        item(
+            id(3),
+            format!(
+                "Plan\n\nSee [spec](:/{}) ![](:/{})\n\nid: {}\nparent_id: {}\n\
+                 created_time: 2024-01-01T10:00:00.000Z\nupdated_time: 2024-02-01T10:00:00.000Z\n\
+                 user_created_time: 2023-12-31T09:00:00.000Z\ntodo_due: 0\ntype_: 1",
+                id(4),
+                id(5),
+                id(3),
+                id(2)
+            ),
+        );
+        item(
+            id(4),
+            format!(
+                "Spec\n\nBody\n\nid: {}\nparent_id: {}\ntype_: 1",
+                id(4),
+                id(1)
+            ),
+        );
+        item(
+            id(5),
+            format!("chart.png\n\nid: {}\nfile_extension: png\ntype_: 4", id(5)),
+        );
+        fs::write(export.join(format!("resources/{}.png", id(5))), "png").unwrap();
+        item(id(6), format!("Urgent\n\nid: {}\ntype_: 5", id(6)));
+        item(
+            id(7),
+            format!(
+                "id: {}\nnote_id: {}\ntag_id: {}\ntype_: 6",
+                id(7),
+                id(3),
+                id(6)
+            ),
+        );
+
+        let target = root.join("notes");
+        let (summary, notes) = import_joplin_export(&export, &target, "todo", |_| {}).unwrap();
+        assert_eq!(summary.notes_imported, 2);
+        assert_eq!(summary.attachments_copied, 1);
+
+        let plan = notes
+            .iter()
+            .find(|n| n.frontmatter.title == "Plan")
+            .unwrap();
+        assert!(plan.file_path.ends_with("Work/Q3-Q4/plan.md"));
+        assert_eq!(
+            plan.content,
+            "See [spec](../spec.md) ![](plan.attachments/chart.png)"
+        );
+        assert_eq!(plan.frontmatter.tags, vec!["urgent"]);
+        assert_eq!(
+            plan.frontmatter.created.to_rfc3339(),
+            "2023-12-31T09:00:00+00:00"
+        );
+        assert_eq!(
+            plan.frontmatter.modified.to_rfc3339(),
+            "2024-02-01T10:00:00+00:00"
+        );
+        assert!(target
+            .join("Work/Q3-Q4/plan.attachments/chart.png")
+            .exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn imports_trello_board() {
+        let root = std::env::temp_dir().join(format!("noteban-trello-{}", Uuid::new_v4()));
+        let board: TrelloBoard = serde_json::from_value(serde_json::json!({
+            "name": "Launch",
+            "lists": [
+                {"id": "l2", "name": "Doing", "pos": 2.0},
+                {"id": "l1", "name": "To Do", "pos": 1.0},
+                {"id": "l3", "name": "Old", "pos": 3.0, "closed": true}
+            ],
+            "cards": [
+                {"id": "5f000000aaaaaaaaaaaaaaaa", "name": "Second", "idList": "l1", "pos": 50000.5},
+                {
+                    "id": "5f000000bbbbbbbbbbbbbbbb", "name": "First", "idList": "l1", "pos": 16384,
+                    "desc": "Details", "due": "2024-05-01T12:00:00.000Z",
+                    "labels": [{"name": "High Priority", "color": "red"}, {"name": "", "color": "green"}],
+                    "dateLastActivity": "2024-04-01T00:00:00.000Z"
+                },
+                {"id": "5f000000cccccccccccccccc", "name": "Gone", "idList": "l3", "pos": 1}
+            ],
+            "checklists": [{
+                "idCard": "5f000000bbbbbbbbbbbbbbbb", "name": "Steps",
+                "checkItems": [
+                    {"name": "Two", "state": "incomplete", "pos": 2},
+                    {"name": "One", "state": "complete", "pos": 1}
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let (result, notes) = import_trello_board(&board, &root, |_| {}).unwrap();
+        let columns: Vec<(&str, usize)> = result
+            .columns
+            .iter()
+            .map(|c| (c.id.as_str(), c.order))
+            .collect();
+        assert_eq!(columns, vec![("to-do", 0), ("doing", 1)]);
+        assert_eq!(result.summary.notes_imported, 2);
+        assert_eq!(result.summary.skipped, vec!["Gone (archived)"]);
+
+        let first = &notes[0].frontmatter;
+        assert_eq!((first.title.as_str(), first.order), ("First", 0));
+        assert_eq!(first.column, "to-do");
+        assert_eq!(first.tags, vec!["high-priority", "green"]);
+        assert_eq!(first.due.as_deref(), Some("2024-05-01"));
+        assert_eq!(first.created.to_rfc3339(), "2020-07-04T04:05:20+00:00");
+        assert_eq!(
+            notes[0].content,
+            "Details\n\n## Steps\n\n- [x] One\n- [ ] Two"
+        );
+        assert_eq!(notes[1].frontmatter.order, 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn round_trips_note_bundle_under_free_name() {
+        let root = std::env::temp_dir().join(format!("noteban-bundle-{}", Uuid::new_v4()));
+        let source = root.join("source");
+        fs::create_dir_all(source.join("plan.attachments/img")).unwrap();
+        fs::write(source.join("plan.attachments/img/chart.png"), "png").unwrap();
+        fs::write(
+            source.join("plan.md"),
+            "---\nid: plan-id\ntitle: Plan\ncreated: 2024-01-01T00:00:00Z\n\
+             modified: 2024-01-01T00:00:00Z\ncolumn: doing\n---\n\n![chart](plan.attachments/img/chart.png)",
+        )
+        .unwrap();
+        let bundle_path = root.join("plan.zip");
+        crate::commands::export::write_note_bundle(&source.join("plan.md"), &bundle_path).unwrap();
+
+        let target = root.join("vault");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("plan.md"), "taken").unwrap();
+        let bundle = read_note_bundle(File::open(&bundle_path).unwrap()).unwrap();
+        let existing = HashSet::from(["plan-id".to_string()]);
+        let note = unpack_note_bundle(bundle, &target, &existing, None, |_| {}).unwrap();
+
+        assert!(note.file_path.ends_with("plan-1.md"));
+        assert_ne!(note.frontmatter.id, "plan-id");
+        assert_eq!(note.frontmatter.column, "doing");
+        assert_eq!(note.content, "![chart](plan-1.attachments/img/chart.png)");
+        assert!(target.join("plan-1.attachments/img/chart.png").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn imports_plain_markdown_with_generated_frontmatter() {
+        let root = std::env::temp_dir().join(format!("noteban-markdown-{}", Uuid::new_v4()));
+        let source = root.join("docs");
+        fs::create_dir_all(source.join("guides/img")).unwrap();
+        fs::create_dir_all(source.join(".git")).unwrap();
+        fs::write(source.join(".git/HEAD"), "ref").unwrap();
+        fs::write(source.join("guides/img/flow.png"), "png").unwrap();
+        fs::write(
+            source.join("guides/setup.md"),
+            "Intro line\n\n# Getting Started #\n\n![flow](img/flow.png) #howto\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("faq.md"),
+            "---\ntitle: Questions\nowner: sam\n---\nSee [setup](guides/setup.md)\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("kept.md"),
+            "---\nid: dup\ntitle: Kept\ncreated: 2024-01-01T00:00:00Z\n\
+             modified: 2024-01-01T00:00:00Z\ncolumn: done\n---\n\nBody",
+        )
+        .unwrap();
+
+        let target = root.join("vault");
+        let existing = HashSet::from(["dup".to_string()]);
+        let (summary, notes) =
+            import_markdown_tree(&source, &target, "todo", &existing, |_| {}).unwrap();
+        assert_eq!(summary.notes_imported, 3);
+        assert_eq!(summary.attachments_copied, 1);
+        assert!(target.join("guides/img/flow.png").exists());
+        assert!(!target.join(".git").exists());
+
+        let by_title = |title: &str| notes.iter().find(|n| n.frontmatter.title == title).unwrap();
+        let setup = by_title("Getting Started");
+        assert!(setup.file_path.ends_with("guides/setup.md"));
+        assert_eq!(setup.frontmatter.tags, vec!["howto"]);
+        assert_eq!(setup.frontmatter.column, "todo");
+        let faq = by_title("Questions");
+        assert_eq!(faq.frontmatter.extra["owner"], "sam");
+        assert!(!faq.frontmatter.extra.contains_key("imported_title"));
+        assert_eq!(faq.content, "See [setup](guides/setup.md)");
+        let kept = by_title("Kept");
+        assert_eq!(kept.frontmatter.column, "done");
+        assert_ne!(kept.frontmatter.id, "dup");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+use crate::commands::audit;
+use crate::commands::board;
+use crate::commands::mounts::ensure_writable;
+use crate::commands::notes::{
+    atomic_write, delete_note, ensure_modifiable, fill_days_in_column, get_file_mtime, move_note,
+    parse_note_content, record_written_content, sanitize_tags, serialize_note,
+    validate_existing_path_within_base, Note, NoteWithTags,
+};
+use crate::lock_or_err;
+use crate::logging;
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::vault_config::{board_root_of, folder_board_root};
+use crate::AppState;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+pub(crate) const INBOX_CONFIG_KEY: &str = "inbox_config";
+/// Frontmatter property marking a note as triaged, so it leaves the inbox even
+/// when the decision kept it in place
+pub const TRIAGED_KEY: &str = "triaged";
+
+fn default_inbox_folder() -> Option<String> {
+    Some("Inbox".to_string())
+}
+
+fn default_archive_folder() -> String {
+    "Archive".to_string()
+}
+
+/// What to do with an inbox note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TriageDecision {
+    /// Move to a folder (vault root when None), optionally tagging it and
+    /// setting its column
+    Move {
+        folder: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        column: Option<String>,
+    },
+    /// Give the note a date, optionally setting its column
+    Schedule {
+        date: String,
+        column: Option<String>,
+    },
+    /// Move to the configured archive folder
+    Archive,
+    /// Move to the trash
+    Delete,
+    /// Apply a disposition saved in the inbox configuration
+    Preset { name: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriagePreset {
+    pub name: String,
+    pub decision: TriageDecision,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxConfig {
+    /// Folder (relative to the vault) that collects captures
+    #[serde(default = "default_inbox_folder")]
+    pub folder: Option<String>,
+    /// Column that collects captures, in addition to the folder
+    #[serde(default)]
+    pub column: Option<String>,
+    #[serde(default = "default_archive_folder")]
+    pub archive_folder: String,
+    #[serde(default)]
+    pub presets: Vec<TriagePreset>,
+}
+
+impl Default for InboxConfig {
+    fn default() -> Self {
+        Self {
+            folder: default_inbox_folder(),
+            column: None,
+            archive_folder: default_archive_folder(),
+            presets: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InboxFilter {
+    /// Case-insensitive match against title and content
+    pub query: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageResult {
+    pub action: String,
+    /// The triaged note, or None when it was deleted
+    pub note: Option<Note>,
+}
+
+fn load_inbox_config(state: &State<AppState>) -> Result<InboxConfig, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    Ok(cache_lock
+        .as_ref()
+        .and_then(|cache| cache.get_meta(INBOX_CONFIG_KEY).ok().flatten())
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+fn is_in_inbox(note: &Note, inbox_dir: Option<&Path>, column: Option<&str>) -> bool {
+    if note.frontmatter.extra.contains_key(TRIAGED_KEY) {
+        return false;
+    }
+    let in_folder = inbox_dir.is_some_and(|dir| Path::new(&note.file_path).starts_with(dir));
+    let in_column = column.is_some_and(|column| note.frontmatter.column == column);
+    in_folder || in_column
+}
+
+fn matches_filter(note: &NoteWithTags, filter: &InboxFilter) -> bool {
+    if let Some(query) = filter.query.as_ref().map(|q| q.to_lowercase()) {
+        let frontmatter = &note.note.frontmatter;
+        if !frontmatter.title.to_lowercase().contains(&query)
+            && !note.note.content.to_lowercase().contains(&query)
+        {
+            return false;
+        }
+    }
+    if let Some(tag) = filter
+        .tag
+        .as_ref()
+        .map(|t| t.trim_start_matches('#').to_lowercase())
+    {
+        let has_tag = note
+            .note
+            .frontmatter
+            .tags
+            .iter()
+            .chain(&note.inline_tags)
+            .any(|t| t.to_lowercase() == tag);
+        if !has_tag {
+            return false;
+        }
+    }
+    true
+}
+
+#[tauri::command]
+pub fn get_inbox_config(state: State<AppState>) -> Result<InboxConfig, String> {
+    load_inbox_config(&state)
+}
+
+#[tauri::command]
+pub fn set_inbox_config(
+    config: InboxConfig,
+    state: State<AppState>,
+) -> Result<InboxConfig, String> {
+    for preset in &config.presets {
+        if preset.name.trim().is_empty() {
+            return Err("Preset names cannot be empty".to_string());
+        }
+        if matches!(preset.decision, TriageDecision::Preset { .. }) {
+            return Err("Presets cannot refer to other presets".to_string());
+        }
+    }
+    let encoded = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to encode inbox config: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(INBOX_CONFIG_KEY, &encoded)?;
+    Ok(config)
+}
+
+/// Untriaged captures, oldest first
+#[tauri::command]
+pub fn get_inbox(
+    notes_dir: String,
+    filter: Option<InboxFilter>,
+    state: State<AppState>,
+) -> Result<Vec<NoteWithTags>, String> {
+    let config = load_inbox_config(&state)?;
+    let filter = filter.unwrap_or_default();
+    let inbox_dir = config
+        .folder
+        .as_ref()
+        .map(|folder| PathBuf::from(&notes_dir).join(folder));
+
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+
+    let mut notes: Vec<NoteWithTags> = cache
+        .get_all_notes()?
+        .into_iter()
+        .filter(|cached| is_in_inbox(&cached.note, inbox_dir.as_deref(), config.column.as_deref()))
+        .map(|cached| NoteWithTags {
+            note: cached.note,
+            inline_tags: cached.inline_tags,
+            days_in_column: None,
+            excerpt: Some(cached.excerpt),
+        })
+        .filter(|note| matches_filter(note, &filter))
+        .collect();
+
+    notes.sort_by_key(|note| note.note.frontmatter.created);
+    fill_days_in_column(cache, &mut notes);
+    Ok(notes)
+}
+
+/// Apply a triage decision to an inbox note in one call. Frontmatter changes
+/// are rolled back if the note cannot be moved afterwards.
+#[tauri::command]
+pub fn triage_note(
+    notes_dir: String,
+    file_path: String,
+    decision: TriageDecision,
+    state: State<AppState>,
+) -> Result<TriageResult, String> {
+    let config = load_inbox_config(&state)?;
+    let decision = match decision {
+        TriageDecision::Preset { name } => config
+            .presets
+            .iter()
+            .find(|preset| preset.name == name)
+            .map(|preset| preset.decision.clone())
+            .ok_or_else(|| format!("Unknown triage preset: {}", name))?,
+        decision => decision,
+    };
+
+    let (action, target_folder, tags, date, column) = match decision {
+        TriageDecision::Delete => {
+            delete_note(notes_dir, file_path, None, state)?;
+            return Ok(TriageResult {
+                action: "delete".to_string(),
+                note: None,
+            });
+        }
+        TriageDecision::Move {
+            folder,
+            tags,
+            column,
+        } => ("move", Some(folder.unwrap_or_default()), tags, None, column),
+        TriageDecision::Schedule { date, column } => {
+            if date.trim().is_empty() {
+                return Err("Schedule date cannot be empty".to_string());
+            }
+            ("schedule", None, Vec::new(), Some(date), column)
+        }
+        TriageDecision::Archive => (
+            "archive",
+            Some(config.archive_folder.clone()),
+            Vec::new(),
+            None,
+            None,
+        ),
+        TriageDecision::Preset { .. } => {
+            return Err("Presets cannot refer to other presets".to_string())
+        }
+    };
+
+    let base_path = PathBuf::from(&notes_dir);
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, &base_path)?;
+    ensure_writable(&path, &state)?;
+
+    let previous_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut note = parse_note_content(&previous_content, &path)?;
+    ensure_modifiable(&note.frontmatter, false)?;
+    let entered_column = column
+        .as_deref()
+        .filter(|column| *column != note.frontmatter.column && !note.frontmatter.archived);
+    if let Some(column) = entered_column {
+        let root = board_root_of(&base_path, &path);
+        // Moves to another board are checked by the move itself
+        let leaves_board = target_folder
+            .as_ref()
+            .is_some_and(|folder| folder_board_root(&base_path, &base_path.join(folder)) != root);
+        if !leaves_board {
+            board::check_wip_limit(&root, column, 1, &state)?;
+        }
+    }
+
+    let mut merged_tags = note.frontmatter.tags.clone();
+    merged_tags.extend(tags);
+    note.frontmatter.tags = sanitize_tags(merged_tags);
+    if let Some(date) = date {
+        note.frontmatter.date = Some(date);
+    }
+    if let Some(column) = column {
+        note.frontmatter.column = column;
+    }
+    let now = Utc::now();
+    note.frontmatter.modified = now;
+    note.frontmatter.extra.insert(
+        TRIAGED_KEY.into(),
+        serde_yaml::Value::String(now.to_rfc3339()),
+    );
+
+    let file_content = serialize_note(&note.frontmatter, &note.content);
+    record_written_content(&file_path, &file_content, &state);
+    let written = atomic_write(&path, &file_content);
+    audit::record(&state, "update", &file_path, None, &written);
+    written?;
+
+    let note = match target_folder {
+        Some(folder) => {
+            match move_note(notes_dir, file_path.clone(), folder, None, state.clone()) {
+                Ok(moved) => moved,
+                Err(e) => {
+                    // Undo the frontmatter changes so the note stays untriaged
+                    record_written_content(&file_path, &previous_content, &state);
+                    let rolled_back = atomic_write(&path, &previous_content);
+                    audit::record(&state, "update", &file_path, None, &rolled_back);
+                    if let Err(rollback_err) = rolled_back {
+                        log::error!(
+                            "Failed to roll back triage of {}: {}",
+                            logging::path(&file_path),
+                            rollback_err
+                        );
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        None => {
+            if let Ok(cache_lock) = state.cache.lock() {
+                if let Some(cache) = cache_lock.as_ref() {
+                    let hash = compute_content_hash(&file_content);
+                    let mtime = get_file_mtime(&path).unwrap_or(0);
+                    let inline_tags = extract_inline_tags(&note.content);
+                    if let Err(e) = cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+                        log::warn!("Cache update failed for triaged note: {}", e);
+                    }
+                }
+            }
+            note
+        }
+    };
+
+    Ok(TriageResult {
+        action: action.to_string(),
+        note: Some(note),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+
+    fn triage(
+        vault: &TestVault,
+        id: &str,
+        decision: TriageDecision,
+    ) -> Result<TriageResult, String> {
+        let file_path = vault.path(&format!("Inbox/{}.md", id));
+        triage_note(vault.notes_dir(), file_path, decision, vault.state())
+    }
+
+    #[test]
+    fn applies_each_triage_decision() {
+        let vault = TestVault::new();
+        for id in ["move", "schedule", "archive", "delete", "preset"] {
+            vault.note(&format!("Inbox/{}.md", id), id, "tags: [capture]\n");
+        }
+        let config = InboxConfig {
+            presets: vec![TriagePreset {
+                name: "Later".into(),
+                decision: TriageDecision::Move {
+                    folder: Some("Someday".into()),
+                    tags: Vec::new(),
+                    column: None,
+                },
+            }],
+            ..InboxConfig::default()
+        };
+        set_inbox_config(config, vault.state()).unwrap();
+
+        let moved = triage(
+            &vault,
+            "move",
+            TriageDecision::Move {
+                folder: Some("Work".into()),
+                tags: vec!["urgent".into()],
+                column: Some("doing".into()),
+            },
+        )
+        .unwrap();
+        let note = moved.note.unwrap();
+        assert_eq!(note.file_path, vault.path("Work/move.md"));
+        assert_eq!(note.frontmatter.tags, ["capture", "urgent"]);
+        assert_eq!(note.frontmatter.column, "doing");
+        assert!(note.frontmatter.extra.contains_key(TRIAGED_KEY));
+
+        let scheduled = triage(
+            &vault,
+            "schedule",
+            TriageDecision::Schedule {
+                date: "2024-05-01".into(),
+                column: None,
+            },
+        )
+        .unwrap();
+        let note = scheduled.note.unwrap();
+        assert_eq!(note.file_path, vault.path("Inbox/schedule.md"));
+        assert_eq!(note.frontmatter.date.as_deref(), Some("2024-05-01"));
+        assert!(vault.read("Inbox/schedule.md").contains(TRIAGED_KEY));
+
+        let archived = triage(&vault, "archive", TriageDecision::Archive).unwrap();
+        assert_eq!(archived.action, "archive");
+        assert!(vault.exists("Archive/archive.md"));
+
+        let deleted = triage(&vault, "delete", TriageDecision::Delete).unwrap();
+        assert!(deleted.note.is_none());
+        assert!(!vault.exists("Inbox/delete.md"));
+
+        let preset = TriageDecision::Preset {
+            name: "Later".into(),
+        };
+        triage(&vault, "preset", preset).unwrap();
+        assert!(vault.exists("Someday/preset.md"));
+        let unknown = TriageDecision::Preset {
+            name: "Never".into(),
+        };
+        assert!(triage(&vault, "preset", unknown).is_err());
+    }
+
+    #[test]
+    fn rolls_back_frontmatter_when_the_move_fails() {
+        let vault = TestVault::new();
+        vault.note("Inbox/a.md", "a", "");
+        vault.note("Work/a.md", "other", "");
+        let before = vault.read("Inbox/a.md");
+        // The target folder is a note, so the move cannot create it
+        let decision = TriageDecision::Move {
+            folder: Some("Work/a.md".into()),
+            tags: vec!["urgent".into()],
+            column: None,
+        };
+        assert!(triage(&vault, "a", decision).is_err());
+        assert_eq!(vault.read("Inbox/a.md"), before);
+    }
+}
@@ -0,0 +1,981 @@
+use crate::cache::CacheDb;
+use crate::commands::conflicts::{write_conflict_copy, REASON_SYNC};
+use crate::commands::notes::validate_path_within_base;
+use crate::commands::sync::{
+    decide_sync_action, delete_local_file, is_locked_local_file, list_local_files,
+    locked_sync_decision, read_selective_sync, should_sync_file, write_conflict_file,
+    write_local_file, SelectiveSyncConfig, SyncDecision,
+};
+use crate::commands::watch;
+use crate::crypto;
+use crate::lock_or_err;
+use crate::logging;
+use crate::AppState;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+const SERVICE_TYPE: &str = "_noteban-sync._tcp.local.";
+const DEVICE_ID_KEY: &str = "lan_device_id";
+/// Prefix of the meta keys holding the hashes last synced with each peer
+const PEER_RECORD_PREFIX: &str = "lan_sync_peer:";
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_DISCOVERY_MS: u64 = 3000;
+/// Hosting stops after this many connections with a wrong pairing code
+const MAX_FAILED_PAIRINGS: u32 = 5;
+/// Crockford base32, so codes read aloud survive O/0 and I/1 mix-ups
+const CODE_ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CODE_GROUP_LEN: usize = 4;
+const MAX_FRAME_LEN: usize = 512 * 1024 * 1024;
+/// Cap on frames read before the peer has proven it knows the pairing code
+const MAX_HANDSHAKE_FRAME_LEN: usize = 16 * 1024;
+
+/// A vault offered to the local network while the pairing code is shown
+pub struct LanHost {
+    info: LanHostInfo,
+    stop: Arc<AtomicBool>,
+    daemon: ServiceDaemon,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanHostInfo {
+    pub device_name: String,
+    pub port: u16,
+    /// 128 random bits in dash-separated base32 groups that the other
+    /// device has to enter; long enough that the salted key derived from it
+    /// cannot be brute-forced offline
+    pub pairing_code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanPeer {
+    pub device_id: String,
+    pub device_name: String,
+    pub address: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LanSyncSummary {
+    pub peer_name: String,
+    pub sent: usize,
+    pub received: usize,
+    pub deleted_local: usize,
+    pub deleted_remote: usize,
+    /// Conflict copies written for files edited on both devices
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Hello {
+    device_id: String,
+    device_name: String,
+    /// Salt for the session key, sent by the host
+    salt: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Manifest,
+    /// Answered with a raw frame holding the file
+    Get {
+        path: String,
+    },
+    /// Followed by a raw frame holding the file
+    Put {
+        path: String,
+    },
+    Delete {
+        path: String,
+    },
+    Done,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Response {
+    Manifest { files: HashMap<String, String> },
+    Ok,
+    Error { message: String },
+}
+
+struct ClientOutcome {
+    summary: LanSyncSummary,
+    records: HashMap<String, String>,
+    /// Remote files edited on both devices; the local side wins and these
+    /// are kept as conflict copies
+    conflicts: Vec<(String, Vec<u8>)>,
+}
+
+fn write_frame(stream: &mut impl Write, bytes: &[u8]) -> Result<(), String> {
+    let len = u32::try_from(bytes.len()).map_err(|_| "Message is too large".to_string())?;
+    stream
+        .write_all(&len.to_be_bytes())
+        .and_then(|_| stream.write_all(bytes))
+        .map_err(|e| format!("Failed to send to peer: {}", e))
+}
+
+fn read_frame(stream: &mut impl Read, max_len: usize) -> Result<Vec<u8>, String> {
+    let mut len = [0u8; 4];
+    stream
+        .read_exact(&mut len)
+        .map_err(|e| format!("Connection to peer lost: {}", e))?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_len {
+        return Err("Peer sent an oversized message".to_string());
+    }
+    let mut bytes = vec![0u8; len];
+    stream
+        .read_exact(&mut bytes)
+        .map_err(|e| format!("Connection to peer lost: {}", e))?;
+    Ok(bytes)
+}
+
+fn send_plain<T: Serialize>(stream: &mut impl Write, value: &T) -> Result<(), String> {
+    let bytes = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    write_frame(stream, &bytes)
+}
+
+fn recv_plain<T: DeserializeOwned>(stream: &mut impl Read) -> Result<T, String> {
+    serde_json::from_slice(&read_frame(stream, MAX_HANDSHAKE_FRAME_LEN)?)
+        .map_err(|e| format!("Peer sent an invalid message: {}", e))
+}
+
+/// A connection whose frames are sealed with the key derived from the
+/// pairing code, so a wrong code fails on the first message. Each frame is
+/// also bound to its direction and position in the stream, so a frame that
+/// is replayed, dropped, reordered or reflected back fails to open.
+struct Channel<S> {
+    stream: S,
+    key: crypto::Key,
+    is_host: bool,
+    sent: u64,
+    received: u64,
+}
+
+/// Associated data of the `seq`-th frame sent by the host or the client
+fn frame_aad(from_host: bool, seq: u64) -> [u8; 9] {
+    let mut aad = [0u8; 9];
+    aad[0] = if from_host { b'H' } else { b'C' };
+    aad[1..].copy_from_slice(&seq.to_be_bytes());
+    aad
+}
+
+impl<S: Read + Write> Channel<S> {
+    fn new(stream: S, key: crypto::Key, is_host: bool) -> Self {
+        Self {
+            stream,
+            key,
+            is_host,
+            sent: 0,
+            received: 0,
+        }
+    }
+
+    fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let aad = frame_aad(self.is_host, self.sent);
+        write_frame(
+            &mut self.stream,
+            &crypto::encrypt_with_aad(&self.key, bytes, &aad)?,
+        )?;
+        self.sent += 1;
+        Ok(())
+    }
+
+    /// Decrypt the next frame expected from the peer
+    fn open(&mut self, frame: &[u8]) -> Result<Vec<u8>, String> {
+        let aad = frame_aad(!self.is_host, self.received);
+        let bytes = crypto::decrypt_with_aad(&self.key, frame, &aad).map_err(|_| {
+            if self.received == 0 {
+                "Pairing code does not match".to_string()
+            } else {
+                "Peer sent a message out of sequence".to_string()
+            }
+        })?;
+        self.received += 1;
+        Ok(bytes)
+    }
+
+    fn recv_bytes(&mut self) -> Result<Vec<u8>, String> {
+        let frame = read_frame(&mut self.stream, MAX_FRAME_LEN)?;
+        self.open(&frame)
+    }
+
+    fn send<T: Serialize>(&mut self, value: &T) -> Result<(), String> {
+        self.send_bytes(&serde_json::to_vec(value).map_err(|e| e.to_string())?)
+    }
+
+    fn recv<T: DeserializeOwned>(&mut self) -> Result<T, String> {
+        serde_json::from_slice(&self.recv_bytes()?)
+            .map_err(|e| format!("Peer sent an invalid message: {}", e))
+    }
+
+    fn expect_ok(&mut self) -> Result<(), String> {
+        match self.recv()? {
+            Response::Ok => Ok(()),
+            Response::Error { message } => Err(message),
+            Response::Manifest { .. } => Err("Peer sent an unexpected reply".to_string()),
+        }
+    }
+}
+
+fn pairing_code() -> String {
+    let bytes = u128::from_le_bytes(crypto::random_salt());
+    let symbols: Vec<char> = (0..26)
+        .map(|i| CODE_ALPHABET[((bytes >> (i * 5)) & 0x1f) as usize] as char)
+        .collect();
+    symbols
+        .chunks(CODE_GROUP_LEN)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Canonical form of an entered pairing code: separators and case dropped,
+/// look-alike letters read as the digits they are mistaken for
+fn normalize_pairing_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        })
+        .collect()
+}
+
+fn device_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Noteban".to_string())
+}
+
+/// Stable id of this device, shared by LAN pairing and sync metadata
+pub(crate) fn device_id(cache: &CacheDb) -> Result<String, String> {
+    if let Some(id) = cache.get_meta(DEVICE_ID_KEY)? {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    cache.set_meta(DEVICE_ID_KEY, &id)?;
+    Ok(id)
+}
+
+fn local_manifest(
+    root: &Path,
+    selective: &SelectiveSyncConfig,
+) -> Result<HashMap<String, String>, String> {
+    Ok(list_local_files(root)?
+        .into_iter()
+        .filter(|(path, _)| selective.includes(path))
+        .map(|(path, file)| (path, file.hash))
+        .collect())
+}
+
+/// Reject peer-supplied paths that escape the vault or fall outside sync
+fn check_peer_path(path: &str, selective: &SelectiveSyncConfig) -> Result<(), String> {
+    let plain = Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !plain || path.contains('\\') || !should_sync_file(path) || !selective.includes(path) {
+        return Err(format!("Refusing to sync {}", logging::text(path)));
+    }
+    Ok(())
+}
+
+/// Resolve a checked peer path on the host, refusing paths that reach
+/// outside the vault through a symlinked folder or file
+fn resolve_peer_path(root: &Path, path: &str) -> Result<PathBuf, String> {
+    let target = root.join(path);
+    let existing = target
+        .ancestors()
+        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+        .ok_or("Path does not exist")?;
+    validate_path_within_base(existing, root)
+        .map_err(|_| format!("Refusing to sync {}", logging::text(path)))?;
+    Ok(target)
+}
+
+/// Serve one paired device. `Ok(false)` means the pairing code was wrong.
+fn serve_peer(
+    stream: TcpStream,
+    root: &Path,
+    code: &str,
+    selective: &SelectiveSyncConfig,
+    host: &Hello,
+) -> Result<bool, String> {
+    stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(IO_TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    let mut stream = stream;
+    let peer: Hello = recv_plain(&mut stream)?;
+    let salt = crypto::random_salt();
+    send_plain(
+        &mut stream,
+        &Hello {
+            salt: Some(crypto::to_hex(&salt)),
+            ..host.clone()
+        },
+    )?;
+    let key = crypto::derive_key(&normalize_pairing_code(code), &salt)?;
+    let mut channel = Channel::new(stream, key, true);
+
+    // Only a first frame that fails authentication is a wrong code; a peer
+    // that times out or hangs up before sending one has guessed nothing
+    let first = read_frame(&mut channel.stream, MAX_HANDSHAKE_FRAME_LEN)?;
+    let Ok(first) = channel.open(&first) else {
+        return Ok(false);
+    };
+    let mut pending = Some(first);
+    loop {
+        let request: Request = match pending.take() {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| format!("Peer sent an invalid message: {}", e))?,
+            None => channel.recv()?,
+        };
+        let outcome = match request {
+            Request::Done => {
+                log::info!(
+                    "LAN sync with {} finished",
+                    logging::text(&peer.device_name)
+                );
+                return Ok(true);
+            }
+            Request::Manifest => {
+                let files = local_manifest(root, selective)?;
+                channel.send(&Response::Manifest { files })?;
+                continue;
+            }
+            Request::Get { path } => {
+                let bytes = check_peer_path(&path, selective)
+                    .and_then(|_| resolve_peer_path(root, &path))
+                    .and_then(|file| fs::read(file).map_err(|e| e.to_string()));
+                match bytes {
+                    Ok(bytes) => channel.send_bytes(&bytes)?,
+                    // An empty frame tells the client the file is gone
+                    Err(_) => channel.send_bytes(&[])?,
+                }
+                continue;
+            }
+            Request::Put { path } => {
+                let bytes = channel.recv_bytes()?;
+                check_peer_path(&path, selective)
+                    .and_then(|_| resolve_peer_path(root, &path))
+                    .and_then(|file| {
+                        if !is_locked_local_file(root, &path) {
+                            return write_local_file(root, &path, &bytes);
+                        }
+                        // The locked note stays; the peer's version is kept
+                        // beside it and comes back as a new file next sync
+                        write_conflict_copy(&file, &bytes, REASON_SYNC, Some("remote")).map(|_| ())
+                    })
+            }
+            Request::Delete { path } => check_peer_path(&path, selective)
+                .and_then(|_| resolve_peer_path(root, &path))
+                .and_then(|_| {
+                    if is_locked_local_file(root, &path) {
+                        // Kept, so the peer downloads it again next sync
+                        log::info!("Kept locked note {} deleted by peer", logging::text(&path));
+                        return Ok(());
+                    }
+                    delete_local_file(root, &path)
+                }),
+        };
+        match outcome {
+            Ok(()) => channel.send(&Response::Ok)?,
+            Err(message) => channel.send(&Response::Error { message })?,
+        }
+    }
+}
+
+fn connect_to_peer(
+    address: SocketAddr,
+    code: &str,
+    local: &Hello,
+) -> Result<(Channel<TcpStream>, Hello), String> {
+    let mut stream = TcpStream::connect_timeout(&address, IO_TIMEOUT)
+        .map_err(|e| format!("Failed to reach {}: {}", address, e))?;
+    stream
+        .set_read_timeout(Some(IO_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    send_plain(&mut stream, local)?;
+    let host: Hello = recv_plain(&mut stream)?;
+    let salt = crypto::from_hex(host.salt.as_deref().ok_or("Peer did not send a salt")?)?;
+    let key = crypto::derive_key(&normalize_pairing_code(code), &salt)?;
+    Ok((Channel::new(stream, key, false), host))
+}
+
+/// Reconcile the local vault with the host, using the hashes both sides had
+/// after the previous sync with this peer to tell edits from deletions
+fn sync_with_host<S: Read + Write>(
+    channel: &mut Channel<S>,
+    root: &Path,
+    selective: &SelectiveSyncConfig,
+    mut records: HashMap<String, String>,
+) -> Result<ClientOutcome, String> {
+    channel.send(&Request::Manifest)?;
+    let remote = match channel.recv()? {
+        Response::Manifest { files } => files,
+        Response::Error { message } => return Err(message),
+        Response::Ok => return Err("Peer sent an unexpected reply".to_string()),
+    };
+    let local = local_manifest(root, selective)?;
+    let mut summary = LanSyncSummary::default();
+    let mut conflicts = Vec::new();
+
+    let mut paths: Vec<String> = local
+        .keys()
+        .chain(remote.keys())
+        .filter(|path| check_peer_path(path, selective).is_ok())
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let local_hash = local.get(&path);
+        let remote_hash = remote.get(&path);
+        let record = records.get(&path);
+        let decision = decide_sync_action(
+            local_hash.is_some(),
+            remote_hash.is_some(),
+            record.is_some(),
+            local_hash.is_some_and(|hash| Some(hash) != record),
+            remote_hash.is_some_and(|hash| Some(hash) != record),
+        );
+        let locked = local_hash.is_some() && is_locked_local_file(root, &path);
+        let decision = locked_sync_decision(decision, locked);
+
+        match (decision, local_hash, remote_hash) {
+            (SyncDecision::Noop, Some(hash), Some(_)) if local_hash == remote_hash => {
+                records.insert(path, hash.clone());
+            }
+            (SyncDecision::Noop, _, _) => {}
+            (SyncDecision::UploadLocal | SyncDecision::UploadNew, Some(hash), _)
+            | (SyncDecision::Conflict, Some(hash), None) => {
+                put_file(channel, root, &path)?;
+                summary.sent += 1;
+                records.insert(path, hash.clone());
+            }
+            (SyncDecision::DownloadRemote | SyncDecision::DownloadNew, _, Some(hash))
+            | (SyncDecision::Conflict, None, Some(hash)) => {
+                if let Some(bytes) = get_file(channel, &path)? {
+                    write_local_file(root, &path, &bytes)?;
+                    summary.received += 1;
+                    records.insert(path, hash.clone());
+                }
+            }
+            (SyncDecision::Conflict, Some(hash), Some(theirs)) => {
+                if hash != theirs {
+                    if let Some(bytes) = get_file(channel, &path)? {
+                        conflicts.push((path.clone(), bytes));
+                    }
+                    put_file(channel, root, &path)?;
+                    summary.sent += 1;
+                }
+                records.insert(path, hash.clone());
+            }
+            (SyncDecision::DeleteLocal, _, _) => {
+                delete_local_file(root, &path)?;
+                summary.deleted_local += 1;
+                records.remove(&path);
+            }
+            (SyncDecision::DeleteRemote, _, _) => {
+                channel.send(&Request::Delete { path: path.clone() })?;
+                channel.expect_ok()?;
+                summary.deleted_remote += 1;
+                records.remove(&path);
+            }
+            _ => {}
+        }
+    }
+
+    channel.send(&Request::Done)?;
+    records.retain(|path, _| local.contains_key(path) || remote.contains_key(path));
+    Ok(ClientOutcome {
+        summary,
+        records,
+        conflicts,
+    })
+}
+
+fn put_file<S: Read + Write>(
+    channel: &mut Channel<S>,
+    root: &Path,
+    path: &str,
+) -> Result<(), String> {
+    let bytes = fs::read(root.join(path)).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    channel.send(&Request::Put {
+        path: path.to_string(),
+    })?;
+    channel.send_bytes(&bytes)?;
+    channel.expect_ok()
+}
+
+fn get_file<S: Read + Write>(
+    channel: &mut Channel<S>,
+    path: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    channel.send(&Request::Get {
+        path: path.to_string(),
+    })?;
+    let bytes = channel.recv_bytes()?;
+    Ok((!bytes.is_empty()).then_some(bytes))
+}
+
+/// Offer this vault on the local network under a fresh pairing code
+#[tauri::command]
+pub fn start_lan_sync_host(
+    notes_dir: String,
+    state: State<AppState>,
+) -> Result<LanHostInfo, String> {
+    if state.safe_mode {
+        return Err("Sync is disabled in safe mode".to_string());
+    }
+    stop_lan_sync_host(state.clone())?;
+    let (device_id, selective) = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        (device_id(cache)?, read_selective_sync(cache)?)
+    };
+
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .map_err(|e| format!("Failed to start LAN sync: {}", e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to start LAN sync: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start LAN sync: {}", e))?
+        .port();
+
+    let info = LanHostInfo {
+        device_name: device_name(),
+        port,
+        pairing_code: pairing_code(),
+    };
+    let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start discovery: {}", e))?;
+    let properties = HashMap::from([
+        ("id".to_string(), device_id.clone()),
+        ("name".to_string(), info.device_name.clone()),
+    ]);
+    let service = ServiceInfo::new(
+        SERVICE_TYPE,
+        &format!("noteban-{}", &device_id[..8]),
+        &format!("noteban-{}.local.", &device_id[..8]),
+        "",
+        port,
+        properties,
+    )
+    .map_err(|e| format!("Failed to announce LAN sync: {}", e))?
+    .enable_addr_auto();
+    daemon
+        .register(service)
+        .map_err(|e| format!("Failed to announce LAN sync: {}", e))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = Arc::clone(&stop);
+    let host = Hello {
+        device_id,
+        device_name: info.device_name.clone(),
+        salt: None,
+    };
+    let code = info.pairing_code.clone();
+    std::thread::spawn(move || {
+        let root = Path::new(&notes_dir);
+        let mut failed = 0;
+        while !stop_flag.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, peer)) => match serve_peer(stream, root, &code, &selective, &host) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        failed += 1;
+                        log::warn!("Rejected LAN sync from {}: wrong pairing code", peer);
+                        if failed >= MAX_FAILED_PAIRINGS {
+                            log::warn!("Stopped LAN sync after repeated wrong pairing codes");
+                            break;
+                        }
+                    }
+                    Err(e) => log::warn!("LAN sync with {} failed: {}", peer, e),
+                },
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(ACCEPT_POLL_INTERVAL)
+                }
+                Err(e) => {
+                    log::warn!("LAN sync stopped: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    *lock_or_err(&state.lan_sync_host)? = Some(LanHost {
+        info: info.clone(),
+        stop,
+        daemon,
+    });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn get_lan_sync_host(state: State<AppState>) -> Result<Option<LanHostInfo>, String> {
+    Ok(lock_or_err(&state.lan_sync_host)?
+        .as_ref()
+        .map(|host| host.info.clone()))
+}
+
+#[tauri::command]
+pub fn stop_lan_sync_host(state: State<AppState>) -> Result<(), String> {
+    if let Some(host) = lock_or_err(&state.lan_sync_host)?.take() {
+        host.stop.store(true, Ordering::Relaxed);
+        if let Err(e) = host.daemon.shutdown() {
+            log::warn!("Failed to stop LAN discovery: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Devices offering LAN sync, found over mDNS within `timeout_ms`
+#[tauri::command]
+pub async fn discover_lan_peers(timeout_ms: Option<u64>) -> Result<Vec<LanPeer>, String> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_DISCOVERY_MS));
+    tauri::async_runtime::spawn_blocking(move || {
+        let daemon =
+            ServiceDaemon::new().map_err(|e| format!("Failed to start discovery: {}", e))?;
+        let events = daemon
+            .browse(SERVICE_TYPE)
+            .map_err(|e| format!("Failed to start discovery: {}", e))?;
+        let deadline = Instant::now() + timeout;
+        let mut peers: Vec<LanPeer> = Vec::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(event) = events.recv_timeout(remaining) else {
+                break;
+            };
+            let ServiceEvent::ServiceResolved(service) = event else {
+                continue;
+            };
+            let Some(address) = service
+                .get_addresses()
+                .iter()
+                .find(|address| address.is_ipv4())
+                .or_else(|| service.get_addresses().iter().next())
+            else {
+                continue;
+            };
+            let device_id = service.get_property_val_str("id").unwrap_or_default();
+            if peers.iter().any(|peer| peer.device_id == device_id) {
+                continue;
+            }
+            peers.push(LanPeer {
+                device_id: device_id.to_string(),
+                device_name: service
+                    .get_property_val_str("name")
+                    .unwrap_or(service.get_hostname())
+                    .to_string(),
+                address: address.to_string(),
+                port: service.get_port(),
+            });
+        }
+        let _ = daemon.shutdown();
+        Ok(peers)
+    })
+    .await
+    .map_err(|e| format!("Discovery failed: {}", e))?
+}
+
+/// Sync this vault with a device running `start_lan_sync_host`, using the
+/// pairing code shown there
+#[tauri::command]
+pub async fn lan_sync_with_peer(
+    notes_dir: String,
+    address: String,
+    port: u16,
+    pairing_code: String,
+    app: AppHandle,
+) -> Result<LanSyncSummary, String> {
+    let address: IpAddr = address
+        .parse()
+        .map_err(|_| format!("Invalid address: {}", address))?;
+    let (local, selective) = {
+        let state = app.state::<AppState>();
+        if state.safe_mode {
+            return Err("Sync is disabled in safe mode".to_string());
+        }
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        let local = Hello {
+            device_id: device_id(cache)?,
+            device_name: device_name(),
+            salt: None,
+        };
+        (local, read_selective_sync(cache)?)
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&notes_dir);
+        let (mut channel, host) =
+            connect_to_peer(SocketAddr::new(address, port), &pairing_code, &local)?;
+        let record_key = format!("{}{}", PEER_RECORD_PREFIX, host.device_id);
+        let records = {
+            let state = app.state::<AppState>();
+            let cache_lock = lock_or_err(&state.cache)?;
+            let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+            cache
+                .get_meta(&record_key)?
+                .and_then(|value| serde_json::from_str(&value).ok())
+                .unwrap_or_default()
+        };
+
+        let app_state = app.state::<AppState>();
+        let _pause = watch::pause(&app_state);
+        let outcome = sync_with_host(&mut channel, root, &selective, records)?;
+        let mut summary = outcome.summary;
+        summary.peer_name = host.device_name;
+
+        let state = app.state::<AppState>();
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        for (path, bytes) in outcome.conflicts {
+            summary
+                .conflicts
+                .push(write_conflict_file(cache, root, &path, &bytes)?);
+        }
+        let encoded = serde_json::to_string(&outcome.records)
+            .map_err(|e| format!("Failed to encode LAN sync state: {}", e))?;
+        cache.set_meta(&record_key, &encoded)?;
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("LAN sync failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syncs_with_host_and_rejects_wrong_code() {
+        let root = std::env::temp_dir().join(format!("noteban-lan-{}", uuid::Uuid::new_v4()));
+        let (host_dir, client_dir) = (root.join("host"), root.join("client"));
+        fs::create_dir_all(host_dir.join("Private")).unwrap();
+        fs::create_dir_all(&client_dir).unwrap();
+        fs::write(host_dir.join("shared.md"), "from host").unwrap();
+        fs::write(host_dir.join("Private/secret.md"), "host only").unwrap();
+        fs::write(client_dir.join("mine.md"), "from client").unwrap();
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let host_root = host_dir.clone();
+        let server = std::thread::spawn(move || {
+            let host = Hello {
+                device_id: "host".to_string(),
+                device_name: "Desktop".to_string(),
+                salt: None,
+            };
+            let selective = SelectiveSyncConfig {
+                local_only: vec!["Private".to_string()],
+                sync_only: Vec::new(),
+            };
+            (0..3)
+                .map(|_| {
+                    let (stream, _) = listener.accept().unwrap();
+                    serve_peer(stream, &host_root, "ABCD-EFGH", &selective, &host)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let client = Hello {
+            device_id: "client".to_string(),
+            device_name: "Laptop".to_string(),
+            salt: None,
+        };
+        // Hanging up before the first frame is not a wrong code
+        drop(connect_to_peer(address, "ABCD-EFGH", &client).unwrap());
+
+        let (mut wrong, _) = connect_to_peer(address, "WXYZ-2345", &client).unwrap();
+        assert!(wrong.send(&Request::Manifest).is_ok());
+        assert!(wrong.recv::<Response>().is_err());
+
+        let (mut channel, host) = connect_to_peer(address, "abcd efgh", &client).unwrap();
+        assert_eq!(host.device_name, "Desktop");
+        let outcome = sync_with_host(
+            &mut channel,
+            &client_dir,
+            &SelectiveSyncConfig::default(),
+            HashMap::new(),
+        )
+        .unwrap();
+        let served = server.join().unwrap();
+        assert!(served[0].is_err());
+        assert_eq!(served[1], Ok(false));
+        assert_eq!(served[2], Ok(true));
+
+        assert_eq!((outcome.summary.sent, outcome.summary.received), (1, 1));
+        assert_eq!(
+            fs::read_to_string(client_dir.join("shared.md")).unwrap(),
+            "from host"
+        );
+        assert_eq!(
+            fs::read_to_string(host_dir.join("mine.md")).unwrap(),
+            "from client"
+        );
+        assert!(!client_dir.join("Private/secret.md").exists());
+        assert_eq!(outcome.records.len(), 2);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn leaves_locked_notes_alone_on_both_sides() {
+        use crate::test_support::TestVault;
+
+        let (host_vault, client_vault) = (TestVault::new(), TestVault::new());
+        host_vault.note("plan.md", "plan", "locked: true\n");
+        host_vault.note("kept.md", "kept", "locked: true\n");
+        client_vault.note("plan.md", "plan", "tags: [edited]\n");
+        client_vault.note("pinned.md", "pinned", "locked: true\n");
+        // Last sync left both devices with the host's plan and kept notes,
+        // then kept was deleted on the client and pinned on the host
+        let all = SelectiveSyncConfig::default();
+        let mut records = local_manifest(&host_vault.dir, &all).unwrap();
+        let client_files = local_manifest(&client_vault.dir, &all).unwrap();
+        records.insert("pinned.md".to_string(), client_files["pinned.md"].clone());
+        assert!(!host_vault.exists("pinned.md"));
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let host_root = host_vault.dir.clone();
+        let server = std::thread::spawn(move || {
+            let host = Hello {
+                device_id: "host".to_string(),
+                device_name: "Desktop".to_string(),
+                salt: None,
+            };
+            let (stream, _) = listener.accept().unwrap();
+            serve_peer(
+                stream,
+                &host_root,
+                "ABCD",
+                &SelectiveSyncConfig::default(),
+                &host,
+            )
+        });
+        let client = Hello {
+            device_id: "client".to_string(),
+            device_name: "Laptop".to_string(),
+            salt: None,
+        };
+        let (mut channel, _) = connect_to_peer(address, "ABCD", &client).unwrap();
+        let outcome = sync_with_host(&mut channel, &client_vault.dir, &all, records).unwrap();
+        assert_eq!(server.join().unwrap(), Ok(true));
+
+        // The client's edit of the host's locked note became a conflict copy
+        assert!(!host_vault.read("plan.md").contains("edited"));
+        let copies: Vec<_> = fs::read_dir(&host_vault.dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("plan.conflict-"))
+            .collect();
+        assert_eq!(copies.len(), 1);
+        // A locked note deleted on one side is kept on the other
+        assert!(host_vault.exists("kept.md"));
+        assert!(client_vault.exists("pinned.md"));
+        assert!(host_vault.exists("pinned.md"));
+        assert_eq!(outcome.summary.deleted_local, 0);
+    }
+
+    #[test]
+    fn rejects_frames_out_of_sequence_or_oversized() {
+        use std::io::Cursor;
+
+        let key = crypto::random_key();
+        let mut client = Channel::new(Cursor::new(Vec::new()), key, false);
+        client.send_bytes(b"first").unwrap();
+        client.send_bytes(b"second").unwrap();
+        let frames = client.stream.into_inner();
+
+        let mut host = Channel::new(Cursor::new(frames.clone()), key, true);
+        assert_eq!(host.recv_bytes().unwrap(), b"first");
+        assert_eq!(host.recv_bytes().unwrap(), b"second");
+
+        // Dropping the first frame leaves the second one out of sequence
+        let first_len = 4 + u32::from_be_bytes(frames[..4].try_into().unwrap()) as usize;
+        let mut host = Channel::new(Cursor::new(frames[first_len..].to_vec()), key, true);
+        assert_eq!(
+            host.recv_bytes(),
+            Err("Pairing code does not match".to_string())
+        );
+        // Replaying the first frame fails the same way
+        let mut host = Channel::new(Cursor::new(frames[..first_len].repeat(2)), key, true);
+        assert!(host.recv_bytes().is_ok());
+        assert_eq!(
+            host.recv_bytes(),
+            Err("Peer sent a message out of sequence".to_string())
+        );
+        // A client frame reflected back does not open as one from the host
+        let mut reflected = Channel::new(Cursor::new(frames), key, false);
+        assert!(reflected.recv_bytes().is_err());
+
+        let mut hello = ((MAX_HANDSHAKE_FRAME_LEN + 1) as u32)
+            .to_be_bytes()
+            .to_vec();
+        hello.resize(MAX_HANDSHAKE_FRAME_LEN + 5, b' ');
+        assert_eq!(
+            recv_plain::<Hello>(&mut Cursor::new(hello)).unwrap_err(),
+            "Peer sent an oversized message"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_peer_paths_through_symlinks() {
+        let root = std::env::temp_dir().join(format!("noteban-lan-{}", uuid::Uuid::new_v4()));
+        let (vault, outside) = (root.join("vault"), root.join("outside"));
+        fs::create_dir_all(vault.join("notes")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.md"), "outside").unwrap();
+        std::os::unix::fs::symlink(&outside, vault.join("linked")).unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.md"), vault.join("file.md")).unwrap();
+        std::os::unix::fs::symlink(outside.join("gone.md"), vault.join("dangling.md")).unwrap();
+
+        assert!(resolve_peer_path(&vault, "notes/new.md").is_ok());
+        assert!(resolve_peer_path(&vault, "new/folder/note.md").is_ok());
+        assert!(resolve_peer_path(&vault, "linked/secret.md").is_err());
+        assert!(resolve_peer_path(&vault, "linked/new.md").is_err());
+        assert!(resolve_peer_path(&vault, "file.md").is_err());
+        assert!(resolve_peer_path(&vault, "dangling.md").is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn pairing_codes_are_long_and_normalized() {
+        let code = pairing_code();
+        assert_eq!(code.len(), 26 + 6);
+        assert_ne!(code, pairing_code());
+        assert_eq!(normalize_pairing_code(&code).len(), 26);
+        assert_eq!(normalize_pairing_code(" o1l-I0 "), "01110");
+        assert_eq!(
+            normalize_pairing_code(&code.to_lowercase().replace('-', " ")),
+            normalize_pairing_code(&code)
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::lock_or_err;
+use crate::AppState;
+use serde::Serialize;
+use tauri::State;
+
+const DEFAULT_FIND_LIMIT: usize = 20;
+
+/// A note found by `find_notes`
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteMatch {
+    pub file_path: String,
+    pub title: String,
+    /// Alias that matched better than the title
+    pub alias: Option<String>,
+    pub score: i64,
+}
+
+/// Score of `candidate` for a fuzzy `query`: every query character has to
+/// appear in order. Runs of consecutive characters and matches at word
+/// starts score higher; `None` when the query doesn't match.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = candidate[position..].iter().position(|c| *c == wanted)?;
+        let index = position + offset;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+    // Prefer shorter names among equal matches
+    Some(score * 100 - candidate.len() as i64)
+}
+
+/// Path of the note a `[[target]]` link resolves to, matching titles,
+/// aliases and file names
+#[tauri::command]
+pub fn resolve_wikilink(target: String, state: State<AppState>) -> Result<Option<String>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.resolve_wikilink(&target)
+}
+
+/// Notes whose title or one of whose aliases fuzzily matches `query`, best
+/// matches first. Archived notes are only searched when asked for.
+#[tauri::command]
+pub fn find_notes(
+    query: String,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<NoteMatch>, String> {
+    let names = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        cache.note_names(include_archived.unwrap_or(false))?
+    };
+
+    let mut matches: Vec<NoteMatch> = names
+        .into_iter()
+        .filter_map(|names| {
+            let by_title = fuzzy_score(&query, &names.title).map(|score| (score, None));
+            let by_alias = names
+                .aliases
+                .into_iter()
+                .filter_map(|alias| fuzzy_score(&query, &alias).map(|score| (score, Some(alias))))
+                .max_by_key(|(score, _)| *score);
+            let (score, alias) = match (by_title, by_alias) {
+                (Some(title), Some(alias)) if alias.0 > title.0 => alias,
+                (Some(title), _) => title,
+                (None, alias) => alias?,
+            };
+            Some(NoteMatch {
+                file_path: names.file_path,
+                title: names.title,
+                alias,
+                score,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+    matches.truncate(limit.unwrap_or(DEFAULT_FIND_LIMIT));
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_fuzzy_matches() {
+        assert!(fuzzy_score("prj", "Project plan").is_some());
+        assert!(fuzzy_score("jrp", "Project plan").is_none());
+        // Consecutive and word-start matches rank first
+        let contiguous = fuzzy_score("plan", "Project plan").unwrap();
+        let scattered = fuzzy_score("plan", "Pending launch").unwrap();
+        assert!(contiguous > scattered);
+    }
+}
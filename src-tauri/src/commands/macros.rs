@@ -0,0 +1,416 @@
+use crate::cache::macros::{MacroDefinition, MacroStep, SavedMacro};
+use crate::commands::audit;
+use crate::commands::inbox::{triage_note, TriageDecision};
+use crate::commands::notes::{
+    create_note, delete_note, move_note, sanitize_tags, update_note, update_note_frontmatter,
+    CreateNoteInput, UpdateNoteInput,
+};
+use crate::commands::undo;
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tauri::State;
+
+/// Steps with this prefix are handed back to the frontend (e.g. `ui:open_window`)
+const FRONTEND_PREFIX: &str = "ui:";
+const MAX_MACRO_STEPS: usize = 50;
+const BACKEND_COMMANDS: [&str; 6] = [
+    "create_note",
+    "update_note",
+    "move_note",
+    "delete_note",
+    "add_tags",
+    "triage_note",
+];
+
+lazy_static! {
+    static ref PLACEHOLDER_REGEX: Regex = Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*\}\}").unwrap();
+}
+
+/// A frontend action requested by a macro, with its arguments substituted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendAction {
+    pub action: String,
+    pub args: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroRunResult {
+    pub steps_run: usize,
+    /// Path of every note a step created or changed, in step order
+    pub touched_paths: Vec<String>,
+    pub frontend_actions: Vec<FrontendAction>,
+}
+
+pub(crate) fn validate_macro(name: &str, definition: &MacroDefinition) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err("Macro name must be between 1 and 100 characters".to_string());
+    }
+    if definition.steps.is_empty() {
+        return Err("A macro needs at least one step".to_string());
+    }
+    if definition.steps.len() > MAX_MACRO_STEPS {
+        return Err(format!("Macros are limited to {} steps", MAX_MACRO_STEPS));
+    }
+    for (index, step) in definition.steps.iter().enumerate() {
+        let known = BACKEND_COMMANDS.contains(&step.command.as_str())
+            || step
+                .command
+                .strip_prefix(FRONTEND_PREFIX)
+                .is_some_and(|action| !action.is_empty());
+        if !known {
+            return Err(format!(
+                "Step {}: unsupported command {}",
+                index + 1,
+                step.command
+            ));
+        }
+        if !step.args.is_null() && !step.args.is_object() {
+            return Err(format!("Step {}: arguments must be an object", index + 1));
+        }
+    }
+    Ok(())
+}
+
+/// Replace `{{name}}` placeholders in every string of `value`
+fn substitute(value: &Value, vars: &HashMap<String, String>) -> Result<Value, String> {
+    match value {
+        Value::String(text) => {
+            let mut missing = None;
+            let replaced = PLACEHOLDER_REGEX.replace_all(text, |caps: &regex::Captures| match vars
+                .get(&caps[1])
+            {
+                Some(value) => value.clone(),
+                None => {
+                    missing.get_or_insert_with(|| caps[1].to_string());
+                    String::new()
+                }
+            });
+            match missing {
+                Some(name) => Err(format!("No value for {{{{{}}}}}", name)),
+                None => Ok(Value::String(replaced.into_owned())),
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| substitute(item, vars))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, item)| Ok((key.clone(), substitute(item, vars)?)))
+            .collect::<Result<Map<_, _>, String>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+fn parse_args<T: DeserializeOwned>(command: &str, args: Map<String, Value>) -> Result<T, String> {
+    serde_json::from_value(Value::Object(args))
+        .map_err(|e| format!("Invalid arguments for {}: {}", command, e))
+}
+
+fn string_arg(args: &Map<String, Value>, key: &str) -> Result<String, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("Missing argument: {}", key))
+}
+
+/// Run one backend step, returning the path of the note it produced
+fn run_step(
+    step: &MacroStep,
+    mut args: Map<String, Value>,
+    notes_dir: &str,
+    state: &State<AppState>,
+) -> Result<Option<String>, String> {
+    // Steps always act on the vault the macro runs against
+    args.insert(
+        "notes_dir".to_string(),
+        Value::String(notes_dir.to_string()),
+    );
+
+    match step.command.as_str() {
+        "create_note" => {
+            let input: CreateNoteInput = parse_args(&step.command, args)?;
+            Ok(Some(create_note(input, state.clone())?.note.file_path))
+        }
+        "update_note" => {
+            let input: UpdateNoteInput = parse_args(&step.command, args)?;
+            Ok(Some(update_note(input, state.clone())?.note.file_path))
+        }
+        "move_note" => {
+            let file_path = string_arg(&args, "file_path")?;
+            let target_folder = string_arg(&args, "target_folder")?;
+            let note = move_note(
+                notes_dir.to_string(),
+                file_path,
+                target_folder,
+                None,
+                state.clone(),
+            )?;
+            Ok(Some(note.file_path))
+        }
+        "delete_note" => {
+            let file_path = string_arg(&args, "file_path")?;
+            delete_note(notes_dir.to_string(), file_path, None, state.clone())?;
+            Ok(None)
+        }
+        "add_tags" => {
+            let file_path = string_arg(&args, "file_path")?;
+            let tags: Vec<String> = args
+                .get("tags")
+                .cloned()
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("Invalid arguments for add_tags: {}", e))?
+                .unwrap_or_default();
+            let result = update_note_frontmatter(notes_dir, &file_path, state, |frontmatter| {
+                let mut merged = frontmatter.tags.clone();
+                merged.extend(tags);
+                frontmatter.tags = sanitize_tags(merged);
+                Ok(())
+            });
+            audit::record(state, "update", &file_path, None, &result);
+            Ok(Some(result?.note.file_path))
+        }
+        "triage_note" => {
+            let file_path = string_arg(&args, "file_path")?;
+            let decision: TriageDecision = args
+                .get("decision")
+                .cloned()
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("Invalid arguments for triage_note: {}", e))?
+                .ok_or("Missing argument: decision")?;
+            let result = triage_note(notes_dir.to_string(), file_path, decision, state.clone())?;
+            Ok(result.note.map(|note| note.file_path))
+        }
+        other => Err(format!("Unsupported command {}", other)),
+    }
+}
+
+#[tauri::command]
+pub fn save_macro(
+    name: String,
+    definition: MacroDefinition,
+    state: State<AppState>,
+) -> Result<SavedMacro, String> {
+    validate_macro(&name, &definition)?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.save_macro(name.trim(), &definition)
+}
+
+#[tauri::command]
+pub fn list_macros(state: State<AppState>) -> Result<Vec<SavedMacro>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    match cache_lock.as_ref() {
+        Some(cache) => cache.get_macros(),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[tauri::command]
+pub fn delete_macro(name: String, state: State<AppState>) -> Result<(), String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    if !cache.delete_macro(&name)? {
+        return Err("Macro not found".to_string());
+    }
+    Ok(())
+}
+
+/// Run a saved macro step by step. Besides the declared parameters, steps can
+/// use `{{today}}`, `{{now}}` and `{{last.file_path}}` (the note produced by
+/// the previous step, also the default `file_path`). Execution stops at the
+/// first failing step; earlier steps are not rolled back.
+#[tauri::command]
+pub fn run_macro(
+    notes_dir: String,
+    name: String,
+    params: Option<HashMap<String, String>>,
+    state: State<AppState>,
+) -> Result<MacroRunResult, String> {
+    let saved = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        cache.get_macro(&name)?.ok_or("Macro not found")?
+    };
+
+    let mut vars = params.unwrap_or_default();
+    if let Some(missing) = saved
+        .definition
+        .params
+        .iter()
+        .find(|param| !vars.contains_key(*param))
+    {
+        return Err(format!("Missing macro parameter: {}", missing));
+    }
+    let now = Utc::now();
+    vars.insert("today".to_string(), now.format("%Y-%m-%d").to_string());
+    vars.insert("now".to_string(), now.to_rfc3339());
+
+    let mut result = MacroRunResult {
+        steps_run: 0,
+        touched_paths: Vec::new(),
+        frontend_actions: Vec::new(),
+    };
+    // Undoing a macro reverts everything its steps deleted or moved
+    let label = format!("Run macro \"{}\"", saved.name);
+    undo::grouped(&state, label, &notes_dir, || {
+        run_steps(&saved, &mut vars, &notes_dir, &state, &mut result)
+    })?;
+    Ok(result)
+}
+
+fn run_steps(
+    saved: &SavedMacro,
+    vars: &mut HashMap<String, String>,
+    notes_dir: &str,
+    state: &State<AppState>,
+    result: &mut MacroRunResult,
+) -> Result<(), String> {
+    let mut last_path: Option<String> = None;
+    for (index, step) in saved.definition.steps.iter().enumerate() {
+        let args =
+            match substitute(&step.args, vars).map_err(|e| format!("Step {}: {}", index + 1, e))? {
+                Value::Object(map) => map,
+                _ => Map::new(),
+            };
+
+        if let Some(action) = step.command.strip_prefix(FRONTEND_PREFIX) {
+            result.frontend_actions.push(FrontendAction {
+                action: action.to_string(),
+                args: Value::Object(args),
+            });
+        } else {
+            let mut args = args;
+            if let Some(path) = &last_path {
+                args.entry("file_path")
+                    .or_insert_with(|| Value::String(path.clone()));
+            }
+            let produced = run_step(step, args, notes_dir, state)
+                .map_err(|e| format!("Step {} ({}): {}", index + 1, step.command, e))?;
+            if let Some(path) = produced {
+                vars.insert("last.file_path".to_string(), path.clone());
+                result.touched_paths.push(path.clone());
+                last_path = Some(path);
+            }
+        }
+        result.steps_run += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn substitutes_placeholders_recursively() {
+        let vars = HashMap::from([
+            ("folder".to_string(), "Projects".to_string()),
+            ("tag".to_string(), "review".to_string()),
+        ]);
+        let args = json!({
+            "folder_path": "{{folder}}/{{ tag }}",
+            "tags": ["{{tag}}", "weekly"],
+            "order": 3
+        });
+        assert_eq!(
+            substitute(&args, &vars).unwrap(),
+            json!({
+                "folder_path": "Projects/review",
+                "tags": ["review", "weekly"],
+                "order": 3
+            })
+        );
+        assert_eq!(
+            substitute(&json!("{{unknown}}"), &vars).unwrap_err(),
+            "No value for {{unknown}}"
+        );
+    }
+
+    #[test]
+    fn runs_a_saved_macro_end_to_end() {
+        use crate::commands::undo::undo_last_operation;
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        let notes_dir = vault.notes_dir();
+        let definition: MacroDefinition = serde_json::from_value(json!({
+            "params": ["project"],
+            "steps": [
+                {
+                    "command": "create_note",
+                    "args": { "title": "{{project}} kickoff", "folder_path": "{{project}}" }
+                },
+                { "command": "add_tags", "args": { "tags": ["kickoff"] } },
+                { "command": "move_note", "args": { "target_folder": "Active" } },
+                { "command": "ui:open_note", "args": { "file_path": "{{last.file_path}}" } }
+            ]
+        }))
+        .unwrap();
+        save_macro("Kickoff".into(), definition, vault.state()).unwrap();
+        assert_eq!(list_macros(vault.state()).unwrap().len(), 1);
+
+        let missing = run_macro(notes_dir.clone(), "Kickoff".into(), None, vault.state());
+        assert_eq!(missing.unwrap_err(), "Missing macro parameter: project");
+
+        let params = HashMap::from([("project".to_string(), "Apollo".to_string())]);
+        let result = run_macro(notes_dir, "Kickoff".into(), Some(params), vault.state()).unwrap();
+        let moved = vault.path("Active/apollo-kickoff.md");
+        assert_eq!(result.steps_run, 4);
+        assert_eq!(
+            result.touched_paths,
+            [
+                vault.path("Apollo/apollo-kickoff.md"),
+                vault.path("Apollo/apollo-kickoff.md"),
+                moved.clone()
+            ]
+        );
+        assert_eq!(result.frontend_actions[0].action, "open_note");
+        assert_eq!(
+            result.frontend_actions[0].args,
+            json!({ "file_path": moved })
+        );
+        assert!(vault.read("Active/apollo-kickoff.md").contains("kickoff"));
+
+        // The whole run is undone as one operation
+        assert_eq!(
+            undo_last_operation(vault.state()).unwrap().label,
+            "Run macro \"Kickoff\""
+        );
+        assert!(vault.exists("Apollo/apollo-kickoff.md"));
+        assert!(!vault.exists("Active/apollo-kickoff.md"));
+
+        delete_macro("Kickoff".into(), vault.state()).unwrap();
+        assert!(delete_macro("Kickoff".into(), vault.state()).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        let definition = |command: &str| MacroDefinition {
+            description: None,
+            params: Vec::new(),
+            steps: vec![MacroStep {
+                command: command.to_string(),
+                args: json!({}),
+            }],
+        };
+        assert!(validate_macro("m", &definition("create_note")).is_ok());
+        assert!(validate_macro("m", &definition("ui:open_window")).is_ok());
+        assert!(validate_macro("m", &definition("ui:")).is_err());
+        assert!(validate_macro("m", &definition("empty_trash")).is_err());
+        assert!(validate_macro(" ", &definition("create_note")).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::commands::encryption::is_encrypted;
+use crate::commands::notes::{
+    parse_note, sanitize_icon, update_note_frontmatter, validate_existing_path_within_base,
+    NoteWithTags,
+};
+use crate::commands::{audit, broadcast};
+use crate::vault_config::{
+    folder_key, load_config, update_folder_meta, FolderMeta, FolderMetaMap, FOLDERS_FILE,
+};
+use crate::AppState;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Keys backed by typed note fields or owned by other commands
+const PROTECTED_KEYS: [&str; 19] = [
+    "id",
+    "title",
+    "created",
+    "modified",
+    "date",
+    "due",
+    "column",
+    "tags",
+    "order",
+    "pinned",
+    "archived",
+    "priority",
+    "color",
+    "cover",
+    "icon",
+    "aliases",
+    "locked",
+    "encrypted",
+    "commits",
+];
+
+fn metadata_json(extra: &serde_yaml::Mapping) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(extra).map_err(|e| format!("Failed to encode metadata: {}", e))? {
+        Value::Object(map) => Ok(map),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Custom frontmatter properties of a note
+#[tauri::command]
+pub fn get_note_metadata(
+    notes_dir: String,
+    file_path: String,
+) -> Result<Map<String, Value>, String> {
+    let path = PathBuf::from(&file_path);
+    validate_existing_path_within_base(&path, Path::new(&notes_dir))?;
+    let note = parse_note(&path)?;
+    metadata_json(&note.frontmatter.extra)
+}
+
+/// Set one custom frontmatter property; `null` removes it. A key that is
+/// already present keeps its place in the file.
+#[tauri::command]
+pub fn set_note_metadata(
+    notes_dir: String,
+    file_path: String,
+    key: String,
+    value: Value,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Metadata key must not be empty".to_string());
+    }
+    if PROTECTED_KEYS.contains(&key) {
+        return Err(format!("\"{}\" can't be set as metadata", key));
+    }
+    let value =
+        serde_yaml::to_value(&value).map_err(|e| format!("Failed to encode metadata: {}", e))?;
+
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        if is_encrypted(frontmatter) {
+            return Err("Unlock the note before changing its metadata".to_string());
+        }
+        if value.is_null() {
+            frontmatter.extra.shift_remove(key);
+        } else {
+            frontmatter.extra.insert(key.into(), value);
+        }
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(&notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+/// Metadata of every folder that has any, keyed by path relative to the vault
+#[tauri::command]
+pub fn get_folder_metadata(notes_dir: String) -> Result<FolderMetaMap, String> {
+    load_config(Path::new(&notes_dir), FOLDERS_FILE)
+}
+
+/// Set or clear (`None` or empty) a folder's icon
+#[tauri::command]
+pub fn set_folder_icon(
+    notes_dir: String,
+    folder_path: String,
+    icon: Option<String>,
+) -> Result<FolderMeta, String> {
+    let icon = icon.as_deref().map(sanitize_icon).transpose()?.flatten();
+    let base = Path::new(&notes_dir);
+    let folder = PathBuf::from(&folder_path);
+    validate_existing_path_within_base(&folder, base)?;
+    if !folder.is_dir() {
+        return Err("Folder does not exist".to_string());
+    }
+    let key = folder_key(base, &folder).ok_or("The vault root has no icon")?;
+    update_folder_meta(base, &key, |meta| meta.icon = icon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+    use serde_json::json;
+
+    #[test]
+    fn sets_and_removes_custom_properties_in_place() {
+        let vault = TestVault::new();
+        let file_path = vault.note("a.md", "a", "project: apollo\nestimate: 3\n");
+        let set = |key: &str, value: Value| {
+            set_note_metadata(
+                vault.notes_dir(),
+                file_path.clone(),
+                key.to_string(),
+                value,
+                vault.state(),
+            )
+        };
+
+        set("project", json!("gemini")).unwrap();
+        set("owner", json!(["ana", "bo"])).unwrap();
+        set("estimate", Value::Null).unwrap();
+        let metadata = get_note_metadata(vault.notes_dir(), file_path.clone()).unwrap();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata["project"], json!("gemini"));
+        assert_eq!(metadata["owner"], json!(["ana", "bo"]));
+        let text = vault.read("a.md");
+        let project = text.find("project: gemini").unwrap();
+        assert!(project < text.find("owner:").unwrap() && !text.contains("estimate"));
+
+        assert!(set("title", json!("x")).is_err());
+        assert!(set("  ", json!("x")).is_err());
+        assert!(get_note_metadata(vault.notes_dir(), vault.path("../b.md")).is_err());
+    }
+
+    #[test]
+    fn sets_and_clears_folder_icons() {
+        let vault = TestVault::new();
+        vault.note("Work/a.md", "a", "");
+        let work = vault.path("Work");
+
+        set_folder_icon(vault.notes_dir(), work.clone(), Some("📁".to_string())).unwrap();
+        let metadata = get_folder_metadata(vault.notes_dir()).unwrap();
+        assert_eq!(metadata["Work"].icon.as_deref(), Some("📁"));
+
+        set_folder_icon(vault.notes_dir(), work.clone(), None).unwrap();
+        assert!(!get_folder_metadata(vault.notes_dir())
+            .unwrap()
+            .contains_key("Work"));
+        assert!(set_folder_icon(vault.notes_dir(), vault.notes_dir(), None).is_err());
+        assert!(set_folder_icon(vault.notes_dir(), vault.path("a b"), None).is_err());
+    }
+}
@@ -1,2 +1,43 @@
+pub mod audit;
+pub mod backup;
+pub mod board;
+pub mod broadcast;
+pub mod cache;
+pub mod calendar;
+pub mod capabilities;
+pub mod cloud;
+pub mod conflicts;
+pub mod console;
+pub mod cover;
+pub mod encryption;
+pub mod export;
+pub mod git;
+pub mod git_sync;
+pub mod history;
+pub mod import;
+pub mod inbox;
+pub mod lan_sync;
+pub mod links;
+pub mod macros;
+pub mod metadata;
+pub mod mounts;
+pub mod note_index;
 pub mod notes;
+pub mod poll_watch;
+pub mod profile;
+pub mod profile_lock;
+pub mod recovery;
+pub mod references;
+pub mod report;
+pub mod scan;
+pub mod scratchpad;
+pub mod secrets;
+pub mod storage;
+pub mod symlinks;
 pub mod sync;
+pub mod sync_schedule;
+pub mod tags;
+pub mod trash;
+pub mod undo;
+pub mod views;
+pub mod watch;
@@ -0,0 +1,260 @@
+use crate::cache::mounts::MountRecord;
+use crate::commands::note_index;
+use crate::commands::notes::{scan_vault, validate_folder_name};
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+
+/// Mounts are listed under this folder, which is never read from the vault
+/// itself, so their relative paths cannot collide with vault folders
+pub const MOUNTS_DIR_NAME: &str = ".mounts";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadonlyMount {
+    pub name: String,
+    pub path: String,
+    pub added_at: String,
+    /// False when the mounted directory is currently missing (e.g. unplugged drive)
+    pub available: bool,
+}
+
+impl From<MountRecord> for ReadonlyMount {
+    fn from(record: MountRecord) -> Self {
+        let available = Path::new(&record.path).is_dir();
+        Self {
+            name: record.name,
+            path: record.path,
+            added_at: record.added_at,
+            available,
+        }
+    }
+}
+
+/// All read-only mounts of the active profile (empty when no cache is open)
+pub(crate) fn readonly_mounts(state: &State<AppState>) -> Vec<MountRecord> {
+    let Ok(cache_lock) = state.cache.lock() else {
+        return Vec::new();
+    };
+    match cache_lock.as_ref().map(|cache| cache.get_readonly_mounts()) {
+        Some(Ok(mounts)) => mounts,
+        Some(Err(e)) => {
+            log::warn!("Failed to load read-only mounts: {}", e);
+            Vec::new()
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Canonicalize a path that may not exist yet by resolving its nearest existing ancestor
+fn resolve_path(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut remainder = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return Some(
+                remainder
+                    .iter()
+                    .rev()
+                    .fold(canonical, |acc, part| acc.join(part)),
+            );
+        }
+        remainder.push(existing.file_name()?.to_os_string());
+        existing = existing.parent()?;
+    }
+}
+
+pub(crate) fn find_mount_for<'a>(
+    path: &Path,
+    mounts: &'a [MountRecord],
+) -> Option<&'a MountRecord> {
+    let resolved = resolve_path(path)?;
+    mounts
+        .iter()
+        .find(|mount| resolved.starts_with(Path::new(&mount.path)))
+}
+
+/// Relative path a mount is listed under
+pub(crate) fn mount_relative_path(name: &str) -> String {
+    format!("{}/{}", MOUNTS_DIR_NAME, name)
+}
+
+/// Refuse any write that would land inside a read-only mount
+pub(crate) fn ensure_writable(path: &Path, state: &State<AppState>) -> Result<(), String> {
+    let mounts = readonly_mounts(state);
+    match find_mount_for(path, &mounts) {
+        Some(mount) => Err(format!("\"{}\" is a read-only mount", mount.name)),
+        None => Ok(()),
+    }
+}
+
+/// Refuse a vault-relative folder inside the mounts listing; joined onto the
+/// vault it would create a folder shadowing the mount
+pub(crate) fn ensure_not_mount_folder(relative: &Path) -> Result<(), String> {
+    let mut components = relative
+        .components()
+        .filter(|component| *component != Component::CurDir);
+    match components.next() {
+        Some(Component::Normal(first)) if first == MOUNTS_DIR_NAME => {
+            let name = components
+                .next()
+                .map(|name| name.as_os_str().to_string_lossy().to_string())
+                .unwrap_or_default();
+            Err(format!("\"{}\" is a read-only mount", name))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Refresh the cache and index after the mounts changed, so mounted notes are
+/// listed and searchable (or stop being) right away
+fn reindex_mounts(notes_dir: &str, state: &State<AppState>) {
+    if let Err(e) = scan_vault(notes_dir, state, &mut |_| {}) {
+        log::warn!("Failed to index read-only mounts: {}", e);
+        note_index::invalidate(state);
+    }
+}
+
+#[tauri::command]
+pub fn add_readonly_mount(
+    notes_dir: String,
+    path: String,
+    mount_name: String,
+    state: State<AppState>,
+) -> Result<ReadonlyMount, String> {
+    validate_folder_name(&mount_name)?;
+    let mount_path = PathBuf::from(&path);
+    if !mount_path.is_dir() {
+        return Err("Mount path is not a directory".to_string());
+    }
+    let canonical = mount_path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve mount path: {}", e))?;
+    let vault = Path::new(&notes_dir)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve notes directory: {}", e))?;
+    if canonical.starts_with(&vault) || vault.starts_with(&canonical) {
+        return Err("Mount path overlaps the vault".to_string());
+    }
+
+    let record = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+
+        for existing in cache.get_readonly_mounts()? {
+            if existing.name == mount_name {
+                return Err("A mount with that name already exists".to_string());
+            }
+            let existing_path = Path::new(&existing.path);
+            if canonical.starts_with(existing_path) || existing_path.starts_with(&canonical) {
+                return Err(format!("Path overlaps the \"{}\" mount", existing.name));
+            }
+        }
+
+        let record = MountRecord {
+            name: mount_name,
+            path: canonical.to_string_lossy().to_string(),
+            added_at: Utc::now().to_rfc3339(),
+        };
+        cache.add_readonly_mount(&record)?;
+        record
+    };
+    reindex_mounts(&notes_dir, &state);
+    Ok(record.into())
+}
+
+#[tauri::command]
+pub fn remove_readonly_mount(
+    notes_dir: String,
+    mount_name: String,
+    state: State<AppState>,
+) -> Result<(), String> {
+    {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        if !cache.remove_readonly_mount(&mount_name)? {
+            return Err("Mount not found".to_string());
+        }
+    }
+    reindex_mounts(&notes_dir, &state);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_readonly_mounts(state: State<AppState>) -> Result<Vec<ReadonlyMount>, String> {
+    Ok(readonly_mounts(&state)
+        .into_iter()
+        .map(ReadonlyMount::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::links::find_notes;
+    use crate::commands::notes::{create_note, list_folders_cached, CreateNoteInput};
+    use crate::test_support::TestVault;
+    use std::fs;
+    use uuid::Uuid;
+
+    #[test]
+    fn mounts_are_namespaced_searchable_and_read_only() {
+        let vault = TestVault::new();
+        let notes_dir = vault.notes_dir();
+        vault.note("Wiki/local.md", "local", "");
+        let external = std::env::temp_dir().join(format!("noteban-mount-{}", Uuid::new_v4()));
+        fs::create_dir_all(&external).unwrap();
+        fs::write(
+            external.join("guide.md"),
+            "---\nid: guide\ntitle: Onboarding guide\ncolumn: todo\n---\n\nBody",
+        )
+        .unwrap();
+        let external_path = external.to_string_lossy().to_string();
+
+        let nested = vault.path("Wiki");
+        let overlap = add_readonly_mount(notes_dir.clone(), nested, "Inner".into(), vault.state());
+        assert!(overlap.is_err());
+
+        add_readonly_mount(
+            notes_dir.clone(),
+            external_path.clone(),
+            "Wiki".into(),
+            vault.state(),
+        )
+        .unwrap();
+        let found = find_notes("onboarding".into(), None, None, vault.state()).unwrap();
+        assert_eq!(found.len(), 1);
+
+        // Same name as a vault folder, listed apart from it
+        let folders = list_folders_cached(notes_dir.clone(), vault.state()).unwrap();
+        let relative: Vec<&str> = folders.iter().map(|f| f.relative_path.as_str()).collect();
+        assert!(relative.contains(&"Wiki"));
+        assert!(relative.contains(&".mounts/Wiki"));
+        let mount = folders
+            .iter()
+            .find(|f| f.relative_path == ".mounts/Wiki")
+            .unwrap();
+        assert_eq!(mount.parent_path, None);
+
+        let input = CreateNoteInput {
+            notes_dir: notes_dir.clone(),
+            folder_path: Some(".mounts/Wiki".into()),
+            title: "Shadow".into(),
+            content: None,
+            date: None,
+            due: None,
+            column: None,
+            tags: None,
+            idempotency_key: None,
+        };
+        assert!(create_note(input, vault.state()).is_err());
+        assert!(!vault.exists(".mounts"));
+
+        remove_readonly_mount(notes_dir, "Wiki".into(), vault.state()).unwrap();
+        let found = find_notes("onboarding".into(), None, None, vault.state()).unwrap();
+        assert!(found.is_empty());
+        fs::remove_dir_all(&external).unwrap();
+    }
+}
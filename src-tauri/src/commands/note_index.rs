@@ -0,0 +1,292 @@
+use crate::cache::CacheDb;
+use crate::commands::cloud::{self, CloudPlaceholder};
+use crate::commands::notes::{
+    get_file_mtime, is_in_trash, parse_note, Folder, NoteWithTags, NotesWithTagsAndFolders,
+};
+use crate::utils::{extract_inline_tags, make_excerpt};
+use crate::vault_config::{nested_boards, BoardScope};
+use crate::AppState;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tauri::State;
+
+/// Listing of the open vault kept in memory so list calls don't walk the
+/// filesystem every time. Set by each full scan, patched by watcher events
+/// and the app's own writes, and dropped when a change can't be applied note
+/// by note (folder renames and deletions).
+pub struct NoteIndex {
+    notes_dir: String,
+    notes: HashMap<String, NoteWithTags>,
+    folders: Vec<Folder>,
+    placeholders: Vec<CloudPlaceholder>,
+    /// Paths the app wrote since they were last read into the index
+    stale: HashSet<String>,
+}
+
+/// Replace the index with the result of a full scan
+pub(crate) fn replace(state: &State<AppState>, notes_dir: &str, listing: &NotesWithTagsAndFolders) {
+    if let Ok(mut index) = state.note_index.lock() {
+        *index = Some(NoteIndex {
+            notes_dir: notes_dir.to_string(),
+            notes: listing
+                .notes
+                .iter()
+                .map(|note| (note.note.file_path.clone(), note.clone()))
+                .collect(),
+            folders: listing.folders.clone(),
+            placeholders: listing.placeholders.clone(),
+            stale: HashSet::new(),
+        });
+    }
+}
+
+/// Forget the index; the next listing walks the vault again
+pub(crate) fn invalidate(state: &AppState) {
+    if let Ok(mut index) = state.note_index.lock() {
+        *index = None;
+    }
+    forget_boards(state);
+}
+
+/// Folders with a board of their own below each board root, looked up once
+/// instead of walking the folder tree on every board command
+pub type NestedBoards = HashMap<PathBuf, Vec<PathBuf>>;
+
+/// Scope of the board at `root`, with its nested boards from the index
+pub(crate) fn board_scope(state: &AppState, root: PathBuf) -> BoardScope {
+    let Ok(mut boards) = state.nested_boards.lock() else {
+        return BoardScope::new(root);
+    };
+    let nested = boards
+        .entry(root.clone())
+        .or_insert_with(|| nested_boards(&root))
+        .clone();
+    BoardScope::with_nested(root, nested)
+}
+
+/// Look for nested boards again, after a board file or folder changed
+pub(crate) fn forget_boards(state: &AppState) {
+    if let Ok(mut boards) = state.nested_boards.lock() {
+        boards.clear();
+    }
+}
+
+/// Re-read `file_path` before the next listing is served
+pub(crate) fn mark_stale(state: &State<AppState>, file_path: &str) {
+    if let Ok(mut index) = state.note_index.lock() {
+        if let Some(index) = index.as_mut() {
+            index.stale.insert(file_path.to_string());
+        }
+    }
+}
+
+/// Apply notes re-read by the watcher
+pub(crate) fn apply_changes(state: &State<AppState>, updated: &[NoteWithTags], removed: &[String]) {
+    if let Ok(mut index) = state.note_index.lock() {
+        if let Some(index) = index.as_mut() {
+            for path in removed {
+                index.notes.remove(path);
+            }
+            for note in updated {
+                index
+                    .notes
+                    .insert(note.note.file_path.clone(), note.clone());
+            }
+        }
+    }
+}
+
+enum Reloaded {
+    Note(Box<NoteWithTags>),
+    Gone,
+    /// A folder changed under the index
+    Invalid,
+}
+
+/// Current state of a path the app wrote, served from the cache when it is
+/// up to date
+fn reload(file_path: &str, cache: Option<&CacheDb>) -> Reloaded {
+    let path = PathBuf::from(file_path);
+    if path.is_dir() {
+        return Reloaded::Invalid;
+    }
+    if !path.is_file()
+        || !path.extension().is_some_and(|ext| ext == "md")
+        || is_in_trash(&path)
+        || cloud::placeholder_note_path(&path).is_some()
+    {
+        return Reloaded::Gone;
+    }
+
+    if let (Some(c), Ok(mtime)) = (cache, get_file_mtime(&path)) {
+        if !c.needs_update(file_path, mtime) {
+            if let Ok(Some(cached)) = c.get_note(file_path) {
+                return Reloaded::Note(Box::new(NoteWithTags {
+                    note: cached.note,
+                    inline_tags: cached.inline_tags,
+                    days_in_column: None,
+                    excerpt: Some(cached.excerpt),
+                }));
+            }
+        }
+    }
+
+    match parse_note(&path) {
+        Ok(note) => {
+            let inline_tags = extract_inline_tags(&note.content);
+            let excerpt = make_excerpt(&note.content);
+            Reloaded::Note(Box::new(NoteWithTags {
+                note,
+                inline_tags,
+                days_in_column: None,
+                excerpt: Some(excerpt),
+            }))
+        }
+        Err(_) => Reloaded::Gone,
+    }
+}
+
+/// Listing of `notes_dir` from the index, or `None` when the vault has to be
+/// walked. Notes come back unsorted and without column aging.
+pub(crate) fn listing(notes_dir: &str, state: &State<AppState>) -> Option<NotesWithTagsAndFolders> {
+    // Safe mode ignores the watcher, so the index could not be kept current
+    if state.safe_mode {
+        return None;
+    }
+    let stale = {
+        let mut index = state.note_index.lock().ok()?;
+        let index = index
+            .as_mut()
+            .filter(|index| index.notes_dir == notes_dir)?;
+        std::mem::take(&mut index.stale)
+    };
+
+    // Read stale notes without holding the index lock
+    let mut reloaded = Vec::with_capacity(stale.len());
+    if !stale.is_empty() {
+        let cache_lock = state.cache.lock().ok()?;
+        for path in stale {
+            match reload(&path, cache_lock.as_ref()) {
+                Reloaded::Invalid => {
+                    drop(cache_lock);
+                    invalidate(state);
+                    return None;
+                }
+                result => reloaded.push((path, result)),
+            }
+        }
+    }
+
+    let mut index = state.note_index.lock().ok()?;
+    let index = index
+        .as_mut()
+        .filter(|index| index.notes_dir == notes_dir)?;
+    for (path, result) in reloaded {
+        match result {
+            Reloaded::Note(note) => {
+                index.notes.insert(path, *note);
+            }
+            _ => {
+                index.notes.remove(&path);
+            }
+        }
+    }
+
+    Some(NotesWithTagsAndFolders {
+        notes: index.notes.values().cloned().collect(),
+        folders: index.folders.clone(),
+        placeholders: index.placeholders.clone(),
+        next_cursor: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::board::create_board;
+    use crate::commands::notes::{apply_file_changes, scan_vault, FileChangeEvent};
+    use crate::test_support::TestVault;
+    use crate::vault_config::{save_config, BoardConfig, BOARD_FILE};
+
+    #[test]
+    fn caches_nested_boards_until_a_board_changes() {
+        let vault = TestVault::new();
+        let state = vault.state();
+        let card = |folder: &str| vault.dir.join(folder).join("card.md");
+        vault.note("acme/card.md", "acme", "");
+        vault.note("side/card.md", "side", "");
+        create_board(vault.notes_dir(), "acme".to_string(), vault.state()).unwrap();
+
+        let scope = board_scope(&state, vault.dir.clone());
+        assert!(!scope.contains(&card("acme")));
+        assert!(scope.contains(&card("side")));
+
+        // Written behind the app's back: the cached scope doesn't see it...
+        save_config(&vault.dir.join("side"), BOARD_FILE, &BoardConfig::default()).unwrap();
+        assert!(board_scope(&state, vault.dir.clone()).contains(&card("side")));
+
+        // ...until the watcher reports the new board file
+        let board_file = vault.path("side/.noteban/board.json");
+        let changes = vec![FileChangeEvent {
+            event_type: "create".to_string(),
+            file_path: board_file,
+        }];
+        apply_file_changes(&vault.notes_dir(), changes, &state).unwrap();
+        assert!(!board_scope(&state, vault.dir.clone()).contains(&card("side")));
+    }
+
+    fn titles(vault: &TestVault) -> Option<Vec<String>> {
+        let listing = listing(&vault.notes_dir(), &vault.state())?;
+        let mut titles: Vec<String> = listing
+            .notes
+            .into_iter()
+            .map(|n| n.note.frontmatter.title)
+            .collect();
+        titles.sort();
+        Some(titles)
+    }
+
+    #[test]
+    fn rereads_stale_notes_and_drops_on_invalidate_or_replace() {
+        let vault = TestVault::new();
+        let state = vault.state();
+        let a = vault.note("a.md", "a", "");
+        let b = vault.note("b.md", "b", "");
+        scan_vault(&vault.notes_dir(), &state, &mut |_| {}).unwrap();
+        assert_eq!(titles(&vault).unwrap(), ["a", "b"]);
+
+        // Edits are only picked up once the path is marked stale
+        vault.write(
+            "a.md",
+            "---\nid: a\ntitle: renamed a\ncolumn: todo\n---\n\nBody\n",
+        );
+        std::fs::remove_file(&b).unwrap();
+        assert_eq!(titles(&vault).unwrap(), ["a", "b"]);
+        mark_stale(&state, &a);
+        mark_stale(&state, &b);
+        assert_eq!(titles(&vault).unwrap(), ["renamed a"]);
+
+        // A folder under a stale path can't be patched note by note
+        std::fs::create_dir(vault.dir.join("b.md")).unwrap();
+        mark_stale(&state, &b);
+        assert!(titles(&vault).is_none());
+
+        scan_vault(&vault.notes_dir(), &state, &mut |_| {}).unwrap();
+        assert!(titles(&vault).is_some());
+        invalidate(&state);
+        assert!(titles(&vault).is_none());
+
+        // An index of another vault is not served for this one
+        let other = TestVault::new();
+        let empty = NotesWithTagsAndFolders {
+            notes: Vec::new(),
+            folders: Vec::new(),
+            placeholders: Vec::new(),
+            next_cursor: None,
+        };
+        replace(&state, &other.notes_dir(), &empty);
+        assert!(titles(&vault).is_none());
+        replace(&state, &vault.notes_dir(), &empty);
+        assert_eq!(titles(&vault).unwrap(), Vec::<String>::new());
+    }
+}
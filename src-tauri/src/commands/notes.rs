@@ -1,32 +1,226 @@
+use crate::cache::folders::CachedFolder;
+use crate::cache::idempotency::IdempotencyClaim;
+use crate::cache::queries::NoteWrite;
+use crate::cache::storage::StorageFileRecord;
 use crate::cache::CacheDb;
+use crate::commands::audit;
+use crate::commands::board;
+use crate::commands::broadcast;
+use crate::commands::cloud::{self, CloudPlaceholder};
+use crate::commands::conflicts;
+use crate::commands::encryption;
+use crate::commands::history::{active_profile_id, history_access};
+use crate::commands::mounts::{
+    ensure_not_mount_folder, ensure_writable, find_mount_for, mount_relative_path, readonly_mounts,
+    MOUNTS_DIR_NAME,
+};
+use crate::commands::note_index;
+use crate::commands::profile_lock;
+use crate::commands::recovery::{self, StartupRecovery};
+use crate::commands::storage::attachment_owner;
+use crate::commands::symlinks::symlink_allowlist;
+use crate::commands::sync_schedule;
+use crate::commands::trash::{self, TRASH_DIR_NAME};
+use crate::commands::undo::{clear_undo, push_undo, UndoStep};
+use crate::commands::watch;
+use crate::history;
+use crate::journal::{self, ContentRewrite, JournalEntry, JournalRename, OperationJournal};
 use crate::lock_or_err;
-use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::logging;
+use crate::sync_meta::SYNC_META_DIR;
+use crate::utils::{compute_content_hash, extract_inline_tags, make_excerpt};
+use crate::vault_config::{
+    self, board_root, board_root_of, config_kind, ConfigKind, VAULT_CONFIG_DIR,
+};
 use crate::AppState;
 use atomicwrites::{AtomicFile, OverwriteBehavior};
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::{Component, Path, PathBuf};
 use std::time::{Duration, Instant, UNIX_EPOCH};
-use tauri::State;
+use tauri::{AppHandle, State};
 use uuid::Uuid;
 use walkdir::WalkDir;
 
+/// Frontmatter flag protecting a note from edits, moves and deletion
+const LOCKED_KEY: &str = "locked";
+/// Cache meta key of the setting keeping titles and first headings in sync
+const TITLE_HEADING_SYNC_KEY: &str = "title_heading_sync";
+/// Longest icon accepted, in chars; emoji joined with ZWJ take several
+const MAX_ICON_CHARS: usize = 16;
+/// Named label colors; any `#rgb` or `#rrggbb` value is accepted too
+const LABEL_COLORS: [&str; 8] = [
+    "red", "orange", "yellow", "green", "blue", "purple", "pink", "gray",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteFrontmatter {
     pub id: String,
+    /// Falls back to the first heading or the file name when missing
+    #[serde(default)]
     pub title: String,
+    /// Falls back to the file's creation time when missing or bogus
+    #[serde(default = "unknown_time", deserialize_with = "lenient_time")]
     pub created: DateTime<Utc>,
     pub modified: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub date: Option<String>,
+    /// Deadline as `YYYY-MM-DD` or an RFC 3339 time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
     pub column: String,
     #[serde(default)]
     pub tags: Vec<String>,
     #[serde(default)]
     pub order: i32,
+    /// Pinned notes list before the others in their column
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Archived notes are left out of listings and search unless asked for
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    #[serde(
+        default,
+        deserialize_with = "lenient_priority",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub priority: Option<Priority>,
+    /// Card label, one of `LABEL_COLORS` or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Cover image, relative to the note's folder like image links in the body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
+    /// Emoji or short symbol shown next to the title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Other names the note can be linked and found by
+    #[serde(
+        default,
+        deserialize_with = "string_or_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub aliases: Vec<String>,
+    /// Custom frontmatter properties, written back as-is and in their
+    /// original order when the note is saved
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+    /// Syntax the frontmatter was read in and is written back in
+    #[serde(skip)]
+    pub format: FrontmatterFormat,
+}
+
+/// Syntax of a note's frontmatter block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrontmatterFormat {
+    /// `---` delimited YAML
+    #[default]
+    Yaml,
+    /// `+++` delimited TOML, as used by Hugo and Zola
+    Toml,
+}
+
+/// Accept `key: value` as well as a list, as Obsidian does for aliases
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(value)) => vec![value],
+        Some(OneOrMany::Many(values)) => values,
+        None => Vec::new(),
+    })
+}
+
+/// How urgent a note is. The cache stores the rank so notes sort by it in SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low = 1,
+    Medium = 2,
+    High = 3,
+    Urgent = 4,
+}
+
+impl Priority {
+    pub fn rank(self) -> i64 {
+        self as i64
+    }
+
+    pub fn from_rank(rank: i64) -> Option<Self> {
+        match rank {
+            1 => Some(Self::Low),
+            2 => Some(Self::Medium),
+            3 => Some(Self::High),
+            4 => Some(Self::Urgent),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            "urgent" => Some(Self::Urgent),
+            rank => rank.parse().ok().and_then(Self::from_rank),
+        }
+    }
+}
+
+/// Accept a priority name or its rank (1 for low to 4 for urgent); any other
+/// value reads as no priority rather than making the note unreadable
+fn lenient_priority<'de, D>(deserializer: D) -> Result<Option<Priority>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let priority = match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+        Some(serde_yaml::Value::String(name)) => Priority::from_name(&name),
+        Some(serde_yaml::Value::Number(rank)) => rank.as_i64().and_then(Priority::from_rank),
+        _ => None,
+    };
+    Ok(priority)
+}
+
+/// Placeholder for a time that is missing or unreadable
+fn unknown_time() -> DateTime<Utc> {
+    DateTime::UNIX_EPOCH
+}
+
+/// Accept an RFC 3339 time or a `YYYY-MM-DD` date; anything else reads as
+/// unknown rather than making the note unreadable
+fn lenient_time<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let time = match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+        Some(serde_yaml::Value::String(value)) => DateTime::parse_from_rfc3339(value.trim())
+            .map(|time| time.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|time| time.and_utc())
+            }),
+        _ => None,
+    };
+    Ok(time.unwrap_or_else(unknown_time))
+}
+
+/// Whether a frontmatter time can't be right: unknown, before 1970 or more
+/// than a day ahead of now
+fn is_bogus_time(time: DateTime<Utc>) -> bool {
+    time.timestamp() <= 0 || time > Utc::now() + ChronoDuration::days(1)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,8 +250,12 @@ pub struct CreateNoteInput {
     pub title: String,
     pub content: Option<String>,
     pub date: Option<String>,
+    #[serde(default)]
+    pub due: Option<String>,
     pub column: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Client-generated key so retried captures return the original note
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -67,21 +265,57 @@ pub struct UpdateNoteInput {
     pub title: Option<String>,
     pub content: Option<String>,
     pub date: Option<String>,
+    /// New due date; an empty string removes it
+    #[serde(default)]
+    pub due: Option<String>,
     pub column: Option<String>,
     pub tags: Option<Vec<String>>,
     pub order: Option<i32>,
+    #[serde(default)]
+    pub aliases: Option<Vec<String>>,
+    /// `modified` of the version the editor loaded; a different value on disk
+    /// means someone else saved in between
+    #[serde(default)]
+    pub base_modified: Option<DateTime<Utc>>,
+    /// `content_hash` of the version the editor loaded, checked like
+    /// `base_modified`
+    #[serde(default)]
+    pub base_hash: Option<String>,
+    /// Modify the note even if it is locked
+    #[serde(default)]
+    pub force: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteWithTags {
     pub note: Note,
     pub inline_tags: Vec<String>,
+    /// Whole days since the note entered its current column (listings only)
+    #[serde(default)]
+    pub days_in_column: Option<i64>,
+    /// Plaintext preview of the body (listings only)
+    #[serde(default)]
+    pub excerpt: Option<String>,
+}
+
+/// Outcome of a command applied to several notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkUpdateResult {
+    pub updated: Vec<NoteWithTags>,
+    /// One message per note that was left unchanged
+    pub errors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotesWithTagsAndFolders {
     pub notes: Vec<NoteWithTags>,
     pub folders: Vec<Folder>,
+    /// Notes a cloud provider has not downloaded yet
+    #[serde(default)]
+    pub placeholders: Vec<CloudPlaceholder>,
+    /// Pass back as `cursor` to fetch the next page; `None` on the last page
+    #[serde(default)]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,14 +324,80 @@ pub struct FileChangeEvent {
     pub file_path: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IncrementalUpdateResult {
     pub updated_notes: Vec<NoteWithTags>,
     pub removed_paths: Vec<String>,
+    /// Renames made outside the app; the old path is also in `removed_paths`
+    /// and the note at its new path in `updated_notes`
+    #[serde(default)]
+    pub moved_notes: Vec<MovedNote>,
+    /// Files added, replaced or removed in `.attachments` folders
+    #[serde(default)]
+    pub attachment_changes: Vec<AttachmentChange>,
+    /// Files changed in the vault configuration folder
+    #[serde(default)]
+    pub config_changes: Vec<ConfigChange>,
+    /// Folders created outside the app, with any folders inside them
+    #[serde(default)]
+    pub added_folders: Vec<Folder>,
+    /// Folders deleted or renamed away outside the app; folders below them
+    /// are gone too
+    #[serde(default)]
+    pub removed_folders: Vec<String>,
+    /// Set instead of the lists above when too many files changed at once;
+    /// a full scan with this id (see `start_vault_scan`) replaces the update
+    #[serde(default)]
+    pub rescan_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub kind: ConfigKind,
+    pub event_type: String,
+    pub file_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentChange {
+    pub event_type: String,
+    pub file_path: String,
+    /// Note the attachments folder belongs to; it may no longer exist
+    pub note_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovedNote {
+    pub from: String,
+    pub to: String,
+}
+
+/// A write the app made, used to tell its own watcher events apart
+#[derive(Debug, Clone)]
+pub struct RecentWrite {
+    at: Instant,
+    /// Hash of the written content, when the writer knows it
+    content_hash: Option<String>,
+}
+
+/// Writes without a known hash suppress events for this long
+const RECENT_WRITE_WINDOW: Duration = Duration::from_secs(2);
+/// Written hashes are remembered long enough for slow watcher deliveries
+const WRITTEN_HASH_TTL: Duration = Duration::from_secs(60);
+
 /// Record a file write for self-save detection
-fn record_write(file_path: &str, state: &State<AppState>) {
+pub(crate) fn record_write(file_path: &str, state: &State<AppState>) {
+    remember_write(file_path, None, state);
+}
+
+/// Record a write of `content`; only events whose file on disk still hashes
+/// to it are treated as our own
+pub(crate) fn record_written_content(file_path: &str, content: &str, state: &State<AppState>) {
+    remember_write(file_path, Some(compute_content_hash(content)), state);
+}
+
+fn remember_write(file_path: &str, content_hash: Option<String>, state: &State<AppState>) {
+    note_index::mark_stale(state, file_path);
     let mut writes = match state.recent_writes.lock() {
         Ok(w) => w,
         Err(_) => {
@@ -110,38 +410,54 @@ fn record_write(file_path: &str, state: &State<AppState>) {
     if writes.len() >= 1000 {
         // Remove oldest entries
         let cutoff = Instant::now() - Duration::from_secs(5);
-        writes.retain(|_, time| *time > cutoff);
+        writes.retain(|_, write| write.at > cutoff);
 
         // If still over limit, clear oldest half
         if writes.len() >= 1000 {
             let mut entries: Vec<_> = writes.drain().collect();
-            entries.sort_by(|a, b| b.1.cmp(&a.1));
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.1.at));
             entries.truncate(500);
             writes.extend(entries);
         }
     }
 
-    writes.insert(file_path.to_string(), Instant::now());
-
-    // Cleanup old entries (older than 5 seconds)
-    writes.retain(|_, time| time.elapsed() < Duration::from_secs(5));
+    writes.insert(
+        file_path.to_string(),
+        RecentWrite {
+            at: Instant::now(),
+            content_hash,
+        },
+    );
+    sync_schedule::mark_local_edit();
+
+    // Cleanup old entries: 5 seconds, or longer while their hash is useful
+    writes.retain(|_, write| match write.content_hash {
+        Some(_) => write.at.elapsed() < WRITTEN_HASH_TTL,
+        None => write.at.elapsed() < Duration::from_secs(5),
+    });
 }
 
 /// Check if a file was recently written by us
 fn is_recent_write(file_path: &str, state: &State<AppState>) -> bool {
-    let writes = match state.recent_writes.lock() {
-        Ok(w) => w,
+    let write = match state.recent_writes.lock() {
+        Ok(writes) => writes.get(file_path).cloned(),
         Err(_) => return false, // Assume not recent if lock fails
     };
-    if let Some(write_time) = writes.get(file_path) {
-        write_time.elapsed() < Duration::from_secs(2)
-    } else {
-        false
+    match write {
+        // An edit made after ours changes the hash, however soon it lands
+        Some(RecentWrite {
+            content_hash: Some(hash),
+            ..
+        }) => fs::read_to_string(file_path)
+            .map(|content| compute_content_hash(&content) == hash)
+            .unwrap_or(false),
+        Some(write) => write.at.elapsed() < RECENT_WRITE_WINDOW,
+        None => false,
     }
 }
 
 /// Get file modification time as unix timestamp
-fn get_file_mtime(path: &PathBuf) -> Result<i64, String> {
+pub(crate) fn get_file_mtime(path: &PathBuf) -> Result<i64, String> {
     let metadata = fs::metadata(path).map_err(|e| format!("Failed to read metadata: {}", e))?;
     let mtime = metadata
         .modified()
@@ -153,13 +469,13 @@ fn get_file_mtime(path: &PathBuf) -> Result<i64, String> {
 }
 
 /// Atomically write content to a file using a temp file and rename
-fn atomic_write(path: &PathBuf, content: &str) -> Result<(), String> {
+pub(crate) fn atomic_write(path: &PathBuf, content: &str) -> Result<(), String> {
     let file = AtomicFile::new(path, OverwriteBehavior::AllowOverwrite);
     file.write(|f| f.write_all(content.as_bytes()))
         .map_err(|e| format!("Failed to write file atomically: {}", e))
 }
 
-fn ensure_safe_relative_path(path: &Path) -> Result<(), String> {
+pub(crate) fn ensure_safe_relative_path(path: &Path) -> Result<(), String> {
     for component in path.components() {
         match component {
             Component::Normal(_) | Component::CurDir => {}
@@ -170,7 +486,7 @@ fn ensure_safe_relative_path(path: &Path) -> Result<(), String> {
 }
 
 /// Validate that a path is within the base directory (prevents symlink attacks)
-fn validate_path_within_base(path: &Path, base: &Path) -> Result<PathBuf, String> {
+pub(crate) fn validate_path_within_base(path: &Path, base: &Path) -> Result<PathBuf, String> {
     let canonical_path = path
         .canonicalize()
         .map_err(|e| format!("Failed to resolve path: {}", e))?;
@@ -185,14 +501,17 @@ fn validate_path_within_base(path: &Path, base: &Path) -> Result<PathBuf, String
     Ok(canonical_path)
 }
 
-fn validate_existing_path_within_base(path: &Path, base: &Path) -> Result<PathBuf, String> {
+pub(crate) fn validate_existing_path_within_base(
+    path: &Path,
+    base: &Path,
+) -> Result<PathBuf, String> {
     if !path.exists() {
         return Err("Path does not exist".to_string());
     }
     validate_path_within_base(path, base)
 }
 
-fn validate_folder_name(folder_name: &str) -> Result<(), String> {
+pub(crate) fn validate_folder_name(folder_name: &str) -> Result<(), String> {
     if folder_name.trim().is_empty() {
         return Err("Folder name cannot be empty".to_string());
     }
@@ -205,6 +524,55 @@ fn validate_folder_name(folder_name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Directories inside the vault that never contain board notes
+pub(crate) fn is_skipped_dir_name(name: &str) -> bool {
+    name.ends_with(".attachments")
+        || name == TRASH_DIR_NAME
+        || name == SYNC_META_DIR
+        || name == VAULT_CONFIG_DIR
+        || name == MOUNTS_DIR_NAME
+}
+
+/// Check whether a path lies inside the vault trash
+pub(crate) fn is_in_trash(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str() == TRASH_DIR_NAME)
+}
+
+/// Whether the note has `locked: true` in its frontmatter
+pub(crate) fn is_locked(frontmatter: &NoteFrontmatter) -> bool {
+    frontmatter
+        .extra
+        .get(LOCKED_KEY)
+        .and_then(serde_yaml::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Refuse to change a locked note unless the caller forces it
+pub(crate) fn ensure_modifiable(frontmatter: &NoteFrontmatter, force: bool) -> Result<(), String> {
+    if is_locked(frontmatter) && !force {
+        return Err(format!(
+            "\"{}\" is locked; unlock it before changing it",
+            frontmatter.title
+        ));
+    }
+    Ok(())
+}
+
+/// Refuse to rename or delete a folder holding a locked note
+pub(crate) fn ensure_folder_modifiable(folder: &Path) -> Result<(), String> {
+    for entry in WalkDir::new(folder).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.extension().is_some_and(|ext| ext == "md") {
+            continue;
+        }
+        if let Ok(note) = parse_note(&path.to_path_buf()) {
+            ensure_modifiable(&note.frontmatter, false)?;
+        }
+    }
+    Ok(())
+}
+
 /// Sanitize a single tag to only allow safe characters
 fn sanitize_tag(tag: &str) -> String {
     tag.chars()
@@ -215,29 +583,339 @@ fn sanitize_tag(tag: &str) -> String {
 }
 
 /// Sanitize a list of tags
-fn sanitize_tags(tags: Vec<String>) -> Vec<String> {
+pub(crate) fn sanitize_tags(tags: Vec<String>) -> Vec<String> {
     tags.into_iter()
         .map(|t| sanitize_tag(&t))
         .filter(|t| !t.is_empty())
         .collect()
 }
 
-fn parse_note(file_path: &PathBuf) -> Result<Note, String> {
+/// Lowercased label color, or `None` for an empty one
+pub(crate) fn normalize_color(color: &str) -> Result<Option<String>, String> {
+    let color = color.trim().to_lowercase();
+    if color.is_empty() {
+        return Ok(None);
+    }
+    let is_hex = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if !is_hex && !LABEL_COLORS.contains(&color.as_str()) {
+        return Err(format!(
+            "Invalid color \"{}\": use one of {} or a hex color",
+            color,
+            LABEL_COLORS.join(", ")
+        ));
+    }
+    Ok(Some(color))
+}
+
+/// Trimmed icon, or `None` for an empty one. Icons are a single emoji or
+/// short symbol without whitespace or control characters.
+pub(crate) fn sanitize_icon(icon: &str) -> Result<Option<String>, String> {
+    let icon = icon.trim();
+    if icon.is_empty() {
+        return Ok(None);
+    }
+    if icon.chars().count() > MAX_ICON_CHARS
+        || icon.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err("Icon must be a single emoji or short symbol".to_string());
+    }
+    Ok(Some(icon.to_string()))
+}
+
+/// Trimmed aliases without blanks or case-insensitive duplicates
+pub(crate) fn clean_aliases(aliases: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    aliases
+        .into_iter()
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty() && seen.insert(alias.to_lowercase()))
+        .collect()
+}
+
+/// Whether any cached note links to `title` with a wikilink
+fn links_to_title(title: &str, state: &State<AppState>) -> bool {
+    let Ok(cache_lock) = state.cache.lock() else {
+        return false;
+    };
+    cache_lock
+        .as_ref()
+        .and_then(|cache| cache.has_wikilinks_to(title).ok())
+        .unwrap_or(false)
+}
+
+pub(crate) fn parse_note(file_path: &PathBuf) -> Result<Note, String> {
     let content =
         fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    parse_note_content(&content, file_path)
+}
+
+/// Split a leading block fenced by `delimiter` lines from markdown text
+fn split_fenced<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let rest = text.strip_prefix(delimiter)?;
+    let rest = rest
+        .strip_prefix('\n')
+        .or_else(|| rest.strip_prefix("\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Split a leading `---` YAML block from markdown text
+pub(crate) fn split_frontmatter(text: &str) -> (Option<&str>, &str) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    match split_fenced(text, "---") {
+        Some((frontmatter, body)) => (Some(frontmatter), body),
+        None => (None, text),
+    }
+}
+
+fn toml_to_yaml(value: toml::Value) -> serde_yaml::Value {
+    match value {
+        toml::Value::String(value) => value.into(),
+        toml::Value::Integer(value) => value.into(),
+        toml::Value::Float(value) => value.into(),
+        toml::Value::Boolean(value) => value.into(),
+        toml::Value::Datetime(value) => value.to_string().into(),
+        toml::Value::Array(items) => {
+            serde_yaml::Value::Sequence(items.into_iter().map(toml_to_yaml).collect())
+        }
+        toml::Value::Table(table) => serde_yaml::Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| (key.into(), toml_to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// TOML has no null, so null values are left out
+fn yaml_to_toml(value: serde_yaml::Value) -> Option<toml::Value> {
+    let value = match value {
+        serde_yaml::Value::Null => return None,
+        serde_yaml::Value::Bool(value) => toml::Value::Boolean(value),
+        serde_yaml::Value::Number(number) => match number.as_i64() {
+            Some(value) => toml::Value::Integer(value),
+            None => toml::Value::Float(number.as_f64()?),
+        },
+        serde_yaml::Value::String(value) => toml::Value::String(value),
+        serde_yaml::Value::Sequence(items) => {
+            toml::Value::Array(items.into_iter().filter_map(yaml_to_toml).collect())
+        }
+        serde_yaml::Value::Mapping(mapping) => toml::Value::Table(
+            mapping
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    let key = match key {
+                        serde_yaml::Value::String(key) => key,
+                        key => serde_yaml::to_string(&key).ok()?.trim().to_string(),
+                    };
+                    Some((key, yaml_to_toml(value)?))
+                })
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_toml(tagged.value)?,
+    };
+    Some(value)
+}
+
+/// Parse `+++` TOML frontmatter. Hugo and Zola pages carry none of the keys
+/// noteban needs, so missing ones are filled in as for a plain markdown file.
+fn toml_frontmatter(text: &str, body: &str, file_path: &Path) -> Result<NoteFrontmatter, String> {
+    let table: toml::Table =
+        toml::from_str(text).map_err(|e| format!("Failed to parse frontmatter: {}", e))?;
+    let mapping: serde_yaml::Mapping = table
+        .into_iter()
+        .map(|(key, value)| (key.into(), toml_to_yaml(value)))
+        .collect();
+    let mut frontmatter = frontmatter_with_defaults(mapping, body, file_path)?;
+    frontmatter.format = FrontmatterFormat::Toml;
+    Ok(frontmatter)
+}
+
+/// Parse `---` YAML frontmatter. Obsidian and other editors often write only
+/// tags or aliases, so missing keys are filled in as for TOML.
+fn yaml_frontmatter(text: &str, body: &str, file_path: &Path) -> Result<NoteFrontmatter, String> {
+    let mapping = match serde_yaml::from_str(text.trim()) {
+        Ok(serde_yaml::Value::Mapping(mapping)) => mapping,
+        Ok(serde_yaml::Value::Null) => serde_yaml::Mapping::new(),
+        Ok(_) => return Err("Failed to parse frontmatter: expected key-value pairs".to_string()),
+        Err(e) => return Err(format!("Failed to parse frontmatter: {}", e)),
+    };
+    frontmatter_with_defaults(mapping, body, file_path)
+}
 
-    // Split frontmatter from content
-    let parts: Vec<&str> = content.splitn(3, "---").collect();
+/// Frontmatter keys without a serde default
+const REQUIRED_KEYS: [&str; 3] = ["id", "modified", "column"];
+
+/// Read frontmatter properties, taking keys the file lacks from the metadata
+/// a plain markdown file at `file_path` would get
+fn frontmatter_with_defaults(
+    mut mapping: serde_yaml::Mapping,
+    body: &str,
+    file_path: &Path,
+) -> Result<NoteFrontmatter, String> {
+    let incomplete = REQUIRED_KEYS.iter().any(|key| !mapping.contains_key(*key));
+    if incomplete {
+        if let Ok(serde_yaml::Value::Mapping(defaults)) =
+            serde_yaml::to_value(synthesized_frontmatter(body, file_path))
+        {
+            for (key, value) in defaults {
+                if !mapping.contains_key(&key) {
+                    mapping.insert(key, value);
+                }
+            }
+        }
+    }
+    serde_yaml::from_value(mapping.into())
+        .map_err(|e| format!("Failed to parse frontmatter: {}", e))
+}
+
+/// Frontmatter keys holding times, written as TOML datetimes
+const TIME_KEYS: [&str; 4] = ["created", "modified", "date", "due"];
+
+fn toml_string(frontmatter: &NoteFrontmatter) -> Result<String, String> {
+    let value = serde_yaml::to_value(frontmatter)
+        .map_err(|e| format!("Failed to serialize frontmatter: {}", e))?;
+    let Some(toml::Value::Table(mut table)) = yaml_to_toml(value) else {
+        return Err("Failed to serialize frontmatter".to_string());
+    };
+    for key in TIME_KEYS {
+        let time = match table.get(key) {
+            Some(toml::Value::String(value)) => value.parse::<toml::value::Datetime>().ok(),
+            _ => None,
+        };
+        if let Some(time) = time {
+            table.insert(key.to_string(), toml::Value::Datetime(time));
+        }
+    }
+    toml::to_string(&table).map_err(|e| format!("Failed to serialize frontmatter: {}", e))
+}
+
+/// Creation (birth) and modification time of a file. Creation falls back to
+/// modification where the platform doesn't record it, and both to the
+/// current time when the file can't be read
+pub(crate) fn file_times(path: &Path) -> (DateTime<Utc>, DateTime<Utc>) {
+    let metadata = fs::metadata(path).ok();
+    let modified = metadata
+        .as_ref()
+        .and_then(|m| m.modified().ok())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(Utc::now);
+    let created = metadata
+        .as_ref()
+        .and_then(|m| m.created().ok())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or(modified);
+    (created.min(modified), modified)
+}
+
+/// Text of a `# Heading` line
+fn heading_text(line: &str) -> Option<&str> {
+    let heading = line.strip_prefix('#')?;
+    if !heading.starts_with([' ', '\t']) {
+        return None;
+    }
+    let heading = heading.trim().trim_end_matches('#').trim_end();
+    (!heading.is_empty()).then_some(heading)
+}
+
+/// Text of the first `# Heading` line of a markdown body
+pub(crate) fn first_heading(body: &str) -> Option<&str> {
+    body.lines().find_map(heading_text)
+}
+
+/// `body` with its first `# Heading` line replaced by one for `title`, or
+/// `None` when it has no such heading
+fn replace_first_heading(body: &str, title: &str) -> Option<String> {
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        if heading_text(text).is_some() {
+            let rest = &body[offset + text.len()..];
+            return Some(format!("{}# {}{}", &body[..offset], title.trim(), rest));
+        }
+        offset += line.len();
+    }
+    None
+}
 
-    if parts.len() < 3 {
-        return Err("Invalid note format: missing frontmatter".to_string());
+/// Title for a note without one: its first heading, else its file name
+fn fallback_title(body: &str, file_path: &Path) -> String {
+    first_heading(body)
+        .map(str::to_string)
+        .or_else(|| {
+            file_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Metadata for a plain markdown file without frontmatter: an id derived
+/// from its path, its first heading or file name as title and its file times
+fn synthesized_frontmatter(body: &str, file_path: &Path) -> NoteFrontmatter {
+    let hash = compute_content_hash(&file_path.to_string_lossy());
+    let title = fallback_title(body, file_path);
+    let (created, modified) = file_times(file_path);
+    NoteFrontmatter {
+        id: hash[..32].to_string(),
+        title,
+        created,
+        modified,
+        date: None,
+        due: None,
+        column: vault_config::nearest_default_column(file_path),
+        tags: Vec::new(),
+        order: 0,
+        pinned: false,
+        archived: false,
+        priority: None,
+        color: None,
+        cover: None,
+        icon: None,
+        aliases: Vec::new(),
+        extra: serde_yaml::Mapping::new(),
+        format: FrontmatterFormat::Yaml,
     }
+}
 
-    let frontmatter_str = parts[1].trim();
-    let note_content = parts[2].trim().to_string();
+/// Parse raw note text as if it had been read from `file_path`. Text without
+/// frontmatter gets synthesized metadata, which is only written to the file
+/// when the note is first saved.
+pub(crate) fn parse_note_content(content: &str, file_path: &Path) -> Result<Note, String> {
+    let text = content.trim_start();
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let (frontmatter, body) = match split_fenced(text, "+++") {
+        Some((toml_str, body)) => (
+            Some(toml_frontmatter(toml_str, body.trim(), file_path)?),
+            body,
+        ),
+        None => {
+            let (frontmatter_str, body) = split_frontmatter(text);
+            let frontmatter = frontmatter_str
+                .map(|frontmatter_str| yaml_frontmatter(frontmatter_str, body.trim(), file_path))
+                .transpose()?;
+            (frontmatter, body)
+        }
+    };
+    let note_content = body.trim().to_string();
 
-    let frontmatter: NoteFrontmatter = serde_yaml::from_str(frontmatter_str)
-        .map_err(|e| format!("Failed to parse frontmatter: {}", e))?;
+    let mut frontmatter =
+        frontmatter.unwrap_or_else(|| synthesized_frontmatter(&note_content, file_path));
+    if frontmatter.title.trim().is_empty() {
+        frontmatter.title = fallback_title(&note_content, file_path);
+    }
+    if is_bogus_time(frontmatter.created) {
+        let (created, _) = file_times(file_path);
+        frontmatter.created = created.min(frontmatter.modified);
+    }
 
     Ok(Note {
         frontmatter,
@@ -246,7 +924,13 @@ fn parse_note(file_path: &PathBuf) -> Result<Note, String> {
     })
 }
 
-fn serialize_note(frontmatter: &NoteFrontmatter, content: &str) -> String {
+pub(crate) fn serialize_note(frontmatter: &NoteFrontmatter, content: &str) -> String {
+    if frontmatter.format == FrontmatterFormat::Toml {
+        match toml_string(frontmatter) {
+            Ok(frontmatter_str) => return format!("+++\n{}+++\n\n{}", frontmatter_str, content),
+            Err(e) => log::warn!("Writing YAML frontmatter instead of TOML: {}", e),
+        }
+    }
     let frontmatter_str = serde_yaml::to_string(frontmatter).unwrap_or_default();
 
     format!("---\n{}---\n\n{}", frontmatter_str, content)
@@ -264,7 +948,7 @@ fn slugify(title: &str) -> String {
         .join("-")
 }
 
-fn slugify_or_fallback(title: &str, fallback_id: &str) -> String {
+pub(crate) fn slugify_or_fallback(title: &str, fallback_id: &str) -> String {
     let slug = slugify(title);
     if slug.is_empty() {
         format!(
@@ -276,9 +960,59 @@ fn slugify_or_fallback(title: &str, fallback_id: &str) -> String {
     }
 }
 
+/// Decides which walked entries belong to the vault. Symlinks are followed
+/// only when they resolve inside the vault or an allowlisted directory, so a
+/// link to e.g. `$HOME` can't pull unrelated files into the board.
+pub(crate) struct VaultWalkFilter {
+    roots: Vec<PathBuf>,
+}
+
+impl VaultWalkFilter {
+    pub(crate) fn new(root: &Path, allowed_symlink_targets: &[PathBuf]) -> Self {
+        let roots = std::iter::once(root)
+            .chain(allowed_symlink_targets.iter().map(PathBuf::as_path))
+            .filter_map(|path| path.canonicalize().ok())
+            .collect();
+        Self { roots }
+    }
+
+    pub(crate) fn allows(&self, entry: &walkdir::DirEntry) -> bool {
+        if !entry.path_is_symlink() {
+            return true;
+        }
+        let allowed = entry
+            .path()
+            .canonicalize()
+            .is_ok_and(|target| self.roots.iter().any(|root| target.starts_with(root)));
+        if !allowed {
+            log::debug!(
+                "Skipping symlink leaving the vault: {}",
+                logging::path(entry.path())
+            );
+        }
+        allowed
+    }
+}
+
 #[tauri::command]
-pub fn list_notes(notes_dir: String) -> Result<NotesWithFolders, String> {
-    let base_path = PathBuf::from(&notes_dir);
+pub fn list_notes(
+    notes_dir: String,
+    include_archived: Option<bool>,
+    state: State<AppState>,
+) -> Result<NotesWithFolders, String> {
+    let mut listing = read_vault(&notes_dir, &symlink_allowlist(&state))?;
+    if !include_archived.unwrap_or(false) {
+        listing.notes.retain(|note| !note.frontmatter.archived);
+    }
+    Ok(listing)
+}
+
+/// Walk the vault for notes and folders without touching the cache
+pub(crate) fn read_vault(
+    notes_dir: &str,
+    allowed_symlink_targets: &[PathBuf],
+) -> Result<NotesWithFolders, String> {
+    let base_path = PathBuf::from(notes_dir);
 
     if !base_path.exists() {
         fs::create_dir_all(&base_path)
@@ -291,16 +1025,19 @@ pub fn list_notes(notes_dir: String) -> Result<NotesWithFolders, String> {
 
     let mut notes = Vec::new();
     let mut folders = Vec::new();
+    let walk_filter = VaultWalkFilter::new(&base_path, allowed_symlink_targets);
 
     for entry in WalkDir::new(&base_path)
         .min_depth(1)
+        .follow_links(true)
         .into_iter()
         .filter_entry(|e| {
-            // Skip .attachments directories
+            // Skip .attachments and trash directories
             !e.file_name()
                 .to_str()
-                .map(|s| s.ends_with(".attachments"))
+                .map(is_skipped_dir_name)
                 .unwrap_or(false)
+                && walk_filter.allows(e)
         })
         .filter_map(|e| e.ok())
     {
@@ -318,16 +1055,21 @@ pub fn list_notes(notes_dir: String) -> Result<NotesWithFolders, String> {
                     .unwrap_or_default(),
                 relative_path: relative.to_string_lossy().to_string(),
             });
-        } else if path.extension().map_or(false, |ext| ext == "md") {
+        } else if path.extension().is_some_and(|ext| ext == "md") {
             match parse_note(&path.to_path_buf()) {
                 Ok(note) => notes.push(note),
-                Err(e) => log::warn!("Skipping invalid note {:?}: {}", path, e),
+                Err(e) => log::warn!("Skipping invalid note {}: {}", logging::path(&path), e),
             }
         }
     }
 
-    // Sort by modified date (newest first)
-    notes.sort_by(|a, b| b.frontmatter.modified.cmp(&a.frontmatter.modified));
+    // Pinned notes first, then by modified date (newest first)
+    notes.sort_by_key(|note| {
+        (
+            std::cmp::Reverse(note.frontmatter.pinned),
+            std::cmp::Reverse(note.frontmatter.modified),
+        )
+    });
     // Sort folders alphabetically by relative path
     folders.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
 
@@ -335,17 +1077,194 @@ pub fn list_notes(notes_dir: String) -> Result<NotesWithFolders, String> {
 }
 
 #[tauri::command]
-pub fn read_note(notes_dir: String, file_path: String) -> Result<Note, String> {
-    let base_path = PathBuf::from(&notes_dir);
+pub fn read_note(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<Note, String> {
     let path = PathBuf::from(&file_path);
-    validate_existing_path_within_base(&path, &base_path)?;
+    validate_readable_path(&path, Path::new(&notes_dir), &state)?;
     parse_note(&path)
 }
 
+/// Check that a note may be read: it lies in the vault, a read-only mount or
+/// an allowlisted symlink target
+fn validate_readable_path(
+    path: &Path,
+    base_path: &Path,
+    state: &State<AppState>,
+) -> Result<(), String> {
+    if let Err(e) = validate_existing_path_within_base(path, base_path) {
+        // Notes in read-only mounts live outside the vault but may still be read
+        let mounts = readonly_mounts(state);
+        match find_mount_for(path, &mounts) {
+            Some(mount) => {
+                validate_existing_path_within_base(path, Path::new(&mount.path))?;
+            }
+            // So may notes reached through a symlink into an allowlisted directory
+            None => {
+                let linked = path.starts_with(base_path)
+                    && symlink_allowlist(state)
+                        .iter()
+                        .any(|target| validate_path_within_base(path, target).is_ok());
+                if !linked {
+                    return Err(e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteContent {
+    pub content: String,
+    /// Hash of the whole file; pass back as `base_hash` when saving
+    pub content_hash: String,
+    /// Served from the cache without reading the file
+    pub cached: bool,
+}
+
+/// Body of a note for the editor. Served from the cache while the file's
+/// mtime still matches the indexed one, otherwise read and re-indexed.
+#[tauri::command]
+pub fn get_note_content(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<NoteContent, String> {
+    let path = PathBuf::from(&file_path);
+    validate_readable_path(&path, Path::new(&notes_dir), &state)?;
+    let mtime = get_file_mtime(&path)?;
+
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref();
+    if let Some((content, content_hash, cached_mtime)) = cache
+        .map(|cache| cache.get_note_content(&file_path))
+        .transpose()?
+        .flatten()
+    {
+        if cached_mtime == mtime {
+            state.cache_counters.record(1, 0);
+            return Ok(NoteContent {
+                content,
+                content_hash,
+                cached: true,
+            });
+        }
+    }
+
+    state.cache_counters.record(0, 1);
+    let file_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let note = parse_note_content(&file_content, &path)?;
+    let content_hash = compute_content_hash(&file_content);
+    if let Some(cache) = cache {
+        let inline_tags = extract_inline_tags(&note.content);
+        if let Err(e) = cache.upsert_note(&note, &content_hash, mtime, &inline_tags) {
+            log::warn!("Cache update failed for note: {}", e);
+        }
+    }
+    Ok(NoteContent {
+        content: note.content,
+        content_hash,
+        cached: false,
+    })
+}
+
+/// Claim an idempotency key for a note about to be created as `note_id`.
+/// Returns the note an earlier request with the key created, wherever it
+/// is now, or `None` when this request should create it.
+fn claim_idempotent_note(
+    key: &str,
+    note_id: &str,
+    state: &State<AppState>,
+) -> Result<Option<NoteWithTags>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let Some(cache) = cache_lock.as_ref() else {
+        return Ok(None);
+    };
+    // A cached path whose file is gone is dropped and the key claimed again
+    for _ in 0..2 {
+        let file_path = match cache.claim_idempotency_key(key, note_id)? {
+            IdempotencyClaim::Claimed => return Ok(None),
+            IdempotencyClaim::Pending => {
+                return Err("A note for this request is still being created".to_string())
+            }
+            IdempotencyClaim::Created(file_path) => file_path,
+        };
+        match parse_note(&PathBuf::from(&file_path)) {
+            Ok(note) => {
+                let inline_tags = extract_inline_tags(&note.content);
+                return Ok(Some(NoteWithTags {
+                    note,
+                    inline_tags,
+                    days_in_column: None,
+                    excerpt: None,
+                }));
+            }
+            Err(_) => cache.remove_note(&file_path)?,
+        }
+    }
+    Err("Failed to claim idempotency key".to_string())
+}
+
 #[tauri::command]
 pub fn create_note(input: CreateNoteInput, state: State<AppState>) -> Result<NoteWithTags, String> {
-    let now = Utc::now();
+    let target_dir = match &input.folder_path {
+        Some(folder) => PathBuf::from(&input.notes_dir).join(folder),
+        None => PathBuf::from(&input.notes_dir),
+    };
+    let notes_dir = input.notes_dir.clone();
+    let result = write_new_note(input, state.clone());
+    let path = match &result {
+        Ok(note) => note.note.file_path.clone(),
+        Err(_) => target_dir.to_string_lossy().to_string(),
+    };
+    audit::record(&state, "create", &path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(&notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+fn write_new_note(input: CreateNoteInput, state: State<AppState>) -> Result<NoteWithTags, String> {
     let id = Uuid::new_v4().to_string();
+    let idempotency_key = input
+        .idempotency_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string);
+    let Some(key) = idempotency_key else {
+        return write_note_file(input, id, state);
+    };
+    if let Some(existing) = claim_idempotent_note(&key, &id, &state)? {
+        return Ok(existing);
+    }
+
+    let result = write_note_file(input, id, state.clone());
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            // A failed attempt releases the key so a retry can create the note
+            let recorded = match &result {
+                Ok(note) => cache.complete_idempotency_key(&key, &note.note.file_path),
+                Err(_) => cache.forget_idempotency_key(&key),
+            };
+            if let Err(e) = recorded {
+                log::warn!("Failed to record idempotency key: {}", e);
+            }
+        }
+    }
+    result
+}
+
+fn write_note_file(
+    input: CreateNoteInput,
+    id: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let now = Utc::now();
 
     let tags = sanitize_tags(input.tags.clone().unwrap_or_default());
 
@@ -353,35 +1272,52 @@ pub fn create_note(input: CreateNoteInput, state: State<AppState>) -> Result<Not
     fs::create_dir_all(&base_path)
         .map_err(|e| format!("Failed to create notes directory: {}", e))?;
 
-    let frontmatter = NoteFrontmatter {
-        id: id.clone(),
-        title: input.title.clone(),
-        created: now,
-        modified: now,
-        date: input.date,
-        column: input.column.unwrap_or_else(|| "todo".to_string()),
-        tags,
-        order: 0,
-    };
-
-    let content = input.content.unwrap_or_default();
-    let file_content = serialize_note(&frontmatter, &content);
-
     // Determine target directory (root or subfolder)
     let target_dir = match &input.folder_path {
         Some(folder) => {
             let folder_path = PathBuf::from(folder);
             ensure_safe_relative_path(&folder_path)?;
+            ensure_not_mount_folder(&folder_path)?;
             base_path.join(folder_path)
         }
         None => base_path.clone(),
     };
 
     // Ensure directory exists
+    ensure_writable(&target_dir, &state)?;
     fs::create_dir_all(&target_dir)
         .map_err(|e| format!("Failed to create notes directory: {}", e))?;
     validate_path_within_base(&target_dir, &base_path)?;
 
+    let column = input
+        .column
+        .unwrap_or_else(|| vault_config::default_column(&base_path, &target_dir));
+    board::check_card_entering(&base_path, &target_dir, &column, &state)?;
+
+    let frontmatter = NoteFrontmatter {
+        id: id.clone(),
+        title: input.title.clone(),
+        created: now,
+        modified: now,
+        date: input.date,
+        due: input.due.filter(|due| !due.trim().is_empty()),
+        column,
+        tags,
+        order: 0,
+        pinned: false,
+        archived: false,
+        priority: None,
+        color: None,
+        cover: None,
+        icon: None,
+        aliases: Vec::new(),
+        extra: serde_yaml::Mapping::new(),
+        format: FrontmatterFormat::Yaml,
+    };
+
+    let content = input.content.unwrap_or_default();
+    let file_content = serialize_note(&frontmatter, &content);
+
     // Generate filename from title, handling duplicates
     let base_slug = slugify_or_fallback(&input.title, &id);
     let mut filename = format!("{}.md", base_slug);
@@ -398,7 +1334,7 @@ pub fn create_note(input: CreateNoteInput, state: State<AppState>) -> Result<Not
     let file_path_str = file_path.to_string_lossy().to_string();
 
     // Record write for self-save detection
-    record_write(&file_path_str, &state);
+    record_written_content(&file_path_str, &file_content, &state);
 
     atomic_write(&file_path, &file_content)?;
 
@@ -422,31 +1358,414 @@ pub fn create_note(input: CreateNoteInput, state: State<AppState>) -> Result<Not
         }
     }
 
-    Ok(NoteWithTags { note, inline_tags })
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+/// Snapshot the on-disk version of a note into the profile history before it is overwritten
+fn snapshot_previous_version(note_id: &str, previous_content: &str, state: &State<AppState>) {
+    let Some((profile_id, key)) = history_access(state) else {
+        return;
+    };
+    if let Err(e) = history::save_snapshot(&profile_id, note_id, previous_content, key.as_ref()) {
+        log::warn!("Failed to snapshot previous note version: {}", e);
+    }
+}
+
+/// True when the file no longer matches what was last indexed, i.e. another
+/// program wrote it and the watcher has not caught up yet
+fn changed_since_indexed(
+    file_path: &str,
+    path: &PathBuf,
+    content: &str,
+    state: &State<AppState>,
+) -> bool {
+    let Ok(cache_lock) = state.cache.lock() else {
+        return false;
+    };
+    let Some(Ok(Some((hash, mtime)))) = cache_lock
+        .as_ref()
+        .map(|cache| cache.get_indexed_state(file_path))
+    else {
+        return false;
+    };
+    get_file_mtime(path).is_ok_and(|current| current != mtime)
+        && compute_content_hash(content) != hash
+}
+
+/// Save an update that raced an external edit next to the note instead of
+/// over it. The returned note is the conflict copy; its `conflict`
+/// frontmatter points back at the original.
+fn save_conflict_copy(
+    original: &Path,
+    incoming: &Note,
+    state: &State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let file_content = serialize_note(&incoming.frontmatter, &incoming.content);
+    let conflict_path = conflicts::write_conflict_copy(
+        original,
+        file_content.as_bytes(),
+        conflicts::REASON_EXTERNAL_EDIT,
+        Some("editor"),
+    )?;
+    record_write(&conflict_path.to_string_lossy(), state);
+    log::warn!(
+        "Note {} changed on disk during edit; saved as {}",
+        logging::path(original),
+        logging::path(&conflict_path)
+    );
+
+    let note = parse_note(&conflict_path)?;
+    let inline_tags = extract_inline_tags(&note.content);
+
+    // Index both sides so the conflict shows up immediately
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            for path in [original, conflict_path.as_path()] {
+                let path = path.to_path_buf();
+                let (Ok(content), Ok(parsed)) = (fs::read_to_string(&path), parse_note(&path))
+                else {
+                    continue;
+                };
+                let hash = compute_content_hash(&content);
+                let mtime = get_file_mtime(&path).unwrap_or(0);
+                let tags = extract_inline_tags(&parsed.content);
+                if let Err(e) = cache.upsert_note(&parsed, &hash, mtime, &tags) {
+                    log::warn!("Cache update failed for conflicting note: {}", e);
+                }
+            }
+            let record = conflicts::conflict_record(
+                &conflict_path,
+                original,
+                conflicts::REASON_EXTERNAL_EDIT,
+                Some("editor"),
+            );
+            if let Err(e) = cache.upsert_conflict(&record) {
+                log::warn!("Failed to index conflict: {}", e);
+            }
+        }
+    }
+
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+/// Apply `edit` to a note's frontmatter and save it in place, keeping the
+/// cache and version history in step. Locked notes are refused.
+pub(crate) fn update_note_frontmatter(
+    notes_dir: &str,
+    file_path: &str,
+    state: &State<AppState>,
+    edit: impl FnOnce(&mut NoteFrontmatter) -> Result<(), String>,
+) -> Result<NoteWithTags, String> {
+    let path = PathBuf::from(file_path);
+    validate_existing_path_within_base(&path, Path::new(notes_dir))?;
+    ensure_writable(&path, state)?;
+
+    let previous_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut note = parse_note_content(&previous_content, &path)?;
+    ensure_modifiable(&note.frontmatter, false)?;
+    edit(&mut note.frontmatter)?;
+    note.frontmatter.modified = Utc::now();
+
+    let file_content = serialize_note(&note.frontmatter, &note.content);
+    snapshot_previous_version(&note.frontmatter.id, &previous_content, state);
+    record_written_content(file_path, &file_content, state);
+    atomic_write(&path, &file_content)?;
+
+    let inline_tags = extract_inline_tags(&note.content);
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            let hash = compute_content_hash(&file_content);
+            let mtime = get_file_mtime(&path).unwrap_or(0);
+            if let Err(e) = cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+                log::warn!("Cache update failed for note: {}", e);
+            }
+        }
+    }
+
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+/// A frontmatter change read and serialized but not yet written
+struct PendingEdit {
+    path: PathBuf,
+    previous_content: String,
+    file_content: String,
+    note: Note,
+}
+
+fn prepare_frontmatter_edit(
+    base: &Path,
+    path: PathBuf,
+    state: &State<AppState>,
+    edit: &impl Fn(&str, &mut NoteFrontmatter) -> Result<(), String>,
+) -> Result<PendingEdit, String> {
+    validate_existing_path_within_base(&path, base)?;
+    ensure_writable(&path, state)?;
+    let previous_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut note = parse_note_content(&previous_content, &path)?;
+    ensure_modifiable(&note.frontmatter, false)?;
+    edit(&note.file_path, &mut note.frontmatter)?;
+    note.frontmatter.modified = Utc::now();
+    let file_content = serialize_note(&note.frontmatter, &note.content);
+    Ok(PendingEdit {
+        path,
+        previous_content,
+        file_content,
+        note,
+    })
+}
+
+/// Apply `edit`, given each note's path, to the frontmatter of several notes
+/// as one operation.
+/// Nothing is written unless every note can be changed, notes already
+/// written are restored when a later write fails, and the cache is updated
+/// in a single transaction.
+pub(crate) fn update_notes_frontmatter(
+    notes_dir: &str,
+    file_paths: &[String],
+    state: &State<AppState>,
+    edit: impl Fn(&str, &mut NoteFrontmatter) -> Result<(), String>,
+) -> Result<Vec<NoteWithTags>, String> {
+    let base = Path::new(notes_dir);
+    let pending = file_paths
+        .iter()
+        .map(|file_path| {
+            prepare_frontmatter_edit(base, PathBuf::from(file_path), state, &edit)
+                .map_err(|e| format!("{}: {}", file_path, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (written, change) in pending.iter().enumerate() {
+        snapshot_previous_version(&change.note.frontmatter.id, &change.previous_content, state);
+        record_written_content(&change.note.file_path, &change.file_content, state);
+        if let Err(e) = atomic_write(&change.path, &change.file_content) {
+            for change in &pending[..written] {
+                record_written_content(&change.note.file_path, &change.previous_content, state);
+                if let Err(rollback_err) = atomic_write(&change.path, &change.previous_content) {
+                    log::error!(
+                        "Failed to restore {} after a failed batch update: {}",
+                        logging::path(&change.path),
+                        rollback_err
+                    );
+                }
+            }
+            return Err(format!("{}: {}", change.note.file_path, e));
+        }
+    }
+
+    let notes: Vec<NoteWithTags> = pending
+        .iter()
+        .map(|change| NoteWithTags {
+            note: change.note.clone(),
+            inline_tags: extract_inline_tags(&change.note.content),
+            days_in_column: None,
+            excerpt: None,
+        })
+        .collect();
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            let writes: Vec<NoteWrite<'_>> = pending
+                .iter()
+                .zip(&notes)
+                .map(|(change, note)| NoteWrite {
+                    note: &note.note,
+                    content_hash: compute_content_hash(&change.file_content),
+                    file_mtime: get_file_mtime(&change.path).unwrap_or(0),
+                    inline_tags: &note.inline_tags,
+                })
+                .collect();
+            if let Err(e) = cache.upsert_notes(&writes) {
+                log::warn!("Cache update failed for notes: {}", e);
+            }
+        }
+    }
+    Ok(notes)
+}
+
+fn journal_rename(from: &Path, to: &Path) -> JournalRename {
+    JournalRename {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+    }
+}
+
+/// Journal a multi-step rename so a crash halfway through is repaired at the
+/// next start. Journaling is best effort and never blocks the operation.
+fn begin_journal(
+    state: &State<AppState>,
+    operation: &str,
+    renames: Vec<JournalRename>,
+    rewrite: Option<ContentRewrite>,
+) -> Option<OperationJournal> {
+    let profile_id = active_profile_id(state)?;
+    let entry = JournalEntry::new(operation, renames, rewrite);
+    match journal::journal_dir(&profile_id).and_then(|dir| OperationJournal::begin(&dir, &entry)) {
+        Ok(journal) => Some(journal),
+        Err(e) => {
+            log::warn!("Operation journal unavailable: {}", e);
+            None
+        }
+    }
 }
 
 #[tauri::command]
 pub fn update_note(input: UpdateNoteInput, state: State<AppState>) -> Result<NoteWithTags, String> {
+    let file_path = input.file_path.clone();
+    let notes_dir = input.notes_dir.clone();
+    let result = write_note_update(input, state.clone());
+    let renamed_to = result
+        .as_ref()
+        .ok()
+        .map(|note| note.note.file_path.as_str())
+        .filter(|new_path| *new_path != file_path);
+    audit::record(&state, "update", &file_path, renamed_to, &result);
+    if let Ok(note) = &result {
+        let removed = renamed_to.map(|_| vec![file_path]).unwrap_or_default();
+        broadcast::broadcast(&notes_dir, vec![note.clone()], removed);
+    }
+    result
+}
+
+fn title_heading_sync(state: &State<AppState>) -> bool {
+    let Ok(cache_lock) = state.cache.lock() else {
+        return false;
+    };
+    cache_lock
+        .as_ref()
+        .and_then(|cache| cache.get_meta(TITLE_HEADING_SYNC_KEY).ok().flatten())
+        .is_some_and(|value| value == "true")
+}
+
+/// Carry a new title over to the first heading of the body, or a new first
+/// heading over to the title
+fn sync_title_and_heading(input: &mut UpdateNoteInput, note: &Note) {
+    let new_title = input
+        .title
+        .as_deref()
+        .filter(|title| !title.trim().is_empty() && *title != note.frontmatter.title);
+    if let Some(title) = new_title {
+        let body = input.content.as_deref().unwrap_or(&note.content);
+        if let Some(body) = replace_first_heading(body, title) {
+            input.content = Some(body);
+        }
+        return;
+    }
+    if let Some(content) = &input.content {
+        let heading = first_heading(content);
+        if heading != first_heading(&note.content) {
+            if let Some(heading) = heading.filter(|heading| *heading != note.frontmatter.title) {
+                input.title = Some(heading.to_string());
+            }
+        }
+    }
+}
+
+/// Whether saving a note keeps its title and first heading in sync
+#[tauri::command]
+pub fn get_title_heading_sync(state: State<AppState>) -> bool {
+    title_heading_sync(&state)
+}
+
+#[tauri::command]
+pub fn set_title_heading_sync(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(
+        TITLE_HEADING_SYNC_KEY,
+        if enabled { "true" } else { "false" },
+    )
+}
+
+fn write_note_update(
+    mut input: UpdateNoteInput,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
     let base_path = PathBuf::from(&input.notes_dir);
     let path = PathBuf::from(&input.file_path);
     validate_existing_path_within_base(&path, &base_path)?;
+    ensure_writable(&path, &state)?;
+    let previous_content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
     let mut note = parse_note(&path)?;
+    if encryption::is_encrypted(&note.frontmatter)
+        && (input.content.is_some() || input.tags.is_some())
+    {
+        return Err("Decrypt the note before editing it".to_string());
+    }
+    ensure_modifiable(&note.frontmatter, input.force)?;
+    let entered_column = input
+        .column
+        .as_deref()
+        .filter(|column| *column != note.frontmatter.column);
+    if let Some(column) = entered_column.filter(|_| !note.frontmatter.archived) {
+        let root = board_root_of(&base_path, &path);
+        board::check_wip_limit(&root, column, 1, &state)?;
+    }
+    if title_heading_sync(&state) {
+        sync_title_and_heading(&mut input, &note);
+    }
     let mut current_path = path.clone();
     let old_file_path = input.file_path.clone();
+    let mut journal = None;
+    // The editor's base is authoritative; the index only stands in for it
+    let changed_on_disk = if input.base_modified.is_none() && input.base_hash.is_none() {
+        changed_since_indexed(&input.file_path, &path, &previous_content, &state)
+    } else {
+        input
+            .base_modified
+            .is_some_and(|base| base != note.frontmatter.modified)
+            || input
+                .base_hash
+                .as_ref()
+                .is_some_and(|base| *base != compute_content_hash(&previous_content))
+    };
 
     // Check if title is changing and rename file if needed
     let title_changed = input
         .title
         .as_ref()
-        .map_or(false, |new_title| new_title != &note.frontmatter.title);
+        .is_some_and(|new_title| new_title != &note.frontmatter.title);
 
     // Update frontmatter fields
+    if let Some(aliases) = input.aliases {
+        note.frontmatter.aliases = clean_aliases(aliases);
+    }
     if let Some(title) = input.title {
-        note.frontmatter.title = title;
+        // Links to the old title keep resolving to the renamed note
+        if title_changed && links_to_title(&note.frontmatter.title, &state) {
+            let old_title = std::mem::replace(&mut note.frontmatter.title, title);
+            note.frontmatter.aliases = clean_aliases(
+                std::iter::once(old_title)
+                    .chain(std::mem::take(&mut note.frontmatter.aliases))
+                    .collect(),
+            );
+        } else {
+            note.frontmatter.title = title;
+        }
     }
     if let Some(date) = input.date {
         note.frontmatter.date = Some(date);
     }
+    if let Some(due) = input.due {
+        note.frontmatter.due = Some(due).filter(|due| !due.trim().is_empty());
+    }
     if let Some(column) = input.column {
         note.frontmatter.column = column;
     }
@@ -463,6 +1782,10 @@ pub fn update_note(input: UpdateNoteInput, state: State<AppState>) -> Result<Not
     // Update modified timestamp
     note.frontmatter.modified = Utc::now();
 
+    if changed_on_disk {
+        return save_conflict_copy(&path, &note, &state);
+    }
+
     // Rename file if title changed
     if title_changed {
         if let Some(parent) = path.parent() {
@@ -497,32 +1820,65 @@ pub fn update_note(input: UpdateNoteInput, state: State<AppState>) -> Result<Not
                 record_write(&path.to_string_lossy(), &state);
                 record_write(&new_path.to_string_lossy(), &state);
 
+                let has_attachments = old_attachments.exists() && old_attachments.is_dir();
+                if has_attachments && new_attachments.exists() {
+                    return Err("Attachments folder already exists".to_string());
+                }
+                let old_pattern = format!("{}.attachments/", old_stem);
+                let new_pattern = format!("{}.attachments/", new_stem);
+
+                let mut renames = Vec::new();
+                if has_attachments {
+                    renames.push(journal_rename(&old_attachments, &new_attachments));
+                }
+                renames.push(journal_rename(&path, &new_path));
+                journal = begin_journal(
+                    &state,
+                    "rename_note",
+                    renames,
+                    Some(ContentRewrite {
+                        file: new_path.to_string_lossy().to_string(),
+                        from: old_pattern.clone(),
+                        to: new_pattern.clone(),
+                    }),
+                );
+
                 // Rename attachments first (if any) to avoid partial state
-                if old_attachments.exists() && old_attachments.is_dir() {
-                    if new_attachments.exists() {
-                        return Err("Attachments folder already exists".to_string());
+                if has_attachments {
+                    if let Err(e) = fs::rename(&old_attachments, &new_attachments) {
+                        if let Some(journal) = journal {
+                            journal.finish();
+                        }
+                        return Err(format!("Failed to rename attachments folder: {}", e));
                     }
-                    fs::rename(&old_attachments, &new_attachments)
-                        .map_err(|e| format!("Failed to rename attachments folder: {}", e))?;
                     attachments_renamed = true;
                 }
 
                 if let Err(e) = fs::rename(&path, &new_path) {
+                    let mut rolled_back = true;
                     if attachments_renamed {
                         if let Err(rollback_err) = fs::rename(&new_attachments, &old_attachments) {
+                            rolled_back = false;
                             log::error!(
-                                "Failed to rollback attachments rename from {:?} to {:?}: {}. Manual cleanup may be required.",
-                                new_attachments, old_attachments, rollback_err
+                                "Failed to rollback attachments rename from {} to {}: {}. Manual cleanup may be required.",
+                                logging::path(&new_attachments), logging::path(&old_attachments), rollback_err
                             );
                         }
                     }
+                    // A failed rollback stays journaled for repair at the next start
+                    if let Some(journal) = journal.filter(|_| rolled_back) {
+                        journal.finish();
+                    }
                     return Err(format!("Failed to rename note: {}", e));
                 }
 
                 // Update attachment references in content to reflect new folder name
-                let old_pattern = format!("{}.attachments/", old_stem);
-                let new_pattern = format!("{}.attachments/", new_stem);
                 note.content = note.content.replace(&old_pattern, &new_pattern);
+                if let Some(cover) = note.frontmatter.cover.as_mut() {
+                    if let Some(rest) = cover.strip_prefix(&old_pattern) {
+                        *cover = format!("{}{}", new_pattern, rest);
+                    }
+                }
 
                 current_path = new_path;
 
@@ -541,10 +1897,17 @@ pub fn update_note(input: UpdateNoteInput, state: State<AppState>) -> Result<Not
     let file_content = serialize_note(&note.frontmatter, &note.content);
     let current_path_str = current_path.to_string_lossy().to_string();
 
+    if file_content != previous_content {
+        snapshot_previous_version(&note.frontmatter.id, &previous_content, &state);
+    }
+
     // Record write for self-save detection
-    record_write(&current_path_str, &state);
+    record_written_content(&current_path_str, &file_content, &state);
 
     atomic_write(&current_path, &file_content)?;
+    if let Some(journal) = journal {
+        journal.finish();
+    }
 
     note.file_path = current_path_str.clone();
 
@@ -562,50 +1925,268 @@ pub fn update_note(input: UpdateNoteInput, state: State<AppState>) -> Result<Not
         }
     }
 
-    Ok(NoteWithTags { note, inline_tags })
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+/// Set or clear a note's `locked` flag
+#[tauri::command]
+pub fn set_note_locked(
+    notes_dir: String,
+    file_path: String,
+    locked: bool,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = write_note_locked(&notes_dir, &file_path, locked, &state);
+    audit::record(&state, "update", &file_path, None, &result);
+    result
+}
+
+fn write_note_locked(
+    notes_dir: &str,
+    file_path: &str,
+    locked: bool,
+    state: &State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let path = PathBuf::from(file_path);
+    validate_existing_path_within_base(&path, &PathBuf::from(notes_dir))?;
+    ensure_writable(&path, state)?;
+    let mut note = parse_note(&path)?;
+    if locked {
+        note.frontmatter
+            .extra
+            .insert(LOCKED_KEY.into(), serde_yaml::Value::Bool(true));
+    } else {
+        note.frontmatter.extra.shift_remove(LOCKED_KEY);
+    }
+
+    let file_content = serialize_note(&note.frontmatter, &note.content);
+    record_written_content(file_path, &file_content, state);
+    atomic_write(&path, &file_content)?;
+
+    let inline_tags = extract_inline_tags(&note.content);
+    if let Some(cache) = lock_or_err(&state.cache)?.as_ref() {
+        let hash = compute_content_hash(&file_content);
+        let mtime = get_file_mtime(&path).unwrap_or(0);
+        if let Err(e) = cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+            log::warn!("Cache update failed for note: {}", e);
+        }
+    }
+
+    Ok(NoteWithTags {
+        note,
+        inline_tags,
+        days_in_column: None,
+        excerpt: None,
+    })
+}
+
+/// Set or clear (`None`) a note's priority
+#[tauri::command]
+pub fn set_note_priority(
+    notes_dir: String,
+    file_path: String,
+    priority: Option<Priority>,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        frontmatter.priority = priority;
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(&notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+/// Set or clear (`None` or empty) a note's label color
+#[tauri::command]
+pub fn set_note_color(
+    notes_dir: String,
+    file_path: String,
+    color: Option<String>,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let color = color.as_deref().map(normalize_color).transpose()?.flatten();
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        frontmatter.color = color;
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(&notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+/// Give several notes the same label color at once. Notes that can't be
+/// changed (e.g. locked ones) are reported instead of failing the batch.
+#[tauri::command]
+pub fn set_notes_color(
+    notes_dir: String,
+    file_paths: Vec<String>,
+    color: Option<String>,
+    state: State<AppState>,
+) -> Result<BulkUpdateResult, String> {
+    let color = color.as_deref().map(normalize_color).transpose()?.flatten();
+    let mut result = BulkUpdateResult::default();
+    for file_path in file_paths {
+        let updated = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+            frontmatter.color = color.clone();
+            Ok(())
+        });
+        audit::record(&state, "update", &file_path, None, &updated);
+        match updated {
+            Ok(note) => result.updated.push(note),
+            Err(e) => result.errors.push(format!("{}: {}", file_path, e)),
+        }
+    }
+    if !result.updated.is_empty() {
+        broadcast::broadcast(&notes_dir, result.updated.clone(), Vec::new());
+    }
+    Ok(result)
+}
+
+/// Set or clear (`None` or empty) a note's icon
+#[tauri::command]
+pub fn set_note_icon(
+    notes_dir: String,
+    file_path: String,
+    icon: Option<String>,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let icon = icon.as_deref().map(sanitize_icon).transpose()?.flatten();
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        frontmatter.icon = icon;
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(&notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+fn set_archived(
+    notes_dir: &str,
+    file_path: &str,
+    archived: bool,
+    state: &State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = update_note_frontmatter(notes_dir, file_path, state, |frontmatter| {
+        if frontmatter.archived && !archived {
+            let root = board_root_of(Path::new(notes_dir), Path::new(file_path));
+            board::check_wip_limit(&root, &frontmatter.column, 1, state)?;
+        }
+        frontmatter.archived = archived;
+        Ok(())
+    });
+    audit::record(state, "update", file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
+}
+
+/// Take a note off the board without deleting it
+#[tauri::command]
+pub fn archive_note(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    set_archived(&notes_dir, &file_path, true, &state)
+}
+
+/// Put an archived note back on the board
+#[tauri::command]
+pub fn unarchive_note(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    set_archived(&notes_dir, &file_path, false, &state)
+}
+
+/// Pin or unpin a note
+#[tauri::command]
+pub fn toggle_pin(
+    notes_dir: String,
+    file_path: String,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let result = update_note_frontmatter(&notes_dir, &file_path, &state, |frontmatter| {
+        frontmatter.pinned = !frontmatter.pinned;
+        Ok(())
+    });
+    audit::record(&state, "update", &file_path, None, &result);
+    if let Ok(note) = &result {
+        broadcast::broadcast(&notes_dir, vec![note.clone()], Vec::new());
+    }
+    result
 }
 
 #[tauri::command]
 pub fn delete_note(
     notes_dir: String,
     file_path: String,
+    force: Option<bool>,
     state: State<AppState>,
 ) -> Result<(), String> {
-    let base_path = PathBuf::from(&notes_dir);
-    let path = PathBuf::from(&file_path);
+    let result = trash_note(&notes_dir, &file_path, force.unwrap_or(false), &state);
+    audit::record(&state, "delete", &file_path, None, &result);
+    if result.is_ok() {
+        broadcast::broadcast(&notes_dir, Vec::new(), vec![file_path]);
+    }
+    result
+}
+
+fn trash_note(
+    notes_dir: &str,
+    file_path: &str,
+    force: bool,
+    state: &State<AppState>,
+) -> Result<(), String> {
+    let base_path = PathBuf::from(notes_dir);
+    let path = PathBuf::from(file_path);
     validate_existing_path_within_base(&path, &base_path)?;
 
     if !path.exists() {
         return Err("Note file does not exist".to_string());
     }
+    ensure_writable(&path, state)?;
 
-    // Get the attachments folder path
-    let stem = path
-        .file_stem()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_default();
-    let attachments = path
-        .parent()
-        .map(|p| p.join(format!("{}.attachments", stem)));
+    let note = parse_note(&path).ok();
+    if let Some(note) = &note {
+        ensure_modifiable(&note.frontmatter, force)?;
+    }
+    let title = note.map(|note| note.frontmatter.title);
 
     // Record write for self-save detection
-    record_write(&file_path, &state);
-
-    // Delete the note file
-    fs::remove_file(&path).map_err(|e| format!("Failed to delete note: {}", e))?;
+    record_write(file_path, state);
 
-    // Delete the attachments folder if it exists
-    if let Some(attach_path) = attachments {
-        if attach_path.exists() && attach_path.is_dir() {
-            fs::remove_dir_all(&attach_path)
-                .map_err(|e| format!("Failed to delete attachments folder: {}", e))?;
-        }
-    }
+    // Move the note and its attachments folder into the trash
+    let item = trash::move_note_to_trash(&base_path, &path, title)?;
+    trash::purge_expired(&base_path, trash::retention_days(state));
+    push_undo(
+        state,
+        format!(
+            "Delete \"{}\"",
+            item.title.as_deref().unwrap_or(&item.original_path)
+        ),
+        notes_dir,
+        vec![UndoStep::Trashed { trash_id: item.id }],
+    );
 
     // Remove from cache
     if let Ok(cache_lock) = state.cache.lock() {
         if let Some(cache) = cache_lock.as_ref() {
-            if let Err(e) = cache.remove_note(&file_path) {
+            if let Err(e) = cache.remove_note(file_path) {
                 log::warn!("Cache remove failed for deleted note: {}", e);
             }
         }
@@ -619,6 +2200,25 @@ pub fn create_folder(
     notes_dir: String,
     folder_name: String,
     parent_path: Option<String>,
+    state: State<AppState>,
+) -> Result<Folder, String> {
+    let requested = PathBuf::from(&notes_dir)
+        .join(parent_path.as_deref().unwrap_or_default())
+        .join(&folder_name);
+    let result = make_folder(notes_dir, folder_name, parent_path, &state);
+    let path = match &result {
+        Ok(folder) => folder.path.clone(),
+        Err(_) => requested.to_string_lossy().to_string(),
+    };
+    audit::record(&state, "create", &path, None, &result);
+    result
+}
+
+fn make_folder(
+    notes_dir: String,
+    folder_name: String,
+    parent_path: Option<String>,
+    state: &State<AppState>,
 ) -> Result<Folder, String> {
     let base = PathBuf::from(&notes_dir);
     validate_folder_name(&folder_name)?;
@@ -626,6 +2226,7 @@ pub fn create_folder(
         Some(parent) => {
             let parent_path = PathBuf::from(parent);
             ensure_safe_relative_path(&parent_path)?;
+            ensure_not_mount_folder(&parent_path)?;
             base.join(parent_path).join(&folder_name)
         }
         None => base.join(&folder_name),
@@ -634,6 +2235,7 @@ pub fn create_folder(
     if target.exists() {
         return Err("Folder already exists".to_string());
     }
+    ensure_writable(&target, state)?;
 
     fs::create_dir_all(&target).map_err(|e| format!("Failed to create folder: {}", e))?;
     validate_path_within_base(&target, &base)?;
@@ -642,11 +2244,29 @@ pub fn create_folder(
         .strip_prefix(&base)
         .map_err(|e| format!("Failed to get relative path: {}", e))?;
 
-    Ok(Folder {
+    let folder = Folder {
         path: target.to_string_lossy().to_string(),
         name: folder_name,
         relative_path: relative.to_string_lossy().to_string(),
-    })
+    };
+    update_folder_index(state, |cache| cache.upsert_folder(&folder));
+    Ok(folder)
+}
+
+/// Apply a folder command's change to the cached folder index. The in-memory
+/// note index can't follow folder changes and is rebuilt on the next listing.
+fn update_folder_index(
+    state: &State<AppState>,
+    change: impl FnOnce(&CacheDb) -> Result<(), String>,
+) {
+    note_index::invalidate(state);
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            if let Err(e) = change(cache) {
+                log::warn!("Failed to update folder index: {}", e);
+            }
+        }
+    }
 }
 
 #[tauri::command]
@@ -654,14 +2274,44 @@ pub fn rename_folder(
     notes_dir: String,
     old_path: String,
     new_name: String,
+    state: State<AppState>,
+) -> Result<Folder, String> {
+    let result = rename_folder_on_disk(&notes_dir, &old_path, new_name, &state);
+    let new_path = result.as_ref().ok().map(|folder| folder.path.as_str());
+    audit::record(&state, "move", &old_path, new_path, &result);
+    if let Ok(folder) = &result {
+        let old_name = Path::new(&old_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        push_undo(
+            &state,
+            format!("Rename folder \"{}\"", old_name),
+            &notes_dir,
+            vec![UndoStep::RenamedFolder {
+                from: old_path.clone(),
+                to: folder.path.clone(),
+            }],
+        );
+    }
+    result
+}
+
+pub(crate) fn rename_folder_on_disk(
+    notes_dir: &str,
+    old_path: &str,
+    new_name: String,
+    state: &State<AppState>,
 ) -> Result<Folder, String> {
     validate_folder_name(&new_name)?;
-    let base = PathBuf::from(&notes_dir);
-    let old = PathBuf::from(&old_path);
+    let base = PathBuf::from(notes_dir);
+    let old = PathBuf::from(old_path);
     let canonical_old = validate_existing_path_within_base(&old, &base)?;
     if !old.exists() || !old.is_dir() {
         return Err("Folder does not exist".to_string());
     }
+    ensure_writable(&old, state)?;
+    ensure_folder_modifiable(&old)?;
 
     let canonical_base = base
         .canonicalize()
@@ -681,24 +2331,46 @@ pub fn rename_folder(
 
     fs::rename(&old, &new).map_err(|e| format!("Failed to rename folder: {}", e))?;
 
-    Ok(Folder {
+    let folder = Folder {
         path: new.to_string_lossy().to_string(),
         name: new_name,
         relative_path: new
             .strip_prefix(&base)
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_default(),
-    })
-}
-
+    };
+    update_folder_index(state, |cache| cache.rename_folder_tree(old_path, &folder));
+    if let (Some(from), Some(to)) = (
+        vault_config::folder_key(&base, &old),
+        vault_config::folder_key(&base, &new),
+    ) {
+        if let Err(e) = vault_config::move_folder_meta(&base, &from, Some(&to)) {
+            log::warn!("Failed to move folder metadata: {}", e);
+        }
+    }
+    Ok(folder)
+}
+
 #[tauri::command]
-pub fn delete_folder(notes_dir: String, folder_path: String) -> Result<(), String> {
-    let base = PathBuf::from(&notes_dir);
-    let path = PathBuf::from(&folder_path);
+pub fn delete_folder(
+    notes_dir: String,
+    folder_path: String,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let result = trash_folder(&notes_dir, &folder_path, &state);
+    audit::record(&state, "delete", &folder_path, None, &result);
+    result
+}
+
+fn trash_folder(notes_dir: &str, folder_path: &str, state: &State<AppState>) -> Result<(), String> {
+    let base = PathBuf::from(notes_dir);
+    let path = PathBuf::from(folder_path);
     let canonical_path = validate_existing_path_within_base(&path, &base)?;
     if !path.exists() {
         return Err("Folder does not exist".to_string());
     }
+    ensure_writable(&path, state)?;
+    ensure_folder_modifiable(&path)?;
 
     let canonical_base = base
         .canonicalize()
@@ -707,7 +2379,22 @@ pub fn delete_folder(notes_dir: String, folder_path: String) -> Result<(), Strin
         return Err("Cannot delete root notes directory".to_string());
     }
 
-    fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete folder: {}", e))?;
+    let item = trash::move_folder_to_trash(&base, &path)?;
+    update_folder_index(state, |cache| {
+        cache.remove_folder_tree(folder_path).map(|_| ())
+    });
+    if let Some(key) = vault_config::folder_key(&base, &path) {
+        if let Err(e) = vault_config::move_folder_meta(&base, &key, None) {
+            log::warn!("Failed to remove folder metadata: {}", e);
+        }
+    }
+    trash::purge_expired(&base, trash::retention_days(state));
+    push_undo(
+        state,
+        format!("Delete folder \"{}\"", item.original_path),
+        notes_dir,
+        vec![UndoStep::Trashed { trash_id: item.id }],
+    );
 
     Ok(())
 }
@@ -717,30 +2404,72 @@ pub fn move_note(
     notes_dir: String,
     file_path: String,
     target_folder: String,
+    force: Option<bool>,
     state: State<AppState>,
 ) -> Result<Note, String> {
-    let base = PathBuf::from(&notes_dir);
-    let source = PathBuf::from(&file_path);
+    let result = match parse_note(&PathBuf::from(&file_path)) {
+        Ok(note) => ensure_modifiable(&note.frontmatter, force.unwrap_or(false)),
+        Err(_) => Ok(()),
+    }
+    .and_then(|_| move_note_file(&notes_dir, &file_path, &target_folder, &state));
+    let moved_to = result.as_ref().ok().map(|note| note.file_path.as_str());
+    audit::record(&state, "move", &file_path, moved_to, &result);
+    let note = result?;
+    broadcast::broadcast(
+        &notes_dir,
+        vec![broadcast::listed(note.clone())],
+        vec![file_path.clone()],
+    );
+    push_undo(
+        &state,
+        format!("Move \"{}\"", note.frontmatter.title),
+        &notes_dir,
+        vec![UndoStep::Moved {
+            from: file_path,
+            to: note.file_path.clone(),
+        }],
+    );
+    Ok(note)
+}
+
+/// Move a note and its attachments folder without recording an undo step
+pub(crate) fn move_note_file(
+    notes_dir: &str,
+    file_path: &str,
+    target_folder: &str,
+    state: &State<AppState>,
+) -> Result<Note, String> {
+    let base = PathBuf::from(notes_dir);
+    let source = PathBuf::from(file_path);
     validate_existing_path_within_base(&source, &base)?;
     if !source.exists() {
         return Err("Note does not exist".to_string());
     }
+    ensure_writable(&source, state)?;
 
     let target_dir = {
-        let raw_target = PathBuf::from(&target_folder);
+        let raw_target = PathBuf::from(target_folder);
         if raw_target.is_absolute() {
             validate_existing_path_within_base(&raw_target, &base)?;
             raw_target
         } else {
             ensure_safe_relative_path(&raw_target)?;
+            ensure_not_mount_folder(&raw_target)?;
             base.join(raw_target)
         }
     };
+    ensure_writable(&target_dir, state)?;
     if !target_dir.exists() {
         fs::create_dir_all(&target_dir)
             .map_err(|e| format!("Failed to create target folder: {}", e))?;
     }
     validate_path_within_base(&target_dir, &base)?;
+    if board_root_of(&base, &source) != vault_config::folder_board_root(&base, &target_dir) {
+        let note = parse_note(&source)?;
+        if !note.frontmatter.archived {
+            board::check_card_entering(&base, &target_dir, &note.frontmatter.column, state)?;
+        }
+    }
 
     let file_name = source.file_name().ok_or("Invalid file name")?;
     let destination = target_dir.join(file_name);
@@ -765,42 +2494,61 @@ pub fn move_note(
     }
 
     // Record writes for self-save detection
-    record_write(&file_path, &state);
-    record_write(&final_dest.to_string_lossy(), &state);
+    record_write(file_path, state);
+    record_write(&final_dest.to_string_lossy(), state);
 
     // Move the attachments folder if it exists
     let mut attachments_moved = false;
     let dest_attachments = target_dir.join(format!("{}.attachments", final_stem));
+    let source_attachments = source_attachments.filter(|p| p.exists() && p.is_dir());
+    if source_attachments.is_some() && dest_attachments.exists() {
+        return Err("Attachments folder already exists".to_string());
+    }
+
+    let mut renames = Vec::new();
+    if let Some(src_attach) = source_attachments.as_ref() {
+        renames.push(journal_rename(src_attach, &dest_attachments));
+    }
+    renames.push(journal_rename(&source, &final_dest));
+    let journal = begin_journal(state, "move_note", renames, None);
+
     if let Some(src_attach) = source_attachments.as_ref() {
-        if src_attach.exists() && src_attach.is_dir() {
-            if dest_attachments.exists() {
-                return Err("Attachments folder already exists".to_string());
+        if let Err(e) = fs::rename(src_attach, &dest_attachments) {
+            if let Some(journal) = journal {
+                journal.finish();
             }
-            fs::rename(src_attach, &dest_attachments)
-                .map_err(|e| format!("Failed to move attachments folder: {}", e))?;
-            attachments_moved = true;
+            return Err(format!("Failed to move attachments folder: {}", e));
         }
+        attachments_moved = true;
     }
 
     // Move the note file
     if let Err(e) = fs::rename(&source, &final_dest) {
+        let mut rolled_back = true;
         if attachments_moved {
             if let Some(src_attach) = source_attachments.as_ref() {
                 if let Err(rollback_err) = fs::rename(&dest_attachments, src_attach) {
+                    rolled_back = false;
                     log::error!(
-                        "Failed to rollback attachments move from {:?} to {:?}: {}. Manual cleanup may be required.",
-                        dest_attachments, src_attach, rollback_err
+                        "Failed to rollback attachments move from {} to {}: {}. Manual cleanup may be required.",
+                        logging::path(&dest_attachments), logging::path(src_attach), rollback_err
                     );
                 }
             }
         }
+        if let Some(journal) = journal.filter(|_| rolled_back) {
+            journal.finish();
+        }
         return Err(format!("Failed to move note: {}", e));
     }
+    if let Some(journal) = journal {
+        journal.finish();
+    }
 
     // Remove old path from cache
     if let Ok(cache_lock) = state.cache.lock() {
         if let Some(cache) = cache_lock.as_ref() {
-            if let Err(e) = cache.remove_note(&file_path) {
+            if let Err(e) = cache.remove_note(file_path) {
                 log::warn!("Cache remove failed for moved note: {}", e);
             }
         }
@@ -824,62 +2572,147 @@ pub fn move_note(
     Ok(note)
 }
 
+/// Add the renames a crash recovery settled to the audit log. The cache is not
+/// installed yet, so entries go straight to it.
+fn audit_recovered_operation(cache: &CacheDb, op: &journal::RecoveredOperation) {
+    for rename in &op.renames {
+        let (from, to, error) = match op.outcome {
+            journal::RecoveryOutcome::RolledForward => (&rename.from, &rename.to, None),
+            journal::RecoveryOutcome::RolledBack => (&rename.to, &rename.from, None),
+            journal::RecoveryOutcome::Unresolved => (
+                &rename.from,
+                &rename.to,
+                Some("Interrupted operation could not be recovered"),
+            ),
+        };
+        if let Err(e) =
+            cache.append_audit_entry(&audit::current_actor(), "recover", from, Some(to), error)
+        {
+            log::warn!("Failed to record recovery in audit log: {}", e);
+        }
+    }
+}
+
 #[tauri::command]
 pub fn initialize_cache(profile_id: String, state: State<AppState>) -> Result<(), String> {
+    profile_lock::require_unlocked(&state, &profile_id)?;
+    note_index::invalidate(&state);
+    if state.safe_mode {
+        // Serve whatever the cache holds without repairing or writing
+        // anything; a broken cache is simply bypassed
+        let cache = match CacheDb::open_readonly(&profile_id) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                log::warn!("Safe mode: running without cache: {}", e);
+                None
+            }
+        };
+        *lock_or_err(&state.cache)? = cache;
+        clear_undo(&state);
+        return Ok(());
+    }
+
     let cache = CacheDb::new(&profile_id)?;
 
     // Verify integrity and rebuild if corrupt
-    if !cache.verify_integrity().unwrap_or(false) {
+    let cache_invalidated = !cache.verify_integrity().unwrap_or(false);
+    if cache_invalidated {
         log::warn!("Cache integrity check failed, invalidating...");
         cache.invalidate_all()?;
     }
 
+    // Repair file operations interrupted by a crash before serving any notes
+    let recovered_operations = match journal::journal_dir(&profile_id) {
+        Ok(dir) => journal::recover_pending(&dir),
+        Err(_) => Vec::new(),
+    };
+    for op in &recovered_operations {
+        log::warn!(
+            "Recovered interrupted {} from {}: {:?}",
+            op.operation,
+            op.started_at,
+            op.outcome
+        );
+        audit_recovered_operation(&cache, op);
+    }
+
+    if let Some(previous) = active_profile_id(&state).filter(|id| *id != profile_id) {
+        recovery::end_session(&previous);
+    }
+    let crashed_session_started_at = recovery::begin_session(&profile_id);
+    if let Some(started_at) = crashed_session_started_at {
+        log::warn!(
+            "Session started at {} did not shut down cleanly",
+            started_at
+        );
+    }
+    *lock_or_err(&state.startup_recovery)? = Some(StartupRecovery {
+        crashed_session_started_at,
+        cache_invalidated,
+        recovered_operations,
+    });
+
     let mut cache_lock = lock_or_err(&state.cache)?;
     *cache_lock = Some(cache);
+    // Undo entries refer to the previous profile's vault
+    clear_undo(&state);
     Ok(())
 }
 
-#[tauri::command]
-pub fn list_notes_cached(
-    notes_dir: String,
-    state: State<AppState>,
-) -> Result<NotesWithTagsAndFolders, String> {
-    let base_path = PathBuf::from(&notes_dir);
+/// What a vault scan has found so far
+struct ScanOutput<'a> {
+    notes: Vec<NoteWithTags>,
+    folders: Vec<Folder>,
+    placeholders: Vec<CloudPlaceholder>,
+    seen_paths: HashSet<String>,
+    /// Notes served from the cache and notes that had to be parsed
+    hits: u64,
+    misses: u64,
+    /// Called for every note as soon as it has been read
+    on_note: &'a mut dyn FnMut(&NoteWithTags),
+}
 
-    if !base_path.exists() {
-        fs::create_dir_all(&base_path)
-            .map_err(|e| format!("Failed to create notes directory: {}", e))?;
-        return Ok(NotesWithTagsAndFolders {
-            notes: vec![],
-            folders: vec![],
-        });
+impl ScanOutput<'_> {
+    fn push_note(&mut self, note: NoteWithTags) {
+        (self.on_note)(&note);
+        self.notes.push(note);
     }
+}
 
-    let cache_lock = lock_or_err(&state.cache)?;
-    let cache = cache_lock.as_ref();
-
-    let mut notes = Vec::new();
-    let mut folders = Vec::new();
-    let mut seen_paths = HashSet::new();
-
-    for entry in WalkDir::new(&base_path)
+/// Walk `root` collecting folders and notes, serving unchanged notes from the cache.
+/// `relative_prefix` is prepended to folder relative paths (used for read-only mounts).
+fn scan_notes_cached(
+    root: &Path,
+    relative_prefix: Option<&str>,
+    allowed_symlink_targets: &[PathBuf],
+    cache: Option<&CacheDb>,
+    out: &mut ScanOutput<'_>,
+) -> Result<(), String> {
+    let walk_filter = VaultWalkFilter::new(root, allowed_symlink_targets);
+    for entry in WalkDir::new(root)
         .min_depth(1)
+        .follow_links(true)
         .into_iter()
         .filter_entry(|e| {
             !e.file_name()
                 .to_str()
-                .map(|s| s.ends_with(".attachments"))
+                .map(is_skipped_dir_name)
                 .unwrap_or(false)
+                && walk_filter.allows(e)
         })
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
         let relative = path
-            .strip_prefix(&base_path)
+            .strip_prefix(root)
             .map_err(|e| format!("Failed to get relative path: {}", e))?;
+        let relative = match relative_prefix {
+            Some(prefix) => Path::new(prefix).join(relative),
+            None => relative.to_path_buf(),
+        };
 
         if path.is_dir() {
-            folders.push(Folder {
+            out.folders.push(Folder {
                 path: path.to_string_lossy().to_string(),
                 name: path
                     .file_name()
@@ -887,27 +2720,45 @@ pub fn list_notes_cached(
                     .unwrap_or_default(),
                 relative_path: relative.to_string_lossy().to_string(),
             });
-        } else if path.extension().map_or(false, |ext| ext == "md") {
+        } else if path.extension().is_some_and(|ext| ext == "md") {
             let file_path_str = path.to_string_lossy().to_string();
-            seen_paths.insert(file_path_str.clone());
+            out.seen_paths.insert(file_path_str.clone());
 
             let path_buf = path.to_path_buf();
             let mtime = get_file_mtime(&path_buf)?;
 
+            // Copies other sync tools leave behind show up as conflicts
+            if let (Some(c), Some(record)) = (cache, conflicts::third_party_conflict_record(path)) {
+                if let Err(e) = c.upsert_conflict(&record) {
+                    log::warn!("Failed to index conflict: {}", e);
+                }
+            }
+
             // Check cache first
             if let Some(c) = cache {
                 if !c.needs_update(&file_path_str, mtime) {
                     if let Ok(Some(cached)) = c.get_note(&file_path_str) {
-                        notes.push(NoteWithTags {
+                        out.hits += 1;
+                        out.push_note(NoteWithTags {
                             note: cached.note,
                             inline_tags: cached.inline_tags,
+                            days_in_column: None,
+                            excerpt: Some(cached.excerpt),
                         });
                         continue;
                     }
                 }
             }
 
+            // Reading a file that is only in the cloud would block on a download
+            if cloud::placeholder_note_path(path).is_some() {
+                out.placeholders
+                    .push(cloud::placeholder_for(path, &relative));
+                continue;
+            }
+
             // Parse and cache
+            out.misses += 1;
             match parse_note(&path_buf) {
                 Ok(note) => {
                     let inline_tags = extract_inline_tags(&note.content);
@@ -921,55 +2772,604 @@ pub fn list_notes_cached(
                         }
                     }
 
-                    notes.push(NoteWithTags { note, inline_tags });
+                    let excerpt = make_excerpt(&note.content);
+                    out.push_note(NoteWithTags {
+                        note,
+                        inline_tags,
+                        days_in_column: None,
+                        excerpt: Some(excerpt),
+                    });
                 }
-                Err(e) => log::warn!("Skipping invalid note {:?}: {}", path, e),
+                Err(e) => log::warn!("Skipping invalid note {}: {}", logging::path(&path), e),
             }
+        } else if let Some(note_path) = cloud::placeholder_note_path(path) {
+            out.placeholders
+                .push(cloud::placeholder_for(&note_path, &relative));
         }
     }
 
+    Ok(())
+}
+
+/// Attach column aging to listed notes
+pub(crate) fn fill_days_in_column(cache: &CacheDb, notes: &mut [NoteWithTags]) {
+    match cache.get_days_in_column() {
+        Ok(days) => {
+            for note in notes {
+                note.days_in_column = days.get(&note.note.frontmatter.id).copied();
+            }
+        }
+        Err(e) => log::warn!("Failed to compute column aging: {}", e),
+    }
+}
+
+/// List notes from the index, walking the vault when there is none. With
+/// `board` set, only the notes on that folder's board (`""` for the vault's
+/// own) are listed.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn list_notes_cached(
+    notes_dir: String,
+    column: Option<String>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+    refresh: Option<bool>,
+    include_archived: Option<bool>,
+    board: Option<String>,
+    state: State<AppState>,
+) -> Result<NotesWithTagsAndFolders, String> {
+    let after = cursor.as_deref().map(parse_cursor).transpose()?;
+    let scope = board
+        .map(|board| board_root(Path::new(&notes_dir), Some(&board)))
+        .transpose()?
+        .map(|root| note_index::board_scope(&state, root));
+    // Walk the vault only when asked to or when there is no index yet
+    let indexed = match refresh {
+        Some(true) => None,
+        _ => note_index::listing(&notes_dir, &state),
+    };
+    let mut listing = match indexed {
+        Some(mut listing) => {
+            if let Some(cache) = lock_or_err(&state.cache)?.as_ref() {
+                fill_days_in_column(cache, &mut listing.notes);
+            }
+            listing
+        }
+        None => scan_vault(&notes_dir, &state, &mut |_| {})?,
+    };
+
+    if let Some(scope) = &scope {
+        listing
+            .notes
+            .retain(|note| scope.contains(Path::new(&note.note.file_path)));
+    }
+    if let Some(column) = &column {
+        listing
+            .notes
+            .retain(|note| &note.note.frontmatter.column == column);
+    }
+    if !include_archived.unwrap_or(false) {
+        listing.notes.retain(|note| !note.note.frontmatter.archived);
+    }
+    let (notes, next_cursor) = paginate_notes(listing.notes, after, limit);
+    Ok(NotesWithTagsAndFolders {
+        notes,
+        next_cursor,
+        ..listing
+    })
+}
+
+/// Walk the vault and its read-only mounts, refreshing the cache, and drop
+/// cache entries of notes that are gone. `on_note` sees each note as it is
+/// read; the returned notes are unsorted.
+pub(crate) fn scan_vault(
+    notes_dir: &str,
+    state: &State<AppState>,
+    on_note: &mut dyn FnMut(&NoteWithTags),
+) -> Result<NotesWithTagsAndFolders, String> {
+    let started = Instant::now();
+    let base_path = PathBuf::from(notes_dir);
+
+    if !base_path.exists() {
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create notes directory: {}", e))?;
+        return Ok(NotesWithTagsAndFolders {
+            notes: vec![],
+            folders: vec![],
+            placeholders: vec![],
+            next_cursor: None,
+        });
+    }
+
+    let mounts = readonly_mounts(state);
+    let allowed_symlink_targets = symlink_allowlist(state);
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref();
+
+    let mut out = ScanOutput {
+        notes: Vec::new(),
+        folders: Vec::new(),
+        placeholders: Vec::new(),
+        seen_paths: HashSet::new(),
+        hits: 0,
+        misses: 0,
+        on_note,
+    };
+
+    scan_notes_cached(&base_path, None, &allowed_symlink_targets, cache, &mut out)?;
+
+    // Read-only mounts appear as folders named after the mount
+    for mount in &mounts {
+        let mount_path = PathBuf::from(&mount.path);
+        if !mount_path.is_dir() {
+            continue;
+        }
+        let relative_path = mount_relative_path(&mount.name);
+        out.folders.push(Folder {
+            path: mount.path.clone(),
+            name: mount.name.clone(),
+            relative_path: relative_path.clone(),
+        });
+        scan_notes_cached(
+            &mount_path,
+            Some(&relative_path),
+            &allowed_symlink_targets,
+            cache,
+            &mut out,
+        )?;
+    }
+
+    let ScanOutput {
+        mut notes,
+        mut folders,
+        mut placeholders,
+        seen_paths,
+        hits,
+        misses,
+        ..
+    } = out;
+    let counters = &state.cache_counters;
+    counters.record(hits, misses);
+    counters.last_scan_ms.store(
+        (started.elapsed().as_millis() as u64).max(1),
+        std::sync::atomic::Ordering::Relaxed,
+    );
+
     // Remove stale cache entries
     if let Some(c) = cache {
         if let Err(e) = c.remove_notes_not_in(&seen_paths) {
             log::warn!("Failed to remove stale cache entries: {}", e);
         }
+        if let Err(e) = c.prune_column_transitions() {
+            log::warn!("Failed to prune column transitions: {}", e);
+        }
+        fill_days_in_column(c, &mut notes);
     }
 
-    // Sort by modified date (newest first)
-    notes.sort_by(|a, b| {
-        b.note
-            .frontmatter
-            .modified
-            .cmp(&a.note.frontmatter.modified)
-    });
     folders.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    if let Some(c) = cache {
+        if let Err(e) = c.replace_folders(&folders) {
+            log::warn!("Failed to index folders: {}", e);
+        }
+    }
+
+    placeholders.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    drop(cache_lock);
+
+    let listing = NotesWithTagsAndFolders {
+        notes,
+        folders,
+        placeholders,
+        next_cursor: None,
+    };
+    note_index::replace(state, notes_dir, &listing);
+    Ok(listing)
+}
+
+/// Folders for the sidebar from the cached folder index. The vault is only
+/// walked when nothing has been indexed yet.
+#[tauri::command]
+pub fn list_folders_cached(
+    notes_dir: String,
+    state: State<AppState>,
+) -> Result<Vec<CachedFolder>, String> {
+    let indexed = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        cache.get_folders()?
+    };
+    if !indexed.is_empty() {
+        return Ok(indexed);
+    }
+
+    scan_vault(&notes_dir, &state, &mut |_| {})?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.get_folders()
+}
+
+/// Markdown files of the vault and its read-only mounts, walked the same way
+/// as the listing scan. Cloud placeholders are left out.
+fn vault_note_paths(notes_dir: &str, state: &State<AppState>) -> Vec<PathBuf> {
+    let allowed_symlink_targets = symlink_allowlist(state);
+    let mut roots = vec![PathBuf::from(notes_dir)];
+    roots.extend(
+        readonly_mounts(state)
+            .into_iter()
+            .map(|mount| PathBuf::from(mount.path))
+            .filter(|path| path.is_dir()),
+    );
+
+    let mut paths = Vec::new();
+    for root in &roots {
+        let walk_filter = VaultWalkFilter::new(root, &allowed_symlink_targets);
+        paths.extend(
+            WalkDir::new(root)
+                .min_depth(1)
+                .follow_links(true)
+                .into_iter()
+                .filter_entry(|e| {
+                    !e.file_name()
+                        .to_str()
+                        .map(is_skipped_dir_name)
+                        .unwrap_or(false)
+                        && walk_filter.allows(e)
+                })
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+                .filter(|path| {
+                    path.is_file()
+                        && path.extension().is_some_and(|ext| ext == "md")
+                        && cloud::placeholder_note_path(path).is_none()
+                }),
+        );
+    }
+    paths
+}
+
+/// Read and parse a note along with the hash and mtime the cache keys it by
+fn read_note_for_cache(path: &PathBuf) -> Result<(Note, String, i64), String> {
+    let mtime = get_file_mtime(path)?;
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let note = parse_note_content(&content, path)?;
+    Ok((note, compute_content_hash(&content), mtime))
+}
+
+/// Drop every cached note and parse the whole vault again, spreading the
+/// parsing over all cores. The cache stays usable while files are parsed and
+/// is only locked to swap in the results. `on_progress` gets the number of
+/// notes parsed so far. Returns the number cached.
+pub(crate) fn rebuild_note_cache(
+    notes_dir: &str,
+    state: &State<AppState>,
+    on_progress: &mut dyn FnMut(usize),
+) -> Result<usize, String> {
+    let paths = vault_note_paths(notes_dir, state);
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk_size = paths.len().div_ceil(workers).max(1);
+
+    let mut parsed_notes = Vec::with_capacity(paths.len());
+    std::thread::scope(|scope| {
+        let (tx, rx) = std::sync::mpsc::channel();
+        for chunk in paths.chunks(chunk_size) {
+            let tx = tx.clone();
+            scope.spawn(move || {
+                for path in chunk {
+                    if tx.send((path, read_note_for_cache(path))).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for (path, parsed) in rx {
+            match parsed {
+                Ok(parsed) => {
+                    parsed_notes.push(parsed);
+                    on_progress(parsed_notes.len());
+                }
+                Err(e) => log::warn!("Skipping invalid note {}: {}", logging::path(path), e),
+            }
+        }
+    });
+
+    // A note written while parsing is cached with its old mtime, which the
+    // next listing sees as stale and reads again
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.invalidate_all()?;
+    let mut cached = 0;
+    for (note, hash, mtime) in parsed_notes {
+        let inline_tags = extract_inline_tags(&note.content);
+        match cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+            Ok(()) => cached += 1,
+            Err(e) => log::warn!("Cache update failed during rebuild: {}", e),
+        }
+    }
+    Ok(cached)
+}
+
+/// Position in a listing: whether the last note returned is pinned, its
+/// `modified` time and its path
+type ListCursor = (bool, DateTime<Utc>, String);
+
+fn note_cursor(note: &NoteWithTags) -> ListCursor {
+    let frontmatter = &note.note.frontmatter;
+    (
+        frontmatter.pinned,
+        frontmatter.modified,
+        note.note.file_path.clone(),
+    )
+}
+
+fn format_cursor((pinned, modified, path): &ListCursor) -> String {
+    format!("{}|{}|{}", u8::from(*pinned), modified.to_rfc3339(), path)
+}
 
-    Ok(NotesWithTagsAndFolders { notes, folders })
+fn parse_cursor(cursor: &str) -> Result<ListCursor, String> {
+    let (pinned, rest) = cursor.split_once('|').ok_or("Invalid cursor")?;
+    let (modified, path) = rest.split_once('|').ok_or("Invalid cursor")?;
+    let modified = DateTime::parse_from_rfc3339(modified)
+        .map_err(|_| "Invalid cursor".to_string())?
+        .with_timezone(&Utc);
+    Ok((pinned == "1", modified, path.to_string()))
+}
+
+/// Sort notes pinned first, then newest first (path as tie-breaker, so pages
+/// never overlap) and return up to `limit` of them following `after`, plus
+/// the cursor of the next page if there is one
+fn paginate_notes(
+    mut notes: Vec<NoteWithTags>,
+    after: Option<ListCursor>,
+    limit: Option<usize>,
+) -> (Vec<NoteWithTags>, Option<String>) {
+    let order = |(pinned, modified, path): &ListCursor, other: &ListCursor| {
+        other
+            .0
+            .cmp(pinned)
+            .then_with(|| other.1.cmp(modified))
+            .then_with(|| path.cmp(&other.2))
+    };
+    notes.sort_by(|a, b| order(&note_cursor(a), &note_cursor(b)));
+    if let Some(after) = &after {
+        notes.retain(|note| order(&note_cursor(note), after).is_gt());
+    }
+
+    let Some(limit) = limit
+        .map(|limit| limit.max(1))
+        .filter(|limit| *limit < notes.len())
+    else {
+        return (notes, None);
+    };
+    notes.truncate(limit);
+    let next_cursor = notes.last().map(|note| format_cursor(&note_cursor(note)));
+    (notes, next_cursor)
 }
 
 #[tauri::command]
 pub fn process_file_changes(
     notes_dir: String,
     changes: Vec<FileChangeEvent>,
+    app: AppHandle,
     state: State<AppState>,
 ) -> Result<IncrementalUpdateResult, String> {
-    let base_path = PathBuf::from(&notes_dir);
+    watch::process_changes(&app, &notes_dir, changes, &state)
+}
+
+/// Last event per path wins, so a save reported as several events is parsed
+/// once. Paths keep the order in which they were first reported.
+pub(crate) fn coalesce_changes(
+    changes: impl IntoIterator<Item = FileChangeEvent>,
+) -> Vec<FileChangeEvent> {
+    let mut coalesced: Vec<FileChangeEvent> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for change in changes {
+        match positions.get(&change.file_path) {
+            Some(&i) => coalesced[i] = change,
+            None => {
+                positions.insert(change.file_path.clone(), coalesced.len());
+                coalesced.push(change);
+            }
+        }
+    }
+    coalesced
+}
+
+/// A rename outside the app arrives as a removal plus a creation. Pair the
+/// two by content hash within one batch of events and move the cache row, so
+/// the note keeps its identity, tags and column history.
+fn pair_renames(changes: &[FileChangeEvent], cache: &CacheDb) -> Vec<MovedNote> {
+    let is_note = |path: &str| Path::new(path).extension().is_some_and(|ext| ext == "md");
+    let mut removed: Vec<(&str, String)> = changes
+        .iter()
+        .filter(|c| c.event_type == "remove" && is_note(&c.file_path))
+        .filter(|c| !Path::new(&c.file_path).exists())
+        .filter_map(|c| {
+            let (hash, _) = cache.get_indexed_state(&c.file_path).ok()??;
+            Some((c.file_path.as_str(), hash))
+        })
+        .collect();
+
+    let mut moved = Vec::new();
+    for change in changes {
+        if removed.is_empty() {
+            break;
+        }
+        if change.event_type != "create" || !is_note(&change.file_path) {
+            continue;
+        }
+        let path = PathBuf::from(&change.file_path);
+        let (Ok(content), Ok(mtime)) = (fs::read_to_string(&path), get_file_mtime(&path)) else {
+            continue;
+        };
+        let hash = compute_content_hash(&content);
+        let Some(i) = removed.iter().position(|(_, h)| *h == hash) else {
+            continue;
+        };
+        let (from, _) = removed.swap_remove(i);
+        match cache.rename_note_path(from, &change.file_path, mtime) {
+            Ok(true) => moved.push(MovedNote {
+                from: from.to_string(),
+                to: change.file_path.clone(),
+            }),
+            Ok(false) => {}
+            Err(e) => log::warn!("Failed to move cached note: {}", e),
+        }
+    }
+    moved
+}
+
+/// Folder entries for a directory the watcher reported as created and the
+/// directories inside it, leaving out anything the listing scan skips
+fn watched_folders(path: &Path, base: &Path) -> Vec<Folder> {
+    let is_listed = |dir: &Path| {
+        let Ok(relative) = dir.strip_prefix(base) else {
+            return false;
+        };
+        !relative.as_os_str().is_empty()
+            && !is_in_trash(dir)
+            && !relative
+                .components()
+                .any(|c| c.as_os_str().to_str().is_some_and(is_skipped_dir_name))
+    };
+    if !is_listed(path) {
+        return Vec::new();
+    }
+
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| e.file_type().is_dir() && is_listed(e.path()))
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let dir = e.path();
+            Some(Folder {
+                path: dir.to_string_lossy().to_string(),
+                name: dir.file_name()?.to_string_lossy().to_string(),
+                relative_path: dir.strip_prefix(base).ok()?.to_string_lossy().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Keep the storage index (and with it orphan tracking) current for a file
+/// in an `.attachments` folder
+fn apply_attachment_change(
+    change: FileChangeEvent,
+    note_path: PathBuf,
+    cache: Option<&CacheDb>,
+) -> Option<AttachmentChange> {
+    let path = Path::new(&change.file_path);
+    if is_in_trash(path) {
+        return None;
+    }
+    match change.event_type.as_str() {
+        "remove" => {
+            if let Some(c) = cache {
+                if let Err(e) = c.remove_storage_file(&change.file_path) {
+                    log::warn!("Failed to untrack attachment: {}", e);
+                }
+            }
+        }
+        "create" | "modify" => {
+            let size = fs::metadata(path).ok().filter(|m| m.is_file())?.len();
+            if let Some(c) = cache {
+                let record = StorageFileRecord {
+                    file_path: change.file_path.clone(),
+                    note_path: Some(note_path.to_string_lossy().to_string()),
+                    size: size as i64,
+                    is_attachment: true,
+                };
+                if let Err(e) = c.upsert_storage_file(&record) {
+                    log::warn!("Failed to track attachment: {}", e);
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(AttachmentChange {
+        event_type: change.event_type,
+        file_path: change.file_path,
+        note_path: note_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Bring the cache up to date with watcher events and report what changed
+pub(crate) fn apply_file_changes(
+    notes_dir: &str,
+    changes: Vec<FileChangeEvent>,
+    state: &State<AppState>,
+) -> Result<IncrementalUpdateResult, String> {
+    // Safe mode ignores the watcher so a change storm cannot retrigger a fault
+    if state.safe_mode {
+        return Ok(IncrementalUpdateResult::default());
+    }
+    let base_path = PathBuf::from(notes_dir);
     let cache_lock = lock_or_err(&state.cache)?;
     let cache = cache_lock.as_ref();
 
+    let changes = coalesce_changes(changes);
+    let moved_notes = match cache {
+        Some(c) => pair_renames(&changes, c),
+        None => Vec::new(),
+    };
     let mut updated_notes = Vec::new();
     let mut removed_paths = Vec::new();
+    let mut attachment_changes = Vec::new();
+    let mut config_changes = Vec::new();
+    let mut added_folders = Vec::new();
+    let mut removed_folders = Vec::new();
 
     for change in changes {
         // Skip self-initiated writes
-        if is_recent_write(&change.file_path, &state) {
-            log::debug!("Skipping self-initiated change: {}", change.file_path);
+        if is_recent_write(&change.file_path, state) {
+            log::debug!(
+                "Skipping self-initiated change: {}",
+                logging::path(&change.file_path)
+            );
+            continue;
+        }
+
+        if let Some(kind) = config_kind(&base_path, Path::new(&change.file_path)) {
+            if kind == ConfigKind::Board {
+                note_index::forget_boards(state);
+            }
+            config_changes.push(ConfigChange {
+                kind,
+                event_type: change.event_type,
+                file_path: change.file_path,
+            });
+            continue;
+        }
+
+        if let Some(note_path) = attachment_owner(Path::new(&change.file_path)) {
+            if let Some(attachment) = apply_attachment_change(change, note_path, cache) {
+                attachment_changes.push(attachment);
+            }
             continue;
         }
 
         match change.event_type.as_str() {
             "remove" => {
+                // Could be a folder; the index can't tell which notes it held
+                if Path::new(&change.file_path).extension().is_none() {
+                    note_index::invalidate(state);
+                }
                 if let Some(c) = cache {
+                    match c.remove_folder_tree(&change.file_path) {
+                        Ok(true) => {
+                            note_index::invalidate(state);
+                            removed_folders.push(change.file_path);
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(e) => log::warn!("Failed to update folder index: {}", e),
+                    }
+                }
+                let renamed = moved_notes.iter().any(|m| m.from == change.file_path);
+                if let (Some(c), false) = (cache, renamed) {
                     if let Err(e) = c.remove_note(&change.file_path) {
                         log::warn!("Cache remove failed for file change: {}", e);
                     }
@@ -978,9 +3378,27 @@ pub fn process_file_changes(
             }
             "create" | "modify" => {
                 let path = PathBuf::from(&change.file_path);
+                if change.event_type == "create" && path.is_dir() {
+                    note_index::invalidate(state);
+                    let folders = watched_folders(&path, &base_path);
+                    if let Some(c) = cache {
+                        for folder in &folders {
+                            if let Err(e) = c.upsert_folder(folder) {
+                                log::warn!("Failed to update folder index: {}", e);
+                            }
+                        }
+                    }
+                    added_folders.extend(folders);
+                    continue;
+                }
 
-                // Skip if not a markdown file or doesn't exist
-                if !path.exists() || !path.extension().map_or(false, |e| e == "md") {
+                // Skip if not a markdown file, doesn't exist, sits in the trash
+                // or has not been downloaded from the cloud
+                if !path.exists()
+                    || !path.extension().is_some_and(|e| e == "md")
+                    || is_in_trash(&path)
+                    || cloud::placeholder_note_path(&path).is_some()
+                {
                     continue;
                 }
 
@@ -988,7 +3406,7 @@ pub fn process_file_changes(
                 if validate_path_within_base(&path, &base_path).is_err() {
                     log::warn!(
                         "Skipping file outside notes directory: {}",
-                        change.file_path
+                        logging::path(&change.file_path)
                     );
                     continue;
                 }
@@ -998,9 +3416,20 @@ pub fn process_file_changes(
                     Err(_) => continue,
                 };
 
-                // Check if we need to update
+                // Check if we need to update; a renamed note is already cached
+                // at its new path but still has to reach the UI there
                 if let Some(c) = cache {
                     if !c.needs_update(&change.file_path, mtime) {
+                        if moved_notes.iter().any(|m| m.to == change.file_path) {
+                            if let Ok(Some(cached)) = c.get_note(&change.file_path) {
+                                updated_notes.push(NoteWithTags {
+                                    note: cached.note,
+                                    inline_tags: cached.inline_tags,
+                                    days_in_column: None,
+                                    excerpt: Some(cached.excerpt),
+                                });
+                            }
+                        }
                         continue;
                     }
                 }
@@ -1018,17 +3447,506 @@ pub fn process_file_changes(
                             }
                         }
 
-                        updated_notes.push(NoteWithTags { note, inline_tags });
+                        let excerpt = make_excerpt(&note.content);
+                        updated_notes.push(NoteWithTags {
+                            note,
+                            inline_tags,
+                            days_in_column: None,
+                            excerpt: Some(excerpt),
+                        });
                     }
-                    Err(e) => log::warn!("Failed to parse {}: {}", change.file_path, e),
+                    Err(e) => log::warn!(
+                        "Failed to parse {}: {}",
+                        logging::path(&change.file_path),
+                        e
+                    ),
                 }
             }
             _ => {}
         }
     }
 
+    if let Some(c) = cache {
+        fill_days_in_column(c, &mut updated_notes);
+    }
+    if !updated_notes.is_empty() || !removed_paths.is_empty() {
+        sync_schedule::mark_local_edit();
+    }
+    note_index::apply_changes(state, &updated_notes, &removed_paths);
+
     Ok(IncrementalUpdateResult {
         updated_notes,
         removed_paths,
+        moved_notes,
+        attachment_changes,
+        config_changes,
+        added_folders,
+        removed_folders,
+        rescan_id: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(path: &str, modified: &str) -> NoteWithTags {
+        let modified = DateTime::parse_from_rfc3339(modified)
+            .unwrap()
+            .with_timezone(&Utc);
+        NoteWithTags {
+            note: Note {
+                frontmatter: NoteFrontmatter {
+                    id: path.to_string(),
+                    title: path.to_string(),
+                    created: modified,
+                    modified,
+                    date: None,
+                    due: None,
+                    column: "todo".to_string(),
+                    tags: Vec::new(),
+                    order: 0,
+                    pinned: false,
+                    archived: false,
+                    priority: None,
+                    color: None,
+                    cover: None,
+                    icon: None,
+                    aliases: Vec::new(),
+                    extra: serde_yaml::Mapping::new(),
+                    format: FrontmatterFormat::Yaml,
+                },
+                content: String::new(),
+                file_path: path.to_string(),
+            },
+            inline_tags: Vec::new(),
+            days_in_column: None,
+            excerpt: None,
+        }
+    }
+
+    #[test]
+    fn pages_through_notes_without_overlap() {
+        let notes = vec![
+            listed("/b.md", "2024-01-02T00:00:00Z"),
+            listed("/c.md", "2024-01-03T00:00:00Z"),
+            listed("/a.md", "2024-01-02T00:00:00Z"),
+            listed("/d.md", "2024-01-01T00:00:00Z"),
+        ];
+        let paths = |page: &[NoteWithTags]| -> Vec<String> {
+            page.iter().map(|n| n.note.file_path.clone()).collect()
+        };
+
+        let (first, cursor) = paginate_notes(notes.clone(), None, Some(2));
+        assert_eq!(paths(&first), ["/c.md", "/a.md"]);
+        let after = parse_cursor(&cursor.unwrap()).unwrap();
+
+        let (second, cursor) = paginate_notes(notes.clone(), Some(after), Some(2));
+        assert_eq!(paths(&second), ["/b.md", "/d.md"]);
+        assert!(cursor.is_none());
+
+        let (all, cursor) = paginate_notes(notes, None, None);
+        assert_eq!(all.len(), 4);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn coalesces_changes_per_path() {
+        let change = |event_type: &str, file_path: &str| FileChangeEvent {
+            event_type: event_type.to_string(),
+            file_path: file_path.to_string(),
+        };
+        let coalesced = coalesce_changes(vec![
+            change("create", "/a.md"),
+            change("modify", "/b.md"),
+            change("modify", "/a.md"),
+            change("remove", "/b.md"),
+            change("modify", "/a.md"),
+        ]);
+        let summary: Vec<(&str, &str)> = coalesced
+            .iter()
+            .map(|c| (c.file_path.as_str(), c.event_type.as_str()))
+            .collect();
+        assert_eq!(summary, [("/a.md", "modify"), ("/b.md", "remove")]);
+    }
+
+    #[test]
+    fn keeps_unknown_frontmatter_keys_in_order() {
+        let text = "---\nid: n1\ntitle: Plan\nzeta: 1\ncreated: 2024-01-01T00:00:00Z\n\
+                    modified: 2024-01-01T00:00:00Z\nsources:\n- plan\ncolumn: todo\n\
+                    alpha: x\n---\n\nBody";
+        let note = parse_note_content(text, Path::new("/plan.md")).unwrap();
+        let saved = serialize_note(&note.frontmatter, &note.content);
+
+        let position = |key: &str| saved.find(&format!("\n{}:", key)).unwrap();
+        assert!(position("zeta") < position("sources"));
+        assert!(position("sources") < position("alpha"));
+        let reparsed = parse_note_content(&saved, Path::new("/plan.md")).unwrap();
+        assert_eq!(reparsed.frontmatter.extra, note.frontmatter.extra);
+        assert_eq!(reparsed.content, "Body");
+    }
+
+    #[test]
+    fn reads_single_alias_as_list() {
+        let text = "---\nid: n1\ntitle: Plan\ncreated: 2024-01-01T00:00:00Z\n\
+                    modified: 2024-01-01T00:00:00Z\ncolumn: todo\naliases: Roadmap\n---\n";
+        let note = parse_note_content(text, Path::new("/plan.md")).unwrap();
+        assert_eq!(note.frontmatter.aliases, ["Roadmap"]);
+        assert!(!note.frontmatter.extra.contains_key("aliases"));
+        assert_eq!(
+            clean_aliases(vec![" Roadmap ".into(), "roadmap".into(), String::new()]),
+            ["Roadmap"]
+        );
+    }
+
+    #[test]
+    fn synthesizes_metadata_for_plain_markdown() {
+        let text = "Intro\n\n# Launch plan #\n\n---\n\nMore";
+        let note = parse_note_content(text, Path::new("/vault/plan.md")).unwrap();
+        assert_eq!(note.frontmatter.title, "Launch plan");
+        assert_eq!(note.content, text);
+        let again = parse_note_content(text, Path::new("/vault/plan.md")).unwrap();
+        assert_eq!(again.frontmatter.id, note.frontmatter.id);
+
+        let plain = parse_note_content("just text", Path::new("/vault/ideas.md")).unwrap();
+        assert_eq!(plain.frontmatter.title, "ideas");
+        assert_ne!(plain.frontmatter.id, note.frontmatter.id);
+    }
+
+    #[test]
+    fn falls_back_to_first_heading_for_missing_title() {
+        let text = "---\nid: n1\ntitle: ''\ncreated: 2024-01-01T00:00:00Z\n\
+                    modified: 2024-01-01T00:00:00Z\ncolumn: todo\n---\n\n#tag\n# Roadmap\n";
+        let note = parse_note_content(text, Path::new("/plan.md")).unwrap();
+        assert_eq!(note.frontmatter.title, "Roadmap");
+        assert_eq!(
+            replace_first_heading("intro\r\n# Roadmap\r\nbody", "Plan").as_deref(),
+            Some("intro\r\n# Plan\r\nbody")
+        );
+        assert_eq!(replace_first_heading("no heading", "Plan"), None);
+    }
+
+    #[test]
+    fn falls_back_to_file_creation_for_bogus_created() {
+        let dir = std::env::temp_dir().join(format!("noteban-created-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("old.md");
+        fs::write(&path, "").unwrap();
+        let (created, _) = file_times(&path);
+
+        let header = "---\nid: n1\ntitle: Old\nmodified: 2099-01-01T00:00:00Z\ncolumn: todo\n";
+        for created_line in ["", "created: someday\n", "created: 1970-01-01T00:00:00Z\n"] {
+            let text = format!("{}{}---\n\nbody", header, created_line);
+            let note = parse_note_content(&text, &path).unwrap();
+            assert_eq!(note.frontmatter.created, created);
+        }
+        let text = format!("{}created: 2024-03-05\n---\n\nbody", header);
+        let note = parse_note_content(&text, &path).unwrap();
+        assert_eq!(
+            note.frontmatter.created.to_rfc3339(),
+            "2024-03-05T00:00:00+00:00"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn round_trips_toml_frontmatter() {
+        let text = "+++\ntitle = \"Launch\"\ndate = 2024-05-01T09:00:00Z\ndraft = true\n\
+                    tags = [\"site\"]\n\n[extra]\nauthor = \"ana\"\n+++\n\nBody";
+        let mut note = parse_note_content(text, Path::new("/blog/launch.md")).unwrap();
+        assert_eq!(note.frontmatter.format, FrontmatterFormat::Toml);
+        assert_eq!(note.frontmatter.title, "Launch");
+        assert_eq!(
+            note.frontmatter.date.as_deref(),
+            Some("2024-05-01T09:00:00Z")
+        );
+        assert_eq!(note.frontmatter.column, "todo");
+        assert_eq!(note.content, "Body");
+
+        note.frontmatter.column = "done".to_string();
+        let written = serialize_note(&note.frontmatter, &note.content);
+        assert!(written.starts_with("+++\n"));
+        assert!(written.contains("date = 2024-05-01T09:00:00Z\n"));
+        assert!(written.contains("draft = true\n"));
+        let reread = parse_note_content(&written, Path::new("/blog/launch.md")).unwrap();
+        assert_eq!(reread.frontmatter.id, note.frontmatter.id);
+        assert_eq!(reread.frontmatter.column, "done");
+        assert_eq!(reread.frontmatter.extra, note.frontmatter.extra);
+    }
+
+    #[test]
+    fn fills_missing_keys_of_yaml_frontmatter() {
+        let path = Path::new("/vault/reading.md");
+        let text = "---\ntags: [books]\naliases: Reading list\nstatus: open\n---\n\nBody";
+        let note = parse_note_content(text, path).unwrap();
+        assert_eq!(note.frontmatter.format, FrontmatterFormat::Yaml);
+        assert_eq!(note.frontmatter.id, synthesized_frontmatter("", path).id);
+        assert_eq!(note.frontmatter.title, "reading");
+        assert_eq!(note.frontmatter.column, "todo");
+        assert_eq!(note.frontmatter.tags, ["books"]);
+        assert_eq!(note.frontmatter.aliases, ["Reading list"]);
+        assert_eq!(note.frontmatter.extra.len(), 1);
+
+        // The id stays the same until the note is saved with it
+        let reread = parse_note_content(text, path).unwrap();
+        assert_eq!(reread.frontmatter.id, note.frontmatter.id);
+        let written = serialize_note(&note.frontmatter, &note.content);
+        let saved = parse_note_content(&written, path).unwrap();
+        assert_eq!(saved.frontmatter.id, note.frontmatter.id);
+        assert_eq!(saved.frontmatter.extra, note.frontmatter.extra);
+
+        assert!(parse_note_content("---\n- a list\n---\n", path).is_err());
+    }
+
+    #[test]
+    fn titles_tags_only_frontmatter_from_first_heading() {
+        let path = Path::new("/vault/imported.md");
+        let text = "---\ntags:\n  - obsidian\n---\n\n# Weekly Review\n\nNotes";
+        let note = parse_note_content(text, path).unwrap();
+        assert_eq!(note.frontmatter.title, "Weekly Review");
+        assert_eq!(note.frontmatter.tags, ["obsidian"]);
+        assert_eq!(note.content, "# Weekly Review\n\nNotes");
+    }
+
+    #[test]
+    fn normalizes_label_colors() {
+        assert_eq!(normalize_color(" Red ").unwrap().as_deref(), Some("red"));
+        assert_eq!(
+            normalize_color("#A1b2C3").unwrap().as_deref(),
+            Some("#a1b2c3")
+        );
+        assert_eq!(normalize_color("#fff").unwrap().as_deref(), Some("#fff"));
+        assert_eq!(normalize_color("  ").unwrap(), None);
+        assert!(normalize_color("#12345").is_err());
+        assert!(normalize_color("teal").is_err());
+    }
+
+    #[test]
+    fn sanitizes_icons() {
+        assert_eq!(sanitize_icon(" 🚀 ").unwrap().as_deref(), Some("🚀"));
+        assert_eq!(sanitize_icon("👩‍💻").unwrap().as_deref(), Some("👩‍💻"));
+        assert_eq!(sanitize_icon("").unwrap(), None);
+        assert!(sanitize_icon("two words").is_err());
+        assert!(sanitize_icon("\u{7}").is_err());
+        assert!(sanitize_icon(&"x".repeat(MAX_ICON_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn reads_priority_names_and_ranks() {
+        let priority = |value: &str| {
+            let text = format!(
+                "---\nid: n1\ntitle: Plan\ncreated: 2024-01-01T00:00:00Z\n\
+                 modified: 2024-01-01T00:00:00Z\ncolumn: todo\npriority: {}\n---\n",
+                value
+            );
+            parse_note_content(&text, Path::new("/plan.md"))
+                .unwrap()
+                .frontmatter
+                .priority
+        };
+        assert_eq!(priority("High"), Some(Priority::High));
+        assert_eq!(priority("4"), Some(Priority::Urgent));
+        assert_eq!(priority("'1'"), Some(Priority::Low));
+        assert_eq!(priority("someday"), None);
+    }
+
+    #[test]
+    fn locked_notes_reject_every_writer() {
+        use crate::commands::inbox::{triage_note, TriageDecision};
+        use crate::commands::{conflicts, history as versions, metadata};
+        use crate::test_support::{TestVault, TEST_PROFILE};
+
+        let vault = TestVault::new();
+        let locked = vault.note("work/plan.md", "plan", "locked: true\n");
+        let original = vault.read("work/plan.md");
+        let conflict = vault.note("work/plan.conflict-20240101.md", "copy", "");
+        let notes_dir = || vault.notes_dir();
+        let is_locked_error =
+            |result: Result<(), String>| result.is_err_and(|e| e.contains("is locked"));
+
+        assert!(is_locked_error(
+            set_note_priority(
+                notes_dir(),
+                locked.clone(),
+                Some(Priority::High),
+                vault.state()
+            )
+            .map(|_| ())
+        ));
+        assert!(is_locked_error(
+            metadata::set_note_metadata(
+                notes_dir(),
+                locked.clone(),
+                "status".to_string(),
+                serde_json::json!("done"),
+                vault.state(),
+            )
+            .map(|_| ())
+        ));
+        assert!(is_locked_error(
+            triage_note(
+                notes_dir(),
+                locked.clone(),
+                TriageDecision::Schedule {
+                    date: "2024-02-01".to_string(),
+                    column: None,
+                },
+                vault.state(),
+            )
+            .map(|_| ())
+        ));
+
+        let version = crate::history::save_snapshot(TEST_PROFILE, "plan", "old text", None)
+            .unwrap()
+            .unwrap();
+        assert!(is_locked_error(
+            versions::restore_version(notes_dir(), locked.clone(), version.id, vault.state())
+                .map(|_| ())
+        ));
+        assert!(is_locked_error(
+            conflicts::merge_conflict(notes_dir(), locked.clone(), conflict, vault.state())
+                .map(|_| ())
+        ));
+        assert!(is_locked_error(
+            rename_folder(
+                notes_dir(),
+                vault.path("work"),
+                "done".to_string(),
+                vault.state()
+            )
+            .map(|_| ())
+        ));
+        assert!(is_locked_error(delete_folder(
+            notes_dir(),
+            vault.path("work"),
+            vault.state()
+        )));
+
+        assert_eq!(vault.read("work/plan.md"), original);
+        assert!(vault.exists("work/plan.conflict-20240101.md"));
+    }
+
+    #[test]
+    fn saves_over_a_stale_index_when_the_base_matches() {
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        let file_path = vault.note("a.md", "a", "");
+        let on_disk = vault.read("a.md");
+        {
+            // The index recorded an older version, as after a missed watcher event
+            let state = vault.state();
+            let cache_lock = state.cache.lock().unwrap();
+            let note = parse_note_content(&on_disk, Path::new(&file_path)).unwrap();
+            let cache = cache_lock.as_ref().unwrap();
+            cache.upsert_note(&note, "stale", 0, &[]).unwrap();
+        }
+        let update = |content: &str, base_hash: Option<String>| {
+            update_note(
+                UpdateNoteInput {
+                    notes_dir: vault.notes_dir(),
+                    file_path: file_path.clone(),
+                    title: None,
+                    content: Some(content.to_string()),
+                    date: None,
+                    due: None,
+                    column: None,
+                    tags: None,
+                    order: None,
+                    aliases: None,
+                    base_modified: None,
+                    base_hash,
+                    force: false,
+                },
+                vault.state(),
+            )
+            .unwrap()
+        };
+
+        let saved = update("Edited", Some(compute_content_hash(&on_disk)));
+        assert_eq!(saved.note.file_path, file_path);
+        assert!(vault.read("a.md").contains("Edited"));
+
+        // Without a base the stale index still counts as an external edit
+        {
+            let state = vault.state();
+            let cache_lock = state.cache.lock().unwrap();
+            let note = parse_note_content(&vault.read("a.md"), Path::new(&file_path)).unwrap();
+            let cache = cache_lock.as_ref().unwrap();
+            cache.upsert_note(&note, "stale", 0, &[]).unwrap();
+        }
+        let copy = update("Again", None);
+        assert_ne!(copy.note.file_path, file_path);
+        assert!(!vault.read("a.md").contains("Again"));
+    }
+
+    #[test]
+    fn rebuilds_cache_without_holding_the_lock_while_parsing() {
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        vault.note("a.md", "a", "");
+        vault.note("work/b.md", "b", "");
+        vault.write("broken.md", "---\nid: [unclosed\n---\n");
+        let state = vault.state();
+
+        let mut parsed = 0;
+        let cached = rebuild_note_cache(&vault.notes_dir(), &state, &mut |count| {
+            assert!(state.cache.try_lock().is_ok());
+            parsed = count;
+        })
+        .unwrap();
+
+        assert_eq!((parsed, cached), (2, 2));
+        let cache_lock = state.cache.lock().unwrap();
+        let cache = cache_lock.as_ref().unwrap();
+        assert!(cache.get_note(&vault.path("work/b.md")).unwrap().is_some());
+        assert!(cache.get_note(&vault.path("broken.md")).unwrap().is_none());
+    }
+
+    #[test]
+    fn idempotency_keys_survive_moves_of_the_created_note() {
+        use crate::test_support::TestVault;
+
+        let vault = TestVault::new();
+        let create = || {
+            create_note(
+                CreateNoteInput {
+                    notes_dir: vault.notes_dir(),
+                    folder_path: None,
+                    title: "Captured".to_string(),
+                    content: None,
+                    date: None,
+                    due: None,
+                    column: None,
+                    tags: None,
+                    idempotency_key: Some("capture-1".to_string()),
+                },
+                vault.state(),
+            )
+            .unwrap()
+            .note
+        };
+
+        let first = create();
+        let moved = move_note(
+            vault.notes_dir(),
+            first.file_path.clone(),
+            "inbox".to_string(),
+            None,
+            vault.state(),
+        )
+        .unwrap();
+        let retried = create();
+        assert_eq!(retried.frontmatter.id, first.frontmatter.id);
+        assert_eq!(retried.file_path, moved.file_path);
+        assert!(!vault.exists("captured.md"));
+
+        // Once the note is gone the key creates a new one
+        fs::remove_file(&moved.file_path).unwrap();
+        let recreated = create();
+        assert_ne!(recreated.frontmatter.id, first.frontmatter.id);
+        assert!(vault.exists("captured.md"));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::commands::notes::FileChangeEvent;
+use crate::commands::trash::TRASH_DIR_NAME;
+use crate::commands::watch;
+use crate::lock_or_err;
+use crate::sync_meta::SYNC_META_DIR;
+use crate::vault_config::VAULT_CONFIG_DIR;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State};
+use walkdir::{DirEntry, WalkDir};
+
+pub(crate) const WATCH_MODE_KEY: &str = "watch_mode";
+/// Time between two scans of a polled vault
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Filesystems that don't deliver change notifications for edits made by
+/// other machines
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "webdav",
+    "davfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+/// Bumped on every start and stop so a superseded poller exits on its next tick
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// How external changes to the vault are picked up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    /// Poll vaults on network filesystems, use native events elsewhere
+    #[default]
+    Auto,
+    Native,
+    Polling,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Seen {
+    modified: Option<SystemTime>,
+    len: u64,
+    is_dir: bool,
+}
+
+type Snapshot = BTreeMap<PathBuf, Seen>;
+
+/// Hidden folders are skipped except the vault configuration and attachments
+fn is_polled_dir(entry: &DirEntry) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    if name == TRASH_DIR_NAME || name == SYNC_META_DIR {
+        return false;
+    }
+    !name.starts_with('.') || name == VAULT_CONFIG_DIR || name.ends_with(".attachments")
+}
+
+/// The paths the native watcher reports to the backend: notes, attachments,
+/// configuration files and folders
+fn is_polled_file(root: &Path, path: &Path) -> bool {
+    if path.extension().is_some_and(|ext| ext == "md") {
+        return true;
+    }
+    path.strip_prefix(root).is_ok_and(|relative| {
+        relative.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy();
+            name == VAULT_CONFIG_DIR || name.ends_with(".attachments")
+        })
+    })
+}
+
+fn snapshot(root: &Path) -> Snapshot {
+    WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !entry.file_type().is_dir() || is_polled_dir(entry))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir() || is_polled_file(root, entry.path()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((
+                entry.into_path(),
+                Seen {
+                    modified: metadata.modified().ok(),
+                    len: metadata.len(),
+                    is_dir: metadata.is_dir(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Watcher events that turn `before` into `after`
+fn diff_snapshots(before: &Snapshot, after: &Snapshot) -> Vec<FileChangeEvent> {
+    let event = |event_type: &str, path: &Path| FileChangeEvent {
+        event_type: event_type.to_string(),
+        file_path: path.to_string_lossy().to_string(),
+    };
+    let mut changes: Vec<FileChangeEvent> = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .map(|path| event("remove", path))
+        .collect();
+    for (path, seen) in after {
+        match before.get(path) {
+            None => changes.push(event("create", path)),
+            Some(previous) if !seen.is_dir && previous != seen => {
+                changes.push(event("modify", path))
+            }
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+fn is_network_fs_type(fs_type: &str) -> bool {
+    NETWORK_FILESYSTEMS.contains(&fs_type)
+}
+
+/// Filesystem type of the deepest mount point containing `path`
+fn mount_fs_type<'a>(mounts: &'a [(PathBuf, String)], path: &Path) -> Option<&'a str> {
+    mounts
+        .iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type.as_str())
+}
+
+#[cfg(target_os = "linux")]
+fn mounts() -> Vec<(PathBuf, String)> {
+    let Ok(table) = std::fs::read_to_string("/proc/self/mounts") else {
+        return Vec::new();
+    };
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces in mount points are escaped as \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn mounts() -> Vec<(PathBuf, String)> {
+    let Ok(output) = std::process::Command::new("mount").output() else {
+        return Vec::new();
+    };
+    // Lines look like `//user@host/share on /Volumes/share (smbfs, nodev, ...)`
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn mounts() -> Vec<(PathBuf, String)> {
+    Vec::new()
+}
+
+/// Whether `notes_dir` is on a network share, where native change events
+/// only cover edits made from this machine
+pub(crate) fn is_network_filesystem(notes_dir: &Path) -> bool {
+    let path = notes_dir
+        .canonicalize()
+        .unwrap_or_else(|_| notes_dir.to_path_buf());
+    if cfg!(windows) {
+        let path = path.to_string_lossy();
+        return path.starts_with(r"\\?\UNC\")
+            || (path.starts_with(r"\\") && !path.starts_with(r"\\?\"));
+    }
+    mount_fs_type(&mounts(), &path).is_some_and(is_network_fs_type)
+}
+
+fn load_watch_mode(state: &AppState) -> Result<WatchMode, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let Some(cache) = cache_lock.as_ref() else {
+        return Ok(WatchMode::default());
+    };
+    Ok(cache
+        .get_meta(WATCH_MODE_KEY)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+fn poll(app: AppHandle, notes_dir: String, generation: u64) {
+    let root = PathBuf::from(&notes_dir);
+    let mut previous = snapshot(&root);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let current = snapshot(&root);
+        let changes = diff_snapshots(&previous, &current);
+        previous = current;
+        if !changes.is_empty() {
+            watch::process_and_emit(&app, &notes_dir, changes, &app.state::<AppState>());
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_watch_mode(state: State<AppState>) -> Result<WatchMode, String> {
+    load_watch_mode(&state)
+}
+
+#[tauri::command]
+pub fn set_watch_mode(mode: WatchMode, state: State<AppState>) -> Result<WatchMode, String> {
+    let encoded =
+        serde_json::to_string(&mode).map_err(|e| format!("Failed to encode watch mode: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(WATCH_MODE_KEY, &encoded)?;
+    Ok(mode)
+}
+
+/// Start polling `notes_dir` for changes when the watch mode asks for it,
+/// replacing any previous poller. Returns the id of the new poller, or `None`
+/// when the caller should use a native watcher instead. Changes found by
+/// polling are emitted as `notes://changes`.
+#[tauri::command]
+pub fn start_polling_watcher(
+    notes_dir: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<Option<u64>, String> {
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    if state.safe_mode {
+        return Ok(None);
+    }
+    let polling = match load_watch_mode(&state)? {
+        WatchMode::Native => false,
+        WatchMode::Polling => true,
+        WatchMode::Auto => is_network_filesystem(Path::new(&notes_dir)),
+    };
+    if !polling {
+        return Ok(None);
+    }
+    log::info!("Polling {} for changes", notes_dir);
+    thread::spawn(move || poll(app, notes_dir, generation));
+    Ok(Some(generation))
+}
+
+/// Stop the poller started as `poll_id`; a poller started after it keeps
+/// running
+#[tauri::command]
+pub fn stop_polling_watcher(poll_id: u64) {
+    let _ = GENERATION.compare_exchange(poll_id, poll_id + 1, Ordering::SeqCst, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_deepest_mount_for_path() {
+        let mounts = vec![
+            (PathBuf::from("/"), "ext4".to_string()),
+            (PathBuf::from("/mnt/share"), "cifs".to_string()),
+            (PathBuf::from("/mnt/share/local"), "ext4".to_string()),
+        ];
+        let fs_type = |path: &str| mount_fs_type(&mounts, Path::new(path));
+        assert_eq!(fs_type("/mnt/share/notes"), Some("cifs"));
+        assert_eq!(fs_type("/mnt/share/local/notes"), Some("ext4"));
+        assert_eq!(fs_type("/mnt/shared"), Some("ext4"));
+        assert!(is_network_fs_type("nfs4"));
+        assert!(!is_network_fs_type("ext4"));
+    }
+
+    #[test]
+    fn diffs_snapshots_into_change_events() {
+        let seen = |len| Seen {
+            modified: None,
+            len,
+            is_dir: false,
+        };
+        let before: Snapshot = [
+            (PathBuf::from("/v/a.md"), seen(1)),
+            (PathBuf::from("/v/b.md"), seen(1)),
+        ]
+        .into_iter()
+        .collect();
+        let after: Snapshot = [
+            (PathBuf::from("/v/a.md"), seen(2)),
+            (PathBuf::from("/v/c.md"), seen(1)),
+        ]
+        .into_iter()
+        .collect();
+        let changes: Vec<(String, String)> = diff_snapshots(&before, &after)
+            .into_iter()
+            .map(|change| (change.event_type, change.file_path))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("remove".to_string(), "/v/b.md".to_string()),
+                ("modify".to_string(), "/v/a.md".to_string()),
+                ("create".to_string(), "/v/c.md".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::cache::macros::SavedMacro;
+use crate::cache::views::{compile_order_by, SavedView};
+use crate::commands::backup::BACKUP_CONFIG_KEY;
+use crate::commands::console::ADVANCED_MODE_KEY;
+use crate::commands::inbox::INBOX_CONFIG_KEY;
+use crate::commands::macros::validate_macro;
+use crate::commands::notes::atomic_write;
+use crate::commands::references::CODE_REFERENCE_CONFIG_KEY;
+use crate::commands::sync::SELECTIVE_SYNC_KEY;
+use crate::commands::sync_schedule::SYNC_SCHEDULE_KEY;
+use crate::commands::trash::TRASH_RETENTION_KEY;
+use crate::commands::views::validate_view_name;
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::State;
+
+const PROFILE_CONFIG_FORMAT: &str = "noteban-profile-config";
+const PROFILE_CONFIG_VERSION: u32 = 1;
+
+/// Preferences kept in the cache that describe the user's setup rather than
+/// the state of this machine's cache
+const PORTABLE_META_KEYS: [&str; 7] = [
+    BACKUP_CONFIG_KEY,
+    INBOX_CONFIG_KEY,
+    CODE_REFERENCE_CONFIG_KEY,
+    TRASH_RETENTION_KEY,
+    ADVANCED_MODE_KEY,
+    SELECTIVE_SYNC_KEY,
+    SYNC_SCHEDULE_KEY,
+];
+
+/// A profile's setup in one portable file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    /// Frontend settings of the profile (board columns, editor, theme, ...)
+    #[serde(default)]
+    pub settings: Value,
+    /// Backend preferences by cache key, in their stored encoding
+    #[serde(default)]
+    pub preferences: BTreeMap<String, String>,
+    #[serde(default)]
+    pub saved_views: Vec<SavedView>,
+    #[serde(default)]
+    pub macros: Vec<SavedMacro>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileConfigImport {
+    /// Frontend settings for the caller to apply
+    pub settings: Value,
+    pub preferences_imported: usize,
+    pub views_imported: usize,
+    pub macros_imported: usize,
+    /// Entries left out because they are unknown or invalid
+    pub skipped: Vec<String>,
+}
+
+/// Parse a profile configuration file and drop the entries this version
+/// cannot apply, recording them in the returned list
+fn parse_profile_config(bytes: &[u8]) -> Result<(ProfileConfig, Vec<String>), String> {
+    let mut config: ProfileConfig = serde_json::from_slice(bytes)
+        .map_err(|e| format!("Not a profile configuration file: {}", e))?;
+    if config.format != PROFILE_CONFIG_FORMAT {
+        return Err("Not a profile configuration file".to_string());
+    }
+    if config.version > PROFILE_CONFIG_VERSION {
+        return Err(format!(
+            "Profile configuration version {} is newer than this app supports",
+            config.version
+        ));
+    }
+
+    let mut skipped = Vec::new();
+    config.preferences.retain(|key, _| {
+        let known = PORTABLE_META_KEYS.contains(&key.as_str());
+        if !known {
+            skipped.push(format!("preference {}", key));
+        }
+        known
+    });
+    config.saved_views.retain(|view| {
+        let valid = validate_view_name(&view.name).is_ok() && compile_order_by(&view.sort).is_ok();
+        if !valid {
+            skipped.push(format!("view {}", view.name));
+        }
+        valid
+    });
+    config.macros.retain(|saved| {
+        let valid = validate_macro(&saved.name, &saved.definition).is_ok();
+        if !valid {
+            skipped.push(format!("macro {}", saved.name));
+        }
+        valid
+    });
+    Ok((config, skipped))
+}
+
+/// Write the active profile's setup to `dest`: the frontend `settings` passed
+/// in, backend preferences, saved views and macros. Machine state such as sync
+/// status, mounts and history stays behind.
+#[tauri::command]
+pub fn export_profile_config(
+    dest: String,
+    settings: Value,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let config = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        let mut preferences = BTreeMap::new();
+        for key in PORTABLE_META_KEYS {
+            if let Some(value) = cache.get_meta(key)? {
+                preferences.insert(key.to_string(), value);
+            }
+        }
+        ProfileConfig {
+            format: PROFILE_CONFIG_FORMAT.to_string(),
+            version: PROFILE_CONFIG_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            settings,
+            preferences,
+            saved_views: cache.get_views()?,
+            macros: cache.get_macros()?,
+        }
+    };
+
+    let dest_path = PathBuf::from(&dest);
+    if let Some(parent) = dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+    let encoded = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to encode profile configuration: {}", e))?;
+    atomic_write(&dest_path, &encoded)?;
+    Ok(dest)
+}
+
+/// Apply a file written by `export_profile_config` to the active profile.
+/// Views and macros with the same name are replaced; the frontend settings
+/// are returned for the caller to merge into the profile.
+#[tauri::command]
+pub fn import_profile_config(
+    path: String,
+    state: State<AppState>,
+) -> Result<ProfileConfigImport, String> {
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("Failed to read profile configuration: {}", e))?;
+    let (config, skipped) = parse_profile_config(&bytes)?;
+
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    for (key, value) in &config.preferences {
+        cache.set_meta(key, value)?;
+    }
+    for view in &config.saved_views {
+        cache.save_view(view.name.trim(), &view.sort)?;
+    }
+    for saved in &config.macros {
+        cache.save_macro(saved.name.trim(), &saved.definition)?;
+    }
+
+    Ok(ProfileConfigImport {
+        settings: config.settings,
+        preferences_imported: config.preferences.len(),
+        views_imported: config.saved_views.len(),
+        macros_imported: config.macros.len(),
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn drops_unknown_and_invalid_entries() {
+        let file = json!({
+            "format": PROFILE_CONFIG_FORMAT,
+            "version": 1,
+            "exported_at": "2024-01-01T00:00:00Z",
+            "settings": { "theme": "dark" },
+            "preferences": { "inbox_config": "{}", "sync_status": "{}" },
+            "saved_views": [
+                { "name": "Recent", "sort": [{ "field": "modified" }], "updated_at": "" },
+                { "name": "Broken", "sort": [{ "field": "nope" }], "updated_at": "" }
+            ],
+            "macros": [
+                { "name": "Bad", "steps": [{ "command": "empty_trash" }], "updated_at": "" }
+            ]
+        });
+        let (config, skipped) = parse_profile_config(file.to_string().as_bytes()).unwrap();
+        assert_eq!(config.settings["theme"], "dark");
+        assert_eq!(
+            config.preferences.keys().collect::<Vec<_>>(),
+            ["inbox_config"]
+        );
+        assert_eq!(config.saved_views.len(), 1);
+        assert!(config.macros.is_empty());
+        assert_eq!(
+            skipped,
+            ["preference sync_status", "view Broken", "macro Bad"]
+        );
+
+        let newer = json!({ "format": PROFILE_CONFIG_FORMAT, "version": 2, "exported_at": "" });
+        assert!(parse_profile_config(newer.to_string().as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::crypto;
+use crate::lock_or_err;
+use crate::AppState;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::ipc::InvokeMessage;
+use tauri::{Manager, Runtime, State};
+
+const LOCK_FILE_NAME: &str = "profile-lock.json";
+/// Plaintext encrypted under the profile key to verify a passphrase
+const PASSPHRASE_CHECK: &[u8] = b"noteban-profile";
+
+/// Commands that keep working while the active profile is locked
+const UNLOCKED_COMMANDS: [&str; 7] = [
+    "get_profile_lock",
+    "set_profile_password",
+    "unlock_profile",
+    "lock_profile",
+    "initialize_cache",
+    "get_initial_profile",
+    "get_safe_mode",
+];
+
+/// Lock state of the password-protected profile in use, if any
+#[derive(Debug, Default)]
+pub struct ProfileLockState {
+    profile_id: Option<String>,
+    /// Last command while unlocked; `None` while locked
+    last_activity: Option<Instant>,
+    auto_lock: Option<Duration>,
+}
+
+impl ProfileLockState {
+    fn is_unlocked(&self, now: Instant) -> bool {
+        self.last_activity.is_some_and(|last| {
+            self.auto_lock
+                .map_or(true, |timeout| now.duration_since(last) < timeout)
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileLockFile {
+    /// Argon2 salt, hex
+    salt: String,
+    /// `PASSPHRASE_CHECK` encrypted under the derived key, hex
+    check: String,
+    #[serde(default)]
+    auto_lock_minutes: Option<u32>,
+}
+
+impl ProfileLockFile {
+    fn new(passphrase: &str, auto_lock_minutes: Option<u32>) -> Result<Self, String> {
+        let salt = crypto::random_salt();
+        let key = crypto::derive_key(passphrase, &salt)?;
+        Ok(Self {
+            salt: crypto::to_hex(&salt),
+            check: crypto::to_hex(&crypto::encrypt(&key, PASSPHRASE_CHECK)?),
+            auto_lock_minutes,
+        })
+    }
+
+    fn verify(&self, passphrase: &str) -> Result<(), String> {
+        let key = crypto::derive_key(passphrase, &crypto::from_hex(&self.salt)?)?;
+        match crypto::decrypt(&key, &crypto::from_hex(&self.check)?) {
+            Ok(check) if check == PASSPHRASE_CHECK => Ok(()),
+            _ => Err("Wrong passphrase".to_string()),
+        }
+    }
+
+    fn auto_lock(&self) -> Option<Duration> {
+        self.auto_lock_minutes
+            .map(|minutes| Duration::from_secs(u64::from(minutes.max(1)) * 60))
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_minutes: Option<u32>,
+}
+
+fn lock_path(profile_id: &str) -> Result<PathBuf, String> {
+    let proj_dirs =
+        ProjectDirs::from("", "", "noteban").ok_or("Could not determine data directory")?;
+    Ok(proj_dirs.data_dir().join(profile_id).join(LOCK_FILE_NAME))
+}
+
+fn read_lock(profile_id: &str) -> Result<Option<ProfileLockFile>, String> {
+    let path = lock_path(profile_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read profile lock: {}", e))?;
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse profile lock: {}", e))
+}
+
+fn lock_now(state: &AppState, lock: &mut ProfileLockState) {
+    lock.last_activity = None;
+    // Close the cache so nothing of the profile stays readable
+    if let Ok(mut cache) = lock_or_err(&state.cache) {
+        *cache = None;
+    }
+}
+
+/// Refuse to open a password-protected profile that has not been unlocked
+pub(crate) fn require_unlocked(state: &AppState, profile_id: &str) -> Result<(), String> {
+    let Some(file) = read_lock(profile_id)? else {
+        *lock_or_err(&state.profile_lock)? = ProfileLockState::default();
+        return Ok(());
+    };
+    let mut lock = lock_or_err(&state.profile_lock)?;
+    if lock.profile_id.as_deref() == Some(profile_id) && lock.is_unlocked(Instant::now()) {
+        return Ok(());
+    }
+    // Arm the lock so commands that work from disk are refused as well
+    *lock = ProfileLockState {
+        profile_id: Some(profile_id.to_string()),
+        last_activity: None,
+        auto_lock: file.auto_lock(),
+    };
+    Err("Profile is locked".to_string())
+}
+
+/// Gate every app command on the active profile being unlocked, locking it
+/// once it has been idle past its auto-lock timeout
+pub(crate) fn check_command<R: Runtime>(message: &InvokeMessage<R>) -> Result<(), String> {
+    let state = message.webview_ref().state::<AppState>();
+    check_command_name(&state, message.command())
+}
+
+fn check_command_name(state: &AppState, command: &str) -> Result<(), String> {
+    if UNLOCKED_COMMANDS.contains(&command) {
+        return Ok(());
+    }
+    let mut lock = lock_or_err(&state.profile_lock)?;
+    if lock.profile_id.is_none() {
+        return Ok(());
+    }
+    let now = Instant::now();
+    if lock.is_unlocked(now) {
+        lock.last_activity = Some(now);
+        return Ok(());
+    }
+    if lock.last_activity.is_some() {
+        lock_now(state, &mut lock);
+    }
+    Err("Profile is locked".to_string())
+}
+
+#[tauri::command]
+pub fn get_profile_lock(
+    profile_id: String,
+    state: State<AppState>,
+) -> Result<ProfileLockStatus, String> {
+    let file = read_lock(&profile_id)?;
+    let lock = lock_or_err(&state.profile_lock)?;
+    let unlocked =
+        lock.profile_id.as_deref() == Some(profile_id.as_str()) && lock.is_unlocked(Instant::now());
+    Ok(ProfileLockStatus {
+        enabled: file.is_some(),
+        locked: file.is_some() && !unlocked,
+        auto_lock_minutes: file.and_then(|file| file.auto_lock_minutes),
+    })
+}
+
+/// Set, change or (with no `passphrase`) remove a profile's password. An
+/// existing password must be given as `current_passphrase`.
+#[tauri::command]
+pub fn set_profile_password(
+    profile_id: String,
+    current_passphrase: Option<String>,
+    passphrase: Option<String>,
+    auto_lock_minutes: Option<u32>,
+    state: State<AppState>,
+) -> Result<ProfileLockStatus, String> {
+    if let Some(existing) = read_lock(&profile_id)? {
+        existing.verify(current_passphrase.as_deref().unwrap_or_default())?;
+    }
+    let path = lock_path(&profile_id)?;
+    let mut lock = lock_or_err(&state.profile_lock)?;
+    let enabled = match passphrase.filter(|passphrase| !passphrase.is_empty()) {
+        Some(passphrase) => {
+            let file = ProfileLockFile::new(&passphrase, auto_lock_minutes)?;
+            let encoded = serde_json::to_vec_pretty(&file)
+                .map_err(|e| format!("Failed to encode profile lock: {}", e))?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+            }
+            fs::write(&path, encoded)
+                .map_err(|e| format!("Failed to write profile lock: {}", e))?;
+            *lock = ProfileLockState {
+                profile_id: Some(profile_id),
+                last_activity: Some(Instant::now()),
+                auto_lock: file.auto_lock(),
+            };
+            true
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove profile lock: {}", e))?;
+            }
+            if lock.profile_id.as_deref() == Some(profile_id.as_str()) {
+                *lock = ProfileLockState::default();
+            }
+            false
+        }
+    };
+    Ok(ProfileLockStatus {
+        enabled,
+        locked: false,
+        auto_lock_minutes: auto_lock_minutes.filter(|_| enabled),
+    })
+}
+
+#[tauri::command]
+pub fn unlock_profile(
+    profile_id: String,
+    passphrase: String,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let file = read_lock(&profile_id)?.ok_or("Profile has no password")?;
+    file.verify(&passphrase)?;
+    *lock_or_err(&state.profile_lock)? = ProfileLockState {
+        profile_id: Some(profile_id),
+        last_activity: Some(Instant::now()),
+        auto_lock: file.auto_lock(),
+    };
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_profile(state: State<AppState>) -> Result<(), String> {
+    let mut lock = lock_or_err(&state.profile_lock)?;
+    if lock.profile_id.is_none() {
+        return Err("Profile has no password".to_string());
+    }
+    lock_now(&state, &mut lock);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+
+    #[test]
+    fn verifies_passphrase_and_times_out() {
+        let file = ProfileLockFile::new("family", Some(5)).unwrap();
+        assert!(file.verify("family").is_ok());
+        assert!(file.verify("guess").is_err());
+
+        let start = Instant::now();
+        let lock = ProfileLockState {
+            profile_id: Some("p1".to_string()),
+            last_activity: Some(start),
+            auto_lock: file.auto_lock(),
+        };
+        assert!(lock.is_unlocked(start + Duration::from_secs(4 * 60)));
+        assert!(!lock.is_unlocked(start + Duration::from_secs(5 * 60)));
+        assert!(!ProfileLockState::default().is_unlocked(start));
+    }
+
+    #[test]
+    fn refuses_commands_for_a_profile_locked_at_startup() {
+        let vault = TestVault::new();
+        let state = vault.state();
+        let profile_id = format!("locked-{}", uuid::Uuid::new_v4());
+        set_profile_password(
+            profile_id.clone(),
+            None,
+            Some("family".to_string()),
+            None,
+            vault.state(),
+        )
+        .unwrap();
+        // Restarted: nothing has been unlocked yet
+        *state.profile_lock.lock().unwrap() = ProfileLockState::default();
+
+        assert!(require_unlocked(&state, &profile_id).is_err());
+        assert!(check_command_name(&state, "list_notes").is_err());
+        assert!(check_command_name(&state, "update_note").is_err());
+        assert!(check_command_name(&state, "unlock_profile").is_ok());
+
+        assert!(unlock_profile(profile_id.clone(), "guess".to_string(), vault.state()).is_err());
+        assert!(check_command_name(&state, "list_notes").is_err());
+        unlock_profile(profile_id.clone(), "family".to_string(), vault.state()).unwrap();
+        assert!(require_unlocked(&state, &profile_id).is_ok());
+        assert!(check_command_name(&state, "list_notes").is_ok());
+    }
+}
@@ -0,0 +1,259 @@
+use crate::journal::RecoveredOperation;
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+
+const SESSION_FILE_NAME: &str = "session.json";
+/// Prefix of the directories `atomicwrites` stages files in
+const ATOMIC_WRITE_PREFIX: &str = ".atomicwrite";
+const RESTORE_PARTIAL_SUFFIX: &str = ".restore-partial";
+
+/// Written when a profile is opened and removed on clean exit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionMarker {
+    pid: u32,
+    started_at: DateTime<Utc>,
+}
+
+/// What startup found and repaired after an unclean shutdown
+#[derive(Debug, Clone, Default)]
+pub struct StartupRecovery {
+    pub crashed_session_started_at: Option<DateTime<Utc>>,
+    /// The cache failed its integrity check and was emptied
+    pub cache_invalidated: bool,
+    pub recovered_operations: Vec<RecoveredOperation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryFix {
+    /// Delete leftover staging files of interrupted writes and restores
+    RemoveTempFiles,
+    /// Drop every cached entry so the next listing re-reads the vault
+    RebuildCache,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Start of the session that did not shut down cleanly
+    pub crashed_session_started_at: Option<DateTime<Utc>>,
+    pub cache_invalidated: bool,
+    pub recovered_operations: Vec<RecoveredOperation>,
+    pub stranded_temp_files: Vec<String>,
+    pub fixes: Vec<RecoveryFix>,
+}
+
+fn session_marker_path(profile_id: &str) -> Result<PathBuf, String> {
+    let proj_dirs =
+        ProjectDirs::from("", "", "noteban").ok_or("Could not determine data directory")?;
+    Ok(proj_dirs
+        .data_dir()
+        .join(profile_id)
+        .join(SESSION_FILE_NAME))
+}
+
+/// Whether `pid` still runs. Only Linux can tell cheaply; elsewhere another
+/// process is assumed to be gone.
+fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Path::new(&format!("/proc/{}", pid)).exists()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+/// Record a new session in `marker`, returning the start of the previous
+/// session when it never removed its marker
+fn begin_session_at(marker: &Path) -> Option<DateTime<Utc>> {
+    let previous: Option<SessionMarker> = fs::read(marker)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+    let crashed = previous
+        .filter(|session| !process_alive(session.pid))
+        .map(|session| session.started_at);
+
+    let current = SessionMarker {
+        pid: std::process::id(),
+        started_at: Utc::now(),
+    };
+    let written = marker
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(marker, serde_json::to_vec(&current).unwrap_or_default()));
+    if let Err(e) = written {
+        log::warn!("Failed to write session marker: {}", e);
+    }
+    crashed
+}
+
+pub(crate) fn begin_session(profile_id: &str) -> Option<DateTime<Utc>> {
+    session_marker_path(profile_id)
+        .ok()
+        .and_then(|marker| begin_session_at(&marker))
+}
+
+/// Mark the profile's session as cleanly closed
+pub(crate) fn end_session(profile_id: &str) {
+    let Ok(marker) = session_marker_path(profile_id) else {
+        return;
+    };
+    if let Err(e) = fs::remove_file(&marker) {
+        if marker.exists() {
+            log::warn!("Failed to clear session marker: {}", e);
+        }
+    }
+}
+
+/// Staging files left in the vault by writes or restores that never finished
+fn find_stranded_temp_files(notes_dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(notes_dir).min_depth(1).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy();
+        if entry.file_type().is_dir() {
+            if name.starts_with(ATOMIC_WRITE_PREFIX) {
+                found.push(entry.path().to_path_buf());
+                walker.skip_current_dir();
+            } else if name == ".git" {
+                walker.skip_current_dir();
+            }
+        } else if name.ends_with(RESTORE_PARTIAL_SUFFIX) {
+            found.push(entry.path().to_path_buf());
+        }
+    }
+    found.sort();
+    found
+}
+
+fn build_report(recovery: &StartupRecovery, notes_dir: &Path) -> RecoveryReport {
+    let stranded_temp_files: Vec<String> = find_stranded_temp_files(notes_dir)
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    let mut fixes = Vec::new();
+    if !stranded_temp_files.is_empty() {
+        fixes.push(RecoveryFix::RemoveTempFiles);
+    }
+    if recovery.crashed_session_started_at.is_some() && !recovery.cache_invalidated {
+        fixes.push(RecoveryFix::RebuildCache);
+    }
+    RecoveryReport {
+        crashed_session_started_at: recovery.crashed_session_started_at,
+        cache_invalidated: recovery.cache_invalidated,
+        recovered_operations: recovery.recovered_operations.clone(),
+        stranded_temp_files,
+        fixes,
+    }
+}
+
+/// Findings of the last startup if the previous session crashed or startup
+/// had to repair something; `None` when there is nothing to tell the user
+#[tauri::command]
+pub fn get_recovery_report(
+    notes_dir: String,
+    state: State<AppState>,
+) -> Result<Option<RecoveryReport>, String> {
+    let recovery = lock_or_err(&state.startup_recovery)?;
+    let Some(recovery) = recovery.as_ref() else {
+        return Ok(None);
+    };
+    let report = build_report(recovery, Path::new(&notes_dir));
+    let noteworthy = report.crashed_session_started_at.is_some()
+        || report.cache_invalidated
+        || !report.recovered_operations.is_empty()
+        || !report.stranded_temp_files.is_empty();
+    Ok(noteworthy.then_some(report))
+}
+
+/// Apply one of the fixes offered by the recovery report and return the
+/// refreshed report
+#[tauri::command]
+pub fn apply_recovery_fix(
+    notes_dir: String,
+    fix: RecoveryFix,
+    state: State<AppState>,
+) -> Result<Option<RecoveryReport>, String> {
+    match fix {
+        RecoveryFix::RemoveTempFiles => {
+            for path in find_stranded_temp_files(Path::new(&notes_dir)) {
+                let removed = if path.is_dir() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+                if let Err(e) = removed {
+                    return Err(format!("Failed to remove {}: {}", path.display(), e));
+                }
+            }
+        }
+        RecoveryFix::RebuildCache => {
+            {
+                let cache_lock = lock_or_err(&state.cache)?;
+                let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+                cache.invalidate_all()?;
+            }
+            if let Some(recovery) = lock_or_err(&state.startup_recovery)?.as_mut() {
+                recovery.cache_invalidated = true;
+            }
+        }
+    }
+    get_recovery_report(notes_dir, state)
+}
+
+/// Forget the startup findings once the user has seen them
+#[tauri::command]
+pub fn dismiss_recovery_report(state: State<AppState>) -> Result<(), String> {
+    *lock_or_err(&state.startup_recovery)? = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_crashed_session_and_temp_files() {
+        let root = std::env::temp_dir().join(format!("noteban-recovery-{}", uuid::Uuid::new_v4()));
+        let marker = root.join("profile").join(SESSION_FILE_NAME);
+        assert!(begin_session_at(&marker).is_none());
+        // A marker left by this very process is a live session, not a crash
+        assert!(begin_session_at(&marker).is_none());
+
+        let stale = SessionMarker {
+            pid: u32::MAX,
+            started_at: Utc::now(),
+        };
+        fs::write(&marker, serde_json::to_vec(&stale).unwrap()).unwrap();
+        assert_eq!(begin_session_at(&marker), Some(stale.started_at));
+
+        let vault = root.join("vault");
+        fs::create_dir_all(vault.join("work/.atomicwriteAbc123")).unwrap();
+        fs::write(vault.join("work/.atomicwriteAbc123/note.md"), "x").unwrap();
+        fs::write(vault.join("restored.md.restore-partial"), "x").unwrap();
+        fs::write(vault.join("note.md"), "x").unwrap();
+        let found = find_stranded_temp_files(&vault);
+        assert_eq!(
+            found,
+            vec![
+                vault.join("restored.md.restore-partial"),
+                vault.join("work/.atomicwriteAbc123"),
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
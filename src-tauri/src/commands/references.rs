@@ -0,0 +1,291 @@
+use crate::commands::notes::ensure_safe_relative_path;
+use crate::lock_or_err;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tauri_plugin_opener::OpenerExt;
+
+pub(crate) const CODE_REFERENCE_CONFIG_KEY: &str = "code_reference_config";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodeReferenceConfig {
+    /// Named source checkouts that `repo:<name>/<path>` links resolve against
+    #[serde(default)]
+    pub roots: BTreeMap<String, String>,
+    /// Editor URL with `{path}`, `{line}` and `{column}` placeholders,
+    /// e.g. `vscode://file/{path}:{line}:{column}`
+    #[serde(default)]
+    pub editor_url: Option<String>,
+}
+
+/// A `file://` or `repo:` link resolved to a local path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalReference {
+    pub link: String,
+    pub path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub exists: bool,
+    /// URL that opens the location in the configured editor
+    pub editor_url: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum LinkTarget {
+    File(PathBuf),
+    Repo { root: String, relative: PathBuf },
+}
+
+/// Split a `:line[:column]` or `#Lline[Ccolumn]` suffix off a link
+fn split_location(link: &str) -> (&str, Option<u32>, Option<u32>) {
+    if let Some((path, fragment)) = link.rsplit_once("#L") {
+        let (line, column) = match fragment.split_once('C') {
+            Some((line, column)) => (line.parse().ok(), column.parse().ok()),
+            None => (fragment.parse().ok(), None),
+        };
+        if line.is_some() {
+            return (path, line, column);
+        }
+    }
+
+    let parse_tail = |s: &str| -> Option<(usize, u32)> {
+        let (head, tail) = s.rsplit_once(':')?;
+        let number = tail
+            .parse()
+            .ok()
+            .filter(|_| tail.bytes().all(|b| b.is_ascii_digit()))?;
+        Some((head.len(), number))
+    };
+    match parse_tail(link) {
+        Some((end, last)) => match parse_tail(&link[..end]) {
+            Some((start, line)) => (&link[..start], Some(line), Some(last)),
+            None => (&link[..end], Some(last), None),
+        },
+        None => (link, None, None),
+    }
+}
+
+fn parse_link(link: &str) -> Result<(LinkTarget, Option<u32>, Option<u32>), String> {
+    let link = link.trim();
+    let (target, line, column) = split_location(link);
+
+    if let Some(rest) = target.strip_prefix("file://") {
+        let decoded = urlencoding::decode(rest)
+            .map_err(|_| "Invalid file link encoding".to_string())?
+            .into_owned();
+        // file:///C:/src/main.rs carries a leading slash before the drive
+        let decoded = match decoded.as_bytes() {
+            [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => decoded[1..].to_string(),
+            _ => decoded,
+        };
+        let path = PathBuf::from(decoded);
+        if !path.is_absolute() {
+            return Err("File links must use an absolute path".to_string());
+        }
+        return Ok((LinkTarget::File(path), line, column));
+    }
+
+    if let Some(rest) = target.strip_prefix("repo:") {
+        let (root, relative) = rest
+            .trim_start_matches('/')
+            .split_once('/')
+            .ok_or("Repository links look like repo:<name>/<path>")?;
+        let relative = PathBuf::from(relative);
+        ensure_safe_relative_path(&relative)?;
+        return Ok((
+            LinkTarget::Repo {
+                root: root.to_string(),
+                relative,
+            },
+            line,
+            column,
+        ));
+    }
+
+    Err("Only file:// and repo: links are supported".to_string())
+}
+
+fn editor_url_for(template: &str, path: &Path, line: Option<u32>, column: Option<u32>) -> String {
+    template
+        .replace("{path}", &path.to_string_lossy())
+        .replace("{line}", &line.unwrap_or(1).to_string())
+        .replace("{column}", &column.unwrap_or(1).to_string())
+}
+
+fn load_config(state: &State<AppState>) -> Result<CodeReferenceConfig, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    Ok(cache_lock
+        .as_ref()
+        .and_then(|cache| cache.get_meta(CODE_REFERENCE_CONFIG_KEY).ok().flatten())
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+fn resolve(link: &str, config: &CodeReferenceConfig) -> Result<ExternalReference, String> {
+    let (target, line, column) = parse_link(link)?;
+    let path = match target {
+        LinkTarget::File(path) => path,
+        LinkTarget::Repo { root, relative } => {
+            let root_path = config
+                .roots
+                .get(&root)
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("Unknown repository: {}", root))?;
+            let path = root_path.join(relative);
+            // Symlinks inside the checkout must not lead outside of it
+            if let (Ok(root), Ok(resolved)) = (root_path.canonicalize(), path.canonicalize()) {
+                if !resolved.starts_with(&root) {
+                    return Err("Link points outside the repository".to_string());
+                }
+            }
+            path
+        }
+    };
+
+    let exists = path.is_file();
+    let editor_url = config
+        .editor_url
+        .as_deref()
+        .filter(|_| exists)
+        .map(|template| editor_url_for(template, &path, line, column));
+
+    Ok(ExternalReference {
+        link: link.trim().to_string(),
+        path: path.to_string_lossy().to_string(),
+        line,
+        column,
+        exists,
+        editor_url,
+    })
+}
+
+#[tauri::command]
+pub fn get_code_reference_config(state: State<AppState>) -> Result<CodeReferenceConfig, String> {
+    load_config(&state)
+}
+
+#[tauri::command]
+pub fn set_code_reference_config(
+    config: CodeReferenceConfig,
+    state: State<AppState>,
+) -> Result<CodeReferenceConfig, String> {
+    for (name, path) in &config.roots {
+        if name.is_empty() || name.contains('/') {
+            return Err(format!("Invalid repository name: {}", name));
+        }
+        if !Path::new(path).is_absolute() {
+            return Err(format!("Repository path for {} must be absolute", name));
+        }
+    }
+    if let Some(template) = &config.editor_url {
+        if !template.contains("{path}") || !template.contains("://") {
+            return Err("Editor URL must be a URL containing {path}".to_string());
+        }
+    }
+
+    let encoded = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to encode code reference config: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(CODE_REFERENCE_CONFIG_KEY, &encoded)?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub fn resolve_external_reference(
+    link: String,
+    state: State<AppState>,
+) -> Result<ExternalReference, String> {
+    resolve(&link, &load_config(&state)?)
+}
+
+/// Jump to a referenced file in the configured editor. Without an editor the
+/// file is only revealed in the file manager, never executed.
+#[tauri::command]
+pub fn open_external_reference(
+    link: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<ExternalReference, String> {
+    let reference = resolve(&link, &load_config(&state)?)?;
+    if !reference.exists {
+        return Err(format!("File does not exist: {}", reference.path));
+    }
+
+    match &reference.editor_url {
+        Some(url) => app
+            .opener()
+            .open_url(url, None::<&str>)
+            .map_err(|e| format!("Failed to open editor: {}", e))?,
+        None => app
+            .opener()
+            .reveal_item_in_dir(&reference.path)
+            .map_err(|e| format!("Failed to reveal file: {}", e))?,
+    }
+    Ok(reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_locations_and_link_kinds() {
+        assert_eq!(
+            split_location("src/main.rs:42:7"),
+            ("src/main.rs", Some(42), Some(7))
+        );
+        assert_eq!(
+            split_location("src/main.rs#L10"),
+            ("src/main.rs", Some(10), None)
+        );
+        assert_eq!(
+            split_location("src/main.rs#L3C9"),
+            ("src/main.rs", Some(3), Some(9))
+        );
+        assert_eq!(
+            split_location("C:/src/main.rs"),
+            ("C:/src/main.rs", None, None)
+        );
+
+        let (target, line, _) = parse_link("repo:api/src/lib.rs:12").unwrap();
+        assert_eq!(
+            target,
+            LinkTarget::Repo {
+                root: "api".to_string(),
+                relative: PathBuf::from("src/lib.rs"),
+            }
+        );
+        assert_eq!(line, Some(12));
+
+        assert!(parse_link("repo:api/../secrets.txt").is_err());
+        assert!(parse_link("file://relative/path.rs").is_err());
+        assert!(parse_link("https://example.com").is_err());
+    }
+
+    #[test]
+    fn resolves_repo_links_with_editor_url() {
+        let root = std::env::temp_dir().join(format!("noteban-refs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn main() {}").unwrap();
+        let config = CodeReferenceConfig {
+            roots: BTreeMap::from([("api".to_string(), root.to_string_lossy().to_string())]),
+            editor_url: Some("vscode://file/{path}:{line}:{column}".to_string()),
+        };
+
+        let reference = resolve("repo:api/src/lib.rs:5", &config).unwrap();
+        assert!(reference.exists);
+        assert_eq!(
+            reference.editor_url.unwrap(),
+            format!("vscode://file/{}:5:1", root.join("src/lib.rs").display())
+        );
+
+        let missing = resolve("repo:api/src/missing.rs", &config).unwrap();
+        assert!(!missing.exists);
+        assert!(missing.editor_url.is_none());
+        assert!(resolve("repo:web/src/lib.rs", &config).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
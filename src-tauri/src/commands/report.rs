@@ -0,0 +1,360 @@
+use crate::cache::queries::CachedNote;
+use crate::cache::transitions::ColumnTransition;
+use crate::commands::notes::atomic_write;
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use tauri::State;
+
+const DEFAULT_DONE_COLUMN: &str = "done";
+
+/// Inclusive range of calendar days (UTC)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ReportRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedCard {
+    pub title: String,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressReport {
+    pub range: ReportRange,
+    pub completed: Vec<CompletedCard>,
+    pub notes_created: usize,
+    pub notes_edited: usize,
+    /// Words in notes created during the range
+    pub words_written: usize,
+    pub active_days: usize,
+    /// Consecutive active days up to the end of the range
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressReportSummary {
+    pub path: String,
+    pub report: ProgressReport,
+}
+
+fn day_start(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+fn in_range(at: DateTime<Utc>, range: &ReportRange) -> bool {
+    let day = at.date_naive();
+    day >= range.from && day <= range.to
+}
+
+/// Longest run of consecutive days, and the run ending on `end` (or the day
+/// before, so a report taken mid-day doesn't break the streak)
+fn streaks(days: &BTreeSet<NaiveDate>, end: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(prev) if *day - prev == ChronoDuration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let mut current = 0;
+    let mut day = if days.contains(&end) {
+        end
+    } else {
+        end - ChronoDuration::days(1)
+    };
+    while days.contains(&day) {
+        current += 1;
+        day -= ChronoDuration::days(1);
+    }
+    (current, longest)
+}
+
+fn build_report(
+    notes: &[CachedNote],
+    transitions: &[ColumnTransition],
+    range: ReportRange,
+    done_column: &str,
+    redact_titles: bool,
+) -> ProgressReport {
+    let titles: HashMap<&str, &str> = notes
+        .iter()
+        .map(|cached| {
+            let frontmatter = &cached.note.frontmatter;
+            (frontmatter.id.as_str(), frontmatter.title.as_str())
+        })
+        .collect();
+
+    let mut active: BTreeSet<NaiveDate> = BTreeSet::new();
+    let mut notes_created = 0;
+    let mut notes_edited = 0;
+    let mut words_written = 0;
+    for cached in notes {
+        let frontmatter = &cached.note.frontmatter;
+        if in_range(frontmatter.created, &range) {
+            notes_created += 1;
+            words_written += cached.note.content.split_whitespace().count();
+            active.insert(frontmatter.created.date_naive());
+        }
+        if in_range(frontmatter.modified, &range) {
+            notes_edited += 1;
+            active.insert(frontmatter.modified.date_naive());
+        }
+    }
+
+    // A card counts once, at the last time it reached the done column
+    let mut completed_at: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for transition in transitions.iter().filter(|t| in_range(t.at, &range)) {
+        active.insert(transition.at.date_naive());
+        if transition.to_column == done_column && transition.from_column != done_column {
+            completed_at.insert(transition.note_id.as_str(), transition.at);
+        }
+    }
+    let mut completed: Vec<(DateTime<Utc>, &str)> = completed_at
+        .into_iter()
+        .map(|(id, at)| (at, titles.get(id).copied().unwrap_or("Deleted card")))
+        .collect();
+    completed.sort();
+    let completed = completed
+        .into_iter()
+        .enumerate()
+        .map(|(index, (completed_at, title))| CompletedCard {
+            title: if redact_titles {
+                format!("Card {}", index + 1)
+            } else {
+                title.to_string()
+            },
+            completed_at,
+        })
+        .collect();
+
+    let (current_streak, longest_streak) = streaks(&active, range.to);
+    ProgressReport {
+        range,
+        completed,
+        notes_created,
+        notes_edited,
+        words_written,
+        active_days: active.len(),
+        current_streak,
+        longest_streak,
+    }
+}
+
+fn summary_rows(report: &ProgressReport) -> Vec<(&'static str, String)> {
+    vec![
+        ("Cards completed", report.completed.len().to_string()),
+        ("Notes created", report.notes_created.to_string()),
+        ("Notes edited", report.notes_edited.to_string()),
+        ("Words written", report.words_written.to_string()),
+        ("Active days", report.active_days.to_string()),
+        ("Current streak", format!("{} days", report.current_streak)),
+        ("Longest streak", format!("{} days", report.longest_streak)),
+    ]
+}
+
+fn render_markdown(report: &ProgressReport) -> String {
+    let mut out = format!(
+        "# Progress report {} to {}\n\n",
+        report.range.from, report.range.to
+    );
+    for (label, value) in summary_rows(report) {
+        out.push_str(&format!("- **{}:** {}\n", label, value));
+    }
+    if !report.completed.is_empty() {
+        out.push_str("\n## Completed\n\n");
+        for card in &report.completed {
+            out.push_str(&format!(
+                "- {} ({})\n",
+                card.title,
+                card.completed_at.format("%Y-%m-%d")
+            ));
+        }
+    }
+    out
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(report: &ProgressReport) -> String {
+    let title = format!(
+        "Progress report {} to {}",
+        report.range.from, report.range.to
+    );
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n<table>\n",
+        title
+    );
+    for (label, value) in summary_rows(report) {
+        out.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, value));
+    }
+    out.push_str("</table>\n");
+    if !report.completed.is_empty() {
+        out.push_str("<h2>Completed</h2>\n<ul>\n");
+        for card in &report.completed {
+            out.push_str(&format!(
+                "<li>{} ({})</li>\n",
+                escape_html(&card.title),
+                card.completed_at.format("%Y-%m-%d")
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Write a shareable summary of the work done in `range` as markdown or HTML.
+/// With `redact_titles`, card titles are replaced by numbers and no note
+/// content leaves the vault.
+#[tauri::command]
+pub fn export_progress_report(
+    range: ReportRange,
+    dest: String,
+    format: Option<String>,
+    done_column: Option<String>,
+    redact_titles: Option<bool>,
+    state: State<AppState>,
+) -> Result<ProgressReportSummary, String> {
+    if range.from > range.to {
+        return Err("Report range starts after it ends".to_string());
+    }
+    let dest_path = PathBuf::from(&dest);
+    let format = match format {
+        Some(format) => format.to_lowercase(),
+        None => dest_path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "md".to_string()),
+    };
+
+    let (notes, transitions) = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        (
+            cache.get_all_notes()?,
+            cache.get_transitions_between(
+                day_start(range.from),
+                day_start(range.to + ChronoDuration::days(1)),
+            )?,
+        )
+    };
+    let report = build_report(
+        &notes,
+        &transitions,
+        range,
+        done_column.as_deref().unwrap_or(DEFAULT_DONE_COLUMN),
+        redact_titles.unwrap_or(false),
+    );
+
+    let content = match format.as_str() {
+        "md" | "markdown" => render_markdown(&report),
+        "html" | "htm" => render_html(&report),
+        other => return Err(format!("Unsupported report format: {}", other)),
+    };
+
+    if let Some(parent) = dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+    atomic_write(&dest_path, &content)?;
+
+    Ok(ProgressReportSummary { path: dest, report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::{FrontmatterFormat, Note, NoteFrontmatter};
+
+    fn at(day: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("{}T12:00:00+00:00", day))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn note(id: &str, title: &str, created: &str, content: &str) -> CachedNote {
+        CachedNote {
+            note: Note {
+                frontmatter: NoteFrontmatter {
+                    id: id.to_string(),
+                    title: title.to_string(),
+                    created: at(created),
+                    modified: at(created),
+                    date: None,
+                    due: None,
+                    column: "done".to_string(),
+                    tags: Vec::new(),
+                    order: 0,
+                    pinned: false,
+                    archived: false,
+                    priority: None,
+                    color: None,
+                    cover: None,
+                    icon: None,
+                    aliases: Vec::new(),
+                    extra: serde_yaml::Mapping::new(),
+                    format: FrontmatterFormat::Yaml,
+                },
+                content: content.to_string(),
+                file_path: format!("/vault/{}.md", id),
+            },
+            inline_tags: Vec::new(),
+            excerpt: String::new(),
+        }
+    }
+
+    fn moved(id: &str, to: &str, day: &str) -> ColumnTransition {
+        ColumnTransition {
+            note_id: id.to_string(),
+            from_column: "doing".to_string(),
+            to_column: to.to_string(),
+            at: at(day),
+        }
+    }
+
+    #[test]
+    fn summarizes_range_with_redaction() {
+        let notes = vec![
+            note("a", "Secret launch plan", "2024-03-04", "three words here"),
+            note("b", "Tax return", "2024-03-05", "two words"),
+            note("c", "Old", "2024-01-01", "outside the range"),
+        ];
+        let transitions = vec![
+            moved("a", "done", "2024-03-06"),
+            moved("b", "todo", "2024-03-06"),
+        ];
+        let range = ReportRange {
+            from: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 3, 7).unwrap(),
+        };
+
+        let report = build_report(&notes, &transitions, range, "done", true);
+        assert_eq!(report.notes_created, 2);
+        assert_eq!(report.words_written, 5);
+        assert_eq!(report.active_days, 3);
+        assert_eq!((report.current_streak, report.longest_streak), (3, 3));
+        assert_eq!(report.completed.len(), 1);
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("- Card 1 (2024-03-06)"));
+        assert!(!markdown.contains("Secret"));
+    }
+}
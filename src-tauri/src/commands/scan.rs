@@ -0,0 +1,248 @@
+use crate::commands::cloud::CloudPlaceholder;
+use crate::commands::notes::{rebuild_note_cache, scan_vault, Folder, NoteWithTags};
+use crate::AppState;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+pub(crate) const PROGRESS_EVENT: &str = "scan://progress";
+pub(crate) const NOTES_BATCH_EVENT: &str = "scan://notes-batch";
+pub(crate) const COMPLETE_EVENT: &str = "scan://complete";
+/// Notes sent per `scan://notes-batch` event
+const SCAN_BATCH_SIZE: usize = 200;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanPhase {
+    /// Re-parsing every note into an emptied cache (`rebuild_cache` only)
+    Rebuild,
+    /// Reading notes for the board
+    List,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub scan_id: String,
+    pub phase: ScanPhase,
+    /// Notes read so far in this phase
+    pub scanned: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanNotesBatch {
+    pub scan_id: String,
+    pub notes: Vec<NoteWithTags>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanComplete {
+    pub scan_id: String,
+    pub total: usize,
+    pub folders: Vec<Folder>,
+    pub placeholders: Vec<CloudPlaceholder>,
+    pub duration_ms: u64,
+    /// Set when the scan stopped early; batches already sent stay valid
+    pub error: Option<String>,
+}
+
+fn emit<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        log::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// Scan the vault in the background, sending notes in batches as they are
+/// read so the board can fill in progressively. Returns the id carried by
+/// every event of this scan; `scan://complete` ends it.
+#[tauri::command]
+pub fn start_vault_scan(notes_dir: String, app: AppHandle) -> String {
+    spawn_scan(app, notes_dir)
+}
+
+pub(crate) fn spawn_scan(app: AppHandle, notes_dir: String) -> String {
+    let scan_id = uuid::Uuid::new_v4().to_string();
+    let id = scan_id.clone();
+    std::thread::spawn(move || run_scan(&app, &notes_dir, &id));
+    scan_id
+}
+
+/// Throw away every cached note and rebuild the cache from disk in the
+/// background, parsing on all cores. Progress is reported in the `rebuild`
+/// phase, after which the board receives the same events as
+/// `start_vault_scan` under the returned id.
+#[tauri::command]
+pub fn rebuild_cache(
+    notes_dir: String,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<String, String> {
+    if state.safe_mode {
+        return Err("The cache is read-only in safe mode".to_string());
+    }
+    let scan_id = uuid::Uuid::new_v4().to_string();
+    let id = scan_id.clone();
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let state = app.state::<AppState>();
+        let rebuilt = rebuild_note_cache(&notes_dir, &state, &mut |cached| {
+            if cached % SCAN_BATCH_SIZE == 0 {
+                emit(
+                    &app,
+                    PROGRESS_EVENT,
+                    ScanProgress {
+                        scan_id: id.clone(),
+                        phase: ScanPhase::Rebuild,
+                        scanned: cached,
+                    },
+                );
+            }
+        });
+        match rebuilt {
+            Ok(cached) => {
+                log::info!(
+                    "Rebuilt cache with {} notes in {}ms",
+                    cached,
+                    started.elapsed().as_millis()
+                );
+                run_scan(&app, &notes_dir, &id);
+            }
+            Err(e) => {
+                log::warn!("Cache rebuild failed: {}", e);
+                emit(
+                    &app,
+                    COMPLETE_EVENT,
+                    ScanComplete {
+                        scan_id: id,
+                        total: 0,
+                        folders: Vec::new(),
+                        placeholders: Vec::new(),
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        error: Some(e),
+                    },
+                );
+            }
+        }
+    });
+    Ok(scan_id)
+}
+
+fn run_scan<R: Runtime>(app: &AppHandle<R>, notes_dir: &str, scan_id: &str) {
+    let started = Instant::now();
+    let state = app.state::<AppState>();
+    // Read before the scan takes the cache lock; notes new to the cache have
+    // no column history yet anyway
+    let days_in_column: HashMap<String, i64> = state
+        .cache
+        .lock()
+        .ok()
+        .and_then(|cache_lock| cache_lock.as_ref()?.get_days_in_column().ok())
+        .unwrap_or_default();
+
+    let mut batch = Vec::with_capacity(SCAN_BATCH_SIZE);
+    let mut scanned = 0;
+    let flush = |batch: &mut Vec<NoteWithTags>, scanned: usize| {
+        if batch.is_empty() {
+            return;
+        }
+        emit(
+            app,
+            NOTES_BATCH_EVENT,
+            ScanNotesBatch {
+                scan_id: scan_id.to_string(),
+                notes: std::mem::take(batch),
+            },
+        );
+        emit(
+            app,
+            PROGRESS_EVENT,
+            ScanProgress {
+                scan_id: scan_id.to_string(),
+                phase: ScanPhase::List,
+                scanned,
+            },
+        );
+    };
+
+    let result = scan_vault(notes_dir, &state, &mut |note| {
+        if note.note.frontmatter.archived {
+            return;
+        }
+        let mut note = note.clone();
+        note.days_in_column = days_in_column.get(&note.note.frontmatter.id).copied();
+        batch.push(note);
+        scanned += 1;
+        if batch.len() >= SCAN_BATCH_SIZE {
+            flush(&mut batch, scanned);
+        }
+    });
+    flush(&mut batch, scanned);
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let complete = match result {
+        Ok(listing) => ScanComplete {
+            scan_id: scan_id.to_string(),
+            total: scanned,
+            folders: listing.folders,
+            placeholders: listing.placeholders,
+            duration_ms,
+            error: None,
+        },
+        Err(e) => {
+            log::warn!("Vault scan failed: {}", e);
+            ScanComplete {
+                scan_id: scan_id.to_string(),
+                total: scanned,
+                folders: Vec::new(),
+                placeholders: Vec::new(),
+                duration_ms,
+                error: Some(e),
+            }
+        }
+    };
+    emit(app, COMPLETE_EVENT, complete);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+    use tauri::Listener;
+
+    #[test]
+    fn sends_unarchived_notes_in_batches_then_completes() {
+        let vault = TestVault::new();
+        for i in 0..SCAN_BATCH_SIZE + 10 {
+            vault.note(&format!("Work/{i}.md"), &format!("n{i}"), "");
+        }
+        vault.note("old.md", "old", "archived: true\n");
+        let app = vault.app_handle();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        for event in [NOTES_BATCH_EVENT, COMPLETE_EVENT] {
+            let events = events.clone();
+            app.listen(event, move |e| {
+                let payload: Value = serde_json::from_str(e.payload()).unwrap();
+                events.lock().unwrap().push(payload);
+            });
+        }
+        run_scan(&app, &vault.notes_dir(), "scan-1");
+
+        let events = events.lock().unwrap();
+        let sizes: Vec<usize> = events
+            .iter()
+            .filter_map(|e| Some(e.get("notes")?.as_array()?.len()))
+            .collect();
+        assert_eq!(sizes, [SCAN_BATCH_SIZE, 10]);
+        let complete = events.last().unwrap();
+        assert_eq!(complete["scanId"], "scan-1");
+        assert_eq!(complete["total"], SCAN_BATCH_SIZE + 10);
+        assert_eq!(complete["folders"].as_array().unwrap().len(), 1);
+        assert!(complete["error"].is_null());
+    }
+}
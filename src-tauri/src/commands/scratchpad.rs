@@ -0,0 +1,240 @@
+use crate::commands::history::active_profile_id;
+use crate::commands::notes::{atomic_write, create_note, CreateNoteInput, NoteWithTags};
+use crate::lock_or_err;
+use crate::AppState;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
+
+const SCRATCHPAD_PATH_KEY: &str = "scratchpad_path";
+const SCRATCHPAD_FILE_NAME: &str = "scratchpad.md";
+
+/// Held while the scratchpad file is changed, so a jot from one window cannot
+/// land between another window reading and rewriting it
+static SCRATCHPAD_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scratchpad {
+    pub path: String,
+    pub content: String,
+}
+
+/// The scratchpad lives in `<data dir>/<profile>/scratchpad.md` unless a
+/// custom location was configured
+fn scratchpad_path(state: &State<AppState>) -> Result<PathBuf, String> {
+    {
+        let cache_lock = lock_or_err(&state.cache)?;
+        if let Some(cache) = cache_lock.as_ref() {
+            if let Some(path) = cache.get_meta(SCRATCHPAD_PATH_KEY)? {
+                return Ok(PathBuf::from(path));
+            }
+        }
+    }
+
+    let profile_id = active_profile_id(state).ok_or("Cache is not initialized")?;
+    let proj_dirs =
+        ProjectDirs::from("", "", "noteban").ok_or("Could not determine data directory")?;
+    Ok(proj_dirs
+        .data_dir()
+        .join(profile_id)
+        .join(SCRATCHPAD_FILE_NAME))
+}
+
+fn read_scratchpad(path: &PathBuf) -> Result<String, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("Failed to read scratchpad: {}", e)),
+    }
+}
+
+#[tauri::command]
+pub fn get_scratchpad(state: State<AppState>) -> Result<Scratchpad, String> {
+    let path = scratchpad_path(&state)?;
+    Ok(Scratchpad {
+        content: read_scratchpad(&path)?,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Append a jotted entry as its own paragraph
+#[tauri::command]
+pub fn append_to_scratchpad(text: String, state: State<AppState>) -> Result<Scratchpad, String> {
+    let entry = text.trim();
+    if entry.is_empty() {
+        return Err("Nothing to append".to_string());
+    }
+
+    let path = scratchpad_path(&state)?;
+    let _guard = lock_or_err(&SCRATCHPAD_LOCK)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create scratchpad directory: {}", e))?;
+    }
+
+    let existing = read_scratchpad(&path)?;
+    let separator = match existing.as_str() {
+        "" => "",
+        content if content.ends_with("\n\n") => "",
+        content if content.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+
+    // Append rather than rewrite so concurrent jots from several windows are kept
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open scratchpad: {}", e))?;
+    writeln!(file, "{}{}", separator, entry)
+        .map_err(|e| format!("Failed to append to scratchpad: {}", e))?;
+
+    Ok(Scratchpad {
+        content: read_scratchpad(&path)?,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+#[tauri::command]
+pub fn clear_scratchpad(state: State<AppState>) -> Result<(), String> {
+    let path = scratchpad_path(&state)?;
+    let _guard = lock_or_err(&SCRATCHPAD_LOCK)?;
+    if path.exists() {
+        atomic_write(&path, "")?;
+    }
+    Ok(())
+}
+
+/// Move the scratchpad to a custom file, or back to the profile data
+/// directory when `path` is None. Existing content moves along.
+#[tauri::command]
+pub fn set_scratchpad_location(path: Option<String>, state: State<AppState>) -> Result<(), String> {
+    if let Some(path) = &path {
+        let target = PathBuf::from(path);
+        if !target.is_absolute() {
+            return Err("Scratchpad path must be absolute".to_string());
+        }
+        if target.is_dir() {
+            return Err("Scratchpad path must be a file".to_string());
+        }
+    }
+
+    let old_path = scratchpad_path(&state)?;
+    let _guard = lock_or_err(&SCRATCHPAD_LOCK)?;
+    let content = read_scratchpad(&old_path)?;
+
+    {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        match &path {
+            Some(path) => cache.set_meta(SCRATCHPAD_PATH_KEY, path)?,
+            None => cache.delete_meta(SCRATCHPAD_PATH_KEY)?,
+        }
+    }
+
+    let new_path = scratchpad_path(&state)?;
+    if new_path != old_path && !content.is_empty() {
+        if let Some(parent) = new_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create scratchpad directory: {}", e))?;
+        }
+        let mut merged = read_scratchpad(&new_path)?;
+        if !merged.is_empty() && !merged.ends_with("\n\n") {
+            merged.push_str(if merged.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        merged.push_str(&content);
+        atomic_write(&new_path, &merged)?;
+        if let Err(e) = fs::remove_file(&old_path) {
+            log::warn!("Failed to remove old scratchpad: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Drop the `consumed` text from the start of the scratchpad, keeping whatever
+/// was added after it was read. A scratchpad edited in the meantime is left
+/// alone.
+fn consume_scratchpad(path: &PathBuf, consumed: &str) -> Result<(), String> {
+    let current = read_scratchpad(path)?;
+    match current.strip_prefix(consumed) {
+        Some(rest) => atomic_write(path, rest.trim_start()),
+        None => {
+            log::warn!("Scratchpad changed while turning it into a note; kept it as is");
+            Ok(())
+        }
+    }
+}
+
+/// Turn the scratchpad into a regular note and empty it
+#[tauri::command]
+pub fn scratchpad_to_note(
+    notes_dir: String,
+    title: String,
+    folder_path: Option<String>,
+    state: State<AppState>,
+) -> Result<NoteWithTags, String> {
+    let path = scratchpad_path(&state)?;
+    let _guard = lock_or_err(&SCRATCHPAD_LOCK)?;
+    let content = read_scratchpad(&path)?;
+    if content.trim().is_empty() {
+        return Err("Scratchpad is empty".to_string());
+    }
+
+    let note = create_note(
+        CreateNoteInput {
+            notes_dir,
+            folder_path,
+            title,
+            content: Some(content.trim().to_string()),
+            date: None,
+            column: None,
+            tags: None,
+            due: None,
+            idempotency_key: None,
+        },
+        state.clone(),
+    )?;
+    consume_scratchpad(&path, &content)?;
+    Ok(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+
+    #[test]
+    fn turns_the_scratchpad_into_a_note() {
+        let vault = TestVault::new();
+        append_to_scratchpad("first idea".into(), vault.state()).unwrap();
+        let pad = append_to_scratchpad("second idea".into(), vault.state()).unwrap();
+        assert_eq!(pad.content, "first idea\n\nsecond idea\n");
+
+        let note =
+            scratchpad_to_note(vault.notes_dir(), "Ideas".into(), None, vault.state()).unwrap();
+        assert_eq!(note.note.content, "first idea\n\nsecond idea");
+        assert_eq!(get_scratchpad(vault.state()).unwrap().content, "");
+        assert!(
+            scratchpad_to_note(vault.notes_dir(), "Empty".into(), None, vault.state()).is_err()
+        );
+    }
+
+    #[test]
+    fn keeps_text_added_after_the_scratchpad_was_read() {
+        let vault = TestVault::new();
+        let path = vault.dir.join("scratchpad.md");
+        fs::write(&path, "read\n\nlater\n").unwrap();
+        consume_scratchpad(&path, "read\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "later\n");
+
+        // Rewritten by something else: nothing is dropped
+        fs::write(&path, "edited\n").unwrap();
+        consume_scratchpad(&path, "later\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited\n");
+    }
+}
@@ -0,0 +1,92 @@
+/// Keychain service for secrets stored through `store_secret`
+const SECRETS_KEYRING_SERVICE: &str = "noteban.secrets";
+const MAX_SECRET_NAME_LEN: usize = 64;
+
+/// Keychain account of a profile's secret (`profile:<id>`)
+pub(crate) fn profile_account(profile_id: &str) -> String {
+    format!("profile:{}", profile_id)
+}
+
+fn entry(service: &str, account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(service, account)
+        .map_err(|e| format!("Failed to open credential store: {}", e))
+}
+
+/// Read a secret from the OS keychain (Keychain, DPAPI or Secret Service)
+pub(crate) fn read_secret(service: &str, account: &str) -> Result<Option<String>, String> {
+    match entry(service, account)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret: {}", e)),
+    }
+}
+
+pub(crate) fn write_secret(service: &str, account: &str, secret: &str) -> Result<(), String> {
+    entry(service, account)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store secret: {}", e))
+}
+
+pub(crate) fn remove_secret(service: &str, account: &str) -> Result<(), String> {
+    match entry(service, account)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove secret: {}", e)),
+    }
+}
+
+/// Keychain account of a named secret, so profiles never see each other's
+fn secret_account(profile_id: &str, name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_SECRET_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err("Secret names may only use letters, digits, '-', '_' and '.'".to_string());
+    }
+    if profile_id.is_empty() || profile_id.contains(':') {
+        return Err("Invalid profile id".to_string());
+    }
+    Ok(format!("{}:{}", profile_account(profile_id), name))
+}
+
+#[tauri::command]
+pub fn store_secret(profile_id: String, name: String, secret: String) -> Result<(), String> {
+    write_secret(
+        SECRETS_KEYRING_SERVICE,
+        &secret_account(&profile_id, &name)?,
+        &secret,
+    )
+}
+
+#[tauri::command]
+pub fn get_secret(profile_id: String, name: String) -> Result<Option<String>, String> {
+    read_secret(
+        SECRETS_KEYRING_SERVICE,
+        &secret_account(&profile_id, &name)?,
+    )
+}
+
+#[tauri::command]
+pub fn delete_secret(profile_id: String, name: String) -> Result<(), String> {
+    remove_secret(
+        SECRETS_KEYRING_SERVICE,
+        &secret_account(&profile_id, &name)?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_secret_names_to_profile() {
+        assert_eq!(
+            secret_account("p1", "git.token").unwrap(),
+            "profile:p1:git.token"
+        );
+        assert!(secret_account("p1", "").is_err());
+        assert!(secret_account("p1", "a:b").is_err());
+        assert!(secret_account("p1:other", "token").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use crate::backup::EXCLUDED_DIRS;
+use crate::cache::storage::StorageFileRecord;
+use crate::commands::notes::VaultWalkFilter;
+use crate::commands::symlinks::symlink_allowlist;
+use crate::lock_or_err;
+use crate::logging;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+
+const STORAGE_SCAN_ROOT_KEY: &str = "storage_scan_root";
+const STORAGE_SCANNED_AT_KEY: &str = "storage_scanned_at";
+/// Cached scans older than this are refreshed automatically
+const STORAGE_SCAN_MAX_AGE_SECS: i64 = 10 * 60;
+const LARGEST_FILES_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteStorage {
+    pub file_path: String,
+    pub note_bytes: u64,
+    pub attachment_bytes: u64,
+    pub attachment_count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageFile {
+    pub file_path: String,
+    pub note_path: Option<String>,
+    pub size: u64,
+    pub is_attachment: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub total_bytes: u64,
+    pub note_bytes: u64,
+    pub attachment_bytes: u64,
+    pub other_bytes: u64,
+    pub file_count: usize,
+    pub notes: Vec<NoteStorage>,
+    pub largest_files: Vec<StorageFile>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+/// Resolve the note that owns an attachment, i.e. `dir/foo.attachments/x.png` -> `dir/foo.md`
+pub(crate) fn attachment_owner(path: &Path) -> Option<PathBuf> {
+    let attachments_dir = path.ancestors().skip(1).find(|ancestor| {
+        ancestor
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".attachments"))
+    })?;
+    let name = attachments_dir.file_name()?.to_str()?;
+    let stem = name.strip_suffix(".attachments")?;
+    Some(attachments_dir.parent()?.join(format!("{}.md", stem)))
+}
+
+fn scan_vault(base_path: &Path, allowed_symlink_targets: &[PathBuf]) -> Vec<StorageFileRecord> {
+    let mut records = Vec::new();
+    let walk_filter = VaultWalkFilter::new(base_path, allowed_symlink_targets);
+
+    for entry in WalkDir::new(base_path)
+        .min_depth(1)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| {
+            let excluded = e.file_type().is_dir()
+                && e.file_name()
+                    .to_str()
+                    .is_some_and(|name| EXCLUDED_DIRS.contains(&name));
+            !excluded && walk_filter.allows(e)
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        let size = match entry.metadata() {
+            Ok(metadata) => metadata.len() as i64,
+            Err(e) => {
+                log::warn!("Skipping unreadable file {}: {}", logging::path(&path), e);
+                continue;
+            }
+        };
+
+        let (note_path, is_attachment) = match attachment_owner(path) {
+            Some(owner) => (Some(owner.to_string_lossy().to_string()), true),
+            None if path.extension().is_some_and(|ext| ext == "md") => {
+                (Some(path.to_string_lossy().to_string()), false)
+            }
+            None => (None, false),
+        };
+
+        records.push(StorageFileRecord {
+            file_path: path.to_string_lossy().to_string(),
+            note_path,
+            size,
+            is_attachment,
+        });
+    }
+
+    records
+}
+
+fn build_storage_report(records: &[StorageFileRecord], scanned_at: DateTime<Utc>) -> StorageReport {
+    let mut notes: HashMap<String, NoteStorage> = HashMap::new();
+    let mut note_bytes = 0;
+    let mut attachment_bytes = 0;
+    let mut other_bytes = 0;
+
+    for record in records {
+        let size = record.size.max(0) as u64;
+        let Some(note_path) = record.note_path.as_ref() else {
+            other_bytes += size;
+            continue;
+        };
+
+        let entry = notes
+            .entry(note_path.clone())
+            .or_insert_with(|| NoteStorage {
+                file_path: note_path.clone(),
+                note_bytes: 0,
+                attachment_bytes: 0,
+                attachment_count: 0,
+                total_bytes: 0,
+            });
+
+        if record.is_attachment {
+            entry.attachment_bytes += size;
+            entry.attachment_count += 1;
+            attachment_bytes += size;
+        } else {
+            entry.note_bytes += size;
+            note_bytes += size;
+        }
+        entry.total_bytes += size;
+    }
+
+    let mut notes: Vec<NoteStorage> = notes.into_values().collect();
+    notes.sort_by(|a, b| {
+        b.total_bytes
+            .cmp(&a.total_bytes)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+
+    let mut largest_files: Vec<StorageFile> = records
+        .iter()
+        .map(|record| StorageFile {
+            file_path: record.file_path.clone(),
+            note_path: record.note_path.clone(),
+            size: record.size.max(0) as u64,
+            is_attachment: record.is_attachment,
+        })
+        .collect();
+    largest_files.sort_by(|a, b| {
+        b.size
+            .cmp(&a.size)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    largest_files.truncate(LARGEST_FILES_LIMIT);
+
+    StorageReport {
+        total_bytes: note_bytes + attachment_bytes + other_bytes,
+        note_bytes,
+        attachment_bytes,
+        other_bytes,
+        file_count: records.len(),
+        notes,
+        largest_files,
+        scanned_at,
+    }
+}
+
+/// Report vault size, per-note attachment usage and the largest files.
+/// Served from the last cached scan unless it is stale or `refresh` is set.
+#[tauri::command]
+pub fn get_storage_report(
+    notes_dir: String,
+    refresh: Option<bool>,
+    state: State<AppState>,
+) -> Result<StorageReport, String> {
+    let base_path = PathBuf::from(&notes_dir);
+    if !base_path.exists() {
+        return Err("Notes directory does not exist".to_string());
+    }
+    let base_path = fs::canonicalize(&base_path)
+        .map_err(|e| format!("Failed to resolve notes directory: {}", e))?;
+    let root = base_path.to_string_lossy().to_string();
+
+    if !refresh.unwrap_or(false) {
+        let cache_lock = lock_or_err(&state.cache)?;
+        if let Some(c) = cache_lock.as_ref() {
+            let cached_root = c.get_meta(STORAGE_SCAN_ROOT_KEY)?;
+            let scanned_at = c
+                .get_meta(STORAGE_SCANNED_AT_KEY)?
+                .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+                .map(|dt| dt.with_timezone(&Utc));
+
+            if let (Some(cached_root), Some(scanned_at)) = (cached_root, scanned_at) {
+                let age = Utc::now().signed_duration_since(scanned_at).num_seconds();
+                if cached_root == root && age < STORAGE_SCAN_MAX_AGE_SECS {
+                    let records = c.get_storage_files()?;
+                    return Ok(build_storage_report(&records, scanned_at));
+                }
+            }
+        }
+    }
+
+    // Walk without the cache lock so a large vault doesn't stall other commands
+    let records = scan_vault(&base_path, &symlink_allowlist(&state));
+    let scanned_at = Utc::now();
+
+    if let Some(c) = lock_or_err(&state.cache)?.as_ref() {
+        let persisted = c
+            .replace_storage_files(&records)
+            .and_then(|_| c.set_meta(STORAGE_SCAN_ROOT_KEY, &root))
+            .and_then(|_| c.set_meta(STORAGE_SCANNED_AT_KEY, &scanned_at.to_rfc3339()));
+        if let Err(e) = persisted {
+            log::warn!("Failed to cache storage scan: {}", e);
+        }
+    }
+
+    Ok(build_storage_report(&records, scanned_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        file_path: &str,
+        note_path: Option<&str>,
+        size: i64,
+        is_attachment: bool,
+    ) -> StorageFileRecord {
+        StorageFileRecord {
+            file_path: file_path.to_string(),
+            note_path: note_path.map(str::to_string),
+            size,
+            is_attachment,
+        }
+    }
+
+    #[test]
+    fn resolves_attachment_owner() {
+        assert_eq!(
+            attachment_owner(Path::new("/vault/work/spec.attachments/diagram.png")),
+            Some(PathBuf::from("/vault/work/spec.md"))
+        );
+        assert_eq!(
+            attachment_owner(Path::new("/vault/spec.attachments/nested/photo.jpg")),
+            Some(PathBuf::from("/vault/spec.md"))
+        );
+        assert_eq!(attachment_owner(Path::new("/vault/work/spec.md")), None);
+    }
+
+    #[test]
+    fn aggregates_storage_per_note() {
+        let records = vec![
+            record("/v/a.md", Some("/v/a.md"), 100, false),
+            record("/v/a.attachments/x.png", Some("/v/a.md"), 5000, true),
+            record("/v/a.attachments/y.png", Some("/v/a.md"), 3000, true),
+            record("/v/b.md", Some("/v/b.md"), 200, false),
+            record("/v/.noteban/board.json", None, 50, false),
+        ];
+        let report = build_storage_report(&records, Utc::now());
+
+        assert_eq!(report.total_bytes, 8350);
+        assert_eq!(report.attachment_bytes, 8000);
+        assert_eq!(report.other_bytes, 50);
+        assert_eq!(report.file_count, 5);
+        assert_eq!(report.notes[0].file_path, "/v/a.md");
+        assert_eq!(report.notes[0].attachment_count, 2);
+        assert_eq!(report.notes[0].total_bytes, 8100);
+        assert_eq!(report.largest_files[0].file_path, "/v/a.attachments/x.png");
+    }
+
+    #[test]
+    fn skips_trash_git_and_symlinks_leaving_the_vault() {
+        use crate::test_support::TestVault;
+
+        let (vault, outside) = (TestVault::new(), TestVault::new());
+        vault.write("a.md", "note");
+        vault.write("a.attachments/x.png", "image");
+        vault.write(".trash/old.md", "deleted");
+        vault.write(".git/objects/ab", "object");
+        outside.write("big.bin", "outside the vault");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside.dir, vault.dir.join("linked")).unwrap();
+
+        let base = vault.dir.canonicalize().unwrap();
+        let mut files: Vec<String> = scan_vault(&base, &[])
+            .into_iter()
+            .map(|record| {
+                let path = Path::new(&record.file_path).strip_prefix(&base).unwrap();
+                path.to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+        files.sort();
+        assert_eq!(files, ["a.attachments/x.png", "a.md"]);
+    }
+}
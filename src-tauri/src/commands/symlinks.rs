@@ -0,0 +1,115 @@
+use crate::lock_or_err;
+use crate::AppState;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Cache meta key holding the directories symlinks in the vault may point into
+const SYMLINK_ALLOWLIST_KEY: &str = "symlink_allowlist";
+
+/// Canonical directories outside the vault that symlinks may resolve into
+/// (empty when no cache is open)
+pub(crate) fn symlink_allowlist(state: &State<AppState>) -> Vec<PathBuf> {
+    let Ok(cache_lock) = state.cache.lock() else {
+        return Vec::new();
+    };
+    cache_lock
+        .as_ref()
+        .and_then(|cache| cache.get_meta(SYMLINK_ALLOWLIST_KEY).ok().flatten())
+        .and_then(|value| serde_json::from_str::<Vec<String>>(&value).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_symlink_allowlist(state: State<AppState>) -> Vec<String> {
+    symlink_allowlist(&state)
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Replace the directories that symlinks inside the vault may lead to.
+/// Links resolving anywhere else are skipped when the vault is listed.
+#[tauri::command]
+pub fn set_symlink_allowlist(
+    paths: Vec<String>,
+    state: State<AppState>,
+) -> Result<Vec<String>, String> {
+    let mut canonical = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(path);
+        if !path.is_dir() {
+            return Err(format!("Not a directory: {}", path.display()));
+        }
+        let resolved = path
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve allowed symlink target: {}", e))?
+            .to_string_lossy()
+            .to_string();
+        if !canonical.contains(&resolved) {
+            canonical.push(resolved);
+        }
+    }
+
+    let encoded = serde_json::to_string(&canonical)
+        .map_err(|e| format!("Failed to encode symlink allowlist: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(SYMLINK_ALLOWLIST_KEY, &encoded)?;
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestVault;
+
+    #[test]
+    fn stores_canonical_allowed_directories() {
+        let vault = TestVault::new();
+        let other = TestVault::new();
+        std::fs::create_dir(other.dir.join("refs")).unwrap();
+        let canonical = other.dir.join("refs").canonicalize().unwrap();
+        let canonical = canonical.to_string_lossy().to_string();
+
+        let through_parent = other.path("refs/../refs");
+        let saved =
+            set_symlink_allowlist(vec![other.path("refs"), through_parent], vault.state()).unwrap();
+        assert_eq!(saved, get_symlink_allowlist(vault.state()));
+        assert_eq!(saved, [canonical]);
+
+        // A bad entry leaves the stored list alone
+        assert!(set_symlink_allowlist(vec![other.path("missing")], vault.state()).is_err());
+        assert_eq!(get_symlink_allowlist(vault.state()).len(), 1);
+        set_symlink_allowlist(Vec::new(), vault.state()).unwrap();
+        assert!(get_symlink_allowlist(vault.state()).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lists_notes_behind_symlinks_only_into_allowed_directories() {
+        use crate::commands::notes::scan_vault;
+
+        let vault = TestVault::new();
+        let other = TestVault::new();
+        vault.note("a.md", "a", "");
+        other.note("refs/r.md", "r", "");
+        std::os::unix::fs::symlink(other.dir.join("refs"), vault.dir.join("refs")).unwrap();
+        let listed = |vault: &TestVault| {
+            let listing = scan_vault(&vault.notes_dir(), &vault.state(), &mut |_| {}).unwrap();
+            let mut ids: Vec<String> = listing
+                .notes
+                .into_iter()
+                .map(|n| n.note.frontmatter.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(listed(&vault), ["a"]);
+        set_symlink_allowlist(vec![other.path("refs")], vault.state()).unwrap();
+        assert_eq!(listed(&vault), ["a", "r"]);
+    }
+}
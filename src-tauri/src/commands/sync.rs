@@ -1,5 +1,16 @@
 use crate::cache::sync::SyncFileRecord;
 use crate::cache::CacheDb;
+use crate::commands::conflicts::{conflict_record, write_conflict_copy, REASON_SYNC};
+use crate::commands::lan_sync::device_id;
+use crate::commands::notes::{ensure_modifiable, is_locked, parse_note, Note};
+use crate::commands::secrets::{profile_account, read_secret, remove_secret, write_secret};
+use crate::commands::trash::TRASH_DIR_NAME;
+use crate::commands::watch;
+use crate::crypto;
+use crate::lock_or_err;
+use crate::logging;
+use crate::merge::{merge_note, TextMerge};
+use crate::sync_meta;
 use crate::AppState;
 use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
@@ -12,15 +23,24 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex as StdMutex, OnceLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex as AsyncMutex;
 use url::Url;
 use uuid::Uuid;
 use walkdir::WalkDir;
 
 const KEYRING_SERVICE: &str = "noteban.nextcloud";
+const ENCRYPTION_KEYRING_SERVICE: &str = "noteban.sync-encryption";
+/// Salt and key check shared by every device syncing an encrypted folder
+const ENCRYPTION_FILE_NAME: &str = ".noteban-encryption.json";
+/// Plaintext encrypted into the key check so a wrong passphrase is caught
+/// before any note is touched
+const ENCRYPTION_CHECK: &[u8] = b"noteban";
 const DEFAULT_REMOTE_FOLDER: &str = "Noteban";
 const SYNC_STATUS_KEY: &str = "sync_status";
+pub(crate) const SELECTIVE_SYNC_KEY: &str = "selective_sync_config";
+/// Emitted while a sync runs so the UI can show an indicator
+pub(crate) const PROGRESS_EVENT: &str = "sync://progress";
 const LOGIN_TIMEOUT: Duration = Duration::from_secs(20 * 60);
 const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
 <d:propfind xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns" xmlns:nc="http://nextcloud.org/ns">
@@ -71,6 +91,89 @@ pub struct SyncStatus {
     pub last_sync_at: Option<String>,
     pub last_error: Option<String>,
     pub conflicts: Vec<String>,
+    /// Local files added, edited or deleted since the last sync
+    #[serde(default)]
+    pub pending_changes: usize,
+}
+
+/// Folders (relative to the vault) that sync providers include or leave out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectiveSyncConfig {
+    /// Never leave this machine
+    #[serde(default)]
+    pub local_only: Vec<String>,
+    /// When not empty, only these folders are synced
+    #[serde(default)]
+    pub sync_only: Vec<String>,
+}
+
+impl SelectiveSyncConfig {
+    pub fn includes(&self, relative_path: &str) -> bool {
+        let within = |folder: &String| {
+            relative_path
+                .strip_prefix(folder.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+        !self.local_only.iter().any(within)
+            && (self.sync_only.is_empty() || self.sync_only.iter().any(within))
+    }
+
+    fn normalized(self) -> Result<Self, String> {
+        let normalize = |folders: Vec<String>| -> Result<Vec<String>, String> {
+            let mut normalized: Vec<String> = Vec::new();
+            for folder in folders {
+                let folder = folder.replace('\\', "/").trim_matches('/').to_string();
+                if folder.is_empty() {
+                    continue;
+                }
+                if folder
+                    .split('/')
+                    .any(|segment| segment == ".." || segment == ".")
+                {
+                    return Err(format!("Invalid folder: {}", folder));
+                }
+                if !normalized.contains(&folder) {
+                    normalized.push(folder);
+                }
+            }
+            Ok(normalized)
+        };
+        Ok(Self {
+            local_only: normalize(self.local_only)?,
+            sync_only: normalize(self.sync_only)?,
+        })
+    }
+}
+
+pub(crate) fn read_selective_sync(cache: &CacheDb) -> Result<SelectiveSyncConfig, String> {
+    Ok(cache
+        .get_meta(SELECTIVE_SYNC_KEY)?
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    /// `listing`, `syncing`, `done` or `error` for Nextcloud; the git stages
+    /// for git sync
+    pub stage: String,
+    /// Files handled so far while `syncing`
+    pub completed: Option<usize>,
+    pub total: Option<usize>,
+    pub path: Option<String>,
+}
+
+impl SyncProgress {
+    pub fn stage(stage: &str) -> Self {
+        Self {
+            stage: stage.to_string(),
+            completed: None,
+            total: None,
+            path: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -82,10 +185,18 @@ pub struct SyncSummary {
     pub downloaded: usize,
     pub deleted_local: usize,
     pub deleted_remote: usize,
+    /// Notes edited on both sides that were merged line by line
+    pub merged: usize,
     pub conflicts: Vec<String>,
     pub errors: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteEncryption {
+    salt: String,
+    check: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct LoginStartJson {
     poll: LoginPollJson,
@@ -142,14 +253,14 @@ struct RemoteFile {
 }
 
 #[derive(Debug, Clone)]
-struct LocalFile {
-    path: PathBuf,
-    hash: String,
+pub(crate) struct LocalFile {
+    pub(crate) path: PathBuf,
+    pub(crate) hash: String,
     mtime: i64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SyncDecision {
+pub(crate) enum SyncDecision {
     Noop,
     UploadLocal,
     DownloadRemote,
@@ -304,10 +415,112 @@ pub fn get_default_notes_dir(profile_id: String) -> Result<String, String> {
     Ok(path.to_string_lossy().to_string())
 }
 
+/// Whether sync payloads are encrypted with a passphrase for this profile
+#[tauri::command]
+pub fn get_sync_encryption(profile_id: String) -> Result<bool, String> {
+    Ok(load_passphrase(&profile_id)?.is_some())
+}
+
+/// Store the sync passphrase in the OS keychain, or drop it with `None`.
+/// The next sync checks it against the remote folder.
+#[tauri::command]
+pub fn set_sync_encryption(profile_id: String, passphrase: Option<String>) -> Result<(), String> {
+    let account = profile_account(&profile_id);
+    match passphrase.filter(|passphrase| !passphrase.is_empty()) {
+        Some(passphrase) => write_secret(ENCRYPTION_KEYRING_SERVICE, &account, &passphrase),
+        None => remove_secret(ENCRYPTION_KEYRING_SERVICE, &account),
+    }
+}
+
+#[tauri::command]
+pub fn get_selective_sync(state: State<AppState>) -> Result<SelectiveSyncConfig, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    read_selective_sync(cache)
+}
+
+/// Save the folders to include or leave out; remote copies of folders that
+/// become local-only are left in place
+#[tauri::command]
+pub fn set_selective_sync(
+    config: SelectiveSyncConfig,
+    state: State<AppState>,
+) -> Result<SelectiveSyncConfig, String> {
+    let config = config.normalized()?;
+    let encoded = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to encode selective sync config: {}", e))?;
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(SELECTIVE_SYNC_KEY, &encoded)?;
+    Ok(config)
+}
+
 #[tauri::command]
 pub fn get_sync_status(profile_id: String) -> Result<SyncStatus, String> {
     let cache = CacheDb::new(&profile_id)?;
-    read_sync_status(&cache)
+    let mut status = read_sync_status(&cache)?;
+    status.pending_changes = count_pending_changes(
+        &cache,
+        &default_notes_dir(&profile_id)?,
+        &read_selective_sync(&cache)?,
+    )?;
+    Ok(status)
+}
+
+pub(crate) fn emit_progress(app: &AppHandle, progress: SyncProgress) {
+    if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+        log::warn!("Failed to report sync progress: {}", e);
+    }
+}
+
+/// Local files that differ from their last synced state. Files whose mtime
+/// matches the sync record are not re-hashed.
+fn count_pending_changes(
+    cache: &CacheDb,
+    local_root: &Path,
+    selective: &SelectiveSyncConfig,
+) -> Result<usize, String> {
+    let records: HashMap<String, SyncFileRecord> = cache
+        .list_sync_records()?
+        .into_iter()
+        .filter(|record| selective.includes(&record.relative_path))
+        .map(|record| (record.relative_path.clone(), record))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut pending = 0;
+
+    for entry in WalkDir::new(local_root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+    {
+        let Ok(relative_path) = entry
+            .path()
+            .strip_prefix(local_root)
+            .map(normalize_relative_path)
+        else {
+            continue;
+        };
+        if !should_sync_file(&relative_path) || !selective.includes(&relative_path) {
+            continue;
+        }
+        let Some(record) = records.get(&relative_path) else {
+            pending += 1;
+            continue;
+        };
+        seen.insert(relative_path);
+        if record.local_mtime == file_mtime(entry.path()).ok() {
+            continue;
+        }
+        let hash = fs::read(entry.path()).ok().map(|bytes| hash_bytes(&bytes));
+        if hash.is_none() || record.last_synced_hash != hash {
+            pending += 1;
+        }
+    }
+
+    pending += records.keys().filter(|path| !seen.contains(*path)).count();
+    Ok(pending)
 }
 
 /// Returns a per-profile async mutex used to serialize `sync_now` calls.
@@ -327,9 +540,12 @@ fn sync_lock_for(profile_id: &str) -> Arc<AsyncMutex<()>> {
 pub async fn sync_now(
     profile_id: String,
     remote_folder: Option<String>,
+    app: AppHandle,
 ) -> Result<SyncSummary, String> {
     let lock = sync_lock_for(&profile_id);
     let _guard = lock.lock().await;
+    let app_state = app.state::<AppState>();
+    let _pause = watch::pause(&app_state);
     let cache = CacheDb::new(&profile_id)?;
     let started_at = Utc::now();
     write_sync_status(
@@ -339,10 +555,11 @@ pub async fn sync_now(
             last_sync_at: Some(started_at.to_rfc3339()),
             last_error: None,
             conflicts: Vec::new(),
+            pending_changes: 0,
         },
     )?;
 
-    let result = run_sync(profile_id, remote_folder, started_at).await;
+    let result = run_sync(profile_id, remote_folder, started_at, &app).await;
 
     match result {
         Ok(summary) => {
@@ -358,8 +575,10 @@ pub async fn sync_now(
                     last_sync_at: Some(summary.finished_at.clone()),
                     last_error: summary.errors.first().cloned(),
                     conflicts: summary.conflicts.clone(),
+                    pending_changes: 0,
                 },
             )?;
+            emit_progress(&app, SyncProgress::stage("done"));
             Ok(summary)
         }
         Err(error) => {
@@ -370,8 +589,10 @@ pub async fn sync_now(
                     last_sync_at: Some(Utc::now().to_rfc3339()),
                     last_error: Some(error.clone()),
                     conflicts: Vec::new(),
+                    pending_changes: 0,
                 },
             )?;
+            emit_progress(&app, SyncProgress::stage("error"));
             Err(error)
         }
     }
@@ -381,7 +602,9 @@ async fn run_sync(
     profile_id: String,
     remote_folder: Option<String>,
     started_at: DateTime<Utc>,
+    app: &AppHandle,
 ) -> Result<SyncSummary, String> {
+    emit_progress(app, SyncProgress::stage("listing"));
     let remote_folder = normalize_remote_folder(remote_folder);
     let credentials = load_credentials(&profile_id)?;
     let local_root = default_notes_dir(&profile_id)?;
@@ -390,9 +613,12 @@ async fn run_sync(
 
     let cache = CacheDb::new(&profile_id)?;
     let client = http_client()?;
+    sync_meta::stamp_local(&local_root, &device_id(&cache)?)?;
 
     ensure_remote_dir(&client, &credentials, &remote_folder).await?;
 
+    let key = sync_key(&client, &credentials, &remote_folder, &profile_id).await?;
+    let key = key.as_ref();
     let remote_files = list_remote_files(&client, &credentials, &remote_folder).await?;
     let local_files = list_local_files(&local_root)?;
 
@@ -403,27 +629,41 @@ async fn run_sync(
         downloaded: 0,
         deleted_local: 0,
         deleted_remote: 0,
+        merged: 0,
         conflicts: Vec::new(),
         errors: Vec::new(),
     };
 
-    let all_paths: HashSet<String> = local_files
+    let selective = read_selective_sync(&cache)?;
+    let mut all_paths: Vec<String> = local_files
         .keys()
         .chain(remote_files.keys())
+        .filter(|path| selective.includes(path))
         .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
         .collect();
-
-    for relative_path in all_paths {
+    // Device metadata first, so merges below see every device's stamps
+    all_paths.sort_by_key(|path| !sync_meta::is_sync_meta_path(path));
+
+    let total = all_paths.len();
+    for (index, relative_path) in all_paths.into_iter().enumerate() {
+        emit_progress(
+            app,
+            SyncProgress {
+                stage: "syncing".to_string(),
+                completed: Some(index),
+                total: Some(total),
+                path: Some(relative_path.clone()),
+            },
+        );
         let local = local_files.get(&relative_path);
         let remote = remote_files.get(&relative_path);
         let record = cache.get_sync_record(&relative_path)?;
 
         let local_changed = local
             .map(|file| {
-                record
-                    .as_ref()
-                    .and_then(|r| r.last_synced_hash.as_ref())
-                    .map_or(true, |hash| hash != &file.hash)
+                record.as_ref().and_then(|r| r.last_synced_hash.as_ref()) != Some(&file.hash)
             })
             .unwrap_or(false);
         let remote_changed = remote
@@ -443,8 +683,9 @@ async fn run_sync(
             local_changed,
             remote_changed,
         );
+        let locked = local.is_some() && is_locked_local_file(&local_root, &relative_path);
 
-        match decision {
+        match locked_sync_decision(decision, locked) {
             SyncDecision::Noop => {
                 if let (Some(local), Some(remote)) = (local, remote) {
                     upsert_record(&cache, &relative_path, local, remote, &local.hash)?;
@@ -458,6 +699,7 @@ async fn run_sync(
                         &remote_folder,
                         &relative_path,
                         &local.path,
+                        key,
                     )
                     .await?;
                     summary.uploaded += 1;
@@ -475,7 +717,7 @@ async fn run_sync(
             SyncDecision::DownloadRemote | SyncDecision::DownloadNew => {
                 if let Some(remote) = remote {
                     let bytes =
-                        download_file(&client, &credentials, &remote_folder, &relative_path)
+                        download_file(&client, &credentials, &remote_folder, &relative_path, key)
                             .await?;
                     write_local_file(&local_root, &relative_path, &bytes)?;
                     let updated_local = local_file_from_path(&local_root, &relative_path)?;
@@ -502,7 +744,7 @@ async fn run_sync(
             SyncDecision::Conflict => match (local, remote) {
                 (Some(local), Some(remote)) => {
                     let bytes =
-                        download_file(&client, &credentials, &remote_folder, &relative_path)
+                        download_file(&client, &credentials, &remote_folder, &relative_path, key)
                             .await?;
                     let remote_hash = hash_bytes(&bytes);
 
@@ -511,8 +753,43 @@ async fn run_sync(
                         continue;
                     }
 
+                    let merge = if locked {
+                        None
+                    } else {
+                        three_way_merge(&cache, &local_root, &relative_path, local, &bytes)
+                    };
+                    if let Some(merge) = merge {
+                        write_local_file(&local_root, &relative_path, merge.text.as_bytes())?;
+                        let merged_local = local_file_from_path(&local_root, &relative_path)?;
+                        let etag = upload_file(
+                            &client,
+                            &credentials,
+                            &remote_folder,
+                            &relative_path,
+                            &merged_local.path,
+                            key,
+                        )
+                        .await?;
+                        summary.uploaded += 1;
+                        summary.merged += 1;
+                        // Conflict markers still need the user's attention
+                        if merge.conflicted {
+                            summary.conflicts.push(relative_path.clone());
+                        }
+                        upsert_record_with_etag(
+                            &cache,
+                            &relative_path,
+                            &merged_local,
+                            etag.or_else(|| remote.etag.clone()),
+                            remote.modified,
+                            remote.size,
+                            &merged_local.hash,
+                        )?;
+                        continue;
+                    }
+
                     let conflict_relative =
-                        write_conflict_file(&local_root, &relative_path, &bytes)?;
+                        write_conflict_file(&cache, &local_root, &relative_path, &bytes)?;
                     let conflict_local = local_file_from_path(&local_root, &conflict_relative)?;
                     let conflict_etag = upload_file(
                         &client,
@@ -520,6 +797,7 @@ async fn run_sync(
                         &remote_folder,
                         &conflict_relative,
                         &conflict_local.path,
+                        key,
                     )
                     .await?;
 
@@ -529,6 +807,7 @@ async fn run_sync(
                         &remote_folder,
                         &relative_path,
                         &local.path,
+                        key,
                     )
                     .await?;
                     summary.uploaded += 2;
@@ -563,6 +842,7 @@ async fn run_sync(
                         &remote_folder,
                         &relative_path,
                         &local.path,
+                        key,
                     )
                     .await?;
                     summary.uploaded += 1;
@@ -585,7 +865,7 @@ async fn run_sync(
                     // surfaced via summary.conflicts; the user can
                     // re-delete locally if they really want it gone.
                     let bytes =
-                        download_file(&client, &credentials, &remote_folder, &relative_path)
+                        download_file(&client, &credentials, &remote_folder, &relative_path, key)
                             .await?;
                     write_local_file(&local_root, &relative_path, &bytes)?;
                     let restored = local_file_from_path(&local_root, &relative_path)?;
@@ -605,7 +885,7 @@ async fn run_sync(
     Ok(summary)
 }
 
-fn decide_sync_action(
+pub(crate) fn decide_sync_action(
     local_exists: bool,
     remote_exists: bool,
     had_record: bool,
@@ -857,6 +1137,7 @@ async fn download_file(
     credentials: &StoredCredentials,
     remote_folder: &str,
     relative_path: &str,
+    key: Option<&crypto::Key>,
 ) -> Result<Vec<u8>, String> {
     let remote_path = join_relative(remote_folder, relative_path);
     let response = client
@@ -875,11 +1156,17 @@ async fn download_file(
         ));
     }
 
-    response
+    let bytes = response
         .bytes()
         .await
         .map(|bytes| bytes.to_vec())
-        .map_err(|e| format!("Failed to read {}: {}", relative_path, e))
+        .map_err(|e| format!("Failed to read {}: {}", relative_path, e))?;
+    // Files uploaded before encryption was switched on stay readable
+    match key {
+        Some(key) if crypto::is_encrypted(&bytes) => crypto::decrypt(key, &bytes)
+            .map_err(|e| format!("Failed to decrypt {}: {}", relative_path, e)),
+        _ => Ok(bytes),
+    }
 }
 
 async fn upload_file(
@@ -888,10 +1175,14 @@ async fn upload_file(
     remote_folder: &str,
     relative_path: &str,
     local_path: &Path,
+    key: Option<&crypto::Key>,
 ) -> Result<Option<String>, String> {
     ensure_remote_parent(client, credentials, remote_folder, relative_path).await?;
-    let bytes = fs::read(local_path)
+    let mut bytes = fs::read(local_path)
         .map_err(|e| format!("Failed to read local file {}: {}", relative_path, e))?;
+    if let Some(key) = key {
+        bytes = crypto::encrypt(key, &bytes)?;
+    }
     let remote_path = join_relative(remote_folder, relative_path);
     let response = client
         .put(dav_url(credentials, &remote_path))
@@ -917,6 +1208,90 @@ async fn upload_file(
         .map(str::to_string))
 }
 
+/// Derive the payload key from the stored passphrase and the remote folder's
+/// salt, creating the salt on first use. A remote folder that is encrypted
+/// while no passphrase is stored is refused rather than synced as ciphertext.
+async fn sync_key(
+    client: &Client,
+    credentials: &StoredCredentials,
+    remote_folder: &str,
+    profile_id: &str,
+) -> Result<Option<crypto::Key>, String> {
+    let passphrase = load_passphrase(profile_id)?;
+    let remote_path = join_relative(remote_folder, ENCRYPTION_FILE_NAME);
+    let response = client
+        .get(dav_url(credentials, &remote_path))
+        .basic_auth(&credentials.login_name, Some(&credentials.app_password))
+        .header("User-Agent", user_agent())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to read remote encryption settings: {}", e))?;
+    let remote: Option<RemoteEncryption> = match response.status() {
+        StatusCode::NOT_FOUND => None,
+        status if status.is_success() => {
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to read remote encryption settings: {}", e))?;
+            Some(
+                serde_json::from_slice(&body)
+                    .map_err(|e| format!("Invalid remote encryption settings: {}", e))?,
+            )
+        }
+        status => {
+            return Err(format!(
+                "Failed to read remote encryption settings, status {}",
+                status
+            ))
+        }
+    };
+
+    let Some(passphrase) = passphrase else {
+        if remote.is_some() {
+            return Err(
+                "The remote notes are encrypted; enter the sync passphrase to sync".to_string(),
+            );
+        }
+        return Ok(None);
+    };
+
+    match remote {
+        Some(remote) => {
+            let key = crypto::derive_key(&passphrase, &crypto::from_hex(&remote.salt)?)?;
+            let check = crypto::decrypt(&key, &crypto::from_hex(&remote.check)?);
+            if check.as_deref() != Ok(ENCRYPTION_CHECK) {
+                return Err("The sync passphrase does not match the remote notes".to_string());
+            }
+            Ok(Some(key))
+        }
+        None => {
+            let salt = crypto::random_salt();
+            let key = crypto::derive_key(&passphrase, &salt)?;
+            let settings = RemoteEncryption {
+                salt: crypto::to_hex(&salt),
+                check: crypto::to_hex(&crypto::encrypt(&key, ENCRYPTION_CHECK)?),
+            };
+            let body = serde_json::to_vec_pretty(&settings)
+                .map_err(|e| format!("Failed to encode encryption settings: {}", e))?;
+            let response = client
+                .put(dav_url(credentials, &remote_path))
+                .basic_auth(&credentials.login_name, Some(&credentials.app_password))
+                .header("User-Agent", user_agent())
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to store remote encryption settings: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!(
+                    "Failed to store remote encryption settings, status {}",
+                    response.status()
+                ));
+            }
+            Ok(Some(key))
+        }
+    }
+}
+
 async fn delete_remote_file(
     client: &Client,
     credentials: &StoredCredentials,
@@ -941,7 +1316,7 @@ async fn delete_remote_file(
     }
 }
 
-fn list_local_files(local_root: &Path) -> Result<HashMap<String, LocalFile>, String> {
+pub(crate) fn list_local_files(local_root: &Path) -> Result<HashMap<String, LocalFile>, String> {
     let mut files = HashMap::new();
     if !local_root.exists() {
         return Ok(files);
@@ -990,7 +1365,40 @@ fn local_file_from_path(local_root: &Path, relative_path: &str) -> Result<LocalF
     })
 }
 
-fn write_local_file(local_root: &Path, relative_path: &str, bytes: &[u8]) -> Result<(), String> {
+/// The note at a local sync path, if it is one that parses
+fn local_note(local_root: &Path, relative_path: &str) -> Option<Note> {
+    let path = local_root.join(relative_path);
+    if !path.extension().is_some_and(|ext| ext == "md") || !path.is_file() {
+        return None;
+    }
+    parse_note(&path).ok()
+}
+
+/// Whether a local sync path holds a note with `locked: true`
+pub(crate) fn is_locked_local_file(local_root: &Path, relative_path: &str) -> bool {
+    local_note(local_root, relative_path).is_some_and(|note| is_locked(&note.frontmatter))
+}
+
+/// A locked local note keeps its content: remote edits and deletions of it
+/// are handled as conflicts, which keep the local side and set the remote
+/// version aside as a conflict copy
+pub(crate) fn locked_sync_decision(decision: SyncDecision, locked: bool) -> SyncDecision {
+    match decision {
+        SyncDecision::DownloadRemote | SyncDecision::DeleteLocal if locked => {
+            SyncDecision::Conflict
+        }
+        decision => decision,
+    }
+}
+
+pub(crate) fn write_local_file(
+    local_root: &Path,
+    relative_path: &str,
+    bytes: &[u8],
+) -> Result<(), String> {
+    if let Some(note) = local_note(local_root, relative_path) {
+        ensure_modifiable(&note.frontmatter, false)?;
+    }
     let path = local_root.join(relative_path);
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| format!("Failed to create local folder: {}", e))?;
@@ -998,7 +1406,10 @@ fn write_local_file(local_root: &Path, relative_path: &str, bytes: &[u8]) -> Res
     fs::write(path, bytes).map_err(|e| format!("Failed to write local file: {}", e))
 }
 
-fn delete_local_file(local_root: &Path, relative_path: &str) -> Result<(), String> {
+pub(crate) fn delete_local_file(local_root: &Path, relative_path: &str) -> Result<(), String> {
+    if let Some(note) = local_note(local_root, relative_path) {
+        ensure_modifiable(&note.frontmatter, false)?;
+    }
     let path = local_root.join(relative_path);
     if path.exists() {
         fs::remove_file(&path)
@@ -1007,90 +1418,27 @@ fn delete_local_file(local_root: &Path, relative_path: &str) -> Result<(), Strin
     Ok(())
 }
 
-fn write_conflict_file(
+pub(crate) fn write_conflict_file(
+    cache: &CacheDb,
     local_root: &Path,
     relative_path: &str,
     bytes: &[u8],
 ) -> Result<String, String> {
-    let mut candidate = conflict_relative_path(relative_path, 0);
-    let mut counter = 1;
-    while local_root.join(&candidate).exists() {
-        candidate = conflict_relative_path(relative_path, counter);
-        counter += 1;
-    }
-
-    let bytes = if relative_path.ends_with(".md") {
-        prepare_conflict_note(bytes)
-    } else {
-        bytes.to_vec()
-    };
-
-    write_local_file(local_root, &candidate, &bytes)?;
-    Ok(candidate)
-}
-
-fn prepare_conflict_note(bytes: &[u8]) -> Vec<u8> {
-    let Ok(text) = std::str::from_utf8(bytes) else {
-        return bytes.to_vec();
-    };
-    let parts: Vec<&str> = text.splitn(3, "---").collect();
-    if parts.len() != 3 || !parts[0].trim().is_empty() {
-        return bytes.to_vec();
+    let original = local_root.join(relative_path);
+    let conflict_path = write_conflict_copy(&original, bytes, REASON_SYNC, Some("remote"))?;
+    if let Err(e) = cache.upsert_conflict(&conflict_record(
+        &conflict_path,
+        &original,
+        REASON_SYNC,
+        Some("remote"),
+    )) {
+        log::warn!("Failed to index sync conflict: {}", e);
     }
 
-    let Ok(mut frontmatter) = serde_yaml::from_str::<serde_yaml::Value>(parts[1].trim()) else {
-        return bytes.to_vec();
-    };
-
-    if let serde_yaml::Value::Mapping(mapping) = &mut frontmatter {
-        mapping.insert(
-            serde_yaml::Value::String("id".to_string()),
-            serde_yaml::Value::String(Uuid::new_v4().to_string()),
-        );
-        mapping.insert(
-            serde_yaml::Value::String("modified".to_string()),
-            serde_yaml::Value::String(Utc::now().to_rfc3339()),
-        );
-        if let Some(serde_yaml::Value::String(title)) =
-            mapping.get_mut(&serde_yaml::Value::String("title".to_string()))
-        {
-            title.push_str(" (Conflict)");
-        }
-    }
-
-    let Ok(frontmatter) = serde_yaml::to_string(&frontmatter) else {
-        return bytes.to_vec();
-    };
-
-    format!("---\n{}---{}", frontmatter, parts[2]).into_bytes()
-}
-
-fn conflict_relative_path(relative_path: &str, counter: usize) -> String {
-    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
-    let path = Path::new(relative_path);
-    let parent = path
-        .parent()
+    conflict_path
+        .strip_prefix(local_root)
         .map(normalize_relative_path)
-        .unwrap_or_default();
-    let stem = path
-        .file_stem()
-        .map(|value| value.to_string_lossy().to_string())
-        .unwrap_or_else(|| "conflict".to_string());
-    let extension = path
-        .extension()
-        .map(|value| format!(".{}", value.to_string_lossy()))
-        .unwrap_or_default();
-    let suffix = if counter == 0 {
-        format!("conflict-{}", timestamp)
-    } else {
-        format!("conflict-{}-{}", timestamp, counter)
-    };
-    let filename = format!("{}.{}{}", stem, suffix, extension);
-    if parent.is_empty() {
-        filename
-    } else {
-        format!("{}/{}", parent, filename)
-    }
+        .map_err(|e| format!("Failed to compute conflict path: {}", e))
 }
 
 fn upsert_record(
@@ -1129,6 +1477,67 @@ fn upsert_record_with_etag(
         remote_mtime,
         remote_size,
         synced_at: Utc::now().timestamp(),
+    })?;
+    if local.hash == synced_hash {
+        store_sync_base(cache, relative_path, local);
+    }
+    Ok(())
+}
+
+/// Keep the synced content of a note as the base for later three-way merges
+fn store_sync_base(cache: &CacheDb, relative_path: &str, local: &LocalFile) {
+    if !relative_path.ends_with(".md") {
+        return;
+    }
+    if cache
+        .get_sync_base_hash(relative_path)
+        .ok()
+        .flatten()
+        .as_deref()
+        == Some(local.hash.as_str())
+    {
+        return;
+    }
+    let stored = fs::read(&local.path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| cache.set_sync_base(relative_path, &local.hash, &bytes));
+    if let Err(e) = stored {
+        log::warn!(
+            "Failed to store sync base for {}: {}",
+            logging::path(relative_path),
+            e
+        );
+    }
+}
+
+/// Merge a note changed on both sides against the content of its last sync,
+/// settling `column` and `order` clashes by the newest device stamp; `None`
+/// when there is no base or the edits can't be combined
+fn three_way_merge(
+    cache: &CacheDb,
+    local_root: &Path,
+    relative_path: &str,
+    local: &LocalFile,
+    remote_bytes: &[u8],
+) -> Option<TextMerge> {
+    if !relative_path.ends_with(".md") {
+        return None;
+    }
+    let synced_hash = cache
+        .get_sync_record(relative_path)
+        .ok()??
+        .last_synced_hash?;
+    let (base_hash, base) = cache.get_sync_base(relative_path).ok()??;
+    if base_hash != synced_hash {
+        return None;
+    }
+    let base = String::from_utf8(base).ok()?;
+    let local_text = fs::read_to_string(&local.path).ok()?;
+    let remote_text = std::str::from_utf8(remote_bytes).ok()?;
+    let metas = sync_meta::load_all(local_root);
+    let note_id = sync_meta::note_id(&local_text).unwrap_or_default();
+    merge_note(&base, &local_text, remote_text, &|field, ours, theirs| {
+        sync_meta::resolve(&metas, &note_id, field, ours, theirs)
     })
 }
 
@@ -1142,6 +1551,7 @@ fn read_sync_status(cache: &CacheDb) -> Result<SyncStatus, String> {
             last_sync_at: None,
             last_error: None,
             conflicts: Vec::new(),
+            pending_changes: 0,
         }),
     }
 }
@@ -1153,40 +1563,27 @@ fn write_sync_status(cache: &CacheDb, status: SyncStatus) -> Result<(), String>
 }
 
 fn store_credentials(profile_id: &str, credentials: &StoredCredentials) -> Result<(), String> {
-    let entry = keyring::Entry::new(KEYRING_SERVICE, &credential_account(profile_id))
-        .map_err(|e| format!("Failed to open credential store: {}", e))?;
     let value = serde_json::to_string(credentials)
         .map_err(|e| format!("Failed to encode Nextcloud credentials: {}", e))?;
-    entry
-        .set_password(&value)
-        .map_err(|e| format!("Failed to store Nextcloud credentials: {}", e))
+    write_secret(KEYRING_SERVICE, &profile_account(profile_id), &value)
 }
 
 fn load_credentials(profile_id: &str) -> Result<StoredCredentials, String> {
-    let entry = keyring::Entry::new(KEYRING_SERVICE, &credential_account(profile_id))
-        .map_err(|e| format!("Failed to open credential store: {}", e))?;
-    let value = entry
-        .get_password()
-        .map_err(|e| format!("Nextcloud account is not connected: {}", e))?;
+    let value = read_secret(KEYRING_SERVICE, &profile_account(profile_id))?
+        .ok_or("Nextcloud account is not connected")?;
     serde_json::from_str(&value)
         .map_err(|e| format!("Failed to decode Nextcloud credentials: {}", e))
 }
 
 fn delete_credentials(profile_id: &str) -> Result<(), String> {
-    let entry = keyring::Entry::new(KEYRING_SERVICE, &credential_account(profile_id))
-        .map_err(|e| format!("Failed to open credential store: {}", e))?;
-    match entry.delete_credential() {
-        Ok(()) => Ok(()),
-        Err(keyring::Error::NoEntry) => Ok(()),
-        Err(e) => Err(format!("Failed to remove Nextcloud credentials: {}", e)),
-    }
+    remove_secret(KEYRING_SERVICE, &profile_account(profile_id))
 }
 
-fn credential_account(profile_id: &str) -> String {
-    format!("profile:{}", profile_id)
+fn load_passphrase(profile_id: &str) -> Result<Option<String>, String> {
+    read_secret(ENCRYPTION_KEYRING_SERVICE, &profile_account(profile_id))
 }
 
-fn default_notes_dir(profile_id: &str) -> Result<PathBuf, String> {
+pub(crate) fn default_notes_dir(profile_id: &str) -> Result<PathBuf, String> {
     let dirs = ProjectDirs::from("", "", "noteban")
         .ok_or("Could not determine app data directory".to_string())?;
     Ok(dirs
@@ -1299,7 +1696,7 @@ fn join_relative(first: &str, second: &str) -> String {
     }
 }
 
-fn normalize_relative_path(path: impl AsRef<Path>) -> String {
+pub(crate) fn normalize_relative_path(path: impl AsRef<Path>) -> String {
     path.as_ref()
         .components()
         .filter_map(|component| match component {
@@ -1310,8 +1707,15 @@ fn normalize_relative_path(path: impl AsRef<Path>) -> String {
         .join("/")
 }
 
-fn should_sync_file(relative_path: &str) -> bool {
+pub(crate) fn should_sync_file(relative_path: &str) -> bool {
+    if relative_path
+        .split('/')
+        .any(|segment| segment == TRASH_DIR_NAME)
+    {
+        return false;
+    }
     relative_path.ends_with(".md")
+        || sync_meta::is_sync_meta_path(relative_path)
         || relative_path
             .split('/')
             .any(|segment| segment.ends_with(".attachments"))
@@ -1377,6 +1781,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn honors_local_only_and_sync_only_folders() {
+        let config = SelectiveSyncConfig {
+            local_only: vec!["/Work/Private/".to_string()],
+            sync_only: vec!["Work".to_string(), "Journal".to_string()],
+        }
+        .normalized()
+        .unwrap();
+        assert!(config.includes("Work/plan.md"));
+        assert!(config.includes("Journal/2024/day.md"));
+        assert!(!config.includes("Work/Private/secret.md"));
+        assert!(!config.includes("Workshop/note.md"));
+        assert!(!config.includes("root.md"));
+        assert!(SelectiveSyncConfig::default().includes("root.md"));
+    }
+
     #[test]
     fn normalizes_server_urls() {
         assert_eq!(
@@ -1484,8 +1904,26 @@ order: 0
 ---
 
 Body"#;
-        let rewritten = String::from_utf8(prepare_conflict_note(original)).unwrap();
+        let rewritten = String::from_utf8(crate::commands::conflicts::add_conflict_provenance(
+            original,
+            "test.md",
+            REASON_SYNC,
+            Some("remote"),
+        ))
+        .unwrap();
         assert!(rewritten.contains("title: Test (Conflict)"));
         assert!(!rewritten.contains("id: original"));
+
+        let toml = b"+++\nid = \"original\"\ntitle = \"Test\"\n+++\n\nBody";
+        let rewritten = String::from_utf8(crate::commands::conflicts::add_conflict_provenance(
+            toml,
+            "test.md",
+            REASON_SYNC,
+            Some("remote"),
+        ))
+        .unwrap();
+        assert!(rewritten.starts_with("+++\n"));
+        assert!(rewritten.contains("title = \"Test (Conflict)\""));
+        assert!(!rewritten.contains("id = \"original\""));
     }
 }
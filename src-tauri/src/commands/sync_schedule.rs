@@ -0,0 +1,243 @@
+use crate::commands::git_sync::git_sync_now;
+use crate::commands::sync::sync_now;
+use crate::lock_or_err;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+pub(crate) const SYNC_SCHEDULE_KEY: &str = "sync_schedule_config";
+/// How often the scheduler checks whether a sync is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(5);
+const BACKOFF_BASE: Duration = Duration::from_secs(30);
+const BACKOFF_MAX: Duration = Duration::from_secs(60 * 60);
+
+static SCHEDULE: Mutex<ScheduleState> = Mutex::new(ScheduleState {
+    last_run: None,
+    last_edit: None,
+    failures: 0,
+    retry_at: None,
+});
+
+fn default_interval_minutes() -> u32 {
+    15
+}
+
+fn default_quiet_seconds() -> u32 {
+    30
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncProvider {
+    Nextcloud,
+    Git,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncScheduleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub provider: SyncProvider,
+    /// Sync at least this often
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u32,
+    /// Sync once local edits have been quiet for this long
+    #[serde(default = "default_quiet_seconds")]
+    pub quiet_seconds: u32,
+    /// Vault synced by the git provider
+    #[serde(default)]
+    pub notes_dir: Option<String>,
+    /// Remote folder used by the Nextcloud provider
+    #[serde(default)]
+    pub remote_folder: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScheduleState {
+    last_run: Option<Instant>,
+    last_edit: Option<Instant>,
+    /// Failed attempts in a row
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+/// Note a local change so a sync follows once edits go quiet
+pub(crate) fn mark_local_edit() {
+    if let Ok(mut schedule) = SCHEDULE.lock() {
+        schedule.last_edit = Some(Instant::now());
+    }
+}
+
+fn backoff_delay(failures: u32) -> Duration {
+    BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(BACKOFF_MAX)
+}
+
+fn is_due(config: &SyncScheduleConfig, schedule: &ScheduleState, now: Instant) -> bool {
+    if schedule.retry_at.is_some_and(|retry_at| now < retry_at) {
+        return false;
+    }
+    let Some(last_run) = schedule.last_run else {
+        return true;
+    };
+    let interval = Duration::from_secs(u64::from(config.interval_minutes.max(1)) * 60);
+    let quiet = Duration::from_secs(u64::from(config.quiet_seconds));
+    let settled_edit = schedule
+        .last_edit
+        .is_some_and(|edit| edit > last_run && now.duration_since(edit) >= quiet);
+    now.duration_since(last_run) >= interval || settled_edit
+}
+
+fn record_attempt(schedule: &mut ScheduleState, succeeded: bool, now: Instant) {
+    schedule.last_run = Some(now);
+    if succeeded {
+        schedule.failures = 0;
+        schedule.retry_at = None;
+    } else {
+        schedule.failures = schedule.failures.saturating_add(1);
+        schedule.retry_at = Some(now + backoff_delay(schedule.failures));
+    }
+}
+
+fn load_schedule(state: &AppState) -> Result<Option<(SyncScheduleConfig, String)>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let Some(cache) = cache_lock.as_ref() else {
+        return Ok(None);
+    };
+    let config = cache
+        .get_meta(SYNC_SCHEDULE_KEY)?
+        .and_then(|value| serde_json::from_str::<SyncScheduleConfig>(&value).ok());
+    Ok(config.map(|config| (config, cache.profile_id.clone())))
+}
+
+fn run_scheduled_sync(app: &AppHandle) {
+    let Ok(Some((config, profile_id))) = load_schedule(&app.state::<AppState>()) else {
+        return;
+    };
+    if !config.enabled {
+        return;
+    }
+    let Ok(schedule) = SCHEDULE.lock().map(|schedule| *schedule) else {
+        return;
+    };
+    if !is_due(&config, &schedule, Instant::now()) {
+        return;
+    }
+
+    let result = match config.provider {
+        SyncProvider::Nextcloud => tauri::async_runtime::block_on(sync_now(
+            profile_id,
+            config.remote_folder.clone(),
+            app.clone(),
+        ))
+        .map(|_| ()),
+        SyncProvider::Git => match config.notes_dir.clone() {
+            Some(notes_dir) => {
+                tauri::async_runtime::block_on(git_sync_now(notes_dir, app.clone())).map(|_| ())
+            }
+            None => Err("Scheduled git sync needs a notes directory".to_string()),
+        },
+    };
+
+    if let Ok(mut schedule) = SCHEDULE.lock() {
+        record_attempt(&mut schedule, result.is_ok(), Instant::now());
+        if let Err(e) = &result {
+            log::warn!(
+                "Scheduled sync failed, retrying in {}s: {}",
+                backoff_delay(schedule.failures).as_secs(),
+                e
+            );
+        }
+    }
+}
+
+/// Sync the active profile on its schedule and after local edits settle,
+/// backing off exponentially while syncs fail
+pub fn start_sync_scheduler(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(SCHEDULER_TICK);
+        run_scheduled_sync(&app);
+    });
+}
+
+#[tauri::command]
+pub fn get_sync_schedule(state: State<AppState>) -> Result<Option<SyncScheduleConfig>, String> {
+    Ok(load_schedule(&state)?.map(|(config, _)| config))
+}
+
+#[tauri::command]
+pub fn set_sync_schedule(
+    config: SyncScheduleConfig,
+    state: State<AppState>,
+) -> Result<SyncScheduleConfig, String> {
+    if config.interval_minutes == 0 {
+        return Err("Sync interval must be at least one minute".to_string());
+    }
+    if config.enabled && config.provider == SyncProvider::Git && config.notes_dir.is_none() {
+        return Err("Scheduled git sync needs a notes directory".to_string());
+    }
+    let encoded = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to encode sync schedule: {}", e))?;
+    {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        cache.set_meta(SYNC_SCHEDULE_KEY, &encoded)?;
+    }
+    // A new schedule starts without the previous one's backoff
+    if let Ok(mut schedule) = SCHEDULE.lock() {
+        schedule.failures = 0;
+        schedule.retry_at = None;
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedules_after_quiet_edits_and_backs_off() {
+        let config = SyncScheduleConfig {
+            enabled: true,
+            provider: SyncProvider::Nextcloud,
+            interval_minutes: 15,
+            quiet_seconds: 30,
+            notes_dir: None,
+            remote_folder: None,
+        };
+        let start = Instant::now();
+        let mut schedule = ScheduleState {
+            last_run: None,
+            last_edit: None,
+            failures: 0,
+            retry_at: None,
+        };
+        assert!(is_due(&config, &schedule, start));
+
+        record_attempt(&mut schedule, true, start);
+        assert!(!is_due(&config, &schedule, start + Duration::from_secs(60)));
+        schedule.last_edit = Some(start + Duration::from_secs(60));
+        assert!(!is_due(&config, &schedule, start + Duration::from_secs(70)));
+        assert!(is_due(&config, &schedule, start + Duration::from_secs(90)));
+        assert!(is_due(
+            &config,
+            &schedule,
+            start + Duration::from_secs(15 * 60)
+        ));
+
+        let failed_at = start + Duration::from_secs(100);
+        record_attempt(&mut schedule, false, failed_at);
+        record_attempt(&mut schedule, false, failed_at);
+        assert_eq!(schedule.retry_at, Some(failed_at + Duration::from_secs(60)));
+        assert!(!is_due(
+            &config,
+            &schedule,
+            failed_at + Duration::from_secs(59)
+        ));
+        assert_eq!(backoff_delay(20), BACKOFF_MAX);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::cache::tags::TagPairCount;
+use crate::commands::notes::atomic_write;
+use crate::lock_or_err;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Tag co-occurrence as a symmetric matrix. `matrix[i][j]` is the number of
+/// notes tagged with both `tags[i]` and `tags[j]`; the diagonal holds the
+/// number of notes carrying each tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCooccurrenceMatrix {
+    pub tags: Vec<String>,
+    pub matrix: Vec<Vec<i64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagExportSummary {
+    pub path: String,
+    pub tag_count: usize,
+    pub pair_count: usize,
+}
+
+fn build_matrix(tag_counts: &[(String, i64)], pairs: &[TagPairCount]) -> TagCooccurrenceMatrix {
+    let tags: Vec<String> = tag_counts.iter().map(|(name, _)| name.clone()).collect();
+    let index: HashMap<&str, usize> = tags
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+
+    let mut matrix = vec![vec![0; tags.len()]; tags.len()];
+    for (i, (_, count)) in tag_counts.iter().enumerate() {
+        matrix[i][i] = *count;
+    }
+    for pair in pairs {
+        if let (Some(&a), Some(&b)) = (
+            index.get(pair.tag_a.as_str()),
+            index.get(pair.tag_b.as_str()),
+        ) {
+            matrix[a][b] = pair.count;
+            matrix[b][a] = pair.count;
+        }
+    }
+
+    TagCooccurrenceMatrix { tags, matrix }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn matrix_to_csv(data: &TagCooccurrenceMatrix) -> String {
+    let mut out = String::from("tag");
+    for tag in &data.tags {
+        out.push(',');
+        out.push_str(&csv_field(tag));
+    }
+    out.push('\n');
+    for (tag, row) in data.tags.iter().zip(&data.matrix) {
+        out.push_str(&csv_field(tag));
+        for count in row {
+            out.push(',');
+            out.push_str(&count.to_string());
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Export tag co-occurrence counts from the cache as a CSV or JSON matrix.
+/// The format follows `format` ("csv" or "json"), or the extension of `dest`.
+#[tauri::command]
+pub fn export_tag_cooccurrence(
+    dest: String,
+    format: Option<String>,
+    state: State<AppState>,
+) -> Result<TagExportSummary, String> {
+    let dest_path = PathBuf::from(&dest);
+    let format = match format {
+        Some(format) => format.to_lowercase(),
+        None => dest_path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "csv".to_string()),
+    };
+
+    let (tag_counts, pairs) = {
+        let cache_lock = lock_or_err(&state.cache)?;
+        let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+        (cache.get_tag_note_counts()?, cache.get_tag_cooccurrence()?)
+    };
+    let data = build_matrix(&tag_counts, &pairs);
+
+    let content = match format.as_str() {
+        "csv" => matrix_to_csv(&data),
+        "json" => serde_json::to_string_pretty(&data)
+            .map_err(|e| format!("Failed to serialize tag matrix: {}", e))?,
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+
+    if let Some(parent) = dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err("Destination folder does not exist".to_string());
+        }
+    }
+    atomic_write(&dest_path, &content)?;
+
+    Ok(TagExportSummary {
+        path: dest,
+        tag_count: data.tags.len(),
+        pair_count: pairs.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_symmetric_matrix_csv() {
+        let counts = vec![
+            ("idea".to_string(), 3),
+            ("rust".to_string(), 2),
+            ("work, misc".to_string(), 1),
+        ];
+        let pairs = vec![TagPairCount {
+            tag_a: "idea".to_string(),
+            tag_b: "rust".to_string(),
+            count: 2,
+        }];
+        let data = build_matrix(&counts, &pairs);
+        assert_eq!(data.matrix[0], vec![3, 2, 0]);
+        assert_eq!(data.matrix[1], vec![2, 2, 0]);
+
+        let csv = matrix_to_csv(&data);
+        assert_eq!(
+            csv,
+            "tag,idea,rust,\"work, misc\"\nidea,3,2,0\nrust,2,2,0\n\"work, misc\",0,0,1\n"
+        );
+    }
+}
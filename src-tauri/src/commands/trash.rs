@@ -0,0 +1,507 @@
+use crate::commands::audit;
+use crate::commands::note_index;
+use crate::commands::notes::{
+    atomic_write, ensure_safe_relative_path, get_file_mtime, is_skipped_dir_name, parse_note,
+    parse_note_content, record_write, serialize_note, validate_existing_path_within_base, Folder,
+};
+use crate::logging;
+use crate::utils::{compute_content_hash, extract_inline_tags};
+use crate::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+/// Name of the vault-level directory holding deleted notes and folders
+pub const TRASH_DIR_NAME: &str = ".trash";
+const TRASH_MANIFEST: &str = "item.json";
+const TRASH_FILES_DIR: &str = "files";
+pub(crate) const TRASH_RETENTION_KEY: &str = "trash_retention_days";
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+pub const TRASH_KIND_NOTE: &str = "note";
+pub const TRASH_KIND_FOLDER: &str = "folder";
+
+/// Manifest stored next to each trashed item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub id: String,
+    pub kind: String,
+    /// Path relative to the vault root the item was deleted from
+    pub original_path: String,
+    pub title: Option<String>,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoredItem {
+    pub id: String,
+    pub kind: String,
+    pub path: String,
+}
+
+fn trash_root(base: &Path) -> PathBuf {
+    base.join(TRASH_DIR_NAME)
+}
+
+fn validate_trash_id(trash_id: &str) -> Result<(), String> {
+    if trash_id.is_empty()
+        || !trash_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err("Invalid trash item id".to_string());
+    }
+    Ok(())
+}
+
+fn relative_to_base(path: &Path, base: &Path) -> Result<PathBuf, String> {
+    let canonical_path = validate_existing_path_within_base(path, base)?;
+    let canonical_base = base
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve base path: {}", e))?;
+    canonical_path
+        .strip_prefix(&canonical_base)
+        .map(Path::to_path_buf)
+        .map_err(|e| format!("Failed to get relative path: {}", e))
+}
+
+fn to_manifest_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn new_trash_item(kind: &str, relative: &Path, title: Option<String>) -> TrashItem {
+    let now = Utc::now();
+    let suffix: String = Uuid::new_v4()
+        .simple()
+        .to_string()
+        .chars()
+        .take(8)
+        .collect();
+    TrashItem {
+        id: format!("{}-{}", now.format("%Y%m%d%H%M%S"), suffix),
+        kind: kind.to_string(),
+        original_path: to_manifest_path(relative),
+        title,
+        deleted_at: now,
+    }
+}
+
+fn write_manifest(item_dir: &Path, item: &TrashItem) -> Result<(), String> {
+    let manifest = serde_json::to_string_pretty(item)
+        .map_err(|e| format!("Failed to encode trash manifest: {}", e))?;
+    atomic_write(&item_dir.join(TRASH_MANIFEST), &manifest)
+}
+
+fn attachments_dir_for(note_path: &Path) -> Option<PathBuf> {
+    let stem = note_path.file_stem()?.to_string_lossy().to_string();
+    Some(note_path.parent()?.join(format!("{}.attachments", stem)))
+}
+
+/// Move a note (and its attachments folder) into the vault trash
+pub(crate) fn move_note_to_trash(
+    base: &Path,
+    note_path: &Path,
+    title: Option<String>,
+) -> Result<TrashItem, String> {
+    let relative = relative_to_base(note_path, base)?;
+    let item = new_trash_item(TRASH_KIND_NOTE, &relative, title);
+    let item_dir = trash_root(base).join(&item.id);
+    let destination = item_dir.join(TRASH_FILES_DIR).join(&relative);
+    let dest_parent = destination.parent().ok_or("Invalid note path")?;
+    fs::create_dir_all(dest_parent).map_err(|e| format!("Failed to create trash folder: {}", e))?;
+
+    // Move attachments first so a failed note move can be rolled back
+    let source_attachments = attachments_dir_for(note_path).filter(|p| p.is_dir());
+    let dest_attachments = attachments_dir_for(&destination);
+    if let (Some(src), Some(dest)) = (source_attachments.as_ref(), dest_attachments.as_ref()) {
+        fs::rename(src, dest).map_err(|e| format!("Failed to move attachments to trash: {}", e))?;
+    }
+
+    if let Err(e) = fs::rename(note_path, &destination) {
+        if let (Some(src), Some(dest)) = (source_attachments.as_ref(), dest_attachments.as_ref()) {
+            if let Err(rollback_err) = fs::rename(dest, src) {
+                log::error!(
+                    "Failed to rollback attachments trash move from {} to {}: {}. Manual cleanup may be required.",
+                    logging::path(dest), logging::path(src), rollback_err
+                );
+            }
+        }
+        let _ = fs::remove_dir_all(&item_dir);
+        return Err(format!("Failed to move note to trash: {}", e));
+    }
+
+    write_manifest(&item_dir, &item)?;
+    Ok(item)
+}
+
+/// Move a whole folder into the vault trash
+pub(crate) fn move_folder_to_trash(base: &Path, folder_path: &Path) -> Result<TrashItem, String> {
+    let relative = relative_to_base(folder_path, base)?;
+    let title = folder_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string());
+    let item = new_trash_item(TRASH_KIND_FOLDER, &relative, title);
+    let item_dir = trash_root(base).join(&item.id);
+    let destination = item_dir.join(TRASH_FILES_DIR).join(&relative);
+    let dest_parent = destination.parent().ok_or("Invalid folder path")?;
+    fs::create_dir_all(dest_parent).map_err(|e| format!("Failed to create trash folder: {}", e))?;
+
+    if let Err(e) = fs::rename(folder_path, &destination) {
+        let _ = fs::remove_dir_all(&item_dir);
+        return Err(format!("Failed to move folder to trash: {}", e));
+    }
+
+    write_manifest(&item_dir, &item)?;
+    Ok(item)
+}
+
+fn read_item(item_dir: &Path) -> Option<TrashItem> {
+    let manifest = fs::read_to_string(item_dir.join(TRASH_MANIFEST)).ok()?;
+    serde_json::from_str(&manifest).ok()
+}
+
+fn read_items(base: &Path) -> Vec<TrashItem> {
+    let Ok(entries) = fs::read_dir(trash_root(base)) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| read_item(&entry.path()))
+        .filter(|item| validate_trash_id(&item.id).is_ok())
+        .collect()
+}
+
+/// Permanently remove trashed items older than the retention period
+pub(crate) fn purge_expired(base: &Path, retention_days: i64) -> usize {
+    let cutoff = Utc::now() - ChronoDuration::days(retention_days);
+    let mut purged = 0;
+    for item in read_items(base) {
+        if item.deleted_at < cutoff {
+            match fs::remove_dir_all(trash_root(base).join(&item.id)) {
+                Ok(()) => purged += 1,
+                Err(e) => log::warn!("Failed to purge trash item {}: {}", item.id, e),
+            }
+        }
+    }
+    purged
+}
+
+pub(crate) fn retention_days(state: &State<AppState>) -> i64 {
+    let configured = state.cache.lock().ok().and_then(|cache_lock| {
+        cache_lock
+            .as_ref()
+            .and_then(|cache| cache.get_meta(TRASH_RETENTION_KEY).ok().flatten())
+            .and_then(|value| value.parse::<i64>().ok())
+    });
+    configured.unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+}
+
+/// Find a free destination for a restored item by appending `-N` to its stem
+fn unique_restore_path(target: &Path, is_note: bool) -> PathBuf {
+    if !target.exists() {
+        return target.to_path_buf();
+    }
+    let parent = target.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = if is_note {
+        target.file_stem()
+    } else {
+        target.file_name()
+    }
+    .map(|s| s.to_string_lossy().to_string())
+    .unwrap_or_default();
+    let mut counter = 1;
+    loop {
+        let candidate = if is_note {
+            parent.join(format!("{}-{}.md", stem, counter))
+        } else {
+            parent.join(format!("{}-{}", stem, counter))
+        };
+        if !candidate.exists() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+fn restore_note(source: &Path, target: &Path, state: &State<AppState>) -> Result<PathBuf, String> {
+    let final_target = unique_restore_path(target, true);
+    let final_target_str = final_target.to_string_lossy().to_string();
+    record_write(&final_target_str, state);
+
+    let source_attachments = attachments_dir_for(source).filter(|p| p.is_dir());
+    let dest_attachments = attachments_dir_for(&final_target);
+    if let (Some(src), Some(dest)) = (source_attachments.as_ref(), dest_attachments.as_ref()) {
+        if dest.exists() {
+            return Err("Attachments folder already exists".to_string());
+        }
+        fs::rename(src, dest).map_err(|e| format!("Failed to restore attachments: {}", e))?;
+    }
+
+    if let Err(e) = fs::rename(source, &final_target) {
+        if let (Some(src), Some(dest)) = (source_attachments.as_ref(), dest_attachments.as_ref()) {
+            if let Err(rollback_err) = fs::rename(dest, src) {
+                log::error!(
+                    "Failed to rollback attachments restore from {} to {}: {}. Manual cleanup may be required.",
+                    logging::path(dest), logging::path(src), rollback_err
+                );
+            }
+        }
+        return Err(format!("Failed to restore note: {}", e));
+    }
+
+    let mut note = parse_note(&final_target)?;
+
+    // Keep attachment links valid if the note had to be restored under a new name
+    let old_stem = target
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let new_stem = final_target
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if old_stem != new_stem {
+        let old_pattern = format!("{}.attachments/", old_stem);
+        let new_pattern = format!("{}.attachments/", new_stem);
+        if note.content.contains(&old_pattern) {
+            note.content = note.content.replace(&old_pattern, &new_pattern);
+            let file_content = serialize_note(&note.frontmatter, &note.content);
+            atomic_write(&final_target, &file_content)?;
+        }
+    }
+
+    if let Ok(cache_lock) = state.cache.lock() {
+        if let Some(cache) = cache_lock.as_ref() {
+            let content = fs::read_to_string(&final_target).unwrap_or_default();
+            let hash = compute_content_hash(&content);
+            let mtime = get_file_mtime(&final_target).unwrap_or(0);
+            let inline_tags = extract_inline_tags(&note.content);
+            if let Err(e) = cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+                log::warn!("Cache update failed for restored note: {}", e);
+            }
+        }
+    }
+
+    Ok(final_target)
+}
+
+fn restore_folder(
+    base: &Path,
+    source: &Path,
+    target: &Path,
+    state: &State<AppState>,
+) -> Result<PathBuf, String> {
+    let final_target = unique_restore_path(target, false);
+    // Everything comes back in one rename; none of it is an outside change
+    for entry in WalkDir::new(source).into_iter().filter_map(|e| e.ok()) {
+        if let Ok(relative) = entry.path().strip_prefix(source) {
+            record_write(&final_target.join(relative).to_string_lossy(), state);
+        }
+    }
+    fs::rename(source, &final_target).map_err(|e| format!("Failed to restore folder: {}", e))?;
+
+    note_index::invalidate(state);
+    let Ok(cache_lock) = state.cache.lock() else {
+        return Ok(final_target);
+    };
+    let Some(cache) = cache_lock.as_ref() else {
+        return Ok(final_target);
+    };
+    let entries = WalkDir::new(&final_target)
+        .into_iter()
+        .filter_entry(|e| {
+            !e.file_name()
+                .to_str()
+                .map(is_skipped_dir_name)
+                .unwrap_or(false)
+        })
+        .filter_map(|e| e.ok());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            let Ok(relative) = path.strip_prefix(base) else {
+                continue;
+            };
+            let folder = Folder {
+                path: path.to_string_lossy().to_string(),
+                name: entry.file_name().to_string_lossy().to_string(),
+                relative_path: relative.to_string_lossy().to_string(),
+            };
+            if let Err(e) = cache.upsert_folder(&folder) {
+                log::warn!("Failed to index restored folder: {}", e);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            match parse_note_content(&content, path) {
+                Ok(note) => {
+                    let hash = compute_content_hash(&content);
+                    let mtime = get_file_mtime(&path.to_path_buf()).unwrap_or(0);
+                    let inline_tags = extract_inline_tags(&note.content);
+                    if let Err(e) = cache.upsert_note(&note, &hash, mtime, &inline_tags) {
+                        log::warn!("Cache update failed for restored note: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Skipping invalid note {}: {}", logging::path(path), e),
+            }
+        }
+    }
+    Ok(final_target)
+}
+
+#[tauri::command]
+pub fn list_trash(notes_dir: String, state: State<AppState>) -> Result<Vec<TrashItem>, String> {
+    let base = PathBuf::from(&notes_dir);
+    purge_expired(&base, retention_days(&state));
+
+    let mut items = read_items(&base);
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    Ok(items)
+}
+
+#[tauri::command]
+pub fn restore_from_trash(
+    notes_dir: String,
+    trash_id: String,
+    state: State<AppState>,
+) -> Result<RestoredItem, String> {
+    validate_trash_id(&trash_id)?;
+    let item_dir = trash_root(Path::new(&notes_dir)).join(&trash_id);
+    let result = restore_item(&notes_dir, &item_dir, &state);
+    let restored_to = result.as_ref().ok().map(|item| item.path.as_str());
+    audit::record(
+        &state,
+        "restore",
+        &item_dir.to_string_lossy(),
+        restored_to,
+        &result,
+    );
+    result
+}
+
+fn restore_item(
+    notes_dir: &str,
+    item_dir: &Path,
+    state: &State<AppState>,
+) -> Result<RestoredItem, String> {
+    let base = PathBuf::from(notes_dir);
+    let item = read_item(item_dir).ok_or("Trash item not found")?;
+
+    let relative = PathBuf::from(&item.original_path);
+    ensure_safe_relative_path(&relative)?;
+    let source = item_dir.join(TRASH_FILES_DIR).join(&relative);
+    if !source.exists() {
+        return Err("Trashed files are missing".to_string());
+    }
+
+    let target = base.join(&relative);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to recreate parent folder: {}", e))?;
+    }
+
+    let restored = if item.kind == TRASH_KIND_FOLDER {
+        restore_folder(&base, &source, &target, state)?
+    } else {
+        restore_note(&source, &target, state)?
+    };
+
+    if let Err(e) = fs::remove_dir_all(item_dir) {
+        log::warn!("Failed to clean up trash item {}: {}", item.id, e);
+    }
+
+    Ok(RestoredItem {
+        id: item.id,
+        kind: item.kind,
+        path: restored.to_string_lossy().to_string(),
+    })
+}
+
+/// Permanently delete one trashed item, or everything in the trash when no id is given
+#[tauri::command]
+pub fn empty_trash(notes_dir: String, trash_id: Option<String>) -> Result<usize, String> {
+    let base = PathBuf::from(&notes_dir);
+    let root = trash_root(&base);
+
+    if let Some(trash_id) = trash_id {
+        validate_trash_id(&trash_id)?;
+        let item_dir = root.join(&trash_id);
+        if !item_dir.exists() {
+            return Err("Trash item not found".to_string());
+        }
+        fs::remove_dir_all(&item_dir).map_err(|e| format!("Failed to empty trash: {}", e))?;
+        return Ok(1);
+    }
+
+    let Ok(entries) = fs::read_dir(&root) else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path).map_err(|e| format!("Failed to empty trash: {}", e))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn set_trash_retention_days(days: u32, state: State<AppState>) -> Result<(), String> {
+    if days == 0 {
+        return Err("Trash retention must be at least one day".to_string());
+    }
+    let cache_lock = crate::lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.set_meta(TRASH_RETENTION_KEY, &days.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::delete_folder;
+    use crate::test_support::TestVault;
+
+    #[test]
+    fn restoring_a_folder_indexes_its_notes_and_folders() {
+        let vault = TestVault::new();
+        let notes_dir = vault.notes_dir();
+        let note = vault.note("Work/sub/a.md", "a", "");
+        delete_folder(notes_dir.clone(), vault.path("Work"), vault.state()).unwrap();
+        assert!(!vault.exists("Work"));
+
+        let items = list_trash(notes_dir.clone(), vault.state()).unwrap();
+        assert_eq!(items.len(), 1);
+        let restored = restore_from_trash(notes_dir, items[0].id.clone(), vault.state()).unwrap();
+        assert_eq!(restored.path, vault.path("Work"));
+        assert!(vault.exists("Work/sub/a.md"));
+
+        let state = vault.state();
+        assert!(state.recent_writes.lock().unwrap().contains_key(&note));
+        let cache_lock = state.cache.lock().unwrap();
+        let cache = cache_lock.as_ref().unwrap();
+        assert_eq!(
+            cache.get_note(&note).unwrap().unwrap().note.frontmatter.id,
+            "a"
+        );
+        let folders: Vec<String> = cache
+            .get_folders()
+            .unwrap()
+            .into_iter()
+            .map(|folder| folder.relative_path)
+            .collect();
+        assert!(folders.contains(&"Work".to_string()));
+        assert!(folders.contains(&"Work/sub".to_string()));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::commands::audit;
+use crate::commands::notes::{move_note_file, rename_folder_on_disk};
+use crate::commands::trash::restore_from_trash;
+use crate::lock_or_err;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::path::Path;
+use tauri::State;
+
+/// Operations kept for undo; older ones are dropped
+const MAX_UNDO_ENTRIES: usize = 50;
+
+thread_local! {
+    /// Steps collected by the grouped operation running on this thread
+    static OPEN_GROUP: RefCell<Option<Vec<UndoStep>>> = const { RefCell::new(None) };
+}
+
+/// One reversible change made by a destructive command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoStep {
+    /// A note or folder moved to the trash
+    Trashed { trash_id: String },
+    /// A note moved from `from` to `to` (absolute paths)
+    Moved { from: String, to: String },
+    /// A folder renamed from `from` to `to` (absolute paths)
+    RenamedFolder { from: String, to: String },
+}
+
+/// A user-visible operation; bulk operations group many steps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub label: String,
+    pub notes_dir: String,
+    pub at: DateTime<Utc>,
+    pub steps: Vec<UndoStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoResult {
+    pub label: String,
+    /// Paths the undone steps brought back
+    pub restored_paths: Vec<String>,
+}
+
+pub(crate) fn push_undo(
+    state: &State<AppState>,
+    label: String,
+    notes_dir: &str,
+    steps: Vec<UndoStep>,
+) {
+    if steps.is_empty() {
+        return;
+    }
+    let grouped = OPEN_GROUP.with(|group| match group.borrow_mut().as_mut() {
+        Some(open) => {
+            open.extend(steps.iter().cloned());
+            true
+        }
+        None => false,
+    });
+    if grouped {
+        return;
+    }
+    let Ok(mut stack) = state.undo_stack.lock() else {
+        return;
+    };
+    stack.push(UndoEntry {
+        label,
+        notes_dir: notes_dir.to_string(),
+        at: Utc::now(),
+        steps,
+    });
+    let overflow = stack.len().saturating_sub(MAX_UNDO_ENTRIES);
+    stack.drain(..overflow);
+}
+
+/// Run `f` as one operation: whatever the commands it runs push is undone
+/// in one go under `label`
+pub(crate) fn grouped<T>(
+    state: &State<AppState>,
+    label: String,
+    notes_dir: &str,
+    f: impl FnOnce() -> T,
+) -> T {
+    let outer = OPEN_GROUP.with(|group| group.replace(Some(Vec::new())));
+    let result = f();
+    let steps = OPEN_GROUP
+        .with(|group| group.replace(outer))
+        .unwrap_or_default();
+    push_undo(state, label, notes_dir, steps);
+    result
+}
+
+pub(crate) fn clear_undo(state: &State<AppState>) {
+    if let Ok(mut stack) = state.undo_stack.lock() {
+        stack.clear();
+    }
+}
+
+fn undo_step(notes_dir: &str, step: &UndoStep, state: &State<AppState>) -> Result<String, String> {
+    match step {
+        UndoStep::Trashed { trash_id } => {
+            restore_from_trash(notes_dir.to_string(), trash_id.clone(), state.clone())
+                .map(|restored| restored.path)
+        }
+        UndoStep::Moved { from, to } => {
+            let original_folder = Path::new(from)
+                .parent()
+                .ok_or("Invalid original note path")?
+                .to_string_lossy()
+                .to_string();
+            let result =
+                move_note_file(notes_dir, to, &original_folder, state).map(|note| note.file_path);
+            let moved_to = result.as_ref().ok().map(String::as_str);
+            audit::record(state, "move", to, moved_to, &result);
+            result
+        }
+        UndoStep::RenamedFolder { from, to } => {
+            let original_name = Path::new(from)
+                .file_name()
+                .ok_or("Invalid original folder path")?
+                .to_string_lossy()
+                .to_string();
+            let result = rename_folder_on_disk(notes_dir, to, original_name, state)
+                .map(|folder| folder.path);
+            let renamed_to = result.as_ref().ok().map(String::as_str);
+            audit::record(state, "move", to, renamed_to, &result);
+            result
+        }
+    }
+}
+
+/// Operations that can be undone, most recent first
+#[tauri::command]
+pub fn list_undo_operations(state: State<AppState>) -> Result<Vec<UndoEntry>, String> {
+    let stack = lock_or_err(&state.undo_stack)?;
+    Ok(stack.iter().rev().cloned().collect())
+}
+
+/// Revert the most recent destructive operation. Steps are undone in reverse
+/// order; a step that fails is reported but does not stop the others. When
+/// every step fails the operation stays on the stack.
+#[tauri::command]
+pub fn undo_last_operation(state: State<AppState>) -> Result<UndoResult, String> {
+    let entry = lock_or_err(&state.undo_stack)?
+        .pop()
+        .ok_or("Nothing to undo")?;
+
+    let mut restored_paths = Vec::new();
+    let mut errors = Vec::new();
+    for step in entry.steps.iter().rev() {
+        match undo_step(&entry.notes_dir, step, &state) {
+            Ok(path) => restored_paths.push(path),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if restored_paths.is_empty() && !errors.is_empty() {
+        let message = format!("Failed to undo {}: {}", entry.label, errors.join("; "));
+        lock_or_err(&state.undo_stack)?.push(entry);
+        return Err(message);
+    }
+    // Labels carry note titles, which stay out of the logs
+    for error in &errors {
+        log::warn!("Partial undo: {}", error);
+    }
+
+    Ok(UndoResult {
+        label: entry.label,
+        restored_paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::{delete_note, move_note, rename_folder};
+    use crate::commands::trash::empty_trash;
+    use crate::test_support::TestVault;
+
+    #[test]
+    fn undoes_deletes_moves_and_folder_renames_in_reverse() {
+        let vault = TestVault::new();
+        let notes_dir = vault.notes_dir();
+        let a = vault.note("a.md", "a", "");
+        let b = vault.note("Work/b.md", "b", "");
+
+        delete_note(notes_dir.clone(), a, None, vault.state()).unwrap();
+        move_note(notes_dir.clone(), b, "Done".into(), None, vault.state()).unwrap();
+        rename_folder(
+            notes_dir,
+            vault.path("Done"),
+            "Archive".into(),
+            vault.state(),
+        )
+        .unwrap();
+        let labels: Vec<String> = list_undo_operations(vault.state())
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.label)
+            .collect();
+        assert_eq!(
+            labels,
+            ["Rename folder \"Done\"", "Move \"b\"", "Delete \"a\""]
+        );
+
+        undo_last_operation(vault.state()).unwrap();
+        assert!(vault.exists("Done/b.md"));
+        undo_last_operation(vault.state()).unwrap();
+        assert!(vault.exists("Work/b.md"));
+        undo_last_operation(vault.state()).unwrap();
+        assert!(vault.exists("a.md"));
+        assert!(undo_last_operation(vault.state()).is_err());
+    }
+
+    #[test]
+    fn groups_steps_into_one_operation() {
+        let vault = TestVault::new();
+        let notes_dir = vault.notes_dir();
+        let a = vault.note("a.md", "a", "");
+        let b = vault.note("b.md", "b", "");
+
+        let state = vault.state();
+        grouped(&state, "Clean up".into(), &notes_dir, || {
+            delete_note(notes_dir.clone(), a, None, vault.state()).unwrap();
+            delete_note(notes_dir.clone(), b, None, vault.state()).unwrap();
+        });
+        let entries = list_undo_operations(vault.state()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].steps.len(), 2);
+
+        let undone = undo_last_operation(vault.state()).unwrap();
+        assert_eq!(undone.label, "Clean up");
+        assert!(vault.exists("a.md") && vault.exists("b.md"));
+    }
+
+    #[test]
+    fn keeps_an_operation_whose_steps_all_fail() {
+        let vault = TestVault::new();
+        let notes_dir = vault.notes_dir();
+        let a = vault.note("a.md", "a", "");
+        delete_note(notes_dir.clone(), a, None, vault.state()).unwrap();
+        empty_trash(notes_dir, None).unwrap();
+
+        assert!(undo_last_operation(vault.state()).is_err());
+        assert_eq!(list_undo_operations(vault.state()).unwrap().len(), 1);
+    }
+}
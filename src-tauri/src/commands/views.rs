@@ -0,0 +1,173 @@
+use crate::cache::views::{compile_order_by, SavedView, SortKey};
+use crate::commands::notes::{fill_days_in_column, NoteWithTags, Priority};
+use crate::lock_or_err;
+use crate::AppState;
+use std::collections::HashMap;
+use tauri::State;
+
+pub(crate) fn validate_view_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("View name cannot be empty".to_string());
+    }
+    if trimmed.len() > 100 {
+        return Err("View name is too long".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn save_view(
+    name: String,
+    sort: Vec<SortKey>,
+    state: State<AppState>,
+) -> Result<SavedView, String> {
+    validate_view_name(&name)?;
+    // Reject unknown fields up front rather than when the view is first used
+    compile_order_by(&sort)?;
+
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    cache.save_view(name.trim(), &sort)
+}
+
+#[tauri::command]
+pub fn list_views(state: State<AppState>) -> Result<Vec<SavedView>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    match cache_lock.as_ref() {
+        Some(cache) => cache.get_views(),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[tauri::command]
+pub fn delete_view(name: String, state: State<AppState>) -> Result<(), String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+    if !cache.delete_view(&name)? {
+        return Err("View not found".to_string());
+    }
+    Ok(())
+}
+
+/// Cached notes ordered by a saved view or an ad-hoc sort. The ordering is
+/// done in SQL so every window sees the same order for the same view.
+/// `min_priority` leaves out notes below that priority or without one.
+#[tauri::command]
+pub fn list_notes_sorted(
+    view_name: Option<String>,
+    sort: Option<Vec<SortKey>>,
+    min_priority: Option<Priority>,
+    include_archived: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<NoteWithTags>, String> {
+    let cache_lock = lock_or_err(&state.cache)?;
+    let cache = cache_lock.as_ref().ok_or("Cache is not initialized")?;
+
+    let sort = match (view_name, sort) {
+        (_, Some(sort)) => sort,
+        (Some(name), None) => cache.get_view(&name)?.ok_or("View not found")?.sort,
+        (None, None) => Vec::new(),
+    };
+
+    let paths = cache.get_sorted_note_paths(&sort)?;
+    let mut notes: HashMap<String, NoteWithTags> = cache
+        .get_all_notes()?
+        .into_iter()
+        .map(|cached| {
+            (
+                cached.note.file_path.clone(),
+                NoteWithTags {
+                    note: cached.note,
+                    inline_tags: cached.inline_tags,
+                    days_in_column: None,
+                    excerpt: Some(cached.excerpt),
+                },
+            )
+        })
+        .collect();
+
+    let mut sorted: Vec<NoteWithTags> = paths
+        .into_iter()
+        .filter_map(|path| notes.remove(&path))
+        .filter(|note| note.note.frontmatter.priority >= min_priority)
+        .filter(|note| include_archived.unwrap_or(false) || !note.note.frontmatter.archived)
+        .collect();
+    fill_days_in_column(cache, &mut sorted);
+    Ok(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::scan_vault;
+    use crate::test_support::TestVault;
+
+    fn key(field: &str, descending: bool) -> SortKey {
+        SortKey {
+            field: field.to_string(),
+            descending,
+            nulls_first: false,
+        }
+    }
+
+    fn ids(notes: Vec<NoteWithTags>) -> Vec<String> {
+        notes.into_iter().map(|n| n.note.frontmatter.id).collect()
+    }
+
+    #[test]
+    fn lists_notes_in_saved_view_order() {
+        let vault = TestVault::new();
+        vault.note("a.md", "a", "priority: low\n");
+        vault.note("b.md", "b", "priority: urgent\n");
+        vault.note("c.md", "c", "");
+        vault.note("d.md", "d", "priority: high\narchived: true\n");
+        scan_vault(&vault.notes_dir(), &vault.state(), &mut |_| {}).unwrap();
+
+        let view = save_view(
+            " Urgent first ".to_string(),
+            vec![key("priority", true), key("title", false)],
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(view.name, "Urgent first");
+        assert!(save_view("Bad".to_string(), vec![key("nope", false)], vault.state()).is_err());
+        assert!(save_view(" ".to_string(), Vec::new(), vault.state()).is_err());
+        assert_eq!(list_views(vault.state()).unwrap().len(), 1);
+
+        let sorted = |min_priority, include_archived| {
+            list_notes_sorted(
+                Some("Urgent first".to_string()),
+                None,
+                min_priority,
+                include_archived,
+                vault.state(),
+            )
+            .unwrap()
+        };
+        assert_eq!(ids(sorted(None, None)), ["b", "a", "c"]);
+        assert_eq!(ids(sorted(None, Some(true))), ["b", "d", "a", "c"]);
+        assert_eq!(ids(sorted(Some(Priority::Low), None)), ["b", "a"]);
+        // An ad-hoc sort wins over the view
+        let by_title = list_notes_sorted(
+            Some("Urgent first".to_string()),
+            Some(vec![key("title", true)]),
+            None,
+            None,
+            vault.state(),
+        )
+        .unwrap();
+        assert_eq!(ids(by_title), ["c", "b", "a"]);
+
+        delete_view("Urgent first".to_string(), vault.state()).unwrap();
+        assert!(delete_view("Urgent first".to_string(), vault.state()).is_err());
+        let missing = list_notes_sorted(
+            Some("Urgent first".to_string()),
+            None,
+            None,
+            None,
+            vault.state(),
+        );
+        assert!(missing.is_err());
+    }
+}
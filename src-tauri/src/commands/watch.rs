@@ -0,0 +1,352 @@
+use crate::commands::notes::{
+    apply_file_changes, coalesce_changes, FileChangeEvent, IncrementalUpdateResult,
+};
+use crate::commands::{note_index, scan};
+use crate::lock_or_err;
+use crate::AppState;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Carries the `IncrementalUpdateResult` of each debounced batch
+pub(crate) const CHANGES_EVENT: &str = "notes://changes";
+/// Sent once per note renamed outside the app, with its old and new path
+pub(crate) const NOTE_MOVED_EVENT: &str = "note-moved";
+/// Sent per file added, replaced or removed in an `.attachments` folder
+pub(crate) const ATTACHMENT_CHANGED_EVENT: &str = "attachment-changed";
+/// Sent per changed file in the vault configuration folder
+pub(crate) const CONFIG_CHANGED_EVENT: &str = "config-changed";
+/// Quiet time after the last watcher event before a batch is processed
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(300);
+/// Above this many changed paths in one batch (a checkout, a sync run) one
+/// rescan is cheaper than per-file updates
+const MASS_CHANGE_THRESHOLD: usize = 500;
+/// Watcher events for the last writes of a bulk operation can arrive after it
+/// resumed the watcher, so they are still ignored for this long
+const RESUME_GRACE: Duration = Duration::from_secs(2);
+
+/// Watcher events waiting for the debounce window to pass
+#[derive(Default)]
+pub struct ChangeQueue {
+    pending: Mutex<PendingChanges>,
+}
+
+#[derive(Default)]
+struct PendingChanges {
+    notes_dir: String,
+    changes: Vec<FileChangeEvent>,
+    last_event: Option<Instant>,
+    worker_running: bool,
+    /// Bulk operations currently holding the watcher paused
+    paused: usize,
+    resumed_at: Option<Instant>,
+    /// Whether events were ignored since the watcher was last paused
+    missed: bool,
+}
+
+impl PendingChanges {
+    fn is_paused(&self) -> bool {
+        self.paused > 0
+            || self
+                .resumed_at
+                .is_some_and(|resumed_at| resumed_at.elapsed() < RESUME_GRACE)
+    }
+}
+
+/// Keeps watcher events from being processed while a bulk operation writes
+/// to the vault; the watcher resumes when it is dropped
+pub(crate) struct WatcherPause<'a> {
+    state: &'a AppState,
+}
+
+impl Drop for WatcherPause<'_> {
+    fn drop(&mut self) {
+        // Ignored events may be for files the operation did not write; the
+        // next listing walks the vault again to pick them up
+        if resume(self.state) {
+            note_index::invalidate(self.state);
+        }
+    }
+}
+
+/// Pause the watcher until the returned guard is dropped
+pub(crate) fn pause(state: &AppState) -> WatcherPause<'_> {
+    hold(state);
+    WatcherPause { state }
+}
+
+fn hold(state: &AppState) {
+    let dropped = {
+        let Ok(mut pending) = state.change_queue.pending.lock() else {
+            return;
+        };
+        if pending.paused == 0 {
+            pending.missed = false;
+        }
+        pending.paused += 1;
+        // Queued events would be processed between the operation's writes,
+        // so they count as missed like the ones arriving while paused
+        let dropped = !pending.changes.is_empty();
+        pending.changes.clear();
+        pending.missed |= dropped;
+        dropped
+    };
+    if dropped {
+        note_index::invalidate(state);
+    }
+}
+
+/// Release one pause; returns whether events were ignored while paused
+fn resume(state: &AppState) -> bool {
+    let Ok(mut pending) = state.change_queue.pending.lock() else {
+        return false;
+    };
+    pending.paused = pending.paused.saturating_sub(1);
+    if pending.paused == 0 {
+        pending.resumed_at = Some(Instant::now());
+    }
+    pending.missed
+}
+
+/// Whether watcher events should be ignored right now. Ignored events leave
+/// the in-memory listing behind, so it is dropped.
+fn suppressed(state: &State<AppState>) -> Result<bool, String> {
+    {
+        let mut pending = lock_or_err(&state.change_queue.pending)?;
+        if !pending.is_paused() {
+            return Ok(false);
+        }
+        pending.missed = true;
+    }
+    note_index::invalidate(state);
+    Ok(true)
+}
+
+/// Stop processing watcher events, e.g. while the frontend writes many files
+/// itself. Pauses nest; each needs a matching `resume_watcher`.
+#[tauri::command]
+pub fn pause_watcher(state: State<AppState>) {
+    hold(&state);
+}
+
+/// Undo one `pause_watcher`. Returns whether watcher events were ignored in
+/// the meantime, in which case the vault should be reloaded.
+#[tauri::command]
+pub fn resume_watcher(state: State<AppState>) -> bool {
+    resume(&state)
+}
+
+/// Hand watcher events to the backend without waiting for them to be
+/// processed. Events are coalesced per path and processed once no new ones
+/// arrived for a short while; results are emitted as `notes://changes`.
+#[tauri::command]
+pub fn queue_file_changes(
+    notes_dir: String,
+    changes: Vec<FileChangeEvent>,
+    app: AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    if state.safe_mode || changes.is_empty() || suppressed(&state)? {
+        return Ok(());
+    }
+
+    let mut pending = lock_or_err(&state.change_queue.pending)?;
+    // Events of a vault that is no longer open are dropped
+    if pending.notes_dir != notes_dir {
+        pending.notes_dir = notes_dir;
+        pending.changes.clear();
+    }
+    let queued = std::mem::take(&mut pending.changes);
+    pending.changes = coalesce_changes(queued.into_iter().chain(changes));
+    pending.last_event = Some(Instant::now());
+
+    if !pending.worker_running {
+        pending.worker_running = true;
+        std::thread::spawn(move || drain_changes(&app));
+    }
+    Ok(())
+}
+
+fn drain_changes(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        let (notes_dir, changes) = {
+            let mut pending = match state.change_queue.pending.lock() {
+                Ok(pending) => pending,
+                Err(_) => return,
+            };
+            let Some(last_event) = pending.last_event else {
+                pending.worker_running = false;
+                return;
+            };
+            let quiet_for = last_event.elapsed();
+            if quiet_for < DEBOUNCE_WINDOW {
+                drop(pending);
+                std::thread::sleep(DEBOUNCE_WINDOW - quiet_for);
+                continue;
+            }
+            pending.last_event = None;
+            (
+                pending.notes_dir.clone(),
+                std::mem::take(&mut pending.changes),
+            )
+        };
+
+        process_and_emit(app, &notes_dir, changes, &state);
+    }
+}
+
+/// Process a batch found without the frontend's involvement and send the
+/// result as `notes://changes` when anything changed
+pub(crate) fn process_and_emit(
+    app: &AppHandle,
+    notes_dir: &str,
+    changes: Vec<FileChangeEvent>,
+    state: &State<AppState>,
+) {
+    match process_changes(app, notes_dir, changes, state) {
+        Ok(result)
+            if result.updated_notes.is_empty()
+                && result.removed_paths.is_empty()
+                && result.attachment_changes.is_empty()
+                && result.config_changes.is_empty()
+                && result.added_folders.is_empty()
+                && result.removed_folders.is_empty()
+                && result.rescan_id.is_none() => {}
+        Ok(result) => {
+            if let Err(e) = app.emit(CHANGES_EVENT, result) {
+                log::warn!("Failed to emit {}: {}", CHANGES_EVENT, e);
+            }
+        }
+        Err(e) => log::warn!("Failed to process file changes: {}", e),
+    }
+}
+
+/// Apply a batch of watcher events, or start a background rescan in their
+/// place when there are too many of them to send note by note
+pub(crate) fn process_changes(
+    app: &AppHandle,
+    notes_dir: &str,
+    changes: Vec<FileChangeEvent>,
+    state: &State<AppState>,
+) -> Result<IncrementalUpdateResult, String> {
+    if suppressed(state)? {
+        return Ok(IncrementalUpdateResult::default());
+    }
+    let changes = coalesce_changes(changes);
+    if changes.len() > MASS_CHANGE_THRESHOLD && !state.safe_mode {
+        log::info!(
+            "{} files changed at once, rescanning the vault instead",
+            changes.len()
+        );
+        return Ok(IncrementalUpdateResult {
+            rescan_id: Some(scan::spawn_scan(app.clone(), notes_dir.to_string())),
+            ..Default::default()
+        });
+    }
+
+    let result = apply_file_changes(notes_dir, changes, state)?;
+    emit_change_events(app, &result);
+    Ok(result)
+}
+
+/// Per-item events for listeners that only care about renames, attachments
+/// or configuration
+pub(crate) fn emit_change_events(app: &AppHandle, result: &IncrementalUpdateResult) {
+    for moved_note in &result.moved_notes {
+        if let Err(e) = app.emit(NOTE_MOVED_EVENT, moved_note) {
+            log::warn!("Failed to emit {}: {}", NOTE_MOVED_EVENT, e);
+        }
+    }
+    for attachment in &result.attachment_changes {
+        if let Err(e) = app.emit(ATTACHMENT_CHANGED_EVENT, attachment) {
+            log::warn!("Failed to emit {}: {}", ATTACHMENT_CHANGED_EVENT, e);
+        }
+    }
+    for config in &result.config_changes {
+        if let Err(e) = app.emit(CONFIG_CHANGED_EVENT, config) {
+            log::warn!("Failed to emit {}: {}", CONFIG_CHANGED_EVENT, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::NotesWithTagsAndFolders;
+    use crate::test_support::TestVault;
+
+    fn index_vault(vault: &TestVault) {
+        let listing = NotesWithTagsAndFolders {
+            notes: Vec::new(),
+            folders: Vec::new(),
+            placeholders: Vec::new(),
+            next_cursor: None,
+        };
+        note_index::replace(&vault.state(), &vault.notes_dir(), &listing);
+    }
+
+    fn is_indexed(vault: &TestVault) -> bool {
+        note_index::listing(&vault.notes_dir(), &vault.state()).is_some()
+    }
+
+    fn queue_change(vault: &TestVault) {
+        let state = vault.state();
+        let mut pending = state.change_queue.pending.lock().unwrap();
+        pending.notes_dir = vault.notes_dir();
+        pending.changes.push(FileChangeEvent {
+            event_type: "modify".to_string(),
+            file_path: vault.path("a.md"),
+        });
+    }
+
+    #[test]
+    fn pausing_drops_queued_events_as_missed() {
+        let vault = TestVault::new();
+        let state = vault.state();
+        index_vault(&vault);
+        pause_watcher(state.clone());
+        assert!(is_indexed(&vault));
+        assert!(!resume_watcher(state.clone()));
+
+        queue_change(&vault);
+        pause_watcher(state.clone());
+        assert!(!is_indexed(&vault));
+        let pending = state.change_queue.pending.lock().unwrap();
+        assert!(pending.changes.is_empty());
+        drop(pending);
+        assert!(resume_watcher(state));
+    }
+
+    #[test]
+    fn dropping_a_pause_after_missed_events_drops_the_index() {
+        let vault = TestVault::new();
+        let state = vault.state();
+        {
+            let _pause = pause(&state);
+            assert!(suppressed(&state).unwrap());
+            // Reindexed mid-operation, e.g. by a listing the frontend asked for
+            index_vault(&vault);
+        }
+        assert!(!is_indexed(&vault));
+
+        index_vault(&vault);
+        drop(pause(&state));
+        assert!(is_indexed(&vault));
+    }
+
+    #[test]
+    fn nested_pauses_resume_together() {
+        let vault = TestVault::new();
+        let state = vault.state();
+        pause_watcher(state.clone());
+        pause_watcher(state.clone());
+        resume_watcher(state.clone());
+        assert!(state.change_queue.pending.lock().unwrap().is_paused());
+        resume_watcher(state.clone());
+        // Still ignoring events for a moment after the last resume
+        let pending = state.change_queue.pending.lock().unwrap();
+        assert_eq!(pending.paused, 0);
+        assert!(pending.resumed_at.is_some());
+    }
+}
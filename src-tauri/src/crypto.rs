@@ -0,0 +1,138 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+
+/// Marks a blob written by `encrypt`, followed by the nonce and ciphertext
+const MAGIC: &[u8] = b"NBENC1";
+const NONCE_LEN: usize = 24;
+pub const SALT_LEN: usize = 16;
+
+pub type Key = [u8; 32];
+
+pub fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+pub fn random_key() -> Key {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+/// Stretch a passphrase into a key with Argon2id
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// Derive a key for one purpose from a random master key, so the master key
+/// itself is never used with a second cipher
+pub fn derive_subkey(master: &[u8], purpose: &str) -> Key {
+    let digest = Sha256::new()
+        .chain_update(purpose.as_bytes())
+        .chain_update([0u8])
+        .chain_update(master)
+        .finalize();
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&digest);
+    key
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt with XChaCha20-Poly1305 under a fresh random nonce
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    encrypt_with_aad(key, plaintext, &[])
+}
+
+/// Like `encrypt`, binding `aad` into the tag so the blob only decrypts
+/// with the same associated data
+pub fn encrypt_with_aad(key: &Key, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| "Failed to encrypt data".to_string())?;
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>, String> {
+    decrypt_with_aad(key, data, &[])
+}
+
+pub fn decrypt_with_aad(key: &Key, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|body| body.len() >= NONCE_LEN)
+        .ok_or("Data is not encrypted")?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| "Failed to decrypt data: wrong key or corrupted content".to_string())
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        return Err("Invalid hex string".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| "Invalid hex string".to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_wrong_key() {
+        let salt = random_salt();
+        let key = derive_key("correct horse", &salt).unwrap();
+        let blob = encrypt(&key, b"# Secret note").unwrap();
+        assert!(is_encrypted(&blob));
+        assert_eq!(decrypt(&key, &blob).unwrap(), b"# Secret note");
+
+        let other = derive_key("battery staple", &salt).unwrap();
+        assert!(decrypt(&other, &blob).is_err());
+        assert_eq!(from_hex(&to_hex(&salt)).unwrap(), salt);
+
+        let bound = encrypt_with_aad(&key, b"frame", b"1").unwrap();
+        assert_eq!(decrypt_with_aad(&key, &bound, b"1").unwrap(), b"frame");
+        assert!(decrypt_with_aad(&key, &bound, b"2").is_err());
+        assert!(decrypt(&key, &bound).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use crate::crypto::{self, Key};
+use crate::utils::compute_content_hash;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use directories::ProjectDirs;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_EXTENSION: &str = ".md.gz";
+const MAX_SNAPSHOTS_PER_NOTE: usize = 100;
+const MAX_SNAPSHOT_AGE_DAYS: i64 = 90;
+/// Unchanged lines shown around each change in a diff
+const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub size: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    /// "context", "added" or "removed"
+    pub kind: String,
+    pub content: String,
+}
+
+/// A run of changes with surrounding context; line numbers are 1-based
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionDiff {
+    pub unified: String,
+    pub hunks: Vec<DiffHunk>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+fn history_root(profile_id: &str) -> Result<PathBuf, String> {
+    let proj_dirs =
+        ProjectDirs::from("", "", "noteban").ok_or("Could not determine cache directory")?;
+    Ok(proj_dirs.cache_dir().join(profile_id).join("history"))
+}
+
+/// Note ids come from user-editable frontmatter, so only use them verbatim as a
+/// directory name when they are plainly safe
+fn note_dir_name(note_id: &str) -> String {
+    let safe = !note_id.is_empty()
+        && note_id.len() <= 64
+        && note_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if safe {
+        note_id.to_string()
+    } else {
+        compute_content_hash(note_id)[..32].to_string()
+    }
+}
+
+/// Snapshots live in `<cache dir>/<profile>/history/<note id>/` and are named
+/// `<unix millis>-<content hash>-<size>.md.gz`, so listing never decompresses
+pub fn note_history_dir(profile_id: &str, note_id: &str) -> Result<PathBuf, String> {
+    Ok(history_root(profile_id)?.join(note_dir_name(note_id)))
+}
+
+fn parse_snapshot_name(file_name: &str) -> Option<SnapshotInfo> {
+    let id = file_name.strip_suffix(SNAPSHOT_EXTENSION)?;
+    let mut parts = id.splitn(3, '-');
+    let millis = parts.next()?.parse::<i64>().ok()?;
+    let hash = parts.next()?.to_string();
+    let size = parts.next()?.parse::<u64>().ok()?;
+    Some(SnapshotInfo {
+        id: id.to_string(),
+        timestamp: DateTime::from_timestamp_millis(millis)?,
+        size,
+        hash,
+    })
+}
+
+/// List the snapshots in a note's history directory, newest first
+pub fn list_snapshots_in(dir: &Path) -> Vec<SnapshotInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<SnapshotInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| parse_snapshot_name(&entry.file_name().to_string_lossy()))
+        .collect();
+    snapshots.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+    snapshots
+}
+
+/// Compress `content` into the history directory unless it duplicates the
+/// latest snapshot. With a key the compressed bytes are encrypted, mirroring
+/// an encrypted cache database.
+pub fn save_snapshot_in(
+    dir: &Path,
+    content: &str,
+    key: Option<&Key>,
+) -> Result<Option<SnapshotInfo>, String> {
+    let now = Utc::now();
+    let hash = compute_content_hash(content)[..16].to_string();
+
+    if list_snapshots_in(dir)
+        .first()
+        .is_some_and(|latest| latest.hash == hash)
+    {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create history directory: {}", e))?;
+
+    let info = SnapshotInfo {
+        id: format!("{}-{}-{}", now.timestamp_millis(), hash, content.len()),
+        timestamp: now,
+        size: content.len() as u64,
+        hash,
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to compress snapshot: {}", e))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress snapshot: {}", e))?;
+
+    let path = dir.join(format!("{}{}", info.id, SNAPSHOT_EXTENSION));
+    fs::write(&path, seal(compressed, key)?)
+        .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+
+    apply_retention(dir);
+    Ok(Some(info))
+}
+
+fn seal(compressed: Vec<u8>, key: Option<&Key>) -> Result<Vec<u8>, String> {
+    match key {
+        Some(key) => crypto::encrypt(key, &compressed),
+        None => Ok(compressed),
+    }
+}
+
+/// Undo `seal`; encrypted snapshots need the key they were written with
+fn open(stored: Vec<u8>, key: Option<&Key>) -> Result<Vec<u8>, String> {
+    if !crypto::is_encrypted(&stored) {
+        return Ok(stored);
+    }
+    let key = key.ok_or("Version history is encrypted but the cache is not")?;
+    crypto::decrypt(key, &stored).map_err(|_| "Failed to decrypt snapshot".to_string())
+}
+
+/// Decompress a snapshot by id
+pub fn read_snapshot_in(
+    dir: &Path,
+    snapshot_id: &str,
+    key: Option<&Key>,
+) -> Result<String, String> {
+    let file_name = format!("{}{}", snapshot_id, SNAPSHOT_EXTENSION);
+    // Only accept well-formed ids so the id cannot point outside the directory
+    let valid = parse_snapshot_name(&file_name).is_some_and(|info| !info.id.contains(['/', '\\']));
+    if !valid {
+        return Err("Invalid version id".to_string());
+    }
+    let stored = fs::read(dir.join(&file_name)).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => "Version not found".to_string(),
+        _ => format!("Failed to read snapshot: {}", e),
+    })?;
+    let compressed = open(stored, key)?;
+    let mut content = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to decompress snapshot: {}", e))?;
+    Ok(content)
+}
+
+/// Line-level diff between two versions, as unified text and structured hunks
+pub fn diff_texts(old: &str, new: &str, old_label: &str, new_label: &str) -> VersionDiff {
+    let diff = TextDiff::from_lines(old, new);
+    let mut hunks = Vec::new();
+    let mut additions = 0;
+    let mut deletions = 0;
+
+    for group in diff.grouped_ops(DIFF_CONTEXT_LINES) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+
+        let mut lines = Vec::new();
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let kind = match change.tag() {
+                    ChangeTag::Equal => "context",
+                    ChangeTag::Insert => {
+                        additions += 1;
+                        "added"
+                    }
+                    ChangeTag::Delete => {
+                        deletions += 1;
+                        "removed"
+                    }
+                };
+                lines.push(DiffLine {
+                    kind: kind.to_string(),
+                    content: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                });
+            }
+        }
+
+        hunks.push(DiffHunk {
+            old_start: old_range.start + 1,
+            old_lines: old_range.len(),
+            new_start: new_range.start + 1,
+            new_lines: new_range.len(),
+            lines,
+        });
+    }
+
+    let unified = diff
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string();
+
+    VersionDiff {
+        unified,
+        hunks,
+        additions,
+        deletions,
+    }
+}
+
+/// Drop snapshots beyond the per-note count limit or older than the age limit
+fn apply_retention(dir: &Path) {
+    let cutoff = Utc::now() - ChronoDuration::days(MAX_SNAPSHOT_AGE_DAYS);
+    for (index, snapshot) in list_snapshots_in(dir).into_iter().enumerate() {
+        if index >= MAX_SNAPSHOTS_PER_NOTE || snapshot.timestamp < cutoff {
+            let path = dir.join(format!("{}{}", snapshot.id, SNAPSHOT_EXTENSION));
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("Failed to prune snapshot {}: {}", snapshot.id, e);
+            }
+        }
+    }
+}
+
+/// Snapshot the previous content of a note before it is overwritten
+pub fn save_snapshot(
+    profile_id: &str,
+    note_id: &str,
+    content: &str,
+    key: Option<&Key>,
+) -> Result<Option<SnapshotInfo>, String> {
+    save_snapshot_in(&note_history_dir(profile_id, note_id)?, content, key)
+}
+
+/// Re-seal every snapshot of a profile after its cache encryption changed
+pub fn rekey_history(profile_id: &str, old: Option<&Key>, new: Option<&Key>) -> Result<(), String> {
+    rekey_history_in(&history_root(profile_id)?, old, new)
+}
+
+fn rekey_history_in(root: &Path, old: Option<&Key>, new: Option<&Key>) -> Result<(), String> {
+    let Ok(note_dirs) = fs::read_dir(root) else {
+        return Ok(());
+    };
+    for note_dir in note_dirs.filter_map(|entry| entry.ok()) {
+        let Ok(entries) = fs::read_dir(note_dir.path()) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if !path.to_string_lossy().ends_with(SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            let stored = fs::read(&path).map_err(|e| format!("Failed to read snapshot: {}", e))?;
+            let sealed = seal(open(stored, old)?, new)?;
+            fs::write(&path, sealed).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_snapshot_names() {
+        let info = parse_snapshot_name("1700000000000-abcdef0123456789-42.md.gz").unwrap();
+        assert_eq!(info.id, "1700000000000-abcdef0123456789-42");
+        assert_eq!(info.size, 42);
+        assert_eq!(info.hash, "abcdef0123456789");
+        assert_eq!(info.timestamp.timestamp(), 1_700_000_000);
+        assert!(parse_snapshot_name("notes.md").is_none());
+    }
+
+    #[test]
+    fn skips_duplicate_snapshots() {
+        let dir = std::env::temp_dir().join(format!("noteban-history-{}", uuid::Uuid::new_v4()));
+        assert!(save_snapshot_in(&dir, "first", None).unwrap().is_some());
+        assert!(save_snapshot_in(&dir, "first", None).unwrap().is_none());
+        // Every change is kept, however soon it follows the previous one
+        assert!(save_snapshot_in(&dir, "second", None).unwrap().is_some());
+        assert_eq!(list_snapshots_in(&dir).len(), 2);
+
+        let mut contents: Vec<String> = list_snapshots_in(&dir)
+            .iter()
+            .map(|info| read_snapshot_in(&dir, &info.id, None).unwrap())
+            .collect();
+        contents.sort();
+        assert_eq!(contents, ["first", "second"]);
+        assert!(read_snapshot_in(&dir, "../escape", None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encrypts_snapshots_with_the_cache_key() {
+        let root = std::env::temp_dir().join(format!("noteban-history-{}", uuid::Uuid::new_v4()));
+        let dir = root.join("note");
+        let key = crypto::random_key();
+        let secret = "meeting with the landlord";
+        let info = save_snapshot_in(&dir, secret, Some(&key)).unwrap().unwrap();
+        let path = dir.join(format!("{}{}", info.id, SNAPSHOT_EXTENSION));
+
+        let stored = fs::read(&path).unwrap();
+        assert!(crypto::is_encrypted(&stored));
+        assert_eq!(
+            read_snapshot_in(&dir, &info.id, Some(&key)).unwrap(),
+            secret
+        );
+        assert!(read_snapshot_in(&dir, &info.id, None).is_err());
+        assert!(read_snapshot_in(&dir, &info.id, Some(&crypto::random_key())).is_err());
+
+        // Turning encryption off rewrites history as plain gzip, and back on again
+        rekey_history_in(&root, Some(&key), None).unwrap();
+        assert!(!crypto::is_encrypted(&fs::read(&path).unwrap()));
+        assert_eq!(read_snapshot_in(&dir, &info.id, None).unwrap(), secret);
+        let next = crypto::random_key();
+        rekey_history_in(&root, None, Some(&next)).unwrap();
+        assert_eq!(
+            read_snapshot_in(&dir, &info.id, Some(&next)).unwrap(),
+            secret
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn diffs_versions_into_hunks() {
+        let old = "title\none\ntwo\nthree\n";
+        let new = "title\none\n2\nthree\nfour\n";
+        let diff = diff_texts(old, new, "a", "b");
+        assert_eq!(diff.additions, 2);
+        assert_eq!(diff.deletions, 1);
+        assert_eq!(diff.hunks.len(), 1);
+        let hunk = &diff.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 4));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 5));
+        assert!(diff.unified.starts_with("--- a\n+++ b\n"));
+        assert!(diff.unified.contains("-two\n+2\n"));
+    }
+
+    #[test]
+    fn sanitizes_note_directory_names() {
+        assert_eq!(note_dir_name("abc-123"), "abc-123");
+        assert_eq!(note_dir_name("../etc").len(), 32);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::logging;
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const JOURNAL_EXTENSION: &str = "json";
+
+/// A single rename performed by a multi-step operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Text replacement the operation applies to `file` once every rename is done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentRewrite {
+    pub file: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Intent record written before the first step of an operation and removed
+/// after the last one. A leftover entry means the app stopped halfway.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub operation: String,
+    pub started_at: DateTime<Utc>,
+    /// Renames in the order they are performed
+    pub renames: Vec<JournalRename>,
+    #[serde(default)]
+    pub rewrite: Option<ContentRewrite>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryOutcome {
+    /// Every rename had happened; pending content fixes were applied
+    RolledForward,
+    /// Completed renames were reverted to the state before the operation
+    RolledBack,
+    /// The files no longer match either state and were left untouched
+    Unresolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveredOperation {
+    pub id: String,
+    pub operation: String,
+    pub started_at: DateTime<Utc>,
+    pub outcome: RecoveryOutcome,
+    /// Renames the operation had planned, in the order they were performed
+    #[serde(default)]
+    pub renames: Vec<JournalRename>,
+}
+
+/// Journals live in `<data dir>/<profile>/journal`, which survives cache clears
+pub fn journal_dir(profile_id: &str) -> Result<PathBuf, String> {
+    let proj_dirs =
+        ProjectDirs::from("", "", "noteban").ok_or("Could not determine data directory")?;
+    Ok(proj_dirs.data_dir().join(profile_id).join("journal"))
+}
+
+impl JournalEntry {
+    pub fn new(
+        operation: &str,
+        renames: Vec<JournalRename>,
+        rewrite: Option<ContentRewrite>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            operation: operation.to_string(),
+            started_at: Utc::now(),
+            renames,
+            rewrite,
+        }
+    }
+}
+
+/// An operation in progress; dropping it without `finish` keeps the entry on
+/// disk so the next start can repair the operation
+pub struct OperationJournal {
+    path: PathBuf,
+}
+
+impl OperationJournal {
+    pub fn begin(dir: &Path, entry: &JournalEntry) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create journal directory: {}", e))?;
+        let path = dir.join(format!("{}.{}", entry.id, JOURNAL_EXTENSION));
+        let encoded = serde_json::to_vec(entry)
+            .map_err(|e| format!("Failed to encode journal entry: {}", e))?;
+        AtomicFile::new(&path, OverwriteBehavior::AllowOverwrite)
+            .write(|f| f.write_all(&encoded))
+            .map_err(|e| format!("Failed to write journal entry: {}", e))?;
+        Ok(Self { path })
+    }
+
+    /// Mark the operation as complete (or cleanly rolled back)
+    pub fn finish(self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!(
+                "Failed to clear journal entry {}: {}",
+                logging::path(&self.path),
+                e
+            );
+        }
+    }
+}
+
+fn apply_rewrite(rewrite: &ContentRewrite) -> Result<(), String> {
+    let path = Path::new(&rewrite.file);
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if !content.contains(&rewrite.from) {
+        return Ok(());
+    }
+    let updated = content.replace(&rewrite.from, &rewrite.to);
+    AtomicFile::new(path, OverwriteBehavior::AllowOverwrite)
+        .write(|f| f.write_all(updated.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+/// Bring the files of an interrupted operation back to a consistent state
+pub fn recover_entry(entry: &JournalEntry) -> RecoveryOutcome {
+    let done = |rename: &JournalRename| {
+        Path::new(&rename.to).exists() && !Path::new(&rename.from).exists()
+    };
+    let pending = |rename: &JournalRename| {
+        Path::new(&rename.from).exists() && !Path::new(&rename.to).exists()
+    };
+
+    if entry.renames.iter().all(done) {
+        if let Some(rewrite) = &entry.rewrite {
+            if let Err(e) = apply_rewrite(rewrite) {
+                log::warn!(
+                    "Failed to finish content update for {}: {}",
+                    logging::path(&rewrite.file),
+                    e
+                );
+            }
+        }
+        return RecoveryOutcome::RolledForward;
+    }
+    if !entry.renames.iter().all(|r| done(r) || pending(r)) {
+        return RecoveryOutcome::Unresolved;
+    }
+
+    for rename in entry.renames.iter().rev().filter(|r| done(r)) {
+        if let Err(e) = fs::rename(&rename.to, &rename.from) {
+            log::error!(
+                "Failed to revert {} to {}: {}",
+                logging::path(&rename.to),
+                logging::path(&rename.from),
+                e
+            );
+            return RecoveryOutcome::Unresolved;
+        }
+    }
+    RecoveryOutcome::RolledBack
+}
+
+/// Repair every operation left in `dir` by a crash and clear their entries
+pub fn recover_pending(dir: &Path) -> Vec<RecoveredOperation> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut recovered = Vec::new();
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some(JOURNAL_EXTENSION) {
+            continue;
+        }
+        let entry: Option<JournalEntry> = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        match entry {
+            Some(entry) => {
+                let outcome = recover_entry(&entry);
+                recovered.push(RecoveredOperation {
+                    id: entry.id,
+                    operation: entry.operation,
+                    started_at: entry.started_at,
+                    outcome,
+                    renames: entry.renames,
+                });
+            }
+            None => log::warn!(
+                "Discarding unreadable journal entry {}",
+                logging::path(&path)
+            ),
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!(
+                "Failed to clear journal entry {}: {}",
+                logging::path(&path),
+                e
+            );
+        }
+    }
+    recovered.sort_by_key(|op| op.started_at);
+    recovered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("noteban-journal-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rename(dir: &Path, from: &str, to: &str) -> JournalRename {
+        JournalRename {
+            from: dir.join(from).to_string_lossy().to_string(),
+            to: dir.join(to).to_string_lossy().to_string(),
+        }
+    }
+
+    #[test]
+    fn rolls_back_half_finished_rename() {
+        let dir = temp_dir();
+        fs::create_dir_all(dir.join("new.attachments")).unwrap();
+        fs::write(dir.join("old.md"), "see old.attachments/a.png").unwrap();
+        let entry = JournalEntry::new(
+            "rename_note",
+            vec![
+                rename(&dir, "old.attachments", "new.attachments"),
+                rename(&dir, "old.md", "new.md"),
+            ],
+            None,
+        );
+        let journal_dir = dir.join("journal");
+        let _journal = OperationJournal::begin(&journal_dir, &entry).unwrap();
+
+        let recovered = recover_pending(&journal_dir);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].outcome, RecoveryOutcome::RolledBack);
+        assert!(dir.join("old.attachments").is_dir());
+        assert!(dir.join("old.md").exists());
+        assert!(recover_pending(&journal_dir).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rolls_forward_completed_renames() {
+        let dir = temp_dir();
+        fs::create_dir_all(dir.join("new.attachments")).unwrap();
+        fs::write(dir.join("new.md"), "see old.attachments/a.png").unwrap();
+        let entry = JournalEntry::new(
+            "rename_note",
+            vec![
+                rename(&dir, "old.attachments", "new.attachments"),
+                rename(&dir, "old.md", "new.md"),
+            ],
+            Some(ContentRewrite {
+                file: dir.join("new.md").to_string_lossy().to_string(),
+                from: "old.attachments/".to_string(),
+                to: "new.attachments/".to_string(),
+            }),
+        );
+
+        assert_eq!(recover_entry(&entry), RecoveryOutcome::RolledForward);
+        assert_eq!(
+            fs::read_to_string(dir.join("new.md")).unwrap(),
+            "see new.attachments/a.png"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,11 +1,23 @@
+mod backup;
 mod cache;
 mod commands;
+mod crypto;
+mod history;
+mod journal;
+mod logging;
+mod merge;
+mod sync_meta;
+#[cfg(test)]
+mod test_support;
 mod utils;
+mod vault_config;
 
 use cache::CacheDb;
 use std::collections::HashMap;
 use std::sync::{Mutex, MutexGuard};
-use std::time::Instant;
+use tauri::Manager;
+
+const SAFE_MODE_FLAG: &str = "--safe-mode";
 
 /// Acquire a mutex lock, returning an error string if the mutex is poisoned.
 pub fn lock_or_err<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, String> {
@@ -16,9 +28,45 @@ pub fn lock_or_err<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, String> {
 
 pub struct AppState {
     pub cache: Mutex<Option<CacheDb>>,
-    pub recent_writes: Mutex<HashMap<String, Instant>>,
+    pub recent_writes: Mutex<HashMap<String, commands::notes::RecentWrite>>,
     pub initial_profile_id: Mutex<Option<String>>,
     pub nextcloud_login_sessions: Mutex<HashMap<String, commands::sync::LoginSession>>,
+    pub undo_stack: Mutex<Vec<commands::undo::UndoEntry>>,
+    /// Started with `--safe-mode`: no watcher updates, no background jobs and
+    /// a read-only cache
+    pub safe_mode: bool,
+    pub startup_recovery: Mutex<Option<commands::recovery::StartupRecovery>>,
+    pub ical_feed: Mutex<Option<commands::calendar::IcalFeed>>,
+    pub lan_sync_host: Mutex<Option<commands::lan_sync::LanHost>>,
+    pub profile_lock: Mutex<commands::profile_lock::ProfileLockState>,
+    /// Roles of restricted secondary windows by window label
+    pub window_roles: Mutex<HashMap<String, commands::capabilities::WindowRole>>,
+    pub cache_counters: commands::cache::CacheCounters,
+    pub note_index: Mutex<Option<commands::note_index::NoteIndex>>,
+    pub nested_boards: Mutex<commands::note_index::NestedBoards>,
+    pub change_queue: commands::watch::ChangeQueue,
+}
+
+impl AppState {
+    pub fn new(initial_profile_id: Option<String>, safe_mode: bool) -> Self {
+        Self {
+            cache: Mutex::new(None),
+            recent_writes: Mutex::new(HashMap::new()),
+            initial_profile_id: Mutex::new(initial_profile_id),
+            nextcloud_login_sessions: Mutex::new(HashMap::new()),
+            undo_stack: Mutex::new(Vec::new()),
+            safe_mode,
+            startup_recovery: Mutex::new(None),
+            ical_feed: Mutex::new(None),
+            lan_sync_host: Mutex::new(None),
+            profile_lock: Mutex::new(Default::default()),
+            window_roles: Mutex::new(HashMap::new()),
+            cache_counters: Default::default(),
+            note_index: Mutex::new(None),
+            nested_boards: Mutex::new(HashMap::new()),
+            change_queue: Default::default(),
+        }
+    }
 }
 
 #[tauri::command]
@@ -45,6 +93,54 @@ fn get_initial_profile(state: tauri::State<AppState>) -> Result<Option<String>,
     Ok(lock_or_err(&state.initial_profile_id)?.clone())
 }
 
+#[tauri::command]
+fn get_safe_mode(state: tauri::State<AppState>) -> bool {
+    state.safe_mode
+}
+
+#[tauri::command]
+fn get_debug_logging() -> bool {
+    logging::is_verbose()
+}
+
+/// Log debug records and full file paths until switched off again
+#[tauri::command]
+fn set_debug_logging(enabled: bool) {
+    logging::set_verbose(enabled);
+}
+
+/// Start a new instance of the app with safe mode switched on or off and
+/// close this one
+#[tauri::command]
+fn restart_in_safe_mode(
+    enabled: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<AppState>,
+) -> Result<(), String> {
+    #[cfg(mobile)]
+    {
+        let _ = (enabled, app, state);
+        return Err("Safe mode restarts are not supported on mobile".to_string());
+    }
+
+    #[cfg(not(mobile))]
+    {
+        let current_exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut command = std::process::Command::new(current_exe);
+        let profile_id = commands::history::active_profile_id(&state)
+            .or_else(|| lock_or_err(&state.initial_profile_id).ok()?.clone());
+        if let Some(profile_id) = profile_id {
+            command.arg(format!("--profile={}", profile_id));
+        }
+        if enabled {
+            command.arg(SAFE_MODE_FLAG);
+        }
+        command.spawn().map_err(|e| e.to_string())?;
+        app.exit(0);
+        Ok(())
+    }
+}
+
 fn install_rustls_crypto_provider() {
     let _ = rustls::crypto::ring::default_provider().install_default();
 }
@@ -56,6 +152,7 @@ pub fn run() {
     // Parse --profile= argument before building the app
     let initial_profile_id: Option<String> =
         std::env::args().find_map(|arg| arg.strip_prefix("--profile=").map(String::from));
+    let safe_mode = std::env::args().any(|arg| arg == SAFE_MODE_FLAG);
 
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
@@ -68,19 +165,24 @@ pub fn run() {
     let builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
 
     builder
-        .manage(AppState {
-            cache: Mutex::new(None),
-            recent_writes: Mutex::new(HashMap::new()),
-            initial_profile_id: Mutex::new(initial_profile_id),
-            nextcloud_login_sessions: Mutex::new(HashMap::new()),
-        })
-        .setup(|app| {
+        .manage(AppState::new(initial_profile_id, safe_mode))
+        .setup(move |app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
+                        .level(log::LevelFilter::Debug)
+                        .format(|out, message, record| {
+                            out.finish(format_args!(
+                                "{}[{}][{}] {}",
+                                chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                                record.level(),
+                                record.target(),
+                                logging::redact(&message.to_string())
+                            ))
+                        })
                         .build(),
                 )?;
+                logging::set_verbose(false);
             }
 
             // Build the main window programmatically (its tauri.conf.json
@@ -106,30 +208,212 @@ pub fn run() {
 
             builder.build()?;
 
+            commands::broadcast::init(app.handle().clone());
+
+            if safe_mode {
+                log::warn!("Started in safe mode; background jobs are disabled");
+            } else {
+                commands::backup::start_backup_scheduler(app.handle().clone());
+                commands::sync_schedule::start_sync_scheduler(app.handle().clone());
+            }
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            commands::notes::list_notes,
-            commands::notes::read_note,
-            commands::notes::create_note,
-            commands::notes::update_note,
-            commands::notes::delete_note,
-            commands::notes::create_folder,
-            commands::notes::rename_folder,
-            commands::notes::delete_folder,
-            commands::notes::move_note,
-            commands::notes::initialize_cache,
-            commands::notes::list_notes_cached,
-            commands::notes::process_file_changes,
-            commands::sync::nextcloud_login_start,
-            commands::sync::nextcloud_login_poll,
-            commands::sync::nextcloud_disconnect,
-            commands::sync::sync_now,
-            commands::sync::get_sync_status,
-            commands::sync::get_default_notes_dir,
-            open_profile_in_new_window,
-            get_initial_profile,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler({
+            let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+                commands::notes::list_notes,
+                commands::notes::read_note,
+                commands::notes::get_note_content,
+                commands::notes::create_note,
+                commands::notes::update_note,
+                commands::notes::delete_note,
+                commands::notes::set_note_locked,
+                commands::notes::toggle_pin,
+                commands::notes::get_title_heading_sync,
+                commands::notes::set_title_heading_sync,
+                commands::notes::archive_note,
+                commands::notes::unarchive_note,
+                commands::notes::set_note_priority,
+                commands::notes::set_note_color,
+                commands::notes::set_notes_color,
+                commands::cover::set_note_cover,
+                commands::cover::pick_note_cover,
+                commands::notes::set_note_icon,
+                commands::metadata::get_folder_metadata,
+                commands::metadata::set_folder_icon,
+                commands::metadata::get_note_metadata,
+                commands::metadata::set_note_metadata,
+                commands::links::resolve_wikilink,
+                commands::links::find_notes,
+                commands::notes::create_folder,
+                commands::notes::rename_folder,
+                commands::notes::delete_folder,
+                commands::notes::move_note,
+                commands::notes::initialize_cache,
+                commands::notes::list_notes_cached,
+                commands::notes::list_folders_cached,
+                commands::scan::start_vault_scan,
+                commands::scan::rebuild_cache,
+                commands::notes::process_file_changes,
+                commands::watch::queue_file_changes,
+                commands::watch::pause_watcher,
+                commands::watch::resume_watcher,
+                commands::poll_watch::get_watch_mode,
+                commands::poll_watch::set_watch_mode,
+                commands::poll_watch::start_polling_watcher,
+                commands::poll_watch::stop_polling_watcher,
+                commands::cloud::hydrate_note,
+                commands::audit::get_audit_log,
+                commands::references::get_code_reference_config,
+                commands::references::set_code_reference_config,
+                commands::references::resolve_external_reference,
+                commands::references::open_external_reference,
+                commands::report::export_progress_report,
+                commands::backup::get_backup_config,
+                commands::backup::set_backup_config,
+                commands::backup::backup_now,
+                commands::backup::list_backups,
+                commands::backup::restore_backup,
+                commands::backup::verify_backup,
+                commands::recovery::get_recovery_report,
+                commands::recovery::apply_recovery_fix,
+                commands::recovery::dismiss_recovery_report,
+                commands::import::import_obsidian,
+                commands::import::import_notion,
+                commands::import::import_joplin,
+                commands::import::import_trello,
+                commands::import::import_markdown_folder,
+                commands::import::import_note_bundle,
+                commands::encryption::lock_note,
+                commands::encryption::unlock_note,
+                commands::export::export_board_json,
+                commands::export::export_csv,
+                commands::export::export_html,
+                commands::export::export_pdf,
+                commands::export::export_note_bundle,
+                commands::calendar::export_ical,
+                commands::calendar::start_ical_feed,
+                commands::calendar::get_ical_feed,
+                commands::calendar::stop_ical_feed,
+                commands::calendar::get_overdue_notes,
+                commands::board::get_stale_cards,
+                commands::board::get_board_config,
+                commands::board::list_boards,
+                commands::board::create_board,
+                commands::board::add_column,
+                commands::board::rename_column,
+                commands::board::reorder_columns,
+                commands::board::delete_column,
+                commands::board::reorder_notes,
+                commands::board::get_wip_status,
+                commands::board::set_column_wip_limit,
+                commands::conflicts::list_conflicts,
+                commands::conflicts::merge_conflict,
+                commands::console::get_advanced_mode,
+                commands::console::set_advanced_mode,
+                commands::console::run_readonly_query,
+                commands::git::git_log,
+                commands::git::git_show,
+                commands::git::git_restore,
+                commands::git::link_commit,
+                commands::git::unlink_commit,
+                commands::git::get_linked_commits,
+                commands::git::generate_changelog_note,
+                commands::git_sync::get_git_sync_config,
+                commands::git_sync::set_git_sync_config,
+                commands::git_sync::git_sync_now,
+                commands::lan_sync::start_lan_sync_host,
+                commands::lan_sync::get_lan_sync_host,
+                commands::lan_sync::stop_lan_sync_host,
+                commands::lan_sync::discover_lan_peers,
+                commands::lan_sync::lan_sync_with_peer,
+                commands::history::list_versions,
+                commands::history::restore_version,
+                commands::history::diff_versions,
+                commands::inbox::get_inbox_config,
+                commands::inbox::set_inbox_config,
+                commands::inbox::get_inbox,
+                commands::inbox::triage_note,
+                commands::macros::save_macro,
+                commands::macros::list_macros,
+                commands::macros::delete_macro,
+                commands::macros::run_macro,
+                commands::profile::export_profile_config,
+                commands::profile::import_profile_config,
+                commands::profile_lock::get_profile_lock,
+                commands::profile_lock::set_profile_password,
+                commands::profile_lock::unlock_profile,
+                commands::profile_lock::lock_profile,
+                commands::cache::get_cache_encryption,
+                commands::cache::set_cache_encryption,
+                commands::cache::optimize_cache,
+                commands::cache::cache_stats,
+                commands::secrets::store_secret,
+                commands::secrets::get_secret,
+                commands::secrets::delete_secret,
+                commands::mounts::add_readonly_mount,
+                commands::mounts::remove_readonly_mount,
+                commands::mounts::list_readonly_mounts,
+                commands::symlinks::get_symlink_allowlist,
+                commands::symlinks::set_symlink_allowlist,
+                commands::scratchpad::get_scratchpad,
+                commands::scratchpad::append_to_scratchpad,
+                commands::scratchpad::clear_scratchpad,
+                commands::scratchpad::set_scratchpad_location,
+                commands::scratchpad::scratchpad_to_note,
+                commands::storage::get_storage_report,
+                commands::tags::export_tag_cooccurrence,
+                commands::sync::nextcloud_login_start,
+                commands::sync::nextcloud_login_poll,
+                commands::sync::nextcloud_disconnect,
+                commands::sync::sync_now,
+                commands::sync::get_sync_status,
+                commands::sync::get_sync_encryption,
+                commands::sync::set_sync_encryption,
+                commands::sync::get_selective_sync,
+                commands::sync::set_selective_sync,
+                commands::sync::get_default_notes_dir,
+                commands::sync_schedule::get_sync_schedule,
+                commands::sync_schedule::set_sync_schedule,
+                commands::trash::list_trash,
+                commands::trash::restore_from_trash,
+                commands::trash::empty_trash,
+                commands::trash::set_trash_retention_days,
+                commands::undo::list_undo_operations,
+                commands::undo::undo_last_operation,
+                commands::views::save_view,
+                commands::views::list_views,
+                commands::views::delete_view,
+                commands::views::list_notes_sorted,
+                commands::capabilities::open_secondary_window,
+                open_profile_in_new_window,
+                get_initial_profile,
+                get_safe_mode,
+                get_debug_logging,
+                set_debug_logging,
+                restart_in_safe_mode,
+            ];
+            move |invoke| {
+                let allowed = commands::capabilities::check_command(&invoke.message)
+                    .and_then(|_| commands::profile_lock::check_command(&invoke.message));
+                if let Err(e) = allowed {
+                    invoke.resolver.reject(e);
+                    return true;
+                }
+                let label = invoke.message.webview_ref().label().to_string();
+                commands::audit::with_invoking_window(&label, || handler(invoke))
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                if let Some(profile_id) = commands::history::active_profile_id(&state) {
+                    if !state.safe_mode {
+                        commands::recovery::end_session(&profile_id);
+                    }
+                }
+            }
+        });
 }
@@ -0,0 +1,115 @@
+use crate::utils::compute_content_hash;
+use lazy_static::lazy_static;
+use log::LevelFilter;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Debug verbosity: debug-level records and unredacted paths
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// Absolute paths in a message: Unix (`/home/...`), Windows (`C:\...`) and
+    /// `file://` URLs, starting a word and running up to whitespace or a quote
+    static ref PATH_REGEX: Regex = Regex::new(
+        r#"(^|[\s"'(\[=])((?:file://)?(?:[A-Za-z]:[\\/]|/)[^\s"'`]*[^\s"'`.,;:)\]])"#
+    )
+    .unwrap();
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Switch debug logging on or off. Off by default, so logs carry neither
+/// debug records nor file paths.
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+    log::set_max_level(if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    });
+}
+
+/// Stable stand-in for a path: the same file always logs the same token
+fn path_token(path: &str) -> String {
+    let extension = Path::new(path)
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    format!("<path:{}{}>", &compute_content_hash(path)[..8], extension)
+}
+
+/// A path for log messages, redacted unless debug logging is on
+pub struct LoggedPath<'a>(&'a Path);
+
+impl fmt::Display for LoggedPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.0.to_string_lossy();
+        if is_verbose() {
+            f.write_str(&path)
+        } else {
+            f.write_str(&path_token(&path))
+        }
+    }
+}
+
+pub fn path<P: AsRef<Path> + ?Sized>(path: &P) -> LoggedPath<'_> {
+    LoggedPath(path.as_ref())
+}
+
+/// User-written text for log messages, e.g. a note name or a device name,
+/// redacted unless debug logging is on
+pub struct LoggedText<'a>(&'a str);
+
+impl fmt::Display for LoggedText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_verbose() {
+            f.write_str(self.0)
+        } else {
+            write!(f, "<text:{}>", &compute_content_hash(self.0)[..8])
+        }
+    }
+}
+
+pub fn text(text: &str) -> LoggedText<'_> {
+    LoggedText(text)
+}
+
+/// Strip absolute paths from a finished log line, e.g. ones carried inside
+/// error strings. The last line of defense before a record is written.
+pub fn redact(message: &str) -> Cow<'_, str> {
+    if is_verbose() {
+        return Cow::Borrowed(message);
+    }
+    PATH_REGEX.replace_all(message, |caps: &Captures| {
+        format!("{}{}", &caps[1], path_token(&caps[2]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_paths_in_messages() {
+        let message =
+            "Failed to read /home/ana/Notes/Health.md: denied (see C:\\Users\\ana\\x.db).";
+        let redacted = redact(message);
+        assert!(!redacted.contains("ana") && !redacted.contains("Health"));
+        assert!(redacted.starts_with("Failed to read <path:"));
+        assert!(redacted.contains(".md>: denied"));
+        assert!(redacted.ends_with(".db>)."));
+        assert_eq!(redact("Synced 2/3 notes"), "Synced 2/3 notes");
+        assert_eq!(
+            path("/home/ana/Notes/Health.md").to_string(),
+            path_token("/home/ana/Notes/Health.md")
+        );
+        let name = text("Ana's laptop").to_string();
+        assert!(name.starts_with("<text:") && !name.contains("Ana"));
+        assert_eq!(name, text("Ana's laptop").to_string());
+    }
+}
@@ -0,0 +1,406 @@
+use chrono::DateTime;
+use serde_yaml::{Mapping, Value};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// Frontmatter key both sides always touch; the newer value wins instead of clashing
+const MODIFIED_KEY: &str = "modified";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMerge {
+    pub text: String,
+    /// Some regions changed on both sides and carry conflict markers
+    pub conflicted: bool,
+}
+
+/// Replacement of `base[start..end]` by `lines` on one side
+#[derive(Debug)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+fn hunks<'a>(base: &[&'a str], other: &[&'a str]) -> Vec<Hunk<'a>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        let (start, end, lines) = match op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => (old_index, old_index + old_len, Vec::new()),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => (
+                old_index,
+                old_index,
+                other[new_index..new_index + new_len].to_vec(),
+            ),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (
+                old_index,
+                old_index + old_len,
+                other[new_index..new_index + new_len].to_vec(),
+            ),
+        };
+        // A delete followed by an insert at the same spot is one change
+        match hunks.last_mut() {
+            Some(last) if last.end == start => {
+                last.end = end;
+                last.lines.extend(lines);
+            }
+            _ => hunks.push(Hunk { start, end, lines }),
+        }
+    }
+    hunks
+}
+
+/// `base[start..end]` with one side's hunks applied
+fn apply<'a>(base: &[&'a str], start: usize, end: usize, group: &[Hunk<'a>]) -> Vec<&'a str> {
+    let mut out = Vec::new();
+    let mut cursor = start;
+    for hunk in group {
+        out.extend_from_slice(&base[cursor..hunk.start]);
+        out.extend_from_slice(&hunk.lines);
+        cursor = hunk.end;
+    }
+    out.extend_from_slice(&base[cursor..end]);
+    out
+}
+
+fn push_block(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+    }
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Line-based three-way merge. Changes from either side are combined; where
+/// both sides changed the same or adjacent lines differently, the region is
+/// kept with `<<<<<<< local` / `=======` / `>>>>>>> remote` markers.
+pub fn merge_text(base: &str, local: &str, remote: &str) -> TextMerge {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let local_lines: Vec<&str> = local.split_inclusive('\n').collect();
+    let remote_lines: Vec<&str> = remote.split_inclusive('\n').collect();
+    let local_hunks = hunks(&base_lines, &local_lines);
+    let remote_hunks = hunks(&base_lines, &remote_lines);
+
+    let mut out = String::new();
+    let mut conflicted = false;
+    let mut position = 0;
+    let (mut i, mut j) = (0, 0);
+    while i < local_hunks.len() || j < remote_hunks.len() {
+        let take_local = match (local_hunks.get(i), remote_hunks.get(j)) {
+            (Some(a), Some(b)) => a.start <= b.start,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        let (first_local, first_remote) = (i, j);
+        let (start, mut end) = if take_local {
+            i += 1;
+            (local_hunks[i - 1].start, local_hunks[i - 1].end)
+        } else {
+            j += 1;
+            (remote_hunks[j - 1].start, remote_hunks[j - 1].end)
+        };
+        // Grow the region over every hunk of either side touching it
+        loop {
+            if let Some(hunk) = local_hunks.get(i).filter(|h| h.start <= end) {
+                end = end.max(hunk.end);
+                i += 1;
+            } else if let Some(hunk) = remote_hunks.get(j).filter(|h| h.start <= end) {
+                end = end.max(hunk.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        for line in &base_lines[position..start] {
+            out.push_str(line);
+        }
+        let local_group = &local_hunks[first_local..i];
+        let remote_group = &remote_hunks[first_remote..j];
+        let ours = apply(&base_lines, start, end, local_group);
+        let theirs = apply(&base_lines, start, end, remote_group);
+        if remote_group.is_empty() || ours == theirs {
+            ours.iter().for_each(|line| out.push_str(line));
+        } else if local_group.is_empty() {
+            theirs.iter().for_each(|line| out.push_str(line));
+        } else {
+            conflicted = true;
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("<<<<<<< local\n");
+            push_block(&mut out, &ours);
+            out.push_str("=======\n");
+            push_block(&mut out, &theirs);
+            out.push_str(">>>>>>> remote\n");
+        }
+        position = end;
+    }
+    for line in &base_lines[position..] {
+        out.push_str(line);
+    }
+    TextMerge {
+        text: out,
+        conflicted,
+    }
+}
+
+/// Frontmatter and trimmed body of a note
+fn split_note(text: &str) -> Option<(Mapping, &str)> {
+    let parts: Vec<&str> = text.splitn(3, "---").collect();
+    if parts.len() < 3 || !parts[0].trim().is_empty() {
+        return None;
+    }
+    let frontmatter = serde_yaml::from_str(parts[1].trim()).ok()?;
+    Some((frontmatter, parts[2].trim()))
+}
+
+fn newer<'a>(a: &'a Value, b: &'a Value) -> Option<&'a Value> {
+    let parse = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+    };
+    let (a_at, b_at) = (parse(a)?, parse(b)?);
+    Some(if a_at >= b_at { a } else { b })
+}
+
+/// Picks the value of a frontmatter key both sides changed, given the key,
+/// the local and the remote value (`Null` when a side removed it); `None`
+/// leaves the clash unresolved
+pub type FieldResolver<'a> = &'a dyn Fn(&str, &Value, &Value) -> Option<Value>;
+
+/// Key by key three-way merge; `None` when both sides set a key to
+/// different values and `resolve` can't settle it
+fn merge_frontmatter(
+    base: &Mapping,
+    local: &Mapping,
+    remote: &Mapping,
+    resolve: FieldResolver,
+) -> Option<Mapping> {
+    let mut merged = local.clone();
+    let keys: Vec<&Value> = local
+        .keys()
+        .chain(remote.keys().filter(|key| !local.contains_key(*key)))
+        .chain(
+            base.keys()
+                .filter(|key| !local.contains_key(*key) && !remote.contains_key(*key)),
+        )
+        .collect();
+    for key in keys {
+        let (ancestor, ours, theirs) = (base.get(key), local.get(key), remote.get(key));
+        let value = if ours == theirs || theirs == ancestor {
+            ours
+        } else if ours == ancestor {
+            theirs
+        } else if key.as_str() == Some(MODIFIED_KEY) {
+            Some(newer(ours?, theirs?)?)
+        } else {
+            let resolved = resolve(
+                key.as_str()?,
+                ours.unwrap_or(&Value::Null),
+                theirs.unwrap_or(&Value::Null),
+            )?;
+            match resolved {
+                Value::Null => merged.remove(key),
+                value => merged.insert(key.clone(), value),
+            };
+            continue;
+        };
+        match value {
+            Some(value) => merged.insert(key.clone(), value.clone()),
+            None => merged.remove(key),
+        };
+    }
+    Some(merged)
+}
+
+/// Three-way merge of two edits of a markdown note. The body is merged line
+/// by line (with conflict markers where needed); `None` when the frontmatter
+/// clashes in a way `resolve` can't settle or a version can't be parsed, so
+/// the caller keeps a conflict copy instead.
+pub fn merge_note(
+    base: &str,
+    local: &str,
+    remote: &str,
+    resolve: FieldResolver,
+) -> Option<TextMerge> {
+    let (base_frontmatter, base_body) = split_note(base)?;
+    let (local_frontmatter, local_body) = split_note(local)?;
+    let (remote_frontmatter, remote_body) = split_note(remote)?;
+    let frontmatter = merge_frontmatter(
+        &base_frontmatter,
+        &local_frontmatter,
+        &remote_frontmatter,
+        resolve,
+    )?;
+    let body = merge_text(base_body, local_body, remote_body);
+    let yaml = serde_yaml::to_string(&frontmatter).ok()?;
+    Some(TextMerge {
+        text: format!("---\n{}---\n\n{}", yaml, body.text),
+        conflicted: body.conflicted,
+    })
+}
+
+/// Frontmatter key holding a note's tags, unioned by `union_merge_note`
+const TAGS_KEY: &str = "tags";
+
+/// Lines of both texts: shared lines once, lines only one side has in
+/// order, and for replaced regions the first side's lines before the
+/// second's
+fn union_text(first: &str, second: &str) -> String {
+    let first_lines: Vec<&str> = first.split_inclusive('\n').collect();
+    let second_lines: Vec<&str> = second.split_inclusive('\n').collect();
+    let mut out = String::new();
+    for op in capture_diff_slices(Algorithm::Myers, &first_lines, &second_lines) {
+        let (old_range, new_range) = match op {
+            DiffOp::Equal { old_index, len, .. } => (old_index..old_index + len, 0..0),
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => (old_index..old_index + old_len, 0..0),
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => (0..0, new_index..new_index + new_len),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (
+                old_index..old_index + old_len,
+                new_index..new_index + new_len,
+            ),
+        };
+        push_block(&mut out, &first_lines[old_range]);
+        push_block(&mut out, &second_lines[new_range]);
+    }
+    out
+}
+
+/// Merge two versions of a note without a common ancestor, as left behind by
+/// sync tools that keep both sides. `original` wins clashing frontmatter
+/// keys except `modified` (newer wins) and `tags` (unioned); keys in
+/// `skip_keys` are not taken from `other`. The bodies are unioned line by
+/// line.
+pub fn union_merge_note(original: &str, other: &str, skip_keys: &[&str]) -> Option<String> {
+    let (mut frontmatter, original_body) = split_note(original)?;
+    let (other_frontmatter, other_body) = split_note(other)?;
+    for (key, theirs) in &other_frontmatter {
+        let name = key.as_str().unwrap_or_default();
+        if skip_keys.contains(&name) {
+            continue;
+        }
+        let merged = match (frontmatter.get(key), theirs) {
+            (None, _) => theirs.clone(),
+            (Some(Value::Sequence(ours)), Value::Sequence(theirs)) if name == TAGS_KEY => {
+                let mut tags = ours.clone();
+                tags.extend(theirs.iter().filter(|tag| !ours.contains(tag)).cloned());
+                Value::Sequence(tags)
+            }
+            (Some(ours), _) if name == MODIFIED_KEY => newer(ours, theirs).unwrap_or(ours).clone(),
+            _ => continue,
+        };
+        frontmatter.insert(key.clone(), merged);
+    }
+    let yaml = serde_yaml::to_string(&frontmatter).ok()?;
+    Some(format!(
+        "---\n{}---\n\n{}",
+        yaml,
+        union_text(original_body, other_body)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_separate_edits_and_marks_overlaps() {
+        let base = "one\ntwo\nthree\nfour\nfive\n";
+        let merged = merge_text(
+            base,
+            "ONE\ntwo\nthree\nfour\nfive\n",
+            "one\ntwo\nthree\nfour\nFIVE\nsix\n",
+        );
+        assert_eq!(
+            merged,
+            TextMerge {
+                text: "ONE\ntwo\nthree\nfour\nFIVE\nsix\n".to_string(),
+                conflicted: false,
+            }
+        );
+
+        let merged = merge_text(
+            base,
+            "one\n2\nthree\nfour\nfive\n",
+            "one\nTWO\nthree\nfour\nfive\n",
+        );
+        assert!(merged.conflicted);
+        assert_eq!(
+            merged.text,
+            "one\n<<<<<<< local\n2\n=======\nTWO\n>>>>>>> remote\nthree\nfour\nfive\n"
+        );
+    }
+
+    #[test]
+    fn merges_note_frontmatter_by_key() {
+        let note = |tags: &str, column: &str, modified: &str, body: &str| {
+            format!(
+                "---\nid: n1\ntitle: Plan\ncolumn: {}\ntags: [{}]\nmodified: {}\n---\n\n{}",
+                column, tags, modified, body
+            )
+        };
+        let base = note("a", "todo", "2024-01-01T00:00:00Z", "intro\n\nbody");
+        let local = note("a, b", "todo", "2024-01-02T00:00:00Z", "intro!\n\nbody");
+        let remote = note(
+            "a",
+            "doing",
+            "2024-01-03T00:00:00Z",
+            "intro\n\nbody\n\nmore",
+        );
+
+        let merged = merge_note(&base, &local, &remote, &|_, _, _| None).unwrap();
+        assert!(!merged.conflicted);
+        let (frontmatter, body) = split_note(&merged.text).unwrap();
+        assert_eq!(frontmatter["column"], "doing");
+        assert_eq!(
+            frontmatter["tags"],
+            serde_yaml::from_str::<Value>("[a, b]").unwrap()
+        );
+        assert_eq!(frontmatter["modified"], "2024-01-03T00:00:00Z");
+        assert_eq!(body, "intro!\n\nbody\n\nmore");
+
+        let clash = note("a", "done", "2024-01-02T00:00:00Z", "intro\n\nbody");
+        assert!(merge_note(&base, &clash, &remote, &|_, _, _| None).is_none());
+        let resolved =
+            merge_note(&base, &clash, &remote, &|_, _, theirs| Some(theirs.clone())).unwrap();
+        assert_eq!(split_note(&resolved.text).unwrap().0["column"], "doing");
+    }
+
+    #[test]
+    fn unions_versions_without_ancestor() {
+        let original = "---\nid: n1\ntitle: Plan\ntags: [a]\nmodified: 2024-01-01T00:00:00Z\n---\n\nintro\nmine\nend";
+        let copy = "---\nid: n1\ntitle: Other\ntags: [a, b]\nmodified: 2024-01-02T00:00:00Z\ndue: 2024-02-01\n---\n\nintro\ntheirs\nend";
+        let merged = union_merge_note(original, copy, &["id"]).unwrap();
+        let (frontmatter, body) = split_note(&merged).unwrap();
+        assert_eq!(frontmatter["title"], "Plan");
+        assert_eq!(
+            frontmatter["tags"],
+            serde_yaml::from_str::<Value>("[a, b]").unwrap()
+        );
+        assert_eq!(frontmatter["modified"], "2024-01-02T00:00:00Z");
+        assert_eq!(frontmatter["due"], "2024-02-01");
+        assert_eq!(body, "intro\nmine\ntheirs\nend");
+    }
+}
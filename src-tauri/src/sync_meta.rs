@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Vault folder holding one metadata file per device. Each device only
+/// writes its own file, so the files never conflict during sync.
+pub const SYNC_META_DIR: &str = ".noteban-sync";
+/// Frontmatter fields resolved last-writer-wins instead of clashing
+const LWW_FIELDS: [&str; 2] = ["column", "order"];
+
+/// When a device last set a field, on a Lamport clock shared by all devices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldStamp {
+    pub value: Value,
+    pub clock: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceMeta {
+    pub device: String,
+    /// Field stamps by note id and field name
+    #[serde(default)]
+    pub notes: BTreeMap<String, BTreeMap<String, FieldStamp>>,
+}
+
+pub fn is_sync_meta_path(relative_path: &str) -> bool {
+    relative_path
+        .strip_prefix(SYNC_META_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|name| !name.contains('/') && name.ends_with(".json"))
+}
+
+fn meta_path(root: &Path, device: &str) -> PathBuf {
+    root.join(SYNC_META_DIR).join(format!("{}.json", device))
+}
+
+/// Metadata of every device that has synced this vault
+pub fn load_all(root: &Path) -> Vec<DeviceMeta> {
+    let Ok(entries) = fs::read_dir(root.join(SYNC_META_DIR)) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read(entry.path()).ok())
+        .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+        .collect()
+}
+
+fn frontmatter(text: &str) -> Option<serde_yaml::Mapping> {
+    let parts: Vec<&str> = text.splitn(3, "---").collect();
+    if parts.len() < 3 || !parts[0].trim().is_empty() {
+        return None;
+    }
+    serde_yaml::from_str(parts[1].trim()).ok()
+}
+
+/// Frontmatter `id` of a note
+pub fn note_id(text: &str) -> Option<String> {
+    frontmatter(text)?
+        .get("id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Stamp fields whose value changed locally since the last stamp with the
+/// next clock tick, and write this device's metadata file
+pub fn stamp_local(root: &Path, device: &str) -> Result<(), String> {
+    let all = load_all(root);
+    let mut clock = all
+        .iter()
+        .flat_map(|meta| meta.notes.values())
+        .flat_map(|fields| fields.values())
+        .map(|stamp| stamp.clock)
+        .max()
+        .unwrap_or(0);
+    let mut own = all
+        .into_iter()
+        .find(|meta| meta.device == device)
+        .unwrap_or_else(|| DeviceMeta {
+            device: device.to_string(),
+            notes: BTreeMap::new(),
+        });
+
+    let mut changed = false;
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != SYNC_META_DIR)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "md"))
+    {
+        let Some(mapping) = fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|text| frontmatter(&text))
+        else {
+            continue;
+        };
+        let Some(id) = mapping.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let fields = own.notes.entry(id.to_string()).or_default();
+        for field in LWW_FIELDS {
+            let value = mapping.get(field).cloned().unwrap_or(Value::Null);
+            if fields.get(field).is_some_and(|stamp| stamp.value == value) {
+                continue;
+            }
+            clock += 1;
+            fields.insert(field.to_string(), FieldStamp { value, clock });
+            changed = true;
+        }
+    }
+
+    if changed {
+        let path = meta_path(root, device);
+        fs::create_dir_all(root.join(SYNC_META_DIR))
+            .map_err(|e| format!("Failed to create sync metadata folder: {}", e))?;
+        let bytes = serde_json::to_vec_pretty(&own)
+            .map_err(|e| format!("Failed to encode sync metadata: {}", e))?;
+        fs::write(path, bytes).map_err(|e| format!("Failed to write sync metadata: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Newest stamp some device recorded for `value`, as (clock, device) so ties
+/// break the same way everywhere
+fn stamp_of<'a>(
+    metas: &'a [DeviceMeta],
+    note_id: &str,
+    field: &str,
+    value: &Value,
+) -> (u64, &'a str) {
+    metas
+        .iter()
+        .filter_map(|meta| {
+            let stamp = meta.notes.get(note_id)?.get(field)?;
+            (stamp.value == *value).then_some((stamp.clock, meta.device.as_str()))
+        })
+        .max()
+        .unwrap_or((0, ""))
+}
+
+/// Last-writer-wins value for a field both sides changed; `None` for fields
+/// that are not resolved this way
+pub fn resolve(
+    metas: &[DeviceMeta],
+    note_id: &str,
+    field: &str,
+    ours: &Value,
+    theirs: &Value,
+) -> Option<Value> {
+    if !LWW_FIELDS.contains(&field) {
+        return None;
+    }
+    let winner = if stamp_of(metas, note_id, field, ours) >= stamp_of(metas, note_id, field, theirs)
+    {
+        ours
+    } else {
+        theirs
+    };
+    Some(winner.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_stamp_wins_column_clash() {
+        let root = std::env::temp_dir().join(format!("noteban-meta-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let note = |column: &str| format!("---\nid: n1\ncolumn: {}\norder: 1\n---\n\nBody", column);
+        fs::write(root.join("a.md"), note("todo")).unwrap();
+        stamp_local(&root, "laptop").unwrap();
+        fs::write(root.join("a.md"), note("doing")).unwrap();
+        stamp_local(&root, "desktop").unwrap();
+        fs::write(root.join("a.md"), note("done")).unwrap();
+        stamp_local(&root, "laptop").unwrap();
+
+        let metas = load_all(&root);
+        assert_eq!(metas.len(), 2);
+        let (doing, done) = (Value::from("doing"), Value::from("done"));
+        assert_eq!(
+            resolve(&metas, "n1", "column", &doing, &done),
+            Some(done.clone())
+        );
+        assert_eq!(resolve(&metas, "n1", "column", &done, &doing), Some(done));
+        assert_eq!(resolve(&metas, "n1", "title", &doing, &doing), None);
+        assert!(is_sync_meta_path(".noteban-sync/laptop.json"));
+        assert!(!is_sync_meta_path(".noteban-sync/nested/laptop.json"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
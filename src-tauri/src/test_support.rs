@@ -0,0 +1,100 @@
+//! Fixtures for tests that drive commands against a scratch vault
+use crate::cache::CacheDb;
+use crate::AppState;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, AppHandle, Manager, State};
+use uuid::Uuid;
+
+pub(crate) const TEST_PROFILE: &str = "test-profile";
+
+static ISOLATE_DIRS: Once = Once::new();
+
+/// Point the per-user cache, data and config directories at a scratch
+/// location so history snapshots and profile files never reach the real ones
+fn isolate_user_dirs() {
+    ISOLATE_DIRS.call_once(|| {
+        let root = std::env::temp_dir().join(format!("noteban-home-{}", Uuid::new_v4()));
+        for (var, dir) in [
+            ("XDG_CACHE_HOME", "cache"),
+            ("XDG_DATA_HOME", "data"),
+            ("XDG_CONFIG_HOME", "config"),
+        ] {
+            let path = root.join(dir);
+            fs::create_dir_all(&path).unwrap();
+            std::env::set_var(var, path);
+        }
+    });
+}
+
+/// A vault directory with an app whose state holds an in-memory cache
+pub(crate) struct TestVault {
+    app: App<MockRuntime>,
+    pub dir: PathBuf,
+}
+
+impl TestVault {
+    pub(crate) fn new() -> Self {
+        isolate_user_dirs();
+        let dir = std::env::temp_dir().join(format!("noteban-vault-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let state = AppState::new(None, false);
+        *state.cache.lock().unwrap() = Some(CacheDb::in_memory(TEST_PROFILE).unwrap());
+        let app = mock_app();
+        app.manage(state);
+        Self { app, dir }
+    }
+
+    pub(crate) fn state(&self) -> State<'_, AppState> {
+        self.app.state::<AppState>()
+    }
+
+    /// Handle for code that emits events or looks up state itself
+    pub(crate) fn app_handle(&self) -> AppHandle<MockRuntime> {
+        self.app.handle().clone()
+    }
+
+    pub(crate) fn notes_dir(&self) -> String {
+        self.dir.to_string_lossy().to_string()
+    }
+
+    pub(crate) fn path(&self, relative: &str) -> String {
+        self.dir.join(relative).to_string_lossy().to_string()
+    }
+
+    /// Write a file, creating its folders, and return its absolute path
+    pub(crate) fn write(&self, relative: &str, content: &str) -> String {
+        let path = self.dir.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    /// Write a note with YAML frontmatter and return its absolute path
+    pub(crate) fn note(&self, relative: &str, id: &str, extra: &str) -> String {
+        self.write(
+            relative,
+            &format!(
+                "---\nid: {id}\ntitle: {id}\ncreated: 2024-01-01T00:00:00Z\nmodified: 2024-01-01T00:00:00Z\ncolumn: todo\n{extra}---\n\n# {id}\n"
+            ),
+        )
+    }
+
+    pub(crate) fn read(&self, relative: &str) -> String {
+        fs::read_to_string(self.dir.join(relative)).unwrap()
+    }
+
+    pub(crate) fn exists(&self, relative: &str) -> bool {
+        Path::new(&self.path(relative)).exists()
+    }
+}
+
+impl Drop for TestVault {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
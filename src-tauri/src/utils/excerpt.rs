@@ -0,0 +1,83 @@
+use lazy_static::lazy_static;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::{Captures, Regex};
+
+/// Longest excerpt in characters, not counting the trailing ellipsis
+const EXCERPT_MAX_CHARS: usize = 200;
+
+lazy_static! {
+    // [[target]], [[target|alias]] and embeds ![[file]]
+    static ref WIKILINK_REGEX: Regex =
+        Regex::new(r"(!?)\[\[([^\[\]|\n]+)(?:\|([^\[\]\n]+))?\]\]").unwrap();
+}
+
+/// Short plaintext preview of a note body for cards. Markdown syntax, code
+/// blocks, images and embeds are dropped and whitespace is collapsed.
+pub fn make_excerpt(content: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut text = String::new();
+    let mut hidden = 0usize;
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Image { .. }) => hidden += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::Image) => hidden = hidden.saturating_sub(1),
+            Event::Text(t) | Event::Code(t) if hidden == 0 => text.push_str(&t),
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell,
+            ) => text.push(' '),
+            _ => {}
+        }
+        // Plenty to fill the excerpt once link syntax is gone
+        if text.len() > EXCERPT_MAX_CHARS * 4 {
+            break;
+        }
+    }
+
+    let text = WIKILINK_REGEX.replace_all(&text, |caps: &Captures| {
+        if &caps[1] == "!" {
+            String::new()
+        } else {
+            caps.get(3)
+                .unwrap_or_else(|| caps.get(2).unwrap())
+                .as_str()
+                .to_string()
+        }
+    });
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= EXCERPT_MAX_CHARS {
+        return collapsed;
+    }
+
+    let cut: String = collapsed.chars().take(EXCERPT_MAX_CHARS).collect();
+    // Prefer ending on a word boundary unless that loses too much
+    let cut = match cut.rfind(' ') {
+        Some(index) if index > EXCERPT_MAX_CHARS / 2 => &cut[..index],
+        _ => &cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_markdown_syntax() {
+        let content = "# Plan\n\nShip **the** [release](https://example.com) and `fix` it.\n\n```\ncode\n```\n- [ ] see [[Other note|other]] ![[diagram.png]]";
+        assert_eq!(
+            make_excerpt(content),
+            "Plan Ship the release and fix it. see other"
+        );
+    }
+
+    #[test]
+    fn truncates_on_a_word_boundary() {
+        let content = "word ".repeat(100);
+        let excerpt = make_excerpt(&content);
+        assert!(excerpt.ends_with("word…"));
+        assert!(excerpt.chars().count() <= EXCERPT_MAX_CHARS + 1);
+    }
+}
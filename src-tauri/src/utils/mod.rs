@@ -1,3 +1,5 @@
+pub mod excerpt;
 pub mod tags;
 
+pub use excerpt::make_excerpt;
 pub use tags::{compute_content_hash, extract_inline_tags};
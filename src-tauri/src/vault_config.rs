@@ -0,0 +1,414 @@
+use crate::commands::notes::{atomic_write, ensure_safe_relative_path, is_skipped_dir_name};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Vault folder holding the board, template and settings files shared by
+/// every window and device working on the vault
+pub const VAULT_CONFIG_DIR: &str = ".noteban";
+/// Per-folder metadata, keyed by path relative to the vault with `/` separators
+pub const FOLDERS_FILE: &str = "folders.json";
+/// Columns of the kanban board, in display order. A folder with its own
+/// board file gets a separate board for the notes below it.
+pub const BOARD_FILE: &str = "board.json";
+/// Columns of a vault without a board file: id, title and color
+const DEFAULT_COLUMNS: [(&str, &str, &str); 4] = [
+    ("backlog", "Backlog", "#6c7086"),
+    ("todo", "To Do", "#89b4fa"),
+    ("doing", "In Progress", "#fab387"),
+    ("done", "Done", "#a6e3a1"),
+];
+/// Column new notes go to while their board has it
+const DEFAULT_NOTE_COLUMN: &str = "todo";
+
+/// Part of the vault configuration a file belongs to, named after the file
+/// or folder directly inside [`VAULT_CONFIG_DIR`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigKind {
+    Board,
+    Templates,
+    Settings,
+    Folders,
+    Other,
+}
+
+/// Metadata kept for a folder, which has no frontmatter of its own
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+impl FolderMeta {
+    fn is_empty(&self) -> bool {
+        self.icon.is_none()
+    }
+}
+
+pub type FolderMetaMap = BTreeMap<String, FolderMeta>;
+
+/// What happens when a card would push a column over its WIP limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WipPolicy {
+    /// The card moves and a warning is sent
+    #[default]
+    Soft,
+    /// The move is refused
+    Hard,
+}
+
+impl WipPolicy {
+    fn is_soft(&self) -> bool {
+        *self == WipPolicy::Soft
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardColumn {
+    /// Value of the `column` frontmatter key of the notes in the column
+    pub id: String,
+    pub title: String,
+    pub color: String,
+    /// Most cards the column should hold; archived notes don't count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wip_limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "WipPolicy::is_soft")]
+    pub wip_policy: WipPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub columns: Vec<BoardColumn>,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        let columns = DEFAULT_COLUMNS
+            .iter()
+            .map(|(id, title, color)| BoardColumn {
+                id: id.to_string(),
+                title: title.to_string(),
+                color: color.to_string(),
+                wip_limit: None,
+                wip_policy: WipPolicy::Soft,
+            })
+            .collect();
+        Self { columns }
+    }
+}
+
+impl BoardConfig {
+    pub fn column(&self, id: &str) -> Option<&BoardColumn> {
+        self.columns.iter().find(|column| column.id == id)
+    }
+
+    pub fn column_mut(&mut self, id: &str) -> Result<&mut BoardColumn, String> {
+        self.columns
+            .iter_mut()
+            .find(|column| column.id == id)
+            .ok_or_else(|| format!("Column not found: {}", id))
+    }
+
+    /// Column new notes go to when none is given: "todo" while the board
+    /// has it, else its first column, since columns can be renamed or
+    /// deleted
+    pub fn default_column(&self) -> String {
+        self.column(DEFAULT_NOTE_COLUMN)
+            .or(self.columns.first())
+            .map(|column| column.id.clone())
+            .unwrap_or_else(|| DEFAULT_NOTE_COLUMN.to_string())
+    }
+}
+
+fn config_path(notes_dir: &Path, name: &str) -> PathBuf {
+    notes_dir.join(VAULT_CONFIG_DIR).join(name)
+}
+
+/// Read a JSON file of the configuration folder; a missing file reads as the
+/// default value
+pub fn load_config<T: DeserializeOwned + Default>(
+    notes_dir: &Path,
+    name: &str,
+) -> Result<T, String> {
+    match fs::read_to_string(config_path(notes_dir, name)) {
+        Ok(text) => {
+            serde_json::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", name, e))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(format!("Failed to read {}: {}", name, e)),
+    }
+}
+
+pub fn save_config<T: Serialize>(notes_dir: &Path, name: &str, value: &T) -> Result<(), String> {
+    let path = config_path(notes_dir, name);
+    fs::create_dir_all(notes_dir.join(VAULT_CONFIG_DIR))
+        .map_err(|e| format!("Failed to create config folder: {}", e))?;
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to encode {}: {}", name, e))?;
+    atomic_write(&path, &text)
+}
+
+/// Key of `folder` in [`FOLDERS_FILE`]
+pub fn folder_key(notes_dir: &Path, folder: &Path) -> Option<String> {
+    let relative = folder.strip_prefix(notes_dir).ok()?;
+    let key = relative.to_string_lossy().replace('\\', "/");
+    (!key.is_empty()).then_some(key)
+}
+
+/// Change the metadata of one folder; entries left empty are dropped
+pub fn update_folder_meta(
+    notes_dir: &Path,
+    key: &str,
+    change: impl FnOnce(&mut FolderMeta),
+) -> Result<FolderMeta, String> {
+    let mut folders: FolderMetaMap = load_config(notes_dir, FOLDERS_FILE)?;
+    let meta = folders.entry(key.to_string()).or_default();
+    change(meta);
+    let meta = meta.clone();
+    if meta.is_empty() {
+        folders.remove(key);
+    }
+    save_config(notes_dir, FOLDERS_FILE, &folders)?;
+    Ok(meta)
+}
+
+/// Re-key the metadata of `from` and its subfolders after a rename, or drop
+/// it when `to` is `None`
+pub fn move_folder_meta(notes_dir: &Path, from: &str, to: Option<&str>) -> Result<(), String> {
+    let mut folders: FolderMetaMap = load_config(notes_dir, FOLDERS_FILE)?;
+    let prefix = format!("{}/", from);
+    let moved: Vec<String> = folders
+        .keys()
+        .filter(|key| *key == from || key.starts_with(&prefix))
+        .cloned()
+        .collect();
+    if moved.is_empty() {
+        return Ok(());
+    }
+    for key in moved {
+        let Some(meta) = folders.remove(&key) else {
+            continue;
+        };
+        if let Some(to) = to {
+            folders.insert(format!("{}{}", to, &key[from.len()..]), meta);
+        }
+    }
+    save_config(notes_dir, FOLDERS_FILE, &folders)
+}
+
+/// Folder of the board `board`, given relative to the vault; `None` or an
+/// empty path is the vault's own board
+pub fn board_root(notes_dir: &Path, board: Option<&str>) -> Result<PathBuf, String> {
+    let board = board.unwrap_or_default().trim().trim_matches('/');
+    if board.is_empty() {
+        return Ok(notes_dir.to_path_buf());
+    }
+    ensure_safe_relative_path(Path::new(board))?;
+    let root = notes_dir.join(board);
+    if !root.is_dir() {
+        return Err(format!("Folder not found: {}", board));
+    }
+    Ok(root)
+}
+
+/// Whether `folder` has a board file of its own
+pub fn has_board(folder: &Path) -> bool {
+    config_path(folder, BOARD_FILE).is_file()
+}
+
+/// Folders below `root` with a board of their own, relative to `root`
+pub fn board_folders(root: &Path) -> Vec<String> {
+    WalkDir::new(root)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || entry.file_type().is_dir()
+                    && !name.starts_with('.')
+                    && !is_skipped_dir_name(&name)
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| has_board(entry.path()))
+        .filter_map(|entry| folder_key(root, entry.path()))
+        .collect()
+}
+
+/// Absolute paths of the folders below `root` with a board of their own
+pub fn nested_boards(root: &Path) -> Vec<PathBuf> {
+    board_folders(root)
+        .into_iter()
+        .map(|folder| root.join(folder))
+        .collect()
+}
+
+/// Folder of the board showing the note at `path`: the nearest one above it
+/// with a board file, or else the vault
+pub fn board_root_of(notes_dir: &Path, path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(folder) => folder_board_root(notes_dir, folder),
+        None => notes_dir.to_path_buf(),
+    }
+}
+
+/// Folder of the board showing notes directly in `folder`
+pub fn folder_board_root(notes_dir: &Path, folder: &Path) -> PathBuf {
+    folder
+        .ancestors()
+        .take_while(|folder| *folder != notes_dir && folder.starts_with(notes_dir))
+        .find(|folder| has_board(folder))
+        .unwrap_or(notes_dir)
+        .to_path_buf()
+}
+
+fn default_column_of(root: &Path) -> String {
+    load_config::<BoardConfig>(root, BOARD_FILE)
+        .unwrap_or_default()
+        .default_column()
+}
+
+/// Column a new note in `folder` goes to when none is given
+pub fn default_column(notes_dir: &Path, folder: &Path) -> String {
+    default_column_of(&folder_board_root(notes_dir, folder))
+}
+
+/// Column for a note at `path` whose vault is unknown, taken from the
+/// nearest board file above it
+pub fn nearest_default_column(path: &Path) -> String {
+    match path.ancestors().skip(1).find(|folder| has_board(folder)) {
+        Some(root) => default_column_of(root),
+        None => BoardConfig::default().default_column(),
+    }
+}
+
+/// Notes shown on one board: those below its folder and not below a folder
+/// with a board of its own
+#[derive(Debug, Clone)]
+pub struct BoardScope {
+    root: PathBuf,
+    nested: Vec<PathBuf>,
+}
+
+impl BoardScope {
+    pub fn new(root: PathBuf) -> Self {
+        let nested = nested_boards(&root);
+        Self::with_nested(root, nested)
+    }
+
+    /// Scope of `root` given the folders below it that have a board
+    pub fn with_nested(root: PathBuf, nested: Vec<PathBuf>) -> Self {
+        Self { root, nested }
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root) && !self.nested.iter().any(|nested| path.starts_with(nested))
+    }
+}
+
+/// Classify `path` if it lies in the configuration folder of `notes_dir`, or
+/// is the board file of one of its folders
+pub fn config_kind(notes_dir: &Path, path: &Path) -> Option<ConfigKind> {
+    let relative = path.strip_prefix(notes_dir).ok()?;
+    let mut components = relative.components();
+    if components.next()?.as_os_str() != VAULT_CONFIG_DIR {
+        // Subfolders only keep a board in their configuration folder
+        let board = Path::new(VAULT_CONFIG_DIR).join(BOARD_FILE);
+        return relative.ends_with(board).then_some(ConfigKind::Board);
+    }
+    let first = components.next()?;
+    let stem = Path::new(first.as_os_str()).file_stem()?.to_str()?;
+    Some(match stem {
+        "board" => ConfigKind::Board,
+        "templates" => ConfigKind::Templates,
+        "settings" => ConfigKind::Settings,
+        "folders" => ConfigKind::Folders,
+        _ => ConfigKind::Other,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_config_files() {
+        let vault = Path::new("/vault");
+        let kind = |path: &str| config_kind(vault, Path::new(path));
+        assert_eq!(kind("/vault/.noteban/board.json"), Some(ConfigKind::Board));
+        assert_eq!(
+            kind("/vault/.noteban/templates/meeting.md"),
+            Some(ConfigKind::Templates)
+        );
+        assert_eq!(
+            kind("/vault/.noteban/settings.json"),
+            Some(ConfigKind::Settings)
+        );
+        assert_eq!(kind("/vault/.noteban/cache.tmp"), Some(ConfigKind::Other));
+        assert_eq!(kind("/vault/.noteban"), None);
+        assert_eq!(kind("/vault/work/board.json"), None);
+        assert_eq!(
+            kind("/vault/work/acme/.noteban/board.json"),
+            Some(ConfigKind::Board)
+        );
+        assert_eq!(kind("/vault/work/.noteban/settings.json"), None);
+    }
+
+    #[test]
+    fn moves_folder_meta_with_subfolders() {
+        let vault = std::env::temp_dir().join(format!("noteban-folders-{}", uuid::Uuid::new_v4()));
+        let icon =
+            |icon: &'static str| move |meta: &mut FolderMeta| meta.icon = Some(icon.to_string());
+        update_folder_meta(&vault, "work", icon("💼")).unwrap();
+        update_folder_meta(&vault, "work/acme", icon("🚀")).unwrap();
+        update_folder_meta(&vault, "workshop", icon("🔧")).unwrap();
+
+        move_folder_meta(&vault, "work", Some("jobs")).unwrap();
+        let folders: FolderMetaMap = load_config(&vault, FOLDERS_FILE).unwrap();
+        let keys: Vec<&str> = folders.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["jobs", "jobs/acme", "workshop"]);
+
+        move_folder_meta(&vault, "jobs", None).unwrap();
+        update_folder_meta(&vault, "workshop", |meta| meta.icon = None).unwrap();
+        let folders: FolderMetaMap = load_config(&vault, FOLDERS_FILE).unwrap();
+        assert!(folders.is_empty());
+
+        fs::remove_dir_all(&vault).unwrap();
+    }
+
+    #[test]
+    fn scopes_notes_to_nearest_board() {
+        let vault = std::env::temp_dir().join(format!("noteban-boards-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(vault.join("projects/acme/specs")).unwrap();
+        save_config(
+            &vault.join("projects/acme"),
+            BOARD_FILE,
+            &BoardConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(board_folders(&vault), ["projects/acme"]);
+        let spec = vault.join("projects/acme/specs/api.md");
+        assert_eq!(board_root_of(&vault, &spec), vault.join("projects/acme"));
+        assert_eq!(
+            board_root_of(&vault, &vault.join("projects/todo.md")),
+            vault
+        );
+
+        let root = BoardScope::new(board_root(&vault, None).unwrap());
+        let acme = BoardScope::new(board_root(&vault, Some("projects/acme/")).unwrap());
+        assert!(root.contains(&vault.join("projects/todo.md")));
+        assert!(!root.contains(&spec));
+        assert!(acme.contains(&spec));
+        assert!(board_root(&vault, Some("../elsewhere")).is_err());
+        assert!(board_root(&vault, Some("missing")).is_err());
+
+        fs::remove_dir_all(&vault).unwrap();
+    }
+}
@@ -3,6 +3,8 @@ import { open } from '@tauri-apps/plugin-dialog';
 import { watchImmediate } from '@tauri-apps/plugin-fs';
 import { exit } from '@tauri-apps/plugin-process';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { UnlistenFn } from '@tauri-apps/api/event';
 import type { UnwatchFn } from '@tauri-apps/plugin-fs';
 import { Layout, SettingsModal } from './components/layout';
 import { NoteEditor } from './components/editor';
@@ -12,7 +14,7 @@ import { useNotesStore } from './stores/notesStore';
 import { useSettingsStore } from './stores/settingsStore';
 import { useUIStore } from './stores/uiStore';
 import { useSyncStore } from './stores/syncStore';
-import type { FileChangeEvent } from './types/folder';
+import type { FileChangeEvent, IncrementalUpdateResult } from './types/folder';
 import { initDebugLogging, debugLog } from './utils/debugLogger';
 import { setWindowTitle } from './utils/windowTitle';
 import { isMobile } from './utils/platform';
@@ -26,8 +28,15 @@ function mapWatchEventType(type: string): FileChangeEvent['event_type'] {
 }
 
 function App() {
-  const { notes, loadNotes, setActiveNote, initializeCache, processFileChanges, cacheInitialized } =
-    useNotesStore();
+  const {
+    notes,
+    loadNotes,
+    setActiveNote,
+    initializeCache,
+    processFileChanges,
+    applyFileChanges,
+    cacheInitialized,
+  } = useNotesStore();
   const { settings, root, setNotesDirectory } = useSettingsStore();
   const { currentView, setView, setShowSettings } = useUIStore();
   const { syncNow, loadStatus, ensureNextcloudNotesDirectory } = useSyncStore();
@@ -242,15 +251,43 @@ function App() {
     if (!settings.notesDirectory || !cacheInitialized || isMobile) return;
 
     let unwatch: UnwatchFn | null = null;
+    let unlisten: UnlistenFn | null = null;
+    let pollId: number | null = null;
+    let cancelled = false;
 
     const startWatching = async () => {
       try {
+        // Network drives don't report edits made elsewhere; the backend polls
+        // those vaults and sends what it finds
+        pollId = await invoke<number | null>('start_polling_watcher', {
+          notesDir: settings.notesDirectory,
+        });
+        if (pollId !== null) {
+          const stop = await listen<IncrementalUpdateResult>('notes://changes', (event) =>
+            applyFileChanges(event.payload)
+          );
+          if (cancelled) {
+            stop();
+            invoke('stop_polling_watcher', { pollId }).catch(() => {});
+          } else {
+            unlisten = stop;
+          }
+          debugLog.log('Polling for file changes in:', settings.notesDirectory);
+          return;
+        }
+
         debugLog.log('Starting file watcher for directory:', settings.notesDirectory);
         unwatch = await watchImmediate(
           settings.notesDirectory,
           (event) => {
-            // Only process markdown file events
-            const mdPaths = event.paths.filter((p) => p.endsWith('.md'));
+            // Only process markdown files, attachments, vault configuration and
+            // folders (paths without an extension)
+            const mdPaths = event.paths.filter(
+              (p) =>
+                p.endsWith('.md') ||
+                /(\.attachments|[\\/]\.noteban)[\\/]/.test(p) ||
+                !/\.[^.\\/]*$/.test(p)
+            );
             if (mdPaths.length === 0) return;
 
             // Map watch events to our change format
@@ -291,16 +328,23 @@ function App() {
     startWatching();
 
     return () => {
+      cancelled = true;
       if (unwatch) {
         debugLog.log('Stopping file watcher');
         unwatch();
       }
+      if (unlisten) {
+        unlisten();
+      }
+      if (pollId !== null) {
+        invoke('stop_polling_watcher', { pollId }).catch(() => {});
+      }
       if (debounceTimerRef.current) {
         clearTimeout(debounceTimerRef.current);
       }
       pendingChangesRef.current = [];
     };
-  }, [settings.notesDirectory, cacheInitialized, processFileChanges]);
+  }, [settings.notesDirectory, cacheInitialized, processFileChanges, applyFileChanges]);
 
   const handleSelectFolder = async () => {
     setIsSelectingFolder(true);
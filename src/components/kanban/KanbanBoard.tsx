@@ -52,6 +52,7 @@ export function KanbanBoard() {
         const noteTags = getNoteTags(note.frontmatter.id);
         return (
           note.frontmatter.title.toLowerCase().includes(query) ||
+          note.frontmatter.aliases?.some((alias) => alias.toLowerCase().includes(query)) ||
           note.content.toLowerCase().includes(query) ||
           noteTags.some((tag) => tag.toLowerCase().includes(query))
         );
@@ -67,7 +68,11 @@ export function KanbanBoard() {
     settings.columns.forEach(col => {
       grouped[col.id] = filteredNotes
         .filter(note => note.frontmatter.column === col.id)
-        .sort((a, b) => a.frontmatter.order - b.frontmatter.order);
+        .sort(
+          (a, b) =>
+            Number(!!b.frontmatter.pinned) - Number(!!a.frontmatter.pinned) ||
+            a.frontmatter.order - b.frontmatter.order
+        );
     });
     return grouped;
   }, [filteredNotes, settings.columns]);
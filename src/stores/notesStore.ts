@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { Note, CreateNoteInput, UpdateNoteInput } from '../types/note';
+import type { Note, CreateNoteInput, UpdateNoteInput, Priority } from '../types/note';
 import type {
   NotesWithTagsAndFolders,
   FileChangeEvent,
@@ -22,8 +22,14 @@ interface NotesState {
   initializeCache: (profileId: string) => Promise<void>;
   loadNotes: (notesDir: string) => Promise<void>;
   processFileChanges: (notesDir: string, changes: FileChangeEvent[]) => Promise<void>;
+  applyFileChanges: (result: IncrementalUpdateResult) => void;
   createNote: (input: CreateNoteInput) => Promise<Note>;
   updateNote: (input: Omit<UpdateNoteInput, 'notes_dir'>) => Promise<void>;
+  togglePin: (filePath: string) => Promise<void>;
+  archiveNote: (filePath: string) => Promise<void>;
+  setNotesColor: (filePaths: string[], color: string | null) => Promise<string[]>;
+  reorderNotes: (column: string, filePaths: string[]) => Promise<void>;
+  setPriority: (filePath: string, priority: Priority | null) => Promise<void>;
   deleteNote: (filePath: string) => Promise<void>;
   moveNote: (filePath: string, targetFolder: string) => Promise<void>;
   setActiveNote: (id: string | null) => void;
@@ -86,57 +92,80 @@ export const useNotesStore = create<NotesState>((set, get) => ({
         notesDir,
         changes,
       });
+      get().applyFileChanges(result);
+    } catch (error) {
+      debugLog.error('Failed to process file changes:', error);
+      console.error('Failed to process file changes:', error);
+      // Fall back to full reload on error
+      get().loadNotes(notesDir);
+    }
+  },
 
-      // Skip update if nothing changed
-      if (result.updated_notes.length === 0 && result.removed_paths.length === 0) {
-        debugLog.log('No actual changes detected after processing');
-        return;
-      }
+  applyFileChanges: (result: IncrementalUpdateResult) => {
+    if (result.added_folders.length > 0 || result.removed_folders.length > 0) {
+      const folderStore = useFolderStore.getState();
+      const removed = result.removed_folders;
+      const kept = folderStore.folders.filter(
+        (f) => !removed.some((path) => f.path === path || f.path.startsWith(`${path}/`))
+      );
+      const known = new Set(kept.map((f) => f.path));
+      const added = result.added_folders.filter((f) => !known.has(f.path));
+      folderStore.setFolders(
+        [...kept, ...added].sort((a, b) => a.relative_path.localeCompare(b.relative_path))
+      );
+    }
+
+    // Skip update if nothing changed
+    if (result.updated_notes.length === 0 && result.removed_paths.length === 0) {
+      debugLog.log('No actual changes detected after processing');
+      return;
+    }
 
-      debugLog.log(`File changes result: ${result.updated_notes.length} updated, ${result.removed_paths.length} removed`);
+    debugLog.log(`File changes result: ${result.updated_notes.length} updated, ${result.removed_paths.length} removed`);
 
-      set((state) => {
-        const newNotes = [...state.notes];
-        const newInlineTags = new Map(state.inlineTags);
+    set((state) => {
+      const newNotes = [...state.notes];
+      const newInlineTags = new Map(state.inlineTags);
 
-        // Remove deleted notes
-        for (const removedPath of result.removed_paths) {
-          const idx = newNotes.findIndex((n) => n.file_path === removedPath);
-          if (idx >= 0) {
-            const noteId = newNotes[idx].frontmatter.id;
-            newInlineTags.delete(noteId);
-            newNotes.splice(idx, 1);
-          }
+      // Remove deleted notes
+      for (const removedPath of result.removed_paths) {
+        const idx = newNotes.findIndex((n) => n.file_path === removedPath);
+        if (idx >= 0) {
+          const noteId = newNotes[idx].frontmatter.id;
+          newInlineTags.delete(noteId);
+          newNotes.splice(idx, 1);
         }
+      }
 
-        // Update/add changed notes
-        for (const nwt of result.updated_notes) {
-          const idx = newNotes.findIndex(
-            (n) => n.frontmatter.id === nwt.note.frontmatter.id
-          );
+      // Update/add changed notes; notes archived elsewhere leave the board
+      for (const nwt of result.updated_notes) {
+        const idx = newNotes.findIndex(
+          (n) => n.frontmatter.id === nwt.note.frontmatter.id
+        );
+        if (nwt.note.frontmatter.archived) {
           if (idx >= 0) {
-            newNotes[idx] = nwt.note;
-          } else {
-            newNotes.push(nwt.note);
+            newNotes.splice(idx, 1);
           }
-          newInlineTags.set(nwt.note.frontmatter.id, nwt.inline_tags);
+          newInlineTags.delete(nwt.note.frontmatter.id);
+          continue;
         }
+        if (idx >= 0) {
+          newNotes[idx] = nwt.note;
+        } else {
+          newNotes.push(nwt.note);
+        }
+        newInlineTags.set(nwt.note.frontmatter.id, nwt.inline_tags);
+      }
 
-        // Sort by modified date (newest first)
-        newNotes.sort(
-          (a, b) =>
-            new Date(b.frontmatter.modified).getTime() -
-            new Date(a.frontmatter.modified).getTime()
-        );
+      // Sort by modified date (newest first)
+      newNotes.sort(
+        (a, b) =>
+          new Date(b.frontmatter.modified).getTime() -
+          new Date(a.frontmatter.modified).getTime()
+      );
 
-        return { notes: newNotes, inlineTags: newInlineTags };
-      });
-    } catch (error) {
-      debugLog.error('Failed to process file changes:', error);
-      console.error('Failed to process file changes:', error);
-      // Fall back to full reload on error
-      get().loadNotes(notesDir);
-    }
+      return { notes: newNotes, inlineTags: newInlineTags };
+    });
   },
 
   createNote: async (input: CreateNoteInput) => {
@@ -169,6 +198,83 @@ export const useNotesStore = create<NotesState>((set, get) => ({
     });
   },
 
+  setPriority: async (filePath: string, priority: Priority | null) => {
+    const notesDir = useSettingsStore.getState().settings.notesDirectory;
+    if (!notesDir) {
+      throw new Error('Notes directory not set');
+    }
+    const result = await invoke<NoteWithTags>('set_note_priority', { notesDir, filePath, priority });
+    set(state => ({
+      notes: state.notes.map(n =>
+        n.frontmatter.id === result.note.frontmatter.id ? result.note : n
+      ),
+    }));
+  },
+
+  setNotesColor: async (filePaths: string[], color: string | null) => {
+    const notesDir = useSettingsStore.getState().settings.notesDirectory;
+    if (!notesDir) {
+      throw new Error('Notes directory not set');
+    }
+    const result = await invoke<{ updated: NoteWithTags[]; errors: string[] }>('set_notes_color', {
+      notesDir,
+      filePaths,
+      color,
+    });
+    const updated = new Map(result.updated.map(u => [u.note.frontmatter.id, u.note]));
+    set(state => ({
+      notes: state.notes.map(n => updated.get(n.frontmatter.id) ?? n),
+    }));
+    return result.errors;
+  },
+
+  reorderNotes: async (column: string, filePaths: string[]) => {
+    const notesDir = useSettingsStore.getState().settings.notesDirectory;
+    if (!notesDir) {
+      throw new Error('Notes directory not set');
+    }
+    const result = await invoke<NoteWithTags[]>('reorder_notes', {
+      notesDir,
+      column,
+      filePaths,
+    });
+    const updated = new Map(result.map(u => [u.note.frontmatter.id, u.note]));
+    set(state => ({
+      notes: state.notes.map(n => updated.get(n.frontmatter.id) ?? n),
+    }));
+  },
+
+  archiveNote: async (filePath: string) => {
+    const notesDir = useSettingsStore.getState().settings.notesDirectory;
+    if (!notesDir) {
+      throw new Error('Notes directory not set');
+    }
+    const result = await invoke<NoteWithTags>('archive_note', { notesDir, filePath });
+    const archivedId = result.note.frontmatter.id;
+    set(state => {
+      const newInlineTags = new Map(state.inlineTags);
+      newInlineTags.delete(archivedId);
+      return {
+        notes: state.notes.filter(n => n.frontmatter.id !== archivedId),
+        inlineTags: newInlineTags,
+        activeNoteId: state.activeNoteId === archivedId ? null : state.activeNoteId,
+      };
+    });
+  },
+
+  togglePin: async (filePath: string) => {
+    const notesDir = useSettingsStore.getState().settings.notesDirectory;
+    if (!notesDir) {
+      throw new Error('Notes directory not set');
+    }
+    const result = await invoke<NoteWithTags>('toggle_pin', { notesDir, filePath });
+    set(state => ({
+      notes: state.notes.map(n =>
+        n.frontmatter.id === result.note.frontmatter.id ? result.note : n
+      ),
+    }));
+  },
+
   deleteNote: async (filePath: string) => {
     const notesDir = useSettingsStore.getState().settings.notesDirectory;
     if (!notesDir) {
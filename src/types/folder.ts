@@ -6,6 +6,14 @@ export type Folder = {
   relative_path: string;
 };
 
+/** Folder metadata from `.noteban/folders.json` */
+export type FolderMeta = {
+  icon?: string;
+};
+
+/** Keyed by folder path relative to the vault, with `/` separators */
+export type FolderMetaMap = Record<string, FolderMeta>;
+
 export type NotesWithFolders = {
   notes: Note[];
   folders: Folder[];
@@ -16,9 +24,16 @@ export type NoteWithTags = {
   inline_tags: string[];
 };
 
+export type CloudPlaceholder = {
+  path: string;
+  name: string;
+  relative_path: string;
+};
+
 export type NotesWithTagsAndFolders = {
   notes: NoteWithTags[];
   folders: Folder[];
+  placeholders: CloudPlaceholder[];
 };
 
 export type FileChangeEvent = {
@@ -29,4 +44,6 @@ export type FileChangeEvent = {
 export type IncrementalUpdateResult = {
   updated_notes: NoteWithTags[];
   removed_paths: string[];
+  added_folders: Folder[];
+  removed_folders: string[];
 };
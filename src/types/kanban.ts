@@ -1,3 +1,5 @@
+import type { NoteWithTags } from './folder';
+
 export type KanbanColumn = {
   id: string;
   title: string;
@@ -11,3 +13,51 @@ export const DEFAULT_COLUMNS: KanbanColumn[] = [
   { id: 'doing', title: 'In Progress', color: '#fab387', order: 2 },
   { id: 'done', title: 'Done', color: '#a6e3a1', order: 3 },
 ];
+
+export type WipPolicy = 'soft' | 'hard';
+
+// Board columns stored in the vault's .noteban/board.json
+export type BoardColumn = {
+  id: string;
+  title: string;
+  color: string;
+  wip_limit?: number;
+  wip_policy?: WipPolicy;
+};
+
+export type BoardConfig = {
+  columns: BoardColumn[];
+};
+
+// A board and the vault-relative folder it belongs to ('' for the vault's own)
+export type BoardSummary = {
+  folder: string;
+  config: BoardConfig;
+};
+
+export type ColumnChange = {
+  board: BoardConfig;
+  // Notes moved to another column by the change
+  updated: NoteWithTags[];
+};
+
+// Where the notes of a deleted column go
+export type Reassignment =
+  | { kind: 'column'; column: string }
+  | { kind: 'archive' };
+
+export type ColumnWip = {
+  column: string;
+  count: number;
+  limit: number | null;
+  policy: WipPolicy;
+  over_limit: boolean;
+};
+
+// Payload of the board://wip-exceeded event
+export type WipViolation = {
+  column: string;
+  count: number;
+  limit: number;
+  policy: WipPolicy;
+};
@@ -1,12 +1,27 @@
+export type Priority = 'low' | 'medium' | 'high' | 'urgent';
+
 export type NoteFrontmatter = {
   id: string;
   title: string;
   created: string;
   modified: string;
   date?: string;
+  due?: string;
   column: string;
   tags: string[];
   order: number;
+  pinned?: boolean;
+  /** Archived notes are left off the board */
+  archived?: boolean;
+  priority?: Priority;
+  /** Label color: a palette name such as `red`, or a hex color */
+  color?: string;
+  /** Cover image, relative to the note's folder */
+  cover?: string;
+  icon?: string;
+  aliases?: string[];
+  /** Custom properties, kept as written in the file */
+  [key: string]: unknown;
 };
 
 export type Note = {
@@ -21,6 +36,7 @@ export type CreateNoteInput = {
   title: string;
   content?: string;
   date?: string;
+  due?: string;
   column?: string;
   tags?: string[];
 };
@@ -31,7 +47,9 @@ export type UpdateNoteInput = {
   title?: string;
   content?: string;
   date?: string;
+  due?: string;
   column?: string;
   tags?: string[];
   order?: number;
+  aliases?: string[];
 };
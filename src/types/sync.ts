@@ -19,6 +19,14 @@ export type SyncStatus = {
   lastSyncAt: string | null;
   lastError: string | null;
   conflicts: string[];
+  pendingChanges: number;
+};
+
+export type SyncProgress = {
+  stage: string;
+  completed: number | null;
+  total: number | null;
+  path: string | null;
 };
 
 export type SyncSummary = {
@@ -28,6 +36,12 @@ export type SyncSummary = {
   downloaded: number;
   deletedLocal: number;
   deletedRemote: number;
+  merged: number;
   conflicts: string[];
   errors: string[];
 };
+
+export type SelectiveSyncConfig = {
+  localOnly: string[];
+  syncOnly: string[];
+};